@@ -3,6 +3,7 @@ import WaspHlsPlayer, { PlayerState as innerPlayerState } from "./ts-main";
 export { WaspHlsPlayerConfig } from "./ts-common/types";
 export {
   AudioTrackInfo,
+  TextTrackInfo,
   VariantInfo,
   WaspError,
   WaspErrorCode,
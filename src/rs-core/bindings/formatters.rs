@@ -1,24 +1,49 @@
 use crate::{
     media_element::SourceBufferCreationError,
-    parser::{AudioTrack, ByteRange, VariantStream, VideoResolution},
+    parser::{
+        AudioTrack, ByteRange, DateRange, InstreamId, TextTrack, VariantStream, VideoResolution,
+        VideoTrack,
+    },
+    subtitles::TextCue,
 };
 
+use super::DateRangeAttributeType;
+
 static NULL_RESOLUTION: VideoResolution = VideoResolution::new(0, 0);
 
 pub(crate) unsafe fn format_variants_info_for_js(variants: &[&VariantStream]) -> Vec<u32> {
+    let mut ret: Vec<u32> = vec![];
+    ret.push(variants.len() as u32);
+    variants.iter().for_each(|v| push_variant_info(&mut ret, v));
+    ret
+}
+
+/// Format the given variants, which cannot be played due to the output protection they require,
+/// so they can be communicated to JavaScript.
+///
+/// The layout is the same than for `format_variants_info_for_js`, with the
+/// `OutputProtectionIssue` of each variant following its other information.
+pub(crate) unsafe fn format_filtered_variants_info_for_js(variants: &[&VariantStream]) -> Vec<u32> {
     let mut ret: Vec<u32> = vec![];
     ret.push(variants.len() as u32);
     variants.iter().for_each(|v| {
-        ret.push(v.id());
-        let resolution = v.resolution().unwrap_or(&NULL_RESOLUTION);
-        ret.push(resolution.height());
-        ret.push(resolution.width());
-        ret.push(v.frame_rate().unwrap_or(0.) as u32);
-        ret.push(v.bandwidth() as u32);
+        push_variant_info(&mut ret, v);
+        ret.push(v.output_protection_issue().map_or(0, |i| i as u32));
     });
     ret
 }
 
+fn push_variant_info(ret: &mut Vec<u32>, variant: &VariantStream) {
+    ret.push(variant.id());
+    let resolution = variant.resolution().unwrap_or(&NULL_RESOLUTION);
+    ret.push(resolution.height());
+    ret.push(resolution.width());
+    ret.push(variant.frame_rate().unwrap_or(0.) as u32);
+    ret.push(variant.bandwidth() as u32);
+    ret.push(variant.uses_supplemental_codecs() as u32);
+    ret.push(variant.video_range() as u32);
+}
+
 pub(crate) fn format_range_for_js(original: Option<&ByteRange>) -> (Option<usize>, Option<usize>) {
     match original {
         None => (None, None),
@@ -65,6 +90,13 @@ pub(crate) fn format_source_buffer_creation_err_for_js(
     }
 }
 
+/// Format the given audio tracks so they can be communicated to JavaScript.
+///
+/// The first element is the number of audio tracks, followed for each by its `id`, the length
+/// and pointer of its language, associated language and name, its channel count (`0` if
+/// unknown), the number of spatial audio identifiers followed by the length and pointer of each,
+/// flags indicating binaural (`1`), immersive (`2`) and downmix (`4`) audio, and finally the
+/// number of characteristics followed by the length and pointer of each.
 pub(crate) unsafe fn format_audio_tracks_for_js(tracks: &[AudioTrack]) -> Vec<u32> {
     let mut ret: Vec<u32> = vec![];
     ret.push(tracks.len() as u32);
@@ -83,6 +115,210 @@ pub(crate) unsafe fn format_audio_tracks_for_js(tracks: &[AudioTrack]) -> Vec<u3
         ret.push(name.len() as u32);
         ret.push(name.as_ptr() as u32);
         ret.push(t.channels().unwrap_or(0));
+
+        let spatial_identifiers = t
+            .audio_channels()
+            .map(|c| c.spatial_identifiers())
+            .unwrap_or_default();
+        ret.push(spatial_identifiers.len() as u32);
+        spatial_identifiers.iter().for_each(|i| {
+            ret.push(i.len() as u32);
+            ret.push(i.as_ptr() as u32);
+        });
+        let mut channels_flags = 0;
+        if let Some(channels) = t.audio_channels() {
+            if channels.is_binaural() {
+                channels_flags |= 1;
+            }
+            if channels.is_immersive() {
+                channels_flags |= 2;
+            }
+            if channels.is_downmix() {
+                channels_flags |= 4;
+            }
+        }
+        ret.push(channels_flags);
+
+        let characteristics = t.characteristics();
+        ret.push(characteristics.len() as u32);
+        characteristics.iter().for_each(|c| {
+            ret.push(c.len() as u32);
+            ret.push(c.as_ptr() as u32);
+        });
+    });
+    ret
+}
+
+/// Format the given video tracks so they can be communicated to JavaScript.
+///
+/// The first element is the number of video tracks, followed for each by its `id` and the length
+/// and pointer of its language, associated language and name.
+pub(crate) unsafe fn format_video_tracks_for_js(tracks: &[VideoTrack]) -> Vec<u32> {
+    let mut ret: Vec<u32> = vec![];
+    ret.push(tracks.len() as u32);
+    tracks.iter().for_each(|t| {
+        ret.push(t.id());
+
+        let language = t.language().unwrap_or("");
+        ret.push(language.len() as u32);
+        ret.push(language.as_ptr() as u32);
+
+        let assoc_language = t.assoc_language().unwrap_or("");
+        ret.push(assoc_language.len() as u32);
+        ret.push(assoc_language.as_ptr() as u32);
+
+        let name = t.name();
+        ret.push(name.len() as u32);
+        ret.push(name.as_ptr() as u32);
+    });
+    ret
+}
+
+/// Format the given text tracks so they can be communicated to JavaScript.
+///
+/// The first element is the number of text tracks, followed for each by its `id`, the length and
+/// pointer of its language, associated language and name, a flag set to `1` if it is forced, `2`
+/// if it is a default track and `4` if it can be auto-selected, the number of its
+/// characteristics followed by the length and pointer of each, then the kind of closed captions
+/// channel it corresponds to (`0` for none, `1` for a CEA-608 channel and `2` for a CEA-708
+/// service) followed by that channel's number (`0` if none).
+pub(crate) unsafe fn format_text_tracks_for_js(tracks: &[TextTrack]) -> Vec<u32> {
+    let mut ret: Vec<u32> = vec![];
+    ret.push(tracks.len() as u32);
+    tracks.iter().for_each(|t| {
+        ret.push(t.id());
+
+        let language = t.language().unwrap_or("");
+        ret.push(language.len() as u32);
+        ret.push(language.as_ptr() as u32);
+
+        let assoc_language = t.assoc_language().unwrap_or("");
+        ret.push(assoc_language.len() as u32);
+        ret.push(assoc_language.as_ptr() as u32);
+
+        let name = t.name();
+        ret.push(name.len() as u32);
+        ret.push(name.as_ptr() as u32);
+
+        let mut flags = 0;
+        if t.is_forced() {
+            flags |= 1;
+        }
+        if t.is_default() {
+            flags |= 2;
+        }
+        if t.is_autoselect() {
+            flags |= 4;
+        }
+        ret.push(flags);
+
+        let characteristics = t.characteristics();
+        ret.push(characteristics.len() as u32);
+        characteristics.iter().for_each(|c| {
+            ret.push(c.len() as u32);
+            ret.push(c.as_ptr() as u32);
+        });
+
+        match t.instream_id() {
+            None => {
+                ret.push(0);
+                ret.push(0);
+            }
+            Some(InstreamId::Cea608(channel)) => {
+                ret.push(1);
+                ret.push(channel as u32);
+            }
+            Some(InstreamId::Cea708(service)) => {
+                ret.push(2);
+                ret.push(service as u32);
+            }
+        }
+    });
+    ret
+}
+
+/// Format the layout indications of `cue` so they can be communicated to JavaScript.
+///
+/// The elements are, in order:
+///   - its `CueVertical` value
+///   - its `line` (`NaN` for an automatic placement)
+///   - `1` if that `line` is a number of lines, `0` if it is a percentage
+///   - its `CueLineAlign` value
+///   - its `position` (`NaN` for an automatic placement)
+///   - its `CuePositionAlign` value
+///   - its `size`
+///   - its `CueAlign` value
+///   - `1` if it is displayed in a region, `0` if not
+///
+/// If it is displayed in a region, the width, lines, region anchor's x and y, viewport
+/// anchor's x and y of that region follow, then `1` if it scrolls up or `0` if not.
+pub(crate) fn format_text_cue_settings_for_js(cue: &TextCue) -> Vec<f64> {
+    let settings = cue.settings();
+    let mut ret: Vec<f64> = vec![
+        settings.vertical as u32 as f64,
+        settings.line.unwrap_or(f64::NAN),
+        if settings.snap_to_lines { 1. } else { 0. },
+        settings.line_align as u32 as f64,
+        settings.position.unwrap_or(f64::NAN),
+        settings.position_align as u32 as f64,
+        settings.size,
+        settings.align as u32 as f64,
+    ];
+    match cue.region() {
+        None => ret.push(0.),
+        Some(region) => {
+            ret.push(1.);
+            ret.push(region.width);
+            ret.push(region.lines as f64);
+            ret.push(region.region_anchor.0);
+            ret.push(region.region_anchor.1);
+            ret.push(region.viewport_anchor.0);
+            ret.push(region.viewport_anchor.1);
+            ret.push(if region.scroll_up { 1. } else { 0. });
+        }
+    }
+    ret
+}
+
+/// Format the attributes of `date_range` which are not communicated through other means to
+/// JavaScript: `CLASS`, `END-ON-NEXT`, `SCTE35-CMD`, `SCTE35-OUT`, `SCTE35-IN` and client-defined
+/// `X-` attributes.
+///
+/// The first element is the number of attributes, followed for each by its
+/// `DateRangeAttributeType`, then the length and pointer of its name, then the length and pointer
+/// of its value.
+pub(crate) unsafe fn format_date_range_attributes_for_js(date_range: &DateRange) -> Vec<u32> {
+    let mut attributes: Vec<(DateRangeAttributeType, &str, &str)> = vec![];
+    if let Some(class) = date_range.class() {
+        attributes.push((DateRangeAttributeType::String, "CLASS", class));
+    }
+    if date_range.ends_on_next() {
+        attributes.push((DateRangeAttributeType::String, "END-ON-NEXT", "YES"));
+    }
+    if let Some(cmd) = date_range.scte35_cmd() {
+        let typ = DateRangeAttributeType::HexadecimalSequence;
+        attributes.push((typ, "SCTE35-CMD", cmd));
+    }
+    if let Some(out) = date_range.scte35_out() {
+        let typ = DateRangeAttributeType::HexadecimalSequence;
+        attributes.push((typ, "SCTE35-OUT", out));
+    }
+    if let Some(scte_in) = date_range.scte35_in() {
+        let typ = DateRangeAttributeType::HexadecimalSequence;
+        attributes.push((typ, "SCTE35-IN", scte_in));
+    }
+    date_range.client_attributes().iter().for_each(|a| {
+        attributes.push((a.typ(), a.name(), a.value()));
+    });
+
+    let mut ret: Vec<u32> = vec![];
+    ret.push(attributes.len() as u32);
+    attributes.into_iter().for_each(|(typ, name, value)| {
+        ret.push(typ as u32);
+        ret.push(name.len() as u32);
+        ret.push(name.as_ptr() as u32);
+        ret.push(value.len() as u32);
+        ret.push(value.as_ptr() as u32);
     });
     ret
 }
@@ -38,6 +38,15 @@ extern "C" {
     // TODO also return ContentType of the requested data?
     pub fn jsGetResourceData(id: ResourceId) -> Option<Vec<u8>>;
 
+    // Copy the given data into JavaScript's memory and return the `ResourceId` now identifying it.
+    //
+    // This is useful when the data of a resource had to be transformed in WebAssembly (e.g.
+    // decrypted) before being used by other JavaScript functions taking a `ResourceId`.
+    //
+    // As for resources obtained through `jsFetch`, it is __VERY__ important to call the
+    // `jsFreeResource` function with that `ResourceId` once it is not needed anymore.
+    pub fn jsStoreResource(data: &[u8]) -> ResourceId;
+
     // Fetch the given `url` from the network and await a response.
     // If at least the `range_base` argument is set, the request will be a range request from
     // `range_base` to `range_end` or to the end of the resource if `range_end` is set to `None`.
@@ -117,8 +126,6 @@ extern "C" {
     // `AddSourceBufferResult` for more details on the return value.
     pub fn jsAddSourceBuffer(media_type: MediaType, typ: &str) -> AddSourceBufferResult;
 
-    pub fn jsIsTypeSupported(media_type: MediaType, typ: &str) -> Option<bool>;
-
     // Append media data to the given SourceBuffer.
     //
     // This process is asynchronous, meaning that the data might not be appended
@@ -136,12 +143,26 @@ extern "C" {
     // with the same `source_buffer_id`, it means that the currently scheduled
     // operation (the first one in the queue) failed. In that case, the
     // SourceBuffer is not usable anymore.
+    //
+    // If `aligned_start` is set, the segment's start time will be parsed and the
+    // SourceBuffer's `timestampOffset` will be updated before pushing it so the
+    // segment ends up beginning at `aligned_start` seconds in media time.
     pub fn jsAppendBuffer(
         source_buffer_id: SourceBufferId,
         segment_id: ResourceId,
         parse_time_information: bool,
+        aligned_start: Option<f64>,
     ) -> AppendBufferResult;
 
+    // Update the `timestampOffset` property of the given SourceBuffer, which is
+    // the offset in seconds applied to the timestamps of media segments pushed
+    // after this call.
+    //
+    // This operation is pushed to the same internal queue than the one used by
+    // `jsAppendBuffer` and `jsRemoveBuffer`, yet its completion is not
+    // notified through `on_source_buffer_update`.
+    pub fn jsSetTimestampOffset(source_buffer_id: SourceBufferId, offset: f64);
+
     // Remove media data from the given SourceBuffer.
     //
     // This process is asynchronous, meaning that the data might not be directly
@@ -218,18 +239,83 @@ extern "C" {
         playlist_nat: PlaylistNature,
     );
 
-    pub fn jsAnnounceFetchedContent(variant_info: Vec<u32>, audio_tracks_info: Vec<u32>);
+    pub fn jsAnnounceFetchedContent(
+        variant_info: Vec<u32>,
+        audio_tracks_info: Vec<u32>,
+        video_tracks_info: Vec<u32>,
+        text_tracks_info: Vec<u32>,
+        filtered_variants_info: Vec<u32>,
+    );
 
     pub fn jsAnnounceVariantUpdate(variant_id: Option<u32>);
 
+    /// Announce the `id` of the audio or video track (depending on `media_type`) now loaded, if
+    /// one, and whether it was explicitely selected by the application.
     pub fn jsAnnounceTrackUpdate(
         media_type: MediaType,
-        current_audio_track: Option<u32>,
-        is_audio_track_selected: bool,
+        current_track: Option<u32>,
+        is_track_selected: bool,
     );
 
+    /// Announce the `id` of the text track now selected, or `None` if text tracks are now
+    /// disabled.
+    pub fn jsAnnounceTextTrackUpdate(current_text_track: Option<u32>);
+
     pub fn jsAnnounceVariantLockStatusChange(variant_id: Option<u32>);
 
+    /// Announce that the current position entered or left the date range with the given
+    /// characteristics.
+    ///
+    /// `start` and `end` are expressed in playlist time, in seconds.
+    ///
+    /// `attributes` lists its other attributes (`CLASS`, `SCTE35-*`, `X-*` and so on), as
+    /// formatted by `format_date_range_attributes_for_js`.
+    pub fn jsAnnounceDateRangeEvent(
+        event: DateRangeEvent,
+        id: &str,
+        start: f64,
+        end: Option<f64>,
+        duration: Option<f64>,
+        planned_duration: Option<f64>,
+        attributes: Vec<u32>,
+    );
+
+    /// Indicate that the HLS Interstitial announced by the `EXT-X-DATERANGE` tag with the `ID`
+    /// `id` just began or stopped being played in place of the primary content.
+    pub fn jsAnnounceInterstitialEvent(event: InterstitialEvent, id: &str);
+
+    /// Announce the data of the `EXT-X-SESSION-DATA` tag with the given `DATA-ID` and
+    /// `LANGUAGE` attributes, generally after it was asked through the `load_session_data`
+    /// method of the `Dispatcher`.
+    ///
+    /// `data` is the UTF-8 content of its `VALUE` attribute if `data_type` is set to
+    /// `SessionDataType::String`, or the data loaded from its `URI` attribute otherwise.
+    pub fn jsAnnounceSessionData(
+        data_id: &str,
+        language: Option<String>,
+        data_type: SessionDataType,
+        data: &[u8],
+    );
+
+    /// Add a cue to display for the current text track.
+    ///
+    /// `start` and `end` are expressed in playlist time, in seconds.
+    ///
+    /// `settings` describes how the cue should be laid out, as formatted by
+    /// `format_text_cue_settings_for_js`. `region_id` is the identifier of the region it
+    /// should be displayed in, if one, whose characteristics are also found in `settings`.
+    pub fn jsAddTextCue(
+        id: Option<String>,
+        start: f64,
+        end: f64,
+        text: &str,
+        settings: Vec<f64>,
+        region_id: Option<String>,
+    );
+
+    /// Remove all cues previously added through `jsAddTextCue`.
+    pub fn jsClearTextCues();
+
     pub fn jsStartRebuffering();
     pub fn jsStopRebuffering();
 
@@ -314,6 +400,20 @@ extern "C" {
     pub fn jsSendOtherError(fatal: bool, code: OtherErrorCode, message: &str);
 }
 
+// Functions replaced by the ones of the `mocks` module when testing, so code relying on them can
+// be tested natively.
+#[cfg(not(test))]
+#[wasm_bindgen]
+extern "C" {
+    // Returns the current time in milliseconds, from a monotonically-increasing clock whose
+    // origin is not defined.
+    //
+    // It is only meaningful when compared to another value returned by that function.
+    pub fn jsGetMonotonicTime() -> f64;
+
+    pub fn jsIsTypeSupported(media_type: MediaType, typ: &str) -> Option<bool>;
+}
+
 #[wasm_bindgen]
 pub enum PlaylistType {
     MultivariantPlaylist,
@@ -325,6 +425,9 @@ pub enum OtherErrorCode {
     NoSupportedVariant,
     UnfoundLockedVariant,
     MediaSourceAttachmentError,
+    UnfoundSessionData,
+    SessionDataLoadingError,
+    TextTrackLoadingError,
     Unknown,
 }
 
@@ -618,6 +721,10 @@ pub enum MultivariantPlaylistParsingErrorCode {
     MediaTagMissingGroupId,
     /// A line could not be read.
     UnableToReadLine,
+    /// A variable was referenced in the Multivariant Playlist without being
+    /// defined through an `EXT-X-DEFINE` tag, or an `EXT-X-DEFINE` tag
+    /// referenced a query parameter absent from the playlist's URL.
+    UndefinedVariable,
     /// Another, uncategorized, error arised.
     Unknown,
 }
@@ -646,6 +753,9 @@ impl From<MultivariantPlaylistParsingError> for MultivariantPlaylistParsingError
             MultivariantPlaylistParsingError::MissingUriLineAfterVariant => {
                 MultivariantPlaylistParsingErrorCode::MissingUriLineAfterVariant
             }
+            MultivariantPlaylistParsingError::UndefinedVariable => {
+                MultivariantPlaylistParsingErrorCode::UndefinedVariable
+            }
             MultivariantPlaylistParsingError::UnableToReadVariantUri
             | MultivariantPlaylistParsingError::UnableToReadLine
             | MultivariantPlaylistParsingError::Unknown => {
@@ -672,6 +782,17 @@ pub enum MediaPlaylistParsingErrorCode {
     /// A `#EXT-X-BYTERANGE` tag or a `BYTERANGE` attribute in the Media Playlist
     /// was not in the right format.
     UnparsableByteRange,
+    /// An `#EXT-X-KEY` tag in the Media Playlist was either missing its mandatory
+    /// `METHOD` attribute or its `URI` attribute.
+    UnparsableKey,
+    /// A variable was referenced in the Media Playlist without being defined
+    /// through an `EXT-X-DEFINE` tag, or an `EXT-X-DEFINE` tag imported a
+    /// variable or referenced a query parameter which did not exist.
+    UndefinedVariable,
+    /// An `#EXT-X-SKIP` tag in a Playlist Delta Update was either unparsable or
+    /// skipped media segments which were not in the previous version of the
+    /// Media Playlist.
+    InvalidDeltaUpdate,
     /// Another, uncategorized, error arised.
     Unknown,
 }
@@ -694,6 +815,15 @@ impl From<MediaPlaylistUpdateError> for MediaPlaylistParsingErrorCode {
             MediaPlaylistUpdateError::ParsingError(MediaPlaylistParsingError::UriWithoutExtInf) => {
                 MediaPlaylistParsingErrorCode::UriWithoutExtInf
             }
+            MediaPlaylistUpdateError::ParsingError(MediaPlaylistParsingError::UnparsableKey) => {
+                MediaPlaylistParsingErrorCode::UnparsableKey
+            }
+            MediaPlaylistUpdateError::ParsingError(
+                MediaPlaylistParsingError::InvalidDeltaUpdate,
+            ) => MediaPlaylistParsingErrorCode::InvalidDeltaUpdate,
+            MediaPlaylistUpdateError::ParsingError(
+                MediaPlaylistParsingError::UndefinedVariable,
+            ) => MediaPlaylistParsingErrorCode::UndefinedVariable,
             MediaPlaylistUpdateError::NotFound => MediaPlaylistParsingErrorCode::Unknown,
         }
     }
@@ -882,6 +1012,9 @@ pub enum SegmentParsingErrorCode {
     NoSourceBuffer,
     /// The operation failed at the transmuxing stage.
     TransmuxerError,
+    /// The segment could not be decrypted, either because its key was invalid or because the
+    /// segment's data itself was not in the expected format.
+    DecryptionError,
     /// The operation failed because of an unknown error.
     UnknownError,
 }
@@ -956,6 +1089,10 @@ pub enum TimerReason {
 
     /// The timer is linked to a failed request that has to be retried.
     RetryRequest = 1,
+
+    /// The timer is linked to trick play, meaning that the position may have
+    /// to be moved to the next I-frame.
+    TrickPlayStep = 2,
 }
 
 /// Levels with which a log can be emitted.
@@ -1014,6 +1151,50 @@ impl fmt::Display for MediaType {
     }
 }
 
+/// Type of event linked to a date range announced through an `EXT-X-DATERANGE` tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[wasm_bindgen]
+pub enum DateRangeEvent {
+    /// The current position just entered the date range.
+    Enter = 0,
+    /// The current position just left the date range.
+    Leave = 1,
+}
+
+/// Type of event linked to the playback of an HLS Interstitial.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[wasm_bindgen]
+pub enum InterstitialEvent {
+    /// The interstitial's asset(s) began to be loaded in place of the primary content.
+    Start = 0,
+    /// The interstitial ended (or was abandoned) and the primary content is being loaded again.
+    End = 1,
+}
+
+/// Type of the data announced through an `EXT-X-SESSION-DATA` tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[wasm_bindgen]
+pub enum SessionDataType {
+    /// The data is a string, set through the `VALUE` attribute.
+    String = 0,
+    /// The data is JSON loaded from the `URI` attribute.
+    Json = 1,
+    /// The data is an uninterpreted binary file loaded from the `URI` attribute.
+    Raw = 2,
+}
+
+/// Format of the value of an `EXT-X-DATERANGE` attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[wasm_bindgen]
+pub enum DateRangeAttributeType {
+    /// A quoted-string.
+    String = 0,
+    /// An hexadecimal-sequence (e.g. `0x1A2B`).
+    HexadecimalSequence = 1,
+    /// A decimal floating-point number.
+    Number = 2,
+}
+
 /// Values for the "Playlist Type" as specified by the HLS specification.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[wasm_bindgen]
@@ -0,0 +1,36 @@
+//! Native replacements, only used when testing, of JavaScript functions declared in the
+//! `js_functions` module, whose behavior can be controlled by the tests.
+
+#![allow(non_snake_case)]
+
+use super::MediaType;
+use std::cell::{Cell, RefCell};
+
+type TypeSupportFn = Box<dyn Fn(MediaType, &str) -> Option<bool>>;
+
+thread_local! {
+    static MONOTONIC_TIME: Cell<f64> = const { Cell::new(0.) };
+    static TYPE_SUPPORT: RefCell<TypeSupportFn> = RefCell::new(Box::new(|_, _| Some(true)));
+}
+
+/// Mock of `jsGetMonotonicTime`, returning the last time set through `set_monotonic_time`.
+pub fn jsGetMonotonicTime() -> f64 {
+    MONOTONIC_TIME.with(|t| t.get())
+}
+
+/// Mock of `jsIsTypeSupported`, relying on the last function set through `set_type_support`.
+///
+/// All types are supported by default.
+pub fn jsIsTypeSupported(media_type: MediaType, typ: &str) -> Option<bool> {
+    TYPE_SUPPORT.with(|f| f.borrow()(media_type, typ))
+}
+
+/// Set the time, in milliseconds, returned by `jsGetMonotonicTime` on the current thread.
+pub fn set_monotonic_time(time: f64) {
+    MONOTONIC_TIME.with(|t| t.set(time));
+}
+
+/// Set the function answering `jsIsTypeSupported` calls on the current thread.
+pub fn set_type_support(f: impl Fn(MediaType, &str) -> Option<bool> + 'static) {
+    TYPE_SUPPORT.with(|s| *s.borrow_mut() = Box::new(f));
+}
@@ -1,4 +1,8 @@
 pub mod formatters;
 mod js_functions;
+#[cfg(test)]
+mod mocks;
 
 pub use js_functions::*;
+#[cfg(test)]
+pub use mocks::*;
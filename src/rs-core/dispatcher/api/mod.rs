@@ -2,8 +2,13 @@ use crate::{
     adaptive::AdaptiveQualitySelector,
     bindings::{jsSendOtherError, OtherErrorCode},
     media_element::MediaElementReference,
+    parser::{
+        parse_allowed_cpc, AudioSelectionPreferences, HdcpLevel, OutputProtectionCapabilities,
+        VideoDynamicRange,
+    },
     requester::{PlaylistFileType, Requester},
     segment_selector::NextSegmentSelectors,
+    subtitles::ClosedCaptionsDecoder,
     utils::url::Url,
     wasm_bindgen, Logger,
 };
@@ -29,8 +34,24 @@ impl Dispatcher {
             media_element_ref: MediaElementReference::new(),
             last_position: 0.,
             buffer_goal: 30.,
+            audio_preferences: AudioSelectionPreferences::default(),
+            output_protection: OutputProtectionCapabilities::default(),
+            supported_video_ranges: vec![
+                VideoDynamicRange::Sdr,
+                VideoDynamicRange::Hlg,
+                VideoDynamicRange::Pq,
+            ],
             segment_selectors: NextSegmentSelectors::new(0., 30.),
             playlist_refresh_timers: vec![],
+            active_date_ranges: vec![],
+            interstitial: None,
+            played_interstitials: vec![],
+            trick_play: Default::default(),
+            text_timescale: None,
+            closed_captions_decoder: ClosedCaptionsDecoder::new(),
+            closed_captions_cues: vec![],
+            deferred_segments: None,
+            multivariant_reload: Default::default(),
         }
     }
 
@@ -79,6 +100,26 @@ impl Dispatcher {
     pub fn set_wanted_speed(&mut self, speed: f64) {
         self.media_element_ref.update_wanted_speed(speed);
         self.check_best_variant();
+        self.check_trick_play();
+    }
+
+    /// Indicate whether the user is currently scrubbing, that is, moving the position through
+    /// multiple successive seeks, for example by dragging a progress bar.
+    ///
+    /// While scrubbing, only I-frames are loaded if the content has I-frame-only variants.
+    pub fn set_scrubbing(&mut self, is_scrubbing: bool) {
+        self.set_scrubbing_core(is_scrubbing);
+    }
+
+    /// Ask for the data announced through the `EXT-X-SESSION-DATA` tag with the given `DATA-ID`
+    /// attribute in the Multivariant Playlist of the current content.
+    ///
+    /// If a `language` is given, the tag with that `LANGUAGE` attribute is preferred.
+    ///
+    /// The data will then be communicated through the `jsAnnounceSessionData` binding, once
+    /// loaded if it has to be fetched from its `URI` attribute.
+    pub fn load_session_data(&mut self, data_id: String, language: Option<String>) {
+        self.load_session_data_core(&data_id, language.as_deref());
     }
 
     /// Update the buffer goal to the given value.
@@ -94,6 +135,79 @@ impl Dispatcher {
         self.check_segments_to_request();
     }
 
+    /// Indicate whether audio renditions with accessibility characteristics (e.g. audio
+    /// descriptions) should be preferred when no audio track is explicitely selected.
+    pub fn set_prefer_accessible_audio(&mut self, prefer: bool) {
+        let preferences = AudioSelectionPreferences {
+            prefer_accessible: prefer,
+            ..self.audio_preferences
+        };
+        self.set_audio_selection_preferences_core(preferences)
+    }
+
+    /// Set the maximum number of audio channels that can be output, or `None` if there's no
+    /// known limit.
+    ///
+    /// Audio renditions with more channels are avoided when no audio track is explicitely
+    /// selected.
+    pub fn set_max_audio_channels(&mut self, max_channels: Option<u32>) {
+        let preferences = AudioSelectionPreferences {
+            max_channels,
+            ..self.audio_preferences
+        };
+        self.set_audio_selection_preferences_core(preferences)
+    }
+
+    /// Set the highest HDCP level that can be enforced on the output, as an `HDCP-LEVEL` value
+    /// (`"NONE"`, `"TYPE-0"` or `"TYPE-1"`), or `None` if unknown.
+    ///
+    /// Variants requiring a higher HDCP level are then not played.
+    pub fn set_max_hdcp_level(&mut self, level: Option<String>) {
+        let max_hdcp_level = level.and_then(|l| match HdcpLevel::parse(&l) {
+            HdcpLevel::Unknown => {
+                Logger::warn(&format!("Unknown HDCP level: {l}"));
+                None
+            }
+            level => Some(level),
+        });
+        let capabilities = OutputProtectionCapabilities {
+            max_hdcp_level,
+            ..self.output_protection.clone()
+        };
+        self.set_output_protection_core(capabilities);
+    }
+
+    /// Set the Content Protection Configurations satisfied by the device, per `KEYFORMAT`, in the
+    /// same format than the `ALLOWED-CPC` attribute (e.g.
+    /// `"com.example.drm1:SMART-TV/PC,com.example.drm2:HW"`), or `None` if unknown.
+    ///
+    /// Variants whose `ALLOWED-CPC` attribute cannot be satisfied are then not played.
+    pub fn set_allowed_cpc(&mut self, allowed_cpc: Option<String>) {
+        let capabilities = OutputProtectionCapabilities {
+            allowed_cpc: allowed_cpc.map(|a| parse_allowed_cpc(&a)),
+            ..self.output_protection.clone()
+        };
+        self.set_output_protection_core(capabilities);
+    }
+
+    /// Indicate which video dynamic ranges can be rendered by the display, as reported for
+    /// example by media queries.
+    ///
+    /// Variants whose `VIDEO-RANGE` is not supported are then not played. All are considered
+    /// supported until this method is called.
+    pub fn set_supported_video_ranges(&mut self, sdr: bool, hlg: bool, pq: bool) {
+        let ranges = [
+            (sdr, VideoDynamicRange::Sdr),
+            (hlg, VideoDynamicRange::Hlg),
+            (pq, VideoDynamicRange::Pq),
+        ];
+        let supported_ranges = ranges
+            .into_iter()
+            .filter_map(|(is_supported, range)| is_supported.then_some(range))
+            .collect();
+        self.set_supported_video_ranges_core(supported_ranges);
+    }
+
     /// Stop the currently loaded content.
     pub fn stop(&mut self) {
         self.stop_current_content();
@@ -115,6 +229,17 @@ impl Dispatcher {
         self.set_audio_track_core(track_id)
     }
 
+    /// Set a video track whose `id` is given in argument, or rely on the content's default video
+    /// renditions if `None`.
+    pub fn set_video_track(&mut self, track_id: Option<u32>) {
+        self.set_video_track_core(track_id)
+    }
+
+    /// Set a text track whose `id` is given in argument, or disable text tracks if `None`.
+    pub fn set_text_track(&mut self, track_id: Option<u32>) {
+        self.set_text_track_core(track_id)
+    }
+
     pub fn set_segment_request_max_retry(&mut self, max_retry: i32) {
         self.requester.config_mut().segment_request_max_retry = max_retry;
     }
@@ -0,0 +1,83 @@
+use super::super::Dispatcher;
+use super::text_tracks::send_text_cue;
+use crate::{
+    bindings::{jsSendOtherError, OtherErrorCode},
+    Logger,
+};
+
+/// Cues of closed captions ending more than this amount of seconds before the current position
+/// are forgotten, and thus cannot be displayed again after switching closed captions channel.
+const MAX_CLOSED_CAPTIONS_HISTORY: f64 = 300.;
+
+impl Dispatcher {
+    /// Method called once the video init segment of a variant announcing closed captions has
+    /// been loaded, to prepare the extraction of those captions from the following segments.
+    pub(super) fn on_closed_captions_init_segment(&mut self, data: &[u8]) {
+        if !self.closed_captions_decoder.parse_init_segment(data) {
+            jsSendOtherError(
+                false,
+                OtherErrorCode::TextTrackLoadingError,
+                "Could not find a supported video track in which closed captions can be read",
+            );
+        }
+    }
+
+    /// Method called once a video segment of a variant announcing closed captions has been
+    /// pushed, with its data and its discontinuity sequence number, to extract and store its
+    /// closed captions for all channels.
+    ///
+    /// Cues of the currently chosen closed captions channel, if one, are directly communicated
+    /// to JavaScript.
+    pub(super) fn on_closed_captions_media_segment(&mut self, data: &[u8], discontinuity: u64) {
+        let Some(offset) = self
+            .media_element_ref
+            .playlist_time_offset_for(discontinuity)
+        else {
+            Logger::warn(&format!(
+                "Core: Cannot place closed captions of unknown discontinuity {}",
+                discontinuity
+            ));
+            return;
+        };
+        let current_channel = self
+            .playlist_store
+            .as_ref()
+            .and_then(|p| p.curr_closed_captions_channel());
+        let min_end = self.media_element_ref.wanted_position() - MAX_CLOSED_CAPTIONS_HISTORY;
+        self.closed_captions_cues
+            .retain(|(_, o, cue)| cue.end() + o >= min_end);
+
+        for (channel, cue) in self.closed_captions_decoder.parse_media_segment(data) {
+            // The same captions may be decoded again, e.g. when segments are re-loaded after a
+            // seek
+            let is_known = self.closed_captions_cues.iter().any(|(c, o, known)| {
+                *c == channel
+                    && known.text() == cue.text()
+                    && (known.start() + o - cue.start() - offset).abs() < 0.01
+            });
+            if is_known {
+                continue;
+            }
+            if Some(channel) == current_channel {
+                send_text_cue(&cue, offset);
+            }
+            self.closed_captions_cues.push((channel, offset, cue));
+        }
+    }
+
+    /// Communicate to JavaScript all stored cues of the closed captions channel currently chosen,
+    /// if one.
+    pub(super) fn replay_closed_captions(&self) {
+        let Some(channel) = self
+            .playlist_store
+            .as_ref()
+            .and_then(|p| p.curr_closed_captions_channel())
+        else {
+            return;
+        };
+        self.closed_captions_cues
+            .iter()
+            .filter(|(c, _, _)| *c == channel)
+            .for_each(|(_, offset, cue)| send_text_cue(cue, *offset));
+    }
+}
@@ -0,0 +1,135 @@
+use super::super::Dispatcher;
+use crate::{
+    bindings::{jsAnnounceVariantLockStatusChange, jsTimer, TimerReason},
+    parser::SteeringManifest,
+    playlist_store::VariantUpdateResult,
+    requester::PlaylistFileType,
+    utils::url::Url,
+    Logger,
+};
+
+/// Delay, in milliseconds, after which the Content Steering manifest is requested again when it
+/// could not be loaded or parsed.
+const STEERING_MANIFEST_RETRY_DELAY: f64 = 300_000.;
+
+impl Dispatcher {
+    /// Fetch the Content Steering manifest of the current content, if it relies on Content
+    /// Steering.
+    pub(super) fn load_steering_manifest(&mut self) {
+        let url = self
+            .playlist_store
+            .as_ref()
+            .and_then(|p| p.steering_manifest_url());
+        if let Some(url) = url {
+            Logger::info("Core: Loading Content Steering manifest");
+            self.requester
+                .fetch_playlist(url, PlaylistFileType::SteeringManifest);
+        }
+    }
+
+    /// Method called once the Content Steering manifest was loaded with success, with its
+    /// response data and url as argument.
+    pub(super) fn on_steering_manifest_loaded(&mut self, data: Vec<u8>, url: Url) {
+        let refresh_delay = match SteeringManifest::parse(&data, &url) {
+            Err(e) => {
+                Logger::warn(&format!("Core: Unparsable Content Steering manifest: {e}"));
+                STEERING_MANIFEST_RETRY_DELAY
+            }
+            Ok(manifest) => {
+                let was_locked = self.is_variant_locked();
+                let update = self
+                    .playlist_store
+                    .as_mut()
+                    .and_then(|p| p.update_steering_manifest(&manifest));
+                if let Some(update) = update {
+                    self.on_pathway_change(update, was_locked, false);
+                }
+                manifest.ttl() * 1000.
+            }
+        };
+        self.schedule_steering_manifest_refresh(refresh_delay);
+    }
+
+    /// Method called once the Content Steering manifest could not be loaded.
+    ///
+    /// As it is not needed for playback, it is just requested again later.
+    pub(super) fn on_steering_manifest_request_failed(&mut self) {
+        Logger::warn("Core: Could not load Content Steering manifest");
+        self.schedule_steering_manifest_refresh(STEERING_MANIFEST_RETRY_DELAY);
+    }
+
+    /// Stop relying on the current Content Steering Pathway, because requests for its
+    /// resources repeatedly failed, and switch to the next preferred one.
+    ///
+    /// Returns `false` if no other Pathway could be switched to.
+    pub(super) fn fail_over_pathway(&mut self) -> bool {
+        let was_locked = self.is_variant_locked();
+        let update = self
+            .playlist_store
+            .as_mut()
+            .and_then(|p| p.drop_curr_pathway());
+        if let Some(update) = update {
+            self.on_pathway_change(update, was_locked, true);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Perform all actions that should be taken after the `PlaylistStore` switched to another
+    /// Content Steering Pathway, which led to the given `update`.
+    ///
+    /// `was_locked` should be set to `true` if a variant was locked before that switch.
+    ///
+    /// `is_failover` should be set to `true` if the previous Pathway had to be left due to
+    /// request failures, in which case its pending segment requests are aborted and segments are
+    /// loaded again from the current position.
+    fn on_pathway_change(
+        &mut self,
+        update: VariantUpdateResult,
+        was_locked: bool,
+        is_failover: bool,
+    ) {
+        let changed_media_types = match update {
+            VariantUpdateResult::Improved(mt)
+            | VariantUpdateResult::EqualOrUnknown(mt)
+            | VariantUpdateResult::Worsened(mt) => mt,
+            VariantUpdateResult::Unchanged => vec![],
+        };
+        if is_failover {
+            let position = self.media_element_ref.wanted_position();
+            changed_media_types.iter().for_each(|mt| {
+                self.segment_selectors
+                    .get_mut(*mt)
+                    .restart_from_position(position)
+            });
+        }
+        self.handle_media_playlist_update(&changed_media_types, is_failover, false);
+
+        // Variants from another Pathway have other `id`s
+        self.announce_variants_and_tracks();
+        if was_locked {
+            let locked_variant = self
+                .playlist_store
+                .as_ref()
+                .filter(|p| p.is_variant_locked())
+                .and_then(|p| p.curr_variant())
+                .map(|v| v.id());
+            jsAnnounceVariantLockStatusChange(locked_variant);
+        }
+    }
+
+    /// Schedule a new request for the Content Steering manifest in `delay` milliseconds.
+    fn schedule_steering_manifest_refresh(&mut self, delay: f64) {
+        let timer_id = jsTimer(delay, TimerReason::MediaPlaylistRefresh);
+        self.playlist_refresh_timers
+            .push((timer_id, PlaylistFileType::SteeringManifest));
+    }
+
+    /// Returns `true` if a variant of the current content is currently locked.
+    fn is_variant_locked(&self) -> bool {
+        self.playlist_store
+            .as_ref()
+            .is_some_and(|p| p.is_variant_locked())
+    }
+}
@@ -0,0 +1,362 @@
+use super::super::{
+    Dispatcher, InterstitialPlayback, MultivariantReloadState, PlayerReadyState, StartingPosition,
+    StartingPositionType,
+};
+use crate::{
+    bindings::{
+        jsAnnounceInterstitialEvent, jsClearTimer, jsSendOtherError, jsStopObservingPlayback,
+        InterstitialEvent, OtherErrorCode,
+    },
+    parser::{parse_asset_list, Interstitial, InterstitialAsset},
+    requester::PlaylistFileType,
+    utils::url::Url,
+    Logger,
+};
+
+/// Interstitials whose end is not known (or is very close to their start) are only considered as
+/// reached during that amount of seconds after their start.
+///
+/// This avoids playing interstitials which were far behind the position we seeked to.
+const INTERSTITIAL_TRIGGER_WINDOW: f64 = 2.;
+
+impl Dispatcher {
+    /// Returns `true` if an HLS Interstitial is currently played in place of the primary content.
+    pub(super) fn is_playing_interstitial(&self) -> bool {
+        self.interstitial.is_some()
+    }
+
+    /// Check if the current position reached an HLS Interstitial of the primary content which was
+    /// not played yet and if so, begin to play it.
+    ///
+    /// `prev_position` is the last position known before the current observation, which allows
+    /// to detect seeks jumping over interstitials which cannot be jumped over.
+    pub(super) fn check_interstitials(&mut self, prev_position: f64, is_seeking: bool) {
+        if self.ready_state.is_loading() {
+            return;
+        }
+        let playlist_store = if let Some(playlist_store) = self.playlist_store.as_ref() {
+            playlist_store
+        } else {
+            return;
+        };
+        let position = self.media_element_ref.wanted_position();
+        let unplayed = playlist_store
+            .curr_interstitials()
+            .into_iter()
+            .filter(|i| !self.played_interstitials.iter().any(|id| id == i.id()))
+            .collect();
+        let to_start =
+            interstitial_to_start(unplayed, prev_position, position, is_seeking, |pos| {
+                playlist_store.closest_segment_boundary(pos)
+            });
+        if let Some((interstitial, resume_position)) = to_start {
+            self.start_interstitial(interstitial, resume_position);
+        }
+    }
+
+    /// Check the state of the interstitial asset currently played, and go to the next asset or
+    /// back to the primary content once it has ended.
+    pub(super) fn check_interstitial_playback(&mut self, is_seeking: bool) {
+        let interstitial = if let Some(interstitial) = self.interstitial.as_ref() {
+            interstitial
+        } else {
+            return;
+        };
+        if is_seeking && interstitial.interstitial.restricts_skip() {
+            let position = self.media_element_ref.wanted_position();
+            if f64::abs(position - self.last_position) > 1. {
+                Logger::info("Core: Seeking is restricted in that interstitial, going back");
+                self.media_element_ref.seek(self.last_position);
+                return;
+            }
+        }
+
+        if self.ready_state.is_loading() || !self.media_element_ref.has_ended() {
+            return;
+        }
+        let asset_duration = self
+            .playlist_store
+            .as_ref()
+            .and_then(|p| p.curr_duration())
+            .unwrap_or(self.last_position);
+        let next_asset = if let Some(interstitial) = self.interstitial.as_mut() {
+            interstitial.played_duration += asset_duration;
+            if interstitial.pending_assets.is_empty() {
+                None
+            } else {
+                Some(interstitial.pending_assets.remove(0))
+            }
+        } else {
+            None
+        };
+        match next_asset {
+            Some(url) => {
+                Logger::info("Core: Interstitial asset ended, loading the next one");
+                self.load_interstitial_asset(url, PlaylistFileType::MultivariantPlaylist);
+            }
+            None => {
+                Logger::info("Core: Interstitial ended");
+                self.end_interstitial();
+            }
+        }
+    }
+
+    /// Method called once the asset list of the current interstitial was loaded with success,
+    /// with its response data and url as argument.
+    pub(super) fn on_asset_list_loaded(&mut self, data: Vec<u8>, url: Url) {
+        match parse_asset_list(&data, &url) {
+            Err(e) => {
+                Logger::warn(&format!("Core: {e}"));
+                self.end_interstitial();
+            }
+            Ok(mut assets) => {
+                if let Some(interstitial) = self.interstitial.as_mut() {
+                    let first_asset = assets.remove(0);
+                    interstitial.pending_assets = assets;
+                    self.requester
+                        .fetch_playlist(first_asset, PlaylistFileType::MultivariantPlaylist);
+                }
+            }
+        }
+    }
+
+    /// Stop playing the interstitial currently played (if one) and load again the primary content
+    /// at the position it should be resumed at.
+    pub(super) fn end_interstitial(&mut self) {
+        let InterstitialPlayback {
+            interstitial,
+            primary_playlist_store,
+            played_duration,
+            resume_position,
+            ..
+        } = if let Some(interstitial) = self.interstitial.take() {
+            interstitial
+        } else {
+            return;
+        };
+
+        let mut resume_position = resume_position.unwrap_or_else(|| {
+            interstitial.start() + interstitial.resume_offset().unwrap_or(played_duration)
+        });
+        if interstitial.snaps_in() {
+            resume_position = primary_playlist_store
+                .closest_segment_boundary(resume_position)
+                .unwrap_or(resume_position);
+        }
+        if let Some(min_position) = primary_playlist_store.curr_min_position() {
+            resume_position = f64::max(resume_position, min_position);
+        }
+        Logger::info(&format!(
+            "Core: Resuming primary content at {resume_position}"
+        ));
+        jsAnnounceInterstitialEvent(InterstitialEvent::End, interstitial.id());
+        self.playlist_store = Some(primary_playlist_store);
+        self.reload_media_source(StartingPosition::new(
+            StartingPositionType::Absolute,
+            resume_position,
+        ));
+        self.last_position = resume_position;
+
+        // Media Playlists may have to be refreshed and the primary content's variants have to be
+        // announced again
+        self.check_ready_to_load_media_playlists();
+        self.load_steering_manifest();
+    }
+
+    /// Begin to play the given `interstitial` in place of the primary content.
+    ///
+    /// If `resume_position` is set, the primary content will be resumed at that position, in
+    /// playlist time, instead of at the interstitial's resumption offset.
+    fn start_interstitial(&mut self, interstitial: Interstitial, resume_position: Option<f64>) {
+        let primary_playlist_store = if let Some(playlist_store) = self.playlist_store.take() {
+            playlist_store
+        } else {
+            return;
+        };
+        Logger::info(&format!("Core: Playing interstitial {}", interstitial.id()));
+        self.played_interstitials.push(interstitial.id().to_owned());
+        jsAnnounceInterstitialEvent(InterstitialEvent::Start, interstitial.id());
+        let (url, playlist_type) = match interstitial.asset() {
+            InterstitialAsset::Uri(url) => (url.clone(), PlaylistFileType::MultivariantPlaylist),
+            InterstitialAsset::List(url) => (url.clone(), PlaylistFileType::AssetList),
+        };
+        self.interstitial = Some(InterstitialPlayback {
+            interstitial,
+            primary_playlist_store,
+            pending_assets: vec![],
+            played_duration: 0.,
+            resume_position,
+        });
+        self.load_interstitial_asset(url, playlist_type);
+    }
+
+    /// Load from the start the interstitial asset (or asset list) at `url`, in place of the
+    /// content currently played.
+    fn load_interstitial_asset(&mut self, url: Url, playlist_type: PlaylistFileType) {
+        self.playlist_store = None;
+        self.reload_media_source(StartingPosition::new(
+            StartingPositionType::FromBeginning,
+            0.,
+        ));
+        self.last_position = 0.;
+        self.requester.fetch_playlist(url, playlist_type);
+    }
+
+    /// Abort all pending requests and playlist refreshes, and attach a new `MediaSource` on which
+    /// the content in `self.playlist_store` will be played from `starting_position`.
+    fn reload_media_source(&mut self, starting_position: StartingPosition) {
+        self.requester.reset();
+        jsStopObservingPlayback();
+        self.stop_trick_play();
+        while let Some((timer_id, _)) = self.playlist_refresh_timers.pop() {
+            jsClearTimer(timer_id);
+        }
+        self.segment_selectors.reset_selectors(0.);
+        self.deferred_segments = None;
+        self.multivariant_reload = MultivariantReloadState::NotReloaded;
+        self.ready_state = PlayerReadyState::Loading {
+            starting_position: Some(starting_position),
+        };
+        if let Err(x) = self.media_element_ref.attach_media_source() {
+            jsSendOtherError(
+                true,
+                OtherErrorCode::MediaSourceAttachmentError,
+                &x.to_string(),
+            );
+            self.stop_current_content();
+        }
+    }
+}
+
+/// Returns the interstitial, amongst the `unplayed` ones in chronological order, which should
+/// begin to be played now that the position went from `prev_position` to `position`.
+///
+/// It is returned alongside the position at which the primary content should be resumed after
+/// it, when it is not the interstitial's resumption position. This is the case for
+/// interstitials which cannot be jumped over, which are played when a seek goes over them.
+///
+/// `closest_segment_boundary` should return the media segment boundary the closest to the given
+/// position, to which interstitials snapping out of the primary content are moved.
+fn interstitial_to_start(
+    mut unplayed: Vec<Interstitial>,
+    prev_position: f64,
+    position: f64,
+    is_seeking: bool,
+    closest_segment_boundary: impl Fn(f64) -> Option<f64>,
+) -> Option<(Interstitial, Option<f64>)> {
+    if is_seeking && position > prev_position {
+        let jumped_over = unplayed.iter().rposition(|i| {
+            i.restricts_jump() && prev_position < i.start() && i.start() <= position
+        });
+        if let Some(idx) = jumped_over {
+            Logger::info(&format!(
+                "Core: Seek jumped over interstitial {} which cannot be jumped over",
+                unplayed[idx].id()
+            ));
+            return Some((unplayed.swap_remove(idx), Some(position)));
+        }
+    }
+
+    let reached = unplayed.iter().position(|i| {
+        let start = if i.snaps_out() {
+            closest_segment_boundary(i.start()).unwrap_or(i.start())
+        } else {
+            i.start()
+        };
+        let end = i.end().map_or(start + INTERSTITIAL_TRIGGER_WINDOW, |end| {
+            f64::max(end, start + INTERSTITIAL_TRIGGER_WINDOW)
+        });
+        position >= start && position < end
+    })?;
+    Some((unplayed.swap_remove(reached), None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::MultivariantPlaylist, utils::logger::LoggerLevel};
+
+    /// Returns the interstitials announced by the given Media Playlist, with the start of its
+    /// first segment, to which their positions can be compared.
+    fn parse_interstitials(media_playlist: &str) -> (Vec<Interstitial>, f64) {
+        Logger::set_logger_level(LoggerLevel::None);
+        let url = Url::new("https://example.com/media.m3u8".to_owned());
+        let playlist =
+            MultivariantPlaylist::from_media_playlist(media_playlist.as_bytes(), url).unwrap();
+        let id = playlist
+            .video_media_playlist_id_for(&playlist.all_variants()[0], None)
+            .unwrap();
+        let media_playlist = playlist.media_playlist(&id).unwrap();
+        let interstitials = media_playlist
+            .date_ranges()
+            .iter()
+            .filter_map(|d| Interstitial::from_date_range(d, media_playlist.url()))
+            .collect();
+        let start = media_playlist.segment_list().media()[0].start();
+        (interstitials, start)
+    }
+
+    const PLAYLIST: &str = "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-PROGRAM-DATE-TIME:2024-01-01T00:00:00Z
+#EXT-X-DATERANGE:ID=\"ad1\",CLASS=\"com.apple.hls.interstitial\",START-DATE=\"2024-01-01T00:00:05Z\",X-ASSET-URI=\"ad1.m3u8\"
+#EXT-X-DATERANGE:ID=\"ad2\",CLASS=\"com.apple.hls.interstitial\",START-DATE=\"2024-01-01T00:00:10Z\",DURATION=6,X-ASSET-URI=\"ad2.m3u8\",X-RESTRICT=\"JUMP\"
+#EXT-X-DATERANGE:ID=\"ad3\",CLASS=\"com.apple.hls.interstitial\",START-DATE=\"2024-01-01T00:00:21Z\",X-ASSET-URI=\"ad3.m3u8\",X-SNAP=\"OUT\"
+#EXTINF:4,
+seg0.ts
+#EXTINF:4,
+seg1.ts
+#EXTINF:4,
+seg2.ts
+#EXTINF:4,
+seg3.ts
+#EXTINF:4,
+seg4.ts
+#EXTINF:4,
+seg5.ts
+";
+
+    /// Returns the `ID` of the interstitial to start when playback goes from `prev_position`
+    /// to `position`, both relative to the start of `PLAYLIST`, alongside the resume position.
+    fn to_start(prev_position: f64, position: f64, is_seeking: bool) -> Option<(String, f64)> {
+        let (interstitials, start) = parse_interstitials(PLAYLIST);
+        let boundary = |pos: f64| Some(start + ((pos - start) / 4.).round() * 4.);
+        interstitial_to_start(
+            interstitials,
+            start + prev_position,
+            start + position,
+            is_seeking,
+            boundary,
+        )
+        .map(|(i, resume)| (i.id().to_owned(), resume.map_or(-1., |r| r - start)))
+    }
+
+    #[test]
+    fn test_interstitial_trigger_window() {
+        assert_eq!(to_start(4.5, 4.9, false), None);
+        assert_eq!(to_start(4.9, 5.1, false), Some(("ad1".to_owned(), -1.)));
+        // Without known end, interstitials are only triggered for a short while
+        assert_eq!(to_start(6.8, 6.9, false), Some(("ad1".to_owned(), -1.)));
+        assert_eq!(to_start(7.1, 7.2, false), None);
+        // Otherwise, they are until their end
+        assert_eq!(to_start(15.8, 15.9, false), Some(("ad2".to_owned(), -1.)));
+        assert_eq!(to_start(16.1, 16.2, false), None);
+        // Interstitials snapping out begin at the closest segment boundary
+        assert_eq!(to_start(19.9, 20.1, false), Some(("ad3".to_owned(), -1.)));
+    }
+
+    #[test]
+    fn test_interstitial_jump_restriction() {
+        // Seeking over an interstitial restricting jumps plays it, then resumes at the seek
+        // position
+        assert_eq!(to_start(2., 18., true), Some(("ad2".to_owned(), 18.)));
+        // The other ones can be jumped over
+        assert_eq!(to_start(2., 9., true), None);
+        // Seeking backward over them is allowed
+        assert_eq!(to_start(18., 2., true), None);
+        // Regular playback does not trigger it
+        assert_eq!(to_start(2., 18., false), None);
+        // Seeking inside it plays it as usual
+        assert_eq!(to_start(10.5, 12., true), Some(("ad2".to_owned(), -1.)));
+    }
+}
@@ -1,36 +1,53 @@
 use super::{
-    event_listeners::JsTimeRanges, Dispatcher, JsMemoryBlob, MediaObservation,
-    MediaSourceReadyState, PlaybackTickReason, PlayerReadyState, StartingPositionType,
+    event_listeners::JsTimeRanges, DeferredSegment, Dispatcher, JsMemoryBlob, MediaObservation,
+    MediaSourceReadyState, MultivariantReloadState, PlaybackTickReason, PlayerReadyState,
+    StartingPositionType,
 };
 use crate::{
     bindings::{
         formatters::{
-            format_audio_tracks_for_js, format_source_buffer_creation_err_for_js,
-            format_variants_info_for_js,
+            format_audio_tracks_for_js, format_date_range_attributes_for_js,
+            format_filtered_variants_info_for_js, format_source_buffer_creation_err_for_js,
+            format_text_tracks_for_js, format_variants_info_for_js, format_video_tracks_for_js,
         },
-        jsAnnounceFetchedContent, jsAnnounceTrackUpdate, jsAnnounceVariantLockStatusChange,
-        jsAnnounceVariantUpdate, jsClearTimer, jsSendMediaPlaylistParsingError,
-        jsSendMediaPlaylistRequestError, jsSendMultivariantPlaylistParsingError,
-        jsSendMultivariantPlaylistRequestError, jsSendOtherError, jsSendPushedSegmentError,
-        jsSendRemovedBufferError, jsSendSegmentParsingError, jsSendSegmentRequestError,
-        jsSendSourceBufferCreationError, jsSetMediaSourceDuration, jsStartObservingPlayback,
-        jsStopObservingPlayback, jsTimer, jsUpdateContentInfo, AddSourceBufferErrorCode, MediaType,
-        MultivariantPlaylistParsingErrorCode, OtherErrorCode, PlaylistNature,
-        PushedSegmentErrorCode, RequestId, SourceBufferId, TimerId, TimerReason,
+        jsAnnounceDateRangeEvent, jsAnnounceFetchedContent, jsAnnounceTextTrackUpdate,
+        jsAnnounceTrackUpdate, jsAnnounceVariantLockStatusChange, jsAnnounceVariantUpdate,
+        jsClearTimer, jsSendMediaPlaylistParsingError, jsSendMediaPlaylistRequestError,
+        jsSendMultivariantPlaylistParsingError, jsSendMultivariantPlaylistRequestError,
+        jsSendOtherError, jsSendPushedSegmentError, jsSendRemovedBufferError,
+        jsSendSegmentParsingError, jsSendSegmentRequestError, jsSendSourceBufferCreationError,
+        jsSetMediaSourceDuration, jsStartObservingPlayback, jsStopObservingPlayback,
+        jsStoreResource, jsTimer, jsUpdateContentInfo, AddSourceBufferErrorCode, DateRangeEvent,
+        MediaType, MultivariantPlaylistParsingErrorCode, OtherErrorCode, PlaylistNature,
+        PushedSegmentErrorCode, RequestId, SegmentParsingErrorCode, SourceBufferId, TimerId,
+        TimerReason,
     },
     media_element::{SegmentQualityContext, SourceBufferCreationError},
-    parser::{MultivariantPlaylist, SegmentTimeInfo},
+    parser::{
+        AudioSelectionPreferences, DateRange, MediaPlaylist, MediaPlaylistParsingError,
+        MediaPlaylistRefresh, MediaPlaylistUpdateError, MultivariantPlaylist,
+        OutputProtectionCapabilities, SegmentTimeInfo, VideoDynamicRange,
+    },
     playlist_store::{
         LockVariantResponse, MediaPlaylistPermanentId, PlaylistStore, PlaylistStoreError,
-        SetAudioTrackResponse, VariantUpdateResult,
+        SetTrackResponse, VariantUpdateResult,
     },
     requester::{
-        FinishedRequestType, PlaylistFileType, PlaylistRequestInfo, RetryResult, SegmentRequestInfo,
+        FinishedRequestType, KeyRequestInfo, PlaylistFileType, PlaylistRequestInfo, RetryResult,
+        SegmentRequestInfo,
     },
-    utils::url::Url,
+    subtitles::ClosedCaptionsDecoder,
+    utils::{aes::decrypt_aes_128_cbc, isobmff, mpeg_ts, url::Url},
     Logger,
 };
 
+mod closed_captions;
+mod content_steering;
+mod interstitials;
+mod session_data;
+mod text_tracks;
+mod trick_play;
+
 impl Dispatcher {
     /// Completely stop playback of the current content if one and free all its associated
     /// resources.
@@ -38,14 +55,37 @@ impl Dispatcher {
         Logger::info("Core: Stopping current content (if one) and resetting player");
         self.requester.reset();
         jsStopObservingPlayback();
+        self.stop_trick_play();
         self.media_element_ref.reset();
         self.segment_selectors.reset_selectors(0.);
         self.playlist_store = None;
         self.last_position = 0.;
         self.clean_up_playlist_refresh_timers();
+        self.active_date_ranges.clear();
+        self.interstitial = None;
+        self.played_interstitials.clear();
+        self.text_timescale = None;
+        self.closed_captions_decoder = ClosedCaptionsDecoder::new();
+        self.closed_captions_cues.clear();
+        self.deferred_segments = None;
+        self.multivariant_reload = MultivariantReloadState::NotReloaded;
         self.ready_state = PlayerReadyState::Stopped;
     }
 
+    /// Stop playing the current content, generally after a fatal error has been sent to
+    /// JavaScript.
+    ///
+    /// If an HLS Interstitial is currently played, only that interstitial is abandoned and the
+    /// primary content is loaded again.
+    fn abort_current_content(&mut self) {
+        if self.is_playing_interstitial() {
+            Logger::warn("Core: Abandoning the current interstitial");
+            self.end_interstitial();
+        } else {
+            self.stop_current_content();
+        }
+    }
+
     /// Check which is the best HLS variant to select according to the current conditions
     /// If it changed, handle the consequences (such as requesting new media playlists, loading
     /// and pushing segments etc.).
@@ -64,6 +104,7 @@ impl Dispatcher {
     pub(super) fn lock_variant_core(&mut self, variant_id: u32) {
         if let Some(pl_store) = self.playlist_store.as_mut() {
             let is_audio_track_selected = pl_store.curr_audio_track_id().is_some();
+            let prev_video_track = pl_store.curr_video_track_id();
             match pl_store.lock_variant(variant_id) {
                 LockVariantResponse::NoVariantWithId => {
                     Logger::warn("Core: Locked variant not found");
@@ -84,7 +125,12 @@ impl Dispatcher {
                             is_audio_track_selected,
                         );
                     }
+                    let has_video_track_changed =
+                        pl_store.curr_video_track_id() != prev_video_track;
                     self.handle_variant_update(updates, true);
+                    if has_video_track_changed {
+                        self.announce_video_track();
+                    }
                     jsAnnounceVariantLockStatusChange(Some(variant_id));
                 }
             }
@@ -105,18 +151,39 @@ impl Dispatcher {
         let found = self.playlist_refresh_timers.iter().position(|x| x.0 == id);
         if let Some(idx) = found {
             let (_, playlist_type) = self.playlist_refresh_timers.remove(idx);
-            if let Some(playlist_store) = &self.playlist_store {
+            let blocking_refresh = match (&playlist_type, &self.playlist_store) {
+                (
+                    PlaylistFileType::MediaPlaylist { id, .. }
+                    | PlaylistFileType::TextMediaPlaylist { id },
+                    Some(playlist_store),
+                ) => playlist_store
+                    .media_playlist(id)
+                    .and_then(|p| p.blocking_refresh()),
+                _ => None,
+            };
+            if blocking_refresh.is_some() {
+                // The previous blocking reload did not bring anything new, retry it now that
+                // we've waited a little
+                self.schedule_media_playlist_refresh(playlist_type, blocking_refresh);
+            } else if let Some(playlist_store) = &self.playlist_store {
                 match playlist_type {
                     PlaylistFileType::MultivariantPlaylist => self
                         .requester
                         .fetch_playlist(playlist_store.url().clone(), playlist_type),
-                    PlaylistFileType::MediaPlaylist { ref id, .. } => {
-                        if let Some(u) = playlist_store.media_playlist_url(id) {
-                            self.requester.fetch_playlist(u.clone(), playlist_type)
+                    PlaylistFileType::MediaPlaylist { ref id, .. }
+                    | PlaylistFileType::TextMediaPlaylist { ref id } => {
+                        if let Some(u) = playlist_store.media_playlist_refresh_url(id, None) {
+                            self.requester.fetch_playlist(u, playlist_type)
                         } else {
                             Logger::error("Core: Cannot refresh Media Playlist: id not found");
                         }
                     }
+                    PlaylistFileType::SteeringManifest => {
+                        if let Some(url) = playlist_store.steering_manifest_url() {
+                            self.requester.fetch_playlist(url, playlist_type)
+                        }
+                    }
+                    PlaylistFileType::AssetList | PlaylistFileType::SessionData { .. } => {}
                 }
             }
         }
@@ -131,21 +198,124 @@ impl Dispatcher {
     /// Set an audio track whose `id` is given in argument.
     pub(super) fn set_audio_track_core(&mut self, track_id: Option<u32>) {
         if let Some(ref mut pl_store) = self.playlist_store {
-            match pl_store.set_audio_track(track_id) {
-                SetAudioTrackResponse::AudioMediaUpdate => {
-                    self.handle_media_playlist_update(&[MediaType::Audio], true, true)
+            let response = pl_store.set_audio_track(track_id);
+            self.handle_set_track_response(response, MediaType::Audio);
+        }
+    }
+
+    /// Set a video track whose `id` is given in argument, or rely on the content's default video
+    /// renditions if `None`.
+    pub(super) fn set_video_track_core(&mut self, track_id: Option<u32>) {
+        if let Some(ref mut pl_store) = self.playlist_store {
+            let response = pl_store.set_video_track(track_id);
+            self.handle_set_track_response(response, MediaType::Video);
+            self.announce_video_track();
+        }
+    }
+
+    /// Update the preferences and capabilities considered when no audio track is explicitely
+    /// selected.
+    pub(super) fn set_audio_selection_preferences_core(
+        &mut self,
+        preferences: AudioSelectionPreferences,
+    ) {
+        self.audio_preferences = preferences;
+        if let Some(ref mut pl_store) = self.playlist_store {
+            let prev_audio_track = pl_store.curr_audio_track_id();
+            let response = pl_store.set_audio_selection_preferences(preferences);
+            let is_audio_track_selected = pl_store.selected_audio_track_id().is_some();
+            let new_audio_track = pl_store.curr_audio_track_id();
+            self.handle_set_track_response(response, MediaType::Audio);
+            if new_audio_track != prev_audio_track {
+                jsAnnounceTrackUpdate(MediaType::Audio, new_audio_track, is_audio_track_selected);
+            }
+        }
+    }
+
+    /// Update the output protection the device is able to enforce, which restricts the variants
+    /// that can be played.
+    pub(super) fn set_output_protection_core(
+        &mut self,
+        capabilities: OutputProtectionCapabilities,
+    ) {
+        self.output_protection = capabilities;
+        let Some(ref mut pl_store) = self.playlist_store else {
+            return;
+        };
+        let result = pl_store.set_output_protection(&self.output_protection);
+        self.handle_capabilities_update(result);
+    }
+
+    /// Update the video dynamic ranges supported by the display, which restrict the variants
+    /// that can be played.
+    pub(super) fn set_supported_video_ranges_core(
+        &mut self,
+        supported_video_ranges: Vec<VideoDynamicRange>,
+    ) {
+        self.supported_video_ranges = supported_video_ranges;
+        let Some(ref mut pl_store) = self.playlist_store else {
+            return;
+        };
+        let result = pl_store.set_supported_video_ranges(self.supported_video_ranges.clone());
+        self.handle_capabilities_update(result);
+    }
+
+    /// Perform the actions needed after the `PlaylistStore` has been informed of a change in the
+    /// device's capabilities, which may have changed the list of variants that can be played.
+    fn handle_capabilities_update(&mut self, result: Result<SetTrackResponse, PlaylistStoreError>) {
+        match result {
+            Ok(response) => {
+                if self
+                    .playlist_store
+                    .as_ref()
+                    .is_some_and(|p| p.are_codecs_checked())
+                {
+                    self.announce_variants_and_tracks();
                 }
-                SetAudioTrackResponse::VariantUpdate {
-                    updates,
-                    unlocked_variant,
-                } => {
-                    self.handle_variant_update(updates, true);
-                    if unlocked_variant {
-                        jsAnnounceVariantLockStatusChange(None);
-                    }
+                self.handle_set_track_response(response, MediaType::Video);
+            }
+            Err(err) => {
+                jsSendOtherError(
+                    !self.is_playing_interstitial(),
+                    OtherErrorCode::NoSupportedVariant,
+                    &err.to_string(),
+                );
+                self.abort_current_content();
+            }
+        }
+    }
+
+    /// Perform the actions needed after a track of the given `MediaType` has been updated in the
+    /// `PlaylistStore`, depending on the `SetTrackResponse` it returned.
+    fn handle_set_track_response(&mut self, response: SetTrackResponse, media_type: MediaType) {
+        match response {
+            SetTrackResponse::MediaUpdate => {
+                self.handle_media_playlist_update(&[media_type], true, true)
+            }
+            SetTrackResponse::VariantUpdate {
+                updates,
+                unlocked_variant,
+            } => {
+                self.handle_variant_update(updates, true);
+                if unlocked_variant {
+                    jsAnnounceVariantLockStatusChange(None);
                 }
-                _ => {}
             }
+            SetTrackResponse::NoUpdate => {}
+        }
+    }
+
+    /// Communicate to JavaScript the video track currently loaded, if one, and whether it was
+    /// explicitely selected.
+    fn announce_video_track(&self) {
+        if let Some(pl_store) = self.playlist_store.as_ref() {
+            let selected_video_track = pl_store.selected_video_track_id();
+            let curr_video_track = selected_video_track.or_else(|| pl_store.curr_video_track_id());
+            jsAnnounceTrackUpdate(
+                MediaType::Video,
+                curr_video_track,
+                selected_video_track.is_some(),
+            );
         }
     }
 
@@ -165,6 +335,12 @@ impl Dispatcher {
             Some(FinishedRequestType::Playlist(pl_info)) => {
                 self.on_playlist_fetch_success(pl_info, data.obtain(), final_url)
             }
+            Some(FinishedRequestType::Key(key_info)) => {
+                self.on_key_fetch_success(key_info, data.obtain())
+            }
+            Some(FinishedRequestType::TextSegment(seg_info)) => {
+                self.on_text_segment_fetch_success(seg_info, data)
+            }
             None => Logger::warn("Core: Unknown request finished"),
         }
     }
@@ -186,8 +362,10 @@ impl Dispatcher {
                 status,
             } => {
                 let time_info = s.time_info();
+                // That segment may still be reachable through another Content Steering Pathway
+                let has_failed_over = self.fail_over_pathway();
                 jsSendSegmentRequestError(
-                    true,
+                    !has_failed_over && !self.is_playing_interstitial(),
                     s.url().get_ref(),
                     time_info.is_none(),
                     time_info.map(|t| vec![t.start(), t.end()]),
@@ -195,7 +373,9 @@ impl Dispatcher {
                     reason,
                     status,
                 );
-                self.stop_current_content();
+                if !has_failed_over {
+                    self.abort_current_content();
+                }
             }
             RetryResult::Failed {
                 request_type: FinishedRequestType::Playlist(x),
@@ -204,26 +384,84 @@ impl Dispatcher {
                 ..
             } => {
                 match x.playlist_type {
-                    PlaylistFileType::MediaPlaylist { media_type, .. } => {
+                    PlaylistFileType::MediaPlaylist {
+                        ref id, media_type, ..
+                    } => {
+                        // That Media Playlist may be replaced by one from another Content
+                        // Steering Pathway
+                        let has_failed_over = self.fail_over_pathway()
+                            && !self
+                                .playlist_store
+                                .as_ref()
+                                .is_some_and(|p| p.is_curr_media_playlist(id));
+                        // Its URL may also have changed since the Multivariant Playlist was
+                        // loaded
+                        let is_reloading =
+                            !has_failed_over && self.reload_multivariant_playlist(media_type);
                         jsSendMediaPlaylistRequestError(
-                            true,
+                            !has_failed_over && !is_reloading && !self.is_playing_interstitial(),
                             x.url.get_ref(),
                             reason,
                             media_type,
                             status,
                         );
+                        if has_failed_over || is_reloading {
+                            return;
+                        }
+                    }
+                    PlaylistFileType::TextMediaPlaylist { .. } => {
+                        // Subtitles are not needed for playback, no need to stop it
+                        self.on_text_media_playlist_request_failed(&x.url);
+                        return;
                     }
                     PlaylistFileType::MultivariantPlaylist => {
                         jsSendMultivariantPlaylistRequestError(
-                            true,
+                            !self.is_playing_interstitial(),
                             x.url.get_ref(),
                             reason,
                             status,
                         );
                     }
+                    PlaylistFileType::AssetList => {
+                        Logger::warn("Core: Could not load the interstitial's asset list")
+                    }
+                    PlaylistFileType::SessionData { .. } => {
+                        // Session data is not needed for playback, no need to stop it
+                        jsSendOtherError(
+                            false,
+                            OtherErrorCode::SessionDataLoadingError,
+                            &format!("Could not load session data from {}", x.url),
+                        );
+                        return;
+                    }
+                    PlaylistFileType::SteeringManifest => {
+                        // Content Steering is not needed for playback, no need to stop it
+                        self.on_steering_manifest_request_failed();
+                        return;
+                    }
                 }
-                self.stop_current_content();
+                self.abort_current_content();
             }
+            RetryResult::Failed {
+                request_type: FinishedRequestType::Key(k),
+                reason,
+                status,
+            } => {
+                jsSendSegmentRequestError(
+                    !self.is_playing_interstitial(),
+                    k.url().get_ref(),
+                    false,
+                    None,
+                    k.media_type(),
+                    reason,
+                    status,
+                );
+                self.abort_current_content();
+            }
+            RetryResult::Failed {
+                request_type: FinishedRequestType::TextSegment(s),
+                ..
+            } => self.on_text_segment_request_failed(s),
 
             RetryResult::RetriedSegment {
                 request_info,
@@ -241,6 +479,27 @@ impl Dispatcher {
                 );
             }
 
+            RetryResult::RetriedKey {
+                request_info,
+                reason,
+                status,
+            } => {
+                jsSendSegmentRequestError(
+                    false,
+                    request_info.url().get_ref(),
+                    false,
+                    None,
+                    request_info.media_type(),
+                    reason,
+                    status,
+                );
+            }
+
+            RetryResult::RetriedTextSegment { request_info, .. } => Logger::warn(&format!(
+                "Core: Text segment request failed, retrying u:{}",
+                request_info.url()
+            )),
+
             RetryResult::RetriedPlaylist {
                 request_info,
                 reason,
@@ -261,6 +520,18 @@ impl Dispatcher {
                         status,
                     )
                 }
+                PlaylistFileType::TextMediaPlaylist { .. } => {
+                    Logger::warn("Core: Text media playlist request failed, retrying")
+                }
+                PlaylistFileType::AssetList => {
+                    Logger::warn("Core: Interstitial's asset list request failed, retrying")
+                }
+                PlaylistFileType::SessionData { .. } => {
+                    Logger::warn("Core: Session data request failed, retrying")
+                }
+                PlaylistFileType::SteeringManifest => {
+                    Logger::warn("Core: Content Steering manifest request failed, retrying")
+                }
             },
 
             RetryResult::NotFound => {
@@ -291,8 +562,13 @@ impl Dispatcher {
             .on_source_buffer_creation_error(source_buffer_id, original_error)
         {
             let (code, msg) = format_source_buffer_creation_err_for_js(e);
-            jsSendSourceBufferCreationError(true, code, media_type, &msg);
-            self.stop_current_content();
+            jsSendSourceBufferCreationError(
+                !self.is_playing_interstitial(),
+                code,
+                media_type,
+                &msg,
+            );
+            self.abort_current_content();
         }
     }
 
@@ -322,15 +598,15 @@ impl Dispatcher {
                         "An error happened while calling `appendBuffer` on the {mt} `SourceBuffer`"
                     ),
                 };
-                jsSendPushedSegmentError(true, code, mt, &message);
+                jsSendPushedSegmentError(!self.is_playing_interstitial(), code, mt, &message);
             }
             None => jsSendOtherError(
-                true,
+                !self.is_playing_interstitial(),
                 OtherErrorCode::Unknown,
                 "An unknown SourceBuffer failed during a push operation.",
             ),
         }
-        self.stop_current_content();
+        self.abort_current_content();
     }
 
     /// Method to call when a `SourceBuffer`'s `remove` call led to an `error` event.
@@ -339,15 +615,15 @@ impl Dispatcher {
             Some(mt) => {
                 let message =
                     &format!("An error happened while calling `remove` on the {mt} `SourceBuffer`");
-                jsSendRemovedBufferError(true, mt, message);
+                jsSendRemovedBufferError(!self.is_playing_interstitial(), mt, message);
             }
             None => jsSendOtherError(
-                true,
+                !self.is_playing_interstitial(),
                 OtherErrorCode::Unknown,
                 "An unknown SourceBuffer failed during a remove operation.",
             ),
         }
-        self.stop_current_content();
+        self.abort_current_content();
     }
 
     /// Method to call when a new `MediaObservation` has been received.
@@ -358,10 +634,56 @@ impl Dispatcher {
             reason,
             observation.current_time()
         ));
+        let prev_position = self.last_position;
         self.media_element_ref.on_observation(observation);
-        match reason {
-            PlaybackTickReason::Seeking => self.on_seek(),
-            _ => self.on_regular_tick(),
+        let is_seeking = matches!(reason, PlaybackTickReason::Seeking);
+        if is_seeking {
+            self.on_seek();
+        } else {
+            self.on_regular_tick();
+        }
+        if self.is_playing_interstitial() {
+            self.check_interstitial_playback(is_seeking);
+        } else {
+            self.check_date_ranges();
+            self.check_interstitials(prev_position, is_seeking);
+        }
+    }
+
+    /// Announce through JS events date ranges that the current position just entered or left.
+    fn check_date_ranges(&mut self) {
+        let playlist_store = if let Some(playlist_store) = self.playlist_store.as_ref() {
+            playlist_store
+        } else {
+            return;
+        };
+        let position = self.media_element_ref.wanted_position();
+        let date_ranges = playlist_store.curr_date_ranges();
+
+        self.active_date_ranges.retain_mut(|active| {
+            if let Some(updated) = date_ranges.iter().find(|d| d.id() == active.id()) {
+                *active = (*updated).clone();
+            }
+            if active.contains(position) {
+                true
+            } else {
+                Logger::debug(&format!("Core: Leaving date range {}", active.id()));
+                announce_date_range_event(DateRangeEvent::Leave, active);
+                false
+            }
+        });
+
+        for date_range in date_ranges {
+            if date_range.contains(position)
+                && !self
+                    .active_date_ranges
+                    .iter()
+                    .any(|d| d.id() == date_range.id())
+            {
+                Logger::debug(&format!("Core: Entering date range {}", date_range.id()));
+                announce_date_range_event(DateRangeEvent::Enter, date_range);
+                self.active_date_ranges.push(date_range.clone());
+            }
         }
     }
 
@@ -384,6 +706,7 @@ impl Dispatcher {
             .for_each(|mt| {
                 self.check_segment_to_request_for_type(mt);
             });
+        self.check_text_segment_to_request();
         if !was_already_locked {
             self.requester.unlock_segment_requests();
         }
@@ -432,11 +755,13 @@ impl Dispatcher {
                 if actual_start > 0. {
                     self.media_element_ref.seek(actual_start);
                 }
+                self.last_position = actual_start;
             } else {
                 let start_time = playlist_store.expected_start_time();
                 if start_time > 0. {
                     self.media_element_ref.seek(start_time);
                 }
+                self.last_position = start_time;
             }
 
             self.ready_state = PlayerReadyState::AwaitingSegments;
@@ -448,16 +773,13 @@ impl Dispatcher {
                 Logger::warn("Core: Unknown content duration");
             }
 
-            if let Some(Err(e)) = self.init_source_buffer(MediaType::Audio) {
-                let (code, msg) = format_source_buffer_creation_err_for_js(e);
-                jsSendSourceBufferCreationError(true, code, MediaType::Audio, &msg);
-                self.stop_current_content();
-                return;
-            }
-            if let Some(Err(e)) = self.init_source_buffer(MediaType::Video) {
-                let (code, msg) = format_source_buffer_creation_err_for_js(e);
-                jsSendSourceBufferCreationError(true, code, MediaType::Video, &msg);
-                self.stop_current_content();
+            if self.should_infer_codecs() {
+                Logger::info(
+                    "Core: Codecs not announced, deferring SourceBuffer creation until \
+                    the segments announcing them are loaded",
+                );
+                self.deferred_segments = Some(vec![]);
+            } else if !self.init_source_buffers() {
                 return;
             }
             jsStartObservingPlayback();
@@ -472,46 +794,192 @@ impl Dispatcher {
         final_url: Url,
     ) {
         let PlaylistRequestInfo { playlist_type, .. } = pl_info;
-        if let PlaylistFileType::MediaPlaylist { id, media_type } = playlist_type {
-            self.on_media_playlist_loaded(id, result, media_type, final_url);
-        } else {
-            self.on_multivariant_playlist_loaded(result, final_url);
+        match playlist_type {
+            PlaylistFileType::MediaPlaylist { id, media_type } => {
+                self.on_media_playlist_loaded(id, result, media_type, final_url)
+            }
+            PlaylistFileType::TextMediaPlaylist { id } => {
+                self.on_text_media_playlist_loaded(id, result, final_url)
+            }
+            PlaylistFileType::MultivariantPlaylist => {
+                self.on_multivariant_playlist_loaded(result, final_url)
+            }
+            PlaylistFileType::AssetList => self.on_asset_list_loaded(result, final_url),
+            PlaylistFileType::SessionData { index } => self.on_session_data_loaded(index, result),
+            PlaylistFileType::SteeringManifest => {
+                self.on_steering_manifest_loaded(result, final_url)
+            }
         }
     }
 
     /// Method called once a Multivariant Playlist was loaded with success, with its response data
     /// and url as argument.
     fn on_multivariant_playlist_loaded(&mut self, data: Vec<u8>, playlist_url: Url) {
-        match MultivariantPlaylist::parse(data.as_ref(), playlist_url) {
-            Err(e) => {
-                let message = e.to_string();
-                jsSendMultivariantPlaylistParsingError(true, e.into(), &message);
-                self.stop_current_content();
-            }
-            Ok(pl) => {
-                Logger::info("Core: Multivariant Playlist parsed successfully");
-                let estimate = self.adaptive_selector.get_estimate();
-                match PlaylistStore::try_new(pl, estimate) {
-                    Ok(pl_store) => {
-                        self.playlist_store = Some(pl_store);
-                        self.check_ready_to_load_media_playlists();
-                    }
-                    Err(err) => {
-                        match err {
-                            PlaylistStoreError::NoSupportedVariant => jsSendOtherError(
-                                true,
-                                OtherErrorCode::NoSupportedVariant,
-                                &err.to_string(),
-                            ),
-                            PlaylistStoreError::NoInitialVariant => jsSendMultivariantPlaylistParsingError(
-                                true,
-                                MultivariantPlaylistParsingErrorCode::MultivariantPlaylistWithoutVariant,
-                                &err.to_string(),
-                            ),
-                        };
-                        self.stop_current_content();
+        let parsed = if MultivariantPlaylist::is_media_playlist(&data) {
+            Logger::info("Core: Loaded a lone Media Playlist, wrapping it in a single variant");
+            match MultivariantPlaylist::from_media_playlist(data.as_ref(), playlist_url) {
+                Ok(pl) => pl,
+                Err(e) => {
+                    let e = MediaPlaylistUpdateError::from(e);
+                    let message = e.to_string();
+                    jsSendMediaPlaylistParsingError(
+                        !self.is_playing_interstitial(),
+                        e.into(),
+                        MediaType::Video,
+                        &message,
+                    );
+                    self.abort_current_content();
+                    return;
+                }
+            }
+        } else {
+            match MultivariantPlaylist::parse(data.as_ref(), playlist_url) {
+                Ok(pl) => pl,
+                Err(e) => {
+                    let message = e.to_string();
+                    jsSendMultivariantPlaylistParsingError(
+                        !self.is_playing_interstitial(),
+                        e.into(),
+                        &message,
+                    );
+                    self.abort_current_content();
+                    return;
+                }
+            }
+        };
+        if self.playlist_store.is_some() {
+            Logger::info("Core: Refreshed Multivariant Playlist parsed successfully");
+            self.on_multivariant_playlist_refreshed(parsed);
+            return;
+        }
+        Logger::info("Core: Multivariant Playlist parsed successfully");
+        let estimate = self.adaptive_selector.get_estimate();
+        match PlaylistStore::try_new(
+            parsed,
+            estimate,
+            self.audio_preferences,
+            &self.output_protection,
+            self.supported_video_ranges.clone(),
+        ) {
+            Ok(pl_store) => {
+                self.playlist_store = Some(pl_store);
+                self.check_ready_to_load_media_playlists();
+                self.load_steering_manifest();
+            }
+            Err(err) => {
+                match err {
+                    PlaylistStoreError::NoSupportedVariant => jsSendOtherError(
+                        !self.is_playing_interstitial(),
+                        OtherErrorCode::NoSupportedVariant,
+                        &err.to_string(),
+                    ),
+                    PlaylistStoreError::NoInitialVariant => jsSendMultivariantPlaylistParsingError(
+                        !self.is_playing_interstitial(),
+                        MultivariantPlaylistParsingErrorCode::MultivariantPlaylistWithoutVariant,
+                        &err.to_string(),
+                    ),
+                };
+                self.abort_current_content();
+            }
+        }
+    }
+
+    /// Method called once a new version of the current Multivariant Playlist was parsed.
+    ///
+    /// The current variant, tracks and already-loaded Media Playlists are kept when still
+    /// present in it, so that it doesn't lead to any flush.
+    fn on_multivariant_playlist_refreshed(&mut self, playlist: MultivariantPlaylist) {
+        let Some(ref mut pl_store) = self.playlist_store else {
+            return;
+        };
+        match pl_store.update_multivariant_playlist(playlist, &self.output_protection) {
+            Ok(response) => {
+                if pl_store.are_codecs_checked() {
+                    self.announce_variants_and_tracks();
+                }
+                if let SetTrackResponse::VariantUpdate {
+                    updates,
+                    unlocked_variant,
+                } = response
+                {
+                    // Like for adaptive switches, there's no need to flush the previous data
+                    self.handle_variant_update(updates, false);
+                    if unlocked_variant {
+                        jsAnnounceVariantLockStatusChange(None);
                     }
                 }
+                self.clean_up_playlist_refresh_timers();
+                self.load_text_media_playlist();
+                if let MultivariantReloadState::Reloading(media_types) = std::mem::replace(
+                    &mut self.multivariant_reload,
+                    MultivariantReloadState::Reloaded,
+                ) {
+                    self.reload_media_playlists(&media_types);
+                }
+            }
+            Err(err) => {
+                match err {
+                    PlaylistStoreError::NoSupportedVariant => jsSendOtherError(
+                        !self.is_playing_interstitial(),
+                        OtherErrorCode::NoSupportedVariant,
+                        &err.to_string(),
+                    ),
+                    PlaylistStoreError::NoInitialVariant => jsSendMultivariantPlaylistParsingError(
+                        !self.is_playing_interstitial(),
+                        MultivariantPlaylistParsingErrorCode::MultivariantPlaylistWithoutVariant,
+                        &err.to_string(),
+                    ),
+                };
+                self.abort_current_content();
+            }
+        }
+    }
+
+    /// Reload the Multivariant Playlist of the current content, because the Media Playlist of
+    /// the given `MediaType` could not be loaded, which may be due to its URL having changed
+    /// since.
+    ///
+    /// Returns `false` if it has already been reloaded since a Media Playlist was last loaded
+    /// with success, in which case it is not reloaded again.
+    fn reload_multivariant_playlist(&mut self, media_type: MediaType) -> bool {
+        match self.multivariant_reload {
+            MultivariantReloadState::Reloaded => false,
+            MultivariantReloadState::Reloading(ref mut media_types) => {
+                media_types.push(media_type);
+                true
+            }
+            MultivariantReloadState::NotReloaded => {
+                let Some(url) = self.playlist_store.as_ref().map(|p| p.url().clone()) else {
+                    return false;
+                };
+                Logger::info("Core: Reloading the Multivariant Playlist");
+                self.multivariant_reload = MultivariantReloadState::Reloading(vec![media_type]);
+                self.requester
+                    .fetch_playlist(url, PlaylistFileType::MultivariantPlaylist);
+                true
+            }
+        }
+    }
+
+    /// Request again the current Media Playlists of the given `MediaType`s, through the URL
+    /// announced by the current Multivariant Playlist, if they are not already being requested.
+    fn reload_media_playlists(&mut self, media_types: &[MediaType]) {
+        let Some(pl_store) = self.playlist_store.as_ref() else {
+            return;
+        };
+        for media_type in media_types {
+            let Some(id) = pl_store.curr_media_playlist_id(*media_type) else {
+                continue;
+            };
+            let playlist_type = PlaylistFileType::MediaPlaylist {
+                id: id.clone(),
+                media_type: *media_type,
+            };
+            if self.requester.is_requesting_playlist(&playlist_type) {
+                continue;
+            }
+            if let Some(url) = pl_store.media_playlist_url(id) {
+                self.requester.fetch_playlist(url.clone(), playlist_type);
             }
         }
     }
@@ -531,44 +999,31 @@ impl Dispatcher {
         ));
         if let Some(ref mut playlist_store) = self.playlist_store.as_mut() {
             match playlist_store.update_media_playlist(&playlist_id, data.as_ref(), playlist_url) {
-                Err(e) => {
-                    let err_message = e.to_string();
-                    jsSendMediaPlaylistParsingError(true, e.into(), media_type, &err_message);
-                    self.stop_current_content();
-                }
-                Ok(p) => {
-                    if let Some(refresh_interval) = p.refresh_interval() {
-                        let timer_id = jsTimer(refresh_interval, TimerReason::MediaPlaylistRefresh);
-                        self.playlist_refresh_timers.push((
-                            timer_id,
+                Err(MediaPlaylistUpdateError::ParsingError(
+                    MediaPlaylistParsingError::InvalidDeltaUpdate,
+                )) => {
+                    Logger::warn("Core: Impossible to apply Playlist Delta Update, reloading it");
+                    if let Some(url) = playlist_store.media_playlist_url(&playlist_id) {
+                        self.requester.fetch_playlist(
+                            url.clone(),
                             PlaylistFileType::MediaPlaylist {
                                 id: playlist_id,
                                 media_type,
                             },
-                        ));
-                    }
-
-                    if let Some(duration) = playlist_store.segment_target_duration() {
-                        let mut min_buffer_time = f64::max(3., duration - 1.);
-                        min_buffer_time = f64::min(8., min_buffer_time);
-                        Logger::debug(&format!(
-                            "Core: Updating min_buffer_time: {min_buffer_time}"
-                        ));
-                        self.media_element_ref
-                            .update_min_buffer_time(min_buffer_time);
-                    }
-                    if self.ready_state.is_loading() {
-                        self.check_ready_to_load_segments();
-                    } else {
-                        self.check_segments_to_request();
-                    }
-
-                    if let Some(playlist_store) = self.playlist_store.as_ref() {
-                        let min_pos = playlist_store.curr_min_position();
-                        let max_pos = playlist_store.curr_max_position();
-                        jsUpdateContentInfo(min_pos, max_pos, playlist_store.playlist_type());
+                        );
                     }
                 }
+                Err(e) => {
+                    let err_message = e.to_string();
+                    jsSendMediaPlaylistParsingError(
+                        !self.is_playing_interstitial(),
+                        e.into(),
+                        media_type,
+                        &err_message,
+                    );
+                    self.abort_current_content();
+                }
+                Ok(_) => self.on_media_playlist_ready(playlist_id, media_type),
             }
         } else {
             jsSendOtherError(
@@ -580,6 +1035,106 @@ impl Dispatcher {
         }
     }
 
+    /// Perform all actions that should be taken once the Media Playlist identified by
+    /// `playlist_id` has been loaded or refreshed.
+    fn on_media_playlist_ready(
+        &mut self,
+        playlist_id: MediaPlaylistPermanentId,
+        media_type: MediaType,
+    ) {
+        let Some(playlist_store) = self.playlist_store.as_ref() else {
+            return;
+        };
+        let next_refresh = playlist_store
+            .media_playlist(&playlist_id)
+            .and_then(|p| p.next_refresh());
+        if let Some(discontinuity) = playlist_store.curr_min_discontinuity_sequence() {
+            self.media_element_ref
+                .forget_discontinuities_before(discontinuity);
+        }
+        if let Some(duration) = playlist_store.segment_target_duration() {
+            let mut min_buffer_time = f64::max(3., duration - 1.);
+            min_buffer_time = f64::min(8., min_buffer_time);
+            if let Some(part_duration) = playlist_store.part_target_duration() {
+                // We're playing close to the live edge, we cannot wait for much
+                min_buffer_time = f64::min(min_buffer_time, f64::max(1., part_duration));
+            }
+            Logger::debug(&format!(
+                "Core: Updating min_buffer_time: {min_buffer_time}"
+            ));
+            self.media_element_ref
+                .update_min_buffer_time(min_buffer_time);
+        }
+        self.schedule_media_playlist_refresh(
+            PlaylistFileType::MediaPlaylist {
+                id: playlist_id,
+                media_type,
+            },
+            next_refresh,
+        );
+        if let MultivariantReloadState::Reloaded = self.multivariant_reload {
+            self.multivariant_reload = MultivariantReloadState::NotReloaded;
+        }
+        if self.ready_state.is_loading() {
+            self.check_ready_to_load_segments();
+        } else {
+            self.check_segments_to_request();
+        }
+
+        if let Some(playlist_store) = self.playlist_store.as_ref() {
+            let min_pos = playlist_store.curr_min_position();
+            let max_pos = playlist_store.curr_max_position();
+            jsUpdateContentInfo(min_pos, max_pos, playlist_store.playlist_type());
+        }
+    }
+
+    /// Schedule the next refresh of the given Media Playlist, according to the `next_refresh`
+    /// it announced, if it is still one of the current ones.
+    fn schedule_media_playlist_refresh(
+        &mut self,
+        playlist_type: PlaylistFileType,
+        next_refresh: Option<MediaPlaylistRefresh>,
+    ) {
+        let playlist_store = if let Some(playlist_store) = self.playlist_store.as_ref() {
+            playlist_store
+        } else {
+            return;
+        };
+        let playlist_id = match &playlist_type {
+            PlaylistFileType::MediaPlaylist { id, .. }
+            | PlaylistFileType::TextMediaPlaylist { id } => id,
+            _ => return,
+        };
+        if !playlist_store.is_curr_media_playlist(playlist_id) {
+            // Not needed anymore, no need to refresh it
+            return;
+        }
+        match next_refresh {
+            Some(MediaPlaylistRefresh::Timer(refresh_interval)) => {
+                let timer_id = jsTimer(refresh_interval, TimerReason::MediaPlaylistRefresh);
+                self.playlist_refresh_timers.push((timer_id, playlist_type));
+            }
+            Some(MediaPlaylistRefresh::Blocking {
+                media_sequence,
+                part_index,
+                timeout,
+            }) => {
+                let directives = Some((media_sequence, part_index));
+                if let Some(url) =
+                    playlist_store.media_playlist_refresh_url(playlist_id, directives)
+                {
+                    Logger::debug(&format!(
+                        "Core: Performing blocking reload of Media Playlist (msn: {}, part: {:?})",
+                        media_sequence, part_index
+                    ));
+                    self.requester
+                        .fetch_blocking_playlist(url, playlist_type, timeout);
+                }
+            }
+            None => {}
+        }
+    }
+
     fn check_ready_to_load_media_playlists(&mut self) {
         let playlist_store = if let Some(playlist_store) = self.playlist_store.as_mut() {
             playlist_store
@@ -595,16 +1150,18 @@ impl Dispatcher {
             }
             Err(err) => {
                 match err {
-                    PlaylistStoreError::NoSupportedVariant => {
-                        jsSendOtherError(true, OtherErrorCode::NoSupportedVariant, &err.to_string())
-                    }
+                    PlaylistStoreError::NoSupportedVariant => jsSendOtherError(
+                        !self.is_playing_interstitial(),
+                        OtherErrorCode::NoSupportedVariant,
+                        &err.to_string(),
+                    ),
                     PlaylistStoreError::NoInitialVariant => jsSendMultivariantPlaylistParsingError(
-                        true,
+                        !self.is_playing_interstitial(),
                         MultivariantPlaylistParsingErrorCode::MultivariantPlaylistWithoutVariant,
                         &err.to_string(),
                     ),
                 };
-                self.stop_current_content();
+                self.abort_current_content();
                 return;
             }
             _ => {}
@@ -612,27 +1169,57 @@ impl Dispatcher {
 
         if playlist_store.supported_variants().is_empty() {
             jsSendOtherError(
-                true,
+                !self.is_playing_interstitial(),
                 crate::bindings::OtherErrorCode::NoSupportedVariant,
                 "Error while parsing MultivariantPlaylist: no compatible variant found.",
             );
-            self.stop_current_content();
+            self.abort_current_content();
             return;
         }
 
         use PlaylistFileType::*;
-        [MediaType::Video, MediaType::Audio]
-            .into_iter()
-            .for_each(|mt| {
-                if let Some(id) = playlist_store.curr_media_playlist_id(mt) {
-                    if let Some(url) = playlist_store.media_playlist_url(id) {
-                        let id = id.clone();
-                        let url = url.clone();
-                        self.requester
-                            .fetch_playlist(url, MediaPlaylist { id, media_type: mt });
-                    }
+        let mut already_loaded = vec![];
+        for mt in [MediaType::Video, MediaType::Audio] {
+            let Some(id) = playlist_store.curr_media_playlist_id(mt) else {
+                continue;
+            };
+            let playlist_type = MediaPlaylist {
+                id: id.clone(),
+                media_type: mt,
+            };
+            if playlist_store.curr_media_playlist(mt).is_some() {
+                // Already loaded, e.g. when the Multivariant Playlist was refreshed or when a lone
+                // Media Playlist was loaded in its stead. In the latter case, it has not been
+                // handled yet.
+                let is_refresh_pending = self.requester.is_requesting_playlist(&playlist_type)
+                    || self
+                        .playlist_refresh_timers
+                        .iter()
+                        .any(|(_, t)| *t == playlist_type);
+                if self.ready_state.is_loading() && !is_refresh_pending {
+                    already_loaded.push((id.clone(), mt));
                 }
-            });
+            } else if let Some(url) = playlist_store.media_playlist_url(id) {
+                self.requester.fetch_playlist(url.clone(), playlist_type);
+            }
+        }
+        self.load_text_media_playlist();
+
+        self.announce_variants_and_tracks();
+        self.check_trick_play();
+        for (id, mt) in already_loaded {
+            self.on_media_playlist_ready(id, mt);
+        }
+    }
+
+    /// Communicate to JavaScript the variants, audio, video and text tracks of the current
+    /// content, as well as the ones currently selected.
+    fn announce_variants_and_tracks(&self) {
+        let playlist_store = if let Some(playlist_store) = self.playlist_store.as_ref() {
+            playlist_store
+        } else {
+            return;
+        };
 
         // SAFETY: The following lines are unsafe because they may actually define raw pointers
         // to point to Rust's heap memory and put it in the returned values.
@@ -645,8 +1232,16 @@ impl Dispatcher {
         // synchronously on call, we should not encounter any issue.
         let variants_info =
             unsafe { format_variants_info_for_js(playlist_store.supported_variants().as_slice()) };
+        let filtered_variants_info = unsafe {
+            format_filtered_variants_info_for_js(
+                playlist_store.output_protected_variants().as_slice(),
+            )
+        };
         let audio_tracks_info =
             unsafe { format_audio_tracks_for_js(playlist_store.audio_tracks()) };
+        let video_tracks_info =
+            unsafe { format_video_tracks_for_js(playlist_store.video_tracks()) };
+        let text_tracks_info = unsafe { format_text_tracks_for_js(playlist_store.text_tracks()) };
         let selected_audio_track = playlist_store.selected_audio_track_id();
         let is_selected = selected_audio_track.is_some();
         let curr_audio_track = if let Some(selected) = selected_audio_track {
@@ -654,9 +1249,118 @@ impl Dispatcher {
         } else {
             playlist_store.curr_audio_track_id()
         };
-        jsAnnounceFetchedContent(variants_info, audio_tracks_info);
+        jsAnnounceFetchedContent(
+            variants_info,
+            audio_tracks_info,
+            video_tracks_info,
+            text_tracks_info,
+            filtered_variants_info,
+        );
         jsAnnounceVariantUpdate(playlist_store.curr_variant().map(|v| v.id()));
         jsAnnounceTrackUpdate(MediaType::Audio, curr_audio_track, is_selected);
+        self.announce_video_track();
+        jsAnnounceTextTrackUpdate(playlist_store.selected_text_track_id());
+    }
+
+    /// Create the `SourceBuffer`s needed to play the current variant.
+    ///
+    /// Returns `false` if that creation failed, in which case an error has been sent and the
+    /// current content has been aborted.
+    fn init_source_buffers(&mut self) -> bool {
+        for media_type in [MediaType::Audio, MediaType::Video] {
+            if let Some(Err(e)) = self.init_source_buffer(media_type) {
+                let (code, msg) = format_source_buffer_creation_err_for_js(e);
+                jsSendSourceBufferCreationError(
+                    !self.is_playing_interstitial(),
+                    code,
+                    media_type,
+                    &msg,
+                );
+                self.abort_current_content();
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns `true` if the formats of the current variant are not announced and may be read
+    /// from the segments of its Media Playlists, in which case the creation of `SourceBuffer`s
+    /// should wait for those to be loaded.
+    fn should_infer_codecs(&self) -> bool {
+        let Some(pl_store) = self.playlist_store.as_ref() else {
+            return false;
+        };
+        pl_store
+            .curr_variant()
+            .is_some_and(|v| v.has_guessed_codecs())
+            && [MediaType::Audio, MediaType::Video].into_iter().any(|mt| {
+                pl_store
+                    .curr_media_playlist(mt)
+                    .is_some_and(has_readable_codecs)
+            })
+    }
+
+    /// If all segments awaited to read the formats of the current variant have been loaded,
+    /// create the `SourceBuffer`s relying on those formats and push those segments to them.
+    fn check_deferred_source_buffers(&mut self) {
+        let (Some(pl_store), Some(deferred)) = (
+            self.playlist_store.as_ref(),
+            self.deferred_segments.as_ref(),
+        ) else {
+            return;
+        };
+        let are_all_loaded = [MediaType::Audio, MediaType::Video].into_iter().all(|mt| {
+            match pl_store.curr_media_playlist(mt) {
+                Some(p) if has_readable_codecs(p) => {
+                    deferred.iter().any(|(loaded_mt, _)| *loaded_mt == mt)
+                }
+                _ => true,
+            }
+        });
+        if !are_all_loaded {
+            return;
+        }
+
+        let deferred = self.deferred_segments.take().unwrap_or_default();
+        if let Some(pl_store) = self.playlist_store.as_mut() {
+            deferred.iter().for_each(|(mt, segment)| {
+                let (codecs, segment_kind) = match segment {
+                    DeferredSegment::Init(data) => (isobmff::read_codecs(data), "initialization"),
+                    DeferredSegment::Media(data, ..) => (mpeg_ts::read_codecs(data), "first media"),
+                };
+                if codecs.is_empty() {
+                    Logger::warn(&format!(
+                        "Core: Could not read codecs from the {mt} {segment_kind} segment"
+                    ));
+                } else {
+                    Logger::info(&format!(
+                        "Core: Read {mt} codecs from its {segment_kind} segment: {}",
+                        codecs.join(",")
+                    ));
+                    pl_store.update_inferred_codecs(*mt, &codecs);
+                }
+            });
+        }
+        if !self.init_source_buffers() {
+            return;
+        }
+        for (mt, segment) in deferred {
+            if !self.media_element_ref.has_buffer(mt) {
+                break;
+            }
+            match segment {
+                DeferredSegment::Init(data) => self.on_init_segment_loaded(
+                    JsMemoryBlob::from_resource_id(jsStoreResource(&data)),
+                    mt,
+                ),
+                DeferredSegment::Media(data, time_info, context) => self.on_media_segment_loaded(
+                    JsMemoryBlob::from_resource_id(jsStoreResource(&data)),
+                    mt,
+                    time_info,
+                    context,
+                ),
+            }
+        }
     }
 
     fn init_source_buffer(
@@ -815,6 +1519,13 @@ impl Dispatcher {
         } else {
             return;
         };
+        // While the creation of `SourceBuffer`s is deferred, only the segments the formats are
+        // read from are requested, if not yet loaded: initialization segments or, when there's
+        // none, the first media segment
+        let deferred_segments = self.deferred_segments.as_ref();
+        if deferred_segments.is_some_and(|d| d.iter().any(|(mt, _)| *mt == media_type)) {
+            return;
+        }
         if !self.requester.has_segment_request_pending(media_type) {
             let inventory = self.media_element_ref.inventory(media_type);
             if let Some(seg_info) = pl_store.curr_media_playlist_segment_info(media_type) {
@@ -827,11 +1538,14 @@ impl Dispatcher {
                         media_type,
                         i.url().clone(),
                         i.byte_range(),
+                        i.key(),
                         seg_info.1,
                     );
                 } else if let Some(seg) = most_needed_segment.media_segment() {
-                    self.requester
-                        .request_media_segment(media_type, seg, seg_info.1);
+                    if deferred_segments.is_none() || !seg_info.0.has_init_segments() {
+                        self.requester
+                            .request_media_segment(media_type, seg, seg_info.1);
+                    }
                 }
             }
         }
@@ -861,6 +1575,10 @@ impl Dispatcher {
         abort_prev: bool,
         flush: bool,
     ) {
+        if let Some(deferred) = self.deferred_segments.as_mut() {
+            // Segments of the previous Media Playlists cannot be relied on anymore
+            deferred.retain(|(mt, _)| !changed_media_types.contains(mt));
+        }
         if let Some(pl_store) = self.playlist_store.as_mut() {
             changed_media_types.iter().for_each(|mt| {
                 let mt = *mt;
@@ -892,9 +1610,9 @@ impl Dispatcher {
                     }
                 }
             });
-            if !changed_media_types.is_empty() {
-                self.clean_up_playlist_refresh_timers();
-            }
+            // The subtitles Media Playlist may have changed even if no other did
+            self.clean_up_playlist_refresh_timers();
+            self.load_text_media_playlist();
             self.check_segments_to_request();
         }
     }
@@ -924,11 +1642,88 @@ impl Dispatcher {
             .add_metric(duration_ms, resource_size);
 
         let media_type = segment_req.media_type();
+        let result = match segment_req.key() {
+            Some(key) if key.is_decrypted_by_player() => {
+                let decrypted = match segment_req.key_data() {
+                    None => Err("The segment's decryption key was not loaded.".to_owned()),
+                    Some(key_data) => decrypt_aes_128_cbc(&result.obtain(), key_data, key.iv())
+                        .map_err(|e| e.to_string()),
+                };
+                match decrypted {
+                    Ok(decrypted) => JsMemoryBlob::from_resource_id(jsStoreResource(&decrypted)),
+                    Err(message) => {
+                        jsSendSegmentParsingError(
+                            !self.is_playing_interstitial(),
+                            SegmentParsingErrorCode::DecryptionError,
+                            media_type,
+                            &message,
+                        );
+                        self.abort_current_content();
+                        return;
+                    }
+                }
+            }
+            Some(key) => {
+                Logger::warn(&format!(
+                    "Core: {:?} encryption with \"{}\" key format cannot be decrypted by the \
+                    player, pushing the segment as is.",
+                    key.method(),
+                    key.key_format()
+                ));
+                result
+            }
+            None => result,
+        };
+
+        // Closed captions are extracted from the video data before it is pushed
+        let has_closed_captions = media_type == MediaType::Video
+            && self
+                .playlist_store
+                .as_ref()
+                .is_some_and(|p| p.has_closed_captions());
+        let (result, closed_captions_data) = if has_closed_captions {
+            let data = result.obtain();
+            let blob = JsMemoryBlob::from_resource_id(jsStoreResource(&data));
+            (blob, Some(data))
+        } else {
+            (result, None)
+        };
+
         let (_, _, time_info, context) = segment_req.deconstruct();
         if let Some(time_info) = time_info {
+            let discontinuity = time_info.discontinuity();
             self.on_media_segment_loaded(result, media_type, time_info, context);
+            if let Some(data) = closed_captions_data {
+                self.on_closed_captions_media_segment(&data, discontinuity);
+            }
         } else {
             self.on_init_segment_loaded(result, media_type);
+            if let Some(data) = closed_captions_data {
+                self.on_closed_captions_init_segment(&data);
+            }
+        }
+    }
+
+    /// Method called once a decryption key request ended with success
+    fn on_key_fetch_success(&mut self, key_info: KeyRequestInfo, data: Vec<u8>) {
+        match <[u8; 16]>::try_from(data.as_slice()) {
+            Ok(key) => {
+                Logger::info(&format!("Core: Loaded key u:{}", key_info.url()));
+                self.requester.add_key(key_info.url().clone(), key);
+            }
+            Err(_) => {
+                jsSendSegmentParsingError(
+                    !self.is_playing_interstitial(),
+                    SegmentParsingErrorCode::DecryptionError,
+                    key_info.media_type(),
+                    &format!(
+                        "The key loaded from {} is {} bytes long instead of the expected 16.",
+                        key_info.url(),
+                        data.len()
+                    ),
+                );
+                self.abort_current_content();
+            }
         }
     }
 
@@ -939,8 +1734,16 @@ impl Dispatcher {
         time_info: SegmentTimeInfo,
         context: SegmentQualityContext,
     ) {
-        let segment_start = time_info.start();
-        let segment_end = time_info.end();
+        if let Some(deferred) = self.deferred_segments.as_mut() {
+            if !deferred.iter().any(|(mt, _)| *mt == media_type) {
+                let segment = DeferredSegment::Media(data.obtain(), time_info, context);
+                deferred.push((media_type, segment));
+                self.check_deferred_source_buffers();
+                self.check_segments_to_request();
+                return;
+            }
+        }
+        let validated_time_info = time_info.clone();
         let prepared_data = self
             .media_element_ref
             .announce_incoming_media_segment(media_type, data, time_info, context);
@@ -953,7 +1756,7 @@ impl Dispatcher {
         self.check_best_variant();
         self.segment_selectors
             .get_mut(media_type)
-            .validate_media_until(segment_end);
+            .validate_media(&validated_time_info);
         self.check_segments_to_request();
 
         match self
@@ -963,11 +1766,20 @@ impl Dispatcher {
             Err(x) => {
                 let media_type = x.media_type();
                 let message = x.to_string();
-                jsSendSegmentParsingError(true, x.into(), media_type, &message);
-                self.stop_current_content();
+                jsSendSegmentParsingError(
+                    !self.is_playing_interstitial(),
+                    x.into(),
+                    media_type,
+                    &message,
+                );
+                self.abort_current_content();
             }
             Ok(()) => {
-                if was_last_segment(self.playlist_store.as_ref(), media_type, segment_start) {
+                if was_last_segment(
+                    self.playlist_store.as_ref(),
+                    media_type,
+                    &validated_time_info,
+                ) {
                     Logger::info(&format!(
                         "Last {} segment request finished, declaring its buffer's end",
                         media_type
@@ -979,12 +1791,24 @@ impl Dispatcher {
     }
 
     fn on_init_segment_loaded(&mut self, data: JsMemoryBlob, media_type: MediaType) {
+        if let Some(deferred) = self.deferred_segments.as_mut() {
+            deferred.retain(|(mt, _)| *mt != media_type);
+            deferred.push((media_type, DeferredSegment::Init(data.obtain())));
+            self.check_deferred_source_buffers();
+            self.check_segments_to_request();
+            return;
+        }
         match self.media_element_ref.push_init_segment(media_type, data) {
             Err(x) => {
                 let media_type = x.media_type();
                 let message = x.to_string();
-                jsSendSegmentParsingError(true, x.into(), media_type, &message);
-                self.stop_current_content();
+                jsSendSegmentParsingError(
+                    !self.is_playing_interstitial(),
+                    x.into(),
+                    media_type,
+                    &message,
+                );
+                self.abort_current_content();
             }
             Ok(()) => self.segment_selectors.get_mut(media_type).validate_init(),
         }
@@ -998,7 +1822,9 @@ impl Dispatcher {
     fn clean_up_playlist_refresh_timers(&mut self) {
         if let Some(ref pl_store) = self.playlist_store {
             self.playlist_refresh_timers.retain(|x| {
-                if let PlaylistFileType::MediaPlaylist { id, .. } = &x.1 {
+                if let PlaylistFileType::MediaPlaylist { id, .. }
+                | PlaylistFileType::TextMediaPlaylist { id } = &x.1
+                {
                     if !pl_store.is_curr_media_playlist(id) {
                         jsClearTimer(x.0);
                         return false;
@@ -1014,10 +1840,26 @@ impl Dispatcher {
     }
 }
 
+/// Send the `DateRangeEvent` `event` for `date_range` to JavaScript.
+fn announce_date_range_event(event: DateRangeEvent, date_range: &DateRange) {
+    // SAFETY: The returned value may contain raw pointers to `date_range`'s data, which is only
+    // dropped after the synchronous call to the JS function, which copies it.
+    let attributes = unsafe { format_date_range_attributes_for_js(date_range) };
+    jsAnnounceDateRangeEvent(
+        event,
+        date_range.id(),
+        date_range.start(),
+        date_range.end(),
+        date_range.duration(),
+        date_range.planned_duration(),
+        attributes,
+    );
+}
+
 fn was_last_segment(
     playlist_store: Option<&PlaylistStore>,
     media_type: MediaType,
-    seg_start: f64,
+    time_info: &SegmentTimeInfo,
 ) -> bool {
     playlist_store
         .and_then(|c| c.curr_media_playlist(media_type))
@@ -1027,8 +1869,27 @@ fn was_last_segment(
                     .segment_list()
                     .media()
                     .last()
-                    .map(|x| x.start() == seg_start)
+                    .map(|x| match time_info.part_index() {
+                        None => x.start() == time_info.start(),
+                        // For partial segments, check that this is the last part of the last
+                        // segment
+                        Some(idx) => {
+                            x.media_sequence() == time_info.media_sequence()
+                                && pl
+                                    .segment_list()
+                                    .parts_for(x.media_sequence())
+                                    .last()
+                                    .and_then(|p| p.time_info().part_index())
+                                    == Some(idx)
+                        }
+                    })
                     .unwrap_or(false)
         })
         .unwrap_or(false)
 }
+
+/// Returns `true` if the formats of the given Media Playlist can be read from its segments:
+/// either from its initialization segments or, for MPEG-2 TS, from its first media segment.
+fn has_readable_codecs(media_playlist: &MediaPlaylist) -> bool {
+    media_playlist.segment_list().has_init_segments() || media_playlist.has_mpeg_ts_segments()
+}
@@ -0,0 +1,72 @@
+use super::super::Dispatcher;
+use crate::{
+    bindings::{jsAnnounceSessionData, jsSendOtherError, OtherErrorCode},
+    parser::{SessionData, SessionDataContent},
+    playlist_store::PlaylistStore,
+    requester::PlaylistFileType,
+    Logger,
+};
+
+impl Dispatcher {
+    /// Announce the data of the `EXT-X-SESSION-DATA` tag with the given `DATA-ID`, preferably
+    /// with the given `LANGUAGE`, fetching it first if it is only available through an `URI`.
+    pub(crate) fn load_session_data_core(&mut self, data_id: &str, language: Option<&str>) {
+        let playlist_store = if let Some(playlist_store) = self.primary_playlist_store() {
+            playlist_store
+        } else {
+            Logger::warn("Core: Cannot load session data: no Multivariant Playlist loaded");
+            return;
+        };
+        let found = SessionData::find(playlist_store.session_data(), data_id, language);
+        let (index, url) = match found {
+            None => {
+                jsSendOtherError(
+                    false,
+                    OtherErrorCode::UnfoundSessionData,
+                    &format!("No EXT-X-SESSION-DATA found with the DATA-ID \"{data_id}\""),
+                );
+                return;
+            }
+            Some((index, data)) => match data.content() {
+                SessionDataContent::Value(value) => {
+                    announce_session_data(data, value.as_bytes());
+                    return;
+                }
+                SessionDataContent::Uri { url, .. } => (index, url.clone()),
+            },
+        };
+        Logger::info(&format!("Core: Loading session data \"{data_id}\""));
+        self.requester
+            .fetch_playlist(url, PlaylistFileType::SessionData { index });
+    }
+
+    /// Method called once the data of the `EXT-X-SESSION-DATA` tag at the given `index` in the
+    /// Multivariant Playlist was loaded with success.
+    pub(super) fn on_session_data_loaded(&mut self, index: usize, data: Vec<u8>) {
+        let session_data = self
+            .primary_playlist_store()
+            .and_then(|p| p.session_data().get(index));
+        if let Some(session_data) = session_data {
+            announce_session_data(session_data, &data);
+        }
+    }
+
+    /// Returns the `PlaylistStore` of the primary content, even when an HLS Interstitial is
+    /// currently played in its place.
+    fn primary_playlist_store(&self) -> Option<&PlaylistStore> {
+        match self.interstitial.as_ref() {
+            Some(interstitial) => Some(&interstitial.primary_playlist_store),
+            None => self.playlist_store.as_ref(),
+        }
+    }
+}
+
+/// Communicate the given `data` of `session_data` to JavaScript.
+fn announce_session_data(session_data: &SessionData, data: &[u8]) {
+    jsAnnounceSessionData(
+        session_data.data_id(),
+        session_data.language().map(|l| l.to_owned()),
+        session_data.data_type(),
+        data,
+    );
+}
@@ -0,0 +1,355 @@
+use super::super::{Dispatcher, JsMemoryBlob};
+use crate::{
+    bindings::{
+        formatters::format_text_cue_settings_for_js, jsAddTextCue, jsAnnounceTextTrackUpdate,
+        jsClearTextCues, jsSendOtherError, OtherErrorCode,
+    },
+    parser::{MediaPlaylistParsingError, MediaPlaylistUpdateError, SegmentTimeInfo},
+    playlist_store::MediaPlaylistPermanentId,
+    requester::{PlaylistFileType, TextSegmentRequestInfo},
+    subtitles::{StppSegment, TextCue, WebVttSegment},
+    utils::{isobmff, url::Url},
+    Logger,
+};
+
+impl Dispatcher {
+    /// Select the text track whose `id` is given in argument, or disable text tracks if `None`
+    /// is given instead.
+    pub(crate) fn set_text_track_core(&mut self, track_id: Option<u32>) {
+        let has_changed = if let Some(pl_store) = self.playlist_store.as_mut() {
+            let has_changed = pl_store.set_text_track(track_id);
+            jsAnnounceTextTrackUpdate(pl_store.selected_text_track_id());
+            has_changed
+        } else {
+            return;
+        };
+        if has_changed {
+            Logger::info("Core: Text MediaPlaylist changed");
+            jsClearTextCues();
+            self.text_timescale = None;
+            self.requester.abort_text_segments();
+            self.segment_selectors
+                .text_mut()
+                .restart_from_position(self.media_element_ref.wanted_position() - 0.2);
+            self.clean_up_playlist_refresh_timers();
+            self.load_text_media_playlist();
+            self.replay_closed_captions();
+            self.check_segments_to_request();
+        }
+    }
+
+    /// Request the subtitles Media Playlist currently chosen if it is neither already loaded nor
+    /// already being requested.
+    pub(super) fn load_text_media_playlist(&mut self) {
+        let pl_store = if let Some(pl_store) = self.playlist_store.as_ref() {
+            pl_store
+        } else {
+            return;
+        };
+        if pl_store.curr_text_media_playlist().is_some() {
+            return;
+        }
+        if let Some(id) = pl_store.curr_text_media_playlist_id() {
+            let playlist_type = PlaylistFileType::TextMediaPlaylist { id: id.clone() };
+            if self.requester.is_requesting_playlist(&playlist_type) {
+                return;
+            }
+            if let Some(url) = pl_store.media_playlist_url(id) {
+                Logger::debug("Core: Requesting text media playlist");
+                self.requester.fetch_playlist(url.clone(), playlist_type);
+            }
+        }
+    }
+
+    /// Method called once a subtitles Media Playlist was loaded with success, with its id,
+    /// response data and url as argument.
+    pub(super) fn on_text_media_playlist_loaded(
+        &mut self,
+        playlist_id: MediaPlaylistPermanentId,
+        data: Vec<u8>,
+        playlist_url: Url,
+    ) {
+        Logger::info(&format!(
+            "Text media playlist loaded successfully: {}",
+            playlist_url.get_ref()
+        ));
+        let playlist_store = if let Some(playlist_store) = self.playlist_store.as_mut() {
+            playlist_store
+        } else {
+            return;
+        };
+        match playlist_store.update_media_playlist(&playlist_id, data.as_ref(), playlist_url) {
+            Err(MediaPlaylistUpdateError::ParsingError(
+                MediaPlaylistParsingError::InvalidDeltaUpdate,
+            )) => {
+                Logger::warn("Core: Impossible to apply Playlist Delta Update, reloading it");
+                if let Some(url) = playlist_store.media_playlist_url(&playlist_id) {
+                    self.requester.fetch_playlist(
+                        url.clone(),
+                        PlaylistFileType::TextMediaPlaylist { id: playlist_id },
+                    );
+                }
+            }
+            Err(e) => {
+                // Subtitles are not needed for playback, no need to stop it
+                jsSendOtherError(false, OtherErrorCode::TextTrackLoadingError, &e.to_string());
+            }
+            Ok(p) => {
+                let next_refresh = p.next_refresh();
+                self.schedule_media_playlist_refresh(
+                    PlaylistFileType::TextMediaPlaylist { id: playlist_id },
+                    next_refresh,
+                );
+                self.check_segments_to_request();
+            }
+        }
+    }
+
+    /// Method called once a subtitles Media Playlist could not be loaded.
+    ///
+    /// As subtitles are not needed for playback, this only leads to a non-fatal error.
+    pub(super) fn on_text_media_playlist_request_failed(&mut self, url: &Url) {
+        jsSendOtherError(
+            false,
+            OtherErrorCode::TextTrackLoadingError,
+            &format!("Could not load the text media playlist from {url}"),
+        );
+    }
+
+    /// Request the next subtitles segment that should be loaded, if one and if no other
+    /// subtitles segment request is pending.
+    pub(super) fn check_text_segment_to_request(&mut self) {
+        if self.ready_state.is_loading() || self.requester.has_text_segment_request_pending() {
+            return;
+        }
+        let pl_store = if let Some(playlist_store) = self.playlist_store.as_ref() {
+            playlist_store
+        } else {
+            return;
+        };
+        if let Some((segment_list, context)) = pl_store.curr_text_media_playlist_segment_info() {
+            // Subtitles are not pushed to a `SourceBuffer`, they have no inventory
+            let most_needed_segment =
+                self.segment_selectors
+                    .text_mut()
+                    .most_needed_segment(segment_list, &context, &[]);
+            if let Some(i) = most_needed_segment.init_segment() {
+                self.requester
+                    .request_text_segment(i.url(), i.byte_range(), None, context);
+            } else if let Some(seg) = most_needed_segment.media_segment() {
+                let discontinuity = seg.time_info().discontinuity();
+                if self
+                    .media_element_ref
+                    .playlist_time_offset_for(discontinuity)
+                    .is_none()
+                {
+                    // Cues can only be placed once the timestamps of the audio and video
+                    // segments of the same discontinuity domain are known
+                    return;
+                }
+                Logger::info(&format!(
+                    "Core: Asking to request text segment: t: {}, d: {}",
+                    seg.start(),
+                    seg.duration()
+                ));
+                self.requester.request_text_segment(
+                    seg.url(),
+                    seg.byte_range(),
+                    Some(seg.time_info().clone()),
+                    context,
+                );
+            }
+        }
+    }
+
+    /// Method called once a subtitles segment request ended with success
+    pub(super) fn on_text_segment_fetch_success(
+        &mut self,
+        segment_req: TextSegmentRequestInfo,
+        data: JsMemoryBlob,
+    ) {
+        match segment_req.time_info() {
+            None => {
+                Logger::info("Core: Loaded text init segment");
+                self.text_timescale = isobmff::read_track_timescale(&data.obtain());
+                if self.text_timescale.is_none() {
+                    jsSendOtherError(
+                        false,
+                        OtherErrorCode::TextTrackLoadingError,
+                        "Could not read the timescale of the text init segment",
+                    );
+                }
+                self.segment_selectors.text_mut().validate_init();
+            }
+            Some(time_info) => {
+                Logger::info(&format!(
+                    "Core: Loaded text segment: t: {}, d: {}",
+                    time_info.start(),
+                    time_info.duration()
+                ));
+                self.add_text_cues(time_info, &data.obtain());
+                self.segment_selectors.text_mut().validate_media(time_info);
+            }
+        }
+        self.check_segments_to_request();
+    }
+
+    /// Parse the subtitles segment whose data and time information are given in argument and
+    /// communicate its cues, converted into playlist time, to JavaScript.
+    ///
+    /// Segments are considered to be fragmented MP4 segments carrying IMSC1 subtitles if the
+    /// track has an initialization segment, and WebVTT segments if not.
+    fn add_text_cues(&self, time_info: &SegmentTimeInfo, data: &[u8]) {
+        let discontinuity = time_info.discontinuity();
+        let playlist_offset = if let Some(offset) = self
+            .media_element_ref
+            .playlist_time_offset_for(discontinuity)
+        {
+            offset
+        } else {
+            Logger::warn(&format!(
+                "Core: Cannot place text cues of unknown discontinuity {}",
+                discontinuity
+            ));
+            return;
+        };
+        let parsed = parse_text_segment(
+            data,
+            self.text_timescale,
+            time_info.start(),
+            playlist_offset,
+        );
+        match parsed {
+            Err(e) => jsSendOtherError(false, OtherErrorCode::TextTrackLoadingError, &e),
+            Ok((cues, offset)) => cues.iter().for_each(|cue| send_text_cue(cue, offset)),
+        }
+    }
+
+    /// Method called once a subtitles segment could not be loaded.
+    ///
+    /// As subtitles are not needed for playback, this only leads to a non-fatal error and that
+    /// segment is skipped.
+    pub(super) fn on_text_segment_request_failed(&mut self, segment_req: TextSegmentRequestInfo) {
+        jsSendOtherError(
+            false,
+            OtherErrorCode::TextTrackLoadingError,
+            &format!("Could not load the text segment at {}", segment_req.url()),
+        );
+        let selector = self.segment_selectors.text_mut();
+        match segment_req.time_info() {
+            None => selector.validate_init(),
+            Some(time_info) => selector.validate_media(time_info),
+        }
+        self.check_segments_to_request();
+    }
+}
+
+/// Parse the given subtitles segment, starting at `segment_start` in playlist time, and return
+/// its cues as well as the offset to add to their times to convert them into playlist time.
+///
+/// `timescale` is the one of the text initialization segment for IMSC1 subtitles, or `None`
+/// for WebVTT ones. `playlist_offset` converts times of the audio and video segments of the
+/// same discontinuity domain into playlist time.
+fn parse_text_segment(
+    data: &[u8],
+    timescale: Option<u32>,
+    segment_start: f64,
+    playlist_offset: f64,
+) -> Result<(Vec<TextCue>, f64), String> {
+    if let Some(timescale) = timescale {
+        // IMSC1 cues are already expressed in the same timeline than audio and video
+        StppSegment::parse(data, timescale)
+            .map(|segment| (segment.cues().to_vec(), playlist_offset))
+            .map_err(|e| e.to_string())
+    } else {
+        WebVttSegment::parse(data)
+            .map(|segment| {
+                let expected_media_start = segment_start - playlist_offset;
+                let offset = segment.media_time_offset(expected_media_start);
+                (segment.cues().to_vec(), offset + playlist_offset)
+            })
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Communicate to JavaScript the given cue, whose times are converted into playlist time by
+/// adding `offset` to them.
+pub(super) fn send_text_cue(cue: &TextCue, offset: f64) {
+    jsAddTextCue(
+        cue.id().map(|id| id.to_owned()),
+        cue.start() + offset,
+        cue.end() + offset,
+        cue.text(),
+        format_text_cue_settings_for_js(cue),
+        cue.region().map(|r| r.id.clone()),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::isobmff::mp4_box;
+
+    /// Returns the start and end, in playlist time, of the cues of the given segment.
+    fn cue_times(
+        data: &[u8],
+        timescale: Option<u32>,
+        segment_start: f64,
+        playlist_offset: f64,
+    ) -> Vec<(f64, f64)> {
+        let (cues, offset) =
+            parse_text_segment(data, timescale, segment_start, playlist_offset).unwrap();
+        cues.iter()
+            .map(|c| (c.start() + offset, c.end() + offset))
+            .collect()
+    }
+
+    #[test]
+    fn test_webvtt_cue_times() {
+        // Audio and video segments start at the media time 10 (MPEG-2 timestamp 900000) when
+        // the playlist time is 0
+        let playlist_offset = -10.;
+        let data = b"WEBVTT\nX-TIMESTAMP-MAP=MPEGTS:900000,LOCAL:00:00:00.000\n\n\
+            00:00:04.000 --> 00:00:06.000\nA";
+        assert_eq!(cue_times(data, None, 4., playlist_offset), vec![(4., 6.)]);
+
+        // Cues relative to the segment's own MPEG-2 timestamp
+        let data = b"WEBVTT\nX-TIMESTAMP-MAP=MPEGTS:1260000,LOCAL:00:00:00.000\n\n\
+            00:00:00.000 --> 00:00:02.000\nB";
+        assert_eq!(cue_times(data, None, 4., playlist_offset), vec![(4., 6.)]);
+
+        // MPEG-2 timestamps of audio and video wrapped around before that segment
+        let playlist_offset = -(8589934592. / 90000. + 10.);
+        assert_eq!(cue_times(data, None, 4., playlist_offset), vec![(4., 6.)]);
+
+        assert!(parse_text_segment(b"not WebVTT", None, 0., 0.).is_err());
+    }
+
+    #[test]
+    fn test_imsc1_cue_times() {
+        // tfhd with default-base-is-moof, tfdt at 14s and a single sample of 2s
+        let tfhd = [&[0, 0x02, 0, 0][..], &1u32.to_be_bytes()].concat();
+        let tfdt = [&[0, 0, 0, 0][..], &14_000u32.to_be_bytes()].concat();
+        let trun = [
+            &[0, 0, 0x01, 0][..],
+            &1u32.to_be_bytes(),
+            &2000u32.to_be_bytes(),
+        ]
+        .concat();
+        let traf = [
+            mp4_box(b"tfhd", &tfhd),
+            mp4_box(b"tfdt", &tfdt),
+            mp4_box(b"trun", &trun),
+        ]
+        .concat();
+        let document = r#"<tt xmlns="http://www.w3.org/ns/ttml"><body>
+            <p begin="00:00:14.500" end="00:00:15.500">A</p>
+            </body></tt>"#;
+        let data = [
+            mp4_box(b"moof", &mp4_box(b"traf", &traf)),
+            mp4_box(b"mdat", document.as_bytes()),
+        ]
+        .concat();
+        assert_eq!(cue_times(&data, Some(1000), 4., -10.), vec![(4.5, 5.5)]);
+    }
+}
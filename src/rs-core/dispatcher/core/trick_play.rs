@@ -0,0 +1,178 @@
+use super::super::Dispatcher;
+use crate::{
+    bindings::{jsClearTimer, jsTimer, MediaType, TimerId, TimerReason},
+    Logger,
+};
+
+/// Minimum absolute wanted speed from which trick play is enabled, if the content has I-frame-only
+/// variants.
+const TRICK_PLAY_MIN_SPEED: f64 = 4.;
+
+/// Interval, in milliseconds, between two seeks performed when trick play is enabled due to a
+/// high wanted speed.
+const TRICK_PLAY_STEP_INTERVAL: f64 = 500.;
+
+impl Dispatcher {
+    /// Update whether the user is currently scrubbing and check if trick play should be enabled
+    /// or disabled as a consequence.
+    pub(crate) fn set_scrubbing_core(&mut self, is_scrubbing: bool) {
+        self.trick_play.is_scrubbing = is_scrubbing;
+        self.check_trick_play();
+    }
+
+    /// Check if trick play should be enabled or disabled according to the current wanted speed
+    /// and scrubbing status, and handle the consequences if it changed.
+    pub(crate) fn check_trick_play(&mut self) {
+        if self.is_playing_interstitial() {
+            return;
+        }
+        let playlist_store = if let Some(playlist_store) = self.playlist_store.as_mut() {
+            playlist_store
+        } else {
+            return;
+        };
+        let speed = self.media_element_ref.wanted_speed();
+        let was_enabled = playlist_store.is_trick_play_enabled();
+        let enabled = if self.trick_play.is_scrubbing || f64::abs(speed) >= TRICK_PLAY_MIN_SPEED {
+            playlist_store.enable_trick_play()
+        } else {
+            playlist_store.disable_trick_play();
+            false
+        };
+        self.media_element_ref.set_trick_play(enabled);
+        if enabled != was_enabled {
+            self.trick_play.target_position = None;
+            let has_video_buffer = self.media_element_ref.has_buffer(MediaType::Video);
+            self.handle_media_playlist_update(&[MediaType::Video], true, has_video_buffer);
+        }
+        self.schedule_trick_play_step();
+    }
+
+    /// Method called once a timer started for a trick play step has ended.
+    pub(crate) fn on_trick_play_timer_ended(&mut self, id: TimerId) {
+        if self.trick_play.step_timer != Some(id) {
+            return;
+        }
+        self.trick_play.step_timer = None;
+        self.perform_trick_play_step();
+        self.schedule_trick_play_step();
+    }
+
+    /// Disable trick play and clear its state, without loading back regular variants.
+    ///
+    /// To call when the current content is stopped or reloaded.
+    pub(super) fn stop_trick_play(&mut self) {
+        if let Some(timer_id) = self.trick_play.step_timer.take() {
+            jsClearTimer(timer_id);
+        }
+        if let Some(playlist_store) = self.playlist_store.as_mut() {
+            playlist_store.disable_trick_play();
+        }
+        self.media_element_ref.set_trick_play(false);
+        self.trick_play.target_position = None;
+    }
+
+    /// Start the timer for the next trick play step if trick play is enabled due to the wanted
+    /// speed, or clear it if it is not needed anymore.
+    fn schedule_trick_play_step(&mut self) {
+        let is_enabled = self
+            .playlist_store
+            .as_ref()
+            .is_some_and(|p| p.is_trick_play_enabled());
+        let needs_step = is_enabled
+            && !self.trick_play.is_scrubbing
+            && self.media_element_ref.wanted_speed() != 0.;
+        match (needs_step, self.trick_play.step_timer) {
+            (true, None) => {
+                self.trick_play.step_timer = Some(jsTimer(
+                    TRICK_PLAY_STEP_INTERVAL,
+                    TimerReason::TrickPlayStep,
+                ));
+            }
+            (false, Some(timer_id)) => {
+                jsClearTimer(timer_id);
+                self.trick_play.step_timer = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Move the position according to the wanted speed and the time elapsed since the last step,
+    /// snapping it to the closest segment boundary so an I-frame can be displayed.
+    fn perform_trick_play_step(&mut self) {
+        let playlist_store = if let Some(playlist_store) = self.playlist_store.as_ref() {
+            playlist_store
+        } else {
+            return;
+        };
+        let position = self.media_element_ref.wanted_position();
+        let step = self.media_element_ref.wanted_speed() * TRICK_PLAY_STEP_INTERVAL / 1000.;
+        let target = next_trick_play_target(
+            self.trick_play.target_position,
+            position,
+            step,
+            playlist_store.segment_target_duration().unwrap_or(0.),
+            (
+                playlist_store.curr_min_position(),
+                playlist_store.curr_max_position(),
+            ),
+        );
+        self.trick_play.target_position = Some(target);
+        let snapped = playlist_store
+            .closest_segment_boundary(target)
+            .unwrap_or(target);
+        if snapped != position {
+            Logger::debug(&format!("Core: Trick play step to {snapped}"));
+            self.media_element_ref.seek(snapped);
+        }
+    }
+}
+
+/// Compute the position targeted by the next trick play step, moving by `step` seconds from
+/// `prev_target`, the one of the previous step, or from `position` if it changed since.
+///
+/// `segment_duration` is the usual duration of segments, by which the position may have been
+/// moved when snapped to a segment boundary. The result is kept within the given minimum and
+/// maximum positions.
+fn next_trick_play_target(
+    prev_target: Option<f64>,
+    position: f64,
+    step: f64,
+    segment_duration: f64,
+    (min_position, max_position): (Option<f64>, Option<f64>),
+) -> f64 {
+    let max_drift = f64::abs(step) + segment_duration;
+    let base = match prev_target {
+        // The position moved by other means (e.g. a seek from the user), begin from it
+        Some(target) if f64::abs(target - position) <= max_drift => target,
+        _ => position,
+    };
+    let mut target = base + step;
+    if let Some(min_position) = min_position {
+        target = f64::max(target, min_position);
+    }
+    if let Some(max_position) = max_position {
+        target = f64::min(target, max_position);
+    }
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_trick_play_target() {
+        let bounds = (Some(0.), Some(100.));
+        assert_eq!(next_trick_play_target(None, 10., 4., 6., bounds), 14.);
+        // Steps accumulate on the previous target, even if the position was snapped elsewhere
+        assert_eq!(next_trick_play_target(Some(14.), 12., 4., 6., bounds), 18.);
+        assert_eq!(next_trick_play_target(Some(14.), 18., -4., 6., bounds), 10.);
+        // A position far from the previous target means that it was moved by other means
+        assert_eq!(next_trick_play_target(Some(14.), 50., 4., 6., bounds), 54.);
+        // The target stays within the playable range
+        assert_eq!(next_trick_play_target(Some(98.), 96., 4., 6., bounds), 100.);
+        assert_eq!(next_trick_play_target(Some(2.), 0., -4., 6., bounds), 0.);
+        assert_eq!(next_trick_play_target(None, 2., -4., 6., (None, None)), -2.);
+    }
+}
@@ -177,6 +177,7 @@ impl Dispatcher {
         match reason {
             TimerReason::MediaPlaylistRefresh => self.on_playlist_refresh_timer_ended(id),
             TimerReason::RetryRequest => self.on_retry_request(id),
+            TimerReason::TrickPlayStep => self.on_trick_play_timer_ended(id),
         }
     }
 
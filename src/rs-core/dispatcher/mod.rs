@@ -1,10 +1,16 @@
 use crate::{
     adaptive::AdaptiveQualitySelector,
-    bindings::TimerId,
-    media_element::MediaElementReference,
+    bindings::{MediaType, TimerId},
+    media_element::{MediaElementReference, SegmentQualityContext},
+    parser::{
+        AudioSelectionPreferences, DateRange, InstreamId, Interstitial,
+        OutputProtectionCapabilities, SegmentTimeInfo, VideoDynamicRange,
+    },
     playlist_store::PlaylistStore,
     requester::{PlaylistFileType, Requester},
     segment_selector::NextSegmentSelectors,
+    subtitles::{ClosedCaptionsDecoder, TextCue},
+    utils::url::Url,
     wasm_bindgen,
 };
 
@@ -48,6 +54,17 @@ pub struct Dispatcher {
     /// This can for example be used to limit memory and network bandwidth usage.
     buffer_goal: f64,
 
+    /// Preferences and capabilities considered when audio renditions are automatically chosen,
+    /// that is when no audio track is explicitely selected.
+    audio_preferences: AudioSelectionPreferences,
+
+    /// Output protection the device is able to enforce, restricting which variants can be
+    /// played.
+    output_protection: OutputProtectionCapabilities,
+
+    /// Video dynamic ranges supported by the display, restricting which variants can be played.
+    supported_video_ranges: Vec<VideoDynamicRange>,
+
     /// The last known current position stored.
     /// Changes periodically and immediately on various time-changing events (such as seeks, stops
     /// etc.)
@@ -56,6 +73,121 @@ pub struct Dispatcher {
     segment_selectors: NextSegmentSelectors,
 
     playlist_refresh_timers: Vec<(TimerId, PlaylistFileType)>,
+
+    /// Date ranges, announced through `EXT-X-DATERANGE` tags, in which the current position
+    /// currently is.
+    ///
+    /// Kept to only announce once when we enter and leave each of them.
+    active_date_ranges: Vec<DateRange>,
+
+    /// Set while an HLS Interstitial is being played in place of the primary content.
+    interstitial: Option<InterstitialPlayback>,
+
+    /// `ID` of the HLS Interstitials of the primary content which have already been played (or
+    /// abandoned), so they are not played again.
+    played_interstitials: Vec<String>,
+
+    /// State linked to trick play, where only I-frames are displayed as the position is moved
+    /// through seeks.
+    trick_play: TrickPlayState,
+
+    /// Timescale of the current text track's fragmented MP4 segments, as read from its
+    /// initialization segment.
+    ///
+    /// `None` if that track has no initialization segment (e.g. WebVTT subtitles) or if it has
+    /// not been loaded yet.
+    text_timescale: Option<u32>,
+
+    /// Extracts the closed captions carried in the video segments of the current variant.
+    closed_captions_decoder: ClosedCaptionsDecoder,
+
+    /// Closed captions cues decoded until now, for all channels, with the offset converting
+    /// their times into playlist time.
+    ///
+    /// They are kept so that they can be displayed when a closed captions track is chosen.
+    closed_captions_cues: Vec<(InstreamId, f64, TextCue)>,
+
+    /// Segments loaded while the creation of `SourceBuffer`s is deferred, because the formats of
+    /// the current variant are not announced and first have to be read from them.
+    ///
+    /// `None` if the creation of `SourceBuffer`s is not deferred.
+    deferred_segments: Option<Vec<(MediaType, DeferredSegment)>>,
+
+    /// State of the reload of the Multivariant Playlist performed when Media Playlists cannot be
+    /// loaded anymore.
+    multivariant_reload: MultivariantReloadState,
+}
+
+/// Segment loaded while the creation of `SourceBuffer`s is deferred, from which the formats of
+/// the current variant are read.
+enum DeferredSegment {
+    /// An initialization segment, for Media Playlists which have some.
+    Init(Vec<u8>),
+    /// The first media segment of a Media Playlist without initialization segment, which should
+    /// then be MPEG-2 TS, with its metadata.
+    Media(Vec<u8>, SegmentTimeInfo, SegmentQualityContext),
+}
+
+/// State of the reload of the Multivariant Playlist performed when a Media Playlist cannot be
+/// loaded anymore, as its URL may have changed since the Multivariant Playlist was loaded (e.g.
+/// because of an expired token).
+#[derive(Default)]
+enum MultivariantReloadState {
+    /// The Multivariant Playlist has not been reloaded since the last Media Playlist loaded with
+    /// success.
+    #[default]
+    NotReloaded,
+
+    /// The Multivariant Playlist is being reloaded, because the Media Playlists of those
+    /// `MediaType`s could not be loaded. They are requested again once it has been reloaded.
+    Reloading(Vec<MediaType>),
+
+    /// The Multivariant Playlist has been reloaded and no Media Playlist has been loaded with
+    /// success since, it should not be reloaded again.
+    Reloaded,
+}
+
+/// State kept to perform trick play, which is enabled either when scrubbing or when the wanted
+/// speed is too high for regular playback.
+#[derive(Default)]
+struct TrickPlayState {
+    /// If `true`, the user is currently scrubbing: the position is moved through seeks performed
+    /// by the application.
+    is_scrubbing: bool,
+
+    /// Timer for the next seek moving the position forward (or backward) according to the wanted
+    /// speed.
+    step_timer: Option<TimerId>,
+
+    /// Position, in playlist time, targeted by the last trick play step.
+    ///
+    /// Kept because the position seeked to is snapped to segment boundaries, which would
+    /// otherwise prevent from progressing for small steps.
+    target_position: Option<f64>,
+}
+
+/// State kept while the asset(s) of an HLS Interstitial are played in place of the primary
+/// content.
+struct InterstitialPlayback {
+    /// The interstitial currently played.
+    interstitial: Interstitial,
+
+    /// The `PlaylistStore` of the primary content, put aside while the interstitial's assets are
+    /// loaded in their own `PlaylistStore`.
+    primary_playlist_store: PlaylistStore,
+
+    /// URL of the Multivariant Playlists of the interstitial's assets which are still to be
+    /// played, in playback order.
+    pending_assets: Vec<Url>,
+
+    /// Sum of the durations, in seconds, of the interstitial's assets already played.
+    played_duration: f64,
+
+    /// If set, position in the primary content's playlist time at which playback should resume
+    /// once the interstitial ends, regardless of its resumption offset.
+    ///
+    /// Set when the interstitial is played because a seek tried to jump over it.
+    resume_position: Option<f64>,
 }
 
 /// Identify the JavaScript `readyState` of a created `MediaSource` instance.
@@ -10,6 +10,7 @@ mod parser;
 mod playlist_store;
 mod requester;
 mod segment_selector;
+mod subtitles;
 mod utils;
 
 pub use utils::logger::Logger;
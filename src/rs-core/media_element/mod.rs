@@ -1,5 +1,6 @@
 use self::segment_inventory::{BufferedSegmentMetadata, SegmentInventory};
 use self::source_buffers::SourceBufferQueueElement;
+use self::timestamp_offsets::TimestampOffsets;
 use crate::bindings::{
     jsAttachMediaSource, jsEndOfStream, jsRemoveMediaSource, jsSeek, jsSetMediaOffset,
     jsSetPlaybackRate, jsStartRebuffering, jsStopRebuffering, AddSourceBufferErrorCode,
@@ -15,6 +16,7 @@ pub(crate) use source_buffers::MediaSegmentPushData;
 
 mod segment_inventory;
 mod source_buffers;
+mod timestamp_offsets;
 
 /// Structure linked to an HTMLMediaElement which allows to perform media-related actions on it,
 /// such as:
@@ -35,13 +37,31 @@ pub(crate) struct MediaElementReference {
     /// `0` and will only be set back to `wanted_speed` once enough data becomes available again.
     is_rebuffering: bool,
 
+    /// If `true`, trick play is currently performed: the playback rate is kept to `0` as the
+    /// position is only moved through seeks, and no rebuffering period is started.
+    is_trick_play: bool,
+
     /// Offset used to convert the media position on the HTMLMediaElement (ultimately linked to
     /// pushed segments and the browser's internal logic) to the playlist position as found in a
     /// Multivariant Playlist, that the WaspHlsPlayer actually uses.
     ///
     /// This offset is only known once a segment is being pushed.
+    ///
+    /// It stays the same across discontinuity domains, as segments from other domains are
+    /// placed through `timestamp_offsets` so they follow that same offset.
     media_offset: Option<f64>,
 
+    /// SourceBuffers' `timestampOffset` value, in seconds, to apply when pushing segments of a
+    /// given discontinuity domain, identified by its discontinuity sequence number.
+    ///
+    /// Segments from different discontinuity domains may have unrelated timestamps (e.g. due to
+    /// an encoder reset or to ad-splicing). Those offsets are computed when pushing the first
+    /// segment of a given domain so that all domains share the same `media_offset`, and removed
+    /// once that domain is no longer part of the current Media Playlists.
+    ///
+    /// The same offset is shared between audio and video to keep them in sync.
+    timestamp_offsets: TimestampOffsets,
+
     /// Current state of the attached MediaSource.
     ///
     /// `None` if no MediaSource is attached for now.
@@ -85,9 +105,11 @@ impl MediaElementReference {
         Self {
             queued_seek: None,
             is_rebuffering: false,
+            is_trick_play: false,
             last_observation: None,
             media_source_ready_state: None,
             media_offset: None,
+            timestamp_offsets: TimestampOffsets::new(),
             video_buffer: None,
             audio_buffer: None,
             wanted_speed: 1.,
@@ -107,9 +129,11 @@ impl MediaElementReference {
         self.last_observation = None;
         self.media_source_ready_state = Some(MediaSourceReadyState::Closed);
         self.media_offset = None;
+        self.timestamp_offsets.clear();
         self.video_buffer = None;
         self.audio_buffer = None;
         self.min_buffer_time = 5.;
+        self.is_trick_play = false;
         self.audio_inventory.reset();
         self.video_inventory.reset();
     }
@@ -120,6 +144,15 @@ impl MediaElementReference {
         self.is_rebuffering
     }
 
+    /// Returns `true` if the last `MediaObservation` indicated that the media element reached the
+    /// end of the content.
+    pub(crate) fn has_ended(&self) -> bool {
+        self.last_observation
+            .as_ref()
+            .map(|o| o.ended())
+            .unwrap_or(false)
+    }
+
     /// Returns the wanted playback rate
     pub(crate) fn wanted_speed(&self) -> f64 {
         self.wanted_speed
@@ -129,7 +162,29 @@ impl MediaElementReference {
     /// Note that playback effects will only happen asynchronously
     pub(crate) fn update_wanted_speed(&mut self, new_speed: f64) {
         self.wanted_speed = new_speed;
-        jsSetPlaybackRate(new_speed);
+        if !self.is_trick_play {
+            jsSetPlaybackRate(new_speed);
+        }
+    }
+
+    /// Enable or disable trick play mode, where the playback rate is kept to `0` and the position
+    /// is only moved through seeks.
+    ///
+    /// Once disabled, the playback rate is set back to the wanted one.
+    pub(crate) fn set_trick_play(&mut self, enabled: bool) {
+        if enabled == self.is_trick_play {
+            return;
+        }
+        self.is_trick_play = enabled;
+        if enabled {
+            if self.is_rebuffering {
+                self.is_rebuffering = false;
+                jsStopRebuffering();
+            }
+            jsSetPlaybackRate(0.);
+        } else {
+            jsSetPlaybackRate(self.wanted_speed);
+        }
     }
 
     /// Attach a new `MediaSource` to the media element linked to this `MediaElementReference`.
@@ -331,6 +386,8 @@ impl MediaElementReference {
             context,
             playlist_start: metadata_start,
             playlist_end: metadata_end,
+            media_sequence: time_info.media_sequence(),
+            is_part: time_info.part_index().is_some(),
         };
         let id = match media_type {
             MediaType::Audio => self.audio_inventory.insert_segment(inventory_metadata),
@@ -354,24 +411,55 @@ impl MediaElementReference {
         media_type: MediaType,
         metadata: MediaSegmentPushData,
     ) -> Result<(), PushSegmentError> {
-        let has_media_offset = self.media_offset.is_some();
+        let media_offset = self.media_offset;
+        let discontinuity = metadata.time_info().discontinuity();
+        let timestamp_offset = self.timestamp_offset_for(discontinuity);
         match self.buffer_mut_for(media_type) {
             None => Err(PushSegmentError::NoSourceBuffer(media_type)),
 
             Some(sb) => {
                 let metadata_start = metadata.start();
-                let do_time_parsing = !has_media_offset
+                let do_time_parsing = media_offset.is_none()
                     && (media_type == MediaType::Audio || media_type == MediaType::Video);
-                let response = sb.push_media_segment(metadata, do_time_parsing)?;
-                if let Some(media_start) = response.media_start() {
-                    let media_offset = media_start - metadata_start;
-                    Logger::info(&format!(
-                        "Setting media offset: {}",
-                        media_start - metadata_start
-                    ));
-                    self.media_offset = Some(media_offset);
-                    jsSetMediaOffset(media_offset);
-                    self.check_queued_seek();
+
+                // When first encountering a new discontinuity domain, align its first segment
+                // on the playlist time it is supposed to start at.
+                let aligned_start = match (media_offset, timestamp_offset) {
+                    (Some(media_offset), None) => Some(metadata_start + media_offset),
+                    _ => None,
+                };
+                if let Some(offset) = timestamp_offset {
+                    sb.set_timestamp_offset(offset);
+                }
+                let response = sb.push_media_segment(metadata, do_time_parsing, aligned_start)?;
+                let curr_timestamp_offset = sb.timestamp_offset();
+                match (media_offset, aligned_start, response.media_start()) {
+                    (None, _, Some(media_start)) => {
+                        // First segment pushed: it defines the `media_offset` and its
+                        // discontinuity domain keeps the current `timestampOffset`.
+                        let media_offset = media_start + curr_timestamp_offset - metadata_start;
+                        Logger::info(&format!("Setting media offset: {}", media_offset));
+                        self.media_offset = Some(media_offset);
+                        if timestamp_offset.is_none() {
+                            self.timestamp_offsets
+                                .set(discontinuity, curr_timestamp_offset);
+                        }
+                        jsSetMediaOffset(media_offset);
+                        self.check_queued_seek();
+                    }
+                    (Some(_), Some(_), Some(_)) => {
+                        Logger::info(&format!(
+                            "Setting timestampOffset for discontinuity {}: {}",
+                            discontinuity, curr_timestamp_offset
+                        ));
+                        self.timestamp_offsets
+                            .set(discontinuity, curr_timestamp_offset);
+                    }
+                    (Some(_), Some(_), None) => Logger::warn(&format!(
+                        "Could not parse start of discontinuity {}, its segments may be misplaced",
+                        discontinuity
+                    )),
+                    _ => {}
                 }
                 Ok(())
             }
@@ -455,7 +543,7 @@ impl MediaElementReference {
         }
         self.last_observation = Some(observation);
 
-        if !self.check_queued_seek() {
+        if !self.check_queued_seek() && !self.is_trick_play {
             let last_observation = self.last_observation.as_ref().unwrap();
             let buffer_gap = get_buffer_gap(last_observation);
             if !self.is_rebuffering {
@@ -704,6 +792,28 @@ impl MediaElementReference {
         false
     }
 
+    /// Returns the `timestampOffset` to set on SourceBuffers when pushing segments from the
+    /// discontinuity domain identified by the given discontinuity sequence number.
+    ///
+    /// `None` if no segment from that domain has been pushed yet.
+    fn timestamp_offset_for(&self, discontinuity: u64) -> Option<f64> {
+        self.timestamp_offsets.get(discontinuity)
+    }
+
+    /// Forget the `timestampOffset` of the discontinuity domains whose discontinuity sequence
+    /// number is lower than `discontinuity`, as none of their segments will be pushed anymore.
+    pub(crate) fn forget_discontinuities_before(&mut self, discontinuity: u64) {
+        self.timestamp_offsets.remove_before(discontinuity);
+    }
+
+    /// Returns the offset, in seconds, to add to a timestamp found in a media segment of the
+    /// discontinuity domain identified by `discontinuity` to convert it into playlist time.
+    ///
+    /// `None` if no audio or video segment from that domain has been pushed yet.
+    pub(crate) fn playlist_time_offset_for(&self, discontinuity: u64) -> Option<f64> {
+        Some(self.timestamp_offset_for(discontinuity)? - self.media_offset?)
+    }
+
     /// Convert a media position, which is the position as played on the
     /// media element, to a playlist position, which is the position actually
     /// used in this player.
@@ -103,6 +103,13 @@ pub(crate) struct BufferedChunk {
 
     /// Identifier for the corresponding quality
     media_id: u32,
+
+    /// Media sequence number of the segment, or of its parent segment if this is a partial
+    /// segment.
+    media_sequence: u64,
+
+    /// If `true`, this chunk comes from a partial segment (as found in Low-Latency HLS).
+    is_part: bool,
 }
 
 impl BufferedChunk {
@@ -118,6 +125,8 @@ impl BufferedChunk {
             last_buffered_end: metadata.end,
             last_buffered_start: metadata.start,
             validated: false,
+            media_sequence: metadata.media_sequence,
+            is_part: metadata.is_part,
         }
     }
 
@@ -430,6 +439,23 @@ impl SegmentInventory {
         let mut updates: Vec<PendingBufferedChunkModificationTask> = vec![];
         let mut insertion_task: Option<PendingBufferChunkInsertionTask> = None;
 
+        if !metadata.is_part {
+            // A full segment takes over all the partial segments it is made of
+            let media_sequence = metadata.media_sequence;
+            let media_type = self.media_type;
+            self.inventory.retain(|c| {
+                if c.is_part && c.media_sequence == media_sequence {
+                    Logger::debug(&format!(
+                        "SI: {} segment pushed replaces one of its parts (s:{}, e:{})",
+                        media_type, c.start, c.end
+                    ));
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
         // Searching in antechronological order, look for first segment with start inferior or
         // equal to the one we want to push.
         let compared_pos = self
@@ -658,6 +684,8 @@ impl SegmentInventory {
                             variant_score: seg.variant_score,
                             media_id: seg.media_id,
                         },
+                        media_sequence: seg.media_sequence,
+                        is_part: seg.is_part,
                     };
                     let duplicated_after = BufferedChunk::new(duplicated, seg.id);
                     seg.end = start;
@@ -1065,6 +1093,14 @@ pub(super) struct BufferedSegmentMetadata {
     pub(super) end: f64,
 
     pub(super) context: SegmentQualityContext,
+
+    /// Media sequence number of the segment, or of its parent segment if this is a partial
+    /// segment.
+    pub(super) media_sequence: u64,
+
+    /// If `true`, this is a partial segment (as found in Low-Latency HLS), which will be replaced
+    /// in the inventory by its parent segment if the latter is pushed.
+    pub(super) is_part: bool,
 }
 
 fn buffered_overlap_size(seg: &BufferedChunk, range: (f64, f64), media_offset: f64) -> f64 {
@@ -1072,3 +1108,60 @@ fn buffered_overlap_size(seg: &BufferedChunk, range: (f64, f64), media_offset: f
     let overlap_end = f64::min(seg.end, range.1 - media_offset);
     overlap_end - overlap_start
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::logger::LoggerLevel;
+
+    fn metadata(
+        start: f64,
+        end: f64,
+        media_sequence: u64,
+        is_part: bool,
+    ) -> BufferedSegmentMetadata {
+        BufferedSegmentMetadata {
+            playlist_start: start,
+            playlist_end: end,
+            start,
+            end,
+            context: SegmentQualityContext::new(1., 1),
+            media_sequence,
+            is_part,
+        }
+    }
+
+    fn chunks(inventory: &SegmentInventory) -> Vec<(f64, f64, bool)> {
+        inventory
+            .inventory()
+            .iter()
+            .map(|c| (c.playlist_start(), c.playlist_end(), c.is_part))
+            .collect()
+    }
+
+    #[test]
+    fn test_part_takeover() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let mut inventory = SegmentInventory::new(MediaType::Video);
+        inventory.insert_segment(metadata(0., 4., 10, false));
+        inventory.insert_segment(metadata(4., 5., 11, true));
+        inventory.insert_segment(metadata(5., 6., 11, true));
+        inventory.insert_segment(metadata(8., 9., 12, true));
+        assert_eq!(
+            chunks(&inventory),
+            vec![
+                (0., 4., false),
+                (4., 5., true),
+                (5., 6., true),
+                (8., 9., true)
+            ]
+        );
+
+        // The parent segment replaces its own parts only
+        inventory.insert_segment(metadata(4., 8., 11, false));
+        assert_eq!(
+            chunks(&inventory),
+            vec![(0., 4., false), (4., 8., false), (8., 9., true)]
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use std::collections::VecDeque;
 
 use crate::bindings::{
-    jsAddSourceBuffer, jsAppendBuffer, jsFlush, jsRemoveBuffer, AddSourceBufferErrorCode, JsResult,
-    MediaType, ParsedSegmentInfo, ResourceId, SegmentParsingErrorCode, SourceBufferId,
+    jsAddSourceBuffer, jsAppendBuffer, jsFlush, jsRemoveBuffer, jsSetTimestampOffset,
+    AddSourceBufferErrorCode, JsResult, MediaType, ParsedSegmentInfo, ResourceId,
+    SegmentParsingErrorCode, SourceBufferId,
 };
 use crate::dispatcher::JsMemoryBlob;
 use crate::parser::SegmentTimeInfo;
@@ -43,6 +44,12 @@ pub(super) struct SourceBuffer {
     /// buffer, so special considerations, such as calling the `jsFlush` function might need to be
     /// taken on buffer updates.
     needs_reflush: bool,
+
+    /// Last `timestampOffset` value, in seconds, set on the underlying SourceBuffer.
+    ///
+    /// It is applied on the timestamps of media segments pushed to it, and allows to place
+    /// segments from different discontinuity domains contiguously in the buffer.
+    timestamp_offset: f64,
 }
 
 impl SourceBuffer {
@@ -67,6 +74,7 @@ impl SourceBuffer {
                 needs_reflush: false,
                 last_segment_pushed: false,
                 media_type,
+                timestamp_offset: 0.,
             }),
             Err(err) => Err(AddSourceBufferError::from_js_add_source_buffer_error(
                 err, &typ,
@@ -96,6 +104,26 @@ impl SourceBuffer {
         !self.queue.is_empty()
     }
 
+    /// Returns the last `timestampOffset` value, in seconds, set on the SourceBuffer.
+    pub(super) fn timestamp_offset(&self) -> f64 {
+        self.timestamp_offset
+    }
+
+    /// Update the `timestampOffset` of the SourceBuffer, which is an offset in seconds applied to
+    /// the timestamps of all media segments pushed after this call.
+    ///
+    /// Does nothing if it was already set to that value.
+    pub(super) fn set_timestamp_offset(&mut self, offset: f64) {
+        if offset != self.timestamp_offset {
+            Logger::debug(&format!(
+                "Buffer {} ({}): Setting timestampOffset to {}",
+                self.id, self.typ, offset
+            ));
+            self.timestamp_offset = offset;
+            jsSetTimestampOffset(self.id, offset);
+        }
+    }
+
     /// Pushes a new initialization segment to the underlying `SourceBuffer.
     ///
     /// # Arguments
@@ -112,7 +140,7 @@ impl SourceBuffer {
             "Buffer {} ({}): Pushing initialization segment",
             self.id, self.typ
         ));
-        match jsAppendBuffer(self.id, segment_data.id(), false).result() {
+        match jsAppendBuffer(self.id, segment_data.id(), false, None).result() {
             Err(err) => Err(PushSegmentError::from_js_append_buffer_error(
                 self.media_type,
                 err,
@@ -134,10 +162,15 @@ impl SourceBuffer {
     /// * `parse_time_info` - If set to `true`, the segment's data will be read before pushing it
     ///   to try recuperate its timing information. If it has been parsed with success, it will
     ///   be contained in the `AppendBufferResponse` returned by this method.
+    ///
+    /// * `aligned_start` - If set, the `timestampOffset` of the SourceBuffer will be updated
+    ///   before pushing that segment so it starts at that time in seconds. This relies on the
+    ///   segment's time information being parsed, and thus implies `parse_time_info`.
     pub(super) fn push_media_segment(
         &mut self,
         data: MediaSegmentPushData,
         parse_time_info: bool,
+        aligned_start: Option<f64>,
     ) -> Result<AppendBufferResponse, PushSegmentError> {
         self.last_segment_pushed = false;
         self.was_used = true;
@@ -146,12 +179,21 @@ impl SourceBuffer {
         self.queue
             .push_back(SourceBufferQueueElement::PushMedia((data, id)));
         Logger::debug(&format!("Buffer {} ({}): Pushing", self.id, self.typ));
-        match jsAppendBuffer(self.id, segment_data, parse_time_info).result() {
+        let parse_time_info = parse_time_info || aligned_start.is_some();
+        match jsAppendBuffer(self.id, segment_data, parse_time_info, aligned_start).result() {
             Err(err) => Err(PushSegmentError::from_js_append_buffer_error(
                 self.media_type,
                 err,
             )),
-            Ok(x) => Ok(AppendBufferResponse { parsed: x }),
+            Ok(x) => {
+                let response = AppendBufferResponse { parsed: x };
+                if let (Some(aligned_start), Some(media_start)) =
+                    (aligned_start, response.media_start())
+                {
+                    self.timestamp_offset = aligned_start - media_start;
+                }
+                Ok(response)
+            }
         }
     }
 
@@ -435,10 +477,13 @@ impl PushSegmentError {
                 err.1
                     .unwrap_or_else(|| "Unknown transmuxing error.".to_owned()),
             ),
-            SegmentParsingErrorCode::UnknownError => PushSegmentError::UnknownError(
-                media_type,
-                err.1.unwrap_or_else(|| "Unknown error.".to_owned()),
-            ),
+            // Decryption is performed before the segment is pushed, so JS should never report it
+            SegmentParsingErrorCode::DecryptionError | SegmentParsingErrorCode::UnknownError => {
+                PushSegmentError::UnknownError(
+                    media_type,
+                    err.1.unwrap_or_else(|| "Unknown error.".to_owned()),
+                )
+            }
         }
     }
 }
@@ -0,0 +1,76 @@
+/// SourceBuffers' `timestampOffset` value, in seconds, to apply when pushing segments of each
+/// discontinuity domain, identified by its discontinuity sequence number.
+pub(super) struct TimestampOffsets {
+    /// Discontinuity sequence number and `timestampOffset` of each known discontinuity domain,
+    /// in the order in which they were encountered.
+    offsets: Vec<(u64, f64)>,
+}
+
+impl TimestampOffsets {
+    pub(super) fn new() -> Self {
+        Self { offsets: vec![] }
+    }
+
+    /// Returns the `timestampOffset` of the discontinuity domain identified by `discontinuity`.
+    ///
+    /// `None` if no segment from that domain has been pushed yet.
+    pub(super) fn get(&self, discontinuity: u64) -> Option<f64> {
+        self.offsets
+            .iter()
+            .find(|(d, _)| *d == discontinuity)
+            .map(|(_, offset)| *offset)
+    }
+
+    /// Set the `timestampOffset` of the discontinuity domain identified by `discontinuity`,
+    /// replacing the previous one if it was already known.
+    pub(super) fn set(&mut self, discontinuity: u64, offset: f64) {
+        match self.offsets.iter_mut().find(|(d, _)| *d == discontinuity) {
+            Some(entry) => entry.1 = offset,
+            None => self.offsets.push((discontinuity, offset)),
+        }
+    }
+
+    /// Forget the `timestampOffset` of all discontinuity domains whose discontinuity sequence
+    /// number is lower than `discontinuity`.
+    pub(super) fn remove_before(&mut self, discontinuity: u64) {
+        self.offsets.retain(|(d, _)| *d >= discontinuity);
+    }
+
+    /// Forget the `timestampOffset` of all discontinuity domains.
+    pub(super) fn clear(&mut self) {
+        self.offsets.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_offsets() {
+        let mut offsets = TimestampOffsets::new();
+        assert_eq!(offsets.get(0), None);
+        offsets.set(3, 0.);
+        offsets.set(4, -120.5);
+        offsets.set(6, 30.);
+        assert_eq!(offsets.get(3), Some(0.));
+        assert_eq!(offsets.get(4), Some(-120.5));
+        assert_eq!(offsets.get(5), None);
+
+        offsets.set(4, -100.);
+        assert_eq!(offsets.get(4), Some(-100.));
+        assert_eq!(offsets.offsets.len(), 3);
+
+        // Domains which left the playlist are forgotten
+        offsets.remove_before(4);
+        assert_eq!(offsets.get(3), None);
+        assert_eq!(offsets.get(4), Some(-100.));
+        assert_eq!(offsets.get(6), Some(30.));
+        offsets.remove_before(7);
+        assert!(offsets.offsets.is_empty());
+
+        offsets.set(7, 1.);
+        offsets.clear();
+        assert_eq!(offsets.get(7), None);
+    }
+}
@@ -1,4 +1,4 @@
-use super::MediaTag;
+use super::{media_tag::MediaTrack, AudioChannels, MediaTag};
 use std::ops::{Deref, DerefMut};
 
 /// Allows to translate various `EXT-X-MEDIA` tag found inside a Multivariant Playlist into well
@@ -18,15 +18,16 @@ impl AudioTrackList {
             let name = media.name();
             let language = media.language();
             let assoc_language = media.assoc_language();
-            let channels = media.channels();
+            let channels = media.audio_channels();
+            let characteristics = media.characteristics();
 
             // Check if the track already exist in another encoding quality
-            // TODO also check characteristics
             let pos_compat = available_audio_tracks.iter().position(|t| {
                 t.name() == name
                     && t.language() == language
                     && t.assoc_language() == assoc_language
-                    && t.channels() == channels
+                    && t.audio_channels() == channels
+                    && t.characteristics() == characteristics
             });
 
             if let Some(pos) = pos_compat {
@@ -91,6 +92,12 @@ impl AudioTrackList {
                 .collect::<Vec<(&AudioTrack, &MediaTag)>>()
         })
     }
+
+    /// Returns an iterator on mutable references to all `MediaTag` objects found in this
+    /// `AudioTrackList`.
+    pub(super) fn iter_media_mut(&mut self) -> impl Iterator<Item = &mut MediaTag> {
+        self.iter_mut().flat_map(|t| t.media_tags.iter_mut())
+    }
 }
 
 impl Deref for AudioTrackList {
@@ -107,6 +114,31 @@ impl DerefMut for AudioTrackList {
     }
 }
 
+/// Preferences and capabilities considered when audio renditions are automatically chosen, that
+/// is when no audio track is explicitely selected.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct AudioSelectionPreferences {
+    /// If `true`, audio renditions with accessibility characteristics (e.g. audio descriptions)
+    /// are preferred.
+    pub(crate) prefer_accessible: bool,
+    /// Maximum number of audio channels that can be output. Audio renditions with more channels
+    /// are avoided when possible.
+    ///
+    /// `None` if there's no known limit.
+    pub(crate) max_channels: Option<u32>,
+}
+
+impl AudioSelectionPreferences {
+    /// Returns `true` if the given audio `MediaTag` does not exceed the `max_channels` limit, or
+    /// if its channels are unknown.
+    pub(super) fn fits_channels(&self, media: &MediaTag) -> bool {
+        match (self.max_channels, media.channels()) {
+            (Some(max), Some(count)) => count <= max,
+            _ => true,
+        }
+    }
+}
+
 /// Describe a single audio track object with its own characteristics.
 pub struct AudioTrack {
     /// Unique identifier for that audio track in the parent `AudioTrackList`.
@@ -117,6 +149,20 @@ pub struct AudioTrack {
     pub(self) media_tags: Vec<MediaTag>,
 }
 
+impl MediaTrack for AudioTrack {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn set_id(&mut self, id: u32) {
+        self.id = id;
+    }
+
+    fn media_tags(&self) -> &[MediaTag] {
+        &self.media_tags
+    }
+}
+
 impl AudioTrack {
     /// Unique identifier for that audio track in the parent `AudioTrackList`.
     pub fn id(&self) -> u32 {
@@ -143,6 +189,21 @@ impl AudioTrack {
         self.media_tags.first().and_then(|t| t.channels())
     }
 
+    /// Returns the full information on the audio channels of that audio track, including the
+    /// spatial audio technologies it relies on, if known.
+    pub fn audio_channels(&self) -> Option<&AudioChannels> {
+        self.media_tags.first().and_then(|t| t.audio_channels())
+    }
+
+    /// Returns the Uniform Type Identifiers describing individual characteristics of that audio
+    /// track (e.g. `"public.accessibility.describes-video"`).
+    pub fn characteristics(&self) -> &[String] {
+        self.media_tags
+            .first()
+            .map(|t| t.characteristics())
+            .unwrap_or_default()
+    }
+
     /// Returns slice of the various `MediaTag` objects that audio track is associated with.
     ///
     /// These are usually several qualities representing the same content.
@@ -150,3 +211,52 @@ impl AudioTrack {
         &self.media_tags
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::url::Url;
+
+    #[test]
+    fn test_audio_track_characteristics() {
+        let url = Url::new("https://example.com/main.m3u8".to_owned());
+        let ad_characteristics =
+            "CHARACTERISTICS=\"public.accessibility.describes-video,com.example.foo\"";
+        let media = [
+            "GROUP-ID=\"low\",NAME=\"English\",DEFAULT=YES,URI=\"en-low.m3u8\"".to_owned(),
+            format!("GROUP-ID=\"low\",NAME=\"English\",{ad_characteristics},URI=\"ad-low.m3u8\""),
+            "GROUP-ID=\"high\",NAME=\"English\",DEFAULT=YES,URI=\"en-high.m3u8\"".to_owned(),
+            format!("GROUP-ID=\"high\",NAME=\"English\",{ad_characteristics},URI=\"ad-high.m3u8\""),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(id, attrs)| {
+            let line = format!("#EXT-X-MEDIA:TYPE=AUDIO,{attrs}");
+            MediaTag::create(&line, &url, id as u32).unwrap()
+        })
+        .collect();
+        let tracks = AudioTrackList::new(media);
+
+        // Renditions with different characteristics are different tracks, even with the same name
+        assert_eq!(tracks.len(), 2);
+        let main = tracks.iter().find(|t| t.characteristics().is_empty());
+        let main = main.unwrap();
+        assert_eq!(main.medias().len(), 2);
+        assert!(main.medias().iter().all(|m| !m.is_accessibility_related()));
+
+        let described = tracks.iter().find(|t| !t.characteristics().is_empty());
+        let described = described.unwrap();
+        assert_eq!(
+            described.characteristics(),
+            ["public.accessibility.describes-video", "com.example.foo"]
+        );
+        assert_eq!(described.medias().len(), 2);
+        assert!(described
+            .medias()
+            .iter()
+            .all(|m| m.is_accessibility_related()));
+        let mut groups = tracks.groups_for_track_id(described.id());
+        groups.sort();
+        assert_eq!(groups, ["high", "low"]);
+    }
+}
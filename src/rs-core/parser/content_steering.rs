@@ -0,0 +1,379 @@
+use std::{error, fmt};
+
+use super::{
+    json::JsonValue,
+    utils::{parse_quoted_string, skip_attribute_list_value},
+};
+use crate::{utils::url::Url, Logger};
+
+/// TTL, in seconds, to consider when a Content Steering manifest does not define one.
+const DEFAULT_STEERING_MANIFEST_TTL: f64 = 300.;
+
+/// Content Steering information announced in a Multivariant Playlist through an
+/// `EXT-X-CONTENT-STEERING` tag.
+///
+/// Content Steering allows a server to prioritize, in real time, between several "Pathways",
+/// which are groups of variants generally served from different CDNs.
+#[derive(Clone, Debug)]
+pub(crate) struct ContentSteering {
+    /// The `SERVER-URI` attribute, resolved against the Multivariant Playlist's URL, from which
+    /// the Content Steering manifest can be fetched.
+    server_url: Url,
+    /// The `PATHWAY-ID` attribute, which is the Pathway that should be relied on until the
+    /// Content Steering manifest is loaded.
+    pathway_id: Option<String>,
+}
+
+impl ContentSteering {
+    /// Parse the `EXT-X-CONTENT-STEERING` tag in `line`.
+    ///
+    /// `base_uri` is the base on which a relative `SERVER-URI` attribute is resolved.
+    ///
+    /// Returns `None` if that tag is invalid, in which case it should be ignored.
+    pub(super) fn parse(line: &str, base_uri: &str) -> Option<Self> {
+        let mut server_uri: Option<&str> = None;
+        let mut pathway_id: Option<&str> = None;
+        let mut offset = line.find(':').map_or(line.len(), |idx| idx + 1);
+        loop {
+            if offset >= line.len() {
+                break;
+            }
+            match line[offset..].find('=') {
+                None => {
+                    Logger::warn("Parser: Attribute Name not followed by equal sign");
+                    break;
+                }
+                Some(idx) => {
+                    let attr = &line[offset..offset + idx];
+                    if let "SERVER-URI" | "PATHWAY-ID" = attr {
+                        let (parsed, end_offset) = parse_quoted_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        let parsed = if let Ok(parsed) = parsed {
+                            Some(parsed)
+                        } else {
+                            Logger::warn(&format!(
+                                "Parser: Unparsable EXT-X-CONTENT-STEERING {attr} value"
+                            ));
+                            None
+                        };
+                        if attr == "SERVER-URI" {
+                            server_uri = parsed;
+                        } else {
+                            pathway_id = parsed;
+                        }
+                    } else {
+                        offset = skip_attribute_list_value(line, offset + idx + 1) + 1;
+                    }
+                }
+            }
+        }
+
+        let server_uri = if let Some(server_uri) = server_uri {
+            server_uri
+        } else {
+            Logger::warn("Parser: Ignoring EXT-X-CONTENT-STEERING without a SERVER-URI attribute");
+            return None;
+        };
+        let server_url = Url::resolve(server_uri, base_uri);
+        Some(Self {
+            server_url,
+            pathway_id: pathway_id.map(|p| p.to_owned()),
+        })
+    }
+
+    /// The URL from which the Content Steering manifest can be fetched.
+    pub(crate) fn server_url(&self) -> &Url {
+        &self.server_url
+    }
+
+    /// The Pathway that should be initially relied on, if one was set.
+    pub(crate) fn pathway_id(&self) -> Option<&str> {
+        self.pathway_id.as_deref()
+    }
+}
+
+/// A parsed Content Steering manifest, the JSON document fetched from an
+/// `EXT-X-CONTENT-STEERING`'s `SERVER-URI`.
+#[derive(Clone, Debug)]
+pub(crate) struct SteeringManifest {
+    /// Number of seconds after which the Content Steering manifest should be reloaded.
+    ttl: f64,
+    /// URL from which the Content Steering manifest should be reloaded, if it changed.
+    reload_url: Option<Url>,
+    /// Pathway IDs, from the most to the least preferred.
+    pathway_priority: Vec<String>,
+    /// New Pathways which should be created by copying an existing one.
+    pathway_clones: Vec<PathwayClone>,
+}
+
+/// A Pathway described by a Content Steering manifest's `PATHWAY-CLONES` property, whose
+/// variants are copies of another Pathway's with their URL modified.
+#[derive(Clone, Debug)]
+pub(crate) struct PathwayClone {
+    /// ID of the new Pathway.
+    id: String,
+    /// ID of the Pathway from which variants are copied.
+    base_id: String,
+    /// If set, the hostname replacing the one in the copied variants' URL.
+    host: Option<String>,
+    /// Query parameters to add to the copied variants' URL.
+    query_params: Vec<(String, String)>,
+    /// URL to use instead for specific copied variants, identified by their
+    /// `STABLE-VARIANT-ID`.
+    per_variant_urls: Vec<(String, Url)>,
+}
+
+impl SteeringManifest {
+    /// Parse the Content Steering manifest in `data`, which was loaded from `url`.
+    pub(crate) fn parse(data: &[u8], url: &Url) -> Result<Self, SteeringManifestParsingError> {
+        let manifest = JsonValue::parse(data)
+            .filter(|m| m.as_object().is_some())
+            .ok_or(SteeringManifestParsingError::InvalidJson)?;
+        if manifest.get("VERSION").and_then(|v| v.as_f64()) != Some(1.) {
+            return Err(SteeringManifestParsingError::UnsupportedVersion);
+        }
+        let ttl = match manifest.get("TTL").and_then(|t| t.as_f64()) {
+            Some(ttl) if ttl > 0. => ttl,
+            _ => {
+                Logger::warn("Parser: Content Steering manifest without a valid TTL");
+                DEFAULT_STEERING_MANIFEST_TTL
+            }
+        };
+        let reload_url = manifest
+            .get("RELOAD-URI")
+            .and_then(|r| r.as_str())
+            .map(|r| Url::resolve(r, url.pathname()));
+        let pathway_priority: Vec<String> = manifest
+            .get("PATHWAY-PRIORITY")
+            .and_then(|p| p.as_array())
+            .ok_or(SteeringManifestParsingError::MissingPathwayPriority)?
+            .iter()
+            .filter_map(|p| p.as_str().map(|p| p.to_owned()))
+            .collect();
+        if pathway_priority.is_empty() {
+            return Err(SteeringManifestParsingError::MissingPathwayPriority);
+        }
+        let pathway_clones = manifest
+            .get("PATHWAY-CLONES")
+            .and_then(|c| c.as_array())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|c| {
+                let clone = PathwayClone::parse(c, url);
+                if clone.is_none() {
+                    Logger::warn("Parser: Ignoring invalid Content Steering PATHWAY-CLONES entry");
+                }
+                clone
+            })
+            .collect();
+        Ok(Self {
+            ttl,
+            reload_url,
+            pathway_priority,
+            pathway_clones,
+        })
+    }
+
+    /// Number of seconds after which the Content Steering manifest should be reloaded.
+    pub(crate) fn ttl(&self) -> f64 {
+        self.ttl
+    }
+
+    /// URL from which the Content Steering manifest should be reloaded, if it changed.
+    pub(crate) fn reload_url(&self) -> Option<&Url> {
+        self.reload_url.as_ref()
+    }
+
+    /// Pathway IDs, from the most to the least preferred.
+    pub(crate) fn pathway_priority(&self) -> &[String] {
+        &self.pathway_priority
+    }
+
+    /// New Pathways which should be created by copying an existing one.
+    pub(crate) fn pathway_clones(&self) -> &[PathwayClone] {
+        &self.pathway_clones
+    }
+}
+
+impl PathwayClone {
+    /// Parse a `PATHWAY-CLONES` entry of a Content Steering manifest loaded from `url`.
+    ///
+    /// Returns `None` if that entry is invalid.
+    fn parse(value: &JsonValue, url: &Url) -> Option<Self> {
+        let id = value.get("ID")?.as_str()?.to_owned();
+        let base_id = value.get("BASE-ID")?.as_str()?.to_owned();
+        let replacement = value.get("URI-REPLACEMENT");
+        let host = replacement
+            .and_then(|r| r.get("HOST"))
+            .and_then(|h| h.as_str())
+            .map(|h| h.to_owned());
+        let query_params = replacement
+            .and_then(|r| r.get("PARAMS"))
+            .and_then(|p| p.as_object())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|(name, val)| val.as_str().map(|v| (name.clone(), v.to_owned())))
+            .collect();
+        // TODO `PER-RENDITION-URIS` is not exploited as renditions are not cloned yet
+        let per_variant_urls = replacement
+            .and_then(|r| r.get("PER-VARIANT-URIS"))
+            .and_then(|p| p.as_object())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|(stable_id, val)| {
+                val.as_str()
+                    .map(|v| (stable_id.clone(), Url::resolve(v, url.pathname())))
+            })
+            .collect();
+        Some(Self {
+            id,
+            base_id,
+            host,
+            query_params,
+            per_variant_urls,
+        })
+    }
+
+    /// ID of the new Pathway.
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// ID of the Pathway from which variants are copied.
+    pub(crate) fn base_id(&self) -> &str {
+        &self.base_id
+    }
+
+    /// Returns the URL the copy of a variant whose URL is `base_url` and whose
+    /// `STABLE-VARIANT-ID` is `stable_id` should have in this new Pathway.
+    pub(crate) fn variant_url(&self, base_url: &Url, stable_id: Option<&str>) -> Url {
+        let per_variant_url = stable_id.and_then(|stable_id| {
+            self.per_variant_urls
+                .iter()
+                .find(|(id, _)| id == stable_id)
+                .map(|(_, url)| url.clone())
+        });
+        if let Some(url) = per_variant_url {
+            return url;
+        }
+        let mut url = match self.host.as_deref() {
+            Some(host) => base_url.with_host(host),
+            None => base_url.clone(),
+        };
+        for (name, value) in &self.query_params {
+            url.add_query_parameter(name, value);
+        }
+        url
+    }
+}
+
+/// Errors that may arise when parsing a Content Steering manifest.
+#[derive(Debug)]
+pub(crate) enum SteeringManifestParsingError {
+    /// The Content Steering manifest was not a valid JSON object.
+    InvalidJson,
+    /// The Content Steering manifest's `VERSION` is not supported.
+    UnsupportedVersion,
+    /// The Content Steering manifest had no valid `PATHWAY-PRIORITY`.
+    MissingPathwayPriority,
+}
+
+impl fmt::Display for SteeringManifestParsingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SteeringManifestParsingError::InvalidJson => {
+                write!(f, "The Content Steering manifest is not valid JSON")
+            }
+            SteeringManifestParsingError::UnsupportedVersion => {
+                write!(
+                    f,
+                    "The Content Steering manifest's VERSION is not supported"
+                )
+            }
+            SteeringManifestParsingError::MissingPathwayPriority => {
+                write!(
+                    f,
+                    "The Content Steering manifest does not contain any PATHWAY-PRIORITY"
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for SteeringManifestParsingError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::logger::LoggerLevel;
+
+    #[test]
+    fn test_parse_steering_manifest() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let url = Url::new("https://example.com/steering/manifest.json".to_owned());
+        let manifest = SteeringManifest::parse(
+            br#"{
+              "VERSION": 1,
+              "TTL": 60,
+              "RELOAD-URI": "next.json",
+              "PATHWAY-PRIORITY": ["CDN-B", "CDN-A"],
+              "PATHWAY-CLONES": [
+                {
+                  "ID": "CDN-C",
+                  "BASE-ID": "CDN-A",
+                  "URI-REPLACEMENT": {
+                    "HOST": "c.example.com",
+                    "PARAMS": { "token": "a b&c" },
+                    "PER-VARIANT-URIS": { "hd": "hd/media.m3u8" }
+                  }
+                },
+                { "ID": "invalid" }
+              ]
+            }"#,
+            &url,
+        )
+        .unwrap();
+        assert_eq!(manifest.ttl(), 60.);
+        assert_eq!(
+            manifest.reload_url().map(|u| u.get_ref()),
+            Some("https://example.com/steering/next.json")
+        );
+        assert_eq!(manifest.pathway_priority(), ["CDN-B", "CDN-A"]);
+
+        let clones = manifest.pathway_clones();
+        assert_eq!(clones.len(), 1);
+        assert_eq!(clones[0].id(), "CDN-C");
+        assert_eq!(clones[0].base_id(), "CDN-A");
+        let base_url = Url::new("https://a.example.com:8080/sd/media.m3u8".to_owned());
+        assert_eq!(
+            clones[0].variant_url(&base_url, Some("sd")).get_ref(),
+            "https://c.example.com:8080/sd/media.m3u8?token=a%20b%26c"
+        );
+        assert_eq!(
+            clones[0].variant_url(&base_url, Some("hd")).get_ref(),
+            "https://example.com/steering/hd/media.m3u8"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_steering_manifest() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let url = Url::new("https://example.com/manifest.json".to_owned());
+        assert!(matches!(
+            SteeringManifest::parse(b"[]", &url),
+            Err(SteeringManifestParsingError::InvalidJson)
+        ));
+        assert!(matches!(
+            SteeringManifest::parse(br#"{"VERSION": 2, "PATHWAY-PRIORITY": ["A"]}"#, &url),
+            Err(SteeringManifestParsingError::UnsupportedVersion)
+        ));
+        assert!(matches!(
+            SteeringManifest::parse(br#"{"VERSION": 1, "PATHWAY-PRIORITY": []}"#, &url),
+            Err(SteeringManifestParsingError::MissingPathwayPriority)
+        ));
+        let manifest =
+            SteeringManifest::parse(br#"{"VERSION": 1, "PATHWAY-PRIORITY": ["A"]}"#, &url).unwrap();
+        assert_eq!(manifest.ttl(), DEFAULT_STEERING_MANIFEST_TTL);
+        assert!(manifest.reload_url().is_none());
+    }
+}
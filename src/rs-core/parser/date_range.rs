@@ -0,0 +1,472 @@
+use crate::{bindings::DateRangeAttributeType, Logger};
+
+use super::utils::{
+    find_attribute_end, parse_decimal_floating_point, parse_enumerated_string, parse_iso_8601_date,
+    parse_quoted_string, skip_attribute_list_value,
+};
+
+/// Information parsed from one or multiple `EXT-X-DATERANGE` tags sharing the same `ID`,
+/// associating a date range to attribute/value pairs.
+///
+/// All times are expressed in playlist time, in seconds.
+#[derive(Clone, Debug)]
+pub(crate) struct DateRange {
+    /// Unique identifier for that date range in its Media Playlist.
+    id: String,
+    /// Client-defined string specifying a set of attributes and their associated semantics.
+    class: Option<String>,
+    /// Time at which the date range begins.
+    start: f64,
+    /// If set, time at which the date range ends.
+    ///
+    /// Deduced from either its `END-DATE`, `DURATION` or `END-ON-NEXT` attribute.
+    end: Option<f64>,
+    /// If set, the duration of the date range, in seconds.
+    duration: Option<f64>,
+    /// If set, the expected duration of the date range, in seconds, for when its actual duration
+    /// is not yet known.
+    planned_duration: Option<f64>,
+    /// If `true`, the date range ends at the start of the following date range of the same
+    /// `class`.
+    end_on_next: bool,
+    /// Splice information carried by the `SCTE35-CMD` attribute, as an hexadecimal-sequence.
+    scte35_cmd: Option<String>,
+    /// Splice information carried by the `SCTE35-OUT` attribute, as an hexadecimal-sequence.
+    scte35_out: Option<String>,
+    /// Splice information carried by the `SCTE35-IN` attribute, as an hexadecimal-sequence.
+    scte35_in: Option<String>,
+    /// Client-defined attributes, whose name are prefixed by `X-`.
+    client_attributes: Vec<DateRangeClientAttribute>,
+}
+
+/// A client-defined attribute of an `EXT-X-DATERANGE` tag.
+#[derive(Clone, Debug)]
+pub(crate) struct DateRangeClientAttribute {
+    /// Name of the attribute, including its `X-` prefix.
+    name: String,
+    /// The attribute's value, as written in the Media Playlist (without quotes for
+    /// quoted-strings).
+    value: String,
+    /// Format of `value`.
+    typ: DateRangeAttributeType,
+}
+
+/// Errors that may arise when parsing an `EXT-X-DATERANGE` tag.
+#[derive(Debug)]
+pub(super) enum DateRangeParsingError {
+    /// The mandatory `ID` attribute was missing.
+    MissingId,
+    /// The mandatory `START-DATE` attribute was missing or unparsable.
+    MissingStartDate,
+}
+
+impl DateRange {
+    /// Parse the `EXT-X-DATERANGE` tag contained in `line`.
+    ///
+    /// As dates found in a Media Playlist are converted to playlist time through their
+    /// `EXT-X-PROGRAM-DATE-TIME` tags, which are themselves directly used as playlist time, the
+    /// `START-DATE` and `END-DATE` attributes are here directly converted to the same timestamps.
+    pub(super) fn parse(line: &str) -> Result<Self, DateRangeParsingError> {
+        let mut id: Option<String> = None;
+        let mut class: Option<String> = None;
+        let mut start: Option<f64> = None;
+        let mut end_date: Option<f64> = None;
+        let mut duration: Option<f64> = None;
+        let mut planned_duration: Option<f64> = None;
+        let mut end_on_next = false;
+        let mut scte35_cmd: Option<String> = None;
+        let mut scte35_out: Option<String> = None;
+        let mut scte35_in: Option<String> = None;
+        let mut client_attributes = vec![];
+
+        let mut offset = "#EXT-X-DATERANGE:".len();
+        loop {
+            if offset >= line.len() {
+                break;
+            }
+            match line[offset..].find('=') {
+                None => {
+                    Logger::warn("Attribute Name not followed by equal sign");
+                    break;
+                }
+                Some(idx) => match &line[offset..offset + idx] {
+                    "ID" => {
+                        let (parsed, end_offset) = parse_quoted_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            id = Some(val.to_owned());
+                        }
+                    }
+                    "CLASS" => {
+                        let (parsed, end_offset) = parse_quoted_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            class = Some(val.to_owned());
+                        }
+                    }
+                    "START-DATE" => {
+                        let (parsed, end_offset) = parse_quoted_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        start = parsed.ok().and_then(|val| parse_iso_8601_date(val, 0));
+                    }
+                    "END-DATE" => {
+                        let (parsed, end_offset) = parse_quoted_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        end_date = parsed.ok().and_then(|val| parse_iso_8601_date(val, 0));
+                        if end_date.is_none() {
+                            Logger::warn("Unparsable END-DATE value");
+                        }
+                    }
+                    "DURATION" => {
+                        let (parsed, end_offset) =
+                            parse_decimal_floating_point(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed {
+                            Ok(val) => duration = Some(val),
+                            Err(_) => Logger::warn("Unparsable DATERANGE DURATION value"),
+                        }
+                    }
+                    "PLANNED-DURATION" => {
+                        let (parsed, end_offset) =
+                            parse_decimal_floating_point(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed {
+                            Ok(val) => planned_duration = Some(val),
+                            Err(_) => Logger::warn("Unparsable PLANNED-DURATION value"),
+                        }
+                    }
+                    "END-ON-NEXT" => {
+                        let (parsed, end_offset) = parse_enumerated_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        end_on_next = parsed == "YES";
+                    }
+                    "SCTE35-CMD" => {
+                        let (parsed, end_offset) = parse_enumerated_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        scte35_cmd = Some(parsed.to_owned());
+                    }
+                    "SCTE35-OUT" => {
+                        let (parsed, end_offset) = parse_enumerated_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        scte35_out = Some(parsed.to_owned());
+                    }
+                    "SCTE35-IN" => {
+                        let (parsed, end_offset) = parse_enumerated_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        scte35_in = Some(parsed.to_owned());
+                    }
+                    name if name.starts_with("X-") => {
+                        let name = name.to_owned();
+                        let (attribute, end_offset) =
+                            parse_client_attribute(line, offset + idx + 1, name);
+                        offset = end_offset + 1;
+                        if let Some(attribute) = attribute {
+                            client_attributes.push(attribute);
+                        }
+                    }
+                    _ => offset = skip_attribute_list_value(line, offset + idx + 1) + 1,
+                },
+            }
+        }
+
+        let start = start.ok_or(DateRangeParsingError::MissingStartDate)?;
+        let end = end_date.or(duration.map(|d| start + d));
+        Ok(DateRange {
+            id: id.ok_or(DateRangeParsingError::MissingId)?,
+            class,
+            start,
+            end,
+            duration,
+            planned_duration,
+            end_on_next,
+            scte35_cmd,
+            scte35_out,
+            scte35_in,
+            client_attributes,
+        })
+    }
+
+    /// Complete this `DateRange` with the information of `other`, which should share the same
+    /// `id`.
+    ///
+    /// Attributes already set on this `DateRange` have priority over the ones in `other`.
+    pub(super) fn complete_with(&mut self, other: &DateRange) {
+        if self.class.is_none() {
+            self.class = other.class.clone();
+        }
+        if self.end.is_none() {
+            self.end = other.end;
+        }
+        if self.duration.is_none() {
+            self.duration = other.duration;
+        }
+        if self.planned_duration.is_none() {
+            self.planned_duration = other.planned_duration;
+        }
+        self.end_on_next = self.end_on_next || other.end_on_next;
+        if self.scte35_cmd.is_none() {
+            self.scte35_cmd = other.scte35_cmd.clone();
+        }
+        if self.scte35_out.is_none() {
+            self.scte35_out = other.scte35_out.clone();
+        }
+        if self.scte35_in.is_none() {
+            self.scte35_in = other.scte35_in.clone();
+        }
+        for attribute in other.client_attributes.iter() {
+            if !self
+                .client_attributes
+                .iter()
+                .any(|a| a.name == attribute.name)
+            {
+                self.client_attributes.push(attribute.clone());
+            }
+        }
+    }
+
+    /// Set the end of this `DateRange` if it was not yet known.
+    pub(super) fn set_end_if_unknown(&mut self, end: f64) {
+        if self.end.is_none() {
+            self.end = Some(end);
+        }
+    }
+
+    /// Unique identifier for that date range in its Media Playlist.
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Client-defined string specifying a set of attributes and their associated semantics.
+    pub(crate) fn class(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+
+    /// Time at which the date range begins, in playlist time.
+    pub(crate) fn start(&self) -> f64 {
+        self.start
+    }
+
+    /// Time at which the date range ends, in playlist time, if known.
+    pub(crate) fn end(&self) -> Option<f64> {
+        self.end
+    }
+
+    /// Duration of the date range in seconds, as indicated by its `DURATION` attribute.
+    pub(crate) fn duration(&self) -> Option<f64> {
+        self.duration
+    }
+
+    /// Expected duration of the date range in seconds, as indicated by its `PLANNED-DURATION`
+    /// attribute.
+    pub(crate) fn planned_duration(&self) -> Option<f64> {
+        self.planned_duration
+    }
+
+    /// Returns `true` if the date range ends at the start of the following date range of the same
+    /// `class`.
+    pub(crate) fn ends_on_next(&self) -> bool {
+        self.end_on_next
+    }
+
+    /// Value of the `SCTE35-CMD` attribute, as an hexadecimal-sequence.
+    pub(crate) fn scte35_cmd(&self) -> Option<&str> {
+        self.scte35_cmd.as_deref()
+    }
+
+    /// Value of the `SCTE35-OUT` attribute, as an hexadecimal-sequence.
+    pub(crate) fn scte35_out(&self) -> Option<&str> {
+        self.scte35_out.as_deref()
+    }
+
+    /// Value of the `SCTE35-IN` attribute, as an hexadecimal-sequence.
+    pub(crate) fn scte35_in(&self) -> Option<&str> {
+        self.scte35_in.as_deref()
+    }
+
+    /// Client-defined attributes, whose name are prefixed by `X-`.
+    pub(crate) fn client_attributes(&self) -> &[DateRangeClientAttribute] {
+        self.client_attributes.as_slice()
+    }
+
+    /// Returns `true` if the given playlist position is inside this date range.
+    ///
+    /// A date range whose end is not yet known is considered to go on indefinitely.
+    pub(crate) fn contains(&self, position: f64) -> bool {
+        position >= self.start && self.end.is_none_or(|end| position < end)
+    }
+}
+
+impl DateRangeClientAttribute {
+    /// Name of the attribute, including its `X-` prefix.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The attribute's value, as written in the Media Playlist (without quotes for
+    /// quoted-strings).
+    pub(crate) fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Format of the value returned by `value`.
+    pub(crate) fn typ(&self) -> DateRangeAttributeType {
+        self.typ
+    }
+}
+
+/// Parse the value of the client-defined attribute `name`, starting at `value_start_offset` in
+/// `line`.
+///
+/// Returns the parsed attribute, or `None` if it could not be parsed, as well as the offset at
+/// which its value ended.
+fn parse_client_attribute(
+    line: &str,
+    value_start_offset: usize,
+    name: String,
+) -> (Option<DateRangeClientAttribute>, usize) {
+    let value_start = &line[value_start_offset..];
+    if value_start.starts_with('"') {
+        let (parsed, end_offset) = parse_quoted_string(line, value_start_offset);
+        let attribute = parsed.ok().map(|val| DateRangeClientAttribute {
+            name,
+            value: val.to_owned(),
+            typ: DateRangeAttributeType::String,
+        });
+        (attribute, end_offset)
+    } else if value_start.starts_with("0x") || value_start.starts_with("0X") {
+        let end_offset = find_attribute_end(line, value_start_offset);
+        let attribute = DateRangeClientAttribute {
+            name,
+            value: line[value_start_offset..end_offset].to_owned(),
+            typ: DateRangeAttributeType::HexadecimalSequence,
+        };
+        (Some(attribute), end_offset)
+    } else {
+        let (parsed, end_offset) = parse_decimal_floating_point(line, value_start_offset);
+        let attribute = parsed.ok().map(|_| DateRangeClientAttribute {
+            name,
+            value: line[value_start_offset..end_offset].to_owned(),
+            typ: DateRangeAttributeType::Number,
+        });
+        if attribute.is_none() {
+            Logger::warn("Unparsable DATERANGE client attribute");
+        }
+        (attribute, end_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::logger::LoggerLevel;
+
+    /// `2022-11-11T18:00:00Z`, as a unix timestamp in seconds.
+    const START: f64 = 1_668_189_600.;
+
+    #[test]
+    fn test_parse_date_range() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let date_range = DateRange::parse(
+            "#EXT-X-DATERANGE:ID=\"ad-1\",CLASS=\"com.example.ad\",\
+START-DATE=\"2022-11-11T18:00:00Z\",PLANNED-DURATION=30.5,SCTE35-OUT=0xFC002F,\
+X-AD-ID=\"a,b\",X-COUNT=3.5,X-HEX=0xAB12,X-INVALID=abc",
+        )
+        .unwrap();
+        assert_eq!(date_range.id(), "ad-1");
+        assert_eq!(date_range.class(), Some("com.example.ad"));
+        assert_eq!(date_range.start(), START);
+        assert_eq!(date_range.end(), None);
+        assert_eq!(date_range.duration(), None);
+        assert_eq!(date_range.planned_duration(), Some(30.5));
+        assert!(!date_range.ends_on_next());
+        assert_eq!(date_range.scte35_out(), Some("0xFC002F"));
+        assert_eq!(date_range.scte35_in(), None);
+        let attributes: Vec<(&str, &str, DateRangeAttributeType)> = date_range
+            .client_attributes()
+            .iter()
+            .map(|a| (a.name(), a.value(), a.typ()))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("X-AD-ID", "a,b", DateRangeAttributeType::String),
+                ("X-COUNT", "3.5", DateRangeAttributeType::Number),
+                (
+                    "X-HEX",
+                    "0xAB12",
+                    DateRangeAttributeType::HexadecimalSequence
+                ),
+            ]
+        );
+        assert!(date_range.contains(START + 1000.));
+        assert!(!date_range.contains(START - 1.));
+
+        assert!(matches!(
+            DateRange::parse("#EXT-X-DATERANGE:START-DATE=\"2022-11-11T18:00:00Z\""),
+            Err(DateRangeParsingError::MissingId)
+        ));
+        assert!(matches!(
+            DateRange::parse("#EXT-X-DATERANGE:ID=\"a\",START-DATE=\"not a date\""),
+            Err(DateRangeParsingError::MissingStartDate)
+        ));
+    }
+
+    #[test]
+    fn test_date_range_end() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let date_range = DateRange::parse(
+            "#EXT-X-DATERANGE:ID=\"a\",START-DATE=\"2022-11-11T18:00:00Z\",DURATION=20",
+        )
+        .unwrap();
+        assert_eq!(date_range.duration(), Some(20.));
+        assert_eq!(date_range.end(), Some(START + 20.));
+        assert!(date_range.contains(START + 19.9));
+        assert!(!date_range.contains(START + 20.));
+
+        // END-DATE has priority over DURATION
+        let date_range = DateRange::parse(
+            "#EXT-X-DATERANGE:ID=\"a\",START-DATE=\"2022-11-11T18:00:00Z\",\
+END-DATE=\"2022-11-11T18:00:10Z\",DURATION=20",
+        )
+        .unwrap();
+        assert_eq!(date_range.duration(), Some(20.));
+        assert_eq!(date_range.end(), Some(START + 10.));
+
+        let mut date_range = DateRange::parse(
+            "#EXT-X-DATERANGE:ID=\"a\",CLASS=\"c\",START-DATE=\"2022-11-11T18:00:00Z\",\
+END-ON-NEXT=YES",
+        )
+        .unwrap();
+        assert!(date_range.ends_on_next());
+        assert_eq!(date_range.end(), None);
+        date_range.set_end_if_unknown(START + 5.);
+        assert_eq!(date_range.end(), Some(START + 5.));
+        date_range.set_end_if_unknown(START + 8.);
+        assert_eq!(date_range.end(), Some(START + 5.));
+    }
+
+    #[test]
+    fn test_complete_with() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let first = DateRange::parse(
+            "#EXT-X-DATERANGE:ID=\"a\",CLASS=\"c\",START-DATE=\"2022-11-11T18:00:00Z\",\
+PLANNED-DURATION=30,X-A=\"1\",X-B=\"2\"",
+        )
+        .unwrap();
+        let mut update = DateRange::parse(
+            "#EXT-X-DATERANGE:ID=\"a\",START-DATE=\"2022-11-11T18:00:00Z\",DURATION=25,\
+X-B=\"3\"",
+        )
+        .unwrap();
+        update.complete_with(&first);
+        assert_eq!(update.class(), Some("c"));
+        assert_eq!(update.end(), Some(START + 25.));
+        assert_eq!(update.planned_duration(), Some(30.));
+        let attributes: Vec<(&str, &str)> = update
+            .client_attributes()
+            .iter()
+            .map(|a| (a.name(), a.value()))
+            .collect();
+        assert_eq!(attributes, vec![("X-B", "3"), ("X-A", "1")]);
+    }
+}
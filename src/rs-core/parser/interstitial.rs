@@ -0,0 +1,240 @@
+use thiserror::Error;
+
+use crate::{utils::url::Url, Logger};
+
+use super::{json::JsonValue, DateRange};
+
+/// `CLASS` of the `EXT-X-DATERANGE` tags describing HLS Interstitials.
+const INTERSTITIAL_CLASS: &str = "com.apple.hls.interstitial";
+
+/// An HLS Interstitial, which is content (generally an advertisement) which has to be played in
+/// place of the primary content once playback reaches a given position.
+///
+/// It is announced through an `EXT-X-DATERANGE` tag whose `CLASS` attribute is set to
+/// `"com.apple.hls.interstitial"`.
+#[derive(Clone, Debug)]
+pub(crate) struct Interstitial {
+    /// `ID` of the corresponding `EXT-X-DATERANGE` tag.
+    id: String,
+    /// Position, in the primary content's playlist time, at which the interstitial should be
+    /// played.
+    start: f64,
+    /// If known, position in the primary content's playlist time at which the corresponding date
+    /// range ends.
+    end: Option<f64>,
+    /// Where the interstitial's asset(s) can be found.
+    asset: InterstitialAsset,
+    /// Offset, in seconds, relative to `start`, at which the primary content should be resumed
+    /// once the interstitial has been played.
+    ///
+    /// If `None`, the duration of the interstitial's asset(s) should be used instead.
+    resume_offset: Option<f64>,
+    /// If `true`, the user should not be able to seek inside the interstitial's asset(s).
+    restrict_skip: bool,
+    /// If `true`, the interstitial should still be played when the user seeks over it.
+    restrict_jump: bool,
+    /// If `true`, the primary content should be left at the segment boundary the closest to
+    /// `start`.
+    snap_out: bool,
+    /// If `true`, the primary content should be resumed at the segment boundary the closest to
+    /// the resumption position.
+    snap_in: bool,
+}
+
+/// Location of the content to play for an `Interstitial`.
+#[derive(Clone, Debug)]
+pub(crate) enum InterstitialAsset {
+    /// The URL of a single asset's Multivariant Playlist, from the `X-ASSET-URI` attribute.
+    Uri(Url),
+    /// The URL of a JSON object listing the assets to play, from the `X-ASSET-LIST` attribute.
+    List(Url),
+}
+
+impl Interstitial {
+    /// Obtain the `Interstitial` described by `date_range`, if it describes one.
+    ///
+    /// `base_url` is the URL of the Media Playlist in which that date range was found, used to
+    /// resolve relative asset URLs.
+    pub(crate) fn from_date_range(date_range: &DateRange, base_url: &Url) -> Option<Self> {
+        if date_range.class() != Some(INTERSTITIAL_CLASS) {
+            return None;
+        }
+        let mut asset_uri: Option<Url> = None;
+        let mut asset_list: Option<Url> = None;
+        let mut resume_offset: Option<f64> = None;
+        let mut restrict_skip = false;
+        let mut restrict_jump = false;
+        let mut snap_out = false;
+        let mut snap_in = false;
+        date_range
+            .client_attributes()
+            .iter()
+            .for_each(|attribute| match attribute.name() {
+                "X-ASSET-URI" => {
+                    asset_uri = Some(Url::resolve(attribute.value(), base_url.pathname()))
+                }
+                "X-ASSET-LIST" => {
+                    asset_list = Some(Url::resolve(attribute.value(), base_url.pathname()))
+                }
+                "X-RESUME-OFFSET" => match attribute.value().parse::<f64>() {
+                    Ok(val) => resume_offset = Some(val),
+                    Err(_) => Logger::warn("Parser: Unparsable X-RESUME-OFFSET value"),
+                },
+                "X-RESTRICT" => attribute.value().split(',').for_each(|r| match r.trim() {
+                    "SKIP" => restrict_skip = true,
+                    "JUMP" => restrict_jump = true,
+                    _ => {}
+                }),
+                "X-SNAP" => attribute.value().split(',').for_each(|s| match s.trim() {
+                    "OUT" => snap_out = true,
+                    "IN" => snap_in = true,
+                    _ => {}
+                }),
+                _ => {}
+            });
+
+        let asset = match (asset_uri, asset_list) {
+            (Some(uri), None) => InterstitialAsset::Uri(uri),
+            (None, Some(list)) => InterstitialAsset::List(list),
+            _ => {
+                Logger::warn(&format!(
+                    "Parser: Interstitial \"{}\" should have either an X-ASSET-URI or an \
+                    X-ASSET-LIST attribute, ignoring it.",
+                    date_range.id()
+                ));
+                return None;
+            }
+        };
+        Some(Self {
+            id: date_range.id().to_owned(),
+            start: date_range.start(),
+            end: date_range.end(),
+            asset,
+            resume_offset,
+            restrict_skip,
+            restrict_jump,
+            snap_out,
+            snap_in,
+        })
+    }
+
+    /// `ID` of the `EXT-X-DATERANGE` tag announcing that interstitial.
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Position, in the primary content's playlist time, at which the interstitial should be
+    /// played.
+    pub(crate) fn start(&self) -> f64 {
+        self.start
+    }
+
+    /// Position, in the primary content's playlist time, at which the corresponding date range
+    /// ends, if known.
+    pub(crate) fn end(&self) -> Option<f64> {
+        self.end
+    }
+
+    /// Where the interstitial's asset(s) can be found.
+    pub(crate) fn asset(&self) -> &InterstitialAsset {
+        &self.asset
+    }
+
+    /// Offset, in seconds relative to `start`, at which the primary content should be resumed.
+    ///
+    /// If `None`, the duration of the played interstitial's asset(s) should be used instead.
+    pub(crate) fn resume_offset(&self) -> Option<f64> {
+        self.resume_offset
+    }
+
+    /// Returns `true` if seeking inside the interstitial's asset(s) should be prevented.
+    pub(crate) fn restricts_skip(&self) -> bool {
+        self.restrict_skip
+    }
+
+    /// Returns `true` if the interstitial should be played even if a seek goes over it.
+    pub(crate) fn restricts_jump(&self) -> bool {
+        self.restrict_jump
+    }
+
+    /// Returns `true` if the primary content should be left at a segment boundary.
+    pub(crate) fn snaps_out(&self) -> bool {
+        self.snap_out
+    }
+
+    /// Returns `true` if the primary content should be resumed at a segment boundary.
+    pub(crate) fn snaps_in(&self) -> bool {
+        self.snap_in
+    }
+}
+
+/// Parse the JSON object loaded from an interstitial's `X-ASSET-LIST` URL and return the URL of
+/// the Multivariant Playlist of each of the listed assets, in playback order.
+///
+/// Only the `URI` properties of the objects in its `ASSETS` array are exploited.
+pub(crate) fn parse_asset_list(
+    data: &[u8],
+    base_url: &Url,
+) -> Result<Vec<Url>, AssetListParsingError> {
+    let list = JsonValue::parse(data).ok_or(AssetListParsingError::InvalidJson)?;
+    let ret: Vec<Url> = list
+        .get("ASSETS")
+        .and_then(|assets| assets.as_array())
+        .ok_or(AssetListParsingError::MissingAssets)?
+        .iter()
+        .filter_map(|asset| asset.get("URI").and_then(|uri| uri.as_str()))
+        .map(|uri| Url::resolve(uri, base_url.pathname()))
+        .collect();
+    if ret.is_empty() {
+        Err(AssetListParsingError::MissingAssets)
+    } else {
+        Ok(ret)
+    }
+}
+
+/// Errors that may arise when parsing an interstitial's asset list.
+#[derive(Error, Debug)]
+pub(crate) enum AssetListParsingError {
+    /// The asset list was not a valid JSON object.
+    #[error("The interstitial's asset list is not valid JSON")]
+    InvalidJson,
+    /// No asset with an `URI` property was found in the asset list.
+    #[error("The interstitial's asset list does not contain any asset")]
+    MissingAssets,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_asset_list() {
+        let url = Url::new("https://example.com/ads/list.json".to_owned());
+        let assets = parse_asset_list(
+            br#"{
+              "ASSETS": [
+                { "URI": "ad1/main.m3u8", "DURATION": 15 },
+                { "DURATION": 10 },
+                { "URI": "https://cdn.test/ad2.m3u8" }
+              ]
+            }"#,
+            &url,
+        )
+        .unwrap();
+        assert_eq!(
+            assets.iter().map(|u| u.get_ref()).collect::<Vec<_>>(),
+            [
+                "https://example.com/ads/ad1/main.m3u8",
+                "https://cdn.test/ad2.m3u8"
+            ]
+        );
+        assert!(matches!(
+            parse_asset_list(br#"{"ASSETS": [{"DURATION": 10}]}"#, &url),
+            Err(AssetListParsingError::MissingAssets)
+        ));
+        assert!(matches!(
+            parse_asset_list(br#"{"ASSETS": "#, &url),
+            Err(AssetListParsingError::InvalidJson)
+        ));
+    }
+}
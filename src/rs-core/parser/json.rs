@@ -0,0 +1,188 @@
+/// A parsed JSON value, as found in the few JSON documents an HLS player has to exploit (e.g.
+/// Content Steering manifests or HLS Interstitials' asset lists).
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// Keys and values of a JSON object, in document order.
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Parse the JSON document in `data`.
+    ///
+    /// Returns `None` if `data` is not a valid UTF-8 JSON document.
+    pub(super) fn parse(data: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(data).ok()?;
+        let (value, rest) = parse_value(text)?;
+        if rest.trim_start().is_empty() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// If this is an object, returns the value of its property called `key`.
+    pub(super) fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(properties) => properties.iter().find(|p| p.0 == key).map(|p| &p.1),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_object(&self) -> Option<&[(String, JsonValue)]> {
+        match self {
+            JsonValue::Object(val) => Some(val),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the JSON value at the start of `input`, ignoring leading whitespaces.
+///
+/// Returns the parsed value and what follows it in `input`, or `None` if `input` does not
+/// start with a valid JSON value.
+fn parse_value(input: &str) -> Option<(JsonValue, &str)> {
+    let input = input.trim_start();
+    match input.as_bytes().first()? {
+        b'{' => {
+            let mut properties = vec![];
+            let mut remaining = input[1..].trim_start();
+            if let Some(rest) = remaining.strip_prefix('}') {
+                return Some((JsonValue::Object(properties), rest));
+            }
+            loop {
+                let (key, rest) = parse_string(remaining.trim_start())?;
+                let rest = rest.trim_start().strip_prefix(':')?;
+                let (value, rest) = parse_value(rest)?;
+                properties.push((key, value));
+                let rest = rest.trim_start();
+                if let Some(rest) = rest.strip_prefix(',') {
+                    remaining = rest;
+                } else {
+                    return Some((JsonValue::Object(properties), rest.strip_prefix('}')?));
+                }
+            }
+        }
+        b'[' => {
+            let mut values = vec![];
+            let mut remaining = input[1..].trim_start();
+            if let Some(rest) = remaining.strip_prefix(']') {
+                return Some((JsonValue::Array(values), rest));
+            }
+            loop {
+                let (value, rest) = parse_value(remaining)?;
+                values.push(value);
+                let rest = rest.trim_start();
+                if let Some(rest) = rest.strip_prefix(',') {
+                    remaining = rest;
+                } else {
+                    return Some((JsonValue::Array(values), rest.strip_prefix(']')?));
+                }
+            }
+        }
+        b'"' => {
+            let (value, rest) = parse_string(input)?;
+            Some((JsonValue::String(value), rest))
+        }
+        b't' => Some((JsonValue::Bool(true), input.strip_prefix("true")?)),
+        b'f' => Some((JsonValue::Bool(false), input.strip_prefix("false")?)),
+        b'n' => Some((JsonValue::Null, input.strip_prefix("null")?)),
+        _ => {
+            let end = input
+                .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+                .unwrap_or(input.len());
+            let value = input[..end].parse::<f64>().ok()?;
+            Some((JsonValue::Number(value), &input[end..]))
+        }
+    }
+}
+
+/// Parse the JSON string at the start of `input`.
+///
+/// Returns the unescaped string and what follows it in `input`, or `None` if `input` does not
+/// start with a valid JSON string.
+fn parse_string(input: &str) -> Option<(String, &str)> {
+    let mut chars = input.strip_prefix('"')?.char_indices();
+    let mut ret = String::new();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Some((ret, &input[idx + 2..])),
+            '\\' => match chars.next()?.1 {
+                'b' => ret.push('\u{8}'),
+                'f' => ret.push('\u{c}'),
+                'n' => ret.push('\n'),
+                'r' => ret.push('\r'),
+                't' => ret.push('\t'),
+                'u' => {
+                    let code: String = (0..4).filter_map(|_| chars.next().map(|c| c.1)).collect();
+                    let code = u32::from_str_radix(&code, 16).ok()?;
+                    ret.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                c => ret.push(c),
+            },
+            c => ret.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let value = JsonValue::parse(
+            br#" { "a": [1, -2.5e1, true, false, null], "b": { }, "c": "x\"\u00e9\n" } "#,
+        )
+        .unwrap();
+        assert_eq!(
+            value.get("a").and_then(|a| a.as_array()),
+            Some(
+                &[
+                    JsonValue::Number(1.),
+                    JsonValue::Number(-25.),
+                    JsonValue::Bool(true),
+                    JsonValue::Bool(false),
+                    JsonValue::Null,
+                ][..]
+            )
+        );
+        assert_eq!(value.get("b").and_then(|b| b.as_object()), Some(&[][..]));
+        assert_eq!(value.get("c").and_then(|c| c.as_str()), Some("x\"é\n"));
+        assert_eq!(value.get("d"), None);
+        assert_eq!(JsonValue::parse(b"[]"), Some(JsonValue::Array(vec![])));
+
+        assert_eq!(JsonValue::parse(b""), None);
+        assert_eq!(JsonValue::parse(b"{\"a\": 1"), None);
+        assert_eq!(JsonValue::parse(b"{\"a\" 1}"), None);
+        assert_eq!(JsonValue::parse(b"[1, 2] 3"), None);
+        assert_eq!(JsonValue::parse(b"\"abc"), None);
+        assert_eq!(JsonValue::parse(&[b'"', 0xFF, b'"']), None);
+    }
+}
@@ -6,12 +6,16 @@ use crate::{
 use std::{error, fmt, io::BufRead};
 
 use super::{
+    date_range::DateRange,
     multi_variant_playlist::MediaPlaylistContext,
+    partial_segment::{PartTag, PreloadHintTag, PreloadHintType},
+    segment_key::{KeyTag, SegmentKey},
     utils::{
         parse_byte_range, parse_decimal_floating_point, parse_decimal_integer,
         parse_enumerated_string, parse_iso_8601_date, parse_quoted_string, parse_start_attribute,
-        StartAttribute,
+        skip_attribute_list_value, StartAttribute,
     },
+    variables::{PlaylistVariables, UndefinedVariableError},
 };
 
 pub use super::utils::ByteRange;
@@ -24,12 +28,41 @@ pub(crate) struct SegmentTimeInfo {
     /// Difference between the last presentation time at which the segment contains data for
     /// in seconds and `start`.
     duration: f64,
+    /// Discontinuity sequence number of the segment, identifying the "discontinuity domain" it is
+    /// part of.
+    ///
+    /// Segments from different discontinuity domains may have completely unrelated internal
+    /// timestamps.
+    discontinuity: u64,
+    /// Media sequence number of the segment.
+    ///
+    /// For partial segments, this is the media sequence number of their parent segment.
+    media_sequence: u64,
+    /// If set, this is a partial segment (as found in Low-Latency HLS Media Playlists) and this
+    /// is its index amongst the partial segments of its parent segment, starting from `0`.
+    part_index: Option<u32>,
 }
 
 impl SegmentTimeInfo {
-    /// Create a new `SegmentTimeInfo` with the given `start` and `duration` in seconds.
-    pub(crate) fn new(start: f64, duration: f64) -> Self {
-        Self { start, duration }
+    /// Create a new `SegmentTimeInfo` with the given `start` and `duration` in seconds for the
+    /// segment with the given media sequence number, part of the discontinuity domain identified
+    /// by `discontinuity`.
+    ///
+    /// `part_index` should only be set for partial segments.
+    pub(crate) fn new(
+        start: f64,
+        duration: f64,
+        discontinuity: u64,
+        media_sequence: u64,
+        part_index: Option<u32>,
+    ) -> Self {
+        Self {
+            start,
+            duration,
+            discontinuity,
+            media_sequence,
+            part_index,
+        }
     }
 
     /// First presentation time the segment contains media data for, in seconds.
@@ -46,6 +79,24 @@ impl SegmentTimeInfo {
     pub(crate) fn duration(&self) -> f64 {
         self.duration
     }
+
+    /// Discontinuity sequence number identifying the discontinuity domain the segment is part of.
+    pub(crate) fn discontinuity(&self) -> u64 {
+        self.discontinuity
+    }
+
+    /// Media sequence number of the segment, or of its parent segment for partial segments.
+    pub(crate) fn media_sequence(&self) -> u64 {
+        self.media_sequence
+    }
+
+    /// If this is a partial segment, returns its index amongst the partial segments of its
+    /// parent segment.
+    ///
+    /// Returns `None` for full segments.
+    pub(crate) fn part_index(&self) -> Option<u32> {
+        self.part_index
+    }
 }
 
 /// List of all segments a `MediaPlaylist` is associated to.
@@ -56,13 +107,34 @@ pub(crate) struct SegmentList {
     init: Vec<InitSegmentInfo>,
     /// Initialization segments a `MediaPlaylist` is associated to, in chronological order.
     media: Vec<MediaSegmentInfo>,
+    /// Partial segments a Low-Latency `MediaPlaylist` is associated to, in chronological order.
+    ///
+    /// Those may either be linked to a media segment in `media`, or to a segment which is not yet
+    /// complete, in which case they are the only way to obtain its media data.
+    parts: Vec<MediaSegmentInfo>,
+    /// If set, the next partial segment, which is not yet available but that may already be
+    /// requested, as hinted by the `MediaPlaylist`.
+    ///
+    /// As its duration is not yet known, it is set to the part target duration.
+    preload_hint: Option<MediaSegmentInfo>,
 }
 
 impl SegmentList {
-    /// Create a new `SegmentList` linked to the given initialization segment list and media
-    /// segment list, both in chronological order.
-    fn new(init: Vec<InitSegmentInfo>, media: Vec<MediaSegmentInfo>) -> Self {
-        Self { init, media }
+    /// Create a new `SegmentList` linked to the given initialization segment list, media
+    /// segment list and partial segment list, all in chronological order, as well as to the
+    /// optional partial segment hinted to be the next one.
+    fn new(
+        init: Vec<InitSegmentInfo>,
+        media: Vec<MediaSegmentInfo>,
+        parts: Vec<MediaSegmentInfo>,
+        preload_hint: Option<MediaSegmentInfo>,
+    ) -> Self {
+        Self {
+            init,
+            media,
+            parts,
+            preload_hint,
+        }
     }
 
     /// Returns a reference to the potential initialization segment linked to the given media
@@ -76,10 +148,38 @@ impl SegmentList {
             .find(|i| i.start <= seg.time_info.start)
     }
 
+    /// Returns `true` if at least one initialization segment is associated to this
+    /// `SegmentList`.
+    pub(crate) fn has_init_segments(&self) -> bool {
+        !self.init.is_empty()
+    }
+
     /// Returns the list of media segments associated to this `SegmentList` in chronological order.
     pub(crate) fn media(&self) -> &[MediaSegmentInfo] {
         self.media.as_slice()
     }
+
+    /// Returns the list of partial segments associated to this `SegmentList` in chronological
+    /// order.
+    ///
+    /// This list is empty for non-Low-Latency `MediaPlaylist`.
+    pub(crate) fn parts(&self) -> &[MediaSegmentInfo] {
+        self.parts.as_slice()
+    }
+
+    /// Returns the partial segment which is not yet available but that may already be requested
+    /// as hinted by the `MediaPlaylist`, if one.
+    pub(crate) fn preload_hint(&self) -> Option<&MediaSegmentInfo> {
+        self.preload_hint.as_ref()
+    }
+
+    /// Returns the partial segments of the segment whose media sequence number is given in
+    /// argument, in chronological order.
+    pub(crate) fn parts_for(&self, media_sequence: u64) -> impl Iterator<Item = &MediaSegmentInfo> {
+        self.parts
+            .iter()
+            .filter(move |p| p.media_sequence() == media_sequence)
+    }
 }
 
 /// Information linked to an initialization segment.
@@ -92,6 +192,9 @@ pub(crate) struct InitSegmentInfo {
     /// If set, byte-range to specifically request only the initialization segment at the given
     /// `url`.
     byte_range: Option<ByteRange>,
+    /// If set, the initialization segment is encrypted and this is the information needed to
+    /// decrypt it.
+    key: Option<SegmentKey>,
 }
 
 impl InitSegmentInfo {
@@ -110,6 +213,12 @@ impl InitSegmentInfo {
     pub(crate) fn url(&self) -> &Url {
         &self.url
     }
+
+    /// If set, the initialization segment is encrypted and this is the information needed to
+    /// decrypt it.
+    pub(crate) fn key(&self) -> Option<&SegmentKey> {
+        self.key.as_ref()
+    }
 }
 
 /// Information linked to a single media segment.
@@ -123,6 +232,11 @@ pub(crate) struct MediaSegmentInfo {
     url: Url,
     /// If set, byte-range to specifically request only the media segment at the given `url`.
     byte_range: Option<ByteRange>,
+    /// If set, the media segment is encrypted and this is the information needed to decrypt it.
+    key: Option<SegmentKey>,
+    /// For partial segments, `true` if it contains an independent frame, from which decoding
+    /// may begin. Always `true` for full segments.
+    independent: bool,
 }
 
 impl MediaSegmentInfo {
@@ -141,6 +255,12 @@ impl MediaSegmentInfo {
         self.time_info.duration()
     }
 
+    /// Discontinuity sequence number identifying the discontinuity domain this media segment is
+    /// part of.
+    pub(crate) fn discontinuity(&self) -> u64 {
+        self.time_info.discontinuity()
+    }
+
     /// Returns reference to the whole `SegmentTimeInfo` object linked to this media segment.
     pub(crate) fn time_info(&self) -> &SegmentTimeInfo {
         &self.time_info
@@ -155,16 +275,219 @@ impl MediaSegmentInfo {
     pub(crate) fn url(&self) -> &Url {
         &self.url
     }
+
+    /// If set, the media segment is encrypted and this is the information needed to decrypt it.
+    pub(crate) fn key(&self) -> Option<&SegmentKey> {
+        self.key.as_ref()
+    }
+
+    /// Media sequence number of this media segment, or of its parent segment if this is a
+    /// partial segment.
+    pub(crate) fn media_sequence(&self) -> u64 {
+        self.time_info.media_sequence()
+    }
+
+    /// Returns `true` if this is a partial segment, as found in Low-Latency HLS Media Playlists.
+    pub(crate) fn is_part(&self) -> bool {
+        self.time_info.part_index().is_some()
+    }
+
+    /// Returns `true` if decoding may begin from this segment.
+    ///
+    /// This is always the case for full media segments, but not necessarily for partial segments.
+    pub(crate) fn is_independent(&self) -> bool {
+        self.independent
+    }
 }
 
-// #[derive(Clone, Debug)]
-// pub struct ServerControl {
-//     can_skip_until: Option<f64>,
-//     can_skip_dateranges: bool,
-//     hold_back: u32,
-//     part_hold_back: Option<u32>,
-//     can_block_reload: bool,
-// }
+/// Information parsed from the `EXT-X-SERVER-CONTROL` tag, indicating which Playlist Delivery
+/// Directives are supported by the server.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ServerControl {
+    /// If set, the server can produce Playlist Delta Updates, skipping media segments older than
+    /// this amount of seconds from the end of the Playlist.
+    can_skip_until: Option<f64>,
+    /// If `true`, the server can also skip `EXT-X-DATERANGE` tags in Playlist Delta Updates.
+    can_skip_dateranges: bool,
+    /// Minimum distance from the end of the Playlist, in seconds, at which playback should begin.
+    hold_back: Option<f64>,
+    /// For Low-Latency Media Playlists, the minimum distance from the end of the Playlist, in
+    /// seconds, at which playback should begin.
+    part_hold_back: Option<f64>,
+    /// If `true`, the server supports Blocking Playlist Reload, through the `_HLS_msn` and
+    /// `_HLS_part` query parameters.
+    can_block_reload: bool,
+}
+
+impl ServerControl {
+    /// Parse the `EXT-X-SERVER-CONTROL` tag contained in `line`, whose attribute list begins at
+    /// `offset`.
+    fn parse(line: &str, mut offset: usize) -> Self {
+        let mut server_control = ServerControl::default();
+        loop {
+            if offset >= line.len() {
+                break;
+            }
+            match line[offset..].find('=') {
+                None => {
+                    Logger::warn("Attribute Name not followed by equal sign");
+                    break;
+                }
+                Some(idx) => match &line[offset..offset + idx] {
+                    "CAN-SKIP-UNTIL" => {
+                        let (parsed, end_offset) =
+                            parse_decimal_floating_point(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed {
+                            Ok(val) => server_control.can_skip_until = Some(val),
+                            Err(_) => Logger::warn("Unparsable CAN-SKIP-UNTIL value"),
+                        }
+                    }
+                    "CAN-SKIP-DATERANGES" => {
+                        let (parsed, end_offset) = parse_enumerated_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        server_control.can_skip_dateranges = parsed == "YES";
+                    }
+                    "HOLD-BACK" => {
+                        let (parsed, end_offset) =
+                            parse_decimal_floating_point(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed {
+                            Ok(val) => server_control.hold_back = Some(val),
+                            Err(_) => Logger::warn("Unparsable HOLD-BACK value"),
+                        }
+                    }
+                    "PART-HOLD-BACK" => {
+                        let (parsed, end_offset) =
+                            parse_decimal_floating_point(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed {
+                            Ok(val) => server_control.part_hold_back = Some(val),
+                            Err(_) => Logger::warn("Unparsable PART-HOLD-BACK value"),
+                        }
+                    }
+                    "CAN-BLOCK-RELOAD" => {
+                        let (parsed, end_offset) = parse_enumerated_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        server_control.can_block_reload = parsed == "YES";
+                    }
+                    _ => offset = skip_attribute_list_value(line, offset + idx + 1) + 1,
+                },
+            }
+        }
+        server_control
+    }
+
+    /// If set, the server can produce Playlist Delta Updates, skipping media segments older than
+    /// this amount of seconds from the end of the Playlist.
+    pub(crate) fn can_skip_until(&self) -> Option<f64> {
+        self.can_skip_until
+    }
+
+    /// Returns `true` if the server can also skip `EXT-X-DATERANGE` tags in Playlist Delta
+    /// Updates.
+    pub(crate) fn can_skip_dateranges(&self) -> bool {
+        self.can_skip_dateranges
+    }
+
+    /// Minimum distance from the end of the Playlist, in seconds, at which playback should begin.
+    pub(crate) fn hold_back(&self) -> Option<f64> {
+        self.hold_back
+    }
+
+    /// For Low-Latency Media Playlists, the minimum distance from the end of the Playlist, in
+    /// seconds, at which playback should begin.
+    pub(crate) fn part_hold_back(&self) -> Option<f64> {
+        self.part_hold_back
+    }
+
+    /// Returns `true` if the server supports Blocking Playlist Reload.
+    pub(crate) fn can_block_reload(&self) -> bool {
+        self.can_block_reload
+    }
+}
+
+/// Information parsed from the `EXT-X-SKIP` tag, found in Playlist Delta Updates in place of the
+/// media segments that were skipped by the server.
+#[derive(Clone, Debug)]
+struct SkipTag {
+    /// Number of media segments replaced by this tag.
+    skipped_segments: u64,
+    /// Identifiers of `EXT-X-DATERANGE` tags that were removed from the Media Playlist since the
+    /// last Playlist Delta Update.
+    recently_removed_dateranges: Vec<String>,
+}
+
+impl SkipTag {
+    /// Parse the `EXT-X-SKIP` tag contained in `line`, whose attribute list begins at `offset`.
+    ///
+    /// Returns `None` if its mandatory `SKIPPED-SEGMENTS` attribute is missing or unparsable.
+    fn parse(line: &str, mut offset: usize) -> Option<Self> {
+        let mut skipped_segments: Option<u64> = None;
+        let mut recently_removed_dateranges = vec![];
+        loop {
+            if offset >= line.len() {
+                break;
+            }
+            match line[offset..].find('=') {
+                None => {
+                    Logger::warn("Attribute Name not followed by equal sign");
+                    break;
+                }
+                Some(idx) => match &line[offset..offset + idx] {
+                    "SKIPPED-SEGMENTS" => {
+                        let (parsed, end_offset) = parse_decimal_integer(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed {
+                            Ok(val) => skipped_segments = Some(val),
+                            Err(_) => Logger::warn("Unparsable SKIPPED-SEGMENTS value"),
+                        }
+                    }
+                    "RECENTLY-REMOVED-DATERANGES" => {
+                        let (parsed, end_offset) = parse_quoted_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed {
+                            Ok(val) => {
+                                recently_removed_dateranges = val
+                                    .split('\t')
+                                    .filter(|id| !id.is_empty())
+                                    .map(|id| id.to_owned())
+                                    .collect()
+                            }
+                            Err(_) => Logger::warn("Unparsable RECENTLY-REMOVED-DATERANGES value"),
+                        }
+                    }
+                    _ => offset = skip_attribute_list_value(line, offset + idx + 1) + 1,
+                },
+            }
+        }
+        Some(SkipTag {
+            skipped_segments: skipped_segments?,
+            recently_removed_dateranges,
+        })
+    }
+}
+
+/// Describes how a `MediaPlaylist` should be refreshed.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum MediaPlaylistRefresh {
+    /// The Media Playlist should be requested again after the given amount of milliseconds.
+    Timer(f64),
+    /// The server supports Blocking Playlist Reload: the Media Playlist can be requested right
+    /// away with the following Delivery Directives, the server only responding once the
+    /// corresponding media segment or partial segment is available.
+    Blocking {
+        /// Value for the `_HLS_msn` Delivery Directive: the media sequence number of the awaited
+        /// media segment.
+        media_sequence: u64,
+        /// Value for the `_HLS_part` Delivery Directive: for Low-Latency Media Playlists, the
+        /// index of the awaited partial segment in the awaited media segment.
+        part_index: Option<u32>,
+        /// Minimum timeout, in milliseconds, to set on that request, as the server may hold its
+        /// response for a while.
+        timeout: f64,
+    },
+}
 
 /// Errors that may arise when parsing a Media Playlist
 ///
@@ -176,6 +499,9 @@ pub enum MediaPlaylistParsingError {
     UriMissingInMap,
     MissingTargetDuration,
     UriWithoutExtInf,
+    UnparsableKey,
+    InvalidDeltaUpdate,
+    UndefinedVariable,
 }
 
 impl fmt::Display for MediaPlaylistParsingError {
@@ -196,12 +522,36 @@ impl fmt::Display for MediaPlaylistParsingError {
             MediaPlaylistParsingError::UnparsableByteRange => {
                 write!(f, "One of the uri had an Unparsable BYTERANGE")
             }
+            MediaPlaylistParsingError::UnparsableKey => {
+                write!(
+                    f,
+                    "An #EXT-X-KEY was missing either its mandatory METHOD or URI attribute"
+                )
+            }
+            MediaPlaylistParsingError::InvalidDeltaUpdate => {
+                write!(
+                    f,
+                    "An #EXT-X-SKIP was either unparsable or skipped unknown media segments"
+                )
+            }
+            MediaPlaylistParsingError::UndefinedVariable => {
+                write!(
+                    f,
+                    "A variable was referenced or imported without being defined"
+                )
+            }
         }
     }
 }
 
 impl error::Error for MediaPlaylistParsingError {}
 
+impl From<UndefinedVariableError> for MediaPlaylistParsingError {
+    fn from(_: UndefinedVariableError) -> MediaPlaylistParsingError {
+        MediaPlaylistParsingError::UndefinedVariable
+    }
+}
+
 /// Structure representing the concept of the `Media Playlist` in HLS.
 ///
 /// It lists Initialization and media segments linked to a rendition, as well as some of its
@@ -220,6 +570,8 @@ pub struct MediaPlaylist {
     target_duration: u32,
     /// Media sequence number of the first media segment in `segment_list`.
     media_sequence: u32,
+    /// Discontinuity sequence number of the first media segment in `segment_list`.
+    discontinuity_sequence: u64,
     /// If `true`,  no more Media Segments will be added to the Media Playlist file.
     end_list: bool,
     /// Mutability information about the Media Playlist file.
@@ -234,17 +586,27 @@ pub struct MediaPlaylist {
     segment_list: SegmentList,
     /// URL at which this Media Playlist may be updated.
     url: Url,
-    // TODO
-    // pub server_control: ServerControl,
-    // pub part_inf: Option<f64>,
-
-    // ignored
-    // pub discontinuity_sequence: Option<u32>;
+    /// For Low-Latency Media Playlists, the maximum duration of a partial segment, in seconds.
+    part_target: Option<f64>,
+    /// Playlist Delivery Directives supported by the server, as announced by the
+    /// `EXT-X-SERVER-CONTROL` tag.
+    server_control: ServerControl,
+    /// If `true`, this `MediaPlaylist` is a reload of a previous version of it which did not
+    /// bring any new media segment nor partial segment.
+    is_unchanged: bool,
+    /// Date ranges announced through `EXT-X-DATERANGE` tags, in chronological order.
+    date_ranges: Vec<DateRange>,
 }
 
 impl MediaPlaylist {
     /// Create a new `MediaPlaylist` object, by giving it a `BufRead` reading into its
     /// corresponding Media Playlist file from its very beginning.
+    ///
+    /// `prev_playlist` should be set to a previously-parsed `MediaPlaylist` sharing the same
+    /// timeline (generally the previous version of the same Media Playlist), if one.
+    /// When the Media Playlist has no `EXT-X-PROGRAM-DATE-TIME` tag, it will be relied on to
+    /// deduce the start time of its media segments from their media sequence number, so they
+    /// stay the same from one refresh to the next.
     pub(crate) fn create(
         playlist: impl BufRead,
         url: Url,
@@ -255,6 +617,7 @@ impl MediaPlaylist {
         let mut independent_segments = false;
         let mut target_duration: Option<u32> = None;
         let mut media_sequence = 0;
+        let mut discontinuity_sequence = 0;
         let mut end_list = false;
         let mut playlist_type = PlaylistNature::Unknown;
         let mut i_frames_only = false;
@@ -263,13 +626,48 @@ impl MediaPlaylist {
         let mut start = None;
         let mut skip_next_segment = false;
 
+        // `EXT-X-KEY` tags currently applying, one per KEYFORMAT
+        let mut curr_keys: Vec<KeyTag> = vec![];
+
+        // Number of media segments (including gaps) encountered until now, used to deduce the
+        // media sequence number of each segment.
+        let mut segment_idx: u64 = 0;
+
+        // Number of `EXT-X-DISCONTINUITY` tags encountered until now, used to deduce the
+        // discontinuity sequence number of each segment.
+        let mut discontinuity_idx: u64 = 0;
+
+        let mut part_target: Option<f64> = None;
+        let mut server_control = ServerControl::default();
+        let mut parts: Vec<MediaSegmentInfo> = vec![];
+        let mut preload_hint_tag: Option<PreloadHintTag> = None;
+
+        // Number of partial segments encountered since the last media segment, and their
+        // cumulated duration in seconds.
+        let mut part_idx: u32 = 0;
+        let mut curr_part_offset = 0.;
+        let mut current_part_byte: Option<usize> = None;
+
         let playlist_base_url = url.pathname();
 
-        let mut curr_start_time = 0.;
+        // Previous version of this same Media Playlist, if one
+        let prev_version = prev_playlist
+            .filter(|p| p.url.pathname() == url.pathname())
+            .filter(|p| p.url.filename() == url.filename());
+
+        let mut has_program_date_time = false;
+        let mut date_ranges: Vec<DateRange> = vec![];
+        // `ID` of `EXT-X-DATERANGE` tags removed since the previous Playlist Delta Update
+        let mut removed_date_ranges: Vec<String> = vec![];
+
+        // Without `EXT-X-PROGRAM-DATE-TIME` tags, start times are deduced from the previous
+        // playlist so they stay coherent from one refresh to the next.
+        let mut curr_start_time = start_time_from_prev_playlist(prev_playlist, 0);
         let mut media_segments: Vec<MediaSegmentInfo> = vec![];
         let mut next_segment_duration: Option<f64> = None;
         let mut current_byte: Option<usize> = None;
         let mut next_segment_byte_range: Option<ByteRange> = None;
+        let mut variables = PlaylistVariables::default();
 
         let lines = playlist.lines();
         for line in lines {
@@ -281,6 +679,13 @@ impl MediaPlaylist {
                     None => str_line.len(),
                     Some(idx) => idx + 4,
                 };
+                let str_line = match &str_line[4..colon_idx] {
+                    "-X-DEFINE" => {
+                        variables.parse_define(&str_line, &url, Some(context.variables()))?;
+                        continue;
+                    }
+                    _ => variables.substitute_in_tag(&str_line)?,
+                };
 
                 match &str_line[4..colon_idx] {
                     "-X-VERSION" => match parse_decimal_integer(&str_line, colon_idx + 1).0 {
@@ -323,10 +728,23 @@ impl MediaPlaylist {
                     }
                     "-X-MEDIA-SEQUENCE" => {
                         match parse_decimal_integer(&str_line, colon_idx + 1).0 {
-                            Ok(s) if s <= (u32::MAX as u64) => media_sequence = s as u32,
+                            Ok(s) if s <= (u32::MAX as u64) => {
+                                media_sequence = s as u32;
+                                if segment_idx == 0 {
+                                    curr_start_time =
+                                        start_time_from_prev_playlist(prev_playlist, s);
+                                }
+                            }
                             _ => Logger::warn("Unparsable MEDIA-SEQUENCE value"),
                         }
                     }
+                    "-X-DISCONTINUITY-SEQUENCE" => {
+                        match parse_decimal_integer(&str_line, colon_idx + 1).0 {
+                            Ok(s) => discontinuity_sequence = s,
+                            _ => Logger::warn("Unparsable DISCONTINUITY-SEQUENCE value"),
+                        }
+                    }
+                    "-X-DISCONTINUITY" => discontinuity_idx += 1,
                     "-X-PLAYLIST-TYPE" => match parse_enumerated_string(&str_line, colon_idx + 1).0
                     {
                         "EVENT" => playlist_type = PlaylistNature::Event,
@@ -338,9 +756,139 @@ impl MediaPlaylist {
                     "-X-PROGRAM-DATE-TIME" => {
                         if let Some(date) = parse_iso_8601_date(&str_line, colon_idx + 1) {
                             curr_start_time = date;
+                            has_program_date_time = true;
                         }
                     }
                     "-X-I-FRAMES-ONLY" => i_frames_only = true,
+                    "-X-PART-INF" => {
+                        let mut offset = colon_idx + 1;
+                        loop {
+                            if offset >= str_line.len() {
+                                break;
+                            }
+                            match str_line[offset..].find('=') {
+                                None => {
+                                    Logger::warn("Attribute Name not followed by equal sign");
+                                    break;
+                                }
+                                Some(idx) => match &str_line[offset..offset + idx] {
+                                    "PART-TARGET" => {
+                                        let (parsed, end_offset) = parse_decimal_floating_point(
+                                            &str_line,
+                                            offset + idx + 1,
+                                        );
+                                        offset = end_offset + 1;
+                                        match parsed {
+                                            Ok(val) => part_target = Some(val),
+                                            Err(_) => Logger::warn("Unparsable PART-TARGET value"),
+                                        }
+                                    }
+                                    _ => {
+                                        offset =
+                                            skip_attribute_list_value(&str_line, offset + idx + 1)
+                                                + 1
+                                    }
+                                },
+                            }
+                        }
+                    }
+                    "-X-SERVER-CONTROL" => {
+                        server_control = ServerControl::parse(&str_line, colon_idx + 1);
+                    }
+                    "-X-SKIP" => {
+                        let skip = SkipTag::parse(&str_line, colon_idx + 1)
+                            .ok_or(MediaPlaylistParsingError::InvalidDeltaUpdate)?;
+                        let prev_version =
+                            prev_version.ok_or(MediaPlaylistParsingError::InvalidDeltaUpdate)?;
+                        let first_skipped = media_sequence as u64 + segment_idx;
+                        let next_after_skip = first_skipped + skip.skipped_segments;
+                        if u64::from(prev_version.media_sequence) > first_skipped
+                            || prev_version.last_media_sequence().unwrap_or(0) + 1 < next_after_skip
+                        {
+                            return Err(MediaPlaylistParsingError::InvalidDeltaUpdate);
+                        }
+
+                        // Re-use the skipped media segments from the previous version
+                        let skipped = prev_version.segment_list.media.iter().filter(|s| {
+                            s.media_sequence() >= first_skipped
+                                && s.media_sequence() < next_after_skip
+                        });
+                        for seg in skipped {
+                            if let Some(init) = prev_version.segment_list.init_for(seg) {
+                                if !maps_info.iter().any(|i| i.start == init.start) {
+                                    maps_info.push(init.clone());
+                                }
+                            }
+                            discontinuity_idx = seg
+                                .time_info
+                                .discontinuity
+                                .saturating_sub(discontinuity_sequence);
+                            current_byte = seg.byte_range.as_ref().map(|br| br.last_byte + 1);
+                            // The keys of the last skipped segment keep applying after it
+                            curr_keys = seg
+                                .key
+                                .iter()
+                                .map(|k| KeyTag::from_segment_key(k, seg.media_sequence()))
+                                .collect();
+                            media_segments.push(seg.clone());
+                        }
+                        segment_idx += skip.skipped_segments;
+                        curr_start_time =
+                            start_time_from_prev_playlist(Some(prev_version), next_after_skip);
+                        removed_date_ranges = skip.recently_removed_dateranges;
+                    }
+                    "-X-DATERANGE" => match DateRange::parse(&str_line) {
+                        Ok(mut date_range) => {
+                            // Tags with the same ID complete each other
+                            match date_ranges.iter_mut().find(|d| d.id() == date_range.id()) {
+                                Some(existing) => {
+                                    date_range.complete_with(existing);
+                                    *existing = date_range;
+                                }
+                                None => date_ranges.push(date_range),
+                            }
+                        }
+                        Err(e) => {
+                            Logger::warn(&format!("Ignoring unparsable EXT-X-DATERANGE: {:?}", e))
+                        }
+                    },
+                    "-X-PART" => {
+                        match PartTag::parse(&str_line, playlist_base_url, current_part_byte) {
+                            Ok(part) => {
+                                let media_sequence = media_sequence as u64 + segment_idx;
+                                current_part_byte = part.byte_range().map(|br| br.last_byte + 1);
+                                if !part.is_gap() {
+                                    parts.push(MediaSegmentInfo {
+                                        time_info: SegmentTimeInfo::new(
+                                            curr_start_time + curr_part_offset,
+                                            part.duration(),
+                                            discontinuity_sequence + discontinuity_idx,
+                                            media_sequence,
+                                            Some(part_idx),
+                                        ),
+                                        url: part.url().clone(),
+                                        byte_range: part.byte_range().cloned(),
+                                        key: key_for_segment(&curr_keys, media_sequence),
+                                        independent: part.is_independent(),
+                                    });
+                                }
+                                curr_part_offset += part.duration();
+                            }
+                            Err(e) => {
+                                Logger::warn(&format!("Ignoring unparsable EXT-X-PART: {:?}", e))
+                            }
+                        }
+                        part_idx += 1;
+                    }
+                    "-X-PRELOAD-HINT" => {
+                        match PreloadHintTag::parse(&str_line, playlist_base_url) {
+                            Ok(hint) => preload_hint_tag = Some(hint),
+                            Err(e) => Logger::warn(&format!(
+                                "Ignoring unparsable EXT-X-PRELOAD-HINT: {:?}",
+                                e
+                            )),
+                        }
+                    }
                     "-X-MAP" => {
                         let mut map_info_url: Option<Url> = None;
                         let mut map_info_byte_range: Option<ByteRange> = None;
@@ -360,13 +908,8 @@ impl MediaPlaylist {
                                             parse_quoted_string(&str_line, base_offset + idx + 1);
                                         base_offset = end_offset + 1;
                                         if let Ok(val) = parsed {
-                                            let init_url = Url::new(val.to_owned());
-                                            let init_url = if init_url.is_absolute() {
-                                                init_url
-                                            } else {
-                                                Url::from_relative(playlist_base_url, init_url)
-                                            };
-                                            map_info_url = Some(init_url);
+                                            map_info_url =
+                                                Some(Url::resolve(val, playlist_base_url));
                                         }
                                     }
 
@@ -391,11 +934,24 @@ impl MediaPlaylist {
                             }
                         }
                         if let Some(url) = map_info_url {
-                            last_incomplete_map = Some((url, map_info_byte_range));
+                            let key =
+                                key_for_segment(&curr_keys, media_sequence as u64 + segment_idx);
+                            last_incomplete_map = Some((url, map_info_byte_range, key));
                         } else {
                             return Err(MediaPlaylistParsingError::UriMissingInMap);
                         }
                     }
+                    "-X-KEY" => match KeyTag::parse(&str_line, playlist_base_url) {
+                        Ok(key) => {
+                            if key.is_none() {
+                                curr_keys.clear();
+                            } else {
+                                curr_keys.retain(|k| k.key_format() != key.key_format());
+                                curr_keys.push(key);
+                            }
+                        }
+                        Err(_) => return Err(MediaPlaylistParsingError::UnparsableKey),
+                    },
                     "M3U" => {}
                     x => Logger::debug(&format!("Unrecognized tag: \"{}\"", x)),
                 }
@@ -403,7 +959,10 @@ impl MediaPlaylist {
                 continue;
             } else if skip_next_segment {
                 skip_next_segment = false;
+                part_idx = 0;
+                curr_part_offset = 0.;
                 if let Some(duration) = next_segment_duration {
+                    segment_idx += 1;
                     curr_start_time += duration;
                     next_segment_duration = None;
                     next_segment_byte_range = None;
@@ -412,19 +971,25 @@ impl MediaPlaylist {
                 }
             } else {
                 // URI
-                let seg_url = Url::new(str_line);
-                let seg_url = if seg_url.is_absolute() {
-                    seg_url
-                } else {
-                    Url::from_relative(playlist_base_url, seg_url)
-                };
+                let seg_url = Url::resolve(&variables.substitute(&str_line)?, playlist_base_url);
                 if let Some(duration) = next_segment_duration {
                     let seg = MediaSegmentInfo {
-                        time_info: SegmentTimeInfo::new(curr_start_time, duration),
+                        time_info: SegmentTimeInfo::new(
+                            curr_start_time,
+                            duration,
+                            discontinuity_sequence + discontinuity_idx,
+                            media_sequence as u64 + segment_idx,
+                            None,
+                        ),
                         byte_range: next_segment_byte_range,
                         url: seg_url,
+                        key: key_for_segment(&curr_keys, media_sequence as u64 + segment_idx),
+                        independent: true,
                     };
-                    if let Some((url, byte_range)) = last_incomplete_map {
+                    segment_idx += 1;
+                    part_idx = 0;
+                    curr_part_offset = 0.;
+                    if let Some((url, byte_range, key)) = last_incomplete_map {
                         last_incomplete_map = None;
                         let init_start = prev_playlist
                             .and_then(|p| {
@@ -435,11 +1000,15 @@ impl MediaPlaylist {
                                     .map(|i| i.start)
                             })
                             .unwrap_or(curr_start_time);
-                        maps_info.push(InitSegmentInfo {
-                            start: init_start,
-                            url,
-                            byte_range,
-                        });
+                        // May already be there if it was inherited through an `EXT-X-SKIP` tag
+                        if !maps_info.iter().any(|i| i.start == init_start) {
+                            maps_info.push(InitSegmentInfo {
+                                start: init_start,
+                                url,
+                                byte_range,
+                                key,
+                            });
+                        }
                     }
                     media_segments.push(seg);
                     curr_start_time += duration;
@@ -467,20 +1036,86 @@ impl MediaPlaylist {
         if playlist_type == PlaylistNature::Unknown && !end_list {
             playlist_type = PlaylistNature::Live;
         }
+
+        if !has_program_date_time && !date_ranges.is_empty() {
+            // Dates cannot be converted into playlist time without `EXT-X-PROGRAM-DATE-TIME`
+            Logger::warn("Ignoring EXT-X-DATERANGE tags in a playlist without PROGRAM-DATE-TIME");
+            date_ranges.clear();
+        }
+        if let Some(prev_version) = prev_version {
+            // Keep date ranges from the previous version which may still be relevant, so they
+            // stay known even when they stop being announced
+            let min_time = media_segments.first().map(|s| s.start()).unwrap_or(0.);
+            for prev_range in prev_version.date_ranges.iter() {
+                match date_ranges.iter_mut().find(|d| d.id() == prev_range.id()) {
+                    Some(date_range) => date_range.complete_with(prev_range),
+                    None => {
+                        let is_removed = removed_date_ranges.iter().any(|id| id == prev_range.id());
+                        let end = prev_range.end().unwrap_or(prev_range.start());
+                        if !is_removed && end >= min_time {
+                            date_ranges.push(prev_range.clone());
+                        }
+                    }
+                }
+            }
+        }
+        date_ranges.sort_by(|a, b| a.start().total_cmp(&b.start()));
+        for i in 0..date_ranges.len() {
+            if date_ranges[i].ends_on_next() {
+                let next_start = date_ranges[i + 1..]
+                    .iter()
+                    .find(|d| d.class() == date_ranges[i].class())
+                    .map(|d| d.start());
+                if let Some(next_start) = next_start {
+                    date_ranges[i].set_end_if_unknown(next_start);
+                }
+            }
+        }
+
+        let preload_hint = match (preload_hint_tag, part_target) {
+            (Some(hint), _) if hint.typ() == PreloadHintType::Map => {
+                Logger::debug("Parser: Ignoring EXT-X-PRELOAD-HINT of TYPE MAP");
+                None
+            }
+            (Some(hint), _) if hint.has_unbounded_byte_range() => {
+                Logger::debug("Parser: Ignoring EXT-X-PRELOAD-HINT without BYTERANGE-LENGTH");
+                None
+            }
+            (Some(hint), Some(part_target)) => {
+                let media_sequence = media_sequence as u64 + segment_idx;
+                Some(MediaSegmentInfo {
+                    time_info: SegmentTimeInfo::new(
+                        curr_start_time + curr_part_offset,
+                        part_target,
+                        discontinuity_sequence + discontinuity_idx,
+                        media_sequence,
+                        Some(part_idx),
+                    ),
+                    url: hint.url().clone(),
+                    byte_range: hint.byte_range().cloned(),
+                    key: key_for_segment(&curr_keys, media_sequence),
+                    // We cannot know yet, consider the worst case
+                    independent: false,
+                })
+            }
+            _ => None,
+        };
         Ok(MediaPlaylist {
             version,
             independent_segments,
             start,
             target_duration,
             media_sequence,
+            discontinuity_sequence,
             end_list,
             playlist_type,
             i_frames_only,
-            segment_list: SegmentList::new(maps_info, media_segments),
+            segment_list: SegmentList::new(maps_info, media_segments, parts, preload_hint),
             url,
-            // TODO
-            // server_control,
-            // part_inf,
+            part_target,
+            server_control,
+            is_unchanged: false,
+            date_ranges,
         })
     }
 
@@ -513,24 +1148,138 @@ impl MediaPlaylist {
             })
     }
 
-    /// Returns the amount of milliseconds at which the Media Playlist should probably be requested.
+    /// Returns how the Media Playlist should next be refreshed.
+    ///
+    /// If the server supports Blocking Playlist Reload, the Media Playlist should be requested
+    /// right away with the returned Delivery Directives. If not, it should be requested after a
+    /// timer.
+    ///
+    /// Returns `None` if this `MediaPlaylist` should never be refreshed.
+    pub(crate) fn next_refresh(&self) -> Option<MediaPlaylistRefresh> {
+        if !self.may_be_refreshed() {
+            return None;
+        }
+        if self.is_unchanged {
+            // As indicated by the HLS specification, wait for half the target duration when a
+            // reload did not change the Media Playlist.
+            // A server supporting Blocking Playlist Reload should not have answered before the
+            // awaited segment was available, so also wait before retrying in that case, though
+            // only for half a part target for Low-Latency Media Playlists.
+            let delay = match self.part_target {
+                Some(part_target) if self.server_control.can_block_reload => part_target * 500.,
+                _ => f64::from(self.target_duration) * 500.,
+            };
+            return Some(MediaPlaylistRefresh::Timer(delay));
+        }
+        self.blocking_refresh()
+            .or_else(|| self.refresh_interval().map(MediaPlaylistRefresh::Timer))
+    }
+
+    /// Returns the Blocking Playlist Reload through which the Media Playlist should be refreshed
+    /// right away.
+    ///
+    /// Returns `None` if the server does not support Blocking Playlist Reload or if that
+    /// `MediaPlaylist` should never be refreshed.
+    pub(crate) fn blocking_refresh(&self) -> Option<MediaPlaylistRefresh> {
+        if !self.server_control.can_block_reload || !self.may_be_refreshed() {
+            return None;
+        }
+        let (media_sequence, part_index) = self.next_delivery_directives()?;
+        Some(MediaPlaylistRefresh::Blocking {
+            media_sequence,
+            part_index,
+            // The HLS specification asks for a timeout of at least three times the target
+            // duration
+            timeout: f64::from(self.target_duration) * 3000.,
+        })
+    }
+
+    /// Returns the media sequence number and, for Low-Latency Media Playlists, the partial
+    /// segment index, of the first media segment or partial segment not yet announced by this
+    /// `MediaPlaylist`.
+    ///
+    /// Returns `None` if it doesn't reference any media segment or partial segment.
+    fn next_delivery_directives(&self) -> Option<(u64, Option<u32>)> {
+        let last_media_sequence = self.last_media_sequence();
+        if self.part_target.is_none() {
+            return last_media_sequence.map(|msn| (msn + 1, None));
+        }
+        match self.segment_list.parts().last() {
+            Some(part) if Some(part.media_sequence()) > last_media_sequence => {
+                let part_index = part.time_info().part_index().map(|idx| idx + 1);
+                Some((part.media_sequence(), part_index))
+            }
+            _ => last_media_sequence.map(|msn| (msn + 1, Some(0))),
+        }
+    }
+
+    /// Compare this `MediaPlaylist` to `prev`, its previously-loaded version, to know whether
+    /// that reload brought any new media segment or partial segment.
+    pub(super) fn compare_to_previous_version(&mut self, prev: &MediaPlaylist) {
+        let last_part_id = |pl: &MediaPlaylist| {
+            pl.segment_list
+                .parts()
+                .last()
+                .map(|p| (p.media_sequence(), p.time_info().part_index()))
+        };
+        self.is_unchanged = self.last_media_sequence() == prev.last_media_sequence()
+            && last_part_id(self) == last_part_id(prev)
+            && self.end_list == prev.end_list;
+    }
+
+    /// Returns the minimum distance from the end of the Playlist, in seconds, at which playback
+    /// should begin, as announced by the server.
+    ///
+    /// For Low-Latency Media Playlists, this is the distance recommended when playing partial
+    /// segments.
+    pub(crate) fn hold_back(&self) -> Option<f64> {
+        if self.part_target.is_some() {
+            self.server_control
+                .part_hold_back()
+                .or(self.server_control.hold_back())
+        } else {
+            self.server_control.hold_back()
+        }
+    }
+
+    /// Returns the date ranges announced by this `MediaPlaylist` in chronological order.
+    pub(crate) fn date_ranges(&self) -> &[DateRange] {
+        self.date_ranges.as_slice()
+    }
+
+    /// Returns the Playlist Delivery Directives supported by the server, as announced by the
+    /// `EXT-X-SERVER-CONTROL` tag.
+    pub(crate) fn server_control(&self) -> &ServerControl {
+        &self.server_control
+    }
+
+    /// Returns the amount of milliseconds at which the Media Playlist should probably be requested
+    /// when the server doesn't support Blocking Playlist Reload.
     ///
     /// This value may change each time the Media Playlist is updated.
     ///
     /// Returns `None` if this `MediaPlaylist` should never be refreshed.
     pub(crate) fn refresh_interval(&self) -> Option<f64> {
-        if self.may_be_refreshed() {
-            Some(
-                self.segment_list
-                    .media()
-                    .last()
-                    .map(|s| s.duration() * 1.1)
-                    .unwrap_or(f64::from(self.target_duration / 2))
-                    * 1000.,
-            )
-        } else {
-            None
+        if !self.may_be_refreshed() {
+            return None;
+        }
+        if let Some(part_target) = self.part_target {
+            // Low-Latency playlists are updated each time a new partial segment is available
+            return Some(part_target * 1000.);
         }
+        Some(
+            self.segment_list
+                .media()
+                .last()
+                .map(|s| s.duration() * 1.1)
+                .unwrap_or(f64::from(self.target_duration / 2))
+                * 1000.,
+        )
+    }
+
+    /// Discontinuity sequence number of the first media segment of this Media Playlist.
+    pub(crate) fn discontinuity_sequence(&self) -> u64 {
+        self.discontinuity_sequence
     }
 
     /// Returns `SegmentList` associated to this `MediaPlaylist` allowing to check which
@@ -544,6 +1293,48 @@ impl MediaPlaylist {
         self.target_duration as f64
     }
 
+    /// For Low-Latency Media Playlists, returns the upper bound on the duration of all partial
+    /// segments in the Playlist, in seconds.
+    ///
+    /// Returns `None` for other Media Playlists.
+    pub(crate) fn part_target_duration(&self) -> Option<f64> {
+        self.part_target
+    }
+
+    /// Returns the media sequence number of the last media segment referenced in that
+    /// `MediaPlaylist`.
+    ///
+    /// Returns `None` if it doesn't reference any media segment.
+    pub(crate) fn last_media_sequence(&self) -> Option<u64> {
+        self.segment_list.media().last().map(|s| s.media_sequence())
+    }
+
+    /// Returns the estimated start time, in seconds, of the media segment whose media sequence
+    /// number is given in argument, based on the media segments of this `MediaPlaylist`.
+    ///
+    /// If that media segment is not referenced in it, its start is extrapolated from the
+    /// closest preceding (or first) media segment by considering that missing segments all
+    /// have a duration equal to the target duration.
+    ///
+    /// Returns `None` if this `MediaPlaylist` doesn't reference any media segment.
+    fn estimated_start_for_media_sequence(&self, media_sequence: u64) -> Option<f64> {
+        let media = self.segment_list.media();
+        let target_duration = self.target_duration as f64;
+        match media
+            .iter()
+            .rev()
+            .find(|s| s.media_sequence() <= media_sequence)
+        {
+            Some(seg) if seg.media_sequence() == media_sequence => Some(seg.start()),
+            Some(seg) => Some(
+                seg.end() + (media_sequence - seg.media_sequence() - 1) as f64 * target_duration,
+            ),
+            None => media.first().map(|first| {
+                first.start() - (first.media_sequence() - media_sequence) as f64 * target_duration
+            }),
+        }
+    }
+
     /// Returns the start time of the first media segment referenced in that `MediaPlaylist`, in
     /// seconds.
     pub(crate) fn beginning(&self) -> Option<f64> {
@@ -552,8 +1343,16 @@ impl MediaPlaylist {
 
     /// Returns the ending time of the last media segment referenced in that `MediaPlaylist`, in
     /// seconds.
+    ///
+    /// For Low-Latency Media Playlists, partial segments of a segment which is not yet complete
+    /// are also considered.
     pub(crate) fn ending(&self) -> Option<f64> {
-        self.segment_list.media().last().map(|s| s.end())
+        let media_end = self.segment_list.media().last().map(|s| s.end());
+        let parts_end = self.segment_list.parts().last().map(|s| s.end());
+        match (media_end, parts_end) {
+            (Some(m), Some(p)) => Some(f64::max(m, p)),
+            (m, p) => m.or(p),
+        }
     }
 
     /// Returns `true` if the `MediaPlaylist` may need to be refreshed later, `false` if it should
@@ -614,11 +1413,47 @@ impl MediaPlaylist {
         }
     }
 
+    /// Returns the formats its media segments most likely rely on, in the syntax of the `CODECS`
+    /// attribute, guessed from their extension.
+    ///
+    /// This is only useful when those formats are not announced by a Multivariant Playlist, in
+    /// which case the most common formats for that container are returned (AVC video with AAC
+    /// audio for MPEG-4 and MPEG-2 TS segments).
+    /// As those containers may hold other formats, this is only a placeholder until the actual
+    /// formats are read from its initialization segments or, for MPEG-2 TS, from its first media
+    /// segment.
+    ///
+    /// Returns an empty `Vec` if unknown.
+    pub(crate) fn guessed_codecs(&self) -> Vec<&'static str> {
+        match self.extension() {
+            Some("mp4a" | "m4a" | "cmfa" | "aac") => vec!["mp4a.40.2"],
+            Some("ac3") => vec!["ac-3"],
+            Some("ec3") => vec!["ec-3"],
+            Some("mp3") => vec!["mp3"],
+            Some("mp4" | "mp4v" | "m4s" | "m4i" | "m4v" | "m4f" | "cmfv" | "ts") => {
+                vec!["avc1.4D401F", "mp4a.40.2"]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Returns `true` if its media segments are MPEG-2 Transport Stream segments, which
+    /// announce the formats they contain in each of them.
+    pub(crate) fn has_mpeg_ts_segments(&self) -> bool {
+        self.extension() == Some("ts")
+    }
+
     /// Returns the URL at which this Media Playlist may be requested.
-    pub(super) fn url(&self) -> &Url {
+    pub(crate) fn url(&self) -> &Url {
         &self.url
     }
 
+    /// Update the URL at which this Media Playlist may be requested, e.g. because a refreshed
+    /// Multivariant Playlist announced another one for it.
+    pub(super) fn update_url(&mut self, url: Url) {
+        self.url = url;
+    }
+
     /// Returns the "extension" part of the media segments referenced in this Media Playlist (e.g.
     /// `"mp4"` for `.mp4` files).
     ///
@@ -637,3 +1472,491 @@ impl MediaPlaylist {
             .find(|s| s.end() > pos && s.start() <= pos)
     }
 }
+
+/// Returns the `SegmentKey` which should be considered for a segment, based on the `EXT-X-KEY` tags
+/// currently applying to it and on its media sequence number.
+///
+/// If multiple `EXT-X-KEY` tags apply with different `KEYFORMAT`, the `"identity"` one (which is
+/// the one we can handle ourselves) is preferred.
+fn key_for_segment(curr_keys: &[KeyTag], media_sequence: u64) -> Option<SegmentKey> {
+    curr_keys
+        .iter()
+        .find(|k| k.key_format() == "identity")
+        .or_else(|| curr_keys.first())
+        .and_then(|k| k.segment_key(media_sequence))
+}
+
+/// Returns the start time, in seconds, that should be given to the media segment with the given
+/// media sequence number, based on the optional previous `MediaPlaylist`.
+///
+/// Returns `0.` if there's no previous `MediaPlaylist` or if it doesn't contain any segment.
+fn start_time_from_prev_playlist(
+    prev_playlist: Option<&MediaPlaylist>,
+    media_sequence: u64,
+) -> f64 {
+    prev_playlist
+        .and_then(|p| p.estimated_start_for_media_sequence(media_sequence))
+        .unwrap_or(0.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::logger::LoggerLevel;
+
+    fn create(
+        playlist: &str,
+        prev: Option<&MediaPlaylist>,
+    ) -> Result<MediaPlaylist, MediaPlaylistParsingError> {
+        MediaPlaylist::create(
+            playlist.as_bytes(),
+            Url::new("https://example.com/media.m3u8".to_owned()),
+            prev,
+            &MediaPlaylistContext::default(),
+        )
+    }
+
+    fn segment_urls(playlist: &MediaPlaylist) -> Vec<&str> {
+        playlist
+            .segment_list()
+            .media()
+            .iter()
+            .map(|s| s.url().get_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_start_time_carry_over() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let starts = |playlist: &MediaPlaylist| -> Vec<(u64, f64)> {
+            let media = playlist.segment_list().media();
+            media
+                .iter()
+                .map(|s| (s.media_sequence(), s.start()))
+                .collect()
+        };
+        let prev = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:10
+#EXTINF:4,
+seg10.ts
+#EXTINF:3.5,
+seg11.ts
+#EXTINF:4,
+seg12.ts
+",
+            None,
+        )
+        .unwrap();
+        assert_eq!(starts(&prev), vec![(10, 0.), (11, 4.), (12, 7.5)]);
+
+        // Segments still present keep their start time after a refresh
+        let refreshed = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:11
+#EXTINF:3.5,
+seg11.ts
+#EXTINF:4,
+seg12.ts
+#EXTINF:4,
+seg13.ts
+",
+            Some(&prev),
+        )
+        .unwrap();
+        assert_eq!(starts(&refreshed), vec![(11, 4.), (12, 7.5), (13, 11.5)]);
+
+        // The Media Playlist of another variant is aligned on the same media sequence numbers,
+        // segments not known yet being estimated from the target duration
+        let switched = MediaPlaylist::create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:12
+#EXTINF:4,
+high12.ts
+"
+            .as_bytes(),
+            Url::new("https://example.com/high.m3u8".to_owned()),
+            Some(&refreshed),
+            &MediaPlaylistContext::default(),
+        )
+        .unwrap();
+        assert_eq!(starts(&switched), vec![(12, 7.5)]);
+        let after_gap = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:15
+#EXTINF:4,
+seg15.ts
+",
+            Some(&refreshed),
+        )
+        .unwrap();
+        assert_eq!(starts(&after_gap), vec![(15, 19.5)]);
+        let before = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:8
+#EXTINF:4,
+seg8.ts
+",
+            Some(&refreshed),
+        )
+        .unwrap();
+        assert_eq!(starts(&before), vec![(8, -8.)]);
+
+        // Without a previous Media Playlist, the first segment starts at `0`
+        let first = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:12
+#EXTINF:4,
+seg12.ts
+",
+            None,
+        )
+        .unwrap();
+        assert_eq!(starts(&first), vec![(12, 0.)]);
+    }
+
+    #[test]
+    fn test_next_refresh() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let timer_delay = |refresh: Option<MediaPlaylistRefresh>| match refresh {
+            Some(MediaPlaylistRefresh::Timer(delay)) => delay,
+            _ => panic!("Unexpected refresh: {refresh:?}"),
+        };
+        let blocking = |media_sequence, part_index| {
+            Some(MediaPlaylistRefresh::Blocking {
+                media_sequence,
+                part_index,
+                timeout: 12000.,
+            })
+        };
+        let create_unchanged = |playlist: &str| {
+            let prev = create(playlist, None).unwrap();
+            let mut refreshed = create(playlist, Some(&prev)).unwrap();
+            refreshed.compare_to_previous_version(&prev);
+            refreshed
+        };
+        let segments = "#EXT-X-MEDIA-SEQUENCE:10
+#EXTINF:4,
+seg10.ts
+#EXTINF:2,
+seg11.ts
+";
+
+        // Without Blocking Playlist Reload, a timer based on the last segment's duration is used
+        let playlist = format!("#EXTM3U\n#EXT-X-TARGETDURATION:4\n{segments}");
+        let parsed = create(&playlist, None).unwrap();
+        assert_eq!(parsed.blocking_refresh(), None);
+        assert!((timer_delay(parsed.next_refresh()) - 2200.).abs() < 1e-6);
+        // Half the target duration is awaited when the Playlist did not change
+        let parsed = create_unchanged(&playlist);
+        assert_eq!(timer_delay(parsed.next_refresh()), 2000.);
+        let parsed = create(&format!("{playlist}#EXT-X-ENDLIST\n"), None).unwrap();
+        assert_eq!(parsed.next_refresh(), None);
+
+        // With Blocking Playlist Reload, the next segment is awaited
+        let playlist = format!(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-SERVER-CONTROL:CAN-BLOCK-RELOAD=YES
+{segments}"
+        );
+        let parsed = create(&playlist, None).unwrap();
+        assert_eq!(parsed.blocking_refresh(), blocking(12, None));
+        assert_eq!(parsed.next_refresh(), blocking(12, None));
+        let parsed = create_unchanged(&playlist);
+        assert_eq!(timer_delay(parsed.next_refresh()), 2000.);
+        assert_eq!(parsed.blocking_refresh(), blocking(12, None));
+
+        // For Low-Latency playlists, the next partial segment is awaited
+        let ll_playlist = format!(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-PART-INF:PART-TARGET=1
+#EXT-X-SERVER-CONTROL:CAN-BLOCK-RELOAD=YES
+{segments}#EXT-X-PART:DURATION=1,URI=\"p12.0.ts\",INDEPENDENT=YES
+#EXT-X-PART:DURATION=1,URI=\"p12.1.ts\"
+"
+        );
+        let parsed = create(&ll_playlist, None).unwrap();
+        assert_eq!(parsed.next_refresh(), blocking(12, Some(2)));
+        // Only half a part target is awaited when the Playlist did not change
+        let parsed = create_unchanged(&ll_playlist);
+        assert_eq!(timer_delay(parsed.next_refresh()), 500.);
+        // The first part of the next segment is awaited when the last one is complete
+        let parsed = create(&format!("{playlist}#EXT-X-PART-INF:PART-TARGET=1\n"), None).unwrap();
+        assert_eq!(parsed.next_refresh(), blocking(12, Some(0)));
+
+        // Without Blocking Playlist Reload, Low-Latency playlists are refreshed each part target
+        let ll_playlist = format!(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-PART-INF:PART-TARGET=1
+{segments}#EXT-X-PART:DURATION=1,URI=\"p12.0.ts\",INDEPENDENT=YES
+"
+        );
+        let parsed = create(&ll_playlist, None).unwrap();
+        assert_eq!(parsed.blocking_refresh(), None);
+        assert_eq!(timer_delay(parsed.next_refresh()), 1000.);
+        let parsed = create_unchanged(&ll_playlist);
+        assert_eq!(timer_delay(parsed.next_refresh()), 2000.);
+    }
+
+    #[test]
+    fn test_delta_update() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let prev = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-SERVER-CONTROL:CAN-SKIP-UNTIL=12
+#EXT-X-MEDIA-SEQUENCE:10
+#EXTINF:4,
+seg10.ts
+#EXTINF:4,
+seg11.ts
+#EXTINF:4,
+seg12.ts
+#EXTINF:4,
+seg13.ts
+",
+            None,
+        )
+        .unwrap();
+
+        // The skipped segments are the ones of the previous version
+        let delta = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-SERVER-CONTROL:CAN-SKIP-UNTIL=12
+#EXT-X-MEDIA-SEQUENCE:11
+#EXT-X-SKIP:SKIPPED-SEGMENTS=2
+#EXTINF:4,
+seg13.ts
+#EXTINF:4,
+seg14.ts
+",
+            Some(&prev),
+        )
+        .unwrap();
+        assert_eq!(
+            segment_urls(&delta),
+            vec![
+                "https://example.com/seg11.ts",
+                "https://example.com/seg12.ts",
+                "https://example.com/seg13.ts",
+                "https://example.com/seg14.ts",
+            ]
+        );
+        let media = delta.segment_list().media();
+        let sequences: Vec<u64> = media.iter().map(|s| s.media_sequence()).collect();
+        assert_eq!(sequences, vec![11, 12, 13, 14]);
+        let starts: Vec<f64> = media.iter().map(|s| s.start()).collect();
+        assert_eq!(starts, vec![4., 8., 12., 16.]);
+
+        // Segments before the first one of the previous version are skipped
+        let delta = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:9
+#EXT-X-SKIP:SKIPPED-SEGMENTS=3
+#EXTINF:4,
+seg12.ts
+",
+            Some(&prev),
+        );
+        assert!(matches!(
+            delta,
+            Err(MediaPlaylistParsingError::InvalidDeltaUpdate)
+        ));
+
+        // Segments after the last one of the previous version are skipped
+        let delta = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:11
+#EXT-X-SKIP:SKIPPED-SEGMENTS=4
+#EXTINF:4,
+seg15.ts
+",
+            Some(&prev),
+        );
+        assert!(matches!(
+            delta,
+            Err(MediaPlaylistParsingError::InvalidDeltaUpdate)
+        ));
+
+        // No previous version of that Media Playlist
+        let delta = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:11
+#EXT-X-SKIP:SKIPPED-SEGMENTS=2
+#EXTINF:4,
+seg13.ts
+",
+            None,
+        );
+        assert!(matches!(
+            delta,
+            Err(MediaPlaylistParsingError::InvalidDeltaUpdate)
+        ));
+
+        // `EXT-X-KEY` tags of skipped segments still apply to the following ones
+        let prev = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-SERVER-CONTROL:CAN-SKIP-UNTIL=12
+#EXT-X-MEDIA-SEQUENCE:10
+#EXTINF:4,
+seg10.ts
+#EXT-X-KEY:METHOD=AES-128,URI=\"key1\"
+#EXTINF:4,
+seg11.ts
+#EXT-X-KEY:METHOD=AES-128,URI=\"key2\",IV=0x0A
+#EXTINF:4,
+seg12.ts
+#EXTINF:4,
+seg13.ts
+",
+            None,
+        )
+        .unwrap();
+        let delta = |skipped_segments: u64| {
+            create(
+                &format!(
+                    "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-SERVER-CONTROL:CAN-SKIP-UNTIL=12
+#EXT-X-MEDIA-SEQUENCE:10
+#EXT-X-SKIP:SKIPPED-SEGMENTS={skipped_segments}
+#EXTINF:4,
+seg{}.ts
+",
+                    10 + skipped_segments
+                ),
+                Some(&prev),
+            )
+            .unwrap()
+        };
+        let last_key = |playlist: &MediaPlaylist| {
+            let key = playlist.segment_list().media().last().unwrap().key.clone();
+            key.map(|k| (k.url().get_ref().to_owned(), k.iv()[15]))
+        };
+        let key1 = "https://example.com/key1".to_owned();
+        let key2 = "https://example.com/key2".to_owned();
+        assert_eq!(last_key(&delta(1)), None);
+        assert_eq!(last_key(&delta(2)), Some((key1, 12)));
+        assert_eq!(last_key(&delta(3)), Some((key2.clone(), 10)));
+        assert_eq!(last_key(&delta(4)), Some((key2, 10)));
+    }
+
+    #[test]
+    fn test_date_ranges() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let playlist = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-PROGRAM-DATE-TIME:2022-11-11T18:00:00Z
+#EXT-X-DATERANGE:ID=\"b\",CLASS=\"c\",START-DATE=\"2022-11-11T18:00:06Z\",END-ON-NEXT=YES
+#EXT-X-DATERANGE:ID=\"a\",CLASS=\"c\",START-DATE=\"2022-11-11T18:00:02Z\",END-ON-NEXT=YES
+#EXT-X-DATERANGE:ID=\"other\",START-DATE=\"2022-11-11T18:00:04Z\"
+#EXT-X-DATERANGE:ID=\"b\",START-DATE=\"2022-11-11T18:00:06Z\",X-NEW=1
+#EXTINF:4,
+seg1.ts
+#EXTINF:4,
+seg2.ts
+",
+            None,
+        )
+        .unwrap();
+        let start = playlist.segment_list().media()[0].start();
+        let ranges: Vec<(&str, f64, Option<f64>)> = playlist
+            .date_ranges()
+            .iter()
+            .map(|d| (d.id(), d.start() - start, d.end().map(|e| e - start)))
+            .collect();
+        // END-ON-NEXT ranges end where the next one of the same CLASS starts
+        assert_eq!(
+            ranges,
+            vec![("a", 2., Some(6.)), ("other", 4., None), ("b", 6., None)]
+        );
+        let b = &playlist.date_ranges()[2];
+        assert_eq!(b.class(), Some("c"));
+        assert!(b.ends_on_next());
+        assert_eq!(b.client_attributes().len(), 1);
+
+        // Date ranges cannot be placed without EXT-X-PROGRAM-DATE-TIME
+        let playlist = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-DATERANGE:ID=\"a\",START-DATE=\"2022-11-11T18:00:02Z\"
+#EXTINF:4,
+seg1.ts
+",
+            None,
+        )
+        .unwrap();
+        assert!(playlist.date_ranges().is_empty());
+    }
+
+    #[test]
+    fn test_discontinuities() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let playlist = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:20
+#EXT-X-DISCONTINUITY-SEQUENCE:5
+#EXTINF:4,
+seg20.ts
+#EXT-X-DISCONTINUITY
+#EXTINF:4,
+seg21.ts
+#EXTINF:4,
+seg22.ts
+#EXT-X-DISCONTINUITY
+#EXTINF:4,
+seg23.ts
+",
+            None,
+        )
+        .unwrap();
+        assert_eq!(playlist.discontinuity_sequence(), 5);
+        let discontinuities: Vec<u64> = playlist
+            .segment_list()
+            .media()
+            .iter()
+            .map(|s| s.discontinuity())
+            .collect();
+        assert_eq!(discontinuities, vec![5, 6, 6, 7]);
+
+        // The first segment of a discontinuity domain left the playlist
+        let playlist = create(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:22
+#EXT-X-DISCONTINUITY-SEQUENCE:6
+#EXTINF:4,
+seg22.ts
+#EXT-X-DISCONTINUITY
+#EXTINF:4,
+seg23.ts
+",
+            Some(&playlist),
+        )
+        .unwrap();
+        assert_eq!(playlist.discontinuity_sequence(), 6);
+        let discontinuities: Vec<u64> = playlist
+            .segment_list()
+            .media()
+            .iter()
+            .map(|s| s.discontinuity())
+            .collect();
+        assert_eq!(discontinuities, vec![6, 7]);
+    }
+}
@@ -1,11 +1,11 @@
 use super::{
-    media_playlist::MediaPlaylistParsingError,
-    multi_variant_playlist::MediaPlaylistContext,
-    utils::{parse_enumerated_string, parse_quoted_string, skip_attribute_list_value},
+    utils::{
+        parse_comma_separated_list, parse_enumerated_string, parse_quoted_string,
+        skip_attribute_list_value,
+    },
     MediaPlaylist,
 };
 use crate::{utils::url::Url, Logger};
-use std::io::BufRead;
 
 /// Structure describing a "Media tag" in the HLS Multivariant Playlist.
 #[derive(Debug)]
@@ -15,8 +15,9 @@ pub struct MediaTag {
     id: u32,
 
     /// Stable identifier for the URI within the parent Multivariant Playlist
-    /// even in the case a different Multivariant fetched in the meantime,
-    /// whereas `id` may change if the Multivariant Playlist is refreshed.
+    /// even in the case a different Multivariant fetched in the meantime.
+    /// It is relied on to give back its `id` to this rendition when the
+    /// Multivariant Playlist is refreshed.
     ///
     /// This identifier allows the URI of the Variant Stream to
     /// change between two distinct downloads of the Multivariant
@@ -75,9 +76,9 @@ pub struct MediaTag {
     /// language).
     forced: bool,
 
-    /// If the `typ` attribute is Audio, then it is the count of audio
-    /// channels indicating the maximum number of independent, simultaneous
-    /// audio channels present in any Media Segment in the Rendition.
+    /// If the `typ` attribute is Audio, then it describes the audio channels
+    /// of the Rendition, starting with the maximum number of independent,
+    /// simultaneous audio channels present in any Media Segment in it.
     /// For example, an AC-3 5.1 Rendition would have a CHANNELS="6" attribute.
     ///
     /// All audio `MediaTag` SHOULD have a channels attribute. If a
@@ -85,10 +86,135 @@ pub struct MediaTag {
     /// encoded with the same codec but a different number of channels,
     /// then the `channels` attribute is REQUIRED; otherwise, it is
     /// OPTIONAL.
-    channels: Option<u32>,
-    // TODO
-    // instream_id
-    // characteristics
+    channels: Option<AudioChannels>,
+
+    /// Uniform Type Identifiers (UTI) indicating individual characteristics of the Rendition,
+    /// such as `"public.accessibility.describes-music-and-sound"` for subtitles also
+    /// transcribing sounds.
+    ///
+    /// Empty if the `CHARACTERISTICS` attribute was not present.
+    characteristics: Vec<String>,
+
+    /// The closed captions channel, carried in the video of the variants linked to this
+    /// `MediaTag`, this Rendition corresponds to.
+    ///
+    /// Only set if `typ` is `ClosedCaptions`.
+    instream_id: Option<InstreamId>,
+}
+
+/// Identifies a closed captions channel carried in a video stream, as indicated by the
+/// `INSTREAM-ID` attribute of a `MediaTag` of type `ClosedCaptions`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InstreamId {
+    /// One of the four CEA-608 channels, from `1` (`"CC1"`) to `4` (`"CC4"`).
+    Cea608(u8),
+    /// One of the CEA-708 services, from `1` (`"SERVICE1"`) to `63` (`"SERVICE63"`).
+    Cea708(u8),
+}
+
+impl InstreamId {
+    /// Parse the value of an `INSTREAM-ID` attribute (e.g. `"CC1"` or `"SERVICE3"`).
+    ///
+    /// Returns `None` if it is not a valid value.
+    fn parse(value: &str) -> Option<Self> {
+        if let Some(channel) = value.strip_prefix("CC") {
+            match channel.parse::<u8>() {
+                Ok(x) if (1..=4).contains(&x) => Some(InstreamId::Cea608(x)),
+                _ => None,
+            }
+        } else if let Some(service) = value.strip_prefix("SERVICE") {
+            match service.parse::<u8>() {
+                Ok(x) if (1..=63).contains(&x) => Some(InstreamId::Cea708(x)),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Information on the audio channels of a Rendition, as indicated by the `CHANNELS` attribute of
+/// a `MediaTag` of type `Audio` (e.g. `"16/JOC"` or `"2/-/BINAURAL"`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AudioChannels {
+    /// Maximum number of independent, simultaneous audio channels present in any Media Segment
+    /// of the Rendition.
+    count: u32,
+    /// Audio Coding Identifiers of the spatial audio technologies relied on by the Rendition
+    /// (e.g. `"JOC"` for Dolby Atmos with Joint Object Coding).
+    ///
+    /// Empty if not indicated or if none is relied on.
+    spatial_identifiers: Vec<String>,
+    /// If `true`, the audio is binaural (e.g. spatialized for headphones).
+    binaural: bool,
+    /// If `true`, the audio is pre-processed content that should not be further spatialized.
+    immersive: bool,
+    /// If `true`, the audio is a downmix of a Rendition with more channels.
+    downmix: bool,
+}
+
+impl AudioChannels {
+    /// Parse the value of a `CHANNELS` attribute, made of slash-separated parameters: the channel
+    /// count, then optionally comma-separated Audio Coding Identifiers (or `"-"` if none) and
+    /// comma-separated special usage identifiers.
+    ///
+    /// Returns `None` if it is not a valid value.
+    fn parse(value: &str) -> Option<Self> {
+        let mut parameters = value.split('/');
+        let count = parameters.next()?.parse::<u32>().ok()?;
+        let spatial_identifiers = match parameters.next() {
+            None | Some("-") => vec![],
+            Some(identifiers) => identifiers
+                .split(',')
+                .filter(|i| !i.is_empty())
+                .map(|i| i.to_owned())
+                .collect(),
+        };
+        let mut channels = AudioChannels {
+            count,
+            spatial_identifiers,
+            binaural: false,
+            immersive: false,
+            downmix: false,
+        };
+        if let Some(usages) = parameters.next() {
+            for usage in usages.split(',') {
+                match usage {
+                    "BINAURAL" => channels.binaural = true,
+                    "IMMERSIVE" => channels.immersive = true,
+                    "DOWNMIX" => channels.downmix = true,
+                    _ => {}
+                }
+            }
+        }
+        Some(channels)
+    }
+
+    /// Returns the maximum number of independent, simultaneous audio channels.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns the Audio Coding Identifiers of the spatial audio technologies relied on, if any.
+    pub fn spatial_identifiers(&self) -> &[String] {
+        &self.spatial_identifiers
+    }
+
+    /// Returns `true` if the audio is binaural.
+    pub fn is_binaural(&self) -> bool {
+        self.binaural
+    }
+
+    /// Returns `true` if the audio is pre-processed content that should not be further
+    /// spatialized.
+    pub fn is_immersive(&self) -> bool {
+        self.immersive
+    }
+
+    /// Returns `true` if the audio is a downmix of a Rendition with more channels.
+    pub fn is_downmix(&self) -> bool {
+        self.downmix
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -108,6 +234,23 @@ pub enum MediaTagParsingError {
     Unknown,
 }
 
+/// A track of a Multivariant Playlist (e.g. an audio track), grouping the `MediaTag`s of the
+/// renditions of the same content.
+pub(super) trait MediaTrack {
+    /// Identifier for that track, unique in the parent Multivariant Playlist.
+    fn id(&self) -> u32;
+
+    fn set_id(&mut self, id: u32);
+
+    /// `MediaTag`s of the renditions of that track.
+    fn media_tags(&self) -> &[MediaTag];
+    /// `STABLE-RENDITION-ID` identifying that track across Multivariant Playlist refreshes, if
+    /// one of its renditions announces it.
+    fn stable_id(&self) -> Option<&str> {
+        self.media_tags().iter().find_map(|m| m.stable_id())
+    }
+}
+
 impl MediaTag {
     pub(super) fn create(
         media_line: &str,
@@ -125,8 +268,10 @@ impl MediaTag {
         let mut default = false;
         let mut autoselect = false;
         let mut forced = false;
+        let mut characteristics: Vec<String> = vec![];
+        let mut instream_id: Option<InstreamId> = None;
 
-        let channels: Option<u32> = None;
+        let mut channels: Option<AudioChannels> = None;
 
         let mut offset = "#EXT-X-MEDIA:".len();
         loop {
@@ -138,108 +283,130 @@ impl MediaTag {
                     Logger::warn("Attribute Name not followed by equal sign");
                     break;
                 }
-                Some(idx) => {
-                    match &media_line[offset..offset + idx] {
-                        "TYPE" => {
-                            let (parsed, end_offset) =
-                                parse_enumerated_string(media_line, offset + idx + 1);
-                            offset = end_offset + 1;
-                            match parsed {
-                                "AUDIO" => typ = Some(MediaTagType::Audio),
-                                "VIDEO" => typ = Some(MediaTagType::Video),
-                                "SUBTITLES" => typ = Some(MediaTagType::Subtitles),
-                                "CLOSED-CAPTIONS" => typ = Some(MediaTagType::ClosedCaptions),
-                                x => {
-                                    Logger::warn(&format!("Unrecognized media type: {}", x));
-                                    typ = Some(MediaTagType::Other);
-                                }
-                            };
-                        }
-                        "URI" => {
-                            let (parsed, end_offset) =
-                                parse_quoted_string(media_line, offset + idx + 1);
-                            offset = end_offset + 1;
-                            if let Ok(parsed) = parsed {
-                                url = Some(Url::new(parsed.to_owned()));
-                            } else {
-                                Logger::warn("Unparsable URI value");
-                            }
-                        }
-                        "GROUP-ID" => {
-                            let (parsed, end_offset) =
-                                parse_quoted_string(media_line, offset + idx + 1);
-                            offset = end_offset + 1;
-                            if let Ok(val) = parsed {
-                                group_id = Some(val.to_owned());
-                            } else {
-                                Logger::warn("Unparsable GROUP-ID value");
+                Some(idx) => match &media_line[offset..offset + idx] {
+                    "TYPE" => {
+                        let (parsed, end_offset) =
+                            parse_enumerated_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed {
+                            "AUDIO" => typ = Some(MediaTagType::Audio),
+                            "VIDEO" => typ = Some(MediaTagType::Video),
+                            "SUBTITLES" => typ = Some(MediaTagType::Subtitles),
+                            "CLOSED-CAPTIONS" => typ = Some(MediaTagType::ClosedCaptions),
+                            x => {
+                                Logger::warn(&format!("Unrecognized media type: {}", x));
+                                typ = Some(MediaTagType::Other);
                             }
+                        };
+                    }
+                    "URI" => {
+                        let (parsed, end_offset) =
+                            parse_quoted_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(parsed) = parsed {
+                            url = Some(Url::resolve(parsed, playlist_base_url));
+                        } else {
+                            Logger::warn("Unparsable URI value");
                         }
-                        "LANGUAGE" => {
-                            let (parsed, end_offset) =
-                                parse_quoted_string(media_line, offset + idx + 1);
-                            offset = end_offset + 1;
-                            if let Ok(val) = parsed {
-                                language = Some(val.to_owned());
-                            } else {
-                                Logger::warn("Unparsable LANGUAGE value");
-                            }
+                    }
+                    "GROUP-ID" => {
+                        let (parsed, end_offset) =
+                            parse_quoted_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            group_id = Some(val.to_owned());
+                        } else {
+                            Logger::warn("Unparsable GROUP-ID value");
                         }
-                        "ASSOC-LANGUAGE" => {
-                            let (parsed, end_offset) =
-                                parse_quoted_string(media_line, offset + idx + 1);
-                            offset = end_offset + 1;
-                            if let Ok(val) = parsed {
-                                assoc_language = Some(val.to_owned());
-                            } else {
-                                Logger::warn("Unparsable ASSOC-LANGUAGE value");
-                            }
+                    }
+                    "LANGUAGE" => {
+                        let (parsed, end_offset) =
+                            parse_quoted_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            language = Some(val.to_owned());
+                        } else {
+                            Logger::warn("Unparsable LANGUAGE value");
                         }
-                        "NAME" => {
-                            let (parsed, end_offset) =
-                                parse_quoted_string(media_line, offset + idx + 1);
-                            offset = end_offset + 1;
-                            if let Ok(val) = parsed {
-                                name = Some(val.to_owned());
-                            } else {
-                                Logger::warn("Unparsable NAME value");
-                            }
+                    }
+                    "ASSOC-LANGUAGE" => {
+                        let (parsed, end_offset) =
+                            parse_quoted_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            assoc_language = Some(val.to_owned());
+                        } else {
+                            Logger::warn("Unparsable ASSOC-LANGUAGE value");
                         }
-                        "STABLE-RENDITION-ID" => {
-                            let (parsed, end_offset) =
-                                parse_quoted_string(media_line, offset + idx + 1);
-                            offset = end_offset + 1;
-                            if let Ok(val) = parsed {
-                                stable_rendition_id = Some(val.to_owned());
-                            } else {
-                                Logger::warn("Unparsable STABLE-RENDITION-ID value");
-                            }
+                    }
+                    "NAME" => {
+                        let (parsed, end_offset) =
+                            parse_quoted_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            name = Some(val.to_owned());
+                        } else {
+                            Logger::warn("Unparsable NAME value");
                         }
-                        "DEFAULT" => {
-                            let end_offset =
-                                skip_attribute_list_value(media_line, offset + idx + 1);
-                            offset = end_offset + 1;
-                            default = true;
+                    }
+                    "STABLE-RENDITION-ID" => {
+                        let (parsed, end_offset) =
+                            parse_quoted_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            stable_rendition_id = Some(val.to_owned());
+                        } else {
+                            Logger::warn("Unparsable STABLE-RENDITION-ID value");
                         }
-                        "AUTOSELECT" => {
-                            let end_offset =
-                                skip_attribute_list_value(media_line, offset + idx + 1);
-                            offset = end_offset + 1;
-                            autoselect = true;
+                    }
+                    "DEFAULT" => {
+                        let (parsed, end_offset) =
+                            parse_enumerated_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        default = parsed == "YES";
+                    }
+                    "AUTOSELECT" => {
+                        let (parsed, end_offset) =
+                            parse_enumerated_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        autoselect = parsed == "YES";
+                    }
+                    "FORCED" => {
+                        let (parsed, end_offset) =
+                            parse_enumerated_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        forced = parsed == "YES";
+                    }
+                    "CHARACTERISTICS" => {
+                        let (parsed, end_offset) =
+                            parse_comma_separated_list(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            characteristics = val.iter().map(|c| (*c).to_owned()).collect();
+                        } else {
+                            Logger::warn("Unparsable CHARACTERISTICS value");
                         }
-                        "FORCED" => {
-                            let end_offset =
-                                skip_attribute_list_value(media_line, offset + idx + 1);
-                            offset = end_offset + 1;
-                            forced = true;
+                    }
+                    "INSTREAM-ID" => {
+                        let (parsed, end_offset) =
+                            parse_quoted_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        instream_id = parsed.ok().and_then(InstreamId::parse);
+                        if instream_id.is_none() {
+                            Logger::warn("Unparsable INSTREAM-ID value");
                         }
-                        "CHANNELS" => {
-                            // TODO
-                            offset = skip_attribute_list_value(media_line, offset + idx + 1) + 1;
+                    }
+                    "CHANNELS" => {
+                        let (parsed, end_offset) =
+                            parse_quoted_string(media_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        channels = parsed.ok().and_then(AudioChannels::parse);
+                        if channels.is_none() {
+                            Logger::warn("Unparsable CHANNELS value");
                         }
-                        _ => offset = skip_attribute_list_value(media_line, offset + idx + 1) + 1,
                     }
-                }
+                    _ => offset = skip_attribute_list_value(media_line, offset + idx + 1) + 1,
+                },
             }
         }
 
@@ -259,13 +426,6 @@ impl MediaTag {
             return Err(MediaTagParsingError::MissingName);
         };
 
-        url = url.map(|u| {
-            if u.is_absolute() {
-                u
-            } else {
-                Url::from_relative(playlist_base_url, u)
-            }
-        });
         Ok(MediaTag {
             id,
             stable_id: stable_rendition_id,
@@ -280,6 +440,8 @@ impl MediaTag {
             autoselect,
             forced,
             channels,
+            characteristics,
+            instream_id: instream_id.filter(|_| typ == MediaTagType::ClosedCaptions),
         })
     }
 
@@ -287,15 +449,33 @@ impl MediaTag {
         self.media_playlist.as_ref()
     }
 
-    pub(crate) fn update(
+    /// Set the `MediaPlaylist` linked to this object, which should have been parsed with the
+    /// previous one as reference if one was loaded.
+    pub(super) fn set_media_playlist(
         &mut self,
-        playlist: impl BufRead,
-        url: Url,
-        context: &MediaPlaylistContext,
-    ) -> Result<&MediaPlaylist, MediaPlaylistParsingError> {
-        let new_mp = MediaPlaylist::create(playlist, url, self.media_playlist.as_ref(), context)?;
-        self.media_playlist = Some(new_mp);
-        Ok(self.media_playlist.as_ref().unwrap())
+        mut media_playlist: MediaPlaylist,
+    ) -> &MediaPlaylist {
+        if let Some(prev) = self.media_playlist.as_ref() {
+            media_playlist.compare_to_previous_version(prev);
+        }
+        self.media_playlist.insert(media_playlist)
+    }
+
+    /// Takes from `prev`, the same rendition in a previous version of the Multivariant Playlist,
+    /// its `id` and already-loaded `MediaPlaylist`.
+    pub(super) fn inherit_from(&mut self, prev: &mut MediaTag) {
+        self.id = prev.id;
+        self.media_playlist = prev.media_playlist.take();
+        if self.url != prev.url {
+            // Its Media Playlist should now be refreshed through the newly-announced URL
+            if let (Some(playlist), Some(url)) = (self.media_playlist.as_mut(), self.url.as_ref()) {
+                playlist.update_url(url.clone());
+            }
+        }
+    }
+
+    pub(super) fn set_id(&mut self, id: u32) {
+        self.id = id;
     }
 
     pub(crate) fn id(&self) -> u32 {
@@ -326,6 +506,10 @@ impl MediaTag {
         self.default
     }
 
+    pub(crate) fn is_forced(&self) -> bool {
+        self.forced
+    }
+
     pub(crate) fn language(&self) -> Option<&str> {
         let l = self.language.as_ref()?;
         Some(l.as_str())
@@ -341,6 +525,55 @@ impl MediaTag {
     }
 
     pub(crate) fn channels(&self) -> Option<u32> {
-        self.channels
+        self.channels.as_ref().map(|c| c.count())
+    }
+
+    pub(crate) fn audio_channels(&self) -> Option<&AudioChannels> {
+        self.channels.as_ref()
+    }
+
+    pub(crate) fn characteristics(&self) -> &[String] {
+        &self.characteristics
+    }
+
+    /// Returns `true` if at least one of the characteristics of that Rendition is an
+    /// accessibility-related one (e.g. `"public.accessibility.describes-video"`).
+    pub(crate) fn is_accessibility_related(&self) -> bool {
+        self.characteristics
+            .iter()
+            .any(|c| c.starts_with("public.accessibility."))
+    }
+
+    pub(crate) fn instream_id(&self) -> Option<InstreamId> {
+        self.instream_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_channels() {
+        let channels = AudioChannels::parse("6").unwrap();
+        assert_eq!(channels.count(), 6);
+        assert!(channels.spatial_identifiers().is_empty());
+        assert!(!channels.is_binaural() && !channels.is_immersive() && !channels.is_downmix());
+
+        let channels = AudioChannels::parse("16/JOC").unwrap();
+        assert_eq!(channels.count(), 16);
+        assert_eq!(channels.spatial_identifiers(), ["JOC"]);
+
+        let channels = AudioChannels::parse("2/-/BINAURAL,DOWNMIX").unwrap();
+        assert_eq!(channels.count(), 2);
+        assert!(channels.spatial_identifiers().is_empty());
+        assert!(channels.is_binaural() && !channels.is_immersive() && channels.is_downmix());
+
+        let channels = AudioChannels::parse("12/JOC,FOO/IMMERSIVE").unwrap();
+        assert_eq!(channels.spatial_identifiers(), ["JOC", "FOO"]);
+        assert!(channels.is_immersive());
+
+        assert!(AudioChannels::parse("").is_none());
+        assert!(AudioChannels::parse("JOC/6").is_none());
     }
 }
@@ -1,18 +1,38 @@
 mod audio_track_list;
+mod content_steering;
+mod date_range;
+mod interstitial;
+mod json;
 mod media_playlist;
 mod media_tag;
 mod multi_variant_playlist;
+mod partial_segment;
+mod segment_key;
+mod session_data;
+mod text_track_list;
 mod utils;
+mod variables;
 mod variant_stream;
+mod video_track_list;
 
-pub(crate) use audio_track_list::AudioTrack;
+pub(crate) use audio_track_list::{AudioSelectionPreferences, AudioTrack};
+pub(crate) use content_steering::{PathwayClone, SteeringManifest};
+pub(crate) use date_range::DateRange;
+pub(crate) use interstitial::{parse_asset_list, Interstitial, InterstitialAsset};
 pub(crate) use media_playlist::{
-    ByteRange, InitSegmentInfo, MediaPlaylist, MediaPlaylistParsingError, MediaSegmentInfo,
-    SegmentList, SegmentTimeInfo,
+    ByteRange, InitSegmentInfo, MediaPlaylist, MediaPlaylistParsingError, MediaPlaylistRefresh,
+    MediaSegmentInfo, SegmentList, SegmentTimeInfo,
 };
-pub(crate) use media_tag::{MediaTag, MediaTagType};
+pub(crate) use media_tag::{AudioChannels, InstreamId, MediaTag, MediaTagType};
 pub(crate) use multi_variant_playlist::{
     MediaPlaylistPermanentId, MediaPlaylistUpdateError, MultivariantPlaylist,
     MultivariantPlaylistParsingError,
 };
-pub(crate) use variant_stream::{VariantStream, VideoResolution};
+pub(crate) use segment_key::SegmentKey;
+pub(crate) use session_data::{SessionData, SessionDataContent};
+pub(crate) use text_track_list::TextTrack;
+pub(crate) use variant_stream::{
+    parse_allowed_cpc, HdcpLevel, OutputProtectionCapabilities, VariantStream, VideoDynamicRange,
+    VideoResolution,
+};
+pub(crate) use video_track_list::VideoTrack;
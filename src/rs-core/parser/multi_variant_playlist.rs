@@ -1,9 +1,16 @@
-use super::audio_track_list::AudioTrackList;
+use super::audio_track_list::{AudioSelectionPreferences, AudioTrackList};
+use super::content_steering::{ContentSteering, PathwayClone};
 use super::media_playlist::{MediaPlaylist, MediaPlaylistParsingError};
-use super::media_tag::{MediaTag, MediaTagParsingError};
+use super::media_tag::{MediaTag, MediaTagParsingError, MediaTrack};
+use super::session_data::SessionData;
+use super::text_track_list::TextTrackList;
 use super::utils::StartAttribute;
-use super::variant_stream::{VariantParsingError, VariantStream};
-use super::{AudioTrack, MediaTagType};
+use super::variables::{PlaylistVariables, UndefinedVariableError};
+use super::variant_stream::{
+    OutputProtectionCapabilities, VariantParsingError, VariantStream, VideoDynamicRange,
+};
+use super::video_track_list::VideoTrackList;
+use super::{AudioTrack, InstreamId, MediaTagType, TextTrack, VideoTrack};
 use crate::parser::utils::parse_start_attribute;
 use crate::utils::url::Url;
 use crate::Logger;
@@ -17,15 +24,38 @@ pub struct MultivariantPlaylist {
     /// Multivariant Playlist.
     variants: Vec<VariantStream>,
 
+    /// I-frame-only variants, announced through `EXT-X-I-FRAME-STREAM-INF` tags, which can be
+    /// relied on for trick play.
+    i_frame_variants: Vec<VariantStream>,
+
     /// Abstraction to simplify the management of tracks.
     ///
     /// It actually corresponds to pre-parsed Media tags in the Multivariant Playlist with their
     /// `TYPE` set to `"audio"`.
     audio_tracks: AudioTrackList,
 
-    /// Parsed Media tags in the Multivariant Playlist which are not of `TYPE` `"audio"`
+    /// Abstraction to simplify the management of video renditions (e.g. camera angles).
+    ///
+    /// It actually corresponds to pre-parsed Media tags in the Multivariant Playlist with their
+    /// `TYPE` set to `"video"`.
+    video_tracks: VideoTrackList,
+
+    /// Abstraction to simplify the management of subtitles.
+    ///
+    /// It actually corresponds to pre-parsed Media tags in the Multivariant Playlist with their
+    /// `TYPE` set to `"subtitles"`.
+    text_tracks: TextTrackList,
+
+    /// Parsed Media tags in the Multivariant Playlist which are neither of `TYPE` `"audio"`,
+    /// `"video"`, `"subtitles"` nor `"closed-captions"`.
     other_media: Vec<MediaTag>,
 
+    /// Arbitrary data announced through `EXT-X-SESSION-DATA` tags, in playlist order.
+    session_data: Vec<SessionData>,
+
+    /// Content Steering information announced through an `EXT-X-CONTENT-STEERING` tag, if one.
+    content_steering: Option<ContentSteering>,
+
     /// Supplementary information that will need to be communicated to other Media Playlists
     /// once they are loaded and parsed.
     context: MediaPlaylistContext,
@@ -46,10 +76,16 @@ impl MultivariantPlaylist {
         let mut last_id = 0u32;
         let playlist_base_url = url.pathname();
         let mut variants: Vec<VariantStream> = vec![];
+        let mut i_frame_variants: Vec<VariantStream> = vec![];
         let mut audio_media: Vec<MediaTag> = vec![];
+        let mut video_media: Vec<MediaTag> = vec![];
+        let mut subtitles_media: Vec<MediaTag> = vec![];
         let mut other_media: Vec<MediaTag> = vec![];
+        let mut session_data: Vec<SessionData> = vec![];
+        let mut content_steering = None;
         let mut start = None;
         let mut independent_segments = None;
+        let mut variables = PlaylistVariables::default();
 
         let mut lines = playlist.lines();
         match lines.next() {
@@ -73,6 +109,13 @@ impl MultivariantPlaylist {
                     None => continue,
                     Some(idx) => idx + 4,
                 };
+                let str_line = match &str_line[4..colon_idx] {
+                    "-X-DEFINE" => {
+                        variables.parse_define(&str_line, &url, None)?;
+                        continue;
+                    }
+                    _ => variables.substitute_in_tag(&str_line)?,
+                };
                 match &str_line[4..colon_idx] {
                     "-X-STREAM-INF" => {
                         let variant_url =
@@ -85,7 +128,9 @@ impl MultivariantPlaylist {
                                         MultivariantPlaylistParsingError::UnableToReadVariantUri,
                                     )
                                 }
-                                Some(Ok(l)) => Url::new(l),
+                                Some(Ok(l)) => {
+                                    Url::resolve(&variables.substitute(&l)?, playlist_base_url)
+                                }
                             };
 
                         let variant = VariantStream::create_from_stream_inf(
@@ -97,15 +142,42 @@ impl MultivariantPlaylist {
                         last_id += 1;
                         variants.push(variant);
                     }
+                    "-X-I-FRAME-STREAM-INF" => {
+                        match VariantStream::create_from_i_frame_stream_inf(
+                            &str_line,
+                            playlist_base_url,
+                            last_id,
+                        ) {
+                            Ok(variant) => {
+                                last_id += 1;
+                                i_frame_variants.push(variant);
+                            }
+                            Err(e) => Logger::warn(&format!(
+                                "Parser: Ignoring invalid EXT-X-I-FRAME-STREAM-INF: {:?}",
+                                e
+                            )),
+                        }
+                    }
                     "-X-MEDIA" => {
                         let media = MediaTag::create(&str_line, &url, last_id)?;
                         last_id += 1;
-                        if media.typ() == MediaTagType::Audio {
-                            audio_media.push(media);
-                        } else {
-                            other_media.push(media);
+                        match media.typ() {
+                            MediaTagType::Audio => audio_media.push(media),
+                            MediaTagType::Video => video_media.push(media),
+                            MediaTagType::Subtitles | MediaTagType::ClosedCaptions => {
+                                subtitles_media.push(media)
+                            }
+                            _ => other_media.push(media),
                         }
                     }
+                    "-X-SESSION-DATA" => {
+                        if let Some(data) = SessionData::parse(&str_line, playlist_base_url) {
+                            session_data.push(data);
+                        }
+                    }
+                    "-X-CONTENT-STEERING" => {
+                        content_steering = ContentSteering::parse(&str_line, playlist_base_url);
+                    }
                     "-X-INDEPENDENT-SEGMENTS" => independent_segments = Some(true),
                     "-X-START" => match parse_start_attribute(&str_line) {
                         Ok(st) => {
@@ -138,19 +210,74 @@ impl MultivariantPlaylist {
             }
         });
 
+        i_frame_variants.sort_by_key(|v| v.bandwidth());
+
         Ok(MultivariantPlaylist {
             last_id,
             url,
             variants,
+            i_frame_variants,
             audio_tracks: AudioTrackList::new(audio_media),
+            video_tracks: VideoTrackList::new(video_media),
+            text_tracks: TextTrackList::new(subtitles_media),
             other_media,
+            session_data,
+            content_steering,
             context: MediaPlaylistContext {
                 start,
                 independent_segments,
+                variables,
             },
         })
     }
 
+    /// Returns `true` if `playlist` looks like a Media Playlist rather than a Multivariant
+    /// Playlist, that is if it describes media segments without announcing any variant.
+    pub(crate) fn is_media_playlist(playlist: &[u8]) -> bool {
+        let mut has_segment_tags = false;
+        for line in playlist.split(|c| *c == b'\n') {
+            if line.starts_with(b"#EXT-X-STREAM-INF") {
+                return false;
+            }
+            has_segment_tags = has_segment_tags
+                || line.starts_with(b"#EXTINF")
+                || line.starts_with(b"#EXT-X-TARGETDURATION");
+        }
+        has_segment_tags
+    }
+
+    /// Creates a new `MultivariantPlaylist` object with a single variant, whose Media Playlist is
+    /// `media_playlist` (whose content is given through a `BufRead` abstraction) found at `url`.
+    ///
+    /// This allows to play lone Media Playlists, which are not described by a Multivariant
+    /// Playlist. The codecs of that variant are guessed from its media segments.
+    ///
+    /// That variant's Media Playlist is already considered as loaded and will only have to be
+    /// requested again when refreshed.
+    pub(crate) fn from_media_playlist(
+        media_playlist: impl io::BufRead,
+        url: Url,
+    ) -> Result<Self, MediaPlaylistParsingError> {
+        let context = MediaPlaylistContext::default();
+        let parsed = MediaPlaylist::create(media_playlist, url.clone(), None, &context)?;
+        let mut variant =
+            VariantStream::create_for_media_playlist(url.clone(), &parsed.guessed_codecs(), 0);
+        variant.set_media_playlist(parsed);
+        Ok(MultivariantPlaylist {
+            last_id: 1,
+            url,
+            variants: vec![variant],
+            i_frame_variants: vec![],
+            audio_tracks: AudioTrackList::new(vec![]),
+            video_tracks: VideoTrackList::new(vec![]),
+            text_tracks: TextTrackList::new(vec![]),
+            other_media: vec![],
+            session_data: vec![],
+            content_steering: None,
+            context,
+        })
+    }
+
     /// Returns a reference to the last known URL of the Multivariant Playlist.
     pub(crate) fn url(&self) -> &Url {
         &self.url
@@ -160,6 +287,99 @@ impl MultivariantPlaylist {
         self.variants.as_slice()
     }
 
+    /// Returns the Content Steering information of this `MultivariantPlaylist`, if it has some.
+    pub(crate) fn content_steering(&self) -> Option<&ContentSteering> {
+        self.content_steering.as_ref()
+    }
+
+    /// Create the variants of the Content Steering Pathway described by `clone`, by copying the
+    /// ones of its base Pathway.
+    ///
+    /// Returns `false` if that Pathway could not be created because its base Pathway has no
+    /// variant, or if it already existed.
+    ///
+    /// Note that renditions (e.g. audio tracks) are not copied, the new variants relying on the
+    /// same ones than the variants they are copied from.
+    pub(crate) fn add_pathway_clone(&mut self, clone: &PathwayClone) -> bool {
+        if self.variants.iter().any(|v| v.pathway_id() == clone.id()) {
+            return false;
+        }
+        let mut new_variants = vec![];
+        for variant in self.variants.iter() {
+            if variant.pathway_id() == clone.base_id() {
+                let url = clone.variant_url(variant.url(), variant.stable_id());
+                new_variants.push(variant.clone_for_pathway(self.last_id, clone.id(), url));
+                self.last_id += 1;
+            }
+        }
+        if new_variants.is_empty() {
+            return false;
+        }
+        for variant in self.i_frame_variants.iter() {
+            if variant.pathway_id() == clone.base_id() {
+                let url = clone.variant_url(variant.url(), variant.stable_id());
+                new_variants.push(variant.clone_for_pathway(self.last_id, clone.id(), url));
+                self.last_id += 1;
+            }
+        }
+        for variant in new_variants {
+            if variant.is_i_frames_only() {
+                self.i_frame_variants.push(variant);
+            } else {
+                self.variants.push(variant);
+            }
+        }
+        true
+    }
+
+    /// Reconcile this `MultivariantPlaylist`, which should be a refreshed version of `prev`,
+    /// with it.
+    ///
+    /// Variants and renditions present in both are matched through their `STABLE-VARIANT-ID`
+    /// or `STABLE-RENDITION-ID` attribute, or through their URL if they don't have one. Those
+    /// take back the `id` they had in `prev` as well as their already-loaded `MediaPlaylist`,
+    /// so that identifiers relied on elsewhere (e.g. `MediaPlaylistPermanentId`s) stay valid.
+    /// The other ones are given an `id` which was never used in `prev`.
+    ///
+    /// The same is done for audio, video and text tracks, based on their renditions.
+    pub(crate) fn reconcile_with(&mut self, prev: &mut MultivariantPlaylist) {
+        let mut last_id = prev.last_id;
+        let mut new_id = || {
+            last_id += 1;
+            last_id - 1
+        };
+        reconcile_variants(&mut self.variants, &mut prev.variants, &mut new_id);
+        reconcile_variants(
+            &mut self.i_frame_variants,
+            &mut prev.i_frame_variants,
+            &mut new_id,
+        );
+        reconcile_media(
+            self.audio_tracks.iter_media_mut(),
+            prev.audio_tracks.iter_media_mut().collect(),
+            &mut new_id,
+        );
+        reconcile_media(
+            self.video_tracks.iter_media_mut(),
+            prev.video_tracks.iter_media_mut().collect(),
+            &mut new_id,
+        );
+        reconcile_media(
+            self.text_tracks.iter_media_mut(),
+            prev.text_tracks.iter_media_mut().collect(),
+            &mut new_id,
+        );
+        reconcile_media(
+            self.other_media.iter_mut(),
+            prev.other_media.iter_mut().collect(),
+            &mut new_id,
+        );
+        reconcile_tracks(&mut self.audio_tracks, &prev.audio_tracks, &mut new_id);
+        reconcile_tracks(&mut self.video_tracks, &prev.video_tracks, &mut new_id);
+        reconcile_tracks(&mut self.text_tracks, &prev.text_tracks, &mut new_id);
+        self.last_id = last_id;
+    }
+
     // pub(crate) fn media_type_for(&self, id: &MediaPlaylistPermanentId) -> Option<MediaType> {
     //     match id.location() {
     //         MediaPlaylistUrlLocation::Variant => {
@@ -178,31 +398,92 @@ impl MultivariantPlaylist {
     // }
 
     /// Returns information on all known variants linked to this `MultivariantPlaylist`, ordered by
-    /// `bandwidth` ascending, for which all codecs are known to be supported.
+    /// `bandwidth` ascending, for which all codecs and the video dynamic range are known to be
+    /// supported and whose output protection requirements can be met.
     pub(crate) fn supported_variants(&self) -> Vec<&VariantStream> {
+        self.variants.iter().filter(|v| v.is_playable()).collect()
+    }
+
+    /// Returns information on all known variants linked to this `MultivariantPlaylist`, ordered by
+    /// `bandwidth` ascending, for which all codecs and the video dynamic range are known to be
+    /// supported, whose output protection requirements can be met and which are linked to the
+    /// given audio track (if one) and video track (if one).
+    ///
+    /// If `max_audio_channels` is set, variants whose audio renditions all have more audio
+    /// channels than that are also filtered out.
+    pub(crate) fn supported_variants_for_tracks(
+        &self,
+        audio_track: Option<u32>,
+        video_track: Option<u32>,
+        max_audio_channels: Option<u32>,
+    ) -> Vec<&VariantStream> {
+        let audio_group_ids = audio_track.map(|id| self.audio_tracks.groups_for_track_id(id));
+        let video_group_ids = video_track.map(|id| self.video_tracks.groups_for_track_id(id));
+        let is_in_groups = |group: Option<&str>, group_ids: &Option<Vec<&str>>| match group_ids {
+            None => true,
+            Some(group_ids) => group.is_some_and(|g| group_ids.contains(&g)),
+        };
         self.variants
             .iter()
-            .filter(|v| v.supported().unwrap_or(false))
+            .filter(|v| {
+                v.is_playable()
+                    && is_in_groups(v.audio_group(), &audio_group_ids)
+                    && is_in_groups(v.video_group(), &video_group_ids)
+                    && self.fits_audio_channels(v, max_audio_channels)
+            })
             .collect()
     }
 
+    /// Returns `true` if the given variant has at least one audio media whose number of channels
+    /// does not exceed `max_channels` (or is unknown), or if it has no audio media at all.
+    pub(crate) fn fits_audio_channels(
+        &self,
+        variant: &VariantStream,
+        max_channels: Option<u32>,
+    ) -> bool {
+        let Some(group_id) = variant.audio_group() else {
+            return true;
+        };
+        let preferences = AudioSelectionPreferences {
+            prefer_accessible: false,
+            max_channels,
+        };
+        let mut medias = self
+            .audio_tracks
+            .iter_tracks_media()
+            .filter(|(_, m)| m.group_id() == group_id)
+            .peekable();
+        medias.peek().is_none() || medias.any(|(_, m)| preferences.fits_channels(m))
+    }
+
     /// Returns information on all known variants linked to this `MultivariantPlaylist`, ordered by
-    /// `bandwidth` ascending, for which all codecs are known to be supported and which are linked
-    /// to the given track_id
-    pub(crate) fn supported_variants_for_audio(&self, track_id: u32) -> Vec<&VariantStream> {
-        let group_ids = self.audio_tracks.groups_for_track_id(track_id);
+    /// `bandwidth` ascending, whose codecs are known to be supported but which cannot be
+    /// presented because of the output protection they require.
+    pub(crate) fn output_protected_variants(&self) -> Vec<&VariantStream> {
         self.variants
             .iter()
-            .filter(|v| {
-                if let Some(group) = v.audio_group() {
-                    group_ids.contains(&group) && v.supported().unwrap_or(false)
-                } else {
-                    false
-                }
-            })
+            .filter(|v| v.supported().unwrap_or(false) && v.output_protection_issue().is_some())
             .collect()
     }
 
+    /// Check the output protection required by all variants, including I-frame-only ones,
+    /// against the given capabilities.
+    pub(crate) fn update_output_protection(&mut self, capabilities: &OutputProtectionCapabilities) {
+        self.variants
+            .iter_mut()
+            .chain(self.i_frame_variants.iter_mut())
+            .for_each(|v| v.update_output_protection(capabilities));
+    }
+
+    /// Check the video dynamic range of all variants, including I-frame-only ones, against the
+    /// ones supported by the display.
+    pub(crate) fn update_video_range_support(&mut self, supported_ranges: &[VideoDynamicRange]) {
+        self.variants
+            .iter_mut()
+            .chain(self.i_frame_variants.iter_mut())
+            .for_each(|v| v.update_video_range_support(supported_ranges));
+    }
+
     /// Returns mutable reference to information on all known variants linked to this
     /// `MultivariantPlaylist`, ordered by `bandwidth` ascending.
     pub(crate) fn variants(&mut self) -> &[VariantStream] {
@@ -215,6 +496,36 @@ impl MultivariantPlaylist {
         self.variants.as_mut_slice()
     }
 
+    /// Returns information on all I-frame-only variants linked to this `MultivariantPlaylist`,
+    /// ordered by `bandwidth` ascending.
+    pub(crate) fn i_frame_variants(&self) -> &[VariantStream] {
+        self.i_frame_variants.as_slice()
+    }
+
+    /// Returns mutable reference to information on all I-frame-only variants linked to this
+    /// `MultivariantPlaylist`, ordered by `bandwidth` ascending.
+    pub(crate) fn i_frame_variants_mut(&mut self) -> &mut [VariantStream] {
+        self.i_frame_variants.as_mut_slice()
+    }
+
+    /// Returns information on all I-frame-only variants linked to this `MultivariantPlaylist`,
+    /// ordered by `bandwidth` ascending, which can be played according to the same criteria than
+    /// for `supported_variants`.
+    pub(crate) fn supported_i_frame_variants(&self) -> Vec<&VariantStream> {
+        self.i_frame_variants
+            .iter()
+            .filter(|v| v.is_playable())
+            .collect()
+    }
+
+    /// Returns information on a specific I-frame-only variant linked to this
+    /// `MultivariantPlaylist` based on its `id`.
+    ///
+    /// Returns `None` if no I-frame-only variant with that `id` are found.
+    pub(crate) fn i_frame_variant(&self, id: u32) -> Option<&VariantStream> {
+        self.i_frame_variants.iter().find(|v| v.id() == id)
+    }
+
     /// Returns information on a specific variant linked to this `MultivariantPlaylist` based on
     /// its `id`.
     ///
@@ -235,34 +546,85 @@ impl MultivariantPlaylist {
     /// Returns the `id` of the video media that should be chosen when loading the variant given
     /// in argument.
     ///
-    /// Returns `None` if there's no specific video media that can be loaded because none
-    /// are compatible with the given characteristics.
+    /// If a video track is currently selected, you also should communicate its own `id` as
+    /// argument, as it influences which video media will be choosen. If that track has no media
+    /// in the variant's video group, its default video media is chosen instead.
+    ///
+    /// Returns `None` if that variant has no video.
     pub(crate) fn video_media_playlist_id_for(
         &self,
         curr_variant: &VariantStream,
+        curr_video_track: Option<u32>,
     ) -> Option<MediaPlaylistPermanentId> {
-        if curr_variant.has_type(crate::bindings::MediaType::Video) {
-            Some(MediaPlaylistPermanentId::new(
-                MediaPlaylistUrlLocation::Variant,
-                curr_variant.id().to_owned(),
-            ))
+        if !curr_variant.has_type(crate::bindings::MediaType::Video) {
+            return None;
+        }
+        let variant_playlist_id = MediaPlaylistPermanentId::new(
+            MediaPlaylistUrlLocation::Variant,
+            curr_variant.id().to_owned(),
+        );
+        let group_id = if let Some(group_id) = curr_variant.video_group() {
+            group_id
         } else {
-            None
+            return Some(variant_playlist_id);
+        };
+        let selected_media = curr_video_track.and_then(|track_id| {
+            self.video_tracks
+                .iter()
+                .find(|t| t.id() == track_id)
+                .and_then(|t| t.medias().iter().find(|m| m.group_id() == group_id))
+        });
+        let media = selected_media.or_else(|| {
+            self.video_tracks
+                .iter_tracks_media()
+                .fold(None, |acc, (_, m)| {
+                    if m.group_id() == group_id && (acc.is_none() || m.is_default()) {
+                        Some(m)
+                    } else {
+                        acc
+                    }
+                })
+        });
+        match media {
+            Some(m) if m.url().is_some() => Some(MediaPlaylistPermanentId::new(
+                MediaPlaylistUrlLocation::VideoTrack,
+                m.id().to_owned(),
+            )),
+            // VideoTrack without URL have in fact their MediaPlaylist's URL in the variant
+            _ => Some(variant_playlist_id),
         }
     }
 
+    /// Returns the `id` of the Media Playlist of the I-frame-only variant given in argument.
+    pub(crate) fn i_frame_media_playlist_id_for(
+        &self,
+        i_frame_variant: &VariantStream,
+    ) -> MediaPlaylistPermanentId {
+        MediaPlaylistPermanentId::new(
+            MediaPlaylistUrlLocation::IFrameVariant,
+            i_frame_variant.id(),
+        )
+    }
+
     /// Returns the `id` of the audio media that should be chosen when loading the variant given
     /// in argument.
     ///
     /// If an audio track is currently selected, you also should communicate its own `id` as
     /// argument, as it media influence which audio media will be choosen.
     ///
+    /// If no audio track is selected, the `DEFAULT` audio media of the variant's group is
+    /// generally chosen, as long as it respects the given `preferences`: audio media exceeding
+    /// its maximum number of channels are avoided and, if it is set to prefer accessible audio,
+    /// audio media which can be automatically selected and which have accessibility
+    /// characteristics (e.g. audio descriptions) are preferred.
+    ///
     /// Returns `None` if there's no specific audio media that can be loaded because none
     /// are compatible with the given characteristics.
     pub(crate) fn audio_media_playlist_id_for(
         &self,
         curr_variant: &VariantStream,
         curr_audio_track: Option<u32>,
+        preferences: AudioSelectionPreferences,
     ) -> Option<MediaPlaylistPermanentId> {
         if let Some(group_id) = curr_variant.audio_group() {
             if let Some(track_id) = curr_audio_track {
@@ -286,33 +648,88 @@ impl MultivariantPlaylist {
                         }
                     })
             } else {
+                let rank = |m: &MediaTag| {
+                    let is_preferred = preferences.prefer_accessible
+                        && m.is_autoselect()
+                        && m.is_accessibility_related();
+                    (preferences.fits_channels(m), is_preferred, m.is_default())
+                };
                 self.audio_tracks
                     .iter_tracks_media()
-                    .fold(None, |acc, (_, m)| {
-                        if m.group_id() == group_id && (acc.is_none() || m.is_default()) {
-                            if m.url().is_some() {
-                                Some(MediaPlaylistPermanentId::new(
-                                    MediaPlaylistUrlLocation::AudioTrack,
-                                    m.id().to_owned(),
-                                ))
-                            } else {
-                                // AudioTrack without URL have in fact their MediaPlaylist's URL in the
-                                // variant
-                                Some(MediaPlaylistPermanentId::new(
-                                    MediaPlaylistUrlLocation::Variant,
-                                    curr_variant.id().to_owned(),
-                                ))
-                            }
+                    .filter(|(_, m)| m.group_id() == group_id)
+                    .fold(None, |acc: Option<&MediaTag>, (_, m)| match acc {
+                        Some(prev) if rank(prev) >= rank(m) => Some(prev),
+                        _ => Some(m),
+                    })
+                    .map(|m| {
+                        if m.url().is_some() {
+                            MediaPlaylistPermanentId::new(
+                                MediaPlaylistUrlLocation::AudioTrack,
+                                m.id().to_owned(),
+                            )
                         } else {
-                            acc
+                            // AudioTrack without URL have in fact their MediaPlaylist's URL in the
+                            // variant
+                            MediaPlaylistPermanentId::new(
+                                MediaPlaylistUrlLocation::Variant,
+                                curr_variant.id().to_owned(),
+                            )
                         }
                     })
             }
+        } else if curr_variant.has_type(crate::bindings::MediaType::Audio)
+            && !curr_variant.has_type(crate::bindings::MediaType::Video)
+        {
+            // Audio-only variant without audio group: its audio is in its own Media Playlist
+            Some(MediaPlaylistPermanentId::new(
+                MediaPlaylistUrlLocation::Variant,
+                curr_variant.id(),
+            ))
         } else {
             None
         }
     }
 
+    /// Returns the `id` of the subtitles media that should be chosen when loading the variant given
+    /// in argument, for the text track whose `id` is `text_track`.
+    ///
+    /// Returns `None` if that text track has no subtitles media compatible with that variant.
+    pub(crate) fn text_media_playlist_id_for(
+        &self,
+        curr_variant: &VariantStream,
+        text_track: u32,
+    ) -> Option<MediaPlaylistPermanentId> {
+        let group_id = curr_variant.subtitles_group()?;
+        self.text_tracks
+            .media_tag_for_group(text_track, MediaTagType::Subtitles, group_id)
+            .filter(|m| m.url().is_some())
+            .map(|m| MediaPlaylistPermanentId::new(MediaPlaylistUrlLocation::TextTrack, m.id()))
+    }
+
+    /// Returns the closed captions channel, carried in the video of the variant given in
+    /// argument, corresponding to the text track whose `id` is `text_track`.
+    ///
+    /// Returns `None` if that text track is not a closed captions track of that variant.
+    pub(crate) fn closed_captions_channel_for(
+        &self,
+        curr_variant: &VariantStream,
+        text_track: u32,
+    ) -> Option<InstreamId> {
+        let group_id = curr_variant.closed_captions_group()?;
+        self.text_tracks
+            .media_tag_for_group(text_track, MediaTagType::ClosedCaptions, group_id)
+            .and_then(|m| m.instream_id())
+    }
+
+    /// Returns `true` if the variant given in argument announces closed captions in its video.
+    pub(crate) fn has_closed_captions(&self, variant: &VariantStream) -> bool {
+        variant.closed_captions_group().is_some_and(|group_id| {
+            self.text_tracks
+                .iter_media()
+                .any(|m| m.typ() == MediaTagType::ClosedCaptions && m.group_id() == group_id)
+        })
+    }
+
     /// Returns an Option to the reference of an `Url` to the Media Playlist whose media `id` (as
     /// returned by methods such as `audio_media_id_for`) is given as argument.
     ///
@@ -339,6 +756,47 @@ impl MultivariantPlaylist {
             .and_then(|x| x.media_playlist())
     }
 
+    /// Returns an Option to the reference of an `Url` to the video Media Playlist whose media
+    /// `id` (as returned by methods such as `video_media_playlist_id_for`) is given as argument.
+    ///
+    /// Returns `None` either if no video media is found with that `id` or if there is but no
+    /// particular `Url` is linked to it.
+    fn video_url(&self, media_id: u32) -> Option<&Url> {
+        self.video_tracks.media_tag(media_id).and_then(|x| {
+            if let Some(playlist) = x.media_playlist() {
+                Some(playlist.url())
+            } else {
+                x.url()
+            }
+        })
+    }
+
+    /// Returns optional reference to the `MediaPlaylist` linked to the given video media `id`.
+    ///
+    /// Returns `None` either if the given `id` isn't linked to any video media, if it has no
+    /// linked `MediaPlaylist` or if its Media Playlist hasn't been fetched yet.
+    fn video_playlist(&self, media_id: u32) -> Option<&MediaPlaylist> {
+        self.video_tracks
+            .media_tag(media_id)
+            .and_then(|x| x.media_playlist())
+    }
+
+    fn text_url(&self, media_id: u32) -> Option<&Url> {
+        self.text_tracks.media_tag(media_id).and_then(|x| {
+            if let Some(playlist) = x.media_playlist() {
+                Some(playlist.url())
+            } else {
+                x.url()
+            }
+        })
+    }
+
+    fn text_playlist(&self, media_id: u32) -> Option<&MediaPlaylist> {
+        self.text_tracks
+            .media_tag(media_id)
+            .and_then(|x| x.media_playlist())
+    }
+
     fn other_media_playlist(&self, media_id: u32) -> Option<&MediaPlaylist> {
         self.other_media
             .iter()
@@ -377,7 +835,12 @@ impl MultivariantPlaylist {
     pub(crate) fn media_playlist_url(&self, wanted_id: &MediaPlaylistPermanentId) -> Option<&Url> {
         match wanted_id.location() {
             MediaPlaylistUrlLocation::Variant => Some(self.variant(wanted_id.id())?.url()),
+            MediaPlaylistUrlLocation::IFrameVariant => {
+                Some(self.i_frame_variant(wanted_id.id())?.url())
+            }
             MediaPlaylistUrlLocation::AudioTrack => self.audio_url(wanted_id.id()),
+            MediaPlaylistUrlLocation::VideoTrack => self.video_url(wanted_id.id()),
+            MediaPlaylistUrlLocation::TextTrack => self.text_url(wanted_id.id()),
             MediaPlaylistUrlLocation::OtherMedia => self.other_media_url(wanted_id.id()),
         }
     }
@@ -390,7 +853,12 @@ impl MultivariantPlaylist {
             MediaPlaylistUrlLocation::Variant => {
                 Some(self.variant(wanted_id.id())?.media_playlist()?)
             }
+            MediaPlaylistUrlLocation::IFrameVariant => {
+                Some(self.i_frame_variant(wanted_id.id())?.media_playlist()?)
+            }
             MediaPlaylistUrlLocation::AudioTrack => self.audio_playlist(wanted_id.id()),
+            MediaPlaylistUrlLocation::VideoTrack => self.video_playlist(wanted_id.id()),
+            MediaPlaylistUrlLocation::TextTrack => self.text_playlist(wanted_id.id()),
             MediaPlaylistUrlLocation::OtherMedia => self.other_media_playlist(wanted_id.id()),
         }
     }
@@ -405,9 +873,18 @@ impl MultivariantPlaylist {
             MediaPlaylistUrlLocation::Variant => {
                 self.update_variant_media_playlist(id.id(), data, url)
             }
+            MediaPlaylistUrlLocation::IFrameVariant => {
+                self.update_i_frame_variant_media_playlist(id.id(), data, url)
+            }
             MediaPlaylistUrlLocation::AudioTrack => {
                 self.update_audio_media_playlist(id.id(), data, url)
             }
+            MediaPlaylistUrlLocation::VideoTrack => {
+                self.update_video_media_playlist(id.id(), data, url)
+            }
+            MediaPlaylistUrlLocation::TextTrack => {
+                self.update_text_media_playlist(id.id(), data, url)
+            }
             MediaPlaylistUrlLocation::OtherMedia => {
                 self.update_other_media_playlist(id.id(), data, url)
             }
@@ -420,8 +897,43 @@ impl MultivariantPlaylist {
         media_playlist_data: impl io::BufRead,
         url: Url,
     ) -> Result<&MediaPlaylist, MediaPlaylistUpdateError> {
+        let prev_playlist = self
+            .variant(variant_id)
+            .and_then(|v| v.media_playlist())
+            .or_else(|| Self::timeline_reference(self.variant_playlists()));
+        let new_mp = MediaPlaylist::create(media_playlist_data, url, prev_playlist, &self.context)?;
         match self.variants.iter_mut().find(|v| v.id() == variant_id) {
-            Some(v) => Ok(v.update_media_playlist(media_playlist_data, url, &self.context)?),
+            Some(v) => Ok(v.set_media_playlist(new_mp)),
+            None => Err(MediaPlaylistUpdateError::NotFound),
+        }
+    }
+
+    fn update_i_frame_variant_media_playlist(
+        &mut self,
+        variant_id: u32,
+        media_playlist_data: impl io::BufRead,
+        url: Url,
+    ) -> Result<&MediaPlaylist, MediaPlaylistUpdateError> {
+        // I-frame-only Media Playlists share the timeline of the regular variants
+        let prev_playlist = self
+            .i_frame_variant(variant_id)
+            .and_then(|v| v.media_playlist())
+            .or_else(|| {
+                Self::timeline_reference(
+                    self.variant_playlists().chain(
+                        self.i_frame_variants
+                            .iter()
+                            .filter_map(|v| v.media_playlist()),
+                    ),
+                )
+            });
+        let new_mp = MediaPlaylist::create(media_playlist_data, url, prev_playlist, &self.context)?;
+        match self
+            .i_frame_variants
+            .iter_mut()
+            .find(|v| v.id() == variant_id)
+        {
+            Some(v) => Ok(v.set_media_playlist(new_mp)),
             None => Err(MediaPlaylistUpdateError::NotFound),
         }
     }
@@ -432,8 +944,61 @@ impl MultivariantPlaylist {
         media_playlist_data: impl io::BufRead,
         url: Url,
     ) -> Result<&MediaPlaylist, MediaPlaylistUpdateError> {
+        // Audio renditions only share media sequence numbers with renditions of the same type
+        let prev_playlist = self.audio_playlist(id).or_else(|| {
+            Self::timeline_reference(
+                self.audio_tracks
+                    .iter_tracks_media()
+                    .filter_map(|(_, m)| m.media_playlist()),
+            )
+        });
+        let new_mp = MediaPlaylist::create(media_playlist_data, url, prev_playlist, &self.context)?;
         match self.audio_tracks.media_tag_mut(id) {
-            Some(m) => Ok(m.update(media_playlist_data, url, &self.context)?),
+            Some(m) => Ok(m.set_media_playlist(new_mp)),
+            None => Err(MediaPlaylistUpdateError::NotFound),
+        }
+    }
+
+    fn update_video_media_playlist(
+        &mut self,
+        id: u32,
+        media_playlist_data: impl io::BufRead,
+        url: Url,
+    ) -> Result<&MediaPlaylist, MediaPlaylistUpdateError> {
+        // Video renditions share the timeline of the regular variants
+        let prev_playlist = self.video_playlist(id).or_else(|| {
+            Self::timeline_reference(
+                self.variant_playlists().chain(
+                    self.video_tracks
+                        .iter_tracks_media()
+                        .filter_map(|(_, m)| m.media_playlist()),
+                ),
+            )
+        });
+        let new_mp = MediaPlaylist::create(media_playlist_data, url, prev_playlist, &self.context)?;
+        match self.video_tracks.media_tag_mut(id) {
+            Some(m) => Ok(m.set_media_playlist(new_mp)),
+            None => Err(MediaPlaylistUpdateError::NotFound),
+        }
+    }
+
+    fn update_text_media_playlist(
+        &mut self,
+        id: u32,
+        media_playlist_data: impl io::BufRead,
+        url: Url,
+    ) -> Result<&MediaPlaylist, MediaPlaylistUpdateError> {
+        // Subtitles renditions only share media sequence numbers with renditions of the same type
+        let prev_playlist = self.text_playlist(id).or_else(|| {
+            Self::timeline_reference(
+                self.text_tracks
+                    .iter_media()
+                    .filter_map(|m| m.media_playlist()),
+            )
+        });
+        let new_mp = MediaPlaylist::create(media_playlist_data, url, prev_playlist, &self.context)?;
+        match self.text_tracks.media_tag_mut(id) {
+            Some(m) => Ok(m.set_media_playlist(new_mp)),
             None => Err(MediaPlaylistUpdateError::NotFound),
         }
     }
@@ -444,16 +1009,56 @@ impl MultivariantPlaylist {
         media_playlist_data: impl io::BufRead,
         url: Url,
     ) -> Result<&MediaPlaylist, MediaPlaylistUpdateError> {
+        // Those renditions only share media sequence numbers with renditions of the same type
+        let prev_playlist = self.other_media_playlist(media_tag_id).or_else(|| {
+            Self::timeline_reference(self.other_media.iter().filter_map(|m| m.media_playlist()))
+        });
+        let new_mp = MediaPlaylist::create(media_playlist_data, url, prev_playlist, &self.context)?;
         match self.other_media.iter_mut().find(|v| v.id() == media_tag_id) {
-            Some(m) => Ok(m.update(media_playlist_data, url, &self.context)?),
+            Some(m) => Ok(m.set_media_playlist(new_mp)),
             None => Err(MediaPlaylistUpdateError::NotFound),
         }
     }
 
+    /// Returns the already-loaded Media Playlists of the regular variants.
+    fn variant_playlists(&self) -> impl Iterator<Item = &MediaPlaylist> {
+        self.variants.iter().filter_map(|v| v.media_playlist())
+    }
+
+    /// When a Media Playlist is loaded for the first time, returns the most up-to-date
+    /// already-loaded `MediaPlaylist` amongst `candidates`, which are the ones expected to share
+    /// its timeline.
+    ///
+    /// That `MediaPlaylist` may then be relied on to keep segment start times coherent between
+    /// Media Playlists (e.g. when switching variants) when they cannot be deduced from the
+    /// Media Playlist alone.
+    ///
+    /// Returns `None` if no candidate is found.
+    fn timeline_reference<'a>(
+        candidates: impl Iterator<Item = &'a MediaPlaylist>,
+    ) -> Option<&'a MediaPlaylist> {
+        candidates
+            .filter(|p| p.last_media_sequence().is_some())
+            .max_by_key(|p| p.last_media_sequence())
+    }
+
     pub(crate) fn audio_tracks(&self) -> &[AudioTrack] {
         self.audio_tracks.as_slice()
     }
 
+    pub(crate) fn video_tracks(&self) -> &[VideoTrack] {
+        self.video_tracks.as_slice()
+    }
+
+    pub(crate) fn text_tracks(&self) -> &[TextTrack] {
+        self.text_tracks.as_slice()
+    }
+
+    /// Returns the data announced through `EXT-X-SESSION-DATA` tags, in playlist order.
+    pub(crate) fn session_data(&self) -> &[SessionData] {
+        self.session_data.as_slice()
+    }
+
     pub(crate) fn audio_track_for_media_id(
         &self,
         id: &MediaPlaylistPermanentId,
@@ -471,6 +1076,124 @@ impl MultivariantPlaylist {
             _ => None,
         }
     }
+
+    pub(crate) fn video_track_for_media_id(
+        &self,
+        id: &MediaPlaylistPermanentId,
+    ) -> Option<&VideoTrack> {
+        match id.location() {
+            MediaPlaylistUrlLocation::VideoTrack => self.video_tracks.track_for_media_tag(id.id()),
+            MediaPlaylistUrlLocation::Variant => {
+                let variant = self.variant(id.id())?;
+                let group = variant.video_group()?;
+                self.video_tracks
+                    .iter_tracks_media()
+                    .find(|(_, v)| v.url().is_none() && v.group_id() == group)
+                    .map(|t| t.0)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Makes each of `variants` inherit from the variant it corresponds to in `prev_variants`, the
+/// variants of a previous version of the same Multivariant Playlist, or give it a new `id`
+/// through `new_id` if there's none.
+///
+/// Variants are matched through their `STABLE-VARIANT-ID` attribute, or through their URL if
+/// they don't have one, in the same Content Steering Pathway.
+fn reconcile_variants(
+    variants: &mut [VariantStream],
+    prev_variants: &mut [VariantStream],
+    new_id: &mut impl FnMut() -> u32,
+) {
+    let mut matched = vec![false; prev_variants.len()];
+    for variant in variants.iter_mut() {
+        let pos = prev_variants.iter().enumerate().position(|(i, prev)| {
+            !matched[i]
+                && variant.pathway_id() == prev.pathway_id()
+                && match (variant.stable_id(), prev.stable_id()) {
+                    (Some(stable_id), Some(prev_stable_id)) => stable_id == prev_stable_id,
+                    _ => variant.url() == prev.url(),
+                }
+        });
+        match pos {
+            Some(pos) => {
+                matched[pos] = true;
+                variant.inherit_from(&mut prev_variants[pos]);
+            }
+            None => variant.set_id(new_id()),
+        }
+    }
+}
+
+/// Gives back to each of `tracks` the `id` of the track it corresponds to in `prev_tracks`, the
+/// tracks of a previous version of the same Multivariant Playlist.
+///
+/// Tracks are matched through their `STABLE-RENDITION-ID`, or through the `id` of their
+/// `MediaTag`s - which should thus already have been reconciled - if they don't have one.
+///
+/// Tracks without a corresponding one in `prev_tracks` are given a new `id` through `new_id`.
+fn reconcile_tracks<T: MediaTrack>(
+    tracks: &mut [T],
+    prev_tracks: &[T],
+    new_id: &mut impl FnMut() -> u32,
+) {
+    let mut used_ids: Vec<u32> = vec![];
+    for track in tracks.iter_mut() {
+        let prev_id = prev_tracks
+            .iter()
+            .filter(|prev| !used_ids.contains(&prev.id()))
+            .find(|prev| match (track.stable_id(), prev.stable_id()) {
+                (Some(stable_id), Some(prev_stable_id)) => stable_id == prev_stable_id,
+                _ => track
+                    .media_tags()
+                    .iter()
+                    .any(|m| prev.media_tags().iter().any(|prev_m| prev_m.id() == m.id())),
+            })
+            .map(|prev| prev.id());
+        let id = prev_id.unwrap_or_else(&mut *new_id);
+        track.set_id(id);
+        used_ids.push(id);
+    }
+}
+
+/// Equivalent of `reconcile_variants` for renditions.
+///
+/// Renditions are matched through their `STABLE-RENDITION-ID` attribute in the same `GROUP-ID`,
+/// or through their URL if they don't have one. Renditions without URL are matched through their
+/// `GROUP-ID` and `NAME`.
+fn reconcile_media<'a>(
+    media: impl Iterator<Item = &'a mut MediaTag>,
+    mut prev_media: Vec<&mut MediaTag>,
+    new_id: &mut impl FnMut() -> u32,
+) {
+    let mut matched = vec![false; prev_media.len()];
+    for media in media {
+        let pos = prev_media.iter().enumerate().position(|(i, prev)| {
+            !matched[i]
+                && media.typ() == prev.typ()
+                && match (media.stable_id(), prev.stable_id()) {
+                    (Some(stable_id), Some(prev_stable_id)) => {
+                        stable_id == prev_stable_id && media.group_id() == prev.group_id()
+                    }
+                    _ => match (media.url(), prev.url()) {
+                        (Some(url), Some(prev_url)) => url == prev_url,
+                        (None, None) => {
+                            media.group_id() == prev.group_id() && media.name() == prev.name()
+                        }
+                        _ => false,
+                    },
+                }
+        });
+        match pos {
+            Some(pos) => {
+                matched[pos] = true;
+                media.inherit_from(prev_media[pos]);
+            }
+            None => media.set_id(new_id()),
+        }
+    }
 }
 
 /// Values parsed from a MultivariantPlaylist that may have an influence on
@@ -479,6 +1202,8 @@ impl MultivariantPlaylist {
 pub(crate) struct MediaPlaylistContext {
     independent_segments: Option<bool>,
     start: Option<StartAttribute>,
+    /// Variables defined in the MultivariantPlaylist, which may be imported by Media Playlists.
+    variables: PlaylistVariables,
 }
 
 impl MediaPlaylistContext {
@@ -488,6 +1213,9 @@ impl MediaPlaylistContext {
     pub(crate) fn independent_segments(&self) -> Option<bool> {
         self.independent_segments
     }
+    pub(crate) fn variables(&self) -> &PlaylistVariables {
+        &self.variables
+    }
 }
 
 // NOTE: should we add information on the line at which the error was encountered?
@@ -508,6 +1236,8 @@ pub enum MultivariantPlaylistParsingError {
 
     UnableToReadLine,
 
+    UndefinedVariable,
+
     Unknown,
 }
 
@@ -541,6 +1271,10 @@ impl fmt::Display for MultivariantPlaylistParsingError {
                 f,
                 "A line of the MultivariantPlaylist was impossible to parse"
             ),
+            MultivariantPlaylistParsingError::UndefinedVariable => write!(
+                f,
+                "A variable was referenced or imported without being defined"
+            ),
             MultivariantPlaylistParsingError::MissingExtM3uHeader => write!(
                 f,
                 "The first line of the Multivariant Playlist isn't `#EXTM3U`. Are you sure this is a Multivariant Playlist?"
@@ -562,10 +1296,19 @@ impl From<VariantParsingError> for MultivariantPlaylistParsingError {
             VariantParsingError::MissingBandwidth => {
                 MultivariantPlaylistParsingError::VariantMissingBandwidth
             }
+            VariantParsingError::MissingUri => {
+                MultivariantPlaylistParsingError::MissingUriLineAfterVariant
+            }
         }
     }
 }
 
+impl From<UndefinedVariableError> for MultivariantPlaylistParsingError {
+    fn from(_: UndefinedVariableError) -> MultivariantPlaylistParsingError {
+        MultivariantPlaylistParsingError::UndefinedVariable
+    }
+}
+
 impl error::Error for MediaPlaylistUpdateError {}
 
 impl From<MediaTagParsingError> for MultivariantPlaylistParsingError {
@@ -640,8 +1383,284 @@ impl MediaPlaylistPermanentId {
 enum MediaPlaylistUrlLocation {
     /// This Media Playlist's URL is defined by a variant in the `MultivariantPlaylist` object.
     Variant,
+    /// This Media Playlist's URL is defined by an I-frame-only variant in the
+    /// `MultivariantPlaylist` object.
+    IFrameVariant,
     /// This Media Playlist's URL is an audio-specific track in the `MultivariantPlaylist` object.
     AudioTrack,
+    /// This Media Playlist's URL is a video-specific track in the `MultivariantPlaylist` object.
+    VideoTrack,
+    /// This Media Playlist's URL is a subtitles track in the `MultivariantPlaylist` object.
+    TextTrack,
     /// This Media Playlist's URL is defined as another media in the `MultivariantPlaylist` object.
     OtherMedia,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bindings::MediaType, utils::logger::LoggerLevel};
+
+    fn parse(playlist: &str) -> MultivariantPlaylist {
+        MultivariantPlaylist::parse(
+            playlist.as_bytes(),
+            Url::new("https://example.com/main.m3u8".to_owned()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_reconcile_with() {
+        let mut prev = parse(
+            "#EXTM3U
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"English\",LANGUAGE=\"en\",URI=\"en.m3u8\"
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"French\",LANGUAGE=\"fr\",URI=\"fr.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,AUDIO=\"aud\",STABLE-VARIANT-ID=\"low\"
+low.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,AUDIO=\"aud\",STABLE-VARIANT-ID=\"high\"
+high.m3u8
+",
+        );
+        let mut new = parse(
+            "#EXTM3U
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"French\",LANGUAGE=\"fr\",URI=\"fr.m3u8\"
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"German\",LANGUAGE=\"de\",URI=\"de.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=3000000,AUDIO=\"aud\",STABLE-VARIANT-ID=\"higher\"
+higher.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,AUDIO=\"aud\",STABLE-VARIANT-ID=\"high\"
+high-new-cdn.m3u8
+",
+        );
+        let prev_high_id = prev.variants.iter().find(|v| v.bandwidth() == 2000000);
+        let prev_high_id = prev_high_id.unwrap().id();
+        let prev_fr_track_id = prev.audio_tracks().iter().find(|t| t.name() == "French");
+        let prev_fr_track_id = prev_fr_track_id.unwrap().id();
+        let prev_last_id = prev.last_id;
+
+        new.reconcile_with(&mut prev);
+
+        let new_high = new.variants.iter().find(|v| v.bandwidth() == 2000000);
+        assert_eq!(new_high.unwrap().id(), prev_high_id);
+        let new_higher = new.variants.iter().find(|v| v.bandwidth() == 3000000);
+        assert!(new_higher.unwrap().id() >= prev_last_id);
+        let new_fr_track = new.audio_tracks().iter().find(|t| t.name() == "French");
+        assert_eq!(new_fr_track.unwrap().id(), prev_fr_track_id);
+        let new_de_track = new.audio_tracks().iter().find(|t| t.name() == "German");
+        assert!(new_de_track.unwrap().id() >= prev_last_id);
+        assert_eq!(new.last_id, prev_last_id + 3);
+    }
+
+    #[test]
+    fn test_media_playlist_timeline_reference() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let media_playlist = |media_sequence: u64, name: &str| {
+            format!(
+                "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MEDIA-SEQUENCE:{media_sequence}
+#EXTINF:4,
+{name}{media_sequence}.mp4
+#EXTINF:4,
+{name}{}.mp4
+",
+                media_sequence + 1
+            )
+        };
+        let mut playlist = parse(
+            "#EXTM3U
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"English\",LANGUAGE=\"en\",URI=\"en.m3u8\"
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"French\",LANGUAGE=\"fr\",URI=\"fr.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,AUDIO=\"aud\"
+low.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,AUDIO=\"aud\"
+high.m3u8
+",
+        );
+        let prefs = AudioSelectionPreferences::default();
+        let ids: Vec<u32> = playlist.variants.iter().map(|v| v.id()).collect();
+        let track_ids: Vec<u32> = playlist.audio_tracks().iter().map(|t| t.id()).collect();
+        let mut load = |variant_id: u32, audio_track: Option<u32>, url: &str, data: String| {
+            let variant = playlist.variant(variant_id).unwrap();
+            let id = match audio_track {
+                Some(t) => playlist.audio_media_playlist_id_for(variant, Some(t), prefs),
+                None => playlist.video_media_playlist_id_for(variant, None),
+            };
+            let url = Url::new(format!("https://example.com/{url}"));
+            let loaded = playlist
+                .update_media_playlist(&id.unwrap(), data.as_bytes(), url)
+                .unwrap();
+            loaded.segment_list().media()[0].start()
+        };
+
+        assert_eq!(
+            load(ids[0], None, "low.m3u8", media_playlist(100, "low")),
+            0.
+        );
+        // Another variant relies on the timeline of the first one
+        let start = load(ids[1], None, "high.m3u8", media_playlist(101, "high"));
+        assert_eq!(start, 4.);
+
+        // Audio renditions do not share media sequence numbers with variants
+        let start = load(
+            ids[0],
+            Some(track_ids[0]),
+            "en.m3u8",
+            media_playlist(5, "en"),
+        );
+        assert_eq!(start, 0.);
+        // But they do with other audio renditions
+        let start = load(
+            ids[0],
+            Some(track_ids[1]),
+            "fr.m3u8",
+            media_playlist(6, "fr"),
+        );
+        assert_eq!(start, 4.);
+    }
+
+    #[test]
+    fn test_reconcile_with_reordered_and_renamed() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let media_playlist = "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXTINF:4,
+seg1.mp4
+#EXT-X-ENDLIST
+";
+        let mut prev = parse(
+            "#EXTM3U
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"English\",LANGUAGE=\"en\",STABLE-RENDITION-ID=\"en-main\",URI=\"en.m3u8\"
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"French\",LANGUAGE=\"fr\",URI=\"fr.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,CODECS=\"avc1.4D401F,mp4a.40.2\",AUDIO=\"aud\"
+low.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,CODECS=\"avc1.4D401F,mp4a.40.2\",AUDIO=\"aud\"
+high.m3u8
+",
+        );
+        let prefs = AudioSelectionPreferences::default();
+        let high = prev.variants.iter().find(|v| v.bandwidth() == 2000000);
+        let high_id = high.unwrap().id();
+        let low = prev.variants.iter().find(|v| v.bandwidth() == 1000000);
+        let low_id = low.unwrap().id();
+        let en_track = prev.audio_tracks().iter().find(|t| t.name() == "English");
+        let en_track_id = en_track.unwrap().id();
+
+        // Load the Media Playlists of the selected variant and audio track
+        let high = prev.variant(high_id).unwrap();
+        let video_pl_id = prev.video_media_playlist_id_for(high, None).unwrap();
+        let audio_pl_id = prev
+            .audio_media_playlist_id_for(high, Some(en_track_id), prefs)
+            .unwrap();
+        for (id, url) in [(&video_pl_id, "high.m3u8"), (&audio_pl_id, "en.m3u8")] {
+            let url = Url::new(format!("https://example.com/{url}"));
+            prev.update_media_playlist(id, media_playlist.as_bytes(), url)
+                .unwrap();
+        }
+
+        // Variants are reordered, without stable id, and the English rendition is renamed and
+        // moved to another URL but keeps its stable id
+        let mut new = parse(
+            "#EXTM3U
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"French\",LANGUAGE=\"fr\",URI=\"fr.m3u8\"
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"English (main)\",LANGUAGE=\"en\",STABLE-RENDITION-ID=\"en-main\",URI=\"en-v2.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,CODECS=\"avc1.4D401F,mp4a.40.2\",AUDIO=\"aud\"
+high.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=1500000,CODECS=\"avc1.4D401F,mp4a.40.2\",AUDIO=\"aud\"
+mid.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,CODECS=\"avc1.4D401F,mp4a.40.2\",AUDIO=\"aud\"
+low.m3u8
+",
+        );
+        new.reconcile_with(&mut prev);
+
+        let new_high = new.variant(high_id).unwrap();
+        assert_eq!(new_high.bandwidth(), 2000000);
+        assert_eq!(new.variant(low_id).unwrap().bandwidth(), 1000000);
+        let new_en_track = new.audio_tracks().iter().find(|t| t.id() == en_track_id);
+        assert_eq!(new_en_track.unwrap().name(), "English (main)");
+
+        // The same Media Playlists are selected and are still loaded
+        let new_video_pl_id = new.video_media_playlist_id_for(new_high, None).unwrap();
+        assert_eq!(new_video_pl_id, video_pl_id);
+        assert!(new.media_playlist(&new_video_pl_id).is_some());
+        let new_audio_pl_id = new
+            .audio_media_playlist_id_for(new_high, Some(en_track_id), prefs)
+            .unwrap();
+        assert_eq!(new_audio_pl_id, audio_pl_id);
+        assert!(new.media_playlist(&new_audio_pl_id).is_some());
+        assert_eq!(
+            new.media_playlist_url(&new_audio_pl_id)
+                .map(|u| u.get_ref()),
+            Some("https://example.com/en-v2.m3u8")
+        );
+    }
+
+    #[test]
+    fn test_is_media_playlist() {
+        assert!(MultivariantPlaylist::is_media_playlist(
+            b"#EXTM3U\n#EXT-X-TARGETDURATION:4\n#EXTINF:4,\nseg1.ts\n#EXT-X-ENDLIST\n"
+        ));
+        assert!(MultivariantPlaylist::is_media_playlist(
+            b"#EXTM3U\n#EXT-X-TARGETDURATION:4\n"
+        ));
+        assert!(!MultivariantPlaylist::is_media_playlist(
+            b"#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=1000000\nlow.m3u8\n"
+        ));
+        assert!(!MultivariantPlaylist::is_media_playlist(b"#EXTM3U\n"));
+        assert!(!MultivariantPlaylist::is_media_playlist(b""));
+    }
+
+    #[test]
+    fn test_from_media_playlist() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let from_media_playlist = |playlist: &str| {
+            MultivariantPlaylist::from_media_playlist(
+                playlist.as_bytes(),
+                Url::new("https://example.com/media.m3u8".to_owned()),
+            )
+        };
+
+        let playlist = from_media_playlist(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXTINF:4,
+seg1.ts
+#EXTINF:4,
+seg2.ts
+#EXT-X-ENDLIST
+",
+        )
+        .unwrap();
+        assert_eq!(playlist.all_variants().len(), 1);
+        let variant = &playlist.all_variants()[0];
+        assert_eq!(variant.url().get_ref(), "https://example.com/media.m3u8");
+        assert!(variant.has_guessed_codecs());
+        assert_eq!(
+            variant.codecs(MediaType::Video).as_deref(),
+            Some("avc1.4D401F,mp4a.40.2")
+        );
+        assert!(playlist.audio_tracks().is_empty());
+
+        // Its Media Playlist is already loaded
+        let id = playlist.video_media_playlist_id_for(variant, None).unwrap();
+        let media_playlist = playlist.media_playlist(&id).unwrap();
+        assert_eq!(media_playlist.segment_list().media().len(), 2);
+        assert!(media_playlist.has_mpeg_ts_segments());
+
+        let playlist = from_media_playlist(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXTINF:4,
+seg1.aac
+",
+        )
+        .unwrap();
+        let variant = &playlist.all_variants()[0];
+        assert_eq!(
+            variant.codecs(MediaType::Video).as_deref(),
+            Some("mp4a.40.2")
+        );
+
+        assert!(from_media_playlist("#EXTM3U\n#EXTINF:4,\nseg1.ts\n").is_err());
+    }
+}
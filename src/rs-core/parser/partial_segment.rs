@@ -0,0 +1,397 @@
+use crate::{utils::url::Url, Logger};
+
+use super::utils::{
+    parse_byte_range, parse_decimal_floating_point, parse_decimal_integer, parse_enumerated_string,
+    parse_quoted_string, skip_attribute_list_value, ByteRange,
+};
+
+/// Information parsed from a single `EXT-X-PART` tag, describing a partial segment of a
+/// Low-Latency HLS Media Playlist.
+#[derive(Clone, Debug)]
+pub(super) struct PartTag {
+    /// URL at which the partial segment may be requested.
+    url: Url,
+    /// Duration of the partial segment, in seconds.
+    duration: f64,
+    /// If `true`, the partial segment contains an independent frame.
+    independent: bool,
+    /// If set, byte-range to specifically request only the partial segment at the given `url`.
+    byte_range: Option<ByteRange>,
+    /// If `true`, the partial segment is not available.
+    gap: bool,
+}
+
+/// Errors that may arise when parsing an `EXT-X-PART` tag.
+#[derive(Debug)]
+pub(super) enum PartTagParsingError {
+    /// The mandatory `URI` attribute was missing.
+    MissingUri,
+    /// The mandatory `DURATION` attribute was missing or unparsable.
+    MissingDuration,
+    /// The `BYTERANGE` attribute was not in the right format.
+    UnparsableByteRange,
+}
+
+impl PartTag {
+    /// Parse the `EXT-X-PART` tag contained in `part_line`.
+    ///
+    /// Relative `URI` attributes are resolved against `playlist_base_url`.
+    ///
+    /// `prev_byte` is the byte following the sub-range of the previous partial segment, if one,
+    /// which is where a `BYTERANGE` without offset will start.
+    pub(super) fn parse(
+        part_line: &str,
+        playlist_base_url: &str,
+        prev_byte: Option<usize>,
+    ) -> Result<Self, PartTagParsingError> {
+        let mut url: Option<Url> = None;
+        let mut duration: Option<f64> = None;
+        let mut independent = false;
+        let mut byte_range: Option<ByteRange> = None;
+        let mut gap = false;
+
+        let mut offset = "#EXT-X-PART:".len();
+        loop {
+            if offset >= part_line.len() {
+                break;
+            }
+            match part_line[offset..].find('=') {
+                None => {
+                    Logger::warn("Attribute Name not followed by equal sign");
+                    break;
+                }
+                Some(idx) => match &part_line[offset..offset + idx] {
+                    "URI" => {
+                        let (parsed, end_offset) = parse_quoted_string(part_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            url = Some(Url::resolve(val, playlist_base_url));
+                        } else {
+                            Logger::warn("Unparsable part URI value");
+                        }
+                    }
+                    "DURATION" => {
+                        let (parsed, end_offset) =
+                            parse_decimal_floating_point(part_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            duration = Some(val);
+                        } else {
+                            Logger::warn("Unparsable part DURATION value");
+                        }
+                    }
+                    "INDEPENDENT" => {
+                        let (parsed, end_offset) =
+                            parse_enumerated_string(part_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        independent = parsed == "YES";
+                    }
+                    "GAP" => {
+                        let (parsed, end_offset) =
+                            parse_enumerated_string(part_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        gap = parsed == "YES";
+                    }
+                    "BYTERANGE" => {
+                        let (parsed, end_offset) = parse_quoted_string(part_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed
+                            .ok()
+                            .and_then(|val| parse_byte_range(val, 0, prev_byte))
+                        {
+                            Some(br) => byte_range = Some(br),
+                            None => return Err(PartTagParsingError::UnparsableByteRange),
+                        }
+                    }
+                    _ => offset = skip_attribute_list_value(part_line, offset + idx + 1) + 1,
+                },
+            }
+        }
+
+        Ok(PartTag {
+            url: url.ok_or(PartTagParsingError::MissingUri)?,
+            duration: duration.ok_or(PartTagParsingError::MissingDuration)?,
+            independent,
+            byte_range,
+            gap,
+        })
+    }
+
+    /// URL at which the partial segment may be requested.
+    pub(super) fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Duration of the partial segment, in seconds.
+    pub(super) fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// Returns `true` if the partial segment contains an independent frame, from which decoding
+    /// may begin.
+    pub(super) fn is_independent(&self) -> bool {
+        self.independent
+    }
+
+    /// If set, byte-range at which the partial segment should be requested.
+    pub(super) fn byte_range(&self) -> Option<&ByteRange> {
+        self.byte_range.as_ref()
+    }
+
+    /// Returns `true` if the partial segment is announced as not available.
+    pub(super) fn is_gap(&self) -> bool {
+        self.gap
+    }
+}
+
+/// The type of resource an `EXT-X-PRELOAD-HINT` tag is hinting at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum PreloadHintType {
+    /// The next partial segment.
+    Part,
+    /// The next initialization segment.
+    Map,
+}
+
+/// Information parsed from a single `EXT-X-PRELOAD-HINT` tag, announcing a resource that will
+/// soon be available and that may be requested in advance.
+#[derive(Clone, Debug)]
+pub(super) struct PreloadHintTag {
+    /// The type of the hinted resource.
+    typ: PreloadHintType,
+    /// URL at which the hinted resource may be requested.
+    url: Url,
+    /// If set, byte-range to specifically request only the hinted resource at the given `url`.
+    byte_range: Option<ByteRange>,
+    /// If `true`, the hinted resource starts at a given byte offset of `url` but its length is
+    /// unknown, in which case `byte_range` is not set.
+    has_unbounded_byte_range: bool,
+}
+
+/// Errors that may arise when parsing an `EXT-X-PRELOAD-HINT` tag.
+#[derive(Debug)]
+pub(super) enum PreloadHintTagParsingError {
+    /// The mandatory `TYPE` attribute was missing or not recognized.
+    MissingType,
+    /// The mandatory `URI` attribute was missing.
+    MissingUri,
+}
+
+impl PreloadHintTag {
+    /// Parse the `EXT-X-PRELOAD-HINT` tag contained in `hint_line`.
+    ///
+    /// Relative `URI` attributes are resolved against `playlist_base_url`.
+    pub(super) fn parse(
+        hint_line: &str,
+        playlist_base_url: &str,
+    ) -> Result<Self, PreloadHintTagParsingError> {
+        let mut typ: Option<PreloadHintType> = None;
+        let mut url: Option<Url> = None;
+        let mut byte_range_start = 0;
+        let mut byte_range_length: Option<usize> = None;
+
+        let mut offset = "#EXT-X-PRELOAD-HINT:".len();
+        loop {
+            if offset >= hint_line.len() {
+                break;
+            }
+            match hint_line[offset..].find('=') {
+                None => {
+                    Logger::warn("Attribute Name not followed by equal sign");
+                    break;
+                }
+                Some(idx) => match &hint_line[offset..offset + idx] {
+                    "TYPE" => {
+                        let (parsed, end_offset) =
+                            parse_enumerated_string(hint_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        typ = match parsed {
+                            "PART" => Some(PreloadHintType::Part),
+                            "MAP" => Some(PreloadHintType::Map),
+                            x => {
+                                Logger::warn(&format!("Unrecognized preload hint TYPE: {}", x));
+                                None
+                            }
+                        };
+                    }
+                    "URI" => {
+                        let (parsed, end_offset) = parse_quoted_string(hint_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            url = Some(Url::resolve(val, playlist_base_url));
+                        } else {
+                            Logger::warn("Unparsable preload hint URI value");
+                        }
+                    }
+                    "BYTERANGE-START" => {
+                        let (parsed, end_offset) =
+                            parse_decimal_integer(hint_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed {
+                            Ok(val) => byte_range_start = val as usize,
+                            Err(_) => Logger::warn("Unparsable BYTERANGE-START value"),
+                        }
+                    }
+                    "BYTERANGE-LENGTH" => {
+                        let (parsed, end_offset) =
+                            parse_decimal_integer(hint_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed {
+                            Ok(val) => byte_range_length = Some(val as usize),
+                            Err(_) => Logger::warn("Unparsable BYTERANGE-LENGTH value"),
+                        }
+                    }
+                    _ => offset = skip_attribute_list_value(hint_line, offset + idx + 1) + 1,
+                },
+            }
+        }
+
+        let (byte_range, has_unbounded_byte_range) = match byte_range_length {
+            Some(length) if length > 0 => (
+                Some(ByteRange {
+                    first_byte: byte_range_start,
+                    last_byte: byte_range_start + length - 1,
+                }),
+                false,
+            ),
+            _ => (None, byte_range_start > 0),
+        };
+        Ok(PreloadHintTag {
+            typ: typ.ok_or(PreloadHintTagParsingError::MissingType)?,
+            url: url.ok_or(PreloadHintTagParsingError::MissingUri)?,
+            byte_range,
+            has_unbounded_byte_range,
+        })
+    }
+
+    /// The type of the hinted resource.
+    pub(super) fn typ(&self) -> PreloadHintType {
+        self.typ
+    }
+
+    /// URL at which the hinted resource may be requested.
+    pub(super) fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// If set, byte-range at which the hinted resource should be requested.
+    pub(super) fn byte_range(&self) -> Option<&ByteRange> {
+        self.byte_range.as_ref()
+    }
+
+    /// Returns `true` if the hinted resource starts at a given byte offset but its length is
+    /// unknown, in which case `byte_range` returns `None`.
+    pub(super) fn has_unbounded_byte_range(&self) -> bool {
+        self.has_unbounded_byte_range
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::logger::LoggerLevel;
+
+    const BASE_URL: &str = "https://example.com/media/";
+
+    #[test]
+    fn test_parse_part_tag() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let part = PartTag::parse(
+            "#EXT-X-PART:DURATION=0.33334,URI=\"part1.mp4\",INDEPENDENT=YES",
+            BASE_URL,
+            None,
+        )
+        .unwrap();
+        assert_eq!(part.url().get_ref(), "https://example.com/media/part1.mp4");
+        assert_eq!(part.duration(), 0.33334);
+        assert!(part.is_independent());
+        assert!(!part.is_gap());
+        assert_eq!(part.byte_range(), None);
+
+        // A BYTERANGE without offset follows the previous partial segment
+        let part = PartTag::parse(
+            "#EXT-X-PART:DURATION=0.5,URI=\"seg.mp4\",BYTERANGE=\"1000\",GAP=YES",
+            BASE_URL,
+            Some(2000),
+        )
+        .unwrap();
+        assert!(!part.is_independent());
+        assert!(part.is_gap());
+        let expected = ByteRange {
+            first_byte: 2000,
+            last_byte: 2999,
+        };
+        assert_eq!(part.byte_range(), Some(&expected));
+        let part = PartTag::parse(
+            "#EXT-X-PART:DURATION=0.5,URI=\"seg.mp4\",BYTERANGE=\"1000@500\"",
+            BASE_URL,
+            Some(2000),
+        )
+        .unwrap();
+        let expected = ByteRange {
+            first_byte: 500,
+            last_byte: 1499,
+        };
+        assert_eq!(part.byte_range(), Some(&expected));
+
+        assert!(matches!(
+            PartTag::parse("#EXT-X-PART:DURATION=0.5", BASE_URL, None),
+            Err(PartTagParsingError::MissingUri)
+        ));
+        assert!(matches!(
+            PartTag::parse("#EXT-X-PART:URI=\"part1.mp4\"", BASE_URL, None),
+            Err(PartTagParsingError::MissingDuration)
+        ));
+        assert!(matches!(
+            PartTag::parse(
+                "#EXT-X-PART:DURATION=0.5,URI=\"seg.mp4\",BYTERANGE=\"1000\"",
+                BASE_URL,
+                None
+            ),
+            Err(PartTagParsingError::UnparsableByteRange)
+        ));
+    }
+
+    #[test]
+    fn test_parse_preload_hint_tag() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let hint =
+            PreloadHintTag::parse("#EXT-X-PRELOAD-HINT:TYPE=PART,URI=\"part2.mp4\"", BASE_URL)
+                .unwrap();
+        assert_eq!(hint.typ(), PreloadHintType::Part);
+        assert_eq!(hint.url().get_ref(), "https://example.com/media/part2.mp4");
+        assert_eq!(hint.byte_range(), None);
+        assert!(!hint.has_unbounded_byte_range());
+
+        let hint = PreloadHintTag::parse(
+            "#EXT-X-PRELOAD-HINT:TYPE=MAP,URI=\"init.mp4\",BYTERANGE-START=100,BYTERANGE-LENGTH=50",
+            BASE_URL,
+        )
+        .unwrap();
+        assert_eq!(hint.typ(), PreloadHintType::Map);
+        let expected = ByteRange {
+            first_byte: 100,
+            last_byte: 149,
+        };
+        assert_eq!(hint.byte_range(), Some(&expected));
+        assert!(!hint.has_unbounded_byte_range());
+
+        // Without a length, the hinted resource goes until the end of the resource
+        let hint = PreloadHintTag::parse(
+            "#EXT-X-PRELOAD-HINT:TYPE=PART,URI=\"seg.mp4\",BYTERANGE-START=3000",
+            BASE_URL,
+        )
+        .unwrap();
+        assert_eq!(hint.byte_range(), None);
+        assert!(hint.has_unbounded_byte_range());
+
+        assert!(matches!(
+            PreloadHintTag::parse("#EXT-X-PRELOAD-HINT:TYPE=OTHER,URI=\"a.mp4\"", BASE_URL),
+            Err(PreloadHintTagParsingError::MissingType)
+        ));
+        assert!(matches!(
+            PreloadHintTag::parse("#EXT-X-PRELOAD-HINT:TYPE=PART", BASE_URL),
+            Err(PreloadHintTagParsingError::MissingUri)
+        ));
+    }
+}
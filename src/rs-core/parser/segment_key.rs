@@ -0,0 +1,302 @@
+use super::utils::{
+    parse_enumerated_string, parse_hexadecimal_sequence, parse_quoted_string,
+    skip_attribute_list_value,
+};
+use crate::{utils::url::Url, Logger};
+
+/// Encryption method used for a media segment, as indicated by the `METHOD` attribute of an
+/// `EXT-X-KEY` tag.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum EncryptionMethod {
+    /// The whole segment is encrypted with AES-128 in CBC mode with PKCS7 padding.
+    Aes128,
+    /// Only media samples are encrypted ("SAMPLE-AES"), generally meant to be decrypted by the
+    /// platform's CDM.
+    SampleAes,
+    /// Only media samples are encrypted in CTR mode ("SAMPLE-AES-CTR"), generally meant to be
+    /// decrypted by the platform's CDM.
+    SampleAesCtr,
+    /// Any other, unrecognized, `METHOD`. Segments are still known to be encrypted but can
+    /// neither be decrypted by the player nor, presumably, by the platform.
+    Unsupported(String),
+}
+
+/// Decryption information linked to a single initialization or media segment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SegmentKey {
+    /// The way in which the segment is encrypted.
+    method: EncryptionMethod,
+
+    /// URL at which the key may be requested.
+    url: Url,
+
+    /// Initialization Vector which should be used with the key.
+    ///
+    /// If it wasn't explicitely announced in the Media Playlist, this is the media sequence
+    /// number of the corresponding media segment as a big-endian 128-bit integer.
+    iv: [u8; 16],
+
+    /// How the key is represented in the resource at `url`.
+    ///
+    /// Defaults to `"identity"`, where the resource directly contains the key as a 16 bytes
+    /// binary array.
+    key_format: String,
+}
+
+impl SegmentKey {
+    /// Returns the way in which the corresponding segment is encrypted.
+    pub(crate) fn method(&self) -> &EncryptionMethod {
+        &self.method
+    }
+
+    /// Returns the URL at which the key may be requested.
+    pub(crate) fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the Initialization Vector which should be used with the key.
+    pub(crate) fn iv(&self) -> &[u8; 16] {
+        &self.iv
+    }
+
+    /// Returns how the key is represented in the resource at `url`.
+    pub(crate) fn key_format(&self) -> &str {
+        &self.key_format
+    }
+
+    /// Returns `true` if the segment linked to this `SegmentKey` has to be decrypted by us before
+    /// being pushed to the buffer.
+    pub(crate) fn is_decrypted_by_player(&self) -> bool {
+        self.method == EncryptionMethod::Aes128 && self.key_format == "identity"
+    }
+}
+
+/// Information parsed from a single `EXT-X-KEY` tag, which will apply to all following segments
+/// until the next `EXT-X-KEY` tag with the same `KEYFORMAT`.
+#[derive(Clone, Debug)]
+pub(super) struct KeyTag {
+    /// The way in which the segment is encrypted.
+    /// `None` if the `METHOD` is set to `NONE`, in which case segments are not encrypted.
+    method: Option<EncryptionMethod>,
+
+    /// URL at which the key may be requested.
+    url: Option<Url>,
+
+    /// Explicit Initialization Vector, if one.
+    iv: Option<[u8; 16]>,
+
+    /// How the key is represented in the resource at `url`.
+    key_format: String,
+}
+
+/// Errors that may arise when parsing an `EXT-X-KEY` tag.
+#[derive(Debug)]
+pub(super) enum KeyTagParsingError {
+    /// The mandatory `METHOD` attribute was missing.
+    MissingMethod,
+    /// The `URI` attribute was missing despite `METHOD` not being set to `NONE`.
+    MissingUri,
+}
+
+impl KeyTag {
+    /// Parse the `EXT-X-KEY` tag contained in `key_line`.
+    ///
+    /// Relative `URI` attributes are resolved against `playlist_base_url`.
+    pub(super) fn parse(
+        key_line: &str,
+        playlist_base_url: &str,
+    ) -> Result<Self, KeyTagParsingError> {
+        let mut method: Option<Option<EncryptionMethod>> = None;
+        let mut url: Option<Url> = None;
+        let mut iv: Option<[u8; 16]> = None;
+        let mut key_format: Option<String> = None;
+
+        let mut offset = "#EXT-X-KEY:".len();
+        loop {
+            if offset >= key_line.len() {
+                break;
+            }
+            match key_line[offset..].find('=') {
+                None => {
+                    Logger::warn("Attribute Name not followed by equal sign");
+                    break;
+                }
+                Some(idx) => match &key_line[offset..offset + idx] {
+                    "METHOD" => {
+                        let (parsed, end_offset) =
+                            parse_enumerated_string(key_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        method = match parsed {
+                            "NONE" => Some(None),
+                            "AES-128" => Some(Some(EncryptionMethod::Aes128)),
+                            "SAMPLE-AES" => Some(Some(EncryptionMethod::SampleAes)),
+                            "SAMPLE-AES-CTR" => Some(Some(EncryptionMethod::SampleAesCtr)),
+                            x => {
+                                Logger::warn(&format!("Unrecognized key METHOD: {}", x));
+                                Some(Some(EncryptionMethod::Unsupported(x.to_owned())))
+                            }
+                        };
+                    }
+                    "URI" => {
+                        let (parsed, end_offset) = parse_quoted_string(key_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            url = Some(Url::resolve(val, playlist_base_url));
+                        } else {
+                            Logger::warn("Unparsable key URI value");
+                        }
+                    }
+                    "IV" => {
+                        let (parsed, end_offset) =
+                            parse_hexadecimal_sequence(key_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        match parsed {
+                            Some(val) if val.len() <= 16 => {
+                                // Left-pad with zeroes if less than 128 bits were given
+                                let mut val_iv = [0u8; 16];
+                                val_iv[16 - val.len()..].copy_from_slice(&val);
+                                iv = Some(val_iv);
+                            }
+                            _ => Logger::warn("Unparsable key IV value"),
+                        }
+                    }
+                    "KEYFORMAT" => {
+                        let (parsed, end_offset) = parse_quoted_string(key_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            key_format = Some(val.to_owned());
+                        } else {
+                            Logger::warn("Unparsable KEYFORMAT value");
+                        }
+                    }
+                    _ => offset = skip_attribute_list_value(key_line, offset + idx + 1) + 1,
+                },
+            }
+        }
+
+        let method = method.ok_or(KeyTagParsingError::MissingMethod)?;
+        if method.is_some() && url.is_none() {
+            return Err(KeyTagParsingError::MissingUri);
+        }
+        Ok(KeyTag {
+            method,
+            url,
+            iv,
+            key_format: key_format.unwrap_or_else(|| "identity".to_owned()),
+        })
+    }
+
+    /// Re-create the `EXT-X-KEY` tag which led to `key` being associated to the segment whose
+    /// media sequence number is `media_sequence`, e.g. to know which keys apply after segments
+    /// skipped by a Playlist Delta Update.
+    ///
+    /// An IV equal to the one deduced from `media_sequence` is considered to not have been
+    /// explicitely announced.
+    pub(super) fn from_segment_key(key: &SegmentKey, media_sequence: u64) -> Self {
+        let mut default_iv = [0u8; 16];
+        default_iv[8..].copy_from_slice(&media_sequence.to_be_bytes());
+        KeyTag {
+            method: Some(key.method.clone()),
+            url: Some(key.url.clone()),
+            iv: if key.iv == default_iv {
+                None
+            } else {
+                Some(key.iv)
+            },
+            key_format: key.key_format.clone(),
+        }
+    }
+
+    /// Returns `true` if this `EXT-X-KEY` tag indicates that following segments are not
+    /// encrypted.
+    pub(super) fn is_none(&self) -> bool {
+        self.method.is_none()
+    }
+
+    /// Returns how the key is represented in the resource at `url`.
+    pub(super) fn key_format(&self) -> &str {
+        &self.key_format
+    }
+
+    /// Produce the `SegmentKey` for a segment whose media sequence number is `media_sequence`.
+    ///
+    /// Returns `None` if this `KeyTag` indicates that segments are not encrypted.
+    pub(super) fn segment_key(&self, media_sequence: u64) -> Option<SegmentKey> {
+        let method = self.method.clone()?;
+        let url = self.url.clone()?;
+        let iv = self.iv.unwrap_or_else(|| {
+            let mut iv = [0u8; 16];
+            iv[8..].copy_from_slice(&media_sequence.to_be_bytes());
+            iv
+        });
+        Some(SegmentKey {
+            method,
+            url,
+            iv,
+            key_format: self.key_format.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::logger::LoggerLevel;
+
+    fn parse(line: &str) -> Result<KeyTag, KeyTagParsingError> {
+        KeyTag::parse(line, "https://example.com/media/")
+    }
+
+    #[test]
+    fn test_parse_key_tag() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let key = parse("#EXT-X-KEY:METHOD=AES-128,URI=\"key.bin\",IV=0x0102").unwrap();
+        let segment_key = key.segment_key(42).unwrap();
+        assert_eq!(segment_key.method(), &EncryptionMethod::Aes128);
+        assert_eq!(
+            segment_key.url().get_ref(),
+            "https://example.com/media/key.bin"
+        );
+        assert_eq!(key.key_format(), "identity");
+        // Explicit IVs of less than 128 bits are left-padded with zeroes
+        let mut iv = [0u8; 16];
+        iv[14..].copy_from_slice(&[1, 2]);
+        assert_eq!(segment_key.iv(), &iv);
+        assert!(segment_key.is_decrypted_by_player());
+
+        // Without IV, the media sequence number is relied on
+        let key = parse(
+            "#EXT-X-KEY:METHOD=SAMPLE-AES,URI=\"skd://key\",KEYFORMAT=\"com.apple.streamingkeydelivery\"",
+        )
+        .unwrap();
+        let segment_key = key.segment_key(0x0203).unwrap();
+        let mut iv = [0u8; 16];
+        iv[14..].copy_from_slice(&[2, 3]);
+        assert_eq!(segment_key.iv(), &iv);
+        assert_eq!(segment_key.key_format(), "com.apple.streamingkeydelivery");
+        assert!(!segment_key.is_decrypted_by_player());
+
+        let key = parse("#EXT-X-KEY:METHOD=NONE").unwrap();
+        assert!(key.is_none());
+        assert!(key.segment_key(1).is_none());
+
+        assert!(matches!(
+            parse("#EXT-X-KEY:METHOD=AES-128,IV=0x01"),
+            Err(KeyTagParsingError::MissingUri)
+        ));
+        assert!(matches!(
+            parse("#EXT-X-KEY:URI=\"key.bin\""),
+            Err(KeyTagParsingError::MissingMethod)
+        ));
+
+        // Segments with an unknown METHOD are still known to be encrypted
+        let key = parse("#EXT-X-KEY:METHOD=AES-256,URI=\"key.bin\"").unwrap();
+        assert!(!key.is_none());
+        let segment_key = key.segment_key(1).unwrap();
+        assert_eq!(
+            segment_key.method(),
+            &EncryptionMethod::Unsupported("AES-256".to_owned())
+        );
+        assert!(!segment_key.is_decrypted_by_player());
+    }
+}
@@ -0,0 +1,262 @@
+use super::utils::{parse_enumerated_string, parse_quoted_string, skip_attribute_list_value};
+use crate::{bindings::SessionDataType, utils::url::Url, Logger};
+
+/// Arbitrary session data announced in a Multivariant Playlist through an `EXT-X-SESSION-DATA`
+/// tag, such as the title of the content or its chapters.
+#[derive(Clone, Debug)]
+pub(crate) struct SessionData {
+    /// The `DATA-ID` attribute, identifying that data (e.g. `"com.example.title"`).
+    data_id: String,
+    /// The data itself, or where it can be loaded from.
+    content: SessionDataContent,
+    /// The `LANGUAGE` attribute, as a BCP 47 language tag, if one.
+    language: Option<String>,
+}
+
+/// The content of an `EXT-X-SESSION-DATA` tag.
+#[derive(Clone, Debug)]
+pub(crate) enum SessionDataContent {
+    /// The data was directly set through the `VALUE` attribute.
+    Value(String),
+    /// The data has to be loaded from the `URI` attribute.
+    Uri {
+        /// The `URI` attribute, resolved against the Multivariant Playlist's URL.
+        url: Url,
+        /// The `FORMAT` attribute, indicating how the loaded data should be interpreted.
+        format: SessionDataFormat,
+    },
+}
+
+/// Format of the data loaded from the `URI` attribute of an `EXT-X-SESSION-DATA` tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SessionDataFormat {
+    /// The data is a JSON value. This is the default.
+    Json,
+    /// The data is an uninterpreted binary file.
+    Raw,
+}
+
+impl SessionData {
+    /// Parse the `EXT-X-SESSION-DATA` tag in `line`.
+    ///
+    /// `base_uri` is the base on which a relative `URI` attribute is resolved.
+    ///
+    /// Returns `None` if that tag is invalid, in which case it should be ignored.
+    pub(super) fn parse(line: &str, base_uri: &str) -> Option<Self> {
+        let mut data_id: Option<&str> = None;
+        let mut value: Option<&str> = None;
+        let mut uri: Option<&str> = None;
+        let mut format = SessionDataFormat::Json;
+        let mut language: Option<&str> = None;
+        let mut offset = line.find(':').map_or(line.len(), |idx| idx + 1);
+        loop {
+            if offset >= line.len() {
+                break;
+            }
+            match line[offset..].find('=') {
+                None => {
+                    Logger::warn("Parser: Attribute Name not followed by equal sign");
+                    break;
+                }
+                Some(idx) => {
+                    let attr = &line[offset..offset + idx];
+                    match attr {
+                        "DATA-ID" | "VALUE" | "URI" | "LANGUAGE" => {
+                            let (parsed, end_offset) = parse_quoted_string(line, offset + idx + 1);
+                            offset = end_offset + 1;
+                            let parsed = if let Ok(parsed) = parsed {
+                                Some(parsed)
+                            } else {
+                                Logger::warn(&format!(
+                                    "Parser: Unparsable EXT-X-SESSION-DATA {attr} value"
+                                ));
+                                None
+                            };
+                            match attr {
+                                "DATA-ID" => data_id = parsed,
+                                "VALUE" => value = parsed,
+                                "URI" => uri = parsed,
+                                _ => language = parsed,
+                            }
+                        }
+                        "FORMAT" => {
+                            let (parsed, end_offset) =
+                                parse_enumerated_string(line, offset + idx + 1);
+                            offset = end_offset + 1;
+                            match parsed {
+                                "JSON" => format = SessionDataFormat::Json,
+                                "RAW" => format = SessionDataFormat::Raw,
+                                x => Logger::warn(&format!(
+                                    "Parser: Unknown EXT-X-SESSION-DATA FORMAT value: {x}"
+                                )),
+                            }
+                        }
+                        _ => offset = skip_attribute_list_value(line, offset + idx + 1) + 1,
+                    }
+                }
+            }
+        }
+
+        let data_id = if let Some(data_id) = data_id {
+            data_id
+        } else {
+            Logger::warn("Parser: Ignoring EXT-X-SESSION-DATA without a DATA-ID attribute");
+            return None;
+        };
+        let content = match (value, uri) {
+            (Some(value), None) => SessionDataContent::Value(value.to_owned()),
+            (None, Some(uri)) => SessionDataContent::Uri {
+                url: Url::resolve(uri, base_uri),
+                format,
+            },
+            _ => {
+                Logger::warn(&format!(
+                    "Parser: EXT-X-SESSION-DATA \"{data_id}\" should have either a VALUE or \
+                    an URI attribute, ignoring it."
+                ));
+                return None;
+            }
+        };
+        Some(Self {
+            data_id: data_id.to_owned(),
+            content,
+            language: language.map(|l| l.to_owned()),
+        })
+    }
+
+    /// Returns the `EXT-X-SESSION-DATA` with the given `DATA-ID` in `session_data`, as well as
+    /// its index in it.
+    ///
+    /// If `language` is set, the one with that `LANGUAGE` is preferred, the first one with that
+    /// `DATA-ID` being returned if none match.
+    pub(crate) fn find<'a>(
+        session_data: &'a [SessionData],
+        data_id: &str,
+        language: Option<&str>,
+    ) -> Option<(usize, &'a SessionData)> {
+        let mut candidates = session_data
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| d.data_id() == data_id);
+        match language {
+            Some(language) => candidates
+                .clone()
+                .find(|(_, d)| d.language() == Some(language))
+                .or_else(|| candidates.next()),
+            None => candidates.next(),
+        }
+    }
+
+    /// The `DATA-ID` attribute, identifying that data.
+    pub(crate) fn data_id(&self) -> &str {
+        &self.data_id
+    }
+
+    /// The data itself, or where it can be loaded from.
+    pub(crate) fn content(&self) -> &SessionDataContent {
+        &self.content
+    }
+
+    /// The `LANGUAGE` attribute of that data, if one.
+    pub(crate) fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// How the data of this `EXT-X-SESSION-DATA` should be interpreted once obtained.
+    pub(crate) fn data_type(&self) -> SessionDataType {
+        match self.content {
+            SessionDataContent::Value(_) => SessionDataType::String,
+            SessionDataContent::Uri {
+                format: SessionDataFormat::Json,
+                ..
+            } => SessionDataType::Json,
+            SessionDataContent::Uri {
+                format: SessionDataFormat::Raw,
+                ..
+            } => SessionDataType::Raw,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::logger::LoggerLevel;
+
+    const BASE_URI: &str = "https://example.com/content";
+
+    #[test]
+    fn test_parse_session_data() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let data = SessionData::parse(
+            "#EXT-X-SESSION-DATA:DATA-ID=\"com.example.title\",VALUE=\"My title\",\
+LANGUAGE=\"en\"",
+            BASE_URI,
+        )
+        .unwrap();
+        assert_eq!(data.data_id(), "com.example.title");
+        assert_eq!(data.language(), Some("en"));
+        assert!(matches!(data.content(), SessionDataContent::Value(v) if v == "My title"));
+        assert_eq!(data.data_type(), SessionDataType::String);
+
+        // FORMAT defaults to JSON
+        let data = SessionData::parse(
+            "#EXT-X-SESSION-DATA:DATA-ID=\"com.example.chapters\",URI=\"chapters.json\"",
+            BASE_URI,
+        )
+        .unwrap();
+        assert_eq!(data.language(), None);
+        match data.content() {
+            SessionDataContent::Uri { url, format } => {
+                assert_eq!(url.get_ref(), "https://example.com/content/chapters.json");
+                assert_eq!(*format, SessionDataFormat::Json);
+            }
+            _ => panic!("Expected an URI"),
+        }
+        assert_eq!(data.data_type(), SessionDataType::Json);
+
+        let data = SessionData::parse(
+            "#EXT-X-SESSION-DATA:DATA-ID=\"com.example.cover\",\
+URI=\"https://cdn.test/cover.png\",FORMAT=RAW",
+            BASE_URI,
+        )
+        .unwrap();
+        assert!(matches!(
+            data.content(),
+            SessionDataContent::Uri { url, .. } if url.get_ref() == "https://cdn.test/cover.png"
+        ));
+        assert_eq!(data.data_type(), SessionDataType::Raw);
+
+        // VALUE and URI are mutually exclusive, one of them and DATA-ID are mandatory
+        assert!(SessionData::parse(
+            "#EXT-X-SESSION-DATA:DATA-ID=\"a\",VALUE=\"b\",URI=\"c.json\"",
+            BASE_URI
+        )
+        .is_none());
+        assert!(SessionData::parse("#EXT-X-SESSION-DATA:DATA-ID=\"a\"", BASE_URI).is_none());
+        assert!(SessionData::parse("#EXT-X-SESSION-DATA:VALUE=\"b\"", BASE_URI).is_none());
+    }
+
+    #[test]
+    fn test_find_session_data() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let session_data: Vec<SessionData> = [
+            "#EXT-X-SESSION-DATA:DATA-ID=\"title\",VALUE=\"Titre\",LANGUAGE=\"fr\"",
+            "#EXT-X-SESSION-DATA:DATA-ID=\"other\",VALUE=\"Other\",LANGUAGE=\"en\"",
+            "#EXT-X-SESSION-DATA:DATA-ID=\"title\",VALUE=\"Title\",LANGUAGE=\"en\"",
+        ]
+        .iter()
+        .map(|line| SessionData::parse(line, BASE_URI).unwrap())
+        .collect();
+        let find = |data_id, language| {
+            SessionData::find(&session_data, data_id, language).map(|(idx, _)| idx)
+        };
+        assert_eq!(find("title", Some("en")), Some(2));
+        assert_eq!(find("title", Some("fr")), Some(0));
+        // Falls back to the first one with that DATA-ID
+        assert_eq!(find("title", Some("de")), Some(0));
+        assert_eq!(find("title", None), Some(0));
+        assert_eq!(find("other", Some("fr")), Some(1));
+        assert_eq!(find("unknown", None), None);
+    }
+}
@@ -0,0 +1,188 @@
+use super::{media_tag::MediaTrack, InstreamId, MediaTag, MediaTagType};
+use std::ops::{Deref, DerefMut};
+
+/// Allows to translate the `EXT-X-MEDIA` tags of type `"SUBTITLES"` and `"CLOSED-CAPTIONS"`
+/// found inside a Multivariant Playlist into well defined text tracks that make more sense in a
+/// player API.
+pub(crate) struct TextTrackList {
+    /// List of text tracks as constructed by this `TextTrackList`.
+    tracks: Vec<TextTrack>,
+}
+
+impl TextTrackList {
+    /// Creates a new `TextTrackList` from the given `Vec<MediaTag>`, which should contain
+    /// information on all media tags of type `"SUBTITLES"` and `"CLOSED-CAPTIONS"`.
+    ///
+    /// Closed captions media tags without a valid `INSTREAM-ID` are ignored.
+    pub(super) fn new(mut subtitles_media: Vec<MediaTag>) -> Self {
+        let mut available_text_tracks: Vec<TextTrack> = vec![];
+        while let Some(media) = subtitles_media.pop() {
+            if media.typ() == MediaTagType::ClosedCaptions && media.instream_id().is_none() {
+                continue;
+            }
+            let id = media.id();
+
+            // Check if the track already exist in another group
+            let pos_compat = available_text_tracks.iter().position(|t| {
+                t.name() == media.name()
+                    && t.language() == media.language()
+                    && t.assoc_language() == media.assoc_language()
+                    && t.is_forced() == media.is_forced()
+                    && t.characteristics() == media.characteristics()
+                    && t.instream_id() == media.instream_id()
+            });
+
+            if let Some(pos) = pos_compat {
+                available_text_tracks[pos].media_tags.push(media);
+            } else {
+                available_text_tracks.push(TextTrack {
+                    id: id.to_owned(),
+                    media_tags: vec![media],
+                });
+            }
+        }
+        Self {
+            tracks: available_text_tracks,
+        }
+    }
+
+    /// Returns the `MediaTag` of type `typ` of the text track whose `id` is given in argument
+    /// which is linked to the HLS `GROUP-ID` `group_id`.
+    ///
+    /// Returns `None` if either that track does not exist or if it has no such `MediaTag`.
+    pub(super) fn media_tag_for_group(
+        &self,
+        id: u32,
+        typ: MediaTagType,
+        group_id: &str,
+    ) -> Option<&MediaTag> {
+        self.iter().find(|t| t.id() == id).and_then(|t| {
+            t.media_tags
+                .iter()
+                .find(|m| m.typ() == typ && m.group_id() == group_id)
+        })
+    }
+
+    /// Returns reference too `MediaTag` object associated to the given `MediaTag`'s id, stored in
+    /// the `TextTrackList`
+    ///
+    /// Returns `None` if none is found.
+    pub(super) fn media_tag(&self, id: u32) -> Option<&MediaTag> {
+        self.iter()
+            .find_map(|t| t.media_tags.iter().find(|m| m.id() == id))
+    }
+
+    /// Returns mutable reference too `MediaTag` object associated to the given `MediaTag`'s id,
+    /// stored in the `TextTrackList`
+    ///
+    /// Returns `None` if none is found.
+    pub(super) fn media_tag_mut(&mut self, id: u32) -> Option<&mut MediaTag> {
+        self.iter_mut()
+            .find_map(|t| t.media_tags.iter_mut().find(|m| m.id() == id))
+    }
+
+    /// Returns an iterator on all `MediaTag` objects found in this `TextTrackList`.
+    pub(super) fn iter_media(&self) -> impl Iterator<Item = &MediaTag> {
+        self.iter().flat_map(|t| t.media_tags.iter())
+    }
+
+    /// Returns an iterator on mutable references to all `MediaTag` objects found in this
+    /// `TextTrackList`.
+    pub(super) fn iter_media_mut(&mut self) -> impl Iterator<Item = &mut MediaTag> {
+        self.iter_mut().flat_map(|t| t.media_tags.iter_mut())
+    }
+}
+
+impl Deref for TextTrackList {
+    type Target = Vec<TextTrack>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tracks
+    }
+}
+
+impl DerefMut for TextTrackList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.tracks
+    }
+}
+
+/// Describe a single text track object with its own characteristics.
+pub struct TextTrack {
+    /// Unique identifier for that text track in the parent `TextTrackList`.
+    id: u32,
+    /// `MediaTag` objects associated to this text track.
+    ///
+    /// These are usually the same content, linked to different variants through distinct
+    /// `GROUP-ID`s.
+    pub(self) media_tags: Vec<MediaTag>,
+}
+
+impl MediaTrack for TextTrack {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn set_id(&mut self, id: u32) {
+        self.id = id;
+    }
+
+    fn media_tags(&self) -> &[MediaTag] {
+        &self.media_tags
+    }
+}
+
+impl TextTrack {
+    /// Unique identifier for that text track in the parent `TextTrackList`.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns reference to the language linked to that text track.
+    pub fn language(&self) -> Option<&str> {
+        self.media_tags.first().and_then(|t| t.language())
+    }
+
+    /// Returns reference to the associated (secondary) language linked to that text track.
+    pub fn assoc_language(&self) -> Option<&str> {
+        self.media_tags.first().and_then(|t| t.assoc_language())
+    }
+
+    /// Returns human-readable name associated with that text track.
+    pub fn name(&self) -> &str {
+        self.media_tags.first().unwrap().name()
+    }
+
+    /// Returns `true` if that text track contains content considered essential to play, such as
+    /// the translation of foreign dialogues.
+    pub fn is_forced(&self) -> bool {
+        self.media_tags.first().is_some_and(|t| t.is_forced())
+    }
+
+    /// Returns `true` if that text track should be played in the absence of information from
+    /// the user indicating a different choice.
+    pub fn is_default(&self) -> bool {
+        self.media_tags.iter().any(|t| t.is_default())
+    }
+
+    /// Returns `true` if that text track may be chosen in the absence of explicit user
+    /// preference because it matches the current playback environment.
+    pub fn is_autoselect(&self) -> bool {
+        self.media_tags.iter().any(|t| t.is_autoselect())
+    }
+
+    /// Returns the Uniform Type Identifiers describing individual characteristics of that text
+    /// track (e.g. `"public.accessibility.transcribes-spoken-dialog"`).
+    pub fn characteristics(&self) -> &[String] {
+        self.media_tags
+            .first()
+            .map(|t| t.characteristics())
+            .unwrap_or_default()
+    }
+
+    /// Returns the closed captions channel this text track corresponds to, or `None` if it is
+    /// not a closed captions track but a subtitles track with its own Media Playlists.
+    pub fn instream_id(&self) -> Option<InstreamId> {
+        self.media_tags.first().and_then(|t| t.instream_id())
+    }
+}
@@ -173,6 +173,61 @@ pub(super) fn parse_decimal_floating_point(
     }
 }
 
+/// Parse hexadecimal-sequence value as defined by the HLS specification (e.g. `0x1A2B`):
+/// From the `value_start_offset` (which is the byte offset in `line` at which
+/// the value starts), to either the next encountered comma, or the end of `line`,
+/// whichever comes sooner.
+///
+/// The returned bytes are in the same order than in the string (big-endian). If the sequence has
+/// an odd number of hexadecimal digits, it is considered to be prepended by a `0`.
+///
+/// Returns `None` as the first element of the tuple if the value was not in the right format.
+pub(super) fn parse_hexadecimal_sequence(
+    line: &str,
+    value_start_offset: usize,
+) -> (Option<Vec<u8>>, usize) {
+    let end = find_attribute_end(line, value_start_offset);
+    let value = &line[value_start_offset..end];
+    let digits = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(d) if !d.is_empty() => d.as_bytes(),
+        _ => return (None, end),
+    };
+    let mut ret = Vec::with_capacity(digits.len().div_ceil(2));
+    let mut curr_idx = 0;
+    if digits.len() % 2 == 1 {
+        match hex_digit_value(digits[0]) {
+            Some(val) => ret.push(val),
+            None => return (None, end),
+        }
+        curr_idx = 1;
+    }
+    while curr_idx < digits.len() {
+        match (
+            hex_digit_value(digits[curr_idx]),
+            hex_digit_value(digits[curr_idx + 1]),
+        ) {
+            (Some(high), Some(low)) => ret.push((high << 4) | low),
+            _ => return (None, end),
+        }
+        curr_idx += 2;
+    }
+    (Some(ret), end)
+}
+
+/// Convert a single ASCII hexadecimal digit into its value, or `None` if it is not an hexadecimal
+/// digit.
+fn hex_digit_value(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
 const DAYS_MONTH_SINCE_YEARS_START: [u32; 12] = [
     0,
     31,
@@ -0,0 +1,312 @@
+use std::borrow::Cow;
+
+use super::utils::{parse_quoted_string, skip_attribute_list_value};
+use crate::{utils::url::Url, Logger};
+
+/// Variables declared through `EXT-X-DEFINE` tags, which may then be referenced in the rest of
+/// the playlist through the `{$name}` syntax.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PlaylistVariables {
+    /// Name and value of each defined variable, in definition order.
+    variables: Vec<(String, String)>,
+}
+
+/// Error returned when a variable is referenced or imported without being defined.
+#[derive(Debug)]
+pub(super) struct UndefinedVariableError;
+
+impl PlaylistVariables {
+    /// Returns the value of the variable called `name`, if defined.
+    pub(super) fn get(&self, name: &str) -> Option<&str> {
+        self.variables
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Parse the `EXT-X-DEFINE` tag in `line` and add the variable it declares.
+    ///
+    /// `url` is the URL of the playlist in which that tag was found, whose query string is
+    /// used for `QUERYPARAM` declarations.
+    ///
+    /// `imported` should be set to the variables of the Multivariant Playlist when parsing a
+    /// Media Playlist, from which `IMPORT` declarations are taken. It should be `None` when
+    /// parsing a Multivariant Playlist, where `IMPORT` is not allowed.
+    pub(super) fn parse_define(
+        &mut self,
+        line: &str,
+        url: &Url,
+        imported: Option<&PlaylistVariables>,
+    ) -> Result<(), UndefinedVariableError> {
+        let mut name: Option<&str> = None;
+        let mut value: Option<&str> = None;
+        let mut import: Option<&str> = None;
+        let mut query_param: Option<&str> = None;
+        let mut offset = line.find(':').map_or(line.len(), |idx| idx + 1);
+        loop {
+            if offset >= line.len() {
+                break;
+            }
+            match line[offset..].find('=') {
+                None => {
+                    Logger::warn("Parser: Attribute Name not followed by equal sign");
+                    break;
+                }
+                Some(idx) => {
+                    let attr = &line[offset..offset + idx];
+                    if let "NAME" | "VALUE" | "IMPORT" | "QUERYPARAM" = attr {
+                        let (parsed, end_offset) = parse_quoted_string(line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        let parsed = if let Ok(parsed) = parsed {
+                            Some(parsed)
+                        } else {
+                            Logger::warn(&format!("Parser: Unparsable EXT-X-DEFINE {attr} value"));
+                            None
+                        };
+                        match attr {
+                            "NAME" => name = parsed,
+                            "VALUE" => value = parsed,
+                            "IMPORT" => import = parsed,
+                            _ => query_param = parsed,
+                        }
+                    } else {
+                        offset = skip_attribute_list_value(line, offset + idx + 1) + 1;
+                    }
+                }
+            }
+        }
+
+        let (name, value) = match (name, value, import, query_param) {
+            (Some(name), Some(value), None, None) => (name, value.to_owned()),
+            (None, None, Some(import), None) => match imported {
+                None => {
+                    Logger::warn("Parser: Ignoring EXT-X-DEFINE IMPORT in Multivariant Playlist");
+                    return Ok(());
+                }
+                Some(imported) => match imported.get(import) {
+                    Some(value) => (import, value.to_owned()),
+                    None => {
+                        Logger::warn(&format!(
+                            "Parser: Imported variable \"{import}\" is not defined"
+                        ));
+                        return Err(UndefinedVariableError);
+                    }
+                },
+            },
+            (None, None, None, Some(query_param)) => match url.query_parameter(query_param) {
+                Some(value) => (query_param, value),
+                None => {
+                    Logger::warn(&format!(
+                        "Parser: Query parameter \"{query_param}\" is not in the playlist's URL"
+                    ));
+                    return Err(UndefinedVariableError);
+                }
+            },
+            _ => {
+                Logger::warn("Parser: Ignoring invalid EXT-X-DEFINE tag");
+                return Ok(());
+            }
+        };
+        if self.get(name).is_some() {
+            Logger::warn(&format!(
+                "Parser: Variable \"{name}\" defined multiple times, ignoring new value"
+            ));
+        } else {
+            self.variables.push((name.to_owned(), value));
+        }
+        Ok(())
+    }
+
+    /// Replace all variable references in `input`, which may be either a URI line or the value
+    /// of a quoted-string attribute.
+    pub(super) fn substitute<'a>(
+        &self,
+        input: &'a str,
+    ) -> Result<Cow<'a, str>, UndefinedVariableError> {
+        if !input.contains("{$") {
+            return Ok(Cow::Borrowed(input));
+        }
+        let mut ret = String::with_capacity(input.len());
+        let mut remaining = input;
+        while let Some(start_idx) = remaining.find("{$") {
+            ret.push_str(&remaining[..start_idx]);
+            let reference = &remaining[start_idx + 2..];
+            let end_idx = match reference.find('}') {
+                Some(idx) => idx,
+                None => {
+                    // Not a variable reference
+                    ret.push_str(&remaining[start_idx..]);
+                    return Ok(Cow::Owned(ret));
+                }
+            };
+            let name = &reference[..end_idx];
+            match self.get(name) {
+                Some(value) => ret.push_str(value),
+                None => {
+                    Logger::warn(&format!("Parser: Variable \"{name}\" is not defined"));
+                    return Err(UndefinedVariableError);
+                }
+            }
+            remaining = &reference[end_idx + 1..];
+        }
+        ret.push_str(remaining);
+        Ok(Cow::Owned(ret))
+    }
+
+    /// Replace all variable references found in the quoted-string attribute values of the tag
+    /// `line`, other parts of that line being kept as is.
+    pub(super) fn substitute_in_tag<'a>(
+        &self,
+        line: &'a str,
+    ) -> Result<Cow<'a, str>, UndefinedVariableError> {
+        if !line.contains("{$") {
+            return Ok(Cow::Borrowed(line));
+        }
+        let mut ret = String::with_capacity(line.len());
+        let mut in_quotes = false;
+        for (idx, part) in line.split('"').enumerate() {
+            if idx > 0 {
+                ret.push('"');
+            }
+            if in_quotes {
+                ret.push_str(&self.substitute(part)?);
+            } else {
+                ret.push_str(part);
+            }
+            in_quotes = !in_quotes;
+        }
+        Ok(Cow::Owned(ret))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::logger::LoggerLevel;
+
+    fn url() -> Url {
+        Url::new("https://example.com/media.m3u8?token=a%20b&empty".to_owned())
+    }
+
+    #[test]
+    fn test_parse_define() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let mut variables = PlaylistVariables::default();
+        variables
+            .parse_define(
+                r#"#EXT-X-DEFINE:NAME="host",VALUE="cdn.test""#,
+                &url(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(variables.get("host"), Some("cdn.test"));
+
+        // First definition wins
+        variables
+            .parse_define(r#"#EXT-X-DEFINE:NAME="host",VALUE="other""#, &url(), None)
+            .unwrap();
+        assert_eq!(variables.get("host"), Some("cdn.test"));
+
+        // Invalid combinations are ignored
+        variables
+            .parse_define(
+                r#"#EXT-X-DEFINE:NAME="a",VALUE="b",QUERYPARAM="token""#,
+                &url(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(variables.get("a"), None);
+        assert_eq!(variables.get("token"), None);
+    }
+
+    #[test]
+    fn test_query_param() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let mut variables = PlaylistVariables::default();
+        variables
+            .parse_define(r#"#EXT-X-DEFINE:QUERYPARAM="token""#, &url(), None)
+            .unwrap();
+        assert_eq!(variables.get("token"), Some("a b"));
+        variables
+            .parse_define(r#"#EXT-X-DEFINE:QUERYPARAM="empty""#, &url(), None)
+            .unwrap();
+        assert_eq!(variables.get("empty"), Some(""));
+        assert!(variables
+            .parse_define(r#"#EXT-X-DEFINE:QUERYPARAM="missing""#, &url(), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_import() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let mut parent = PlaylistVariables::default();
+        parent
+            .parse_define(
+                r#"#EXT-X-DEFINE:NAME="host",VALUE="cdn.test""#,
+                &url(),
+                None,
+            )
+            .unwrap();
+
+        let mut variables = PlaylistVariables::default();
+        variables
+            .parse_define(r#"#EXT-X-DEFINE:IMPORT="host""#, &url(), Some(&parent))
+            .unwrap();
+        assert_eq!(variables.get("host"), Some("cdn.test"));
+
+        // Not defined in the parent playlist
+        assert!(variables
+            .parse_define(r#"#EXT-X-DEFINE:IMPORT="other""#, &url(), Some(&parent))
+            .is_err());
+
+        // IMPORT is ignored in Multivariant Playlists
+        let mut variables = PlaylistVariables::default();
+        variables
+            .parse_define(r#"#EXT-X-DEFINE:IMPORT="host""#, &url(), None)
+            .unwrap();
+        assert_eq!(variables.get("host"), None);
+    }
+
+    #[test]
+    fn test_substitute() {
+        Logger::set_logger_level(LoggerLevel::None);
+        let mut variables = PlaylistVariables::default();
+        variables
+            .parse_define(
+                r#"#EXT-X-DEFINE:NAME="host",VALUE="cdn.test""#,
+                &url(),
+                None,
+            )
+            .unwrap();
+        variables
+            .parse_define(r#"#EXT-X-DEFINE:NAME="q",VALUE="4k""#, &url(), None)
+            .unwrap();
+
+        assert!(matches!(
+            variables.substitute("seg.ts"),
+            Ok(Cow::Borrowed("seg.ts"))
+        ));
+        assert_eq!(
+            variables.substitute("https://{$host}/{$q}/seg.ts").unwrap(),
+            "https://cdn.test/4k/seg.ts"
+        );
+        assert_eq!(variables.substitute("{$host").unwrap(), "{$host");
+        assert!(variables.substitute("https://{$undefined}/seg.ts").is_err());
+
+        assert_eq!(
+            variables
+                .substitute_in_tag(r#"#EXT-X-MAP:URI="{$q}/init.mp4",BYTERANGE="{$q}""#)
+                .unwrap(),
+            r#"#EXT-X-MAP:URI="4k/init.mp4",BYTERANGE="4k""#
+        );
+        assert_eq!(
+            variables
+                .substitute_in_tag(r#"#EXT-X-FOO:{$q}=1,BAR="{$host}""#)
+                .unwrap(),
+            r#"#EXT-X-FOO:{$q}=1,BAR="cdn.test""#
+        );
+        assert!(variables
+            .substitute_in_tag(r#"#EXT-X-MAP:URI="{$undefined}""#)
+            .is_err());
+    }
+}
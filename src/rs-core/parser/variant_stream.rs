@@ -1,7 +1,5 @@
-use std::io::BufRead;
-
 use super::{
-    media_playlist::{MediaPlaylist, MediaPlaylistParsingError},
+    media_playlist::MediaPlaylist,
     multi_variant_playlist::MediaPlaylistContext,
     utils::{
         parse_comma_separated_list, parse_decimal_floating_point, parse_decimal_integer,
@@ -18,8 +16,9 @@ pub struct VariantStream {
     id: u32,
 
     /// Stable identifier for the URI within the parent Multivariant Playlist
-    /// even in the case a different Multivariant fetched in the meantime,
-    /// whereas `id` may change if the Multivariant Playlist is refreshed.
+    /// even in the case a different Multivariant fetched in the meantime.
+    /// It is relied on to give back its `id` to this variant stream when the
+    /// Multivariant Playlist is refreshed.
     ///
     /// This identifier allows the URI of the Variant Stream to
     /// change between two distinct downloads of the Multivariant
@@ -74,6 +73,14 @@ pub struct VariantStream {
     /// Stream with an hdcp_level attribute unless its value is `None`.
     hdcp_level: HdcpLevel,
 
+    /// Content Protection Configurations which are acceptable to present this variant stream,
+    /// per `KEYFORMAT`, as announced by its `ALLOWED-CPC` attribute.
+    ///
+    /// Each element is a tuple of a `KEYFORMAT` and of the Content Protection Configuration
+    /// labels which are allowed with it (e.g. `"SW"` or `"HW"`).
+    /// Empty if the `ALLOWED-CPC` attribute was not present.
+    allowed_cpc: Vec<(String, Vec<String>)>,
+
     /// The dynamic range of the video of this variant stream.
     /// Clients that do not recognize the attribute value SHOULD NOT select the
     /// Variant Stream.
@@ -96,6 +103,20 @@ pub struct VariantStream {
     /// this with a format identifier such as "stpp.ttml.im1t".
     codecs: Vec<(Option<MediaType>, String)>,
 
+    /// How `codecs` were obtained, as the most common formats are assumed when they are not
+    /// announced, until they can be read from the initialization segments.
+    codecs_origin: CodecsOrigin,
+
+    /// Formats, each associated to a `MediaType`, which describe the same media samples than some
+    /// of the ones in `codecs` but with supplemental information they are backward-compatible
+    /// with, such as Dolby Vision metadata on top of HEVC video (e.g. `"dvh1.08.07"`).
+    ///
+    /// The compatibility brands which may follow each format in the `SUPPLEMENTAL-CODECS`
+    /// attribute (e.g. `"/db4h"`) are not kept here.
+    ///
+    /// Empty if the `SUPPLEMENTAL-CODECS` attribute was not present.
+    supplemental_codecs: Vec<(Option<MediaType>, String)>,
+
     /// The value is the optimal pixel resolution at which to display all the
     /// video in the Variant Stream.
     resolution: Option<VideoResolution>,
@@ -169,12 +190,29 @@ pub struct VariantStream {
     /// Pathway.
     pathway_id: Option<String>,
 
+    /// If `true`, this variant stream was announced through an `EXT-X-I-FRAME-STREAM-INF` tag
+    /// and its Media Playlist only describes I-frames, which can be used for trick play.
+    i_frames_only: bool,
+
     supported: Option<bool>,
 
+    /// If `Some(true)`, the formats in `supplemental_codecs` are supported and are relied on in
+    /// place of the ones they supplement in `codecs`.
+    ///
+    /// `None` if there's no supplemental codecs or if their support is not yet known.
+    supplemental_codecs_supported: Option<bool>,
+
+    /// If `Some(false)`, `video_range` is either unknown or not supported by the current
+    /// display, in which case this variant stream should not be selected.
+    ///
+    /// `None` if not yet checked through `update_video_range_support`.
+    video_range_supported: Option<bool>,
+
+    /// Set if this variant stream cannot be presented because of the output protection it
+    /// requires, as checked through `update_output_protection`.
+    output_protection_issue: Option<OutputProtectionIssue>,
+
     context: Option<MediaPlaylistContext>,
-    // TODO
-    // ALLOWED-CPC
-    // SUPPLEMENTAL-CODECS
 }
 
 /// Pixel resolution of a video content
@@ -201,8 +239,8 @@ impl VideoResolution {
 }
 
 /// Indicate the HDCP level typically enforced by the concerned content.
-#[derive(Copy, Clone, Debug)]
-enum HdcpLevel {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HdcpLevel {
     /// Indicates that the corresponding content could fail to play unless the
     /// output is protected by High-bandwidth Digital Content Protection (HDCP)
     /// Type 0 [HDCP] or equivalent
@@ -217,36 +255,123 @@ enum HdcpLevel {
     Unknown,
 }
 
+impl HdcpLevel {
+    /// Parse an `HDCP-LEVEL` value, e.g. `"TYPE-0"`.
+    pub(crate) fn parse(value: &str) -> Self {
+        match value {
+            "TYPE-0" => HdcpLevel::Type0,
+            "TYPE-1" => HdcpLevel::Type1,
+            "NONE" => HdcpLevel::None,
+            _ => HdcpLevel::Unknown,
+        }
+    }
+
+    /// Returns a value allowing to compare HDCP levels, a higher level being more restrictive.
+    ///
+    /// Returns `None` for an `Unknown` level, which cannot be compared.
+    fn rank(self) -> Option<u8> {
+        match self {
+            HdcpLevel::None => Some(0),
+            HdcpLevel::Type0 => Some(1),
+            HdcpLevel::Type1 => Some(2),
+            HdcpLevel::Unknown => None,
+        }
+    }
+}
+
+/// Output protection the current device is able to enforce, against which the `HDCP-LEVEL` and
+/// `ALLOWED-CPC` attributes of variant streams are checked.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OutputProtectionCapabilities {
+    /// The highest HDCP level that can be enforced on the output.
+    ///
+    /// `None` if unknown, in which case the `HDCP-LEVEL` attribute is not considered.
+    pub(crate) max_hdcp_level: Option<HdcpLevel>,
+
+    /// For each `KEYFORMAT`, the Content Protection Configuration labels the device satisfies.
+    ///
+    /// `None` if unknown, in which case the `ALLOWED-CPC` attribute is not considered.
+    pub(crate) allowed_cpc: Option<Vec<(String, Vec<String>)>>,
+}
+
+/// How the `codecs` of a `VariantStream` were obtained.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum CodecsOrigin {
+    /// They were announced, through its `CODECS` attribute.
+    Announced,
+    /// They were not announced and are only a guess, which should be replaced by the formats
+    /// found in its initialization segments.
+    Guessed,
+    /// They were not announced but were read from its initialization segments.
+    Inferred,
+}
+
+/// Formats assumed for variant streams whose `CODECS` attribute is missing, as they are the most
+/// common ones: AVC video with AAC audio.
+const GUESSED_CODECS: [&str; 2] = ["avc1.4D401F", "mp4a.40.2"];
+
+/// Reason why a variant stream cannot be presented with the current
+/// `OutputProtectionCapabilities`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum OutputProtectionIssue {
+    /// The variant's `HDCP-LEVEL` is higher than the one which can be enforced, or unknown.
+    HdcpLevel = 1,
+    /// None of the Content Protection Configurations in the variant's `ALLOWED-CPC` attribute
+    /// is satisfied for the `KEYFORMAT`s the device knows about.
+    AllowedCpc = 2,
+}
+
+/// Parse an `ALLOWED-CPC` value, without its surrounding quotes, into a list of `KEYFORMAT`s each
+/// associated to its Content Protection Configuration labels.
+///
+/// For example `"com.example.drm1:SMART-TV/PC,com.example.drm2:HW"` gives
+/// `[("com.example.drm1", ["SMART-TV", "PC"]), ("com.example.drm2", ["HW"])]`.
+pub(crate) fn parse_allowed_cpc(value: &str) -> Vec<(String, Vec<String>)> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let (key_format, labels) = entry.trim().rsplit_once(':')?;
+            let labels = labels
+                .split('/')
+                .filter(|l| !l.is_empty())
+                .map(|l| l.to_owned())
+                .collect();
+            Some((key_format.to_owned(), labels))
+        })
+        .collect()
+}
+
 /// Indicate the dynamic range of the video track(s) of the concerned content.
-#[derive(Copy, Clone, Debug)]
-enum VideoDynamicRange {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VideoDynamicRange {
     /// The video in the corresponding content is encoded using one of the
     /// following reference opto-electronic transfer characteristic functions
     /// specified by the TransferCharacteristics code point: [CICP] 1, 6, 13,
     /// 14, 15.
     /// Note that different TransferCharacteristics code points can use the
     /// same transfer function.
-    Sdr,
+    Sdr = 0,
 
     /// The video in the corresponding content is encoded using a reference
     /// opto-electronic transfer characteristic function specified by the
     /// TransferCharacteristics code point 18, or consists of such video mixed
     /// with video qualifying as `Sdr` (see above).
-    Hlg,
+    Hlg = 1,
 
     /// The video in the corresponding content is encoded using a reference
     /// opto-electronic transfer characteristic function specified by the
     /// TransferCharacteristics code point 16, or consists of such video mixed
     /// with video qualifying as Sdr or Hlg (see above).
-    Pq,
+    Pq = 2,
 
     /// The video dynamic range of the current content is any other.
-    Unknown,
+    Unknown = 3,
 }
 
 #[derive(Debug)]
 pub enum VariantParsingError {
     MissingBandwidth,
+    MissingUri,
     InvalidDecimalInteger,
 }
 
@@ -259,15 +384,166 @@ impl VariantStream {
         self.supported = Some(supported);
     }
 
+    /// Returns `true` if all codecs of this variant are known to be supported and if it does not
+    /// require an output protection which cannot be enforced.
+    pub(crate) fn is_playable(&self) -> bool {
+        self.supported.unwrap_or(false)
+            && self.video_range_supported.unwrap_or(false)
+            && self.output_protection_issue.is_none()
+    }
+
+    /// The dynamic range of the video of this variant, `Sdr` by default.
+    pub(crate) fn video_range(&self) -> VideoDynamicRange {
+        self.video_range
+    }
+
+    /// Check whether the dynamic range of this variant's video is part of the ones the display
+    /// supports. A variant with an `Unknown` dynamic range is never supported.
+    pub(crate) fn update_video_range_support(&mut self, supported_ranges: &[VideoDynamicRange]) {
+        self.video_range_supported = Some(
+            self.video_range != VideoDynamicRange::Unknown
+                && supported_ranges.contains(&self.video_range),
+        );
+    }
+
+    /// Returns the reason why this variant cannot be presented with the last
+    /// `OutputProtectionCapabilities` given to `update_output_protection`, if any.
+    pub(crate) fn output_protection_issue(&self) -> Option<OutputProtectionIssue> {
+        self.output_protection_issue
+    }
+
+    /// Check whether the output protection required by this variant, through its `HDCP-LEVEL`
+    /// and `ALLOWED-CPC` attributes, can be enforced with the given capabilities.
+    ///
+    /// An `ALLOWED-CPC` is only considered unsatisfied if it shares at least one `KEYFORMAT`
+    /// with the capabilities and that none of the Content Protection Configurations of those
+    /// `KEYFORMAT`s are satisfied.
+    pub(crate) fn update_output_protection(&mut self, capabilities: &OutputProtectionCapabilities) {
+        let hdcp_issue = capabilities.max_hdcp_level.is_some_and(|max| {
+            match (self.hdcp_level.rank(), max.rank()) {
+                (Some(wanted), Some(max)) => wanted > max,
+                (Some(wanted), None) => wanted > 0,
+                (None, _) => true,
+            }
+        });
+        let cpc_issue = capabilities.allowed_cpc.as_ref().is_some_and(|allowed| {
+            let mut common_key_formats = self
+                .allowed_cpc
+                .iter()
+                .filter_map(|(key_format, labels)| {
+                    allowed
+                        .iter()
+                        .find(|(k, _)| k == key_format)
+                        .map(|(_, satisfied)| (labels, satisfied))
+                })
+                .peekable();
+            common_key_formats.peek().is_some()
+                && !common_key_formats
+                    .any(|(labels, satisfied)| labels.iter().any(|l| satisfied.contains(l)))
+        });
+        self.output_protection_issue = if hdcp_issue {
+            Some(OutputProtectionIssue::HdcpLevel)
+        } else if cpc_issue {
+            Some(OutputProtectionIssue::AllowedCpc)
+        } else {
+            None
+        };
+    }
+
     pub(crate) fn has_type(&self, media_type: MediaType) -> bool {
         self.codecs
             .iter()
             .any(|c| matches!(c.0, Some(x) if x == media_type))
     }
 
+    /// Returns the formats present in this variant for the given `MediaType`, as a
+    /// comma-separated string.
+    ///
+    /// If supplemental codecs are known to be supported, they replace in that string the
+    /// formats they are backward-compatible with.
     pub(crate) fn codecs(&self, media_type: MediaType) -> Option<String> {
-        let concerned_codecs = self
+        if self.uses_supplemental_codecs() {
+            self.supplemental_codecs(media_type)
+        } else {
+            self.format_codecs(&self.codecs, media_type)
+        }
+    }
+
+    /// Returns `true` if this variant's formats were not announced and are only guessed, in
+    /// which case the actual ones should be read from its initialization segments and
+    /// communicated through `update_inferred_codecs`.
+    pub(crate) fn has_guessed_codecs(&self) -> bool {
+        self.codecs_origin == CodecsOrigin::Guessed
+    }
+
+    /// Replace the formats of this variant which are present in the Media Playlist of the given
+    /// `MediaType` by the ones read from its initialization segment.
+    ///
+    /// Does nothing if this variant's formats were announced through its `CODECS` attribute.
+    pub(crate) fn update_inferred_codecs(&mut self, media_type: MediaType, codecs: &[String]) {
+        if self.codecs_origin == CodecsOrigin::Announced || codecs.is_empty() {
+            return;
+        }
+        let has_muxed_audio = media_type == MediaType::Video && self.audio.is_none();
+        self.codecs.retain(|c| match c.0 {
+            Some(MediaType::Audio) => media_type != MediaType::Audio && !has_muxed_audio,
+            Some(MediaType::Video) => media_type != MediaType::Video,
+            None => true,
+        });
+        self.codecs.extend(
+            codecs
+                .iter()
+                .map(|c| (guess_media_type_from_codec(c), c.to_owned())),
+        );
+        self.codecs_origin = CodecsOrigin::Inferred;
+    }
+
+    /// Returns the formats present in this variant for the given `MediaType` when relying on
+    /// its supplemental codecs, as a comma-separated string.
+    ///
+    /// Returns `None` if there's no supplemental codecs for that `MediaType`.
+    pub(crate) fn supplemental_codecs(&self, media_type: MediaType) -> Option<String> {
+        let supplemented_types: Vec<Option<MediaType>> =
+            self.supplemental_codecs.iter().map(|c| c.0).collect();
+        if !supplemented_types.contains(&Some(media_type)) {
+            return None;
+        }
+        let codecs: Vec<(Option<MediaType>, String)> = self
             .codecs
+            .iter()
+            .filter(|c| !supplemented_types.contains(&c.0))
+            .chain(self.supplemental_codecs.iter())
+            .cloned()
+            .collect();
+        self.format_codecs(&codecs, media_type)
+    }
+
+    /// Returns `true` if this variant has supplemental codecs.
+    pub(crate) fn has_supplemental_codecs(&self) -> bool {
+        !self.supplemental_codecs.is_empty()
+    }
+
+    /// Returns `true` if this variant's supplemental codecs are known to be supported and are
+    /// thus relied on.
+    pub(crate) fn uses_supplemental_codecs(&self) -> bool {
+        self.supplemental_codecs_supported == Some(true)
+    }
+
+    /// Returns `Some` with the support of this variant's supplemental codecs if known.
+    pub(crate) fn supplemental_codecs_supported(&self) -> Option<bool> {
+        self.supplemental_codecs_supported
+    }
+
+    pub(crate) fn update_supplemental_codecs_support(&mut self, supported: bool) {
+        self.supplemental_codecs_supported = Some(supported);
+    }
+
+    fn format_codecs(
+        &self,
+        codecs: &[(Option<MediaType>, String)],
+        media_type: MediaType,
+    ) -> Option<String> {
+        let concerned_codecs = codecs
             .iter()
             .filter(|c| match c.0 {
                 Some(MediaType::Audio) => media_type == MediaType::Audio || self.audio.is_none(),
@@ -311,19 +587,62 @@ impl VariantStream {
         self.bandwidth
     }
 
+    /// The Content Steering Pathway this variant belongs to, `"."` being the default Pathway.
+    pub(crate) fn pathway_id(&self) -> &str {
+        self.pathway_id.as_deref().unwrap_or(".")
+    }
+
+    /// Returns `true` if this variant's Media Playlist only describes I-frames, as announced by
+    /// an `EXT-X-I-FRAME-STREAM-INF` tag.
+    pub(crate) fn is_i_frames_only(&self) -> bool {
+        self.i_frames_only
+    }
+
     pub(super) fn media_playlist(&self) -> Option<&MediaPlaylist> {
         self.media_playlist.as_ref()
     }
 
-    pub(super) fn update_media_playlist(
+    /// Set the `MediaPlaylist` linked to this object, which should have been parsed with the
+    /// previous one as reference if one was loaded.
+    pub(super) fn set_media_playlist(
         &mut self,
-        playlist: impl BufRead,
-        url: Url,
-        context: &MediaPlaylistContext,
-    ) -> Result<&MediaPlaylist, MediaPlaylistParsingError> {
-        let new_mp = MediaPlaylist::create(playlist, url, self.media_playlist.as_ref(), context)?;
-        self.media_playlist = Some(new_mp);
-        Ok(self.media_playlist.as_ref().unwrap())
+        mut media_playlist: MediaPlaylist,
+    ) -> &MediaPlaylist {
+        if let Some(prev) = self.media_playlist.as_ref() {
+            media_playlist.compare_to_previous_version(prev);
+        }
+        self.media_playlist.insert(media_playlist)
+    }
+
+    /// Takes from `prev`, the same variant stream in a previous version of the Multivariant
+    /// Playlist, its `id` and already-loaded `MediaPlaylist`, as well as the known support of
+    /// its codecs if they did not change.
+    ///
+    /// Formats which were read from its initialization segments are also kept if they are
+    /// still not announced.
+    pub(super) fn inherit_from(&mut self, prev: &mut VariantStream) {
+        self.id = prev.id;
+        self.media_playlist = prev.media_playlist.take();
+        if self.url != prev.url {
+            // Its Media Playlist should now be refreshed through the newly-announced URL
+            if let Some(playlist) = self.media_playlist.as_mut() {
+                playlist.update_url(self.url.clone());
+            }
+        }
+        if self.codecs_origin == CodecsOrigin::Guessed
+            && prev.codecs_origin == CodecsOrigin::Inferred
+        {
+            self.codecs = prev.codecs.clone();
+            self.codecs_origin = CodecsOrigin::Inferred;
+        }
+        if self.codecs == prev.codecs && self.supplemental_codecs == prev.supplemental_codecs {
+            self.supported = prev.supported;
+            self.supplemental_codecs_supported = prev.supplemental_codecs_supported;
+        }
+    }
+
+    pub(super) fn set_id(&mut self, id: u32) {
+        self.id = id;
     }
 
     pub(super) fn get_url(&self) -> &Url {
@@ -334,17 +653,94 @@ impl VariantStream {
         self.audio.as_deref()
     }
 
+    pub(super) fn video_group(&self) -> Option<&str> {
+        self.video.as_deref()
+    }
+
+    pub(super) fn subtitles_group(&self) -> Option<&str> {
+        self.subtitles.as_deref()
+    }
+
+    pub(super) fn closed_captions_group(&self) -> Option<&str> {
+        self.closed_captions.as_deref()
+    }
+
     pub(super) fn create_from_stream_inf(
         variant_line: &str,
         url: Url,
         base_uri: &str,
         id: u32,
     ) -> Result<Self, VariantParsingError> {
+        Self::parse(variant_line, Some(url), base_uri, id)
+    }
+
+    /// Create an I-frame-only `VariantStream` from an `EXT-X-I-FRAME-STREAM-INF` tag, whose Media
+    /// Playlist's URL is given through its `URI` attribute.
+    pub(super) fn create_from_i_frame_stream_inf(
+        variant_line: &str,
+        base_uri: &str,
+        id: u32,
+    ) -> Result<Self, VariantParsingError> {
+        Self::parse(variant_line, None, base_uri, id)
+    }
+
+    /// Create a `VariantStream` for a lone Media Playlist, found at `url`, which is not described
+    /// by a Multivariant Playlist.
+    ///
+    /// As no attribute is announced for it, its `codecs` have to be guessed, until they can be
+    /// read from its initialization segments.
+    pub(super) fn create_for_media_playlist(url: Url, codecs: &[&str], id: u32) -> Self {
+        Self {
+            id,
+            stable_id: None,
+            audio: None,
+            average_bandwitdh: None,
+            bandwidth: 0,
+            closed_captions: None,
+            codecs: codecs
+                .iter()
+                .map(|c| (guess_media_type_from_codec(c), (*c).to_owned()))
+                .collect(),
+            codecs_origin: CodecsOrigin::Guessed,
+            supplemental_codecs: vec![],
+            frame_rate: None,
+            hdcp_level: HdcpLevel::None,
+            allowed_cpc: vec![],
+            media_playlist: None,
+            pathway_id: None,
+            program_id: None,
+            resolution: None,
+            score: None,
+            subtitles: None,
+            url,
+            video: None,
+            video_range: VideoDynamicRange::Sdr,
+            context: None,
+            i_frames_only: false,
+            supported: None,
+            supplemental_codecs_supported: None,
+            video_range_supported: None,
+            output_protection_issue: None,
+        }
+    }
+
+    /// Parse the attributes of either an `EXT-X-STREAM-INF` tag, in which case `url` is the URI
+    /// line following it, or of an `EXT-X-I-FRAME-STREAM-INF` tag, in which case `url` is `None`.
+    fn parse(
+        variant_line: &str,
+        url: Option<Url>,
+        base_uri: &str,
+        id: u32,
+    ) -> Result<Self, VariantParsingError> {
+        let i_frames_only = url.is_none();
+        let mut uri: Option<Url> = None;
         let mut bandwidth: Option<u64> = None;
         let mut resolution: Option<VideoResolution> = None;
         let mut average_bandwitdh: Option<u64> = None;
         let mut codecs: Vec<(Option<MediaType>, String)> = vec![];
+        let mut supplemental_codecs: Vec<(Option<MediaType>, String)> = vec![];
         let mut hdcp_level: HdcpLevel = HdcpLevel::None;
+        let mut allowed_cpc: Vec<(String, Vec<String>)> = vec![];
         let mut video_range: VideoDynamicRange = VideoDynamicRange::Sdr;
         let mut program_id: Option<u64> = None;
         let mut score: Option<f64> = None;
@@ -356,7 +752,9 @@ impl VariantStream {
         let mut closed_captions: Option<String> = None;
         let mut pathway_id: Option<String> = None;
 
-        let mut offset = "#EXT-X-STREAM-INF:".len();
+        let mut offset = variant_line
+            .find(':')
+            .map_or(variant_line.len(), |idx| idx + 1);
         loop {
             if offset >= variant_line.len() {
                 break;
@@ -401,6 +799,23 @@ impl VariantStream {
                             Logger::warn("Unparsable CODECS value");
                         }
                     }
+                    "SUPPLEMENTAL-CODECS" => {
+                        let (parsed, end_offset) =
+                            parse_comma_separated_list(variant_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            supplemental_codecs = val
+                                .iter()
+                                .map(|c| {
+                                    // Remove the compatibility brands, e.g. "/db4h"
+                                    let codec = c.split('/').next().unwrap_or(c);
+                                    (guess_media_type_from_codec(codec), codec.to_owned())
+                                })
+                                .collect();
+                        } else {
+                            Logger::warn("Unparsable SUPPLEMENTAL-CODECS value");
+                        }
+                    }
                     "FRAME-RATE" => {
                         let (parsed, end_offset) =
                             parse_decimal_floating_point(variant_line, offset + idx + 1);
@@ -415,12 +830,17 @@ impl VariantStream {
                         let (parsed, end_offset) =
                             parse_enumerated_string(variant_line, offset + idx + 1);
                         offset = end_offset + 1;
-                        hdcp_level = match parsed {
-                            "TYPE-0" => HdcpLevel::Type0,
-                            "TYPE-1" => HdcpLevel::Type1,
-                            "NONE" => HdcpLevel::None,
-                            _ => HdcpLevel::Unknown,
-                        };
+                        hdcp_level = HdcpLevel::parse(parsed);
+                    }
+                    "ALLOWED-CPC" => {
+                        let (parsed, end_offset) =
+                            parse_quoted_string(variant_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            allowed_cpc = parse_allowed_cpc(val);
+                        } else {
+                            Logger::warn("Unparsable ALLOWED-CPC value");
+                        }
                     }
                     "PROGRAM-ID" => {
                         let (parsed, end_offset) =
@@ -519,6 +939,16 @@ impl VariantStream {
                             _ => VideoDynamicRange::Unknown,
                         };
                     }
+                    "URI" if i_frames_only => {
+                        let (parsed, end_offset) =
+                            parse_quoted_string(variant_line, offset + idx + 1);
+                        offset = end_offset + 1;
+                        if let Ok(val) = parsed {
+                            uri = Some(Url::resolve(val, base_uri));
+                        } else {
+                            Logger::warn("Unparsable URI value");
+                        }
+                    }
                     "PATHWAY-ID" => {
                         let (parsed, end_offset) =
                             parse_quoted_string(variant_line, offset + idx + 1);
@@ -536,11 +966,19 @@ impl VariantStream {
             }
         }
 
-        let url = if url.is_absolute() {
-            url
+        let codecs_origin = if codecs.is_empty() {
+            // I-frame-only variant streams only contain video
+            codecs = GUESSED_CODECS
+                .iter()
+                .map(|c| (guess_media_type_from_codec(c), (*c).to_owned()))
+                .filter(|c| !i_frames_only || c.0 == Some(MediaType::Video))
+                .collect();
+            CodecsOrigin::Guessed
         } else {
-            Url::from_relative(base_uri, url)
+            CodecsOrigin::Announced
         };
+
+        let url = url.or(uri).ok_or(VariantParsingError::MissingUri)?;
         if let Some(bandwidth) = bandwidth {
             Ok(Self {
                 id,
@@ -550,8 +988,11 @@ impl VariantStream {
                 bandwidth,
                 closed_captions,
                 codecs,
+                codecs_origin,
+                supplemental_codecs,
                 frame_rate,
                 hdcp_level,
+                allowed_cpc,
                 media_playlist: None,
                 pathway_id,
                 program_id,
@@ -562,13 +1003,53 @@ impl VariantStream {
                 video,
                 video_range,
                 context: None,
+                i_frames_only,
                 supported: None,
+                supplemental_codecs_supported: None,
+                video_range_supported: None,
+                output_protection_issue: None,
             })
         } else {
             Err(VariantParsingError::MissingBandwidth)
         }
     }
 
+    /// Create a copy of this `VariantStream`, with the given `id`, for the Content Steering
+    /// Pathway `pathway_id`, whose Media Playlist is found at `url` instead.
+    ///
+    /// That copy has no `MediaPlaylist` loaded yet.
+    pub(super) fn clone_for_pathway(&self, id: u32, pathway_id: &str, url: Url) -> Self {
+        Self {
+            id,
+            stable_id: self.stable_id.clone(),
+            audio: self.audio.clone(),
+            average_bandwitdh: self.average_bandwitdh,
+            bandwidth: self.bandwidth,
+            closed_captions: self.closed_captions.clone(),
+            codecs: self.codecs.clone(),
+            codecs_origin: self.codecs_origin,
+            supplemental_codecs: self.supplemental_codecs.clone(),
+            frame_rate: self.frame_rate,
+            hdcp_level: self.hdcp_level,
+            allowed_cpc: self.allowed_cpc.clone(),
+            media_playlist: None,
+            pathway_id: Some(pathway_id.to_owned()),
+            program_id: self.program_id,
+            resolution: self.resolution,
+            score: self.score,
+            subtitles: self.subtitles.clone(),
+            url,
+            video: self.video.clone(),
+            video_range: self.video_range,
+            context: None,
+            i_frames_only: self.i_frames_only,
+            supported: self.supported,
+            supplemental_codecs_supported: self.supplemental_codecs_supported,
+            video_range_supported: self.video_range_supported,
+            output_protection_issue: self.output_protection_issue,
+        }
+    }
+
     pub(super) fn communicate_context(&mut self, context: MediaPlaylistContext) {
         self.context = Some(context);
     }
@@ -578,11 +1059,12 @@ fn guess_media_type_from_codec(codec: &str) -> Option<MediaType> {
     let (base, _) = split_codec(codec);
 
     match base {
-        "mp4a" => Some(MediaType::Audio),
+        "mp4a" | "mp3" => Some(MediaType::Audio),
         "ec-3" | "ac-3" => Some(MediaType::Audio),
         "avc1" | "avc3" => Some(MediaType::Video),
         "hvc1" | "hev1" => Some(MediaType::Video),
-        "dvh1" | "dvhe" => Some(MediaType::Video),
+        "av01" => Some(MediaType::Video),
+        "dvh1" | "dvhe" | "dav1" => Some(MediaType::Video),
         _ => None,
     }
 }
@@ -596,3 +1078,63 @@ fn split_codec(codec: &str) -> (&str, &str) {
         (codec, "")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_allowed_cpc() {
+        assert_eq!(
+            parse_allowed_cpc("com.example.drm1:SMART-TV/PC,com.example.drm2:HW"),
+            vec![
+                (
+                    "com.example.drm1".to_owned(),
+                    vec!["SMART-TV".to_owned(), "PC".to_owned()]
+                ),
+                ("com.example.drm2".to_owned(), vec!["HW".to_owned()]),
+            ]
+        );
+        assert_eq!(
+            parse_allowed_cpc("urn:uuid:edef8ba9:SW"),
+            vec![("urn:uuid:edef8ba9".to_owned(), vec!["SW".to_owned()])]
+        );
+        assert!(parse_allowed_cpc("").is_empty());
+    }
+
+    #[test]
+    fn test_update_video_range_support() {
+        let variant = |attrs: &str| {
+            let line = format!("#EXT-X-STREAM-INF:BANDWIDTH=1000000{attrs}");
+            let url = Url::new("https://example.com/variant.m3u8".to_owned());
+            let mut variant =
+                VariantStream::create_from_stream_inf(&line, url, "https://example.com/", 0)
+                    .unwrap();
+            variant.update_support(true);
+            variant
+        };
+        let is_supported = |variant: &mut VariantStream, ranges: &[VideoDynamicRange]| {
+            variant.update_video_range_support(ranges);
+            variant.is_playable()
+        };
+
+        let mut sdr = variant("");
+        assert_eq!(sdr.video_range(), VideoDynamicRange::Sdr);
+        // Not playable until the video range support is known
+        assert!(!sdr.is_playable());
+        assert!(is_supported(&mut sdr, &[VideoDynamicRange::Sdr]));
+        assert!(!is_supported(&mut sdr, &[]));
+
+        let mut pq = variant(",VIDEO-RANGE=PQ");
+        assert_eq!(pq.video_range(), VideoDynamicRange::Pq);
+        assert!(!is_supported(&mut pq, &[VideoDynamicRange::Sdr]));
+        let hdr_ranges = [VideoDynamicRange::Sdr, VideoDynamicRange::Pq];
+        assert!(is_supported(&mut pq, &hdr_ranges));
+        assert!(!is_supported(&mut pq, &[VideoDynamicRange::Hlg]));
+
+        let mut unknown = variant(",VIDEO-RANGE=FOO");
+        assert_eq!(unknown.video_range(), VideoDynamicRange::Unknown);
+        assert!(!is_supported(&mut unknown, &hdr_ranges));
+        assert!(!is_supported(&mut unknown, &[VideoDynamicRange::Unknown]));
+    }
+}
@@ -0,0 +1,206 @@
+use super::{media_tag::MediaTrack, MediaTag};
+use std::ops::{Deref, DerefMut};
+
+/// Allows to translate the `EXT-X-MEDIA` tags of type `"VIDEO"` found inside a Multivariant
+/// Playlist into well defined video tracks (e.g. the different camera angles of a same event)
+/// that make more sense in a player API.
+pub(crate) struct VideoTrackList {
+    /// List of video tracks as constructed by this `VideoTrackList`.
+    tracks: Vec<VideoTrack>,
+}
+
+impl VideoTrackList {
+    /// Creates a new `VideoTrackList` from the given `Vec<MediaTag>`, which should contain
+    /// information on all media tags of type `"VIDEO"`.
+    pub(super) fn new(mut video_media: Vec<MediaTag>) -> Self {
+        let mut available_video_tracks: Vec<VideoTrack> = vec![];
+        while let Some(media) = video_media.pop() {
+            let id = media.id();
+
+            // Check if the track already exist in another encoding quality
+            let pos_compat = available_video_tracks.iter().position(|t| {
+                t.name() == media.name()
+                    && t.language() == media.language()
+                    && t.assoc_language() == media.assoc_language()
+            });
+
+            if let Some(pos) = pos_compat {
+                available_video_tracks[pos].media_tags.push(media);
+            } else {
+                available_video_tracks.push(VideoTrack {
+                    id: id.to_owned(),
+                    media_tags: vec![media],
+                });
+            }
+        }
+        Self {
+            tracks: available_video_tracks,
+        }
+    }
+
+    /// Returns values of all of the HLS `GROUP-ID` attributes who are linked to the given track's
+    /// `id`.
+    ///
+    /// This method can be called for example to check if a given HLS variant is compatible to the
+    /// given track's id.
+    pub(super) fn groups_for_track_id(&self, id: u32) -> Vec<&str> {
+        self.iter()
+            .find(|t| t.id() == id)
+            .map(|t| t.media_tags.iter().map(|m| m.group_id()).collect())
+            .unwrap_or(vec![])
+    }
+
+    /// Returns `VideoTrack` object associated to the given `MediaTag`'s id.
+    ///
+    /// Returns `None` if none is found.
+    pub(super) fn track_for_media_tag(&self, id: u32) -> Option<&VideoTrack> {
+        self.iter()
+            .find(|t| t.media_tags.iter().any(|m| m.id() == id))
+    }
+
+    /// Returns reference too `MediaTag` object associated to the given `MediaTag`'s id, stored in
+    /// the `VideoTrackList`
+    ///
+    /// Returns `None` if none is found.
+    pub(super) fn media_tag(&self, id: u32) -> Option<&MediaTag> {
+        self.iter()
+            .find_map(|t| t.media_tags.iter().find(|m| m.id() == id))
+    }
+
+    /// Returns mutable reference too `MediaTag` object associated to the given `MediaTag`'s id,
+    /// stored in the `VideoTrackList`
+    ///
+    /// Returns `None` if none is found.
+    pub(super) fn media_tag_mut(&mut self, id: u32) -> Option<&mut MediaTag> {
+        self.iter_mut()
+            .find_map(|t| t.media_tags.iter_mut().find(|m| m.id() == id))
+    }
+
+    /// Returns an iterator implementation on tuples of `VideoTrack` and inner `MediaTag` objects
+    /// found in this `VideoTrackList`.
+    pub(super) fn iter_tracks_media(&self) -> impl Iterator<Item = (&VideoTrack, &MediaTag)> {
+        self.iter()
+            .flat_map(|t| t.media_tags.iter().map(move |m| (t, m)))
+    }
+
+    /// Returns an iterator on mutable references to all `MediaTag` objects found in this
+    /// `VideoTrackList`.
+    pub(super) fn iter_media_mut(&mut self) -> impl Iterator<Item = &mut MediaTag> {
+        self.iter_mut().flat_map(|t| t.media_tags.iter_mut())
+    }
+}
+
+impl Deref for VideoTrackList {
+    type Target = Vec<VideoTrack>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tracks
+    }
+}
+
+impl DerefMut for VideoTrackList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.tracks
+    }
+}
+
+/// Describe a single video track object with its own characteristics.
+pub struct VideoTrack {
+    /// Unique identifier for that video track in the parent `VideoTrackList`.
+    id: u32,
+    /// `MediaTag` objects associated to this video track.
+    ///
+    /// These are usually several qualities representing the same content.
+    pub(self) media_tags: Vec<MediaTag>,
+}
+
+impl MediaTrack for VideoTrack {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn set_id(&mut self, id: u32) {
+        self.id = id;
+    }
+
+    fn media_tags(&self) -> &[MediaTag] {
+        &self.media_tags
+    }
+}
+
+impl VideoTrack {
+    /// Unique identifier for that video track in the parent `VideoTrackList`.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns reference to the language linked to that video track.
+    pub fn language(&self) -> Option<&str> {
+        self.media_tags.first().and_then(|t| t.language())
+    }
+
+    /// Returns reference to the associated (secondary) language linked to that video track.
+    pub fn assoc_language(&self) -> Option<&str> {
+        self.media_tags.first().and_then(|t| t.assoc_language())
+    }
+
+    /// Returns human-readable name associated with that video track (e.g. the name of a camera
+    /// angle).
+    pub fn name(&self) -> &str {
+        self.media_tags.first().unwrap().name()
+    }
+
+    /// Returns slice of the various `MediaTag` objects that video track is associated with.
+    ///
+    /// These are usually several qualities representing the same content.
+    pub(super) fn medias(&self) -> &[MediaTag] {
+        &self.media_tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::url::Url;
+
+    #[test]
+    fn test_video_track_list() {
+        let url = Url::new("https://example.com/main.m3u8".to_owned());
+        let media = [
+            "GROUP-ID=\"low\",NAME=\"Main\",DEFAULT=YES,URI=\"main-low.m3u8\"",
+            "GROUP-ID=\"low\",NAME=\"Side\",URI=\"side-low.m3u8\"",
+            "GROUP-ID=\"high\",NAME=\"Main\",DEFAULT=YES,URI=\"main-high.m3u8\"",
+            "GROUP-ID=\"high\",NAME=\"Side\",URI=\"side-high.m3u8\"",
+            "GROUP-ID=\"high\",NAME=\"Aerial\",URI=\"aerial-high.m3u8\"",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(id, attrs)| {
+            let line = format!("#EXT-X-MEDIA:TYPE=VIDEO,{attrs}");
+            MediaTag::create(&line, &url, id as u32).unwrap()
+        })
+        .collect();
+        let tracks = VideoTrackList::new(media);
+        let track_id = |name: &str| tracks.iter().find(|t| t.name() == name).unwrap().id();
+
+        // The same angle in several qualities is grouped in a single track
+        assert_eq!(tracks.len(), 3);
+        let mut groups = tracks.groups_for_track_id(track_id("Main"));
+        groups.sort();
+        assert_eq!(groups, ["high", "low"]);
+        assert_eq!(tracks.groups_for_track_id(track_id("Aerial")), ["high"]);
+        assert!(tracks.groups_for_track_id(9999).is_empty());
+
+        assert_eq!(tracks.track_for_media_tag(1).unwrap().name(), "Side");
+        assert_eq!(
+            tracks.track_for_media_tag(2).unwrap().id(),
+            track_id("Main")
+        );
+        assert_eq!(tracks.media_tag(3).unwrap().group_id(), "high");
+        assert!(tracks.media_tag(5).is_none());
+        assert_eq!(tracks.iter_tracks_media().count(), 5);
+        for (track, media) in tracks.iter_tracks_media() {
+            assert_eq!(track.name(), media.name());
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use crate::{
-    bindings::{jsIsTypeSupported, MediaType, PlaylistNature},
+    bindings::{jsGetMonotonicTime, jsIsTypeSupported, MediaType, PlaylistNature},
     media_element::SegmentQualityContext,
     parser::{
-        AudioTrack, MediaPlaylist, MediaPlaylistUpdateError, MultivariantPlaylist, SegmentList,
-        VariantStream,
+        AudioSelectionPreferences, AudioTrack, DateRange, InstreamId, Interstitial, MediaPlaylist,
+        MediaPlaylistUpdateError, MultivariantPlaylist, OutputProtectionCapabilities, PathwayClone,
+        SegmentList, SessionData, SteeringManifest, TextTrack, VariantStream, VideoDynamicRange,
+        VideoTrack,
     },
     utils::url::Url,
     Logger,
@@ -15,7 +17,7 @@ pub(crate) use crate::parser::MediaPlaylistPermanentId;
 /// Stores information about the current loaded Multivariant Playlist and its sub-playlists:
 ///   - Information on the Multivariant Playlist itself.
 ///   - On the current variant selected.
-///   - Information on the different audio and video Media Playlists selected.
+///   - Information on the different audio, video and subtitles Media Playlists selected.
 pub(crate) struct PlaylistStore {
     /// A struct representing the "Multivariant Playlist", a.k.a. "Master Playlist" of
     /// the currently loaded HLS content.
@@ -43,18 +45,78 @@ pub(crate) struct PlaylistStore {
     /// Playlist but to the `id` of the track itself.
     curr_audio_track: Option<u32>,
 
+    /// `id` identifier for the currently-selected video track. `None` if no video track is
+    /// explicitely selected.
+    ///
+    /// Like `curr_audio_track`, this identifier is linked to the `id` of the track itself.
+    curr_video_track: Option<u32>,
+
+    /// Preferences and capabilities considered when no audio track is explicitely selected.
+    audio_preferences: AudioSelectionPreferences,
+
+    /// Video dynamic ranges supported by the display. Variants with another dynamic range are
+    /// not played.
+    supported_video_ranges: Vec<VideoDynamicRange>,
+
+    /// Chosen playlist for subtitles.
+    ///
+    /// Set to `None` if no text track is selected or if it is not available for the current
+    /// variant.
+    curr_text_id: Option<MediaPlaylistPermanentId>,
+
+    /// `id` identifier for the currently-selected text track. `None` if text tracks are
+    /// disabled, which is the default.
+    ///
+    /// Like `curr_audio_track`, this identifier is linked to the `id` of the track itself.
+    curr_text_track: Option<u32>,
+
     /// If `true` a variant is being manually locked and as such, cannot change.
     is_variant_locked: bool,
 
+    /// `id` of the I-frame-only variant whose Media Playlist is currently chosen for video in
+    /// place of the one of the current variant, when trick play is enabled.
+    ///
+    /// Set to `None` when trick play is disabled.
+    curr_i_frame_variant_id: Option<u32>,
+
     /// Store the last communicated bandwidth
     last_bandwidth: f64,
 
+    /// Time, as returned by `jsGetMonotonicTime`, at which each loaded Media Playlist was last
+    /// updated, allowing to know whether Playlist Delta Updates can be requested for it.
+    media_playlist_update_times: Vec<(MediaPlaylistPermanentId, f64)>,
+
     /// Before actually playing a content, supported codecs need to be checked
     /// to avoid mistakenly choosing an unsupported codec.
     ///
     /// This bool is set to `true` only once ALL codecs in the
     /// `MultivariantPlaylist` have been properly checked.
     codecs_checked: bool,
+
+    /// URL from which the Content Steering manifest should be loaded, without its query
+    /// parameters linked to the current playback conditions.
+    ///
+    /// Set to `None` if the content does not rely on Content Steering.
+    steering_manifest_url: Option<Url>,
+
+    /// Content Steering Pathways, from the most to the least preferred, as announced by the last
+    /// loaded Content Steering manifest.
+    ///
+    /// Empty if no Content Steering manifest has been loaded yet.
+    pathway_priority: Vec<String>,
+
+    /// Content Steering Pathways which should not be relied on anymore because requests for
+    /// their resources repeatedly failed.
+    dropped_pathways: Vec<String>,
+
+    /// Content Steering Pathways created by cloning other ones, as announced by loaded Content
+    /// Steering manifests. Kept to be able to re-create them when the Multivariant Playlist is
+    /// refreshed.
+    pathway_clones: Vec<PathwayClone>,
+
+    /// The Content Steering Pathway the current variant and the ones it can be switched to
+    /// belong to (`"."` being the default Pathway).
+    curr_pathway_id: String,
 }
 
 impl PlaylistStore {
@@ -62,27 +124,59 @@ impl PlaylistStore {
     ///
     /// Automatically selects the variant with the highest quality (or score if defined) on call.
     /// Please call `update_curr_bandwidth` to select a variant based on an actual criteria.
+    ///
+    /// `audio_preferences` are the preferences and capabilities considered when automatically
+    /// choosing audio renditions.
+    ///
+    /// Variants requiring an output protection which cannot be enforced according to
+    /// `output_protection` are not considered. Variants whose video dynamic range is not part of
+    /// `supported_video_ranges` won't be either once codecs are checked.
     pub(crate) fn try_new(
-        playlist: MultivariantPlaylist,
+        mut playlist: MultivariantPlaylist,
         initial_bandwidth: f64,
+        audio_preferences: AudioSelectionPreferences,
+        output_protection: &OutputProtectionCapabilities,
+        supported_video_ranges: Vec<VideoDynamicRange>,
     ) -> Result<Self, PlaylistStoreError> {
         Logger::debug(&format!(
             "PS: Creating new PlaylistStore (bw: {initial_bandwidth})"
         ));
-        let variants = playlist.all_variants();
-        let initial_variant =
-            if let Some(variant_id) = best_variant_id(variants.iter(), initial_bandwidth) {
-                playlist.variant(variant_id).unwrap()
-            } else if let Some(variant_id) = fallback_variant_id(variants.iter()) {
-                Logger::info("PS: Found no bandwidth-compatible variant amongst all variants");
-                playlist.variant(variant_id).unwrap()
-            } else {
-                Logger::error("PS: Found no variant in the given MultivariantPlaylist");
-                return Err(PlaylistStoreError::NoInitialVariant);
-            };
+        playlist.update_output_protection(output_protection);
+        let curr_pathway_id = initial_pathway_id(&playlist);
+        let mut variants: Vec<&VariantStream> = playlist
+            .all_variants()
+            .iter()
+            .filter(|v| v.pathway_id() == curr_pathway_id)
+            .collect();
+        if variants
+            .iter()
+            .any(|v| v.output_protection_issue().is_none())
+        {
+            variants.retain(|v| v.output_protection_issue().is_none());
+        }
+        let max_channels = audio_preferences.max_channels;
+        if variants
+            .iter()
+            .any(|v| playlist.fits_audio_channels(v, max_channels))
+        {
+            variants.retain(|v| playlist.fits_audio_channels(v, max_channels));
+        }
+        let initial_variant = if let Some(variant_id) =
+            best_variant_id(variants.iter().copied(), initial_bandwidth)
+        {
+            playlist.variant(variant_id).unwrap()
+        } else if let Some(variant_id) = fallback_variant_id(variants.into_iter()) {
+            Logger::info("PS: Found no bandwidth-compatible variant amongst all variants");
+            playlist.variant(variant_id).unwrap()
+        } else {
+            Logger::error("PS: Found no variant in the given MultivariantPlaylist");
+            return Err(PlaylistStoreError::NoInitialVariant);
+        };
         let curr_variant_id = initial_variant.id();
-        let curr_video_id = playlist.video_media_playlist_id_for(initial_variant);
-        let curr_audio_id = playlist.audio_media_playlist_id_for(initial_variant, None);
+        let curr_video_id = playlist.video_media_playlist_id_for(initial_variant, None);
+        let curr_audio_id =
+            playlist.audio_media_playlist_id_for(initial_variant, None, audio_preferences);
+        let steering_manifest_url = playlist.content_steering().map(|c| c.server_url().clone());
 
         Ok(Self {
             playlist,
@@ -90,9 +184,21 @@ impl PlaylistStore {
             curr_audio_id,
             curr_video_id,
             curr_audio_track: None,
+            curr_video_track: None,
+            audio_preferences,
+            supported_video_ranges,
+            curr_text_id: None,
+            curr_text_track: None,
             is_variant_locked: false,
+            curr_i_frame_variant_id: None,
             last_bandwidth: 0.,
+            media_playlist_update_times: vec![],
             codecs_checked: false,
+            steering_manifest_url,
+            pathway_priority: vec![],
+            dropped_pathways: vec![],
+            pathway_clones: vec![],
+            curr_pathway_id,
         })
     }
 
@@ -102,7 +208,135 @@ impl PlaylistStore {
         self.playlist.url()
     }
 
-    /// Check which codecs present in the `MultivariantPlaylist` are supported.
+    /// Replace the stored `MultivariantPlaylist` by `playlist`, a refreshed version of it.
+    ///
+    /// Variants and renditions still present in it keep their `id` and their already-loaded
+    /// `MediaPlaylist`s, so the current variant - even when locked -, the chosen tracks and the
+    /// current Media Playlists are kept. The current variant is only updated if it is not
+    /// present or playable anymore, and tracks are only unselected if they disappeared.
+    ///
+    /// Variants requiring an output protection which cannot be enforced according to
+    /// `output_protection` are not considered.
+    ///
+    /// Returns an error if codecs have all been checked and no variant can be played anymore.
+    pub(crate) fn update_multivariant_playlist(
+        &mut self,
+        mut playlist: MultivariantPlaylist,
+        output_protection: &OutputProtectionCapabilities,
+    ) -> Result<SetTrackResponse, PlaylistStoreError> {
+        Logger::info("PS: Updating the Multivariant Playlist");
+        self.pathway_clones.iter().for_each(|clone| {
+            playlist.add_pathway_clone(clone);
+        });
+        playlist.reconcile_with(&mut self.playlist);
+        playlist.update_output_protection(output_protection);
+        self.playlist = playlist;
+        if self.steering_manifest_url.is_none() {
+            self.steering_manifest_url = self
+                .playlist
+                .content_steering()
+                .map(|c| c.server_url().clone());
+        }
+        if !self
+            .playlist
+            .all_variants()
+            .iter()
+            .any(|v| v.pathway_id() == self.curr_pathway_id)
+        {
+            self.curr_pathway_id = initial_pathway_id(&self.playlist);
+        }
+        if self
+            .curr_audio_track
+            .is_some_and(|id| !self.audio_tracks().iter().any(|t| t.id() == id))
+        {
+            self.curr_audio_track = None;
+        }
+        if self
+            .curr_video_track
+            .is_some_and(|id| !self.video_tracks().iter().any(|t| t.id() == id))
+        {
+            self.curr_video_track = None;
+        }
+        if self
+            .curr_text_track
+            .is_some_and(|id| !self.text_tracks().iter().any(|t| t.id() == id))
+        {
+            self.curr_text_track = None;
+        }
+        if self
+            .curr_i_frame_variant_id
+            .is_some_and(|id| self.playlist.i_frame_variant(id).is_none())
+        {
+            self.curr_i_frame_variant_id = None;
+        }
+
+        let prev_variant_id = self.curr_variant_id;
+        let prev_audio_id = self.curr_audio_id.clone();
+        let prev_video_id = self.curr_video_id.clone();
+        self.codecs_checked = false;
+        self.check_codecs()?;
+
+        let is_curr_variant_kept = self.curr_variant_id == prev_variant_id
+            && self.curr_variant().is_some_and(|v| v.is_playable());
+        let unlocked_variant = !is_curr_variant_kept && self.is_variant_locked;
+        if is_curr_variant_kept {
+            let is_stale = |id: &Option<MediaPlaylistPermanentId>| {
+                id.as_ref()
+                    .is_some_and(|id| self.playlist.media_playlist_url(id).is_none())
+            };
+            if is_stale(&self.curr_audio_id)
+                || is_stale(&self.curr_video_id)
+                || is_stale(&self.curr_text_id)
+            {
+                self.set_curr_variant_and_media_id(self.curr_variant_id);
+            }
+        } else {
+            self.is_variant_locked = false;
+            if self.variants_for_curr_track().is_empty() {
+                self.curr_audio_track = None;
+            }
+            if !self.variants_for_curr_track().is_empty() {
+                self.update_variant(None);
+            } else if let Some(variant_id) = self
+                .playlist
+                .all_variants()
+                .iter()
+                .find(|v| v.pathway_id() == self.curr_pathway_id)
+                .map(|v| v.id())
+            {
+                // Codecs of the new variants are still being checked, the current variant
+                // will be re-considered once they are
+                self.set_curr_variant_and_media_id(variant_id);
+            } else {
+                Logger::error("PS: Found no variant in the refreshed MultivariantPlaylist");
+                return Err(PlaylistStoreError::NoInitialVariant);
+            }
+        }
+        self.curr_text_id = self.text_media_playlist_id();
+
+        let mut updates = vec![];
+        if self.curr_audio_id != prev_audio_id {
+            updates.push(MediaType::Audio);
+        }
+        if self.curr_video_id != prev_video_id {
+            updates.push(MediaType::Video);
+        }
+        if updates.is_empty() && !unlocked_variant {
+            Ok(SetTrackResponse::NoUpdate)
+        } else {
+            Ok(SetTrackResponse::VariantUpdate {
+                updates: if updates.is_empty() {
+                    VariantUpdateResult::Unchanged
+                } else {
+                    VariantUpdateResult::EqualOrUnknown(updates)
+                },
+                unlocked_variant,
+            })
+        }
+    }
+
+    /// Check which codecs present in the `MultivariantPlaylist` are supported, as well as which
+    /// variants have a video dynamic range supported by the display.
     ///
     /// This allows the playlist store to know which variant can actually be relied on.
     /// As such you should be extra careful when using the `PlaylistStore` before that check has
@@ -120,34 +354,73 @@ impl PlaylistStore {
         }
 
         let mut are_all_codecs_checked = true;
-        self.playlist.variants_mut().iter_mut().for_each(|v| {
-            if v.supported().is_some() {
-                return;
+        let supported_video_ranges = self.supported_video_ranges.as_slice();
+        let mut check_variant_codecs = |v: &mut VariantStream| {
+            v.update_video_range_support(supported_video_ranges);
+            if v.supported().is_none() {
+                [MediaType::Video, MediaType::Audio]
+                    .into_iter()
+                    .for_each(|mt| {
+                        if let Some(codec) = v.codecs(mt) {
+                            if let Some(is_supported) = jsIsTypeSupported(mt, &codec) {
+                                v.update_support(is_supported);
+                            } else {
+                                are_all_codecs_checked = false;
+                            }
+                        }
+                    });
             }
-            [MediaType::Video, MediaType::Audio]
-                .into_iter()
-                .for_each(|mt| {
-                    if let Some(codec) = v.codecs(mt) {
-                        if let Some(is_supported) = jsIsTypeSupported(mt, &codec) {
-                            v.update_support(is_supported);
-                        } else {
-                            are_all_codecs_checked = false;
+            if v.has_supplemental_codecs() && v.supplemental_codecs_supported().is_none() {
+                let mut supplemental_support = Some(true);
+                [MediaType::Video, MediaType::Audio]
+                    .into_iter()
+                    .for_each(|mt| {
+                        if let Some(codec) = v.supplemental_codecs(mt) {
+                            match jsIsTypeSupported(mt, &codec) {
+                                Some(true) => {}
+                                Some(false) => supplemental_support = Some(false),
+                                None => {
+                                    if supplemental_support == Some(true) {
+                                        supplemental_support = None;
+                                    }
+                                }
+                            }
+                        }
+                    });
+                match supplemental_support {
+                    Some(is_supported) => {
+                        if is_supported {
+                            Logger::info(&format!(
+                                "PS: Relying on supplemental codecs for variant {}",
+                                v.id()
+                            ));
+                            v.update_support(true);
                         }
+                        v.update_supplemental_codecs_support(is_supported);
                     }
-                });
-        });
+                    None => are_all_codecs_checked = false,
+                }
+            }
+        };
+        self.playlist
+            .variants_mut()
+            .iter_mut()
+            .for_each(&mut check_variant_codecs);
+        self.playlist
+            .i_frame_variants_mut()
+            .iter_mut()
+            .for_each(&mut check_variant_codecs);
         self.codecs_checked = are_all_codecs_checked;
 
         if are_all_codecs_checked {
             Logger::info("PS: All codecs have been checked");
             let curr_variant_still_here = self
-                .playlist
                 .supported_variants()
                 .iter()
                 .any(|v| v.id() == self.curr_variant_id);
 
             if !curr_variant_still_here {
-                let new_variant_id = self.playlist.supported_variants().first().map(|v| v.id());
+                let new_variant_id = self.supported_variants().first().map(|v| v.id());
                 if let Some(variant_id) = new_variant_id {
                     self.set_curr_variant_and_media_id(variant_id);
                 } else {
@@ -161,6 +434,12 @@ impl PlaylistStore {
         Ok(are_all_codecs_checked)
     }
 
+    /// Returns `true` once all codecs in the `MultivariantPlaylist` have been checked through
+    /// `check_codecs`.
+    pub(crate) fn are_codecs_checked(&self) -> bool {
+        self.codecs_checked
+    }
+
     /// Returns the list of tuples listing loaded media playlists.
     ///
     /// The tuples are defined as such:
@@ -178,7 +457,9 @@ impl PlaylistStore {
     }
 
     pub(crate) fn is_curr_media_playlist(&self, id: &MediaPlaylistPermanentId) -> bool {
-        Some(id) == self.curr_audio_id.as_ref() || Some(id) == self.curr_video_id.as_ref()
+        Some(id) == self.curr_audio_id.as_ref()
+            || Some(id) == self.curr_video_id.as_ref()
+            || Some(id) == self.curr_text_id.as_ref()
     }
 
     /// Returns `true` if the current playlist linked to the given `MediaType` has been loaded.
@@ -214,23 +495,211 @@ impl PlaylistStore {
         media_playlist_data: impl BufRead,
         url: Url,
     ) -> Result<&MediaPlaylist, MediaPlaylistUpdateError> {
-        self.playlist
-            .update_media_playlist(id, media_playlist_data, url)
+        let media_playlist = self
+            .playlist
+            .update_media_playlist(id, media_playlist_data, url)?;
+        self.media_playlist_update_times.retain(|(i, _)| i != id);
+        self.media_playlist_update_times
+            .push((id.clone(), jsGetMonotonicTime()));
+        Ok(media_playlist)
     }
 
     /// Returns vec describing all available variant streams in the current MultivariantPlaylist.
+    ///
+    /// Only variants of the current Content Steering Pathway are returned.
     pub(crate) fn supported_variants(&self) -> Vec<&VariantStream> {
-        self.playlist.supported_variants()
+        self.in_curr_pathway(self.playlist.supported_variants())
+    }
+
+    /// Returns vec describing the variant streams in the current MultivariantPlaylist which are
+    /// supported but cannot be presented due to the output protection they require.
+    ///
+    /// Only variants of the current Content Steering Pathway are returned.
+    pub(crate) fn output_protected_variants(&self) -> Vec<&VariantStream> {
+        self.in_curr_pathway(self.playlist.output_protected_variants())
     }
 
     /// Returns vec describing all available variant streams in the current MultivariantPlaylist.
     pub(crate) fn variants_for_curr_track(&self) -> Vec<&VariantStream> {
-        if let Some(track_id) = self.curr_audio_track {
-            self.playlist.supported_variants_for_audio(track_id)
-        } else if let Some(track_id) = self.curr_audio_track_id() {
-            self.playlist.supported_variants_for_audio(track_id)
+        self.variants_for_curr_track_in(&self.curr_pathway_id)
+    }
+
+    /// Returns vec describing all available variant streams of the Content Steering Pathway
+    /// `pathway_id` which are linked to the current audio and video tracks.
+    ///
+    /// If no audio track is explicitely selected, variants whose audio renditions all exceed the
+    /// maximum number of audio channels are also filtered out.
+    ///
+    /// If no variant is linked to both, the video track is not considered. If there's still no
+    /// variant, the maximum number of audio channels isn't either.
+    fn variants_for_curr_track_in(&self, pathway_id: &str) -> Vec<&VariantStream> {
+        let max_audio_channels = if self.curr_audio_track.is_some() {
+            None
+        } else {
+            self.audio_preferences.max_channels
+        };
+        let audio_track = self.curr_audio_track.or_else(|| {
+            // A track chosen by default is not kept if it exceeds the maximum number of channels
+            self.curr_audio_track_id().filter(|id| {
+                self.audio_tracks()
+                    .iter()
+                    .find(|t| t.id() == *id)
+                    .and_then(|t| t.channels())
+                    .zip(max_audio_channels)
+                    .is_none_or(|(count, max)| count <= max)
+            })
+        });
+        let video_track = self.curr_video_track.or_else(|| self.curr_video_track_id());
+        let mut variants = self.playlist.supported_variants_for_tracks(
+            audio_track,
+            video_track,
+            max_audio_channels,
+        );
+        if variants.is_empty() && video_track.is_some() {
+            variants =
+                self.playlist
+                    .supported_variants_for_tracks(audio_track, None, max_audio_channels);
+        }
+        if variants.is_empty() && max_audio_channels.is_some() {
+            variants = self
+                .playlist
+                .supported_variants_for_tracks(audio_track, None, None);
+        }
+        variants
+            .into_iter()
+            .filter(|v| v.pathway_id() == pathway_id)
+            .collect()
+    }
+
+    /// Only keep from `variants` the ones belonging to the current Content Steering Pathway.
+    fn in_curr_pathway<'a>(&self, variants: Vec<&'a VariantStream>) -> Vec<&'a VariantStream> {
+        variants
+            .into_iter()
+            .filter(|v| v.pathway_id() == self.curr_pathway_id)
+            .collect()
+    }
+
+    /// Returns the URL at which the Content Steering manifest should be loaded, with the
+    /// `_HLS_pathway` and `_HLS_throughput` query parameters indicating the current Pathway and
+    /// the last communicated bandwidth.
+    ///
+    /// Returns `None` if the content does not rely on Content Steering.
+    pub(crate) fn steering_manifest_url(&self) -> Option<Url> {
+        let mut url = self.steering_manifest_url.clone()?;
+        url.add_query_parameter("_HLS_pathway", &self.curr_pathway_id);
+        if self.last_bandwidth > 0. {
+            url.add_query_parameter(
+                "_HLS_throughput",
+                &(self.last_bandwidth.round() as u64).to_string(),
+            );
+        }
+        Some(url)
+    }
+
+    /// Exploit a newly-loaded Content Steering manifest, by creating the Pathways it clones and
+    /// by switching to its preferred Pathway if it changed.
+    ///
+    /// Returns `None` if the current Pathway was kept, or the result of switching to another
+    /// Pathway's variant.
+    pub(crate) fn update_steering_manifest(
+        &mut self,
+        manifest: &SteeringManifest,
+    ) -> Option<VariantUpdateResult> {
+        if let Some(reload_url) = manifest.reload_url() {
+            self.steering_manifest_url = Some(reload_url.clone());
+        }
+        manifest.pathway_clones().iter().for_each(|clone| {
+            if self.playlist.add_pathway_clone(clone) {
+                Logger::info(&format!("PS: Created cloned pathway {}", clone.id()));
+                self.pathway_clones.push(clone.clone());
+            }
+        });
+        self.pathway_priority = manifest.pathway_priority().to_vec();
+        let wanted_pathway = self
+            .pathway_priority
+            .iter()
+            .find(|p| self.is_pathway_available(p))?;
+        if *wanted_pathway == self.curr_pathway_id {
+            None
         } else {
-            self.supported_variants()
+            Some(self.switch_pathway(wanted_pathway.clone()))
+        }
+    }
+
+    /// Stop relying on the current Content Steering Pathway, for example because requests for
+    /// its resources repeatedly failed, and switch to the next preferred one.
+    ///
+    /// Returns `None` if no other Pathway is available, in which case the current one is kept.
+    /// Returns the result of switching to another Pathway's variant otherwise.
+    pub(crate) fn drop_curr_pathway(&mut self) -> Option<VariantUpdateResult> {
+        self.dropped_pathways.push(self.curr_pathway_id.clone());
+        let new_pathway = self
+            .pathway_priority
+            .iter()
+            .map(|p| p.as_str())
+            .chain(self.playlist.all_variants().iter().map(|v| v.pathway_id()))
+            .find(|p| self.is_pathway_available(p))
+            .map(|p| p.to_owned());
+        match new_pathway {
+            Some(pathway_id) => Some(self.switch_pathway(pathway_id)),
+            None => {
+                self.dropped_pathways.pop();
+                None
+            }
+        }
+    }
+
+    /// Returns `true` if the Content Steering Pathway `pathway_id` has supported variants for
+    /// the current audio track and was not dropped.
+    fn is_pathway_available(&self, pathway_id: &str) -> bool {
+        !self.dropped_pathways.iter().any(|p| p == pathway_id)
+            && !self.variants_for_curr_track_in(pathway_id).is_empty()
+    }
+
+    /// Switch to the Content Steering Pathway `pathway_id`, selecting in it the variant
+    /// equivalent to the current one if it is locked, or the best one otherwise.
+    fn switch_pathway(&mut self, pathway_id: String) -> VariantUpdateResult {
+        Logger::info(&format!(
+            "PS: Switching from pathway {} to {pathway_id}",
+            self.curr_pathway_id
+        ));
+        let prev_audio_id = self.curr_audio_id.clone();
+        let prev_video_id = self.curr_video_id.clone();
+        let equivalent_variant_id = self.curr_variant().and_then(|curr| {
+            self.variants_for_curr_track_in(&pathway_id)
+                .into_iter()
+                .find(|v| match (v.stable_id(), curr.stable_id()) {
+                    (Some(stable_id), Some(curr_stable_id)) => stable_id == curr_stable_id,
+                    _ => v.bandwidth() == curr.bandwidth(),
+                })
+                .map(|v| v.id())
+        });
+        self.curr_pathway_id = pathway_id;
+        let was_trick_play_enabled = self.curr_i_frame_variant_id.take().is_some();
+        match equivalent_variant_id {
+            Some(id) if self.is_variant_locked => {
+                self.update_variant(Some(id));
+            }
+            _ => {
+                self.is_variant_locked = false;
+                self.update_variant(None);
+            }
+        }
+        if was_trick_play_enabled {
+            self.enable_trick_play();
+        }
+
+        let mut updates = vec![];
+        if self.curr_audio_id != prev_audio_id {
+            updates.push(MediaType::Audio);
+        }
+        if self.curr_video_id != prev_video_id {
+            updates.push(MediaType::Video);
+        }
+        if updates.is_empty() {
+            VariantUpdateResult::Unchanged
+        } else {
+            VariantUpdateResult::EqualOrUnknown(updates)
         }
     }
 
@@ -269,6 +738,21 @@ impl PlaylistStore {
         }
     }
 
+    /// For Low-Latency contents, returns the maximum duration of a partial segment, in seconds,
+    /// in the currently selected audio and video media playlists.
+    ///
+    /// Returns `None` if there's no loaded media playlist or if one of them has no partial
+    /// segments.
+    pub(crate) fn part_target_duration(&self) -> Option<f64> {
+        let media_playlists = self.curr_media_playlists();
+        if media_playlists.is_empty() {
+            return None;
+        }
+        media_playlists.iter().try_fold(0., |acc: f64, p| {
+            p.1.part_target_duration().map(|pt| acc.max(pt))
+        })
+    }
+
     /// Returns the minimum reachable position seen in the last fetched media playlist.
     ///
     /// This function actually defines the minimum position as the maximum of the
@@ -308,6 +792,23 @@ impl PlaylistStore {
         self.playlist.variant(self.curr_variant_id)
     }
 
+    /// Communicate the formats, as RFC 6381 codec strings, read from the initialization segment
+    /// of the current Media Playlist of the given `MediaType`.
+    ///
+    /// They replace the formats of the current variant if those were only guessed, because its
+    /// `CODECS` attribute is missing.
+    pub(crate) fn update_inferred_codecs(&mut self, media_type: MediaType, codecs: &[String]) {
+        let curr_variant_id = self.curr_variant_id;
+        if let Some(variant) = self
+            .playlist
+            .variants_mut()
+            .iter_mut()
+            .find(|v| v.id() == curr_variant_id)
+        {
+            variant.update_inferred_codecs(media_type, codecs);
+        }
+    }
+
     /// Optionally update currently-selected variant by communicating the last bandwidth estimate.
     ///
     /// Returns a vec of `MediaType` corresponding to the MediaPlaylists that have been in
@@ -368,6 +869,66 @@ impl PlaylistStore {
         self.update_variant(None)
     }
 
+    /// Returns `true` if I-frame-only variants, which may be relied on for trick play, are
+    /// available and supported in the current content.
+    pub(crate) fn has_i_frame_variants(&self) -> bool {
+        !self
+            .in_curr_pathway(self.playlist.supported_i_frame_variants())
+            .is_empty()
+    }
+
+    /// Returns `true` if trick play is currently enabled, meaning that the video Media Playlist
+    /// is the one of an I-frame-only variant.
+    pub(crate) fn is_trick_play_enabled(&self) -> bool {
+        self.curr_i_frame_variant_id.is_some()
+    }
+
+    /// Enable trick play by selecting for video the Media Playlist of the I-frame-only variant
+    /// the most adapted to the last communicated bandwidth.
+    ///
+    /// Returns `false` if no video is currently played or if no supported I-frame-only variant
+    /// exists, in which case trick play stays disabled. Returns `true` if trick play is (or
+    /// already was) enabled.
+    pub(crate) fn enable_trick_play(&mut self) -> bool {
+        if self.curr_i_frame_variant_id.is_some() {
+            return true;
+        }
+        if self.curr_video_id.is_none() {
+            return false;
+        }
+        let i_frame_variants = self.in_curr_pathway(self.playlist.supported_i_frame_variants());
+        let variant_id = best_variant_id(i_frame_variants.iter().copied(), self.last_bandwidth)
+            .or_else(|| fallback_variant_id(i_frame_variants.into_iter()));
+        match variant_id.and_then(|id| self.playlist.i_frame_variant(id)) {
+            Some(variant) => {
+                Logger::info(&format!(
+                    "PS: Enabling trick play with I-frame variant {}",
+                    variant.id()
+                ));
+                self.curr_video_id = Some(self.playlist.i_frame_media_playlist_id_for(variant));
+                self.curr_i_frame_variant_id = Some(variant.id());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Disable trick play previously enabled through `enable_trick_play`, selecting back the
+    /// video Media Playlist of the current variant.
+    ///
+    /// Returns `true` if trick play was enabled before that call.
+    pub(crate) fn disable_trick_play(&mut self) -> bool {
+        if self.curr_i_frame_variant_id.take().is_none() {
+            return false;
+        }
+        Logger::info("PS: Disabling trick play");
+        self.curr_video_id = self.curr_variant().and_then(|v| {
+            self.playlist
+                .video_media_playlist_id_for(v, self.curr_video_track)
+        });
+        true
+    }
+
     /// Returns `true` if a variant is currently locked, preventing adaptive streaming
     /// from choosing the more adapted one. Such lock can be enabled through the
     /// lock_variant` method.
@@ -390,6 +951,124 @@ impl PlaylistStore {
         self.playlist.media_playlist_url(wanted_id)
     }
 
+    /// Returns the MediaPlaylist identified by `wanted_id`, if it has been loaded.
+    pub(crate) fn media_playlist(
+        &self,
+        wanted_id: &MediaPlaylistPermanentId,
+    ) -> Option<&MediaPlaylist> {
+        self.playlist.media_playlist(wanted_id)
+    }
+
+    /// Returns the URL at which the MediaPlaylist identified by `wanted_id` should be refreshed.
+    ///
+    /// `blocking_directives` should be set to the media sequence number and optional partial
+    /// segment index awaited when performing a Blocking Playlist Reload, in which case the
+    /// corresponding `_HLS_msn` and `_HLS_part` Delivery Directives are added to it.
+    /// The `_HLS_skip` Delivery Directive is also added if the server supports Playlist Delta
+    /// Updates and if the MediaPlaylist was last updated less than half its Skip Boundary ago,
+    /// as asked by the HLS specification. It is set to `v2` when the server can also skip
+    /// `EXT-X-DATERANGE` tags.
+    ///
+    /// Returns `None` if the URL of the MediaPlaylist is not known.
+    pub(crate) fn media_playlist_refresh_url(
+        &self,
+        wanted_id: &MediaPlaylistPermanentId,
+        blocking_directives: Option<(u64, Option<u32>)>,
+    ) -> Option<Url> {
+        let mut url = self.playlist.media_playlist_url(wanted_id)?.clone();
+        if let Some((media_sequence, part_index)) = blocking_directives {
+            url.add_query_parameter("_HLS_msn", &media_sequence.to_string());
+            if let Some(part_index) = part_index {
+                url.add_query_parameter("_HLS_part", &part_index.to_string());
+            }
+        }
+        let server_control = self
+            .playlist
+            .media_playlist(wanted_id)
+            .map(|p| p.server_control());
+        let skip_boundary = server_control.and_then(|s| s.can_skip_until());
+        let last_update = self
+            .media_playlist_update_times
+            .iter()
+            .find(|(id, _)| id == wanted_id)
+            .map(|(_, time)| *time);
+        let can_skip = match (skip_boundary, last_update) {
+            (Some(skip_boundary), Some(last_update)) => {
+                jsGetMonotonicTime() - last_update < skip_boundary * 500.
+            }
+            _ => false,
+        };
+        if can_skip {
+            // Also skip `EXT-X-DATERANGE` tags if the server allows it
+            let skip_value = if server_control.is_some_and(|s| s.can_skip_dateranges()) {
+                "v2"
+            } else {
+                "YES"
+            };
+            url.add_query_parameter("_HLS_skip", skip_value);
+        }
+        Some(url)
+    }
+
+    /// Returns the date ranges announced by the currently selected audio and video media
+    /// playlists.
+    ///
+    /// Date ranges announced by multiple media playlists are only returned once, based on their
+    /// `ID`.
+    pub(crate) fn curr_date_ranges(&self) -> Vec<&DateRange> {
+        let mut ret: Vec<&DateRange> = vec![];
+        self.curr_media_playlists().into_iter().for_each(|(_, pl)| {
+            pl.date_ranges().iter().for_each(|date_range| {
+                if !ret.iter().any(|d| d.id() == date_range.id()) {
+                    ret.push(date_range);
+                }
+            });
+        });
+        ret
+    }
+
+    /// Returns the HLS Interstitials announced by the currently selected audio and video media
+    /// playlists, in chronological order.
+    ///
+    /// Interstitials announced by multiple media playlists are only returned once, based on their
+    /// `ID`.
+    pub(crate) fn curr_interstitials(&self) -> Vec<Interstitial> {
+        let mut ret: Vec<Interstitial> = vec![];
+        self.curr_media_playlists().into_iter().for_each(|(_, pl)| {
+            pl.date_ranges().iter().for_each(|date_range| {
+                if !ret.iter().any(|i| i.id() == date_range.id()) {
+                    if let Some(interstitial) = Interstitial::from_date_range(date_range, pl.url())
+                    {
+                        ret.push(interstitial);
+                    }
+                }
+            });
+        });
+        ret.sort_by(|a, b| a.start().partial_cmp(&b.start()).unwrap_or(Ordering::Equal));
+        ret
+    }
+
+    /// Returns the media segment boundary (the start or end of a media segment) of the current
+    /// Media Playlists the closest to the given playlist position, in seconds.
+    ///
+    /// The video Media Playlist is preferred when there's one.
+    /// Returns `None` if no media segment is known.
+    pub(crate) fn closest_segment_boundary(&self, position: f64) -> Option<f64> {
+        let media_playlist = self
+            .curr_media_playlist(MediaType::Video)
+            .or(self.curr_media_playlist(MediaType::Audio))?;
+        media_playlist
+            .segment_list()
+            .media()
+            .iter()
+            .flat_map(|s| [s.start(), s.end()])
+            .min_by(|a, b| {
+                f64::abs(a - position)
+                    .partial_cmp(&f64::abs(b - position))
+                    .unwrap_or(Ordering::Equal)
+            })
+    }
+
     /// Returns the `MediaPlaylistPermanentId` of the MediaPlaylist linked to the media
     /// of the given `MediaType`.
     ///
@@ -428,11 +1107,12 @@ impl PlaylistStore {
             MediaType::Audio => &self.curr_audio_id,
         } {
             self.playlist.media_playlist(wanted_id).map(|m| {
-                let score: f64 = self
-                    .playlist
-                    .variant(self.curr_variant_id)
-                    .map(|v| v.score().unwrap_or(v.bandwidth() as f64))
-                    .unwrap();
+                let score: f64 = match (media_type, self.curr_i_frame_variant_id) {
+                    (MediaType::Video, Some(id)) => self.playlist.i_frame_variant(id),
+                    _ => self.playlist.variant(self.curr_variant_id),
+                }
+                .map(|v| v.score().unwrap_or(v.bandwidth() as f64))
+                .unwrap();
 
                 let context = SegmentQualityContext::new(score, wanted_id.as_u32());
                 (m.segment_list(), context)
@@ -442,6 +1122,52 @@ impl PlaylistStore {
         }
     }
 
+    /// Returns the `MediaPlaylistPermanentId` of the subtitles MediaPlaylist currently chosen.
+    ///
+    /// Returns `None` if there's no choosen subtitles MediaPlaylist.
+    pub(crate) fn curr_text_media_playlist_id(&self) -> Option<&MediaPlaylistPermanentId> {
+        self.curr_text_id.as_ref()
+    }
+
+    /// Returns a reference to the subtitles MediaPlaylist currently chosen.
+    ///
+    /// Returns `None` either if there's no subtitles MediaPlaylist selected or if it is not yet
+    /// loaded.
+    pub(crate) fn curr_text_media_playlist(&self) -> Option<&MediaPlaylist> {
+        self.playlist.media_playlist(self.curr_text_id.as_ref()?)
+    }
+
+    /// Returns the lowest discontinuity sequence number found in the Media Playlists currently
+    /// chosen and loaded, below which no segment can be loaded anymore.
+    ///
+    /// Returns `None` if none of them is loaded.
+    pub(crate) fn curr_min_discontinuity_sequence(&self) -> Option<u64> {
+        [
+            self.curr_media_playlist(MediaType::Video),
+            self.curr_media_playlist(MediaType::Audio),
+            self.curr_text_media_playlist(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|p| p.discontinuity_sequence())
+        .min()
+    }
+
+    /// Equivalent of `curr_media_playlist_segment_info` for the subtitles MediaPlaylist currently
+    /// chosen.
+    pub(crate) fn curr_text_media_playlist_segment_info(
+        &self,
+    ) -> Option<(&SegmentList, SegmentQualityContext)> {
+        let wanted_id = self.curr_text_id.as_ref()?;
+        let media_playlist = self.playlist.media_playlist(wanted_id)?;
+        let score = self
+            .curr_variant()
+            .map(|v| v.score().unwrap_or(v.bandwidth() as f64))
+            .unwrap_or(0.);
+        let context = SegmentQualityContext::new(score, wanted_id.as_u32());
+        Some((media_playlist.segment_list(), context))
+    }
+
     pub(crate) fn playlist_type(&self) -> PlaylistNature {
         let media_playlists = self.curr_media_playlists();
         media_playlists
@@ -486,7 +1212,15 @@ impl PlaylistStore {
                 }
             });
             if let Some(min_duration) = min_duration {
-                (min_duration - 10.).max(0.)
+                let live_delay = media_playlists
+                    .iter()
+                    .try_fold(0., |acc: f64, p| p.1.hold_back().map(|hb| acc.max(hb)))
+                    .unwrap_or_else(|| {
+                        // For Low-Latency contents, we can play much closer to the live edge, at
+                        // three partial segments from it
+                        self.part_target_duration().map(|pt| pt * 3.).unwrap_or(10.)
+                    });
+                (min_duration - live_delay).max(0.)
             } else {
                 0.
             }
@@ -522,17 +1256,84 @@ impl PlaylistStore {
         self.playlist.audio_tracks()
     }
 
+    /// Returns the `id` of the `VideoTrack` object which is associated to the video media of the
+    /// current variant.
+    ///
+    /// Unlike `curr_audio_track_id`, it is not impacted by trick play, which replaces that video
+    /// media by the one of an I-frame-only variant.
+    ///
+    /// Returns `None` if the current variant has no video or if no `VideoTrack` is linked to it.
+    pub(crate) fn curr_video_track_id(&self) -> Option<u32> {
+        let variant = self.curr_variant()?;
+        let video_id = self
+            .playlist
+            .video_media_playlist_id_for(variant, self.curr_video_track)?;
+        self.playlist
+            .video_track_for_media_id(&video_id)
+            .map(|t| t.id())
+    }
+
+    /// Returns the `id` of the `VideoTrack` object explicitely selected through the
+    /// `set_video_track` API.
+    ///
+    /// Returns `None` if no video track is currently selected.
+    pub(crate) fn selected_video_track_id(&self) -> Option<u32> {
+        self.curr_video_track
+    }
+
+    /// Returns the list of available video tracks on the current content
+    pub(crate) fn video_tracks(&self) -> &[VideoTrack] {
+        self.playlist.video_tracks()
+    }
+
+    /// Returns the `id` of the `TextTrack` object explicitely selected through the
+    /// `set_text_track` API.
+    ///
+    /// Returns `None` if text tracks are disabled.
+    pub(crate) fn selected_text_track_id(&self) -> Option<u32> {
+        self.curr_text_track
+    }
+
+    /// Returns the list of available text tracks on the current content
+    pub(crate) fn text_tracks(&self) -> &[TextTrack] {
+        self.playlist.text_tracks()
+    }
+
+    /// Returns the closed captions channel, carried in the current variant's video, which should
+    /// be displayed for the current text track choice.
+    ///
+    /// Returns `None` if the chosen text track, if any, is not a closed captions track of the
+    /// current variant.
+    pub(crate) fn curr_closed_captions_channel(&self) -> Option<InstreamId> {
+        let variant = self.curr_variant()?;
+        self.playlist
+            .closed_captions_channel_for(variant, self.curr_text_track?)
+    }
+
+    /// Returns `true` if the current variant announces closed captions in its video.
+    pub(crate) fn has_closed_captions(&self) -> bool {
+        self.curr_variant()
+            .is_some_and(|v| self.playlist.has_closed_captions(v))
+    }
+
+    /// Returns the data announced through `EXT-X-SESSION-DATA` tags in the Multivariant Playlist.
+    pub(crate) fn session_data(&self) -> &[SessionData] {
+        self.playlist.session_data()
+    }
+
     /// Explicitely select an `AudioTrack` based on its `id` property or disable the explicit
     /// selection of one (by giving `None` as argument).
     ///
     /// Returns `true` if this call led to a changement for the Audio Media Playlist.
-    pub(crate) fn set_audio_track(&mut self, track_id: Option<u32>) -> SetAudioTrackResponse {
+    pub(crate) fn set_audio_track(&mut self, track_id: Option<u32>) -> SetTrackResponse {
         self.curr_audio_track = track_id;
 
         if let Some(variant) = self.curr_variant() {
-            let new_audio_id = self
-                .playlist
-                .audio_media_playlist_id_for(variant, self.curr_audio_track);
+            let new_audio_id = self.playlist.audio_media_playlist_id_for(
+                variant,
+                self.curr_audio_track,
+                self.audio_preferences,
+            );
 
             if new_audio_id.is_none() && self.curr_audio_id.is_some() {
                 // We may be in a case where the choosen track is not available in the
@@ -540,21 +1341,201 @@ impl PlaylistStore {
                 let old_variant_locked = self.is_variant_locked;
                 self.is_variant_locked = false;
                 let variant_update = self.update_variant(None);
-                SetAudioTrackResponse::VariantUpdate {
+                SetTrackResponse::VariantUpdate {
                     updates: variant_update,
                     unlocked_variant: old_variant_locked,
                 }
             } else if new_audio_id != self.curr_audio_id {
                 self.curr_audio_id = new_audio_id;
-                SetAudioTrackResponse::AudioMediaUpdate
+                SetTrackResponse::MediaUpdate
             } else {
-                SetAudioTrackResponse::NoUpdate
+                SetTrackResponse::NoUpdate
+            }
+        } else {
+            SetTrackResponse::NoUpdate
+        }
+    }
+
+    /// Update the preferences and capabilities considered when no audio track is explicitely
+    /// selected, such as the preference for audio renditions with accessibility characteristics
+    /// or the maximum number of audio channels that can be output.
+    ///
+    /// Like for `set_audio_track`, the current variant is updated if it is not compatible with
+    /// those new preferences.
+    pub(crate) fn set_audio_selection_preferences(
+        &mut self,
+        preferences: AudioSelectionPreferences,
+    ) -> SetTrackResponse {
+        self.audio_preferences = preferences;
+        let Some(variant) = self.curr_variant() else {
+            return SetTrackResponse::NoUpdate;
+        };
+        let new_audio_id =
+            self.playlist
+                .audio_media_playlist_id_for(variant, self.curr_audio_track, preferences);
+        let has_audio_changed = new_audio_id != self.curr_audio_id;
+        self.curr_audio_id = new_audio_id;
+
+        let is_curr_variant_compatible = self
+            .variants_for_curr_track()
+            .iter()
+            .any(|v| v.id() == self.curr_variant_id);
+        if !is_curr_variant_compatible {
+            let old_variant_locked = self.is_variant_locked;
+            self.is_variant_locked = false;
+            let variant_update = self.update_variant(None);
+            SetTrackResponse::VariantUpdate {
+                updates: variant_update,
+                unlocked_variant: old_variant_locked,
             }
+        } else if has_audio_changed {
+            SetTrackResponse::MediaUpdate
         } else {
-            SetAudioTrackResponse::NoUpdate
+            SetTrackResponse::NoUpdate
         }
     }
 
+    /// Update the output protection the device is able to enforce, which restricts the variants
+    /// that can be played.
+    ///
+    /// Like for `set_audio_track`, the current variant is updated if it cannot be presented
+    /// anymore, even if it was locked.
+    ///
+    /// Returns an error if codecs have all been checked and no variant can be played anymore.
+    pub(crate) fn set_output_protection(
+        &mut self,
+        capabilities: &OutputProtectionCapabilities,
+    ) -> Result<SetTrackResponse, PlaylistStoreError> {
+        self.playlist.update_output_protection(capabilities);
+        self.ensure_playable_variant()
+    }
+
+    /// Update the video dynamic ranges supported by the display, which restrict the variants
+    /// that can be played.
+    ///
+    /// Like for `set_output_protection`, the current variant is updated if it cannot be played
+    /// anymore and an error is returned if no variant can be played.
+    pub(crate) fn set_supported_video_ranges(
+        &mut self,
+        supported_video_ranges: Vec<VideoDynamicRange>,
+    ) -> Result<SetTrackResponse, PlaylistStoreError> {
+        self.supported_video_ranges = supported_video_ranges;
+        if !self.codecs_checked {
+            // Video ranges will be checked at the same time than codecs
+            return Ok(SetTrackResponse::NoUpdate);
+        }
+        self.playlist
+            .update_video_range_support(&self.supported_video_ranges);
+        self.ensure_playable_variant()
+    }
+
+    /// Switch to another variant if the current one cannot be played anymore, e.g. due to a
+    /// change of the device's capabilities.
+    ///
+    /// Explicitely-selected tracks are unselected if none of their variants can be played anymore.
+    ///
+    /// Returns an error if codecs have all been checked and no variant can be played.
+    fn ensure_playable_variant(&mut self) -> Result<SetTrackResponse, PlaylistStoreError> {
+        if !self.codecs_checked {
+            // The current variant will be re-checked once codecs are
+            return Ok(SetTrackResponse::NoUpdate);
+        }
+        if self.supported_variants().is_empty() {
+            Logger::error("PS: No variant can be played with the current capabilities");
+            return Err(PlaylistStoreError::NoSupportedVariant);
+        }
+        let is_curr_variant_playable = self.curr_variant().is_some_and(|v| v.is_playable());
+        if is_curr_variant_playable {
+            return Ok(SetTrackResponse::NoUpdate);
+        }
+        if self.variants_for_curr_track().is_empty() {
+            // None of the variants of the chosen tracks can be played anymore
+            self.curr_audio_track = None;
+            self.curr_video_track = None;
+        }
+        let old_variant_locked = self.is_variant_locked;
+        self.is_variant_locked = false;
+        let variant_update = if self.variants_for_curr_track().is_empty() {
+            // The audio rendition currently relied on is only linked to unplayable variants
+            let variant_id = self.supported_variants()[0].id();
+            self.update_variant(Some(variant_id))
+        } else {
+            self.update_variant(None)
+        };
+        Ok(SetTrackResponse::VariantUpdate {
+            updates: variant_update,
+            unlocked_variant: old_variant_locked,
+        })
+    }
+
+    /// Explicitely select a `VideoTrack` based on its `id` property or disable the explicit
+    /// selection of one (by giving `None` as argument).
+    ///
+    /// Like for audio tracks, the current variant is updated if it is not linked to the chosen
+    /// video track.
+    pub(crate) fn set_video_track(&mut self, track_id: Option<u32>) -> SetTrackResponse {
+        self.curr_video_track = track_id;
+
+        let is_curr_variant_compatible = self
+            .variants_for_curr_track()
+            .iter()
+            .any(|v| v.id() == self.curr_variant_id);
+        if !is_curr_variant_compatible {
+            // The choosen track is not available in the current variant, re-check the best
+            // variant to have with the new track.
+            let old_variant_locked = self.is_variant_locked;
+            self.is_variant_locked = false;
+            let variant_update = self.update_variant(None);
+            return SetTrackResponse::VariantUpdate {
+                updates: variant_update,
+                unlocked_variant: old_variant_locked,
+            };
+        }
+        if self.curr_i_frame_variant_id.is_some() {
+            // The video track will be considered once trick play is disabled
+            return SetTrackResponse::NoUpdate;
+        }
+        let new_video_id = self.curr_variant().and_then(|v| {
+            self.playlist
+                .video_media_playlist_id_for(v, self.curr_video_track)
+        });
+        if new_video_id != self.curr_video_id {
+            self.curr_video_id = new_video_id;
+            SetTrackResponse::MediaUpdate
+        } else {
+            SetTrackResponse::NoUpdate
+        }
+    }
+
+    /// Explicitely select a `TextTrack` based on its `id` property or disable text tracks (by
+    /// giving `None` as argument).
+    ///
+    /// Unlike audio tracks, the current variant is never updated due to a text track choice: if
+    /// the current variant is not linked to the chosen text track, no subtitles are loaded until
+    /// a compatible variant is chosen.
+    ///
+    /// Returns `true` if this call led to a changement for the subtitles Media Playlist or for
+    /// the closed captions channel to display.
+    pub(crate) fn set_text_track(&mut self, track_id: Option<u32>) -> bool {
+        let prev_closed_captions_channel = self.curr_closed_captions_channel();
+        self.curr_text_track = track_id;
+        let new_text_id = self.text_media_playlist_id();
+        if new_text_id != self.curr_text_id {
+            self.curr_text_id = new_text_id;
+            true
+        } else {
+            self.curr_closed_captions_channel() != prev_closed_captions_channel
+        }
+    }
+
+    /// Returns the `MediaPlaylistPermanentId` of the subtitles MediaPlaylist that should be loaded
+    /// for the current variant and text track choice.
+    fn text_media_playlist_id(&self) -> Option<MediaPlaylistPermanentId> {
+        let variant = self.curr_variant()?;
+        self.playlist
+            .text_media_playlist_id_for(variant, self.curr_text_track?)
+    }
+
     /// Select the best variant available according to your bandwidth and track choice
     fn update_variant(&mut self, variant_id: Option<u32>) -> VariantUpdateResult {
         let new_id = if let Some(id) = variant_id {
@@ -604,13 +1585,34 @@ impl PlaylistStore {
     fn set_curr_variant_and_media_id(&mut self, variant_id: u32) {
         let variant = self.playlist.variant(variant_id).unwrap();
         self.curr_variant_id = variant_id;
-        self.curr_video_id = self.playlist.video_media_playlist_id_for(variant);
-        self.curr_audio_id = self
-            .playlist
-            .audio_media_playlist_id_for(variant, self.curr_audio_track);
+        if self.curr_i_frame_variant_id.is_none() {
+            self.curr_video_id = self
+                .playlist
+                .video_media_playlist_id_for(variant, self.curr_video_track);
+        }
+        self.curr_audio_id = self.playlist.audio_media_playlist_id_for(
+            variant,
+            self.curr_audio_track,
+            self.audio_preferences,
+        );
+        self.curr_text_id = self.text_media_playlist_id();
     }
 }
 
+/// Returns the Content Steering Pathway whose variants should initially be relied on in
+/// `playlist`: the one indicated by its `EXT-X-CONTENT-STEERING` tag if it has variants, or the
+/// one of its first variant otherwise.
+fn initial_pathway_id(playlist: &MultivariantPlaylist) -> String {
+    let variants = playlist.all_variants();
+    playlist
+        .content_steering()
+        .and_then(|c| c.pathway_id())
+        .filter(|p| variants.iter().any(|v| v.pathway_id() == *p))
+        .or_else(|| variants.first().map(|v| v.pathway_id()))
+        .unwrap_or(".")
+        .to_owned()
+}
+
 /// From a `DoubleEndedIterator` of references to `VariantStream`s ordered first by `score` then
 /// `bandwidth` ascending, find the best `VariantStream` which is compatible with the given
 /// bandwidth and returns its `id` property.
@@ -674,19 +1676,20 @@ pub enum VariantUpdateResult {
     EqualOrUnknown(Vec<MediaType>),
 }
 
-/// Result of calling the `set_audio_track` `PlaylistStore`'s method
+/// Result of calling the `set_audio_track` or `set_video_track` `PlaylistStore`'s methods, or
+/// of other methods which may similarly lead to a variant change such as `set_output_protection`.
 #[allow(clippy::enum_variant_names)]
-pub(crate) enum SetAudioTrackResponse {
-    /// The audio track change led to a change of the Media Playlist for the audio.
+pub(crate) enum SetTrackResponse {
+    /// The track change led to a change of the Media Playlist for the corresponding media type.
     ///
-    /// Because variants may be or not be linked to a given audio track it is also possible that
-    /// the list of currently adaptively switchable variants has changed.
-    AudioMediaUpdate,
+    /// Because variants may be or not be linked to a given track it is also possible that the
+    /// list of currently adaptively switchable variants has changed.
+    MediaUpdate,
 
-    /// The audio track change led to a change for the currently-chosen variant due to the previous
-    /// one not being compatible with the new chosen audio track.
+    /// The track change led to a change for the currently-chosen variant due to the previous
+    /// one not being compatible with the new chosen track.
     ///
-    /// Because variants may be or not be linked to a given audio track it is also possible that the
+    /// Because variants may be or not be linked to a given track it is also possible that the
     /// list of currently adaptively switchable variants has changed.
     ///
     /// The `updates` element of the associated struct is the result of such update, the
@@ -700,8 +1703,8 @@ pub(crate) enum SetAudioTrackResponse {
 
     /// No Media Playlist nor the current variant were changed due to this track change.
     ///
-    /// Because variants may be or not be linked to a given audio track it is however possible that
-    /// the list of currently adaptively switchable variants has changed.
+    /// Because variants may be or not be linked to a given track it is however possible that the
+    /// list of currently adaptively switchable variants has changed.
     NoUpdate,
 }
 
@@ -723,3 +1726,486 @@ pub(crate) enum PlaylistStoreError {
     #[error("No variant was found in the MultivariantPlaylist. Are you sure that this isn't a Media Playlist?")]
     NoInitialVariant,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bindings, parser::HdcpLevel, utils::logger::LoggerLevel};
+
+    /// Create a `PlaylistStore` whose codecs still have to be checked.
+    fn new_store(
+        playlist: &str,
+        initial_bandwidth: f64,
+        supported_video_ranges: Vec<VideoDynamicRange>,
+    ) -> PlaylistStore {
+        Logger::set_logger_level(LoggerLevel::None);
+        let playlist = MultivariantPlaylist::parse(
+            playlist.as_bytes(),
+            Url::new("https://example.com/main.m3u8".to_owned()),
+        )
+        .unwrap();
+        PlaylistStore::try_new(
+            playlist,
+            initial_bandwidth,
+            AudioSelectionPreferences::default(),
+            &OutputProtectionCapabilities::default(),
+            supported_video_ranges,
+        )
+        .unwrap()
+    }
+
+    /// Create a `PlaylistStore` whose codecs are all supported.
+    fn create_store(playlist: &str, initial_bandwidth: f64) -> PlaylistStore {
+        bindings::set_type_support(|_, _| Some(true));
+        let mut store = new_store(playlist, initial_bandwidth, vec![VideoDynamicRange::Sdr]);
+        assert!(store.check_codecs().unwrap());
+        store
+    }
+
+    fn audio_track_id(store: &PlaylistStore, name: &str) -> u32 {
+        store
+            .audio_tracks()
+            .iter()
+            .find(|t| t.name() == name)
+            .unwrap()
+            .id()
+    }
+
+    fn variant_bandwidth(store: &PlaylistStore) -> u64 {
+        store.curr_variant().unwrap().bandwidth()
+    }
+
+    const PROTECTED_AUDIO_PLAYLIST: &str = "#EXTM3U
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"main\",NAME=\"English\",DEFAULT=YES,URI=\"en.m3u8\"
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"protected\",NAME=\"Commentary\",DEFAULT=YES,URI=\"com.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,AUDIO=\"main\",HDCP-LEVEL=NONE
+low.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,AUDIO=\"protected\",HDCP-LEVEL=TYPE-0
+high.m3u8
+";
+
+    #[test]
+    fn test_output_protection_change_with_unplayable_track() {
+        let mut store = create_store(PROTECTED_AUDIO_PLAYLIST, 1500000.);
+        assert_eq!(variant_bandwidth(&store), 1000000);
+        let commentary_id = audio_track_id(&store, "Commentary");
+        store.set_audio_track(Some(commentary_id));
+        store.lock_variant(store.curr_variant_id);
+        assert_eq!(variant_bandwidth(&store), 2000000);
+
+        // All variants of the chosen track now require an output protection which can't be
+        // enforced: the track has to be unselected
+        let capabilities = OutputProtectionCapabilities {
+            max_hdcp_level: Some(HdcpLevel::None),
+            allowed_cpc: None,
+        };
+        let res = store.set_output_protection(&capabilities).unwrap();
+        assert!(matches!(
+            res,
+            SetTrackResponse::VariantUpdate {
+                unlocked_variant: true,
+                ..
+            }
+        ));
+        assert_eq!(variant_bandwidth(&store), 1000000);
+        assert_eq!(store.selected_audio_track_id(), None);
+        assert_eq!(
+            store.curr_audio_track_id(),
+            Some(audio_track_id(&store, "English"))
+        );
+        assert!(!store.is_variant_locked());
+    }
+
+    #[test]
+    fn test_output_protection_change_with_unplayable_rendition() {
+        // The audio rendition is only chosen by default here
+        let mut store = create_store(PROTECTED_AUDIO_PLAYLIST, 5000000.);
+        assert_eq!(variant_bandwidth(&store), 2000000);
+        assert_eq!(store.selected_audio_track_id(), None);
+
+        let capabilities = OutputProtectionCapabilities {
+            max_hdcp_level: Some(HdcpLevel::None),
+            allowed_cpc: None,
+        };
+        store.set_output_protection(&capabilities).unwrap();
+        assert_eq!(variant_bandwidth(&store), 1000000);
+        assert_eq!(
+            store.curr_audio_track_id(),
+            Some(audio_track_id(&store, "English"))
+        );
+    }
+
+    #[test]
+    fn test_media_playlist_refresh_url() {
+        let mut store = create_store(
+            "#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=1000000
+media.m3u8
+",
+            1000000.,
+        );
+        let id = store.curr_video_id.clone().unwrap();
+        let media_playlist = |server_control: &str| {
+            format!(
+                "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-SERVER-CONTROL:{server_control}
+#EXT-X-MEDIA-SEQUENCE:10
+#EXTINF:4,
+seg10.ts
+"
+            )
+        };
+        let url = Url::new("https://example.com/media.m3u8".to_owned());
+        let refresh_url = |store: &PlaylistStore, directives| {
+            store
+                .media_playlist_refresh_url(&id, directives)
+                .unwrap()
+                .get_ref()
+                .to_owned()
+        };
+
+        bindings::set_monotonic_time(1000.);
+        let data = media_playlist("CAN-SKIP-UNTIL=24");
+        store
+            .update_media_playlist(&id, data.as_bytes(), url.clone())
+            .unwrap();
+        assert_eq!(
+            refresh_url(&store, Some((11, Some(2)))),
+            "https://example.com/media.m3u8?_HLS_msn=11&_HLS_part=2&_HLS_skip=YES"
+        );
+
+        // Delta Updates are not asked for anymore after half the Skip Boundary
+        bindings::set_monotonic_time(13000.);
+        assert_eq!(
+            refresh_url(&store, Some((11, None))),
+            "https://example.com/media.m3u8?_HLS_msn=11"
+        );
+
+        let data = media_playlist("CAN-SKIP-UNTIL=24,CAN-SKIP-DATERANGES=YES");
+        store
+            .update_media_playlist(&id, data.as_bytes(), url)
+            .unwrap();
+        assert_eq!(
+            refresh_url(&store, None),
+            "https://example.com/media.m3u8?_HLS_skip=v2"
+        );
+    }
+
+    const TRICK_PLAY_PLAYLIST: &str = "#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,PATHWAY-ID=\"a\"
+a/low.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,PATHWAY-ID=\"a\"
+a/high.m3u8
+#EXT-X-I-FRAME-STREAM-INF:BANDWIDTH=100000,PATHWAY-ID=\"a\",URI=\"a/iframe-low.m3u8\"
+#EXT-X-I-FRAME-STREAM-INF:BANDWIDTH=300000,PATHWAY-ID=\"a\",URI=\"a/iframe-high.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,PATHWAY-ID=\"b\"
+b/low.m3u8
+#EXT-X-I-FRAME-STREAM-INF:BANDWIDTH=100000,PATHWAY-ID=\"b\",URI=\"b/iframe.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,PATHWAY-ID=\"c\"
+c/low.m3u8
+";
+
+    fn video_url(store: &PlaylistStore) -> &str {
+        let id = store.curr_media_playlist_id(MediaType::Video).unwrap();
+        store.media_playlist_url(id).unwrap().get_ref()
+    }
+
+    #[test]
+    fn test_trick_play() {
+        let mut store = create_store(TRICK_PLAY_PLAYLIST, 5000000.);
+        assert!(store.has_i_frame_variants());
+        assert_eq!(video_url(&store), "https://example.com/a/high.m3u8");
+
+        // Without bandwidth estimate, the lowest I-frame variant is chosen
+        assert!(store.enable_trick_play());
+        assert_eq!(video_url(&store), "https://example.com/a/iframe-low.m3u8");
+        assert!(store.disable_trick_play());
+        store.update_curr_bandwidth(5000000.);
+
+        assert!(store.enable_trick_play());
+        assert!(store.is_trick_play_enabled());
+        assert_eq!(video_url(&store), "https://example.com/a/iframe-high.m3u8");
+        // Enabling it again keeps the same I-frame variant
+        assert!(store.enable_trick_play());
+        assert_eq!(video_url(&store), "https://example.com/a/iframe-high.m3u8");
+
+        assert!(store.disable_trick_play());
+        assert!(!store.is_trick_play_enabled());
+        assert_eq!(video_url(&store), "https://example.com/a/high.m3u8");
+        assert!(!store.disable_trick_play());
+        assert_eq!(video_url(&store), "https://example.com/a/high.m3u8");
+    }
+
+    #[test]
+    fn test_trick_play_pathway_switch() {
+        let mut store = create_store(TRICK_PLAY_PLAYLIST, 5000000.);
+        store.update_curr_bandwidth(5000000.);
+        assert!(store.enable_trick_play());
+        assert_eq!(video_url(&store), "https://example.com/a/iframe-high.m3u8");
+
+        // Trick play stays enabled with an I-frame variant from the new pathway
+        store.switch_pathway("b".to_owned());
+        assert!(store.is_trick_play_enabled());
+        assert_eq!(video_url(&store), "https://example.com/b/iframe.m3u8");
+        assert!(store.disable_trick_play());
+        assert_eq!(video_url(&store), "https://example.com/b/low.m3u8");
+
+        // It is disabled if the new pathway has no I-frame variant
+        assert!(store.enable_trick_play());
+        store.switch_pathway("c".to_owned());
+        assert!(!store.has_i_frame_variants());
+        assert!(!store.is_trick_play_enabled());
+        assert_eq!(video_url(&store), "https://example.com/c/low.m3u8");
+        assert!(!store.enable_trick_play());
+        assert_eq!(video_url(&store), "https://example.com/c/low.m3u8");
+    }
+
+    #[test]
+    fn test_set_video_track() {
+        let mut store = create_store(
+            "#EXTM3U
+#EXT-X-MEDIA:TYPE=VIDEO,GROUP-ID=\"low\",NAME=\"Main\",DEFAULT=YES,URI=\"main-low.m3u8\"
+#EXT-X-MEDIA:TYPE=VIDEO,GROUP-ID=\"low\",NAME=\"Side\",URI=\"side-low.m3u8\"
+#EXT-X-MEDIA:TYPE=VIDEO,GROUP-ID=\"high\",NAME=\"Main\",DEFAULT=YES,URI=\"main-high.m3u8\"
+#EXT-X-MEDIA:TYPE=VIDEO,GROUP-ID=\"high\",NAME=\"Side\",URI=\"side-high.m3u8\"
+#EXT-X-MEDIA:TYPE=VIDEO,GROUP-ID=\"high\",NAME=\"Aerial\",URI=\"aerial-high.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,VIDEO=\"low\"
+low.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,VIDEO=\"high\"
+high.m3u8
+",
+            1500000.,
+        );
+        let track_id = |store: &PlaylistStore, name: &str| {
+            let track = store.video_tracks().iter().find(|t| t.name() == name);
+            track.unwrap().id()
+        };
+        assert_eq!(variant_bandwidth(&store), 1000000);
+        assert_eq!(video_url(&store), "https://example.com/main-low.m3u8");
+        assert_eq!(store.curr_video_track_id(), Some(track_id(&store, "Main")));
+        store.lock_variant(store.curr_variant_id);
+
+        // The current variant has the chosen track, only its Media Playlist changes
+        let res = store.set_video_track(Some(track_id(&store, "Side")));
+        assert!(matches!(res, SetTrackResponse::MediaUpdate));
+        assert_eq!(video_url(&store), "https://example.com/side-low.m3u8");
+        assert!(store.is_variant_locked());
+
+        // The current variant lacks the chosen track, another one has to be selected
+        let res = store.set_video_track(Some(track_id(&store, "Aerial")));
+        assert!(matches!(
+            res,
+            SetTrackResponse::VariantUpdate {
+                unlocked_variant: true,
+                ..
+            }
+        ));
+        assert!(!store.is_variant_locked());
+        assert_eq!(variant_bandwidth(&store), 2000000);
+        assert_eq!(video_url(&store), "https://example.com/aerial-high.m3u8");
+        assert_eq!(
+            store.selected_video_track_id(),
+            Some(track_id(&store, "Aerial"))
+        );
+        // Only variants with the chosen track are considered, even if too heavy
+        store.update_curr_bandwidth(1500000.);
+        assert_eq!(variant_bandwidth(&store), 2000000);
+
+        // Without selection, the default rendition is chosen back
+        let res = store.set_video_track(None);
+        assert!(matches!(res, SetTrackResponse::MediaUpdate));
+        assert_eq!(video_url(&store), "https://example.com/main-high.m3u8");
+        store.update_curr_bandwidth(1500000.);
+        assert_eq!(variant_bandwidth(&store), 1000000);
+        assert_eq!(video_url(&store), "https://example.com/main-low.m3u8");
+    }
+
+    #[test]
+    fn test_prefer_accessible_audio() {
+        let mut store = create_store(
+            "#EXTM3U
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"English\",DEFAULT=YES,AUTOSELECT=YES,URI=\"en.m3u8\"
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"Commentary\",CHARACTERISTICS=\"public.accessibility.describes-video\",URI=\"com.m3u8\"
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"English AD\",AUTOSELECT=YES,CHARACTERISTICS=\"public.accessibility.describes-video\",URI=\"en-ad.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,AUDIO=\"aud\"
+media.m3u8
+",
+            1000000.,
+        );
+        let audio_url = |store: &PlaylistStore| {
+            let id = store.curr_media_playlist_id(MediaType::Audio).unwrap();
+            store.media_playlist_url(id).unwrap().get_ref().to_owned()
+        };
+        assert_eq!(audio_url(&store), "https://example.com/en.m3u8");
+
+        // Only accessible renditions which can be automatically selected are preferred
+        let preferences = AudioSelectionPreferences {
+            prefer_accessible: true,
+            max_channels: None,
+        };
+        let res = store.set_audio_selection_preferences(preferences);
+        assert!(matches!(res, SetTrackResponse::MediaUpdate));
+        assert_eq!(audio_url(&store), "https://example.com/en-ad.m3u8");
+        assert_eq!(
+            store.curr_audio_track_id(),
+            Some(audio_track_id(&store, "English AD"))
+        );
+        let res = store.set_audio_selection_preferences(preferences);
+        assert!(matches!(res, SetTrackResponse::NoUpdate));
+
+        // An explicitely-selected track is kept whatever the preferences
+        store.set_audio_track(Some(audio_track_id(&store, "English")));
+        assert_eq!(audio_url(&store), "https://example.com/en.m3u8");
+        store.set_audio_track(None);
+        assert_eq!(audio_url(&store), "https://example.com/en-ad.m3u8");
+
+        store.set_audio_selection_preferences(AudioSelectionPreferences::default());
+        assert_eq!(audio_url(&store), "https://example.com/en.m3u8");
+    }
+
+    const SUPPLEMENTAL_CODECS_PLAYLIST: &str = "#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,CODECS=\"avc1.640028\"
+avc.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,CODECS=\"hvc1.2.4.L153.B0\",SUPPLEMENTAL-CODECS=\"dvh1.08.07/db4h\"
+dv.m3u8
+";
+
+    /// Returns the variant announcing supplemental codecs in `SUPPLEMENTAL_CODECS_PLAYLIST`.
+    fn supplemented_variant(store: &PlaylistStore) -> &VariantStream {
+        let variants = store.playlist.all_variants();
+        variants
+            .iter()
+            .find(|v| v.has_supplemental_codecs())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_supported_supplemental_codecs() {
+        bindings::set_type_support(|_, typ| Some(!typ.contains("hvc1")));
+        let mut store = new_store(
+            SUPPLEMENTAL_CODECS_PLAYLIST,
+            5000000.,
+            vec![VideoDynamicRange::Sdr],
+        );
+        assert!(store.check_codecs().unwrap());
+
+        // The variant is played through its supplemental codecs, even if its regular ones aren't
+        // supported
+        let variant = supplemented_variant(&store);
+        assert_eq!(variant.supplemental_codecs_supported(), Some(true));
+        assert!(variant.uses_supplemental_codecs());
+        assert_eq!(variant.codecs(MediaType::Video).unwrap(), "dvh1.08.07");
+        assert!(variant.is_playable());
+        assert_eq!(variant_bandwidth(&store), 2000000);
+    }
+
+    #[test]
+    fn test_unsupported_supplemental_codecs() {
+        bindings::set_type_support(|_, typ| Some(!typ.contains("dvh1")));
+        let mut store = new_store(
+            SUPPLEMENTAL_CODECS_PLAYLIST,
+            5000000.,
+            vec![VideoDynamicRange::Sdr],
+        );
+        assert!(store.check_codecs().unwrap());
+
+        // The regular codecs are relied on instead
+        let variant = supplemented_variant(&store);
+        assert_eq!(variant.supplemental_codecs_supported(), Some(false));
+        assert!(!variant.uses_supplemental_codecs());
+        assert_eq!(
+            variant.codecs(MediaType::Video).unwrap(),
+            "hvc1.2.4.L153.B0"
+        );
+        assert!(variant.is_playable());
+        assert_eq!(variant_bandwidth(&store), 2000000);
+
+        // Without them, only the other variant is left
+        bindings::set_type_support(|_, typ| Some(!typ.contains("dvh1") && !typ.contains("hvc1")));
+        let mut store = new_store(
+            SUPPLEMENTAL_CODECS_PLAYLIST,
+            5000000.,
+            vec![VideoDynamicRange::Sdr],
+        );
+        assert!(store.check_codecs().unwrap());
+        assert!(!supplemented_variant(&store).is_playable());
+        assert_eq!(variant_bandwidth(&store), 1000000);
+    }
+
+    #[test]
+    fn test_pending_supplemental_codecs_check() {
+        bindings::set_type_support(|_, typ| (!typ.contains("dvh1")).then_some(true));
+        let mut store = new_store(
+            SUPPLEMENTAL_CODECS_PLAYLIST,
+            5000000.,
+            vec![VideoDynamicRange::Sdr],
+        );
+        assert!(!store.check_codecs().unwrap());
+        assert!(!store.are_codecs_checked());
+        let variant = supplemented_variant(&store);
+        assert_eq!(variant.supplemental_codecs_supported(), None);
+        assert!(!variant.uses_supplemental_codecs());
+
+        // Once the answer is known, the next check completes
+        bindings::set_type_support(|_, _| Some(true));
+        assert!(store.check_codecs().unwrap());
+        assert!(store.are_codecs_checked());
+        let variant = supplemented_variant(&store);
+        assert!(variant.uses_supplemental_codecs());
+        assert!(variant.is_playable());
+    }
+
+    #[test]
+    fn test_set_supported_video_ranges() {
+        let playlist = "#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,VIDEO-RANGE=SDR
+sdr.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,VIDEO-RANGE=PQ
+pq.m3u8
+";
+        let hdr_ranges = vec![VideoDynamicRange::Sdr, VideoDynamicRange::Pq];
+        bindings::set_type_support(|_, _| Some(true));
+        let mut store = new_store(playlist, 5000000., hdr_ranges.clone());
+        assert!(store.check_codecs().unwrap());
+        store.update_curr_bandwidth(5000000.);
+        assert_eq!(variant_bandwidth(&store), 2000000);
+
+        // The HDR variant cannot be played anymore
+        let res = store.set_supported_video_ranges(vec![VideoDynamicRange::Sdr]);
+        assert!(matches!(res, Ok(SetTrackResponse::VariantUpdate { .. })));
+        assert_eq!(variant_bandwidth(&store), 1000000);
+        assert_eq!(store.supported_variants().len(), 1);
+        store.update_curr_bandwidth(5000000.);
+        assert_eq!(variant_bandwidth(&store), 1000000);
+
+        let res = store.set_supported_video_ranges(hdr_ranges);
+        assert!(matches!(res, Ok(SetTrackResponse::NoUpdate)));
+        store.update_curr_bandwidth(5000000.);
+        assert_eq!(variant_bandwidth(&store), 2000000);
+
+        // No variant is left
+        let res = store.set_supported_video_ranges(vec![VideoDynamicRange::Hlg]);
+        assert!(matches!(res, Err(PlaylistStoreError::NoSupportedVariant)));
+    }
+
+    #[test]
+    fn test_video_ranges_before_codecs_check() {
+        bindings::set_type_support(|_, _| Some(true));
+        let mut store = new_store(
+            "#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=1000000,VIDEO-RANGE=PQ
+pq.m3u8
+",
+            1000000.,
+            vec![VideoDynamicRange::Sdr, VideoDynamicRange::Pq],
+        );
+        // Video ranges are only checked with the codecs
+        let res = store.set_supported_video_ranges(vec![VideoDynamicRange::Sdr]);
+        assert!(matches!(res, Ok(SetTrackResponse::NoUpdate)));
+        assert!(matches!(
+            store.check_codecs(),
+            Err(PlaylistStoreError::NoSupportedVariant)
+        ));
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
         RequestErrorReason, RequestId, TimerId, TimerReason,
     },
     media_element::SegmentQualityContext,
-    parser::{ByteRange, MediaSegmentInfo, SegmentTimeInfo},
+    parser::{ByteRange, MediaSegmentInfo, SegmentKey, SegmentTimeInfo},
     playlist_store::MediaPlaylistPermanentId,
     utils::url::Url,
     Logger,
@@ -16,6 +16,9 @@ pub(crate) use configuration::RequesterConfiguration;
 
 const PRIORITY_STEPS: [f64; 6] = [2., 4., 8., 12., 18., 25.];
 
+/// Maximum number of decryption keys kept in the `Requester`'s cache.
+const MAX_LOADED_KEYS: usize = 20;
+
 /// The `Requester` is the module performing HTTP(s) requests.
 ///
 /// Depending on the nature of the resource and on its configuration, it also has both a
@@ -41,6 +44,26 @@ pub(crate) struct Requester {
     /// `pending_segment_requests` vector) or waiting at the same time.
     segment_waiting_queue: Vec<WaitingSegmentInfo>,
 
+    /// List information on the current decryption key requests performed, by chronological order
+    /// (from the time the request was made).
+    ///
+    /// Segments needing one of those keys to be decrypted wait in the `segment_waiting_queue`
+    /// until it is loaded.
+    pending_key_requests: Vec<KeyRequestInfo>,
+
+    /// List information on the current subtitles segment requests performed, by chronological
+    /// order (from the time the request was made).
+    ///
+    /// Unlike audio and video segments, those requests are never delayed to let more urgent
+    /// requests finish first.
+    pending_text_segment_requests: Vec<TextSegmentRequestInfo>,
+
+    /// Decryption keys already loaded, with the URL they have been requested from, by
+    /// chronological order (from the time the key was loaded).
+    ///
+    /// Only the last `MAX_LOADED_KEYS` keys are kept.
+    loaded_keys: Vec<(Url, [u8; 16])>,
+
     /// Depending the nature of the failure, failed requests might be retried.
     ///
     /// To avoid overloading the server serving those resources, retried requests are actually
@@ -48,9 +71,10 @@ pub(crate) struct Requester {
     /// This variable allows to store and link here a timer's TimerId (which will be communicated
     /// back when the timer has elapsed) to the RequestId.
     ///
-    /// Note that retried segment requests stay in the `pending_segment_requests` vector and retried
-    /// playlist requests stay in the `pending_playlist_requests` vector, even when the request is
-    /// not really pending.
+    /// Note that retried segment requests stay in the `pending_segment_requests` vector, retried
+    /// key requests stay in the `pending_key_requests` vector, retried subtitles segment requests
+    /// stay in the `pending_text_segment_requests` vector and retried playlist requests stay in
+    /// the `pending_playlist_requests` vector, even when the request is not really pending.
     retry_timers: Vec<(TimerId, RequestId)>,
 
     /// If `true`, no new requests will be started (they all will be pushed in
@@ -89,6 +113,17 @@ pub(crate) enum PlaylistFileType {
         id: MediaPlaylistPermanentId,
         media_type: MediaType,
     },
+    /// This is a subtitles Media Playlist with this associated `id`.
+    TextMediaPlaylist { id: MediaPlaylistPermanentId },
+    /// This is the JSON asset list of an HLS Interstitial, listing the Multivariant Playlists of
+    /// the assets to play.
+    AssetList,
+    /// This is data announced through an `EXT-X-SESSION-DATA` tag with an `URI` attribute, with
+    /// the index of that tag among the Multivariant Playlist's session data.
+    SessionData { index: usize },
+    /// This is the JSON Content Steering manifest announced through an `EXT-X-CONTENT-STEERING`
+    /// tag.
+    SteeringManifest,
 }
 
 /// Metadata associated with a pending Playlist (either a Multivariant Playlist or a Media
@@ -112,6 +147,8 @@ pub(crate) struct PlaylistRequestInfo {
     /// In that case, the `request_id` corresponds to the one of the previous request
     /// and should not be relied on.
     pub(crate) is_waiting_for_retry: bool,
+    /// Timeout, in milliseconds, to set on that request and on its retries.
+    timeout: f64,
 }
 
 /// Metadata associated with a pending media segment request.
@@ -130,6 +167,9 @@ pub(crate) struct WaitingSegmentInfo {
 
     /// Information about the quality linked to that segment
     context: SegmentQualityContext,
+
+    /// If set, the segment is encrypted and this is the information needed to decrypt it.
+    key: Option<SegmentKey>,
 }
 
 impl WaitingSegmentInfo {
@@ -163,6 +203,11 @@ impl WaitingSegmentInfo {
     pub(crate) fn context(&self) -> &SegmentQualityContext {
         &self.context
     }
+
+    /// If set, the segment is encrypted and this is the information needed to decrypt it.
+    pub(crate) fn key(&self) -> Option<&SegmentKey> {
+        self.key.as_ref()
+    }
 }
 
 /// Trait unifying the Requester's segment which are either in a pending request or which are
@@ -241,6 +286,13 @@ pub(crate) struct SegmentRequestInfo {
     /// `None` if the segment contains no media data, such as initialization segments
     time_info: Option<SegmentTimeInfo>,
 
+    /// If set, the segment is encrypted and this is the information needed to decrypt it.
+    key: Option<SegmentKey>,
+
+    /// If the segment has to be decrypted by us, the key's value as loaded when the request was
+    /// started.
+    key_data: Option<[u8; 16]>,
+
     /// Number of time the request has already been attempted.
     attempts_failed: u32,
 
@@ -281,6 +333,15 @@ impl SegmentRequestInfo {
         &self.context
     }
 
+    pub(crate) fn key(&self) -> Option<&SegmentKey> {
+        self.key.as_ref()
+    }
+
+    /// Value of the key needed to decrypt that segment, if it has to be decrypted by us.
+    pub(crate) fn key_data(&self) -> Option<&[u8; 16]> {
+        self.key_data.as_ref()
+    }
+
     pub(crate) fn deconstruct(
         self,
     ) -> (
@@ -293,11 +354,104 @@ impl SegmentRequestInfo {
     }
 }
 
+/// Metadata associated with a pending decryption key request.
+pub(crate) struct KeyRequestInfo {
+    /// ID identifying the request on the JavaScript-side.
+    request_id: RequestId,
+
+    /// Url on which the request is done
+    url: Url,
+
+    /// type of media of the first segment which needed that key.
+    media_type: MediaType,
+
+    /// Number of time the request has already been attempted.
+    attempts_failed: u32,
+
+    /// If `true` the request is not really pending, we're currently pending for some
+    /// timer to finish before retrying it.
+    ///
+    /// In that case, the `request_id` corresponds to the one of the previous request
+    /// and should not be relied on.
+    is_waiting_for_retry: bool,
+}
+
+impl KeyRequestInfo {
+    pub(crate) fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub(crate) fn media_type(&self) -> MediaType {
+        self.media_type
+    }
+
+    pub(crate) fn attempts_failed(&self) -> u32 {
+        self.attempts_failed
+    }
+
+    pub(crate) fn is_waiting_for_retry(&self) -> bool {
+        self.is_waiting_for_retry
+    }
+}
+
+/// Metadata associated with a pending subtitles segment request.
+pub(crate) struct TextSegmentRequestInfo {
+    /// ID identifying the request on the JavaScript-side.
+    request_id: RequestId,
+
+    /// Url on which the request is done
+    url: Url,
+
+    byte_range: Option<ByteRange>,
+
+    /// Start and end of the requested segment.
+    /// `None` if the segment contains no media data, such as initialization segments
+    time_info: Option<SegmentTimeInfo>,
+
+    /// Information about the Media Playlist linked to that segment
+    context: SegmentQualityContext,
+
+    /// Number of time the request has already been attempted.
+    attempts_failed: u32,
+
+    /// If `true` the request is not really pending, we're currently pending for some
+    /// timer to finish before retrying it.
+    ///
+    /// In that case, the `request_id` corresponds to the one of the previous request
+    /// and should not be relied on.
+    is_waiting_for_retry: bool,
+}
+
+impl TextSegmentRequestInfo {
+    pub(crate) fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub(crate) fn time_info(&self) -> Option<&SegmentTimeInfo> {
+        self.time_info.as_ref()
+    }
+
+    pub(crate) fn context(&self) -> &SegmentQualityContext {
+        &self.context
+    }
+
+    pub(crate) fn attempts_failed(&self) -> u32 {
+        self.attempts_failed
+    }
+
+    pub(crate) fn is_waiting_for_retry(&self) -> bool {
+        self.is_waiting_for_retry
+    }
+}
+
 pub(crate) enum FinishedRequestType {
     Playlist(PlaylistRequestInfo),
     Segment(SegmentRequestInfo),
+    Key(KeyRequestInfo),
+    TextSegment(TextSegmentRequestInfo),
 }
 
+#[allow(clippy::large_enum_variant)]
 pub(crate) enum RetryResult<'a> {
     NotFound,
     RetriedPlaylist {
@@ -310,6 +464,16 @@ pub(crate) enum RetryResult<'a> {
         reason: RequestErrorReason,
         status: Option<u32>,
     },
+    RetriedKey {
+        request_info: &'a KeyRequestInfo,
+        reason: RequestErrorReason,
+        status: Option<u32>,
+    },
+    RetriedTextSegment {
+        request_info: &'a TextSegmentRequestInfo,
+        reason: RequestErrorReason,
+        status: Option<u32>,
+    },
     Failed {
         request_type: FinishedRequestType,
         reason: RequestErrorReason,
@@ -323,6 +487,9 @@ impl Requester {
             pending_playlist_requests: vec![],
             pending_segment_requests: vec![],
             segment_waiting_queue: vec![],
+            pending_key_requests: vec![],
+            pending_text_segment_requests: vec![],
+            loaded_keys: vec![],
             segment_request_locked: false,
             base_position: None,
             retry_timers: vec![],
@@ -337,6 +504,9 @@ impl Requester {
         self.pending_playlist_requests.clear();
         self.pending_segment_requests.clear();
         self.segment_waiting_queue.clear();
+        self.pending_key_requests.clear();
+        self.pending_text_segment_requests.clear();
+        self.loaded_keys.clear();
         self.retry_timers.clear();
         self.base_position = None;
         self.segment_request_locked = false;
@@ -366,12 +536,36 @@ impl Requester {
     ///
     /// Once it succeeds, the `on_request_finished` function will be called.
     pub(crate) fn fetch_playlist(&mut self, url: Url, playlist_type: PlaylistFileType) {
-        let timeout = match playlist_type {
-            PlaylistFileType::MultivariantPlaylist => {
-                self.config.multi_variant_playlist_request_timeout
-            }
-            PlaylistFileType::MediaPlaylist { .. } => self.config.media_playlist_request_timeout,
+        let timeout = self.playlist_request_timeout(&playlist_type);
+        self.fetch_playlist_with_timeout(url, playlist_type, timeout);
+    }
+
+    /// Same as `fetch_playlist` but for a Blocking Playlist Reload of a Media Playlist, whose
+    /// response may be held by the server until the awaited segment is available.
+    ///
+    /// The request's timeout is thus set to at least `min_timeout` milliseconds, unless timeouts
+    /// have been disabled for Media Playlist requests.
+    pub(crate) fn fetch_blocking_playlist(
+        &mut self,
+        url: Url,
+        playlist_type: PlaylistFileType,
+        min_timeout: f64,
+    ) {
+        let timeout = self.playlist_request_timeout(&playlist_type);
+        let timeout = if timeout < 0. {
+            timeout
+        } else {
+            f64::max(timeout, min_timeout)
         };
+        self.fetch_playlist_with_timeout(url, playlist_type, timeout);
+    }
+
+    fn fetch_playlist_with_timeout(
+        &mut self,
+        url: Url,
+        playlist_type: PlaylistFileType,
+        timeout: f64,
+    ) {
         let url_ref = url.get_ref();
         let request_id = jsFetch(url_ref, None, None, timeout);
         Logger::info(&format!(
@@ -383,9 +577,25 @@ impl Requester {
             playlist_type,
             attempts_failed: 0,
             is_waiting_for_retry: false,
+            timeout,
         });
     }
 
+    /// Returns the configured timeout, in milliseconds, for requests of the given playlist type.
+    fn playlist_request_timeout(&self, playlist_type: &PlaylistFileType) -> f64 {
+        match playlist_type {
+            PlaylistFileType::MultivariantPlaylist
+            | PlaylistFileType::AssetList
+            | PlaylistFileType::SessionData { .. }
+            | PlaylistFileType::SteeringManifest => {
+                self.config.multi_variant_playlist_request_timeout
+            }
+            PlaylistFileType::MediaPlaylist { .. } | PlaylistFileType::TextMediaPlaylist { .. } => {
+                self.config.media_playlist_request_timeout
+            }
+        }
+    }
+
     /// Fetch the initialization segment whose metadata is given here add its
     /// `request_id` to `pending_segment_requests`.
     ///
@@ -395,9 +605,23 @@ impl Requester {
         media_type: MediaType,
         url: Url,
         byte_range: Option<&ByteRange>,
+        key: Option<&SegmentKey>,
         context: SegmentQualityContext,
     ) {
-        self.request_segment_now(&url, byte_range, media_type, None, context);
+        if self.is_key_ready(key) {
+            self.request_segment_now(&url, byte_range, media_type, None, key.cloned(), context);
+        } else {
+            Logger::debug("Req: pushing init segment request to queue until its key is loaded");
+            self.request_key_if_needed(key, media_type);
+            self.segment_waiting_queue.push(WaitingSegmentInfo {
+                media_type,
+                url,
+                byte_range: byte_range.cloned(),
+                time_info: None,
+                context,
+                key: key.cloned(),
+            });
+        }
     }
 
     /// Returns `true` if a segment with the given identifying characteristics is currently either
@@ -441,20 +665,110 @@ impl Requester {
             seg.duration()
         ));
         let time_info = Some(seg.time_info().clone());
-        if self.can_start_request(seg.start()) {
-            self.request_segment_now(seg.url(), seg.byte_range(), media_type, time_info, context)
+        if self.is_key_ready(seg.key()) && self.can_start_request(seg.start()) {
+            self.request_segment_now(
+                seg.url(),
+                seg.byte_range(),
+                media_type,
+                time_info,
+                seg.key().cloned(),
+                context,
+            )
         } else {
             Logger::debug("Req: pushing segment request to queue");
+            self.request_key_if_needed(seg.key(), media_type);
             self.segment_waiting_queue.push(WaitingSegmentInfo {
                 media_type,
                 url: seg.url().clone(),
                 byte_range: seg.byte_range().cloned(),
                 time_info,
                 context,
+                key: seg.key().cloned(),
             });
         }
     }
 
+    /// Fetch a subtitles segment through the given `url`, right away.
+    ///
+    /// `time_info` should be set to `None` for initialization segments.
+    ///
+    /// Once the request finishes with success, the `on_request_finished` function will be
+    /// called.
+    pub(crate) fn request_text_segment(
+        &mut self,
+        url: &Url,
+        byte_range: Option<&ByteRange>,
+        time_info: Option<SegmentTimeInfo>,
+        context: SegmentQualityContext,
+    ) {
+        let (range_start, range_end) = format_range_for_js(byte_range);
+        let url_ref = url.get_ref();
+        let request_id = jsFetch(
+            url_ref,
+            range_start,
+            range_end,
+            self.config.segment_request_timeout,
+        );
+        Logger::debug(&format!(
+            "Req: Performing text segment request. u:{url_ref} id:{request_id}"
+        ));
+        self.pending_text_segment_requests
+            .push(TextSegmentRequestInfo {
+                request_id,
+                url: url.clone(),
+                byte_range: byte_range.cloned(),
+                time_info,
+                context,
+                attempts_failed: 0,
+                is_waiting_for_retry: false,
+            });
+    }
+
+    /// Returns `true` if a subtitles segment request is currently pending.
+    pub(crate) fn has_text_segment_request_pending(&self) -> bool {
+        !self.pending_text_segment_requests.is_empty()
+    }
+
+    /// Abort all pending subtitles segment requests.
+    pub(crate) fn abort_text_segments(&mut self) {
+        while let Some(last_req) = self.pending_text_segment_requests.pop() {
+            Logger::info(&format!(
+                "Req: Aborting text segment request u:{}",
+                last_req.url.get_ref()
+            ));
+            jsAbortRequest(last_req.request_id);
+        }
+    }
+
+    /// Returns `true` if the Playlist of the given type is currently being requested.
+    pub(crate) fn is_requesting_playlist(&self, playlist_type: &PlaylistFileType) -> bool {
+        self.pending_playlist_requests
+            .iter()
+            .any(|p| &p.playlist_type == playlist_type)
+    }
+
+    /// Returns the value of the decryption key loaded from the given `url`, if it has been loaded
+    /// and is still in the `Requester`'s cache.
+    pub(crate) fn key(&self, url: &Url) -> Option<&[u8; 16]> {
+        self.loaded_keys
+            .iter()
+            .find(|(key_url, _)| key_url == url)
+            .map(|(_, key)| key)
+    }
+
+    /// Communicate the value of a decryption key loaded from the given `url`, generally after a
+    /// `FinishedRequestType::Key` has been returned by `on_pending_request_success`.
+    ///
+    /// Segment requests which were waiting for that key may then be started.
+    pub(crate) fn add_key(&mut self, url: Url, key: [u8; 16]) {
+        self.loaded_keys.retain(|(key_url, _)| key_url != &url);
+        if self.loaded_keys.len() >= MAX_LOADED_KEYS {
+            self.loaded_keys.remove(0);
+        }
+        self.loaded_keys.push((url, key));
+        self.check_segment_queue();
+    }
+
     pub(crate) fn lock_segment_requests(&mut self) -> bool {
         let was_locked = self.segment_request_locked;
         self.segment_request_locked = true;
@@ -503,13 +817,28 @@ impl Requester {
                 .position(|x| x.request_id == request_id)
             {
                 self.retry_pending_segment_request(pos, reason, status)
+            } else if let Some(pos) = self
+                .pending_key_requests
+                .iter()
+                .position(|x| x.request_id == request_id)
+            {
+                self.retry_pending_key_request(pos, reason, status)
+            } else if let Some(pos) = self
+                .pending_text_segment_requests
+                .iter()
+                .position(|x| x.request_id == request_id)
+            {
+                self.retry_pending_text_segment_request(pos, reason, status)
             } else if let Some(pos) = self
                 .pending_playlist_requests
                 .iter()
                 .position(|x| x.request_id == request_id)
             {
                 match self.pending_playlist_requests[pos].playlist_type {
-                    PlaylistFileType::MultivariantPlaylist => self.retry_playlist_request(
+                    PlaylistFileType::MultivariantPlaylist
+                    | PlaylistFileType::AssetList
+                    | PlaylistFileType::SessionData { .. }
+                    | PlaylistFileType::SteeringManifest => self.retry_playlist_request(
                         pos,
                         reason,
                         status,
@@ -558,6 +887,33 @@ impl Requester {
                         self.config.segment_request_timeout,
                     );
                     seg.request_id = request_id;
+                } else if let Some(key) = self
+                    .pending_key_requests
+                    .iter_mut()
+                    .find(|k| k.request_id == timer.1)
+                {
+                    key.is_waiting_for_retry = false;
+                    let request_id = jsFetch(
+                        key.url.get_ref(),
+                        None,
+                        None,
+                        self.config.segment_request_timeout,
+                    );
+                    key.request_id = request_id;
+                } else if let Some(seg) = self
+                    .pending_text_segment_requests
+                    .iter_mut()
+                    .find(|s| s.request_id == timer.1)
+                {
+                    seg.is_waiting_for_retry = false;
+                    let (range_start, range_end) = format_range_for_js(seg.byte_range.as_ref());
+                    let request_id = jsFetch(
+                        seg.url.get_ref(),
+                        range_start,
+                        range_end,
+                        self.config.segment_request_timeout,
+                    );
+                    seg.request_id = request_id;
                 } else {
                     let pla = self
                         .pending_playlist_requests
@@ -565,15 +921,7 @@ impl Requester {
                         .find(|p| p.request_id == timer.1);
                     if let Some(pla) = pla {
                         pla.is_waiting_for_retry = false;
-                        let timeout = match pla.playlist_type {
-                            PlaylistFileType::MultivariantPlaylist => {
-                                self.config.multi_variant_playlist_request_timeout
-                            }
-                            PlaylistFileType::MediaPlaylist { .. } => {
-                                self.config.media_playlist_request_timeout
-                            }
-                        };
-                        let request_id = jsFetch(pla.url.get_ref(), None, None, timeout);
+                        let request_id = jsFetch(pla.url.get_ref(), None, None, pla.timeout);
                         pla.request_id = request_id;
                     }
                 }
@@ -611,6 +959,14 @@ impl Requester {
         while let Some(last_req) = self.segment_waiting_queue.pop() {
             log_segment_abort(&last_req);
         }
+        while let Some(last_req) = self.pending_key_requests.pop() {
+            Logger::info(&format!(
+                "Req: Aborting key request u:{}",
+                last_req.url.get_ref()
+            ));
+            jsAbortRequest(last_req.request_id);
+        }
+        self.abort_text_segments();
     }
 
     pub(crate) fn abort_segments_with_type(&mut self, media_type: MediaType) {
@@ -644,6 +1000,22 @@ impl Requester {
     fn end_pending_request(&mut self, request_id: RequestId) -> Option<FinishedRequestType> {
         if let Some(res) = self.end_pending_segment_request(request_id) {
             Some(FinishedRequestType::Segment(res))
+        } else if let Some(pos) = self
+            .pending_key_requests
+            .iter()
+            .position(|k| k.request_id == request_id)
+        {
+            Some(FinishedRequestType::Key(
+                self.pending_key_requests.remove(pos),
+            ))
+        } else if let Some(pos) = self
+            .pending_text_segment_requests
+            .iter()
+            .position(|s| s.request_id == request_id)
+        {
+            Some(FinishedRequestType::TextSegment(
+                self.pending_text_segment_requests.remove(pos),
+            ))
         } else {
             Some(FinishedRequestType::Playlist(
                 self.end_pending_playlist_request(request_id)?,
@@ -724,6 +1096,92 @@ impl Requester {
         }
     }
 
+    fn retry_pending_key_request(
+        &mut self,
+        pos: usize,
+        reason: RequestErrorReason,
+        status: Option<u32>,
+    ) -> RetryResult<'_> {
+        let req = self.pending_key_requests.get(pos).unwrap();
+        let max_retry = self.config.segment_request_max_retry;
+        if max_retry >= 0 && req.attempts_failed >= (max_retry as u32) {
+            Logger::info(&format!(
+                "Req: Too much attempts for key request id:{} a:{}",
+                req.request_id, req.attempts_failed
+            ));
+            let key = self.pending_key_requests.remove(pos);
+            RetryResult::Failed {
+                request_type: FinishedRequestType::Key(key),
+                reason,
+                status,
+            }
+        } else {
+            let req = self.pending_key_requests.get_mut(pos).unwrap();
+            req.attempts_failed += 1;
+            req.is_waiting_for_retry = true;
+            let retry_delay = get_waiting_delay(
+                req.attempts_failed,
+                self.config.segment_backoff_base,
+                self.config.segment_backoff_max,
+            );
+            Logger::info(&format!(
+                "Req: Retrying key request after timer id:{} d:{} a:{}",
+                req.request_id, retry_delay, req.attempts_failed
+            ));
+            let timer_id = jsTimer(retry_delay, TimerReason::RetryRequest);
+            self.retry_timers.push((timer_id, req.request_id));
+            let req = self.pending_key_requests.get(pos).unwrap();
+            RetryResult::RetriedKey {
+                reason,
+                status,
+                request_info: req,
+            }
+        }
+    }
+
+    fn retry_pending_text_segment_request(
+        &mut self,
+        pos: usize,
+        reason: RequestErrorReason,
+        status: Option<u32>,
+    ) -> RetryResult<'_> {
+        let req = self.pending_text_segment_requests.get(pos).unwrap();
+        let max_retry = self.config.segment_request_max_retry;
+        if max_retry >= 0 && req.attempts_failed >= (max_retry as u32) {
+            Logger::info(&format!(
+                "Req: Too much attempts for text segment request id:{} a:{}",
+                req.request_id, req.attempts_failed
+            ));
+            let seg = self.pending_text_segment_requests.remove(pos);
+            RetryResult::Failed {
+                request_type: FinishedRequestType::TextSegment(seg),
+                reason,
+                status,
+            }
+        } else {
+            let req = self.pending_text_segment_requests.get_mut(pos).unwrap();
+            req.attempts_failed += 1;
+            req.is_waiting_for_retry = true;
+            let retry_delay = get_waiting_delay(
+                req.attempts_failed,
+                self.config.segment_backoff_base,
+                self.config.segment_backoff_max,
+            );
+            Logger::info(&format!(
+                "Req: Retrying text segment request after timer id:{} d:{} a:{}",
+                req.request_id, retry_delay, req.attempts_failed
+            ));
+            let timer_id = jsTimer(retry_delay, TimerReason::RetryRequest);
+            self.retry_timers.push((timer_id, req.request_id));
+            let req = self.pending_text_segment_requests.get(pos).unwrap();
+            RetryResult::RetriedTextSegment {
+                reason,
+                status,
+                request_info: req,
+            }
+        }
+    }
+
     fn retry_playlist_request(
         &mut self,
         pos: usize,
@@ -748,11 +1206,15 @@ impl Requester {
             req.attempts_failed += 1;
             req.is_waiting_for_retry = true;
             let (base, max) = match req.playlist_type {
-                PlaylistFileType::MultivariantPlaylist => (
+                PlaylistFileType::MultivariantPlaylist
+                | PlaylistFileType::AssetList
+                | PlaylistFileType::SessionData { .. }
+                | PlaylistFileType::SteeringManifest => (
                     self.config.multi_variant_playlist_backoff_base,
                     self.config.multi_variant_playlist_backoff_max,
                 ),
-                PlaylistFileType::MediaPlaylist { .. } => (
+                PlaylistFileType::MediaPlaylist { .. }
+                | PlaylistFileType::TextMediaPlaylist { .. } => (
                     self.config.media_playlist_backoff_base,
                     self.config.media_playlist_backoff_max,
                 ),
@@ -820,7 +1282,10 @@ impl Requester {
                     .segment_waiting_queue
                     .iter()
                     .enumerate()
-                    .filter(|(_, w)| get_segment_priority(w.start_time(), base_pos) <= new_min_prio)
+                    .filter(|(_, w)| {
+                        get_segment_priority(w.start_time(), base_pos) <= new_min_prio
+                            && self.is_key_ready(w.key.as_ref())
+                    })
                     .map(|w| w.0)
                     .collect();
 
@@ -833,22 +1298,77 @@ impl Requester {
                             seg.byte_range.as_ref(),
                             seg.media_type,
                             seg.time_info,
+                            seg.key,
                             seg.context,
                         );
                     },
                 );
             }
         } else {
-            while let Some(seg) = self.segment_waiting_queue.pop() {
-                self.request_segment_now(
-                    &seg.url,
-                    seg.byte_range.as_ref(),
-                    seg.media_type,
-                    seg.time_info,
-                    seg.context,
-                );
+            let mut i = self.segment_waiting_queue.len();
+            while i > 0 {
+                i -= 1;
+                if self.is_key_ready(self.segment_waiting_queue[i].key.as_ref()) {
+                    let seg = self.segment_waiting_queue.remove(i);
+                    self.request_segment_now(
+                        &seg.url,
+                        seg.byte_range.as_ref(),
+                        seg.media_type,
+                        seg.time_info,
+                        seg.key,
+                        seg.context,
+                    );
+                }
             }
         }
+
+        // Keys may have been evicted from the cache since those segments were scheduled
+        let missing_keys: Vec<(SegmentKey, MediaType)> = self
+            .segment_waiting_queue
+            .iter()
+            .filter_map(|w| match &w.key {
+                Some(k) if !self.is_key_ready(Some(k)) => Some((k.clone(), w.media_type)),
+                _ => None,
+            })
+            .collect();
+        for (key, media_type) in missing_keys {
+            self.request_key_if_needed(Some(&key), media_type);
+        }
+    }
+
+    /// Returns `true` if a segment linked to the given `SegmentKey` can be requested right away,
+    /// either because it doesn't need to be decrypted by us or because its key is already loaded.
+    fn is_key_ready(&self, key: Option<&SegmentKey>) -> bool {
+        match key {
+            Some(k) if k.is_decrypted_by_player() => self.key(k.url()).is_some(),
+            _ => true,
+        }
+    }
+
+    /// Request the key described by the given `SegmentKey` if it has to be loaded by us and is
+    /// neither already loaded nor already being requested.
+    fn request_key_if_needed(&mut self, key: Option<&SegmentKey>, media_type: MediaType) {
+        let key = match key {
+            Some(k) if !self.is_key_ready(Some(k)) => k,
+            _ => return,
+        };
+        if self
+            .pending_key_requests
+            .iter()
+            .any(|k| &k.url == key.url())
+        {
+            return;
+        }
+        let url_ref = key.url().get_ref();
+        let request_id = jsFetch(url_ref, None, None, self.config.segment_request_timeout);
+        Logger::info(&format!("Req: Fetching key u:{url_ref}, id:{request_id}"));
+        self.pending_key_requests.push(KeyRequestInfo {
+            request_id,
+            url: key.url().clone(),
+            media_type,
+            attempts_failed: 0,
+            is_waiting_for_retry: false,
+        });
     }
 
     fn min_pending_priority(&self) -> Option<PriorityLevel> {
@@ -895,8 +1415,13 @@ impl Requester {
         byte_range: Option<&ByteRange>,
         media_type: MediaType,
         time_info: Option<SegmentTimeInfo>,
+        key: Option<SegmentKey>,
         context: SegmentQualityContext,
     ) {
+        let key_data = key
+            .as_ref()
+            .filter(|k| k.is_decrypted_by_player())
+            .and_then(|k| self.key(k.url()).copied());
         let (range_start, range_end) = format_range_for_js(byte_range);
         let url_ref = url.get_ref();
         let request_id = jsFetch(
@@ -914,6 +1439,8 @@ impl Requester {
             url: url.clone(),
             byte_range: byte_range.cloned(),
             time_info,
+            key,
+            key_data,
             attempts_failed: 0,
             is_waiting_for_retry: false,
             context,
@@ -16,6 +16,8 @@ pub(crate) struct NextSegmentSelectors {
     audio: NextSegmentSelector,
     /// Segment-selection logic for the video media type
     video: NextSegmentSelector,
+    /// Segment-selection logic for subtitles
+    text: NextSegmentSelector,
 }
 
 impl NextSegmentSelectors {
@@ -37,6 +39,7 @@ impl NextSegmentSelectors {
         Self {
             audio: NextSegmentSelector::new(base_pos, buffer_goal),
             video: NextSegmentSelector::new(base_pos, buffer_goal),
+            text: NextSegmentSelector::new(base_pos, buffer_goal),
         }
     }
 
@@ -50,6 +53,7 @@ impl NextSegmentSelectors {
         let pos = f64::max(0., pos);
         self.audio.reset(pos);
         self.video.reset(pos);
+        self.text.reset(pos);
     }
 
     /// Updates the "base position" - used by the `NextSegmentSelectors` and its inner
@@ -69,6 +73,7 @@ impl NextSegmentSelectors {
     pub(crate) fn advance_position(&mut self, pos: f64) {
         self.audio.advance_position(pos);
         self.video.advance_position(pos);
+        self.text.advance_position(pos);
     }
 
     /// Force the `NextSegmentSelectors` and its inner `NextSegmentSelector`s to re-consider
@@ -92,6 +97,7 @@ impl NextSegmentSelectors {
         let pos = f64::max(0., pos);
         self.audio.restart_from_position(pos);
         self.video.restart_from_position(pos);
+        self.text.restart_from_position(pos);
     }
 
     /// Update the "buffer_goal" which is the amount of media data, in seconds of media, ahead of
@@ -103,6 +109,7 @@ impl NextSegmentSelectors {
     pub(crate) fn update_buffer_goal(&mut self, buffer_goal: f64) {
         self.audio.buffer_goal = buffer_goal;
         self.video.buffer_goal = buffer_goal;
+        self.text.buffer_goal = buffer_goal;
     }
 
     /// Get the unique `NextSegmentSelector` for the type of media communicated as a mutable
@@ -119,6 +126,14 @@ impl NextSegmentSelectors {
             MediaType::Video => &mut self.video,
         }
     }
+
+    /// Get the `NextSegmentSelector` for subtitles as a mutable reference.
+    ///
+    /// As subtitles segments are not pushed to a `SourceBuffer`, no inventory of already-loaded
+    /// segments should be given to its `most_needed_segment` method.
+    pub(crate) fn text_mut(&mut self) -> &mut NextSegmentSelector {
+        &mut self.text
+    }
 }
 
 pub(crate) struct NextSegmentSelector {
@@ -159,6 +174,14 @@ pub(crate) struct NextSegmentSelector {
     /// To ensure that playback can still continue, segments that have been previously skipped
     /// should be re-checked regularly, if it is needed again, the segment should be loaded.
     skipped_segments: Vec<SegmentTimeInfo>,
+
+    /// If the last validated media segment was a partial segment (as found in Low-Latency HLS),
+    /// this is its parent segment's media sequence number and its index amongst its parent's
+    /// partial segments.
+    ///
+    /// This allows to continue with the following partial segment, even when the time
+    /// information of the last one was only estimated (e.g. for preload hints).
+    last_validated_part: Option<(u64, u32)>,
 }
 
 impl NextSegmentSelector {
@@ -185,6 +208,7 @@ impl NextSegmentSelector {
             last_media_id: None,
             init_status: InitializationSegmentSelectorStatus::Unchecked,
             skipped_segments: vec![],
+            last_validated_part: None,
         }
     }
 
@@ -196,6 +220,7 @@ impl NextSegmentSelector {
         self.last_media_id = None;
         self.segment_cursor = SegmentCursor::new(base_pos);
         self.skipped_segments.clear();
+        self.last_validated_part = None;
     }
 
     /// See `NextSegmentSelectors`'s `advance_position` method.
@@ -209,6 +234,7 @@ impl NextSegmentSelector {
         self.base_pos = f64::max(0., base_pos);
         self.segment_cursor = SegmentCursor::new(base_pos);
         self.skipped_segments.clear();
+        self.last_validated_part = None;
     }
 
     /// Calling this method allows to indicate that the initialization segment was requested and as
@@ -221,10 +247,14 @@ impl NextSegmentSelector {
         }
     }
 
-    /// Calling this method allows to indicate that the media segment ending at `pos` was requested
-    /// and as such, don't need to be returned anymore by this `NextSegmentSelector`.
-    pub(crate) fn validate_media_until(&mut self, pos: f64) {
-        self.segment_cursor.move_cursor(pos);
+    /// Calling this method allows to indicate that the media segment (or partial segment) with
+    /// the given time information was requested and as such, don't need to be returned anymore by
+    /// this `NextSegmentSelector`.
+    pub(crate) fn validate_media(&mut self, time_info: &SegmentTimeInfo) {
+        self.segment_cursor.move_cursor(time_info.end());
+        self.last_validated_part = time_info
+            .part_index()
+            .map(|idx| (time_info.media_sequence(), idx));
     }
 
     /// Returns the current most needed segment(s) according to the current situation and to the
@@ -234,7 +264,11 @@ impl NextSegmentSelector {
     /// do have been requested, to avoid just getting the same segment on the next
     /// `most_needed_segment` call.
     /// To "validate" a segment, you can call `validate_init` if we're talking about an
-    /// initialization segment, or `validate_media_until` if we're talking about a media segment.
+    /// initialization segment, or `validate_media` if we're talking about a media segment.
+    ///
+    /// For Low-Latency HLS contents, the returned media segment may also be a partial segment,
+    /// when the wanted segment is not yet complete or when we were already loading its partial
+    /// segments.
     pub(crate) fn most_needed_segment<'a>(
         &mut self,
         segment_list: &'a SegmentList,
@@ -250,6 +284,7 @@ impl NextSegmentSelector {
             self.init_status = InitializationSegmentSelectorStatus::Unchecked;
             self.segment_cursor.move_cursor(self.base_pos);
             self.skipped_segments.clear();
+            self.last_validated_part = None;
             let start_pos = self.recompute_starting_position(context, inventory);
             self.segment_cursor.move_cursor(start_pos);
         }
@@ -257,8 +292,8 @@ impl NextSegmentSelector {
         if let Some(val) = self.check_skipped_segments(context, inventory) {
             self.segment_cursor.move_cursor(val);
         }
-        let most_needed_segment = if let Some(seg) = self
-            .recursively_check_most_needed_media_segment(segment_list.media(), context, inventory)
+        let most_needed_segment = if let Some(seg) =
+            self.most_needed_media_segment_or_part(segment_list, context, inventory)
         {
             seg
         } else {
@@ -367,6 +402,82 @@ impl NextSegmentSelector {
         None
     }
 
+    /// Returns the most needed media segment or partial segment according to the current
+    /// situation.
+    ///
+    /// Full media segments are preferred, except when we were already loading the partial
+    /// segments of that same segment, or if there's no complete segment left to load, in which
+    /// case we continue with partial segments, including the one hinted by the `SegmentList`.
+    fn most_needed_media_segment_or_part<'a>(
+        &mut self,
+        segment_list: &'a SegmentList,
+        context: &SegmentQualityContext,
+        inventory: &[BufferedChunk],
+    ) -> Option<&'a MediaSegmentInfo> {
+        loop {
+            let seg = match self.recursively_check_most_needed_media_segment(
+                segment_list.media(),
+                context,
+                inventory,
+            ) {
+                Some(seg) => seg,
+                None => return self.next_part(segment_list),
+            };
+            match self.last_validated_part {
+                Some((media_sequence, idx)) if media_sequence == seg.media_sequence() => {
+                    if let Some(part) = segment_list
+                        .parts_for(media_sequence)
+                        .find(|p| p.time_info().part_index() == Some(idx + 1))
+                    {
+                        return Some(part);
+                    }
+
+                    // All partial segments of that segment have already been loaded
+                    self.segment_cursor.move_cursor(seg.end());
+                }
+                _ => return Some(seg),
+            }
+        }
+    }
+
+    /// Returns the next partial segment that should be loaded, for when there's no complete
+    /// segment left to load.
+    ///
+    /// If a partial segment has been validated last, the one following it is returned.
+    /// If not, we start from the last independent partial segment of the segment whose partial
+    /// segments end after the current cursor.
+    fn next_part<'a>(&mut self, segment_list: &'a SegmentList) -> Option<&'a MediaSegmentInfo> {
+        let maximum_position = self.buffer_goal + self.base_pos;
+        let mut parts = segment_list
+            .parts()
+            .iter()
+            .chain(segment_list.preload_hint());
+        let next_part = if let Some((media_sequence, idx)) = self.last_validated_part {
+            parts.find(|p| {
+                let (p_media_sequence, p_idx) =
+                    (p.media_sequence(), p.time_info().part_index().unwrap_or(0));
+                (p_media_sequence == media_sequence && p_idx == idx + 1)
+                    || (p_media_sequence == media_sequence + 1 && p_idx == 0)
+            })
+        } else {
+            let cursor = self.segment_cursor.position();
+            let first_part = parts.find(|p| p.end() > cursor)?;
+            if first_part.is_independent() {
+                Some(first_part)
+            } else {
+                segment_list
+                    .parts_for(first_part.media_sequence())
+                    .filter(|p| p.start() <= first_part.start() && p.is_independent())
+                    .last()
+                    .or(Some(first_part))
+            }
+        };
+        match next_part {
+            Some(part) if part.start() <= maximum_position => Some(part),
+            _ => None,
+        }
+    }
+
     /// Returns the most needed segment according to the current situation.
     /// Internally, this method may be validating and re-calling itself (hence its name) if it sees
     /// that segments of a higher or similar quality are already present in the buffer, through a
@@ -391,7 +502,7 @@ impl NextSegmentSelector {
                 si.start(),
                 si.duration()
             ));
-            let skipped = SegmentTimeInfo::new(si.start(), si.duration());
+            let skipped = si.time_info().clone();
             match self
                 .skipped_segments
                 .iter()
@@ -563,6 +674,11 @@ impl SegmentCursor {
         self.current_cursor = pos;
     }
 
+    /// Returns the current position of the cursor, in playlist time in seconds.
+    pub(crate) fn position(&self) -> f64 {
+        self.current_cursor
+    }
+
     /// Get the first chronological segment in `media_segments` that ends after the cursor's
     /// position, unless it also starts at or after the given `maximum_position`.
     ///
@@ -580,3 +696,114 @@ impl SegmentCursor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parser::MultivariantPlaylist,
+        utils::{logger::LoggerLevel, url::Url},
+    };
+
+    const LL_HLS_PLAYLIST: &str = "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-PART-INF:PART-TARGET=1
+#EXT-X-MEDIA-SEQUENCE:10
+#EXTINF:4,
+seg10.mp4
+#EXT-X-PART:DURATION=1,URI=\"p11.0.mp4\",INDEPENDENT=YES
+#EXT-X-PART:DURATION=1,URI=\"p11.1.mp4\"
+#EXT-X-PART:DURATION=1,URI=\"p11.2.mp4\",INDEPENDENT=YES
+#EXT-X-PART:DURATION=1,URI=\"p11.3.mp4\"
+#EXTINF:4,
+seg11.mp4
+#EXT-X-PART:DURATION=1,URI=\"p12.0.mp4\",INDEPENDENT=YES
+#EXT-X-PART:DURATION=1,URI=\"p12.1.mp4\"
+#EXT-X-PRELOAD-HINT:TYPE=PART,URI=\"p12.2.mp4\"
+";
+
+    fn parse(media_playlist: &str) -> MultivariantPlaylist {
+        Logger::set_logger_level(LoggerLevel::None);
+        MultivariantPlaylist::from_media_playlist(
+            media_playlist.as_bytes(),
+            Url::new("https://example.com/media.m3u8".to_owned()),
+        )
+        .unwrap()
+    }
+
+    fn segment_list_of(playlist: &MultivariantPlaylist) -> &SegmentList {
+        let variant = &playlist.all_variants()[0];
+        let id = playlist.video_media_playlist_id_for(variant, None).unwrap();
+        playlist.media_playlist(&id).unwrap().segment_list()
+    }
+
+    /// Returns the name of the next needed media segment and validates it.
+    fn next_segment(selector: &mut NextSegmentSelector, segment_list: &SegmentList) -> String {
+        let context = SegmentQualityContext::new(1., 1);
+        let needed = selector.most_needed_segment(segment_list, &context, &[]);
+        let Some(seg) = needed.media_segment() else {
+            return "none".to_owned();
+        };
+        selector.validate_media(seg.time_info());
+        seg.url()
+            .get_ref()
+            .trim_start_matches("https://example.com/")
+            .to_owned()
+    }
+
+    #[test]
+    fn test_parts_after_full_segments() {
+        let playlist = parse(LL_HLS_PLAYLIST);
+        let segment_list = segment_list_of(&playlist);
+        let mut selector = NextSegmentSelector::new(0., 30.);
+        assert_eq!(next_segment(&mut selector, segment_list), "seg10.mp4");
+        assert_eq!(next_segment(&mut selector, segment_list), "seg11.mp4");
+        // Partial segments are loaded once no complete segment is left, including the hinted one
+        assert_eq!(next_segment(&mut selector, segment_list), "p12.0.mp4");
+        assert_eq!(next_segment(&mut selector, segment_list), "p12.1.mp4");
+        assert_eq!(next_segment(&mut selector, segment_list), "p12.2.mp4");
+        assert_eq!(next_segment(&mut selector, segment_list), "none");
+
+        // Once the segment is complete, the selector continues with its following parts
+        let playlist = parse(&format!(
+            "{LL_HLS_PLAYLIST}#EXT-X-PART:DURATION=1,URI=\"p12.2.mp4\"
+#EXT-X-PART:DURATION=1,URI=\"p12.3.mp4\"
+#EXTINF:4,
+seg12.mp4
+#EXT-X-PRELOAD-HINT:TYPE=PART,URI=\"p13.0.mp4\"
+"
+        ));
+        let segment_list = segment_list_of(&playlist);
+        assert_eq!(next_segment(&mut selector, segment_list), "p12.3.mp4");
+        assert_eq!(next_segment(&mut selector, segment_list), "p13.0.mp4");
+    }
+
+    #[test]
+    fn test_parts_from_independent_part() {
+        let playlist = parse(LL_HLS_PLAYLIST);
+        let segment_list = segment_list_of(&playlist);
+
+        // Loading begins at the last independent part preceding the wanted position
+        let mut selector = NextSegmentSelector::new(9.5, 30.);
+        assert_eq!(next_segment(&mut selector, segment_list), "p12.0.mp4");
+        assert_eq!(next_segment(&mut selector, segment_list), "p12.1.mp4");
+
+        // Complete segments are preferred when available
+        let mut selector = NextSegmentSelector::new(5.5, 30.);
+        assert_eq!(next_segment(&mut selector, segment_list), "seg11.mp4");
+
+        // Unless parts of that same segment were already being loaded
+        let mut selector = NextSegmentSelector::new(0., 30.);
+        assert_eq!(next_segment(&mut selector, segment_list), "seg10.mp4");
+        let part = segment_list.parts_for(11).nth(1).unwrap();
+        selector.validate_media(part.time_info());
+        assert_eq!(next_segment(&mut selector, segment_list), "p11.2.mp4");
+        assert_eq!(next_segment(&mut selector, segment_list), "p11.3.mp4");
+        assert_eq!(next_segment(&mut selector, segment_list), "p12.0.mp4");
+
+        // Parts starting after the buffer goal are not loaded
+        let mut selector = NextSegmentSelector::new(8.5, 0.4);
+        assert_eq!(next_segment(&mut selector, segment_list), "p12.0.mp4");
+        assert_eq!(next_segment(&mut selector, segment_list), "none");
+    }
+}
@@ -0,0 +1,399 @@
+use super::closed_captions::{CaptionRow, CaptionStyle, DisplayChange};
+use super::CuePositionAlign;
+
+/// Number of rows of the CEA-608 caption grid.
+const ROWS: usize = 15;
+
+/// Number of columns of the CEA-608 caption grid.
+const COLUMNS: usize = 32;
+
+/// Characters of the basic character set, from `0x20` to `0x7F`, when they differ from ASCII.
+const BASIC_CHARS_EXCEPTIONS: [(u8, char); 10] = [
+    (0x2A, 'á'),
+    (0x5C, 'é'),
+    (0x5E, 'í'),
+    (0x5F, 'ó'),
+    (0x60, 'ú'),
+    (0x7B, 'ç'),
+    (0x7C, '÷'),
+    (0x7D, 'Ñ'),
+    (0x7E, 'ñ'),
+    (0x7F, '█'),
+];
+
+/// Special characters, from `0x30` to `0x3F` after a `0x11` byte.
+const SPECIAL_CHARS: [char; 16] = [
+    '®', '°', '½', '¿', '™', '¢', '£', '♪', 'à', '\u{a0}', 'è', 'â', 'ê', 'î', 'ô', 'û',
+];
+
+/// Extended Spanish and French characters, from `0x20` to `0x3F` after a `0x12` byte.
+const EXTENDED_CHARS_1: [char; 32] = [
+    'Á', 'É', 'Ó', 'Ú', 'Ü', 'ü', '‘', '¡', '*', '\'', '—', '©', '℠', '•', '“', '”', 'À', 'Â', 'Ç',
+    'È', 'Ê', 'Ë', 'ë', 'Î', 'Ï', 'ï', 'Ô', 'Ù', 'ù', 'Û', '«', '»',
+];
+
+/// Extended Portuguese, German and Danish characters, from `0x20` to `0x3F` after a `0x13`
+/// byte.
+const EXTENDED_CHARS_2: [char; 32] = [
+    'Ã', 'ã', 'Í', 'Ì', 'ì', 'Ò', 'ò', 'Õ', 'õ', '{', '}', '\\', '^', '_', '|', '~', 'Ä', 'ä', 'Ö',
+    'ö', 'ß', '¥', '¤', '¦', 'Å', 'å', 'Ø', 'ø', '┌', '┐', '└', '┘',
+];
+
+/// WebVTT color classes of the CEA-608 foreground colors, `None` being white.
+const COLORS: [Option<&str>; 7] = [
+    None,
+    Some("lime"),
+    Some("blue"),
+    Some("cyan"),
+    Some("red"),
+    Some("yellow"),
+    Some("magenta"),
+];
+
+/// Decodes the CEA-608 byte pairs of both fields of a video stream, maintaining the captions
+/// displayed for each of its four channels (`CC1` to `CC4`).
+pub(super) struct Cea608Decoder {
+    /// State of the channels `CC1` to `CC4`.
+    channels: [Cea608Channel; 4],
+    /// For each field, the data channel (`0` or `1`) characters are currently sent to.
+    data_channels: [usize; 2],
+    /// For each field, the last control code received if it was the last byte pair received,
+    /// as control codes are usually sent twice in a row.
+    last_control_codes: [Option<(u8, u8)>; 2],
+    /// If `true`, the second field is currently transporting Extended Data Services packets,
+    /// which are ignored.
+    is_in_xds: bool,
+}
+
+impl Cea608Decoder {
+    pub(super) fn new() -> Self {
+        Self {
+            channels: Default::default(),
+            data_channels: [0, 0],
+            last_control_codes: [None, None],
+            is_in_xds: false,
+        }
+    }
+
+    /// Process a CEA-608 byte pair of the given `field` (`0` for the first field, `1` for the
+    /// second one).
+    pub(super) fn push_pair(&mut self, field: usize, byte1: u8, byte2: u8) {
+        let byte1 = byte1 & 0x7F;
+        let byte2 = byte2 & 0x7F;
+        if byte1 == 0 && byte2 == 0 {
+            // Padding
+            return;
+        }
+        if field == 1 && (0x01..=0x0F).contains(&byte1) {
+            // Extended Data Services, until a `0x0F` "end" code
+            self.is_in_xds = byte1 != 0x0F;
+            return;
+        }
+        if (0x10..=0x1F).contains(&byte1) {
+            if field == 1 {
+                self.is_in_xds = false;
+            }
+            if self.last_control_codes[field] == Some((byte1, byte2)) {
+                // Redundant control code
+                self.last_control_codes[field] = None;
+                return;
+            }
+            self.last_control_codes[field] = Some((byte1, byte2));
+            let data_channel = if byte1 & 0x08 != 0 { 1 } else { 0 };
+            self.data_channels[field] = data_channel;
+            let channel = &mut self.channels[field * 2 + data_channel];
+            channel.process_control_code(byte1 & !0x08, byte2);
+            return;
+        }
+        self.last_control_codes[field] = None;
+        if self.is_in_xds && field == 1 {
+            return;
+        }
+        let channel = &mut self.channels[field * 2 + self.data_channels[field]];
+        for byte in [byte1, byte2] {
+            if byte >= 0x20 {
+                channel.write_char(basic_char(byte));
+            }
+        }
+    }
+
+    /// Returns the most important change made to the captions displayed for the given channel
+    /// (from `1` to `4`) since `acknowledge_change` was last called for it, if any.
+    pub(super) fn change(&self, channel: u8) -> Option<DisplayChange> {
+        self.channels.get(channel as usize - 1)?.change
+    }
+
+    /// Indicate that the changes made to the displayed captions of the given channel have been
+    /// taken into account.
+    pub(super) fn acknowledge_change(&mut self, channel: u8) {
+        if let Some(c) = self.channels.get_mut(channel as usize - 1) {
+            c.change = None;
+        }
+    }
+
+    /// Returns the rows of captions currently displayed for the given channel (from `1` to `4`).
+    pub(super) fn displayed_rows(&self, channel: u8) -> Vec<CaptionRow> {
+        let Some(channel) = self.channels.get(channel as usize - 1) else {
+            return vec![];
+        };
+        channel
+            .displayed
+            .iter()
+            .enumerate()
+            .filter_map(|(row_idx, row)| {
+                let first_column = row.iter().position(|c| c.is_some())?;
+                let last_column = row.iter().rposition(|c| c.is_some())?;
+                // Captions are displayed in a safe area made of the 80% at the center of the
+                // video
+                CaptionRow::from_chars(
+                    10. + row_idx as f64 * 80. / ROWS as f64,
+                    10. + first_column as f64 * 80. / COLUMNS as f64,
+                    CuePositionAlign::LineLeft,
+                    row[first_column..=last_column]
+                        .iter()
+                        .map(|c| c.unwrap_or((' ', CaptionStyle::default()))),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns the character corresponding to the given byte of the basic character set.
+fn basic_char(byte: u8) -> char {
+    BASIC_CHARS_EXCEPTIONS
+        .iter()
+        .find(|(b, _)| *b == byte)
+        .map_or(byte as char, |(_, c)| *c)
+}
+
+/// A row of the CEA-608 caption grid, with its styled characters.
+type Row = [Option<(char, CaptionStyle)>; COLUMNS];
+
+/// How captions are displayed on a CEA-608 channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum CaptionMode {
+    /// No caption mode has been set yet, characters are ignored.
+    #[default]
+    Unknown,
+    /// Captions are written off-screen before being displayed all at once.
+    PopOn,
+    /// Captions are directly written on screen.
+    PaintOn,
+    /// Captions are directly written on screen on the last row of a window containing the
+    /// given number of rows, which scroll up when a new row is started.
+    RollUp(usize),
+    /// Text mode, whose data is ignored.
+    Text,
+}
+
+/// State of a single CEA-608 channel.
+#[derive(Default)]
+struct Cea608Channel {
+    mode: CaptionMode,
+    /// Caption grid currently displayed.
+    displayed: [Row; ROWS],
+    /// Caption grid being written off-screen in pop-on mode.
+    non_displayed: [Row; ROWS],
+    /// Row of the cursor, which is also the last row of the window in roll-up mode.
+    row: usize,
+    /// Column of the cursor.
+    column: usize,
+    /// Style applied to the next written characters.
+    style: CaptionStyle,
+    /// Most important change made to `displayed` since it was last acknowledged.
+    change: Option<DisplayChange>,
+}
+
+impl Cea608Channel {
+    /// Process a control code, whose first byte has been normalized to the one of the first data
+    /// channel of its field.
+    fn process_control_code(&mut self, byte1: u8, byte2: u8) {
+        match (byte1, byte2) {
+            (0x14 | 0x15, 0x20..=0x2F) => self.process_misc_control_code(byte2),
+            (0x17, 0x21..=0x23) => {
+                // Tab offsets
+                self.column = usize::min(self.column + (byte2 - 0x20) as usize, COLUMNS - 1);
+            }
+            (0x11, 0x20..=0x2F) => {
+                // Mid-row codes, which are displayed as a space
+                let attribute = (byte2 - 0x20) >> 1;
+                self.style = CaptionStyle {
+                    color: COLORS.get(attribute as usize).copied().flatten(),
+                    italics: attribute == 7,
+                    underline: byte2 & 0x01 != 0,
+                };
+                self.write_char(' ');
+            }
+            (0x11, 0x30..=0x3F) => self.write_char(SPECIAL_CHARS[(byte2 - 0x30) as usize]),
+            (0x12 | 0x13, 0x20..=0x3F) => {
+                // Extended characters replace the basic character sent before them as a
+                // fallback for decoders not supporting them
+                self.backspace();
+                let table = if byte1 == 0x12 {
+                    &EXTENDED_CHARS_1
+                } else {
+                    &EXTENDED_CHARS_2
+                };
+                self.write_char(table[(byte2 - 0x20) as usize]);
+            }
+            (_, 0x40..=0x7F) => self.process_preamble_address_code(byte1, byte2),
+            _ => {}
+        }
+    }
+
+    fn process_misc_control_code(&mut self, byte2: u8) {
+        match byte2 {
+            // Resume Caption Loading
+            0x20 => self.mode = CaptionMode::PopOn,
+            // Backspace
+            0x21 => self.backspace(),
+            // Delete to End of Row
+            0x24 => {
+                let column = self.column;
+                self.edit_row(|row| row[column..].fill(None));
+            }
+            // Roll-Up Captions (2, 3 or 4 rows)
+            0x25..=0x27 => {
+                let nb_rows = (byte2 - 0x23) as usize;
+                if !matches!(self.mode, CaptionMode::RollUp(_)) {
+                    self.erase_displayed_memory();
+                    self.non_displayed = Default::default();
+                    self.row = ROWS - 1;
+                }
+                self.mode = CaptionMode::RollUp(nb_rows);
+                self.column = 0;
+                // Only keep the rows in the new roll-up window
+                let top = (self.row + 1).saturating_sub(nb_rows);
+                if self.displayed[..top]
+                    .iter()
+                    .any(|r| r.iter().any(|c| c.is_some()))
+                {
+                    self.displayed[..top].fill([None; COLUMNS]);
+                    self.signal_change(DisplayChange::Major);
+                }
+            }
+            // Resume Direct Captioning
+            0x29 => self.mode = CaptionMode::PaintOn,
+            // Text Restart and Resume Text Display
+            0x2A | 0x2B => self.mode = CaptionMode::Text,
+            // Erase Displayed Memory
+            0x2C => self.erase_displayed_memory(),
+            // Carriage Return
+            0x2D => {
+                if let CaptionMode::RollUp(nb_rows) = self.mode {
+                    let top = (self.row + 1).saturating_sub(nb_rows);
+                    self.displayed[top..=self.row].rotate_left(1);
+                    self.displayed[self.row] = [None; COLUMNS];
+                    self.column = 0;
+                    self.signal_change(DisplayChange::Major);
+                }
+            }
+            // Erase Non-Displayed Memory
+            0x2E => self.non_displayed = Default::default(),
+            // End Of Caption
+            0x2F => {
+                std::mem::swap(&mut self.displayed, &mut self.non_displayed);
+                self.mode = CaptionMode::PopOn;
+                self.signal_change(DisplayChange::Major);
+            }
+            // Alarm Off, Alarm On and Flash On are ignored
+            _ => {}
+        }
+    }
+
+    /// Process a Preamble Address Code, which moves the cursor to a new row and sets the style of
+    /// the next characters.
+    fn process_preamble_address_code(&mut self, byte1: u8, byte2: u8) {
+        let is_second_row = byte2 & 0x20 != 0;
+        let row = match (byte1 & 0x07, is_second_row) {
+            (1, _) => 0,
+            (2, _) => 2,
+            (5, _) => 4,
+            (6, _) => 6,
+            (7, _) => 8,
+            (0, false) => 10,
+            (3, _) => 11,
+            (4, _) => 13,
+            _ => return,
+        } + if is_second_row && byte1 & 0x07 != 0 {
+            1
+        } else {
+            0
+        };
+
+        if let CaptionMode::RollUp(nb_rows) = self.mode {
+            if row != self.row {
+                // Move the roll-up window so it ends on the new row
+                let nb_rows = usize::min(nb_rows, row + 1);
+                let mut new_displayed: [Row; ROWS] = Default::default();
+                for i in 0..nb_rows {
+                    if let Some(src) = self.row.checked_sub(i) {
+                        new_displayed[row - i] = self.displayed[src];
+                    }
+                }
+                self.displayed = new_displayed;
+                self.signal_change(DisplayChange::Major);
+            }
+        }
+        self.row = row;
+
+        let attribute = (byte2 & 0x1F) >> 1;
+        let underline = byte2 & 0x01 != 0;
+        if attribute < 8 {
+            self.style = CaptionStyle {
+                color: COLORS.get(attribute as usize).copied().flatten(),
+                italics: attribute == 7,
+                underline,
+            };
+            self.column = 0;
+        } else {
+            self.style = CaptionStyle {
+                underline,
+                ..CaptionStyle::default()
+            };
+            self.column = (attribute as usize - 8) * 4;
+        }
+    }
+
+    /// Write a character at the cursor's position and move it to the next column.
+    fn write_char(&mut self, c: char) {
+        if matches!(self.mode, CaptionMode::Unknown | CaptionMode::Text) {
+            return;
+        }
+        let (column, style) = (self.column, self.style);
+        if column < COLUMNS {
+            self.edit_row(|row| row[column] = Some((c, style)));
+            self.column += 1;
+        }
+    }
+
+    /// Remove the character before the cursor and move the cursor to its column.
+    fn backspace(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+            let column = self.column;
+            self.edit_row(|row| row[column] = None);
+        }
+    }
+
+    /// Modify the row of the cursor in the memory currently written to.
+    fn edit_row(&mut self, edit: impl FnOnce(&mut Row)) {
+        match self.mode {
+            CaptionMode::PopOn => edit(&mut self.non_displayed[self.row]),
+            CaptionMode::PaintOn | CaptionMode::RollUp(_) => {
+                edit(&mut self.displayed[self.row]);
+                self.signal_change(DisplayChange::Minor);
+            }
+            CaptionMode::Unknown | CaptionMode::Text => {}
+        }
+    }
+
+    fn erase_displayed_memory(&mut self) {
+        self.displayed = Default::default();
+        self.signal_change(DisplayChange::Major);
+    }
+
+    fn signal_change(&mut self, change: DisplayChange) {
+        self.change = Some(self.change.map_or(change, |c| c.max(change)));
+    }
+}
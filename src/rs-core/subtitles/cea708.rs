@@ -0,0 +1,529 @@
+use std::collections::HashMap;
+
+use super::closed_captions::{CaptionRow, CaptionStyle, DisplayChange};
+use super::CuePositionAlign;
+
+/// Number of windows each CEA-708 service can define.
+const NB_WINDOWS: usize = 8;
+
+/// Maximum number of columns of a window, for 16:9 displays.
+const MAX_COLUMNS: usize = 42;
+
+/// Height of a caption row, as a percentage of the video's height.
+const ROW_HEIGHT: f64 = 80. / 15.;
+
+/// Characters of the G2 character set, from `0x20` to `0x7F` after an `EXT1` code, which are
+/// not mapped to `'_'`.
+const G2_CHARS: [(u8, char); 26] = [
+    (0x20, ' '),
+    (0x21, '\u{a0}'),
+    (0x25, '…'),
+    (0x2A, 'Š'),
+    (0x2C, 'Œ'),
+    (0x30, '█'),
+    (0x31, '‘'),
+    (0x32, '’'),
+    (0x33, '“'),
+    (0x34, '”'),
+    (0x35, '•'),
+    (0x39, '™'),
+    (0x3A, 'š'),
+    (0x3C, 'œ'),
+    (0x3D, '℠'),
+    (0x3F, 'Ÿ'),
+    (0x76, '⅛'),
+    (0x77, '⅜'),
+    (0x78, '⅝'),
+    (0x79, '⅞'),
+    (0x7A, '│'),
+    (0x7B, '┐'),
+    (0x7C, '└'),
+    (0x7D, '─'),
+    (0x7E, '┘'),
+    (0x7F, '┌'),
+];
+
+/// Decodes the CEA-708 (DTVCC) data of a video stream, maintaining the captions displayed for
+/// each of its services.
+pub(super) struct Cea708Decoder {
+    /// Data of the DTVCC packet being received, its header included.
+    packet: Vec<u8>,
+    /// State of the services encountered until now, per service number.
+    services: HashMap<u8, Cea708Service>,
+}
+
+impl Cea708Decoder {
+    pub(super) fn new() -> Self {
+        Self {
+            packet: vec![],
+            services: HashMap::new(),
+        }
+    }
+
+    /// Process a CEA-708 byte pair, `is_packet_start` being set to `true` if it begins a new
+    /// DTVCC packet (`cc_type` of `3`).
+    pub(super) fn push_pair(&mut self, is_packet_start: bool, byte1: u8, byte2: u8) {
+        if is_packet_start {
+            self.process_packet();
+            self.packet.clear();
+        } else if self.packet.is_empty() {
+            // Continuation of a packet whose start was not received
+            return;
+        }
+        self.packet.push(byte1);
+        self.packet.push(byte2);
+        if self.packet.len() >= packet_size(self.packet[0]) {
+            self.process_packet();
+            self.packet.clear();
+        }
+    }
+
+    /// Returns the numbers of the services whose displayed captions changed since
+    /// `acknowledge_change` was last called for them.
+    pub(super) fn changed_services(&self) -> Vec<u8> {
+        self.services
+            .iter()
+            .filter(|(_, s)| s.change.is_some())
+            .map(|(n, _)| *n)
+            .collect()
+    }
+
+    /// Returns the most important change made to the captions displayed for the given service
+    /// since `acknowledge_change` was last called for it, if any.
+    pub(super) fn change(&self, service: u8) -> Option<DisplayChange> {
+        self.services.get(&service)?.change
+    }
+
+    /// Indicate that the changes made to the displayed captions of the given service have been
+    /// taken into account.
+    pub(super) fn acknowledge_change(&mut self, service: u8) {
+        if let Some(s) = self.services.get_mut(&service) {
+            s.change = None;
+        }
+    }
+
+    /// Returns the rows of captions currently displayed for the given service.
+    pub(super) fn displayed_rows(&self, service: u8) -> Vec<CaptionRow> {
+        let Some(service) = self.services.get(&service) else {
+            return vec![];
+        };
+        service
+            .windows
+            .iter()
+            .flatten()
+            .filter(|w| w.is_visible)
+            .flat_map(|w| w.displayed_rows())
+            .collect()
+    }
+
+    /// Process the service blocks of the DTVCC packet received until now, if complete.
+    fn process_packet(&mut self) {
+        let Some(&header) = self.packet.first() else {
+            return;
+        };
+        let size = usize::min(packet_size(header), self.packet.len());
+        let mut offset = 1;
+        while offset < size {
+            let block_header = self.packet[offset];
+            offset += 1;
+            let mut service_number = block_header >> 5;
+            let block_size = (block_header & 0x1F) as usize;
+            if service_number == 0 {
+                // Null service block, ending the packet
+                return;
+            }
+            if service_number == 7 && block_size != 0 {
+                let Some(extended) = self.packet.get(offset) else {
+                    return;
+                };
+                service_number = extended & 0x3F;
+                offset += 1;
+            }
+            let Some(block) = self
+                .packet
+                .get(offset..usize::min(offset + block_size, size))
+            else {
+                return;
+            };
+            self.services
+                .entry(service_number)
+                .or_default()
+                .process_block(block);
+            offset += block_size;
+        }
+    }
+}
+
+/// Returns the size in bytes, header included, of a DTVCC packet with the given header byte.
+fn packet_size(header: u8) -> usize {
+    match header & 0x3F {
+        0 => 128,
+        size_code => size_code as usize * 2,
+    }
+}
+
+/// State of a single CEA-708 service.
+#[derive(Default)]
+struct Cea708Service {
+    /// The windows defined by that service.
+    windows: [Option<Cea708Window>; NB_WINDOWS],
+    /// Index of the window text is currently written to.
+    current_window: usize,
+    /// Most important change made to the displayed captions since it was last acknowledged.
+    change: Option<DisplayChange>,
+}
+
+impl Cea708Service {
+    /// Process the data of a service block.
+    fn process_block(&mut self, block: &[u8]) {
+        let mut offset = 0;
+        while offset < block.len() {
+            let code = block[offset];
+            offset += 1;
+            let params = &block[offset..];
+            let consumed = match code {
+                0x00..=0x1F => self.process_c0_code(code, params),
+                0x20..=0x7F => {
+                    self.write_char(if code == 0x7F { '♪' } else { code as char });
+                    0
+                }
+                0x80..=0x9F => self.process_c1_code(code, params),
+                0xA0..=0xFF => {
+                    self.write_char(code as char);
+                    0
+                }
+            };
+            offset += consumed;
+        }
+    }
+
+    /// Process a C0 control code, returning the number of bytes of `params` it consumed.
+    fn process_c0_code(&mut self, code: u8, params: &[u8]) -> usize {
+        match code {
+            // End of Text
+            0x03 => self.signal_window_change(DisplayChange::Major),
+            // Backspace
+            0x08 => self.edit_current_window(|w| {
+                if w.pen_column > 0 {
+                    w.pen_column -= 1;
+                    if let Some(c) = w.rows[w.pen_row].get_mut(w.pen_column) {
+                        *c = None;
+                    }
+                }
+            }),
+            // Form Feed
+            0x0C => self.edit_current_window(|w| {
+                w.clear();
+                w.pen_row = 0;
+                w.pen_column = 0;
+            }),
+            // Carriage Return
+            0x0D => {
+                self.edit_current_window(|w| w.carriage_return());
+                self.signal_window_change(DisplayChange::Major);
+            }
+            // Horizontal Carriage Return
+            0x0E => self.edit_current_window(|w| {
+                w.rows[w.pen_row].clear();
+                w.pen_column = 0;
+            }),
+            // EXT1
+            0x10 => return self.process_extended_code(params),
+            0x11..=0x17 => return 1,
+            // P16, whose 16-bit characters are not supported
+            0x18 => {
+                self.write_char('_');
+                return 2;
+            }
+            0x19..=0x1F => return 2,
+            _ => {}
+        }
+        0
+    }
+
+    /// Process a code following an `EXT1` code, returning the number of bytes of `params` it
+    /// consumed, `EXT1` excluded.
+    fn process_extended_code(&mut self, params: &[u8]) -> usize {
+        let Some(&code) = params.first() else {
+            return 0;
+        };
+        match code {
+            // C2 codes, with no defined meaning
+            0x00..=0x07 => 1,
+            0x08..=0x0F => 2,
+            0x10..=0x17 => 3,
+            0x18..=0x1F => 4,
+            0x20..=0x7F => {
+                let c = G2_CHARS
+                    .iter()
+                    .find(|(b, _)| *b == code)
+                    .map_or('_', |(_, c)| *c);
+                self.write_char(c);
+                1
+            }
+            // C3 codes, with no defined meaning
+            0x80..=0x87 => 5,
+            0x88..=0x8F => 6,
+            // Variable-length C3 codes
+            0x90..=0x9F => 2 + params.get(1).map_or(0, |h| (h & 0x3F) as usize),
+            // G3 characters, only the closed captions logo is defined
+            0xA0..=0xFF => {
+                self.write_char('_');
+                1
+            }
+        }
+    }
+
+    /// Process a C1 control code, returning the number of bytes of `params` it consumed.
+    fn process_c1_code(&mut self, code: u8, params: &[u8]) -> usize {
+        match code {
+            // SetCurrentWindow0 to 7
+            0x80..=0x87 => {
+                self.current_window = (code - 0x80) as usize;
+                0
+            }
+            // ClearWindows, DisplayWindows, HideWindows, ToggleWindows and DeleteWindows
+            0x88..=0x8C => {
+                let Some(&bitmap) = params.first() else {
+                    return 1;
+                };
+                for idx in (0..NB_WINDOWS).filter(|i| bitmap & (1 << i) != 0) {
+                    let Some(window) = self.windows[idx].as_mut() else {
+                        continue;
+                    };
+                    let was_visible = window.is_visible;
+                    match code {
+                        0x88 => window.clear(),
+                        0x89 => window.is_visible = true,
+                        0x8A => window.is_visible = false,
+                        0x8B => window.is_visible = !window.is_visible,
+                        _ => {
+                            self.windows[idx] = None;
+                        }
+                    }
+                    let is_visible = self.windows[idx].as_ref().is_some_and(|w| w.is_visible);
+                    if was_visible || is_visible {
+                        self.signal_change(DisplayChange::Major);
+                    }
+                }
+                1
+            }
+            // Delay, DelayCancel: ignored, captions are displayed as soon as decoded
+            0x8D => 1,
+            0x8E => 0,
+            // Reset
+            0x8F => {
+                if self.windows.iter().flatten().any(|w| w.is_visible) {
+                    self.signal_change(DisplayChange::Major);
+                }
+                self.windows = Default::default();
+                self.current_window = 0;
+                0
+            }
+            // SetPenAttributes
+            0x90 => {
+                if let Some(&attributes) = params.get(1) {
+                    self.edit_current_window(|w| {
+                        w.pen_style.italics = attributes & 0x80 != 0;
+                        w.pen_style.underline = attributes & 0x40 != 0;
+                    });
+                }
+                2
+            }
+            // SetPenColor
+            0x91 => {
+                if let Some(&color) = params.first() {
+                    self.edit_current_window(|w| w.pen_style.color = color_class(color));
+                }
+                3
+            }
+            // SetPenLocation
+            0x92 => {
+                if let (Some(&row), Some(&column)) = (params.first(), params.get(1)) {
+                    self.edit_current_window(|w| {
+                        w.pen_row = usize::min((row & 0x0F) as usize, w.rows.len() - 1);
+                        w.pen_column = usize::min((column & 0x3F) as usize, MAX_COLUMNS - 1);
+                    });
+                }
+                2
+            }
+            // SetWindowAttributes
+            0x97 => 4,
+            // DefineWindow0 to 7
+            0x98..=0x9F => {
+                let idx = (code - 0x98) as usize;
+                if params.len() >= 6 {
+                    self.define_window(idx, &params[..6]);
+                }
+                6
+            }
+            // Reserved
+            _ => 0,
+        }
+    }
+
+    /// Define or update the window `idx` with the given `DefineWindow` parameters, and make it
+    /// the current window.
+    fn define_window(&mut self, idx: usize, params: &[u8]) {
+        let is_visible = params[0] & 0x20 != 0;
+        let is_relative = params[1] & 0x80 != 0;
+        let anchor_vertical = (params[1] & 0x7F) as f64;
+        let anchor_horizontal = params[2] as f64;
+        let anchor_point = params[3] >> 4;
+        let row_count = (params[3] & 0x0F) as usize + 1;
+
+        let (line, position) = if is_relative {
+            (anchor_vertical, anchor_horizontal)
+        } else {
+            // Absolute positions are expressed in a 75 rows x 210 columns grid (for 16:9)
+            (
+                anchor_vertical * 100. / 75.,
+                anchor_horizontal * 100. / 210.,
+            )
+        };
+        let window = self.windows[idx].get_or_insert_with(|| Cea708Window {
+            is_visible: false,
+            rows: vec![],
+            pen_row: 0,
+            pen_column: 0,
+            pen_style: CaptionStyle::default(),
+            anchor_line: 0.,
+            anchor_position: 0.,
+            anchor_point: 0,
+        });
+        let was_visible = window.is_visible;
+        window.is_visible = is_visible;
+        window.anchor_line = line.clamp(0., 100.);
+        window.anchor_position = position.clamp(0., 100.);
+        window.anchor_point = anchor_point;
+        window.rows.resize_with(row_count, Vec::new);
+        window.pen_row = usize::min(window.pen_row, row_count - 1);
+        self.current_window = idx;
+        if was_visible || is_visible {
+            self.signal_change(DisplayChange::Major);
+        }
+    }
+
+    /// Write a character at the pen's position in the current window.
+    fn write_char(&mut self, c: char) {
+        self.edit_current_window(|w| {
+            if w.pen_column >= MAX_COLUMNS {
+                return;
+            }
+            let row = &mut w.rows[w.pen_row];
+            if row.len() <= w.pen_column {
+                row.resize(w.pen_column + 1, None);
+            }
+            row[w.pen_column] = Some((c, w.pen_style));
+            w.pen_column += 1;
+        });
+    }
+
+    /// Modify the current window, if it is defined, signaling a `DisplayChange::Minor` if it is
+    /// visible.
+    fn edit_current_window(&mut self, edit: impl FnOnce(&mut Cea708Window)) {
+        if let Some(window) = self.windows[self.current_window].as_mut() {
+            edit(window);
+            if window.is_visible {
+                self.signal_change(DisplayChange::Minor);
+            }
+        }
+    }
+
+    /// Signal the given change if the current window is visible.
+    fn signal_window_change(&mut self, change: DisplayChange) {
+        if self.windows[self.current_window]
+            .as_ref()
+            .is_some_and(|w| w.is_visible)
+        {
+            self.signal_change(change);
+        }
+    }
+
+    fn signal_change(&mut self, change: DisplayChange) {
+        self.change = Some(self.change.map_or(change, |c| c.max(change)));
+    }
+}
+
+/// A window defined by a CEA-708 service, in which captions are written.
+struct Cea708Window {
+    is_visible: bool,
+    /// Styled characters of each row of the window.
+    rows: Vec<Vec<Option<(char, CaptionStyle)>>>,
+    pen_row: usize,
+    pen_column: usize,
+    /// Style applied to the next written characters.
+    pen_style: CaptionStyle,
+    /// Vertical position of the window's anchor point, as a percentage of the video's height.
+    anchor_line: f64,
+    /// Horizontal position of the window's anchor point, as a percentage of the video's width.
+    anchor_position: f64,
+    /// Which point of the window is anchored, from `0` (top left) to `8` (bottom right), row by
+    /// row.
+    anchor_point: u8,
+}
+
+impl Cea708Window {
+    fn clear(&mut self) {
+        self.rows.iter_mut().for_each(|r| r.clear());
+    }
+
+    /// Move the pen to the start of the next row, scrolling rows up if it was on the last one.
+    fn carriage_return(&mut self) {
+        self.pen_column = 0;
+        if self.pen_row + 1 < self.rows.len() {
+            self.pen_row += 1;
+        } else {
+            self.rows.rotate_left(1);
+            if let Some(last) = self.rows.last_mut() {
+                last.clear();
+            }
+        }
+    }
+
+    fn displayed_rows(&self) -> Vec<CaptionRow> {
+        let nb_rows = self.rows.len() as f64;
+        let top = match self.anchor_point / 3 {
+            0 => self.anchor_line,
+            1 => self.anchor_line - nb_rows * ROW_HEIGHT / 2.,
+            _ => self.anchor_line - nb_rows * ROW_HEIGHT,
+        };
+        let position_align = match self.anchor_point % 3 {
+            0 => CuePositionAlign::LineLeft,
+            1 => CuePositionAlign::Center,
+            _ => CuePositionAlign::LineRight,
+        };
+        self.rows
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, row)| {
+                let first_column = row.iter().position(|c| c.is_some())?;
+                let last_column = row.iter().rposition(|c| c.is_some())?;
+                CaptionRow::from_chars(
+                    (top + idx as f64 * ROW_HEIGHT).clamp(0., 100. - ROW_HEIGHT),
+                    self.anchor_position,
+                    position_align,
+                    row[first_column..=last_column]
+                        .iter()
+                        .map(|c| c.unwrap_or((' ', CaptionStyle::default()))),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns the WebVTT color class closest to the given CEA-708 foreground color byte, whose six
+/// lowest bits are its red, green and blue components on 2 bits each. `None` for white.
+fn color_class(color: u8) -> Option<&'static str> {
+    let is_on = |shift: u8| (color >> shift) & 0x03 >= 2;
+    match (is_on(4), is_on(2), is_on(0)) {
+        (true, true, true) => None,
+        (true, true, false) => Some("yellow"),
+        (true, false, true) => Some("magenta"),
+        (true, false, false) => Some("red"),
+        (false, true, true) => Some("cyan"),
+        (false, true, false) => Some("lime"),
+        (false, false, true) => Some("blue"),
+        (false, false, false) => Some("black"),
+    }
+}
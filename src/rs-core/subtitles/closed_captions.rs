@@ -0,0 +1,601 @@
+use std::collections::HashMap;
+
+use super::cea608::Cea608Decoder;
+use super::cea708::Cea708Decoder;
+use super::{CueAlign, CueLineAlign, CuePositionAlign, CueSettings, TextCue};
+use crate::{parser::InstreamId, utils::isobmff};
+
+/// Minimum duration, in seconds, a cue should have before being replaced due to a
+/// `DisplayChange::Minor` change, to avoid creating a cue per video frame when captions are
+/// progressively written on screen.
+const MIN_MINOR_CHANGE_INTERVAL: f64 = 0.5;
+
+/// Maximum interval, in seconds, between the presentation times of two consecutive video
+/// samples after which we consider that they are not contiguous (e.g. after a seek).
+const MAX_SAMPLE_INTERVAL: f64 = 1.;
+
+/// Extracts CEA-608 and CEA-708 closed captions from the SEI NAL units of fragmented MP4 video
+/// segments, and decodes them into `TextCue`s for each of their channels.
+pub(crate) struct ClosedCaptionsDecoder {
+    /// Information on the video track captions are extracted from, as read from its
+    /// initialization segment.
+    track: Option<VideoTrackInfo>,
+    cea608: Cea608Decoder,
+    cea708: Cea708Decoder,
+    /// For each channel, the rows currently displayed and the time, in seconds, since which they
+    /// are.
+    displayed: HashMap<InstreamId, (f64, Vec<CaptionRow>)>,
+    /// Presentation time of the last processed video sample, in seconds.
+    last_time: Option<f64>,
+}
+
+/// Information on a video track needed to extract its closed captions.
+struct VideoTrackInfo {
+    track_id: u32,
+    timescale: u32,
+    /// If `true`, samples contain HEVC NAL units. If `false`, AVC ones.
+    is_hevc: bool,
+}
+
+impl ClosedCaptionsDecoder {
+    pub(crate) fn new() -> Self {
+        Self {
+            track: None,
+            cea608: Cea608Decoder::new(),
+            cea708: Cea708Decoder::new(),
+            displayed: HashMap::new(),
+            last_time: None,
+        }
+    }
+
+    /// Read, from a video initialization segment, the information needed to extract captions
+    /// from its following media segments.
+    ///
+    /// Returns `false` if no AVC or HEVC video track could be found in it, in which case no
+    /// caption will be extracted until another initialization segment is parsed.
+    pub(crate) fn parse_init_segment(&mut self, data: &[u8]) -> bool {
+        self.track = isobmff::read_tracks(data)
+            .into_iter()
+            .filter(|t| &t.handler_type == b"vide")
+            .find_map(|t| {
+                let is_hevc = match &t.sample_entry_type? {
+                    b"avc1" | b"avc3" | b"encv" => false,
+                    b"hvc1" | b"hev1" => true,
+                    _ => return None,
+                };
+                Some(VideoTrackInfo {
+                    track_id: t.track_id,
+                    timescale: t.timescale?,
+                    is_hevc,
+                })
+            });
+        self.track.is_some()
+    }
+
+    /// Extract and decode the closed captions found in the given video media segment.
+    ///
+    /// Returns the cues, with the channel they belong to, which stopped being displayed in that
+    /// segment. Their times are expressed in the video track's timeline, in seconds.
+    ///
+    /// Segments are expected to be given in order. When they are not contiguous, captions still
+    /// displayed at the end of the previous one are ended and decoding restarts from scratch.
+    pub(crate) fn parse_media_segment(&mut self, data: &[u8]) -> Vec<(InstreamId, TextCue)> {
+        let mut cues = vec![];
+        let Some(track) = self.track.as_ref() else {
+            return cues;
+        };
+        let timescale = track.timescale as f64;
+        let mut samples_cc_data = isobmff::read_samples(data)
+            .into_iter()
+            .filter(|s| s.track_id == track.track_id)
+            .map(|s| {
+                let time = (s.decode_time as i64 + s.composition_offset) as f64 / timescale;
+                let mut cc_data = vec![];
+                extract_cc_data(s.data, track.is_hevc, &mut cc_data);
+                (time, cc_data)
+            })
+            .collect::<Vec<(f64, Vec<[u8; 3]>)>>();
+
+        // Captions are transported in decoding order but have to be interpreted in
+        // presentation order
+        samples_cc_data.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (time, cc_data) in samples_cc_data {
+            if let Some(last_time) = self.last_time {
+                if time < last_time || time > last_time + MAX_SAMPLE_INTERVAL {
+                    self.flush(last_time, &mut cues);
+                }
+            }
+            self.last_time = Some(time);
+            for [header, byte1, byte2] in cc_data {
+                match header & 0x03 {
+                    0 => self.cea608.push_pair(0, byte1, byte2),
+                    1 => self.cea608.push_pair(1, byte1, byte2),
+                    2 => self.cea708.push_pair(false, byte1, byte2),
+                    _ => self.cea708.push_pair(true, byte1, byte2),
+                }
+            }
+            self.update_displayed_rows(time, &mut cues);
+        }
+        cues
+    }
+
+    /// Report the changes made to the displayed captions of each channel at the given time.
+    fn update_displayed_rows(&mut self, time: f64, cues: &mut Vec<(InstreamId, TextCue)>) {
+        let changed_608_channels = (1..=4).filter_map(|channel| {
+            Some((InstreamId::Cea608(channel), self.cea608.change(channel)?))
+        });
+        let changed_708_services =
+            self.cea708
+                .changed_services()
+                .into_iter()
+                .filter_map(|service| {
+                    Some((InstreamId::Cea708(service), self.cea708.change(service)?))
+                });
+        let changes = changed_608_channels
+            .chain(changed_708_services)
+            .collect::<Vec<(InstreamId, DisplayChange)>>();
+        for (id, change) in changes {
+            let since = self.displayed.get(&id).map_or(f64::NEG_INFINITY, |d| d.0);
+            if change == DisplayChange::Minor && time - since < MIN_MINOR_CHANGE_INTERVAL {
+                continue;
+            }
+            let rows = match id {
+                InstreamId::Cea608(channel) => {
+                    self.cea608.acknowledge_change(channel);
+                    self.cea608.displayed_rows(channel)
+                }
+                InstreamId::Cea708(service) => {
+                    self.cea708.acknowledge_change(service);
+                    self.cea708.displayed_rows(service)
+                }
+            };
+            self.set_displayed_rows(id, time, rows, cues);
+        }
+    }
+
+    /// Indicate that the given `rows` are displayed for the channel `id` since `time`, ending
+    /// the cues previously displayed for it.
+    fn set_displayed_rows(
+        &mut self,
+        id: InstreamId,
+        time: f64,
+        rows: Vec<CaptionRow>,
+        cues: &mut Vec<(InstreamId, TextCue)>,
+    ) {
+        if let Some((start, prev_rows)) = self.displayed.remove(&id) {
+            if prev_rows == rows {
+                self.displayed.insert(id, (start, prev_rows));
+                return;
+            }
+            if time > start {
+                cues.extend(prev_rows.into_iter().map(|r| (id, r.into_cue(start, time))));
+            }
+        }
+        if !rows.is_empty() {
+            self.displayed.insert(id, (time, rows));
+        }
+    }
+
+    /// End all cues currently displayed at the given time and reset decoding state.
+    fn flush(&mut self, time: f64, cues: &mut Vec<(InstreamId, TextCue)>) {
+        let ids = self.displayed.keys().copied().collect::<Vec<InstreamId>>();
+        for id in ids {
+            self.set_displayed_rows(id, time, vec![], cues);
+        }
+        self.cea608 = Cea608Decoder::new();
+        self.cea708 = Cea708Decoder::new();
+    }
+}
+
+/// Push to `cc_data` the `cc_data_pkt` triplets (the byte containing `cc_valid` and `cc_type`,
+/// then the two `cc_data` bytes) of the valid closed captions data found in the given video
+/// sample.
+///
+/// Its NAL units are expected to be preceded by a 4-bytes length.
+fn extract_cc_data(sample: &[u8], is_hevc: bool, cc_data: &mut Vec<[u8; 3]>) {
+    let mut offset = 0;
+    while offset + 4 <= sample.len() {
+        let nal_length = u32::from_be_bytes([
+            sample[offset],
+            sample[offset + 1],
+            sample[offset + 2],
+            sample[offset + 3],
+        ]) as usize;
+        offset += 4;
+        let Some(nal) = sample.get(offset..offset + nal_length) else {
+            return;
+        };
+        offset += nal_length;
+        let sei_payload = match nal.first() {
+            // AVC SEI
+            Some(header) if !is_hevc && header & 0x1F == 6 => &nal[1..],
+            // HEVC prefix SEI
+            Some(header) if is_hevc && (header >> 1) & 0x3F == 39 => nal.get(2..).unwrap_or(&[]),
+            _ => continue,
+        };
+        let rbsp = remove_emulation_prevention_bytes(sei_payload);
+        parse_sei_messages(&rbsp, cc_data);
+    }
+}
+
+/// Parse the SEI messages found in the given SEI RBSP, pushing the closed captions data found in
+/// them to `cc_data`.
+fn parse_sei_messages(rbsp: &[u8], cc_data: &mut Vec<[u8; 3]>) {
+    let mut offset = 0;
+    // Stop at the RBSP trailing bits
+    while offset < rbsp.len() && rbsp[offset] != 0x80 {
+        let mut payload_type = 0;
+        while rbsp.get(offset) == Some(&0xFF) {
+            payload_type += 255;
+            offset += 1;
+        }
+        payload_type += *rbsp.get(offset).unwrap_or(&0) as usize;
+        offset += 1;
+        let mut payload_size = 0;
+        while rbsp.get(offset) == Some(&0xFF) {
+            payload_size += 255;
+            offset += 1;
+        }
+        payload_size += *rbsp.get(offset).unwrap_or(&0) as usize;
+        offset += 1;
+        let Some(payload) = rbsp.get(offset..offset + payload_size) else {
+            return;
+        };
+        offset += payload_size;
+
+        // user_data_registered_itu_t_t35
+        if payload_type == 4 {
+            parse_itu_t_t35_payload(payload, cc_data);
+        }
+    }
+}
+
+/// Parse a `user_data_registered_itu_t_t35` SEI payload, pushing to `cc_data` the closed
+/// captions data it contains if it is an ATSC A/53 one.
+fn parse_itu_t_t35_payload(payload: &[u8], cc_data: &mut Vec<[u8; 3]>) {
+    // country code (United States), provider code (ATSC), user identifier ("GA94") and
+    // user_data_type_code (cc_data)
+    if payload.len() < 10 || payload[..8] != [0xB5, 0x00, 0x31, b'G', b'A', b'9', b'4', 0x03] {
+        return;
+    }
+    let process_cc_data = payload[8] & 0x40 != 0;
+    if !process_cc_data {
+        return;
+    }
+    let cc_count = (payload[8] & 0x1F) as usize;
+    // Skip `em_data`
+    payload[10..]
+        .chunks_exact(3)
+        .take(cc_count)
+        .filter(|pkt| pkt[0] & 0x04 != 0)
+        .for_each(|pkt| cc_data.push([pkt[0], pkt[1], pkt[2]]));
+}
+
+/// Remove from a NAL unit payload the `emulation_prevention_three_byte`s, which are `0x03` bytes
+/// following two `0x00` bytes.
+fn remove_emulation_prevention_bytes(data: &[u8]) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(data.len());
+    let mut zeros = 0;
+    for &byte in data {
+        if zeros >= 2 && byte == 0x03 {
+            zeros = 0;
+            continue;
+        }
+        zeros = if byte == 0 { zeros + 1 } else { 0 };
+        ret.push(byte);
+    }
+    ret
+}
+
+/// Change made to the captions displayed for a channel since they were last reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum DisplayChange {
+    /// Characters were added to text already displayed, which is not worth creating new cues
+    /// for right away.
+    Minor,
+    /// Text was displayed, cleared or moved.
+    Major,
+}
+
+/// Style of a closed captions character that can be expressed through WebVTT markup.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) struct CaptionStyle {
+    /// WebVTT color class of the character, `None` for the default white.
+    pub(super) color: Option<&'static str>,
+    pub(super) italics: bool,
+    pub(super) underline: bool,
+}
+
+/// A single row of closed captions text, as displayed on screen.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct CaptionRow {
+    /// Position of the top of the row, as a percentage of the video's height.
+    pub(super) line: f64,
+    /// Horizontal position of the row, as a percentage of the video's width.
+    pub(super) position: f64,
+    /// Which part of the row `position` refers to.
+    pub(super) position_align: CuePositionAlign,
+    /// Text of the row, with WebVTT markup.
+    pub(super) text: String,
+}
+
+impl CaptionRow {
+    /// Create a `CaptionRow` from the given styled characters, returning `None` if it would
+    /// contain no visible character.
+    pub(super) fn from_chars(
+        line: f64,
+        position: f64,
+        position_align: CuePositionAlign,
+        chars: impl Iterator<Item = (char, CaptionStyle)>,
+    ) -> Option<Self> {
+        let mut text = String::new();
+        let mut curr_style = CaptionStyle::default();
+        let mut close_tags = String::new();
+        for (c, style) in chars {
+            if style != curr_style {
+                text.push_str(&close_tags);
+                close_tags.clear();
+                if let Some(color) = style.color {
+                    text.push_str(&format!("<c.{color}>"));
+                    close_tags.insert_str(0, "</c>");
+                }
+                if style.italics {
+                    text.push_str("<i>");
+                    close_tags.insert_str(0, "</i>");
+                }
+                if style.underline {
+                    text.push_str("<u>");
+                    close_tags.insert_str(0, "</u>");
+                }
+                curr_style = style;
+            }
+            match c {
+                '&' => text.push_str("&amp;"),
+                '<' => text.push_str("&lt;"),
+                '>' => text.push_str("&gt;"),
+                _ => text.push(c),
+            }
+        }
+        text.push_str(&close_tags);
+        if text.trim().is_empty() {
+            None
+        } else {
+            Some(Self {
+                line,
+                position,
+                position_align,
+                text,
+            })
+        }
+    }
+
+    fn into_cue(self, start: f64, end: f64) -> TextCue {
+        let settings = CueSettings {
+            line: Some(self.line),
+            snap_to_lines: false,
+            line_align: CueLineAlign::Start,
+            position: Some(self.position),
+            position_align: self.position_align,
+            align: match self.position_align {
+                CuePositionAlign::Center => CueAlign::Center,
+                CuePositionAlign::LineRight => CueAlign::End,
+                _ => CueAlign::Start,
+            },
+            ..CueSettings::default()
+        };
+        TextCue::new(None, start, end, settings, None, self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::isobmff::mp4_box;
+
+    /// Create an initialization segment declaring a single track with the given handler type,
+    /// sample entry type and a timescale of 90000.
+    fn init_segment(handler_type: &[u8; 4], sample_entry_type: &[u8; 4]) -> Vec<u8> {
+        let tkhd = [&[0; 12][..], &1u32.to_be_bytes(), &[0; 4]].concat();
+        let hdlr = [&[0; 8][..], handler_type, &[0; 12]].concat();
+        let mdhd = [&[0; 12][..], &90_000u32.to_be_bytes(), &[0; 8]].concat();
+        let stsd = [
+            &[0, 0, 0, 0, 0, 0, 0, 1][..],
+            &mp4_box(sample_entry_type, &[0; 78]),
+        ]
+        .concat();
+        let minf = mp4_box(b"minf", &mp4_box(b"stbl", &mp4_box(b"stsd", &stsd)));
+        let mdia = mp4_box(
+            b"mdia",
+            &[mp4_box(b"hdlr", &hdlr), mp4_box(b"mdhd", &mdhd), minf].concat(),
+        );
+        let trak = mp4_box(b"trak", &[mp4_box(b"tkhd", &tkhd), mdia].concat());
+        mp4_box(b"moov", &trak)
+    }
+
+    /// Create a media segment for the track `1`, whose samples are described by their decode
+    /// time, their composition offset and their data.
+    fn media_segment(samples: &[(u64, i32, Vec<u8>)]) -> Vec<u8> {
+        // tfhd with default-base-is-moof
+        let tfhd = [&[0, 0x02, 0, 0][..], &1u32.to_be_bytes()].concat();
+        let tfdt = [&[1, 0, 0, 0][..], &samples[0].0.to_be_bytes()].concat();
+        // trun version 1 with a data offset, sample durations, sizes and composition offsets
+        let mut trun = [
+            &[1, 0, 0x0B, 0x01][..],
+            &(samples.len() as u32).to_be_bytes(),
+        ]
+        .concat();
+        trun.extend_from_slice(&[0; 4]);
+        for (idx, (decode_time, composition_offset, data)) in samples.iter().enumerate() {
+            let next_decode_time = samples.get(idx + 1).map_or(decode_time + 3000, |s| s.0);
+            trun.extend_from_slice(&((next_decode_time - decode_time) as u32).to_be_bytes());
+            trun.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            trun.extend_from_slice(&composition_offset.to_be_bytes());
+        }
+        let traf = mp4_box(
+            b"traf",
+            &[
+                mp4_box(b"tfhd", &tfhd),
+                mp4_box(b"tfdt", &tfdt),
+                mp4_box(b"trun", &trun),
+            ]
+            .concat(),
+        );
+        let mut moof = mp4_box(b"moof", &traf);
+        let data_offset = (moof.len() + 8) as u32;
+        // moof header (8) + traf header (8) + tfhd box + tfdt box + trun header (8) + fields (8)
+        let data_offset_idx = 16 + (tfhd.len() + 8) + (tfdt.len() + 8) + 16;
+        moof[data_offset_idx..data_offset_idx + 4].copy_from_slice(&data_offset.to_be_bytes());
+        let mdat: Vec<u8> = samples.iter().flat_map(|s| s.2.clone()).collect();
+        [moof, mp4_box(b"mdat", &mdat)].concat()
+    }
+
+    /// Insert the `emulation_prevention_three_byte`s needed in a NAL unit payload.
+    fn add_emulation_prevention_bytes(data: &[u8]) -> Vec<u8> {
+        let mut ret = vec![];
+        let mut zeros = 0;
+        for &byte in data {
+            if zeros >= 2 && byte <= 0x03 {
+                ret.push(0x03);
+                zeros = 0;
+            }
+            zeros = if byte == 0 { zeros + 1 } else { 0 };
+            ret.push(byte);
+        }
+        ret
+    }
+
+    /// Create an AVC sample made of a single SEI NAL unit containing the given `cc_data`
+    /// triplets.
+    fn avc_sample_with_cc_data(triplets: &[[u8; 3]]) -> Vec<u8> {
+        let mut payload = vec![0xB5, 0x00, 0x31, b'G', b'A', b'9', b'4', 0x03];
+        payload.push(0x40 | triplets.len() as u8);
+        payload.push(0xFF);
+        triplets.iter().for_each(|t| payload.extend_from_slice(t));
+        payload.push(0xFF);
+
+        let mut rbsp = vec![0x04, payload.len() as u8];
+        rbsp.extend(payload);
+        rbsp.push(0x80);
+        let mut nal = vec![0x06];
+        nal.extend(add_emulation_prevention_bytes(&rbsp));
+
+        let mut sample = (nal.len() as u32).to_be_bytes().to_vec();
+        sample.extend(nal);
+        sample
+    }
+
+    #[test]
+    fn test_cea608_pop_on_captions() {
+        let triplets = [
+            // Invalid CEA-708 padding, whose zeros lead to an emulation prevention byte
+            [0xF8, 0x00, 0x00],
+            // Resume Caption Loading, sent twice as is usual for control codes
+            [0xFC, 0x14, 0x20],
+            [0xFC, 0x14, 0x20],
+            // Preamble Address Code: row 15, column 0, white
+            [0xFC, 0x14, 0x60],
+            [0xFC, b'H', b'I'],
+            [0xFC, b'!', 0x80],
+            // End Of Caption
+            [0xFC, 0x14, 0x2F],
+        ];
+        let sample = avc_sample_with_cc_data(&triplets);
+        let mut cc_data = vec![];
+        extract_cc_data(&sample, false, &mut cc_data);
+        assert_eq!(cc_data, triplets[1..]);
+
+        let mut decoder = ClosedCaptionsDecoder::new();
+        let mut cues = vec![];
+        for [_, byte1, byte2] in cc_data {
+            decoder.cea608.push_pair(0, byte1, byte2);
+        }
+        decoder.update_displayed_rows(1., &mut cues);
+        assert!(cues.is_empty());
+
+        // Erase Displayed Memory
+        decoder.cea608.push_pair(0, 0x14, 0x2C);
+        decoder.update_displayed_rows(3.5, &mut cues);
+        assert_eq!(cues.len(), 1);
+        let (channel, cue) = &cues[0];
+        assert_eq!(*channel, InstreamId::Cea608(1));
+        assert_eq!(cue.start(), 1.);
+        assert_eq!(cue.end(), 3.5);
+        assert_eq!(cue.text(), "HI!");
+        assert_eq!(cue.settings().line, Some(10. + 14. * 80. / 15.));
+        assert_eq!(cue.settings().position, Some(10.));
+    }
+
+    #[test]
+    fn test_cea708_window() {
+        // Packet header (6 * 2 bytes), service block header (service 1, 10 bytes), then
+        // DefineWindow0 (visible, anchored by its center at 50% / 50%, 1 row), "Hi" and ETX
+        let packet = [
+            0x06, 0x2A, 0x98, 0x20, 0xB2, 0x32, 0x40, 0x1F, 0x00, b'H', b'i', 0x03,
+        ];
+        let mut decoder = ClosedCaptionsDecoder::new();
+        let mut cues = vec![];
+        for (idx, pair) in packet.chunks_exact(2).enumerate() {
+            decoder.cea708.push_pair(idx == 0, pair[0], pair[1]);
+        }
+        decoder.update_displayed_rows(2., &mut cues);
+        decoder.flush(4., &mut cues);
+        assert_eq!(cues.len(), 1);
+        let (channel, cue) = &cues[0];
+        assert_eq!(*channel, InstreamId::Cea708(1));
+        assert_eq!((cue.start(), cue.end()), (2., 4.));
+        assert_eq!(cue.text(), "Hi");
+        assert_eq!(cue.settings().position, Some(50.));
+        assert_eq!(cue.settings().position_align, CuePositionAlign::Center);
+        assert_eq!(cue.settings().align, CueAlign::Center);
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        let mut decoder = ClosedCaptionsDecoder::new();
+        assert!(!decoder.parse_init_segment(&init_segment(b"soun", b"mp4a")));
+        assert!(!decoder.parse_init_segment(&init_segment(b"vide", b"av01")));
+        assert!(decoder.parse_init_segment(&init_segment(b"vide", b"avc1")));
+
+        let load_caption = |text: &[u8; 2]| {
+            avc_sample_with_cc_data(&[
+                // Resume Caption Loading, Preamble Address Code for row 15, then the text
+                [0xFC, 0x14, 0x20],
+                [0xFC, 0x14, 0x20],
+                [0xFC, 0x14, 0x60],
+                [0xFC, text[0], text[1]],
+            ])
+        };
+        let end_of_caption = avc_sample_with_cc_data(&[[0xFC, 0x14, 0x2F]]);
+        let empty_sample = avc_sample_with_cc_data(&[]);
+
+        // Samples are given in decoding order but interpreted in presentation order: the
+        // caption is loaded by the second one then displayed by the first one
+        let segment = media_segment(&[
+            (900_000, 3000, end_of_caption.clone()),
+            (903_000, -3000, load_caption(b"HI")),
+            (906_000, 0, empty_sample.clone()),
+        ]);
+        assert!(decoder.parse_media_segment(&segment).is_empty());
+
+        // A new caption replaces the displayed one
+        let segment = media_segment(&[
+            (909_000, 0, load_caption(b"OK")),
+            (912_000, 0, end_of_caption),
+            (918_000, 0, empty_sample.clone()),
+        ]);
+        let cues = decoder.parse_media_segment(&segment);
+        assert_eq!(cues.len(), 1);
+        assert_eq!(cues[0].0, InstreamId::Cea608(1));
+        assert_eq!(cues[0].1.text(), "HI");
+        assert_eq!(cues[0].1.start(), 903_000. / 90_000.);
+        assert_eq!(cues[0].1.end(), 912_000. / 90_000.);
+
+        // Non-contiguous segments end the displayed captions at the last sample's time
+        let segment = media_segment(&[(9_000_000, 0, empty_sample)]);
+        let cues = decoder.parse_media_segment(&segment);
+        assert_eq!(cues.len(), 1);
+        assert_eq!(cues[0].1.text(), "OK");
+        assert_eq!(cues[0].1.start(), 912_000. / 90_000.);
+        assert_eq!(cues[0].1.end(), 918_000. / 90_000.);
+    }
+}
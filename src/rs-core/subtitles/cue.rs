@@ -0,0 +1,179 @@
+/// A single subtitles cue: some text to display between two positions, with optional indications
+/// on how it should be laid out.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TextCue {
+    /// Optional identifier of that cue in the segment it was found in.
+    id: Option<String>,
+    /// Time at which the cue should begin to be displayed, in seconds.
+    start: f64,
+    /// Time at which the cue should stop being displayed, in seconds.
+    end: f64,
+    /// Layout indications for that cue.
+    settings: CueSettings,
+    /// The region this cue should be displayed in, if one.
+    region: Option<CueRegion>,
+    /// Text of the cue, which may contain WebVTT markup (e.g. `<b>`, `<i>` or `<v Speaker>`
+    /// tags).
+    text: String,
+}
+
+impl TextCue {
+    pub(super) fn new(
+        id: Option<String>,
+        start: f64,
+        end: f64,
+        settings: CueSettings,
+        region: Option<CueRegion>,
+        text: String,
+    ) -> Self {
+        Self {
+            id,
+            start,
+            end,
+            settings,
+            region,
+            text,
+        }
+    }
+
+    /// Optional identifier of that cue in the segment it was found in.
+    pub(crate) fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Time at which the cue should begin to be displayed, in seconds.
+    pub(crate) fn start(&self) -> f64 {
+        self.start
+    }
+
+    /// Time at which the cue should stop being displayed, in seconds.
+    pub(crate) fn end(&self) -> f64 {
+        self.end
+    }
+
+    /// Update the times at which the cue should begin and stop being displayed, in seconds.
+    pub(super) fn set_times(&mut self, start: f64, end: f64) {
+        self.start = start;
+        self.end = end;
+    }
+
+    /// Layout indications for that cue.
+    pub(crate) fn settings(&self) -> &CueSettings {
+        &self.settings
+    }
+
+    /// The region this cue should be displayed in, if one.
+    pub(crate) fn region(&self) -> Option<&CueRegion> {
+        self.region.as_ref()
+    }
+
+    /// Text of the cue, which may contain WebVTT markup.
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Layout indications for a `TextCue`, with the same semantics than WebVTT cue settings.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CueSettings {
+    /// Writing direction of the cue.
+    pub(crate) vertical: CueVertical,
+    /// Offset of the cue on the line axis, `None` for an automatic placement.
+    pub(crate) line: Option<f64>,
+    /// If `true`, `line` is a number of lines. If `false`, it is a percentage of the video.
+    pub(crate) snap_to_lines: bool,
+    /// Which part of the cue `line` refers to.
+    pub(crate) line_align: CueLineAlign,
+    /// Indentation of the cue, as a percentage of the video, `None` for an automatic placement.
+    pub(crate) position: Option<f64>,
+    /// Which part of the cue `position` refers to.
+    pub(crate) position_align: CuePositionAlign,
+    /// Size of the cue box, as a percentage of the video.
+    pub(crate) size: f64,
+    /// Alignment of the text inside the cue box.
+    pub(crate) align: CueAlign,
+}
+
+impl Default for CueSettings {
+    fn default() -> Self {
+        Self {
+            vertical: CueVertical::Horizontal,
+            line: None,
+            snap_to_lines: true,
+            line_align: CueLineAlign::Start,
+            position: None,
+            position_align: CuePositionAlign::Auto,
+            size: 100.,
+            align: CueAlign::Center,
+        }
+    }
+}
+
+/// Writing direction of a `TextCue`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CueVertical {
+    Horizontal = 0,
+    RightToLeft = 1,
+    LeftToRight = 2,
+}
+
+/// Part of a `TextCue` the `line` setting refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CueLineAlign {
+    Start = 0,
+    Center = 1,
+    End = 2,
+}
+
+/// Part of a `TextCue` the `position` setting refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CuePositionAlign {
+    Auto = 0,
+    LineLeft = 1,
+    Center = 2,
+    LineRight = 3,
+}
+
+/// Alignment of the text inside a `TextCue`'s box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CueAlign {
+    Start = 0,
+    Center = 1,
+    End = 2,
+    Left = 3,
+    Right = 4,
+}
+
+/// A rectangular area of the video in which `TextCue`s can be displayed, with the same semantics
+/// than a WebVTT region.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CueRegion {
+    /// Identifier of that region.
+    pub(crate) id: String,
+    /// Width of the region, as a percentage of the video.
+    pub(crate) width: f64,
+    /// Height of the region, as a number of lines.
+    pub(crate) lines: u32,
+    /// Point of the region, as percentages of its width and height, which is anchored to
+    /// `viewport_anchor`.
+    pub(crate) region_anchor: (f64, f64),
+    /// Point of the video, as percentages of its width and height, the region is anchored to.
+    pub(crate) viewport_anchor: (f64, f64),
+    /// If `true`, cues in that region scroll up when new ones are added.
+    pub(crate) scroll_up: bool,
+}
+
+impl CueRegion {
+    /// Create a `CueRegion` with the given `id` and all other properties set to their default
+    /// value.
+    pub(super) fn new(id: String) -> Self {
+        Self {
+            id,
+            width: 100.,
+            lines: 3,
+            region_anchor: (0., 100.),
+            viewport_anchor: (0., 100.),
+            scroll_up: false,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+mod cea608;
+mod cea708;
+mod closed_captions;
+mod cue;
+mod ttml;
+mod webvtt;
+mod xml;
+
+pub(crate) use closed_captions::ClosedCaptionsDecoder;
+pub(crate) use cue::{
+    CueAlign, CueLineAlign, CuePositionAlign, CueRegion, CueSettings, CueVertical, TextCue,
+};
+pub(crate) use ttml::StppSegment;
+pub(crate) use webvtt::WebVttSegment;
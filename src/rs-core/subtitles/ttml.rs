@@ -0,0 +1,781 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use super::xml::{parse_xml, XmlElement, XmlNode, XmlParsingError};
+use super::{CueAlign, CueLineAlign, CuePositionAlign, CueSettings, CueVertical, TextCue};
+use crate::utils::isobmff;
+
+/// Styling attributes (without their `tts:` prefix) taken into account when converting TTML
+/// content into `TextCue`s.
+const STYLING_ATTRIBUTES: [&str; 10] = [
+    "color",
+    "backgroundColor",
+    "fontStyle",
+    "fontWeight",
+    "textDecoration",
+    "textAlign",
+    "displayAlign",
+    "origin",
+    "extent",
+    "writingMode",
+];
+
+/// Maximum depth of style references (a style referencing another style) followed before giving
+/// up, mainly to protect against reference loops.
+const MAX_STYLE_REFERENCE_DEPTH: u32 = 8;
+
+/// Colors of WebVTT's default text color classes (e.g. `<c.yellow>`), the only ones that can be
+/// relied on when using the browser's native rendering.
+const WEBVTT_COLOR_CLASSES: [(&str, (u8, u8, u8)); 8] = [
+    ("white", (255, 255, 255)),
+    ("lime", (0, 255, 0)),
+    ("cyan", (0, 255, 255)),
+    ("red", (255, 0, 0)),
+    ("yellow", (255, 255, 0)),
+    ("magenta", (255, 0, 255)),
+    ("blue", (0, 0, 255)),
+    ("black", (0, 0, 0)),
+];
+
+/// A parsed fragmented MP4 segment carrying IMSC1 subtitles, which are TTML documents stored as
+/// samples (announced by the `stpp.ttml.im1t` codec).
+#[derive(Debug)]
+pub(crate) struct StppSegment {
+    /// Cues found in that segment, in their order of appearance. Their times are expressed in
+    /// the track's timeline, in seconds.
+    cues: Vec<TextCue>,
+}
+
+impl StppSegment {
+    /// Parse the fragmented MP4 segment contained in `data`, for a track whose timescale (as
+    /// found in its initialization segment) is `timescale`.
+    ///
+    /// Cue timing is bounded by the timing of the sample they have been found in, as indicated
+    /// by the `tfdt` and `trun` boxes of the segment.
+    pub(crate) fn parse(data: &[u8], timescale: u32) -> Result<Self, TtmlParsingError> {
+        let samples = isobmff::read_samples(data);
+        if samples.is_empty() {
+            return Err(TtmlParsingError::NoSample);
+        }
+        let timescale = timescale as f64;
+        let mut cues = vec![];
+        for sample in samples {
+            let sample_start = sample.decode_time as f64 / timescale;
+            let sample_end = if sample.duration > 0 {
+                sample_start + sample.duration as f64 / timescale
+            } else {
+                f64::INFINITY
+            };
+            let mut sample_cues = parse_ttml(&String::from_utf8_lossy(sample.data))?;
+
+            // TTML times should be expressed in the track's timeline, yet some packagers express
+            // them relative to the sample instead, which we detect when they would all end before
+            // the sample even begins.
+            let is_sample_relative = sample_start > 0.
+                && !sample_cues.is_empty()
+                && sample_cues.iter().all(|c| c.end() <= sample_start);
+            let offset = if is_sample_relative { sample_start } else { 0. };
+            for cue in sample_cues.iter_mut() {
+                let start = f64::max(cue.start() + offset, sample_start);
+                let end = f64::min(cue.end() + offset, sample_end);
+                cue.set_times(start, end);
+            }
+            cues.extend(
+                sample_cues
+                    .into_iter()
+                    .filter(|c| c.end().is_finite() && c.end() > c.start()),
+            );
+        }
+        Ok(Self { cues })
+    }
+
+    /// Cues found in that segment, in their order of appearance.
+    ///
+    /// Their times are expressed in the track's timeline, in seconds.
+    pub(crate) fn cues(&self) -> &[TextCue] {
+        &self.cues
+    }
+}
+
+/// Parameters defined on a TTML document's root element needed to interpret its time
+/// expressions.
+struct TimingParameters {
+    /// Effective frame rate, in frames per second.
+    frame_rate: f64,
+    /// Number of sub-frames per frame.
+    sub_frame_rate: f64,
+    /// Number of ticks per second.
+    tick_rate: f64,
+}
+
+impl TimingParameters {
+    fn from_root(root: &XmlElement) -> Self {
+        let declared_frame_rate = root
+            .attribute("frameRate")
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|r| *r > 0.);
+        let frame_rate_multiplier = root
+            .attribute("frameRateMultiplier")
+            .and_then(|v| {
+                let mut values = v.split_whitespace().map(|n| n.parse::<f64>().ok());
+                Some(values.next()?? / values.next()??)
+            })
+            .filter(|m| m.is_finite() && *m > 0.)
+            .unwrap_or(1.);
+        let sub_frame_rate = root
+            .attribute("subFrameRate")
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|r| *r > 0.)
+            .unwrap_or(1.);
+        let tick_rate = root
+            .attribute("tickRate")
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|r| *r > 0.)
+            .unwrap_or_else(|| declared_frame_rate.map_or(1., |f| f * sub_frame_rate));
+        Self {
+            frame_rate: declared_frame_rate.unwrap_or(30.) * frame_rate_multiplier,
+            sub_frame_rate,
+            tick_rate,
+        }
+    }
+
+    /// Parse a TTML time expression, either a clock time (e.g. `"00:00:01.500"` or
+    /// `"00:00:01:12"`) or an offset time (e.g. `"1.5s"` or `"36f"`), into seconds.
+    ///
+    /// Returns `None` if it could not be parsed.
+    fn parse_time_expression(&self, expr: &str) -> Option<f64> {
+        let expr = expr.trim();
+        if expr.contains(':') {
+            let parts = expr.split(':').collect::<Vec<&str>>();
+            if parts.len() != 3 && parts.len() != 4 {
+                return None;
+            }
+            let hours = parts[0].parse::<u32>().ok()? as f64;
+            let minutes = parts[1].parse::<u32>().ok()? as f64;
+            let seconds = parts[2].parse::<f64>().ok()?;
+            let frames = match parts.get(3) {
+                Some(frames) => {
+                    let (frames, sub_frames) = frames.split_once('.').unwrap_or((frames, "0"));
+                    frames.parse::<u32>().ok()? as f64
+                        + sub_frames.parse::<u32>().ok()? as f64 / self.sub_frame_rate
+                }
+                None => 0.,
+            };
+            return Some(hours * 3600. + minutes * 60. + seconds + frames / self.frame_rate);
+        }
+        let metric_idx = expr.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let value = expr[..metric_idx].parse::<f64>().ok()?;
+        match &expr[metric_idx..] {
+            "h" => Some(value * 3600.),
+            "m" => Some(value * 60.),
+            "s" => Some(value),
+            "ms" => Some(value / 1000.),
+            "f" => Some(value / self.frame_rate),
+            "t" => Some(value / self.tick_rate),
+            _ => None,
+        }
+    }
+}
+
+/// Styling attributes applying to an element, per their name (without its `tts:` prefix).
+type StyleSet<'a> = HashMap<&'a str, &'a str>;
+
+/// Information shared while converting the content of a single TTML document into cues.
+struct TtmlContext<'a> {
+    timing: TimingParameters,
+    /// Width and height in pixels of the root container, if declared, to convert pixel lengths.
+    root_extent: Option<(f64, f64)>,
+    /// `style` elements declared in the document's `head`, per their `xml:id`.
+    styles: HashMap<&'a str, &'a XmlElement>,
+    /// Styles applying to each `region` declared in the document's `head`, per their `xml:id`.
+    regions: HashMap<&'a str, StyleSet<'a>>,
+}
+
+/// Parse the given TTML document into `TextCue`s.
+///
+/// Cues whose end is not known have it set to an infinite value.
+fn parse_ttml(text: &str) -> Result<Vec<TextCue>, TtmlParsingError> {
+    let root = parse_xml(text)?;
+    if root.local_name() != "tt" {
+        return Err(TtmlParsingError::NotTtml);
+    }
+    let root_extent = root.attribute("extent").and_then(|extent| {
+        let mut values = extent.split_whitespace().map(parse_pixels);
+        Some((values.next()??, values.next()??))
+    });
+    let mut ctx = TtmlContext {
+        timing: TimingParameters::from_root(&root),
+        root_extent,
+        styles: HashMap::new(),
+        regions: HashMap::new(),
+    };
+    for head in root.child_elements("head") {
+        for styling in head.child_elements("styling") {
+            for style in styling.child_elements("style") {
+                if let Some(id) = style.attribute("id") {
+                    ctx.styles.insert(id, style);
+                }
+            }
+        }
+    }
+    for head in root.child_elements("head") {
+        for layout in head.child_elements("layout") {
+            for region in layout.child_elements("region") {
+                if let Some(id) = region.attribute("id") {
+                    let mut style_set = StyleSet::new();
+                    for style in region.child_elements("style") {
+                        ctx.apply_styles(style, &mut style_set, 0);
+                    }
+                    ctx.apply_styles(region, &mut style_set, 0);
+                    ctx.regions.insert(id, style_set);
+                }
+            }
+        }
+    }
+
+    let mut cues = vec![];
+    if let Some(body) = root.child_elements("body").next() {
+        ctx.parse_content(body, 0., f64::INFINITY, None, &StyleSet::new(), &mut cues);
+    }
+    Ok(cues)
+}
+
+impl<'a> TtmlContext<'a> {
+    /// Add to `style_set` the styling attributes applying to `element`: first those of the styles
+    /// it references through its `style` attribute, then its own.
+    fn apply_styles(&self, element: &'a XmlElement, style_set: &mut StyleSet<'a>, depth: u32) {
+        if depth > MAX_STYLE_REFERENCE_DEPTH {
+            return;
+        }
+        if let Some(style_refs) = element.attribute("style") {
+            for style_ref in style_refs.split_whitespace() {
+                if let Some(style) = self.styles.get(style_ref) {
+                    self.apply_styles(style, style_set, depth + 1);
+                }
+            }
+        }
+        for name in STYLING_ATTRIBUTES {
+            if let Some(value) = element.attribute(name) {
+                style_set.insert(name, value.trim());
+            }
+        }
+    }
+
+    /// Convert into cues the `p` elements found in `element`, a `body`, `div` or `p` element,
+    /// whose parent's active interval goes from `parent_begin` to `parent_end` in seconds.
+    fn parse_content(
+        &self,
+        element: &'a XmlElement,
+        parent_begin: f64,
+        parent_end: f64,
+        parent_region: Option<&'a str>,
+        parent_style: &StyleSet<'a>,
+        cues: &mut Vec<TextCue>,
+    ) {
+        let time_attr = |name| {
+            element
+                .attribute(name)
+                .and_then(|v| self.timing.parse_time_expression(v))
+        };
+        let begin = parent_begin + time_attr("begin").unwrap_or(0.);
+        let end = match (time_attr("end"), time_attr("dur")) {
+            (Some(end), _) => parent_begin + end,
+            (None, Some(dur)) => begin + dur,
+            (None, None) => parent_end,
+        };
+        let end = f64::min(end, parent_end);
+        let region = element.attribute("region").or(parent_region);
+        let style = self.compute_style(element, parent_style);
+
+        if element.local_name() != "p" {
+            for child in element.children() {
+                if let XmlNode::Element(child) = child {
+                    if matches!(child.local_name(), "div" | "p") {
+                        self.parse_content(child, begin, end, region, &style, cues);
+                    }
+                }
+            }
+            return;
+        }
+        if end <= begin {
+            return;
+        }
+
+        let region_style = region.and_then(|r| self.regions.get(r));
+        let mut cue_style = region_style.cloned().unwrap_or_default();
+        cue_style.extend(style.iter());
+        let settings = self.compute_cue_settings(&cue_style);
+
+        let mut text = String::new();
+        let base_decoration = TextDecoration::from_style(&style);
+        let (open_tags, close_tags) = base_decoration.tags(&TextDecoration::default());
+        text.push_str(&open_tags);
+        let mut whitespace_state = WhitespaceState::default();
+        self.render_text(
+            element,
+            &style,
+            &base_decoration,
+            &mut text,
+            &mut whitespace_state,
+        );
+        text.push_str(&close_tags);
+        let id = element.attribute("id").map(|id| id.to_owned());
+        cues.push(TextCue::new(id, begin, end, settings, None, text));
+    }
+
+    /// Compute the styles applying to `element`, whose parent's styles are `parent_style`.
+    fn compute_style(&self, element: &'a XmlElement, parent_style: &StyleSet<'a>) -> StyleSet<'a> {
+        let mut style = parent_style.clone();
+        // `backgroundColor` is the only one of the considered properties not being inherited
+        style.remove("backgroundColor");
+        self.apply_styles(element, &mut style, 0);
+        style
+    }
+
+    /// Push to `text`, with WebVTT markup, the text content of `element`, a `p` or `span` element
+    /// whose computed styles are `style` and whose text decoration is `decoration`.
+    ///
+    /// Whitespaces are collapsed, and removed at the start and end of lines.
+    fn render_text(
+        &self,
+        element: &'a XmlElement,
+        style: &StyleSet<'a>,
+        decoration: &TextDecoration,
+        text: &mut String,
+        whitespace_state: &mut WhitespaceState,
+    ) {
+        for child in element.children() {
+            match child {
+                XmlNode::Text(content) => {
+                    for c in content.chars() {
+                        if c.is_whitespace() {
+                            whitespace_state.has_pending_space = !whitespace_state.is_line_start;
+                            continue;
+                        }
+                        if whitespace_state.has_pending_space {
+                            text.push(' ');
+                        }
+                        *whitespace_state = WhitespaceState {
+                            is_line_start: false,
+                            has_pending_space: false,
+                        };
+                        match c {
+                            '&' => text.push_str("&amp;"),
+                            '<' => text.push_str("&lt;"),
+                            '>' => text.push_str("&gt;"),
+                            _ => text.push(c),
+                        }
+                    }
+                }
+                XmlNode::Element(child) => match child.local_name() {
+                    "br" => {
+                        text.push('\n');
+                        *whitespace_state = WhitespaceState::default();
+                    }
+                    "span" => {
+                        let span_style = self.compute_style(child, style);
+                        let span_decoration = TextDecoration::from_style(&span_style);
+                        let (open_tags, close_tags) = span_decoration.tags(decoration);
+                        text.push_str(&open_tags);
+                        self.render_text(
+                            child,
+                            &span_style,
+                            &span_decoration,
+                            text,
+                            whitespace_state,
+                        );
+                        text.push_str(&close_tags);
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    /// Convert the positioning styles applying to a cue into `CueSettings`.
+    ///
+    /// The cue box is placed where its region would be; other settings keep their default
+    /// values when not specified.
+    fn compute_cue_settings(&self, style: &StyleSet) -> CueSettings {
+        let vertical = match style.get("writingMode").copied() {
+            Some("tbrl" | "tb") => CueVertical::RightToLeft,
+            Some("tblr") => CueVertical::LeftToRight,
+            _ => CueVertical::Horizontal,
+        };
+        let align = match style.get("textAlign").copied() {
+            Some("left") => CueAlign::Left,
+            Some("right") => CueAlign::Right,
+            Some("start") => CueAlign::Start,
+            Some("end") => CueAlign::End,
+            _ => CueAlign::Center,
+        };
+        let mut settings = CueSettings {
+            vertical,
+            align,
+            ..CueSettings::default()
+        };
+
+        let origin = style.get("origin").and_then(|o| self.parse_length_pair(o));
+        let extent = style.get("extent").and_then(|e| self.parse_length_pair(e));
+        let (Some((x, y)), Some((width, height))) = (origin, extent) else {
+            return settings;
+        };
+        // Lengths on the line axis, where lines are stacked, then on the position axis
+        let ((line_start, line_length), (position, size)) = match settings.vertical {
+            CueVertical::Horizontal => ((y, height), (x, width)),
+            CueVertical::RightToLeft | CueVertical::LeftToRight => ((x, width), (y, height)),
+        };
+        settings.snap_to_lines = false;
+        settings.position = Some(position);
+        settings.position_align = CuePositionAlign::LineLeft;
+        settings.size = size;
+        (settings.line, settings.line_align) = match style.get("displayAlign").copied() {
+            Some("center") => (Some(line_start + line_length / 2.), CueLineAlign::Center),
+            Some("after") => (Some(line_start + line_length), CueLineAlign::End),
+            _ => (Some(line_start), CueLineAlign::Start),
+        };
+        settings
+    }
+
+    /// Parse a pair of TTML lengths (e.g. `"10% 80%"` or `"192px 864px"`) into percentages of
+    /// the root container's width and height.
+    ///
+    /// Returns `None` if it could not be parsed or if pixel lengths are used without the root
+    /// container's extent being known.
+    fn parse_length_pair(&self, value: &str) -> Option<(f64, f64)> {
+        let mut values = value.split_whitespace();
+        let first = values.next()?;
+        let second = values.next()?;
+        let parse_length = |value: &str, root_length: Option<f64>| {
+            if let Some(percentage) = value.strip_suffix('%') {
+                percentage.parse::<f64>().ok()
+            } else {
+                Some(parse_pixels(value)? / root_length? * 100.)
+            }
+        };
+        Some((
+            parse_length(first, self.root_extent.map(|e| e.0))?,
+            parse_length(second, self.root_extent.map(|e| e.1))?,
+        ))
+    }
+}
+
+/// Whitespace handling state while rendering the text of a TTML `p` element.
+struct WhitespaceState {
+    /// If `true`, no character has been rendered yet on the current line.
+    is_line_start: bool,
+    /// If `true`, whitespaces have been encountered since the last rendered character, which
+    /// have to be rendered as a single space if other characters follow on the same line.
+    has_pending_space: bool,
+}
+
+impl Default for WhitespaceState {
+    fn default() -> Self {
+        Self {
+            is_line_start: true,
+            has_pending_space: false,
+        }
+    }
+}
+
+/// Parse a length in pixels (e.g. `"1920px"`).
+fn parse_pixels(value: &str) -> Option<f64> {
+    value
+        .strip_suffix("px")?
+        .parse::<f64>()
+        .ok()
+        .filter(|v| *v > 0.)
+}
+
+/// Text styling that can be expressed through WebVTT markup.
+#[derive(Default, PartialEq)]
+struct TextDecoration {
+    /// WebVTT color class corresponding to the text's color.
+    color: Option<&'static str>,
+    /// WebVTT color class corresponding to the text's background color.
+    background_color: Option<&'static str>,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl TextDecoration {
+    fn from_style(style: &StyleSet) -> Self {
+        let class_of = |name| {
+            style
+                .get(name)
+                .and_then(|c| parse_color(c))
+                .and_then(color_class)
+        };
+        Self {
+            color: class_of("color"),
+            background_color: class_of("backgroundColor"),
+            bold: style.get("fontWeight") == Some(&"bold"),
+            italic: matches!(style.get("fontStyle").copied(), Some("italic" | "oblique")),
+            underline: style
+                .get("textDecoration")
+                .is_some_and(|d| d.split_whitespace().any(|d| d == "underline")),
+        }
+    }
+
+    /// Returns the WebVTT tags to respectively open and close to apply that decoration to text
+    /// whose parent has the `parent` decoration.
+    fn tags(&self, parent: &TextDecoration) -> (String, String) {
+        let mut open_tags = String::new();
+        let mut close_tags = String::new();
+        let mut classes = String::new();
+        if self.color.is_some() && self.color != parent.color {
+            classes.push('.');
+            classes.push_str(self.color.unwrap_or_default());
+        }
+        if self.background_color.is_some() && self.background_color != parent.background_color {
+            classes.push_str(".bg_");
+            classes.push_str(self.background_color.unwrap_or_default());
+        }
+        if !classes.is_empty() {
+            open_tags.push_str(&format!("<c{classes}>"));
+            close_tags.insert_str(0, "</c>");
+        }
+        [
+            (self.bold && !parent.bold, "b"),
+            (self.italic && !parent.italic, "i"),
+            (self.underline && !parent.underline, "u"),
+        ]
+        .into_iter()
+        .filter(|(apply, _)| *apply)
+        .for_each(|(_, tag)| {
+            open_tags.push_str(&format!("<{tag}>"));
+            close_tags.insert_str(0, &format!("</{tag}>"));
+        });
+        (open_tags, close_tags)
+    }
+}
+
+/// Parse a TTML color (e.g. `"#FFFF00"`, `"#FFFF00FF"`, `"rgba(255,255,0,255)"` or `"yellow"`)
+/// into its red, green, blue and alpha components.
+fn parse_color(value: &str) -> Option<(u8, u8, u8, u8)> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        let component = |idx: usize| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok();
+        return match hex.len() {
+            6 => Some((component(0)?, component(2)?, component(4)?, 255)),
+            8 => Some((component(0)?, component(2)?, component(4)?, component(6)?)),
+            _ => None,
+        };
+    }
+    let functional = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("));
+    if let Some(args) = functional.and_then(|f| f.strip_suffix(')')) {
+        let components = args
+            .split(',')
+            .map(|c| c.trim().parse::<u8>().ok())
+            .collect::<Option<Vec<u8>>>()?;
+        return match components[..] {
+            [r, g, b] => Some((r, g, b, 255)),
+            [r, g, b, a] => Some((r, g, b, a)),
+            _ => None,
+        };
+    }
+    let (r, g, b, a) = match value {
+        "transparent" => (0, 0, 0, 0),
+        "black" => (0, 0, 0, 255),
+        "silver" => (192, 192, 192, 255),
+        "gray" => (128, 128, 128, 255),
+        "white" => (255, 255, 255, 255),
+        "maroon" => (128, 0, 0, 255),
+        "red" => (255, 0, 0, 255),
+        "purple" => (128, 0, 128, 255),
+        "fuchsia" | "magenta" => (255, 0, 255, 255),
+        "green" => (0, 128, 0, 255),
+        "lime" => (0, 255, 0, 255),
+        "olive" => (128, 128, 0, 255),
+        "yellow" => (255, 255, 0, 255),
+        "navy" => (0, 0, 128, 255),
+        "blue" => (0, 0, 255, 255),
+        "teal" => (0, 128, 128, 255),
+        "aqua" | "cyan" => (0, 255, 255, 255),
+        _ => return None,
+    };
+    Some((r, g, b, a))
+}
+
+/// Returns the WebVTT color class closest to the given color, or `None` if it is fully
+/// transparent.
+fn color_class((r, g, b, a): (u8, u8, u8, u8)) -> Option<&'static str> {
+    if a == 0 {
+        return None;
+    }
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        [(r, cr), (g, cg), (b, cb)]
+            .into_iter()
+            .map(|(x, y)| (x as i32 - y as i32).pow(2))
+            .sum::<i32>()
+    };
+    WEBVTT_COLOR_CLASSES
+        .iter()
+        .min_by_key(|(_, color)| distance(*color))
+        .map(|(name, _)| *name)
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum TtmlParsingError {
+    #[error("No sample could be found in the segment")]
+    NoSample,
+    #[error("Invalid XML: {0}")]
+    InvalidXml(#[from] XmlParsingError),
+    #[error("The document's root element is not a `tt` element")]
+    NotTtml,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::isobmff::mp4_box;
+
+    #[test]
+    fn test_parse_time_expression() {
+        let root = parse_xml(r#"<tt ttp:frameRate="25" ttp:tickRate="10000000"/>"#).unwrap();
+        let timing = TimingParameters::from_root(&root);
+        assert_eq!(timing.parse_time_expression("00:01:02.500"), Some(62.5));
+        assert_eq!(timing.parse_time_expression("01:00:00:05"), Some(3600.2));
+        assert_eq!(timing.parse_time_expression("1.5s"), Some(1.5));
+        assert_eq!(timing.parse_time_expression("250ms"), Some(0.25));
+        assert_eq!(timing.parse_time_expression("2m"), Some(120.));
+        assert_eq!(timing.parse_time_expression("50f"), Some(2.));
+        assert_eq!(timing.parse_time_expression("15000000t"), Some(1.5));
+        assert_eq!(timing.parse_time_expression("12"), None);
+        assert_eq!(timing.parse_time_expression("00:01"), None);
+    }
+
+    #[test]
+    fn test_parse_ttml() {
+        let text = r##"<?xml version="1.0" encoding="UTF-8"?>
+            <tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling"
+                xmlns:ttp="http://www.w3.org/ns/ttml#parameter" ttp:frameRate="25"
+                tts:extent="1920px 1080px" xml:lang="en">
+              <head>
+                <styling>
+                  <style xml:id="base" tts:color="white" tts:textAlign="center"/>
+                  <style xml:id="yellow" style="base" tts:color="#FFFF00"/>
+                </styling>
+                <layout>
+                  <region xml:id="bottom" tts:origin="10% 80%" tts:extent="80% 10%"
+                    tts:displayAlign="after"/>
+                  <region xml:id="top" tts:origin="192px 108px" tts:extent="1536px 108px"/>
+                </layout>
+              </head>
+              <body style="base" region="bottom">
+                <div begin="10s">
+                  <p xml:id="p1" begin="00:00:01.000" end="00:00:03.000">
+                    Hello <span tts:fontStyle="italic">there</span>
+                    <br/>  general &amp; co
+                  </p>
+                  <p begin="3s" dur="12f" region="top" style="yellow">
+                    <span tts:backgroundColor="#000000CC" tts:fontWeight="bold">Top</span>
+                  </p>
+                  <!-- <p begin="0s" end="1s">Commented</p> -->
+                  <p begin="4s">Unbounded</p>
+                </div>
+              </body>
+            </tt>"##;
+        let cues = parse_ttml(text).unwrap();
+        assert_eq!(cues.len(), 3);
+
+        assert_eq!(cues[0].id(), Some("p1"));
+        assert_eq!(cues[0].start(), 11.);
+        assert_eq!(cues[0].end(), 13.);
+        assert_eq!(
+            cues[0].text(),
+            "<c.white>Hello<i> there</i>\ngeneral &amp; co</c>"
+        );
+        let settings = cues[0].settings();
+        assert!(!settings.snap_to_lines);
+        assert_eq!(settings.line, Some(90.));
+        assert_eq!(settings.line_align, CueLineAlign::End);
+        assert_eq!(settings.position, Some(10.));
+        assert_eq!(settings.position_align, CuePositionAlign::LineLeft);
+        assert_eq!(settings.size, 80.);
+        assert_eq!(settings.align, CueAlign::Center);
+
+        assert_eq!(cues[1].id(), None);
+        assert_eq!(cues[1].start(), 13.);
+        assert_eq!(cues[1].end(), 13.48);
+        assert_eq!(cues[1].text(), "<c.yellow><c.bg_black><b>Top</b></c></c>");
+        let settings = cues[1].settings();
+        assert_eq!(settings.line, Some(10.));
+        assert_eq!(settings.line_align, CueLineAlign::Start);
+        assert_eq!(settings.position, Some(10.));
+        assert_eq!(settings.size, 80.);
+
+        assert_eq!(cues[2].start(), 14.);
+        assert_eq!(cues[2].end(), f64::INFINITY);
+
+        assert!(matches!(
+            parse_ttml("<html><body/></html>"),
+            Err(TtmlParsingError::NotTtml)
+        ));
+        assert!(matches!(
+            parse_ttml("<tt><body></tt>"),
+            Err(TtmlParsingError::InvalidXml(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_stpp_segment() {
+        let document = r#"<tt xmlns="http://www.w3.org/ns/ttml"><body><div>
+            <p begin="00:00:09.000" end="00:00:11.000">Before</p>
+            <p begin="00:00:11.000" end="00:00:13.000">Inside</p>
+            <p begin="00:00:13.000" end="00:00:15.000">Overlapping</p>
+            <p begin="00:00:16.000" end="00:00:17.000">After</p>
+            </div></body></tt>"#;
+        let build_segment = |decode_time: u32, document: &str| {
+            // tfhd with default-base-is-moof
+            let tfhd = [&[0, 0x02, 0, 0][..], &1u32.to_be_bytes()].concat();
+            let tfdt = [&[0, 0, 0, 0][..], &decode_time.to_be_bytes()].concat();
+            // trun with a single sample of 4 seconds, with no size nor data offset
+            let trun = [
+                &[0, 0, 0x01, 0][..],
+                &1u32.to_be_bytes(),
+                &4000u32.to_be_bytes(),
+            ]
+            .concat();
+            let traf = mp4_box(
+                b"traf",
+                &[
+                    mp4_box(b"tfhd", &tfhd),
+                    mp4_box(b"tfdt", &tfdt),
+                    mp4_box(b"trun", &trun),
+                ]
+                .concat(),
+            );
+            [
+                mp4_box(b"moof", &traf),
+                mp4_box(b"mdat", document.as_bytes()),
+            ]
+            .concat()
+        };
+
+        let segment = StppSegment::parse(&build_segment(10_000, document), 1000).unwrap();
+        let cues = segment.cues();
+        assert_eq!(cues.len(), 3);
+        assert_eq!((cues[0].start(), cues[0].end()), (10., 11.));
+        assert_eq!((cues[1].start(), cues[1].end()), (11., 13.));
+        assert_eq!((cues[2].start(), cues[2].end()), (13., 14.));
+
+        // Times relative to the sample
+        let document = r#"<tt xmlns="http://www.w3.org/ns/ttml"><body>
+            <p begin="0.5s" end="1.5s">Relative</p>
+            </body></tt>"#;
+        let segment = StppSegment::parse(&build_segment(100_000, document), 1000).unwrap();
+        let cues = segment.cues();
+        assert_eq!(cues.len(), 1);
+        assert_eq!((cues[0].start(), cues[0].end()), (100.5, 101.5));
+
+        assert!(matches!(
+            StppSegment::parse(&mp4_box(b"mdat", document.as_bytes()), 1000),
+            Err(TtmlParsingError::NoSample)
+        ));
+    }
+}
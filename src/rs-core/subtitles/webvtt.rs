@@ -0,0 +1,435 @@
+use super::{
+    CueAlign, CueLineAlign, CuePositionAlign, CueRegion, CueSettings, CueVertical, TextCue,
+};
+
+/// Frequency of MPEG-2 timestamps, in Hz.
+const MPEG2_TIMESCALE: f64 = 90000.;
+
+/// Duration after which 33-bit MPEG-2 timestamps wrap around, in seconds.
+const MPEG2_ROLLOVER: f64 = 8589934592. / MPEG2_TIMESCALE;
+
+/// A parsed WebVTT segment, as found in HLS subtitles Media Playlists.
+#[derive(Debug)]
+pub(crate) struct WebVttSegment {
+    /// `MPEGTS` value of the `X-TIMESTAMP-MAP` header, in 90kHz units.
+    ///
+    /// Defaults to `0` when that header is absent.
+    mpegts: u64,
+    /// `LOCAL` value of the `X-TIMESTAMP-MAP` header, in seconds.
+    ///
+    /// Defaults to `0` when that header is absent.
+    local: f64,
+    /// Cues found in that segment, in their order of appearance. Their times are expressed in
+    /// the segment's own timeline.
+    cues: Vec<TextCue>,
+}
+
+impl WebVttSegment {
+    /// Parse the WebVTT segment contained in `data`.
+    ///
+    /// Invalid cues are ignored. An error is only returned if the segment as a whole could not
+    /// be parsed.
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, WebVttParsingError> {
+        let text = String::from_utf8_lossy(data);
+        let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+
+        let mut blocks = text
+            .split("\n\n")
+            .map(|b| b.trim_start_matches('\n'))
+            .filter(|b| !b.is_empty());
+        let header = blocks.next().ok_or(WebVttParsingError::MissingHeader)?;
+        let mut header_lines = header.split('\n');
+        match header_lines.next().and_then(|l| l.strip_prefix("WEBVTT")) {
+            Some(rest) if rest.is_empty() || rest.starts_with([' ', '\t']) => {}
+            _ => return Err(WebVttParsingError::MissingHeader),
+        }
+
+        let mut mpegts = 0;
+        let mut local = 0.;
+        for line in header_lines {
+            if let Some(value) = line.strip_prefix("X-TIMESTAMP-MAP=") {
+                (mpegts, local) = parse_timestamp_map(value)
+                    .ok_or_else(|| WebVttParsingError::InvalidTimestampMap(value.to_owned()))?;
+            }
+        }
+
+        let mut regions: Vec<CueRegion> = vec![];
+        let mut cues: Vec<TextCue> = vec![];
+        for block in blocks {
+            let mut lines = block.split('\n');
+            let first_line = lines.next().unwrap_or("");
+            if is_block_of_type(first_line, "NOTE") || is_block_of_type(first_line, "STYLE") {
+                continue;
+            }
+            if is_block_of_type(first_line, "REGION") {
+                if let Some(region) = parse_region(lines) {
+                    regions.push(region);
+                }
+                continue;
+            }
+            let (id, timing_line) = if first_line.contains("-->") {
+                (None, first_line)
+            } else {
+                match lines.next() {
+                    Some(line) if line.contains("-->") => (Some(first_line.to_owned()), line),
+                    _ => continue,
+                }
+            };
+            if let Some((start, end, settings, region)) = parse_timing_line(timing_line, &regions) {
+                let text = lines.collect::<Vec<&str>>().join("\n");
+                cues.push(TextCue::new(id, start, end, settings, region, text));
+            }
+        }
+        Ok(Self {
+            mpegts,
+            local,
+            cues,
+        })
+    }
+
+    /// Cues found in that segment, in their order of appearance.
+    ///
+    /// Their times are expressed in the segment's own timeline, see `media_time_offset` to
+    /// convert them.
+    pub(crate) fn cues(&self) -> &[TextCue] {
+        &self.cues
+    }
+
+    /// Returns the offset, in seconds, to add to the time of the cues of this segment to convert
+    /// them into the timeline of the corresponding audio and video segments, as indicated by
+    /// its `X-TIMESTAMP-MAP` header.
+    ///
+    /// As 33-bit MPEG-2 timestamps regularly wrap around, `expected_start`, the time in that
+    /// same timeline at which that segment is expected to start, is used to choose the right
+    /// wrapping period.
+    pub(crate) fn media_time_offset(&self, expected_start: f64) -> f64 {
+        let offset = self.mpegts as f64 / MPEG2_TIMESCALE - self.local;
+        let first_cue_start = self.cues.first().map_or(self.local, |c| c.start());
+        let drift = expected_start - (first_cue_start + offset);
+        offset + (drift / MPEG2_ROLLOVER).round() * MPEG2_ROLLOVER
+    }
+}
+
+/// Returns `true` if `first_line`, the first line of a WebVTT block, indicates that this block
+/// is of the type `block_type` (e.g. `"NOTE"`).
+fn is_block_of_type(first_line: &str, block_type: &str) -> bool {
+    match first_line.strip_prefix(block_type) {
+        Some(rest) => rest.is_empty() || rest.starts_with([' ', '\t']),
+        None => false,
+    }
+}
+
+/// Parse the value of an `X-TIMESTAMP-MAP` header (e.g. `"MPEGTS:900000,LOCAL:00:00:00.000"`)
+/// into its `MPEGTS` value in 90kHz units and its `LOCAL` value in seconds.
+///
+/// Returns `None` if it could not be parsed.
+fn parse_timestamp_map(value: &str) -> Option<(u64, f64)> {
+    let mut mpegts = 0;
+    let mut local = 0.;
+    for attribute in value.split(',') {
+        match attribute.trim().split_once(':')? {
+            ("MPEGTS", val) => mpegts = val.parse::<u64>().ok()?,
+            ("LOCAL", val) => local = parse_timestamp(val)?,
+            _ => {}
+        }
+    }
+    Some((mpegts, local))
+}
+
+/// Parse a WebVTT timestamp (e.g. `"01:02:03.456"` or `"02:03.456"`) into seconds.
+///
+/// Returns `None` if it could not be parsed.
+fn parse_timestamp(value: &str) -> Option<f64> {
+    let (hms, millis) = value.split_once('.')?;
+    if millis.len() != 3 {
+        return None;
+    }
+    let millis = parse_digits(millis)?;
+    let mut parts = hms.split(':').rev();
+    let seconds = parts
+        .next()
+        .filter(|s| s.len() == 2)
+        .and_then(parse_digits)?;
+    let minutes = parts
+        .next()
+        .filter(|m| m.len() == 2)
+        .and_then(parse_digits)?;
+    let hours = match parts.next() {
+        Some(h) => parse_digits(h)?,
+        None => 0,
+    };
+    if parts.next().is_some() || minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+    Some((hours * 3600 + minutes * 60 + seconds) as f64 + millis as f64 / 1000.)
+}
+
+/// Parse a non-empty string only made of ASCII digits into a `u64`.
+fn parse_digits(value: &str) -> Option<u64> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse::<u64>().ok()
+}
+
+/// Parse a WebVTT percentage (e.g. `"42.5%"`) into a number between `0` and `100`.
+fn parse_percentage(value: &str) -> Option<f64> {
+    let val = value.strip_suffix('%')?.parse::<f64>().ok()?;
+    if (0. ..=100.).contains(&val) {
+        Some(val)
+    } else {
+        None
+    }
+}
+
+/// Parse two comma-separated WebVTT percentages (e.g. `"10%,90%"`).
+fn parse_percentage_pair(value: &str) -> Option<(f64, f64)> {
+    let (x, y) = value.split_once(',')?;
+    Some((parse_percentage(x)?, parse_percentage(y)?))
+}
+
+/// Parse a cue timing line (e.g. `"00:01.000 --> 00:04.000 align:start line:0"`) into the
+/// cue's start and end in seconds, settings and region.
+///
+/// `regions` are the regions defined until now in the segment, which the cue can refer to.
+///
+/// Returns `None` if the timings could not be parsed, in which case the cue should be ignored.
+fn parse_timing_line(
+    line: &str,
+    regions: &[CueRegion],
+) -> Option<(f64, f64, CueSettings, Option<CueRegion>)> {
+    let (start, rest) = line.split_once("-->")?;
+    let start = parse_timestamp(start.trim())?;
+    let rest = rest.trim_start();
+    let (end, settings) = rest
+        .split_once(|c: char| c.is_ascii_whitespace())
+        .unwrap_or((rest, ""));
+    let end = parse_timestamp(end)?;
+    let (settings, region) = parse_cue_settings(settings, regions);
+    Some((start, end, settings, region))
+}
+
+/// Parse the settings of a cue (e.g. `"align:start line:0"`) and returns them as well as the
+/// cue's region amongst `regions`, if it refers to one.
+///
+/// Invalid settings are ignored.
+fn parse_cue_settings(input: &str, regions: &[CueRegion]) -> (CueSettings, Option<CueRegion>) {
+    let mut settings = CueSettings::default();
+    let mut region = None;
+    for (name, value) in input
+        .split_ascii_whitespace()
+        .filter_map(|s| s.split_once(':'))
+    {
+        match name {
+            "vertical" => match value {
+                "rl" => settings.vertical = CueVertical::RightToLeft,
+                "lr" => settings.vertical = CueVertical::LeftToRight,
+                _ => {}
+            },
+            "line" => {
+                let (val, align) = value.split_once(',').unwrap_or((value, ""));
+                if val.ends_with('%') {
+                    if let Some(line) = parse_percentage(val) {
+                        settings.line = Some(line);
+                        settings.snap_to_lines = false;
+                    }
+                } else if let Ok(line) = val.parse::<f64>() {
+                    settings.line = Some(line);
+                    settings.snap_to_lines = true;
+                }
+                match align {
+                    "start" => settings.line_align = CueLineAlign::Start,
+                    "center" => settings.line_align = CueLineAlign::Center,
+                    "end" => settings.line_align = CueLineAlign::End,
+                    _ => {}
+                }
+            }
+            "position" => {
+                let (val, align) = value.split_once(',').unwrap_or((value, ""));
+                if let Some(position) = parse_percentage(val) {
+                    settings.position = Some(position);
+                }
+                match align {
+                    "line-left" => settings.position_align = CuePositionAlign::LineLeft,
+                    "center" => settings.position_align = CuePositionAlign::Center,
+                    "line-right" => settings.position_align = CuePositionAlign::LineRight,
+                    _ => {}
+                }
+            }
+            "size" => {
+                if let Some(size) = parse_percentage(value) {
+                    settings.size = size;
+                }
+            }
+            "align" => match value {
+                "start" => settings.align = CueAlign::Start,
+                "center" | "middle" => settings.align = CueAlign::Center,
+                "end" => settings.align = CueAlign::End,
+                "left" => settings.align = CueAlign::Left,
+                "right" => settings.align = CueAlign::Right,
+                _ => {}
+            },
+            "region" => region = regions.iter().find(|r| r.id == value).cloned(),
+            _ => {}
+        }
+    }
+    (settings, region)
+}
+
+/// Parse the lines following the `REGION` line of a region definition block.
+///
+/// Returns `None` if that region has no identifier, in which case it should be ignored.
+fn parse_region<'a>(lines: impl Iterator<Item = &'a str>) -> Option<CueRegion> {
+    let mut id = None;
+    let mut region = CueRegion::new(String::new());
+    for (name, value) in lines
+        .flat_map(|l| l.split_ascii_whitespace())
+        .filter_map(|s| s.split_once(':'))
+    {
+        match name {
+            "id" if !value.is_empty() && !value.contains("-->") => id = Some(value),
+            "width" => {
+                if let Some(width) = parse_percentage(value) {
+                    region.width = width;
+                }
+            }
+            "lines" => {
+                if let Some(lines) = parse_digits(value) {
+                    region.lines = lines as u32;
+                }
+            }
+            "regionanchor" => {
+                if let Some(anchor) = parse_percentage_pair(value) {
+                    region.region_anchor = anchor;
+                }
+            }
+            "viewportanchor" => {
+                if let Some(anchor) = parse_percentage_pair(value) {
+                    region.viewport_anchor = anchor;
+                }
+            }
+            "scroll" => region.scroll_up = value == "up",
+            _ => {}
+        }
+    }
+    region.id = id?.to_owned();
+    Some(region)
+}
+
+use thiserror::Error;
+
+/// Error encountered when parsing a WebVTT segment.
+#[derive(Error, Debug)]
+pub(crate) enum WebVttParsingError {
+    #[error("The WebVTT segment does not start with a \"WEBVTT\" line")]
+    MissingHeader,
+    #[error("Invalid X-TIMESTAMP-MAP header in WebVTT segment: {0}")]
+    InvalidTimestampMap(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("00:00:00.000"), Some(0.));
+        assert_eq!(parse_timestamp("01:02.500"), Some(62.5));
+        assert_eq!(parse_timestamp("01:02:03.004"), Some(3723.004));
+        assert_eq!(parse_timestamp("100:00:00.000"), Some(360000.));
+        assert_eq!(parse_timestamp("1:02.500"), None);
+        assert_eq!(parse_timestamp("01:60.000"), None);
+        assert_eq!(parse_timestamp("01:02.50"), None);
+        assert_eq!(parse_timestamp("01:02"), None);
+        assert_eq!(parse_timestamp("+1:01:02.500"), None);
+    }
+
+    #[test]
+    fn test_parse_segment() {
+        let data = "\u{feff}WEBVTT\r\n\
+            X-TIMESTAMP-MAP=LOCAL:00:00:10.000,MPEGTS:1800000\r\n\
+            \r\n\
+            REGION\r\n\
+            id:bottom width:40% lines:2 regionanchor:0%,100% viewportanchor:10%,90% scroll:up\r\n\
+            \r\n\
+            NOTE a comment\r\n\
+            \r\n\
+            intro\r\n\
+            00:10.000 --> 00:12.500 align:start line:80%,end position:10%,line-left\r\n\
+            <v Bob>Hello</v>\r\n\
+            world\r\n\
+            \r\n\
+            \r\n\
+            00:00:13.000 --> 00:00:14.000 region:bottom vertical:rl size:50%\r\n\
+            Second\r\n\
+            \r\n\
+            00:15.000 -> 00:16.000\r\n\
+            Invalid\r\n";
+        let segment = WebVttSegment::parse(data.as_bytes()).unwrap();
+        assert_eq!(segment.mpegts, 1800000);
+        assert_eq!(segment.local, 10.);
+        let cues = segment.cues();
+        assert_eq!(cues.len(), 2);
+
+        assert_eq!(cues[0].id(), Some("intro"));
+        assert_eq!(cues[0].start(), 10.);
+        assert_eq!(cues[0].end(), 12.5);
+        assert_eq!(cues[0].text(), "<v Bob>Hello</v>\nworld");
+        assert_eq!(cues[0].region(), None);
+        let settings = cues[0].settings();
+        assert_eq!(settings.align, CueAlign::Start);
+        assert_eq!(settings.line, Some(80.));
+        assert!(!settings.snap_to_lines);
+        assert_eq!(settings.line_align, CueLineAlign::End);
+        assert_eq!(settings.position, Some(10.));
+        assert_eq!(settings.position_align, CuePositionAlign::LineLeft);
+
+        assert_eq!(cues[1].id(), None);
+        assert_eq!(cues[1].start(), 13.);
+        assert_eq!(cues[1].text(), "Second");
+        assert_eq!(cues[1].settings().vertical, CueVertical::RightToLeft);
+        assert_eq!(cues[1].settings().size, 50.);
+        let region = cues[1].region().unwrap();
+        assert_eq!(region.id, "bottom");
+        assert_eq!(region.width, 40.);
+        assert_eq!(region.lines, 2);
+        assert_eq!(region.region_anchor, (0., 100.));
+        assert_eq!(region.viewport_anchor, (10., 90.));
+        assert!(region.scroll_up);
+    }
+
+    #[test]
+    fn test_invalid_segments() {
+        assert!(matches!(
+            WebVttSegment::parse(b"WEBVTTX\n\n00:01.000 --> 00:02.000\nA"),
+            Err(WebVttParsingError::MissingHeader)
+        ));
+        assert!(matches!(
+            WebVttSegment::parse(b""),
+            Err(WebVttParsingError::MissingHeader)
+        ));
+        assert!(matches!(
+            WebVttSegment::parse(b"WEBVTT\nX-TIMESTAMP-MAP=MPEGTS:abc,LOCAL:00:00:00.000\n"),
+            Err(WebVttParsingError::InvalidTimestampMap(_))
+        ));
+        let segment = WebVttSegment::parse(b"WEBVTT - Title\n").unwrap();
+        assert!(segment.cues().is_empty());
+    }
+
+    #[test]
+    fn test_media_time_offset() {
+        let data = "WEBVTT\nX-TIMESTAMP-MAP=MPEGTS:900000,LOCAL:00:00:00.000\n\n\
+            00:01.000 --> 00:02.000\nA";
+        let segment = WebVttSegment::parse(data.as_bytes()).unwrap();
+        assert_eq!(segment.media_time_offset(10.), 10.);
+
+        // The MPEG-2 timestamps of the audio and video segments wrapped around
+        let expected = MPEG2_ROLLOVER + 10.;
+        assert_eq!(segment.media_time_offset(expected), 10. + MPEG2_ROLLOVER);
+
+        // No X-TIMESTAMP-MAP: the cue time 0 maps to the MPEG-2 timestamp 0
+        let segment = WebVttSegment::parse(b"WEBVTT\n\n00:01.000 --> 00:02.000\nA").unwrap();
+        assert_eq!(segment.media_time_offset(0.), 0.);
+    }
+}
@@ -0,0 +1,273 @@
+//! Minimal non-validating XML parser, only implementing what is needed to read the XML-based
+//! subtitles formats (i.e. TTML).
+//!
+//! Namespaces are not resolved: element and attribute names keep their prefix, and are generally
+//! matched through their local name instead.
+
+use thiserror::Error;
+
+/// A node of an XML document.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum XmlNode {
+    Element(XmlElement),
+    /// Character data, with entities and CDATA sections already decoded.
+    Text(String),
+}
+
+/// An XML element, with its attributes and children.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct XmlElement {
+    /// Qualified name of that element (e.g. `tt:p`).
+    name: String,
+    /// Attributes of that element, with their qualified name and decoded value, in document order.
+    attributes: Vec<(String, String)>,
+    /// Child nodes of that element, in document order.
+    children: Vec<XmlNode>,
+}
+
+impl XmlElement {
+    fn new(name: String, attributes: Vec<(String, String)>) -> Self {
+        Self {
+            name,
+            attributes,
+            children: vec![],
+        }
+    }
+
+    /// Local name of that element, that is its name without any namespace prefix.
+    pub(super) fn local_name(&self) -> &str {
+        local_name(&self.name)
+    }
+
+    /// Returns the value of the first attribute with the given local name, if one.
+    pub(super) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| local_name(n) == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Child nodes of that element, in document order.
+    pub(super) fn children(&self) -> &[XmlNode] {
+        &self.children
+    }
+
+    /// Returns an iterator over the child elements of that element with the given local name.
+    pub(super) fn child_elements<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a XmlElement> + 'a {
+        self.children.iter().filter_map(move |c| match c {
+            XmlNode::Element(e) if e.local_name() == name => Some(e),
+            _ => None,
+        })
+    }
+}
+
+/// Returns the given qualified name without its namespace prefix.
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Parse the given XML document and return its root element.
+pub(super) fn parse_xml(text: &str) -> Result<XmlElement, XmlParsingError> {
+    let mut parser = XmlParser { text, pos: 0 };
+    let mut stack: Vec<XmlElement> = vec![];
+    let mut root: Option<XmlElement> = None;
+    while parser.pos < text.len() {
+        let rest = parser.rest();
+        if rest.starts_with("<!--") {
+            parser.skip_after("-->")?;
+        } else if rest.starts_with("<![CDATA[") {
+            parser.pos += "<![CDATA[".len();
+            let content = parser.read_until("]]>")?;
+            if let Some(parent) = stack.last_mut() {
+                parent.children.push(XmlNode::Text(content.to_owned()));
+            }
+        } else if rest.starts_with("<?") {
+            parser.skip_after("?>")?;
+        } else if rest.starts_with("<!") {
+            // DOCTYPE declaration, internal subsets are not supported
+            parser.skip_after(">")?;
+        } else if rest.starts_with("</") {
+            parser.pos += 2;
+            let name = parser.read_until(">")?.trim();
+            let element = stack.pop().ok_or(XmlParsingError::UnexpectedClosingTag)?;
+            if element.name != name {
+                return Err(XmlParsingError::UnexpectedClosingTag);
+            }
+            push_element(&mut stack, &mut root, element)?;
+        } else if rest.starts_with('<') {
+            parser.pos += 1;
+            let (element, is_self_closing) = parser.read_start_tag()?;
+            if is_self_closing {
+                push_element(&mut stack, &mut root, element)?;
+            } else {
+                stack.push(element);
+            }
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let content = &rest[..end];
+            parser.pos += end;
+            if let Some(parent) = stack.last_mut() {
+                parent
+                    .children
+                    .push(XmlNode::Text(decode_entities(content)));
+            }
+        }
+    }
+    if !stack.is_empty() {
+        return Err(XmlParsingError::UnexpectedEnd);
+    }
+    root.ok_or(XmlParsingError::NoRootElement)
+}
+
+/// Add a fully-parsed `element` to its parent, or set it as the document's root element if it
+/// has none.
+fn push_element(
+    stack: &mut [XmlElement],
+    root: &mut Option<XmlElement>,
+    element: XmlElement,
+) -> Result<(), XmlParsingError> {
+    if let Some(parent) = stack.last_mut() {
+        parent.children.push(XmlNode::Element(element));
+    } else if root.is_none() {
+        *root = Some(element);
+    } else {
+        return Err(XmlParsingError::MultipleRootElements);
+    }
+    Ok(())
+}
+
+struct XmlParser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> XmlParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    /// Move after the next occurence of `pattern`.
+    fn skip_after(&mut self, pattern: &str) -> Result<(), XmlParsingError> {
+        self.read_until(pattern).map(|_| ())
+    }
+
+    /// Returns the text until the next occurence of `pattern` and move after that pattern.
+    fn read_until(&mut self, pattern: &str) -> Result<&'a str, XmlParsingError> {
+        let rest = self.rest();
+        let idx = rest.find(pattern).ok_or(XmlParsingError::UnexpectedEnd)?;
+        self.pos += idx + pattern.len();
+        Ok(&rest[..idx])
+    }
+
+    fn skip_whitespaces(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Read a name (of an element or attribute) at the current position.
+    fn read_name(&mut self) -> Result<&'a str, XmlParsingError> {
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(XmlParsingError::InvalidTag);
+        }
+        self.pos += end;
+        Ok(&rest[..end])
+    }
+
+    /// Read a start tag, the position being right after its `<` character.
+    ///
+    /// Returns the corresponding element, without its children, and a boolean set to `true` if
+    /// that was an empty-element tag (e.g. `<br/>`).
+    fn read_start_tag(&mut self) -> Result<(XmlElement, bool), XmlParsingError> {
+        let name = self.read_name()?.to_owned();
+        let mut attributes = vec![];
+        loop {
+            self.skip_whitespaces();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                return Ok((XmlElement::new(name, attributes), true));
+            } else if rest.starts_with('>') {
+                self.pos += 1;
+                return Ok((XmlElement::new(name, attributes), false));
+            } else if rest.is_empty() {
+                return Err(XmlParsingError::UnexpectedEnd);
+            }
+            let attr_name = self.read_name()?.to_owned();
+            self.skip_whitespaces();
+            if !self.rest().starts_with('=') {
+                return Err(XmlParsingError::InvalidTag);
+            }
+            self.pos += 1;
+            self.skip_whitespaces();
+            let quote = match self.rest().chars().next() {
+                Some(c @ ('"' | '\'')) => c,
+                _ => return Err(XmlParsingError::InvalidTag),
+            };
+            self.pos += 1;
+            let mut quote_buf = [0; 4];
+            let value = self.read_until(quote.encode_utf8(&mut quote_buf))?;
+            attributes.push((attr_name, decode_entities(value)));
+        }
+    }
+}
+
+/// Replace the predefined entities and character references found in `text` by the character
+/// they represent. Unknown entities are kept as is.
+fn decode_entities(text: &str) -> String {
+    let mut ret = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find('&') {
+        ret.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => {
+                    if let Some(hex) = entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                        u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+                    } else if let Some(dec) = entity.strip_prefix('#') {
+                        dec.parse::<u32>().ok().and_then(char::from_u32)
+                    } else {
+                        None
+                    }
+                }
+            };
+            c.map(|c| (c, end))
+        });
+        if let Some((c, end)) = decoded {
+            ret.push(c);
+            rest = &rest[end + 1..];
+        } else {
+            ret.push('&');
+            rest = &rest[1..];
+        }
+    }
+    ret.push_str(rest);
+    ret
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum XmlParsingError {
+    #[error("The XML document ended unexpectedly")]
+    UnexpectedEnd,
+    #[error("A closing tag does not correspond to the last opened element")]
+    UnexpectedClosingTag,
+    #[error("Invalid XML tag")]
+    InvalidTag,
+    #[error("The XML document has no root element")]
+    NoRootElement,
+    #[error("The XML document has multiple root elements")]
+    MultipleRootElements,
+}
@@ -0,0 +1,266 @@
+//! Minimal AES-128 decryption implementation, used to decrypt segments encrypted with the
+//! `AES-128` HLS method (the whole segment is encrypted in CBC mode with PKCS7 padding).
+//!
+//! Tables are all computed at compile-time to avoid both having to ship them as hardcoded arrays
+//! and the cost of recomputing them each time.
+
+/// Size of an AES block, in bytes.
+const BLOCK_SIZE: usize = 16;
+
+/// Number of rounds for AES-128.
+const NB_ROUNDS: usize = 10;
+
+/// Multiply two elements of AES' Galois Field GF(2^8).
+const fn gmul(mut a: u8, mut b: u8) -> u8 {
+    let mut res = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            res ^= a;
+        }
+        let high_bit = a & 0x80;
+        a <<= 1;
+        if high_bit != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    res
+}
+
+/// Compute the AES substitution box.
+const fn compute_sbox() -> [u8; 256] {
+    let mut sbox = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        // Multiplicative inverse in GF(2^8) is x^254 (with 0 mapped to 0)
+        let x = i as u8;
+        let mut inv = 1u8;
+        let mut power = 0;
+        while power < 254 {
+            inv = gmul(inv, x);
+            power += 1;
+        }
+        if x == 0 {
+            inv = 0;
+        }
+        sbox[i] = inv
+            ^ inv.rotate_left(1)
+            ^ inv.rotate_left(2)
+            ^ inv.rotate_left(3)
+            ^ inv.rotate_left(4)
+            ^ 0x63;
+        i += 1;
+    }
+    sbox
+}
+
+/// Compute the inverse of the given substitution box.
+const fn compute_inv_sbox(sbox: &[u8; 256]) -> [u8; 256] {
+    let mut inv_sbox = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        inv_sbox[sbox[i] as usize] = i as u8;
+        i += 1;
+    }
+    inv_sbox
+}
+
+/// Compute the table of products of all GF(2^8) elements by `factor`.
+const fn compute_mul_table(factor: u8) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = gmul(i as u8, factor);
+        i += 1;
+    }
+    table
+}
+
+static SBOX: [u8; 256] = compute_sbox();
+static INV_SBOX: [u8; 256] = compute_inv_sbox(&SBOX);
+static MUL_9: [u8; 256] = compute_mul_table(9);
+static MUL_11: [u8; 256] = compute_mul_table(11);
+static MUL_13: [u8; 256] = compute_mul_table(13);
+static MUL_14: [u8; 256] = compute_mul_table(14);
+
+/// Expand the given AES-128 key into all its round keys.
+fn expand_key(key: &[u8; 16]) -> [[u8; BLOCK_SIZE]; NB_ROUNDS + 1] {
+    let mut round_keys = [[0u8; BLOCK_SIZE]; NB_ROUNDS + 1];
+    round_keys[0] = *key;
+    let mut rcon = 1u8;
+    for round in 1..=NB_ROUNDS {
+        let prev = round_keys[round - 1];
+        let mut word = [
+            SBOX[prev[13] as usize] ^ rcon,
+            SBOX[prev[14] as usize],
+            SBOX[prev[15] as usize],
+            SBOX[prev[12] as usize],
+        ];
+        rcon = gmul(rcon, 2);
+        let curr = &mut round_keys[round];
+        for col in 0..4 {
+            for row in 0..4 {
+                word[row] ^= prev[col * 4 + row];
+                curr[col * 4 + row] = word[row];
+            }
+        }
+    }
+    round_keys
+}
+
+/// Decrypt a single AES-128 block in place.
+fn decrypt_block(block: &mut [u8; BLOCK_SIZE], round_keys: &[[u8; BLOCK_SIZE]; NB_ROUNDS + 1]) {
+    add_round_key(block, &round_keys[NB_ROUNDS]);
+    for round in (1..NB_ROUNDS).rev() {
+        inv_shift_rows_and_sub_bytes(block);
+        add_round_key(block, &round_keys[round]);
+        inv_mix_columns(block);
+    }
+    inv_shift_rows_and_sub_bytes(block);
+    add_round_key(block, &round_keys[0]);
+}
+
+fn add_round_key(block: &mut [u8; BLOCK_SIZE], round_key: &[u8; BLOCK_SIZE]) {
+    for (b, k) in block.iter_mut().zip(round_key.iter()) {
+        *b ^= k;
+    }
+}
+
+fn inv_shift_rows_and_sub_bytes(block: &mut [u8; BLOCK_SIZE]) {
+    let prev = *block;
+    for col in 0..4 {
+        for row in 0..4 {
+            // Row `n` is rotated right by `n` columns
+            let src_col = (col + 4 - row) % 4;
+            block[col * 4 + row] = INV_SBOX[prev[src_col * 4 + row] as usize];
+        }
+    }
+}
+
+fn inv_mix_columns(block: &mut [u8; BLOCK_SIZE]) {
+    for col in block.chunks_exact_mut(4) {
+        let (a0, a1, a2, a3) = (
+            col[0] as usize,
+            col[1] as usize,
+            col[2] as usize,
+            col[3] as usize,
+        );
+        col[0] = MUL_14[a0] ^ MUL_11[a1] ^ MUL_13[a2] ^ MUL_9[a3];
+        col[1] = MUL_9[a0] ^ MUL_14[a1] ^ MUL_11[a2] ^ MUL_13[a3];
+        col[2] = MUL_13[a0] ^ MUL_9[a1] ^ MUL_14[a2] ^ MUL_11[a3];
+        col[3] = MUL_11[a0] ^ MUL_13[a1] ^ MUL_9[a2] ^ MUL_14[a3];
+    }
+}
+
+/// Decrypt `data` encrypted with AES-128 in CBC mode with PKCS7 padding, as is the case for
+/// segments encrypted with the `AES-128` HLS method.
+///
+/// # Arguments
+///
+/// * `data` - The encrypted data. Its length should be a multiple of 16 bytes.
+///
+/// * `key` - The 128 bits key used to encrypt that data.
+///
+/// * `iv` - The 128 bits Initialization Vector used to encrypt that data.
+pub(crate) fn decrypt_aes_128_cbc(
+    data: &[u8],
+    key: &[u8; 16],
+    iv: &[u8; 16],
+) -> Result<Vec<u8>, AesDecryptionError> {
+    if data.is_empty() || !data.len().is_multiple_of(BLOCK_SIZE) {
+        return Err(AesDecryptionError::InvalidDataLength(data.len()));
+    }
+    let round_keys = expand_key(key);
+    let mut output = Vec::with_capacity(data.len());
+    let mut prev_block = *iv;
+    for chunk in data.chunks_exact(BLOCK_SIZE) {
+        let mut block = [0u8; BLOCK_SIZE];
+        block.copy_from_slice(chunk);
+        decrypt_block(&mut block, &round_keys);
+        for (b, p) in block.iter_mut().zip(prev_block.iter()) {
+            *b ^= p;
+        }
+        output.extend_from_slice(&block);
+        prev_block.copy_from_slice(chunk);
+    }
+
+    let padding = *output.last().unwrap_or(&0) as usize;
+    if padding == 0
+        || padding > BLOCK_SIZE
+        || output[output.len() - padding..]
+            .iter()
+            .any(|b| *b as usize != padding)
+    {
+        return Err(AesDecryptionError::InvalidPadding);
+    }
+    output.truncate(output.len() - padding);
+    Ok(output)
+}
+
+use thiserror::Error;
+
+/// Error encountered when trying to decrypt AES-128 data.
+#[derive(Error, Debug)]
+pub(crate) enum AesDecryptionError {
+    #[error("Invalid encrypted data length: {0} bytes is not a multiple of the AES block size.")]
+    InvalidDataLength(usize),
+    #[error("Invalid PKCS7 padding after decryption. Is the key or IV wrong?")]
+    InvalidPadding,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 16] = [
+        0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f,
+        0x3c,
+    ];
+    const IV: [u8; 16] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ];
+
+    #[test]
+    fn test_sbox() {
+        assert_eq!(SBOX[0x00], 0x63);
+        assert_eq!(SBOX[0x53], 0xed);
+        assert_eq!(INV_SBOX[0x63], 0x00);
+        assert_eq!(INV_SBOX[0xed], 0x53);
+    }
+
+    #[test]
+    fn test_decrypt_block() {
+        // NIST SP 800-38A, F.1.2 ECB-AES128.Decrypt, first block
+        let round_keys = expand_key(&KEY);
+        let mut block = [
+            0x3a, 0xd7, 0x7b, 0xb4, 0x0d, 0x7a, 0x36, 0x60, 0xa8, 0x9e, 0xca, 0xf3, 0x24, 0x66,
+            0xef, 0x97,
+        ];
+        decrypt_block(&mut block, &round_keys);
+        assert_eq!(
+            block,
+            [
+                0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93,
+                0x17, 0x2a
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decrypt_aes_128_cbc() {
+        let encrypted = [
+            0xc6, 0x5d, 0xae, 0xe3, 0x6e, 0xd9, 0x12, 0x3f, 0xa3, 0x23, 0x42, 0xec, 0x67, 0x70,
+            0x98, 0xc0, 0xf0, 0x54, 0x99, 0x29, 0x1f, 0x55, 0xc8, 0x95, 0x0f, 0x73, 0xcd, 0x25,
+            0xab, 0x0d, 0x5f, 0x07,
+        ];
+        assert_eq!(
+            decrypt_aes_128_cbc(&encrypted, &KEY, &IV).unwrap(),
+            b"wasp-hls AES-128 test"
+        );
+        assert!(matches!(
+            decrypt_aes_128_cbc(&encrypted[..20], &KEY, &IV),
+            Err(AesDecryptionError::InvalidDataLength(20))
+        ));
+    }
+}
@@ -0,0 +1,737 @@
+//! Minimal ISOBMFF (a.k.a. MP4) reading utilities, only implementing what is needed to read
+//! the few informations the player needs from fragmented MP4 segments.
+
+/// A single ISOBMFF box, as found by `IsobmffBoxIterator`.
+pub(crate) struct IsobmffBox<'a> {
+    /// The four-character code identifying the type of that box (e.g. `b"moof"`).
+    pub(crate) box_type: [u8; 4],
+    /// Offset of the start of that box (its header included) in the data it has been read from.
+    pub(crate) offset: usize,
+    /// Offset of the start of that box's content in the data it has been read from.
+    pub(crate) content_offset: usize,
+    /// Content of that box, its header excluded.
+    pub(crate) content: &'a [u8],
+}
+
+/// Iterator over the boxes found at the top level of some ISOBMFF data.
+///
+/// Iteration stops at the first box whose header is either invalid or truncated.
+pub(crate) struct IsobmffBoxIterator<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for IsobmffBoxIterator<'a> {
+    type Item = IsobmffBox<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.offset;
+        let size = read_u32(self.data, start)? as u64;
+        let box_type: [u8; 4] = self.data.get(start + 4..start + 8)?.try_into().ok()?;
+        let (header_size, box_size) = match size {
+            0 => (8, (self.data.len() - start) as u64),
+            1 => (16, read_u64(self.data, start + 8)?),
+            _ => (8, size),
+        };
+        if box_size < header_size as u64 {
+            return None;
+        }
+        let end = start.checked_add(usize::try_from(box_size).ok()?)?;
+        let content = self.data.get(start + header_size..end)?;
+        self.offset = end;
+        Some(IsobmffBox {
+            box_type,
+            offset: start,
+            content_offset: start + header_size,
+            content,
+        })
+    }
+}
+
+/// Returns an iterator over the boxes found at the top level of `data`.
+pub(crate) fn boxes(data: &[u8]) -> IsobmffBoxIterator<'_> {
+    IsobmffBoxIterator { data, offset: 0 }
+}
+
+/// Returns the content of the first box found at the top level of `data` with the given type.
+pub(crate) fn find_box<'a>(data: &'a [u8], box_type: &[u8; 4]) -> Option<&'a [u8]> {
+    boxes(data)
+        .find(|b| &b.box_type == box_type)
+        .map(|b| b.content)
+}
+
+/// Returns the content of the first box found by following `path`, each element being the type
+/// of a box contained in the previous one.
+pub(crate) fn find_box_at_path<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> Option<&'a [u8]> {
+    path.iter()
+        .try_fold(data, |content, box_type| find_box(content, box_type))
+}
+
+/// Information on a track declared in an initialization segment.
+pub(crate) struct IsobmffTrackInfo {
+    /// Identifier of that track, as found in the `tfhd` boxes of its media segments.
+    pub(crate) track_id: u32,
+    /// Handler type of that track (e.g. `b"vide"`, `b"soun"` or `b"subt"`).
+    pub(crate) handler_type: [u8; 4],
+    /// Timescale of that track, in units per second. `None` if unknown.
+    pub(crate) timescale: Option<u32>,
+    /// Type of the first sample entry of that track (e.g. `b"avc1"` or `b"hvc1"`), if found.
+    pub(crate) sample_entry_type: Option<[u8; 4]>,
+}
+
+/// Read information on all tracks declared in the given initialization segment.
+pub(crate) fn read_tracks(init_segment: &[u8]) -> Vec<IsobmffTrackInfo> {
+    let Some(moov) = find_box(init_segment, b"moov") else {
+        return vec![];
+    };
+    boxes(moov)
+        .filter(|b| &b.box_type == b"trak")
+        .filter_map(|trak| {
+            let tkhd = find_box(trak.content, b"tkhd")?;
+            let track_id = match tkhd.first()? {
+                // version (1) + flags (3) + creation_time (8) + modification_time (8)
+                1 => read_u32(tkhd, 20),
+                // version (1) + flags (3) + creation_time (4) + modification_time (4)
+                _ => read_u32(tkhd, 12),
+            }?;
+            let mdia = find_box(trak.content, b"mdia")?;
+            // version (1) + flags (3) + pre_defined (4)
+            let handler_type = find_box(mdia, b"hdlr")?.get(8..12)?.try_into().ok()?;
+            let sample_entry_type = find_box_at_path(mdia, &[b"minf", b"stbl", b"stsd"])
+                // version (1) + flags (3) + entry_count (4) + first entry's size (4)
+                .and_then(|stsd| stsd.get(12..16)?.try_into().ok());
+            Some(IsobmffTrackInfo {
+                track_id,
+                handler_type,
+                timescale: read_mdhd_timescale(mdia),
+                sample_entry_type,
+            })
+        })
+        .collect()
+}
+
+/// Read the timescale of the first track declared in the given initialization segment, from
+/// its `mdhd` box.
+///
+/// Returns `None` if it could not be found or is invalid.
+pub(crate) fn read_track_timescale(init_segment: &[u8]) -> Option<u32> {
+    read_mdhd_timescale(find_box_at_path(
+        init_segment,
+        &[b"moov", b"trak", b"mdia"],
+    )?)
+}
+
+/// Read the timescale indicated by the `mdhd` box of the given `mdia` box content.
+fn read_mdhd_timescale(mdia: &[u8]) -> Option<u32> {
+    let mdhd = find_box(mdia, b"mdhd")?;
+    let timescale = match mdhd.first()? {
+        // version (1) + flags (3) + creation_time (8) + modification_time (8)
+        1 => read_u32(mdhd, 20),
+        // version (1) + flags (3) + creation_time (4) + modification_time (4)
+        _ => read_u32(mdhd, 12),
+    };
+    timescale.filter(|t| *t > 0)
+}
+
+/// Read the formats of the tracks declared in the given initialization segment, as RFC 6381
+/// codec strings (e.g. `"avc1.64001F"` or `"mp4a.40.2"`), from the decoder configuration found
+/// in the first sample entry of each of them.
+///
+/// Tracks whose format is not recognized are ignored.
+pub(crate) fn read_codecs(init_segment: &[u8]) -> Vec<String> {
+    let Some(moov) = find_box(init_segment, b"moov") else {
+        return vec![];
+    };
+    boxes(moov)
+        .filter(|b| &b.box_type == b"trak")
+        .filter_map(|trak| {
+            let stsd = find_box_at_path(trak.content, &[b"mdia", b"minf", b"stbl", b"stsd"])?;
+            // version (1) + flags (3) + entry_count (4)
+            let entry = boxes(stsd.get(8..)?).next()?;
+            read_sample_entry_codec(&entry)
+        })
+        .collect()
+}
+
+/// Build the RFC 6381 codec string corresponding to the given sample entry, relying on the
+/// decoder configuration box it contains.
+///
+/// For encrypted sample entries (`encv` and `enca`), the original format is read from their
+/// `frma` box.
+fn read_sample_entry_codec(entry: &IsobmffBox) -> Option<String> {
+    let children = match &entry.box_type {
+        // SampleEntry fields (8) + VisualSampleEntry fields (70)
+        b"avc1" | b"avc3" | b"hvc1" | b"hev1" | b"av01" | b"encv" => entry.content.get(78..)?,
+        b"mp4a" | b"ac-3" | b"ec-3" | b"enca" => {
+            // SampleEntry fields (8) + AudioSampleEntry fields (20), those last ones being
+            // extended in QuickTime's version 1 and 2 sound sample descriptions
+            let offset = match read_u16(entry.content, 8)? {
+                1 => 44,
+                2 => 64,
+                _ => 28,
+            };
+            entry.content.get(offset..)?
+        }
+        _ => return None,
+    };
+    let format: [u8; 4] = match &entry.box_type {
+        b"encv" | b"enca" => find_box_at_path(children, &[b"sinf", b"frma"])?
+            .get(0..4)?
+            .try_into()
+            .ok()?,
+        box_type => *box_type,
+    };
+    match &format {
+        b"avc1" | b"avc3" => {
+            let avcc = find_box(children, b"avcC")?;
+            Some(format!(
+                "{}.{:02X}{:02X}{:02X}",
+                std::str::from_utf8(&format).ok()?,
+                avcc.get(1)?,
+                avcc.get(2)?,
+                avcc.get(3)?
+            ))
+        }
+        b"hvc1" | b"hev1" => read_hevc_codec(
+            std::str::from_utf8(&format).ok()?,
+            find_box(children, b"hvcC")?,
+        ),
+        b"av01" => read_av1_codec(find_box(children, b"av1C")?),
+        b"mp4a" => read_mp4a_codec(find_box(children, b"esds")?),
+        b"ac-3" => find_box(children, b"dac3").map(|_| "ac-3".to_owned()),
+        b"ec-3" => find_box(children, b"dec3").map(|_| "ec-3".to_owned()),
+        _ => None,
+    }
+}
+
+/// Build an HEVC codec string (e.g. `"hvc1.1.6.L93.B0"`) from the content of an `hvcC` box, as
+/// defined by ISO/IEC 14496-15 Annex E.
+fn read_hevc_codec(format: &str, hvcc: &[u8]) -> Option<String> {
+    let profile_byte = *hvcc.get(1)?;
+    let profile_space = match profile_byte >> 6 {
+        1 => "A",
+        2 => "B",
+        3 => "C",
+        _ => "",
+    };
+    let tier = if profile_byte & 0x20 != 0 { 'H' } else { 'L' };
+    let profile_idc = profile_byte & 0x1F;
+    let compatibility_flags = read_u32(hvcc, 2)?.reverse_bits();
+    let constraint_flags = hvcc.get(6..12)?;
+    let level_idc = *hvcc.get(12)?;
+    let mut codec = format!(
+        "{}.{}{}.{:X}.{}{}",
+        format, profile_space, profile_idc, compatibility_flags, tier, level_idc
+    );
+    // Trailing bytes of the constraint flags which are equal to zero are omitted
+    let constraint_len = constraint_flags
+        .iter()
+        .rposition(|b| *b != 0)
+        .map_or(0, |idx| idx + 1);
+    constraint_flags[..constraint_len]
+        .iter()
+        .for_each(|b| codec.push_str(&format!(".{:X}", b)));
+    Some(codec)
+}
+
+/// Build an AV1 codec string (e.g. `"av01.0.04M.08"`) from the content of an `av1C` box, as
+/// defined by the AV1 Codec ISO Media File Format Binding.
+fn read_av1_codec(av1c: &[u8]) -> Option<String> {
+    let profile = av1c.get(1)? >> 5;
+    let level = av1c.get(1)? & 0x1F;
+    let flags = *av1c.get(2)?;
+    let tier = if flags & 0x80 != 0 { 'H' } else { 'M' };
+    let bit_depth = match (flags & 0x40 != 0, flags & 0x20 != 0) {
+        (true, true) if profile == 2 => 12,
+        (true, _) => 10,
+        _ => 8,
+    };
+    Some(format!(
+        "av01.{}.{:02}{}.{:02}",
+        profile, level, tier, bit_depth
+    ))
+}
+
+/// Build an MPEG-4 audio codec string (e.g. `"mp4a.40.2"`) from the content of an `esds` box,
+/// relying on the object type indication of its `DecoderConfigDescriptor` and, for MPEG-4
+/// Audio, on the audio object type of its `AudioSpecificConfig`.
+fn read_mp4a_codec(esds: &[u8]) -> Option<String> {
+    // version (1) + flags (3)
+    let (tag, es_descriptor) = read_descriptor(esds, 4)?;
+    if tag != 0x03 {
+        return None;
+    }
+    let es_flags = *es_descriptor.get(2)?;
+    // ES_ID (2) + flags (1)
+    let mut offset = 3;
+    if es_flags & 0x80 != 0 {
+        // dependsOn_ES_ID
+        offset += 2;
+    }
+    if es_flags & 0x40 != 0 {
+        // URLlength (1) + URLstring
+        offset += 1 + *es_descriptor.get(offset)? as usize;
+    }
+    if es_flags & 0x20 != 0 {
+        // OCR_ES_Id
+        offset += 2;
+    }
+    let (tag, decoder_config) = read_descriptor(es_descriptor, offset)?;
+    if tag != 0x04 {
+        return None;
+    }
+    let object_type_indication = *decoder_config.first()?;
+    if object_type_indication != 0x40 {
+        return Some(format!("mp4a.{:02X}", object_type_indication));
+    }
+    // objectTypeIndication (1) + streamType (1) + bufferSizeDB (3) + maxBitrate (4) +
+    // avgBitrate (4)
+    match read_audio_object_type(decoder_config, 13) {
+        Some(audio_object_type) => Some(format!("mp4a.40.{}", audio_object_type)),
+        None => Some("mp4a.40".to_owned()),
+    }
+}
+
+/// Read the audio object type of the `AudioSpecificConfig` found in the `DecoderSpecificInfo`
+/// descriptor at the given offset of `data`.
+fn read_audio_object_type(data: &[u8], offset: usize) -> Option<u8> {
+    let (tag, audio_specific_config) = read_descriptor(data, offset)?;
+    if tag != 0x05 {
+        return None;
+    }
+    let first_byte = *audio_specific_config.first()?;
+    match first_byte >> 3 {
+        // Escape value, the actual audio object type is coded on the 6 following bits
+        31 => Some(32 + (((first_byte & 0x07) << 3) | (audio_specific_config.get(1)? >> 5))),
+        audio_object_type => Some(audio_object_type),
+    }
+}
+
+/// Read the MPEG-4 descriptor (as defined by ISO/IEC 14496-1) at the given offset of `data`,
+/// returning both its tag and its content.
+fn read_descriptor(data: &[u8], offset: usize) -> Option<(u8, &[u8])> {
+    let tag = *data.get(offset)?;
+    let mut size = 0;
+    let mut content_offset = offset + 1;
+    // The size is coded on up to 4 bytes, with 7 bits per byte, the most significant bit
+    // indicating if another byte follows
+    for _ in 0..4 {
+        let byte = *data.get(content_offset)?;
+        content_offset += 1;
+        size = (size << 7) | (byte & 0x7F) as usize;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    let content_end = (content_offset + size).min(data.len());
+    Some((tag, data.get(content_offset..content_end)?))
+}
+
+/// A single sample found in a fragmented MP4 segment.
+pub(crate) struct IsobmffSample<'a> {
+    /// Identifier of the track this sample is part of.
+    pub(crate) track_id: u32,
+    /// Decode time of that sample, in the track's timescale.
+    pub(crate) decode_time: u64,
+    /// Offset to add to `decode_time` to obtain that sample's presentation time, in the track's
+    /// timescale.
+    pub(crate) composition_offset: i64,
+    /// Duration of that sample, in the track's timescale. `0` if unknown.
+    pub(crate) duration: u32,
+    /// The sample's data, as found in the `mdat` box.
+    pub(crate) data: &'a [u8],
+}
+
+/// Read all samples from a fragmented MP4 segment, relying on the `tfhd`, `tfdt` and `trun`
+/// boxes of each of its `moof` to know where they are and what their timing is.
+///
+/// When sample sizes are not announced, the whole `mdat` following the `moof` is considered to
+/// be a single sample, as is usually the case for subtitles tracks.
+pub(crate) fn read_samples(segment: &[u8]) -> Vec<IsobmffSample<'_>> {
+    let mut samples = vec![];
+    let top_level_boxes = boxes(segment).collect::<Vec<IsobmffBox>>();
+    for (moof_idx, moof) in top_level_boxes.iter().enumerate() {
+        if &moof.box_type != b"moof" {
+            continue;
+        }
+        let next_mdat = top_level_boxes[moof_idx + 1..]
+            .iter()
+            .find(|b| &b.box_type == b"mdat");
+        boxes(moof.content)
+            .filter(|b| &b.box_type == b"traf")
+            .for_each(|traf| {
+                read_traf_samples(segment, moof.offset, next_mdat, traf.content, &mut samples)
+            });
+    }
+    samples
+}
+
+/// Push to `samples` the samples described by the given `traf` box content.
+fn read_traf_samples<'a>(
+    segment: &'a [u8],
+    moof_offset: usize,
+    next_mdat: Option<&IsobmffBox<'a>>,
+    traf: &[u8],
+    samples: &mut Vec<IsobmffSample<'a>>,
+) {
+    let Some(tfhd) = find_box(traf, b"tfhd") else {
+        return;
+    };
+    let tfhd_flags = read_u32(tfhd, 0).unwrap_or(0) & 0x00FF_FFFF;
+    let track_id = read_u32(tfhd, 4).unwrap_or(0);
+    // flags (4) + track_ID (4)
+    let mut tfhd_offset = 8;
+    let mut base_data_offset = moof_offset;
+    if tfhd_flags & 0x01 != 0 {
+        if let Some(offset) = read_u64(tfhd, tfhd_offset).and_then(|o| usize::try_from(o).ok()) {
+            base_data_offset = offset;
+        }
+        tfhd_offset += 8;
+    }
+    if tfhd_flags & 0x02 != 0 {
+        tfhd_offset += 4;
+    }
+    let mut default_duration = 0;
+    if tfhd_flags & 0x08 != 0 {
+        default_duration = read_u32(tfhd, tfhd_offset).unwrap_or(0);
+        tfhd_offset += 4;
+    }
+    let default_size = if tfhd_flags & 0x10 != 0 {
+        read_u32(tfhd, tfhd_offset)
+    } else {
+        None
+    };
+
+    let mut decode_time = find_box(traf, b"tfdt")
+        .and_then(|tfdt| match tfdt.first()? {
+            1 => read_u64(tfdt, 4),
+            _ => read_u32(tfdt, 4).map(|t| t as u64),
+        })
+        .unwrap_or(0);
+
+    // Data of a `trun` without a data offset directly follows the one of the previous `trun`,
+    // or begins at the start of the `mdat` for the first one
+    let mut data_pos = next_mdat.map(|mdat| mdat.content_offset);
+    for trun in boxes(traf).filter(|b| &b.box_type == b"trun") {
+        let trun = trun.content;
+        let trun_version = trun.first().copied().unwrap_or(0);
+        let trun_flags = read_u32(trun, 0).unwrap_or(0) & 0x00FF_FFFF;
+        let Some(sample_count) = read_u32(trun, 4) else {
+            return;
+        };
+        let mut trun_offset = 8;
+        if trun_flags & 0x01 != 0 {
+            if let Some(data_offset) = read_u32(trun, trun_offset) {
+                let pos = base_data_offset as i64 + data_offset as i32 as i64;
+                data_pos = usize::try_from(pos).ok();
+            }
+            trun_offset += 4;
+        }
+        if trun_flags & 0x04 != 0 {
+            trun_offset += 4;
+        }
+        // Each optional per-sample field takes 4 bytes
+        let sample_entry_size = [0x100, 0x200, 0x400, 0x800]
+            .iter()
+            .filter(|f| trun_flags & *f != 0)
+            .count()
+            * 4;
+        for _ in 0..sample_count {
+            if trun_offset + sample_entry_size > trun.len() {
+                // The `trun` is smaller than what its sample count implies
+                break;
+            }
+            let mut duration = default_duration;
+            if trun_flags & 0x100 != 0 {
+                duration = read_u32(trun, trun_offset).unwrap_or(default_duration);
+                trun_offset += 4;
+            }
+            let mut size = default_size;
+            if trun_flags & 0x200 != 0 {
+                size = read_u32(trun, trun_offset);
+                trun_offset += 4;
+            }
+            if trun_flags & 0x400 != 0 {
+                trun_offset += 4;
+            }
+            let mut composition_offset = 0;
+            if trun_flags & 0x800 != 0 {
+                let offset = read_u32(trun, trun_offset).unwrap_or(0);
+                composition_offset = if trun_version == 0 {
+                    offset as i64
+                } else {
+                    offset as i32 as i64
+                };
+                trun_offset += 4;
+            }
+
+            let data = match (data_pos, size) {
+                (Some(pos), Some(size)) => pos
+                    .checked_add(size as usize)
+                    .and_then(|end| segment.get(pos..end)),
+                (Some(pos), None) => {
+                    let mdat_end = next_mdat.map(|m| m.content_offset + m.content.len());
+                    mdat_end.and_then(|end| segment.get(pos..end))
+                }
+                (None, _) => None,
+            };
+            let Some(data) = data else {
+                // No more data to read samples from
+                break;
+            };
+            data_pos = data_pos.map(|pos| pos + data.len());
+            samples.push(IsobmffSample {
+                track_id,
+                decode_time,
+                composition_offset,
+                duration,
+                data,
+            });
+            decode_time += duration as u64;
+        }
+    }
+}
+
+/// Read a big-endian 16-bit unsigned integer at the given offset of `data`.
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes(bytes.try_into().ok()?))
+}
+
+/// Read a big-endian 32-bit unsigned integer at the given offset of `data`.
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+/// Read a big-endian 64-bit unsigned integer at the given offset of `data`.
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset + 8)?;
+    Some(u64::from_be_bytes(bytes.try_into().ok()?))
+}
+
+/// Wrap `content` into an ISOBMFF box of the given type, to build segments in tests.
+#[cfg(test)]
+pub(crate) fn mp4_box(box_type: &[u8; 4], content: &[u8]) -> Vec<u8> {
+    let mut ret = ((content.len() + 8) as u32).to_be_bytes().to_vec();
+    ret.extend_from_slice(box_type);
+    ret.extend_from_slice(content);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_track_timescale() {
+        let mut mdhd_v0 = vec![0; 12];
+        mdhd_v0.extend_from_slice(&1000u32.to_be_bytes());
+        mdhd_v0.extend_from_slice(&[0; 8]);
+        let mdia = mp4_box(
+            b"mdia",
+            &[mp4_box(b"hdlr", &[0; 8]), mp4_box(b"mdhd", &mdhd_v0)].concat(),
+        );
+        let init = [
+            mp4_box(b"ftyp", b"iso6"),
+            mp4_box(b"moov", &mp4_box(b"trak", &mdia)),
+        ]
+        .concat();
+        assert_eq!(read_track_timescale(&init), Some(1000));
+
+        let mut mdhd_v1 = vec![1, 0, 0, 0];
+        mdhd_v1.extend_from_slice(&[0; 16]);
+        mdhd_v1.extend_from_slice(&90000u32.to_be_bytes());
+        mdhd_v1.extend_from_slice(&[0; 12]);
+        let mdia = mp4_box(b"mdia", &mp4_box(b"mdhd", &mdhd_v1));
+        let init = mp4_box(b"moov", &mp4_box(b"trak", &mdia));
+        assert_eq!(read_track_timescale(&init), Some(90000));
+
+        assert_eq!(read_track_timescale(&mp4_box(b"moov", &[])), None);
+    }
+
+    /// Wrap the given sample entry into an initialization segment declaring a single track.
+    fn init_with_sample_entry(sample_entry: &[u8]) -> Vec<u8> {
+        let stsd = mp4_box(
+            b"stsd",
+            &[&[0, 0, 0, 0, 0, 0, 0, 1][..], sample_entry].concat(),
+        );
+        let stbl = mp4_box(b"stbl", &stsd);
+        let minf = mp4_box(b"minf", &stbl);
+        let mdia = mp4_box(b"mdia", &minf);
+        mp4_box(b"moov", &mp4_box(b"trak", &mdia))
+    }
+
+    #[test]
+    fn test_read_codecs() {
+        let visual_fields = [0; 78];
+        let audio_fields = [0; 28];
+
+        let avcc = mp4_box(b"avcC", &[1, 0x64, 0x00, 0x1F, 0xFF]);
+        let avc1 = mp4_box(b"avc1", &[&visual_fields[..], &avcc].concat());
+        assert_eq!(
+            read_codecs(&init_with_sample_entry(&avc1)),
+            vec!["avc1.64001F"]
+        );
+
+        let hvcc = [
+            &[1, 0x01][..],
+            &0x6000_0000u32.to_be_bytes(),
+            &[0xB0, 0, 0, 0, 0, 0],
+            &[93],
+        ]
+        .concat();
+        let hvc1 = mp4_box(
+            b"hvc1",
+            &[&visual_fields[..], &mp4_box(b"hvcC", &hvcc)].concat(),
+        );
+        assert_eq!(
+            read_codecs(&init_with_sample_entry(&hvc1)),
+            vec!["hvc1.1.6.L93.B0"]
+        );
+
+        let av01 = mp4_box(
+            b"av01",
+            &[
+                &visual_fields[..],
+                &mp4_box(b"av1C", &[0x81, 0x08, 0x40, 0]),
+            ]
+            .concat(),
+        );
+        assert_eq!(
+            read_codecs(&init_with_sample_entry(&av01)),
+            vec!["av01.0.08M.10"]
+        );
+
+        // ES_Descriptor > DecoderConfigDescriptor > DecoderSpecificInfo (AAC-LC)
+        let esds = [
+            &[0, 0, 0, 0][..],
+            &[0x03, 22, 0, 1, 0],
+            &[0x04, 17, 0x40, 0x15, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[0x05, 2, 0x12, 0x10],
+        ]
+        .concat();
+        let mp4a = mp4_box(
+            b"mp4a",
+            &[&audio_fields[..], &mp4_box(b"esds", &esds)].concat(),
+        );
+        assert_eq!(
+            read_codecs(&init_with_sample_entry(&mp4a)),
+            vec!["mp4a.40.2"]
+        );
+
+        // Encrypted entries are described through their original format
+        let sinf = mp4_box(b"sinf", &mp4_box(b"frma", b"ec-3"));
+        let enca = mp4_box(
+            b"enca",
+            &[&audio_fields[..], &mp4_box(b"dec3", &[0; 5]), &sinf].concat(),
+        );
+        let init = [mp4_box(b"ftyp", b"iso6"), init_with_sample_entry(&enca)].concat();
+        assert_eq!(read_codecs(&init), vec!["ec-3"]);
+
+        // Unknown formats and missing decoder configurations are ignored
+        let ac3 = mp4_box(b"ac-3", &audio_fields);
+        assert!(read_codecs(&init_with_sample_entry(&ac3)).is_empty());
+        let stpp = mp4_box(b"stpp", &[0; 16]);
+        assert!(read_codecs(&init_with_sample_entry(&stpp)).is_empty());
+    }
+
+    #[test]
+    fn test_read_samples() {
+        // tfhd with default-base-is-moof and a default sample duration of 500
+        let tfhd = [
+            &[0, 0x02, 0, 0x08][..],
+            &1u32.to_be_bytes(),
+            &500u32.to_be_bytes(),
+        ]
+        .concat();
+        // tfdt version 1
+        let tfdt = [&[1, 0, 0, 0][..], &10_000u64.to_be_bytes()].concat();
+        // trun with a data offset and sample sizes, the data offset is filled in afterwards
+        let trun = [
+            &[0, 0, 0x02, 0x01][..],
+            &2u32.to_be_bytes(),
+            &0u32.to_be_bytes(),
+            &3u32.to_be_bytes(),
+            &2u32.to_be_bytes(),
+        ]
+        .concat();
+        let traf = mp4_box(
+            b"traf",
+            &[
+                mp4_box(b"tfhd", &tfhd),
+                mp4_box(b"tfdt", &tfdt),
+                mp4_box(b"trun", &trun),
+            ]
+            .concat(),
+        );
+        let mut moof = mp4_box(b"moof", &traf);
+        let data_offset = (moof.len() + 8) as u32;
+        let trun_data_offset_idx = moof.len() - 12;
+        moof[trun_data_offset_idx..trun_data_offset_idx + 4]
+            .copy_from_slice(&data_offset.to_be_bytes());
+        let segment = [mp4_box(b"styp", b"msdh"), moof, mp4_box(b"mdat", b"abcde")].concat();
+
+        // As the `styp` box precedes the `moof`, offsets are relative to the latter
+        let samples = read_samples(&segment);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].decode_time, 10_000);
+        assert_eq!(samples[0].duration, 500);
+        assert_eq!(samples[0].data, b"abc");
+        assert_eq!(samples[1].decode_time, 10_500);
+        assert_eq!(samples[1].duration, 500);
+        assert_eq!(samples[1].data, b"de");
+
+        // Without sample sizes nor data offset, the whole `mdat` is the only sample
+        let trun = [
+            &[0, 0, 0x01, 0][..],
+            &1u32.to_be_bytes(),
+            &700u32.to_be_bytes(),
+        ]
+        .concat();
+        let traf = mp4_box(
+            b"traf",
+            &[mp4_box(b"tfhd", &[0; 8]), mp4_box(b"trun", &trun)].concat(),
+        );
+        let segment = [mp4_box(b"moof", &traf), mp4_box(b"mdat", b"abcde")].concat();
+        let samples = read_samples(&segment);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].decode_time, 0);
+        assert_eq!(samples[0].duration, 700);
+        assert_eq!(samples[0].data, b"abcde");
+
+        // Reading stops once the `mdat` is exhausted, despite the announced sample count
+        let tfhd = [
+            &[0, 0x02, 0, 0x10][..],
+            &1u32.to_be_bytes(),
+            &2u32.to_be_bytes(),
+        ]
+        .concat();
+        let trun = [&[0, 0, 0, 0][..], &u32::MAX.to_be_bytes()].concat();
+        let traf = mp4_box(
+            b"traf",
+            &[mp4_box(b"tfhd", &tfhd), mp4_box(b"trun", &trun)].concat(),
+        );
+        let segment = [mp4_box(b"moof", &traf), mp4_box(b"mdat", b"abcde")].concat();
+        let samples = read_samples(&segment);
+        let data: Vec<&[u8]> = samples.iter().map(|s| s.data).collect();
+        assert_eq!(data, vec![b"ab", b"cd"]);
+
+        // It also stops at the end of the `trun`, despite the announced sample count
+        let trun = [
+            &[0, 0, 0x02, 0][..],
+            &u32::MAX.to_be_bytes(),
+            &1u32.to_be_bytes(),
+            &1u32.to_be_bytes(),
+        ]
+        .concat();
+        let traf = mp4_box(
+            b"traf",
+            &[mp4_box(b"tfhd", &[0; 8]), mp4_box(b"trun", &trun)].concat(),
+        );
+        let segment = [mp4_box(b"moof", &traf), mp4_box(b"mdat", b"abcde")].concat();
+        let samples = read_samples(&segment);
+        let data: Vec<&[u8]> = samples.iter().map(|s| s.data).collect();
+        assert_eq!(data, vec![b"a", b"b"]);
+    }
+}
@@ -1,3 +1,6 @@
+pub mod aes;
+pub mod isobmff;
 pub mod logger;
+pub mod mpeg_ts;
 pub mod time_ranges;
 pub mod url;
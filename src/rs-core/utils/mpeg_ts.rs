@@ -0,0 +1,178 @@
+//! Minimal MPEG-2 Transport Stream reading utilities, only implementing what is needed to read
+//! the few informations the player needs from MPEG-2 TS segments.
+
+/// Size in bytes of a single transport stream packet.
+const PACKET_SIZE: usize = 188;
+
+/// Byte each transport stream packet begins with.
+const SYNC_BYTE: u8 = 0x47;
+
+/// PID of the packets carrying the Program Association Table.
+const PAT_PID: u16 = 0x0000;
+
+/// Returns the formats of the elementary streams announced by the Program Map Table of the given
+/// MPEG-2 TS segment, in the syntax of the `CODECS` attribute (e.g. `"mp4a.40.2"` for AAC).
+///
+/// As the Program Map Table only tells the type of each stream, the most common profile is
+/// assumed for video formats.
+///
+/// Returns an empty `Vec` if that table could not be found or if no known format is announced.
+pub(crate) fn read_codecs(segment: &[u8]) -> Vec<String> {
+    let mut pmt_pid: Option<u16> = None;
+    for packet in segment.chunks_exact(PACKET_SIZE) {
+        if packet[0] != SYNC_BYTE {
+            return vec![];
+        }
+        let is_payload_unit_start = packet[1] & 0x40 != 0;
+        if !is_payload_unit_start {
+            // Tables are assumed to fit in the packet starting them
+            continue;
+        }
+        let pid = u16::from_be_bytes([packet[1] & 0x1F, packet[2]]);
+        let Some(section) = packet_payload(packet).and_then(psi_section) else {
+            continue;
+        };
+        if pid == PAT_PID {
+            pmt_pid = read_pmt_pid(section);
+        } else if Some(pid) == pmt_pid {
+            return read_pmt_codecs(section);
+        }
+    }
+    vec![]
+}
+
+/// Returns the payload of the given transport stream packet, its adaptation field excluded.
+///
+/// Returns `None` if it has no payload.
+fn packet_payload(packet: &[u8]) -> Option<&[u8]> {
+    match (packet[3] >> 4) & 0x03 {
+        0x01 => packet.get(4..),
+        0x03 => packet.get(5 + usize::from(*packet.get(4)?)..),
+        _ => None,
+    }
+}
+
+/// Returns the Program-Specific Information section starting in the given payload, from its
+/// `table_id` to its CRC.
+///
+/// Returns `None` if it is truncated.
+fn psi_section(payload: &[u8]) -> Option<&[u8]> {
+    let pointer_field = usize::from(*payload.first()?);
+    let section = payload.get(1 + pointer_field..)?;
+    let section_length = usize::from(read_12_bits(section, 1)?);
+    section.get(..3 + section_length)
+}
+
+/// Returns the PID of the packets carrying the Program Map Table of the first program
+/// announced in the given Program Association Table section.
+fn read_pmt_pid(section: &[u8]) -> Option<u16> {
+    if section[0] != 0x00 {
+        return None;
+    }
+    // Skip the section's header and CRC
+    let programs = section.get(8..section.len().checked_sub(4)?)?;
+    programs
+        .chunks_exact(4)
+        // Program number `0` points to the Network Information Table instead
+        .find(|p| u16::from_be_bytes([p[0], p[1]]) != 0)
+        .map(|p| u16::from_be_bytes([p[2] & 0x1F, p[3]]))
+}
+
+/// Returns the formats of the elementary streams announced in the given Program Map Table
+/// section.
+fn read_pmt_codecs(section: &[u8]) -> Vec<String> {
+    let mut codecs: Vec<String> = vec![];
+    if section[0] != 0x02 {
+        return codecs;
+    }
+    let Some(program_info_length) = read_12_bits(section, 10) else {
+        return codecs;
+    };
+    let end = section.len().saturating_sub(4);
+    let mut offset = 12 + usize::from(program_info_length);
+    while offset + 5 <= end {
+        if let Some(codec) = codec_for_stream_type(section[offset]) {
+            if !codecs.iter().any(|c| c == codec) {
+                codecs.push(codec.to_owned());
+            }
+        }
+        let es_info_length = read_12_bits(section, offset + 3).unwrap_or(0);
+        offset += 5 + usize::from(es_info_length);
+    }
+    codecs
+}
+
+/// Returns the format, in the syntax of the `CODECS` attribute, corresponding to the given
+/// `stream_type` of a Program Map Table.
+///
+/// Returns `None` if unknown or if that stream is not audio or video.
+fn codec_for_stream_type(stream_type: u8) -> Option<&'static str> {
+    match stream_type {
+        0x1B => Some("avc1.4D401F"),
+        0x24 => Some("hvc1.1.6.L93.B0"),
+        0x0F | 0x11 => Some("mp4a.40.2"),
+        0x03 | 0x04 => Some("mp3"),
+        0x81 => Some("ac-3"),
+        0x87 => Some("ec-3"),
+        _ => None,
+    }
+}
+
+/// Read the 12 least significant bits of the big-endian 16 bits integer at `offset`.
+fn read_12_bits(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0] & 0x0F, bytes[1]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wrap the given PSI section into a transport stream packet with the given PID.
+    fn psi_packet(pid: u16, section: &[u8]) -> Vec<u8> {
+        let mut packet = vec![SYNC_BYTE, 0x40 | (pid >> 8) as u8, pid as u8, 0x10, 0x00];
+        packet.extend_from_slice(section);
+        packet.resize(PACKET_SIZE, 0xFF);
+        packet
+    }
+
+    /// Creates a PSI section with the given `table_id` and content, followed by a dummy CRC.
+    fn section(table_id: u8, content: &[u8]) -> Vec<u8> {
+        let length = (content.len() + 5 + 4) as u16;
+        let mut ret = vec![table_id, 0xB0 | (length >> 8) as u8, length as u8];
+        ret.extend_from_slice(&[0x00, 0x01, 0xC1, 0x00, 0x00]);
+        ret.extend_from_slice(content);
+        ret.extend_from_slice(&[0; 4]);
+        ret
+    }
+
+    #[test]
+    fn test_read_codecs() {
+        let pat = section(0x00, &[0x00, 0x01, 0xF0, 0x00]);
+        let pmt = section(
+            0x02,
+            &[
+                0xE1, 0x00, 0xF0, 0x00, // PCR PID, no program info
+                0x1B, 0xE1, 0x00, 0xF0, 0x00, // AVC
+                0x15, 0xE1, 0x02, 0xF0, 0x00, // ID3 timed metadata
+                0x0F, 0xE1, 0x01, 0xF0, 0x02, 0x0A, 0x00, // AAC, with a descriptor
+            ],
+        );
+        let segment = [psi_packet(0x0000, &pat), psi_packet(0x1000, &pmt)].concat();
+        assert_eq!(read_codecs(&segment), vec!["avc1.4D401F", "mp4a.40.2"]);
+
+        // Audio-only
+        let pmt = section(
+            0x02,
+            &[0xE1, 0x01, 0xF0, 0x00, 0x0F, 0xE1, 0x01, 0xF0, 0x00],
+        );
+        let segment = [psi_packet(0x0000, &pat), psi_packet(0x1000, &pmt)].concat();
+        assert_eq!(read_codecs(&segment), vec!["mp4a.40.2"]);
+
+        // PMT before the PAT cannot be identified
+        let segment = [psi_packet(0x1000, &pmt), psi_packet(0x0000, &pat)].concat();
+        assert!(read_codecs(&segment).is_empty());
+
+        assert!(read_codecs(&[0; PACKET_SIZE]).is_empty());
+    }
+}
@@ -39,6 +39,16 @@ impl Url {
         }
     }
 
+    /// Creates a `Url` from `url`, resolved against `base_url` if it is relative.
+    pub fn resolve(url: &str, base_url: &str) -> Self {
+        let url = Url::new(url.to_owned());
+        if url.is_absolute() {
+            url
+        } else {
+            Url::from_relative(base_url, url)
+        }
+    }
+
     pub fn take(self) -> String {
         self.inner
     }
@@ -91,6 +101,64 @@ impl Url {
         }
     }
 
+    /// Add the `name` query parameter with the given `value` to this `Url`, before its fragment
+    /// if one.
+    ///
+    /// `value` is percent-encoded, `name` is expected to only contain URL-safe characters.
+    pub fn add_query_parameter(&mut self, name: &str, value: &str) {
+        let fragment_idx = self.inner.find('#').unwrap_or(self.inner.len());
+        let separator = if self.inner[..fragment_idx].contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        self.inner.insert_str(
+            fragment_idx,
+            &format!("{separator}{name}={}", percent_encode(value)),
+        );
+    }
+
+    /// Returns a copy of this `Url` whose hostname is replaced by `host`, its scheme, port and
+    /// path being kept as is.
+    ///
+    /// Relative URLs, which have no hostname, are returned unchanged.
+    pub fn with_host(&self, host: &str) -> Url {
+        match self.domain_name() {
+            Some(domain) => {
+                let host_start = domain.find("://").map_or(0, |idx| idx + 3);
+                let host_end = domain[host_start..]
+                    .find(':')
+                    .map_or(domain.len(), |idx| host_start + idx);
+                Url {
+                    inner: format!(
+                        "{}{}{}",
+                        &self.inner[..host_start],
+                        host,
+                        &self.inner[host_end..]
+                    ),
+                }
+            }
+            None => self.clone(),
+        }
+    }
+
+    /// Returns the percent-decoded value of the first `name` query parameter of this `Url`, if
+    /// it has one.
+    pub fn query_parameter(&self, name: &str) -> Option<String> {
+        let without_fragment = match self.inner.find('#') {
+            Some(idx) => &self.inner[0..idx],
+            None => &self.inner,
+        };
+        let query = &without_fragment[without_fragment.find('?')? + 1..];
+        query
+            .split('&')
+            .find_map(|param| match param.split_once('=') {
+                Some((n, value)) if n == name => Some(percent_decode(value)),
+                None if param == name => Some(String::new()),
+                _ => None,
+            })
+    }
+
     pub fn extension(&self) -> &str {
         let filename = self.filename();
         let last_dot = filename.rfind('.');
@@ -155,3 +223,63 @@ fn url_domain_name(url: &str) -> Option<&str> {
         }
     }
 }
+
+/// Encode all characters of `value` which are not unreserved in URLs (alphanumeric characters
+/// and `-`, `.`, `_` and `~`) into `%XX` escape sequences of their UTF-8 bytes.
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Decode the `%XX` escape sequences found in `value`, the ones not forming a valid UTF-8
+/// sequence being replaced by the replacement character.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' && idx + 2 < bytes.len() {
+            let escaped = std::str::from_utf8(&bytes[idx + 1..idx + 3]).ok();
+            if let Some(byte) = escaped.and_then(|e| u8::from_str_radix(e, 16).ok()) {
+                decoded.push(byte);
+                idx += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[idx]);
+        idx += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_query_parameter() {
+        let mut url = Url::new("https://example.com/media.m3u8#frag".to_owned());
+        url.add_query_parameter("_HLS_pathway", "CDN A&B=1");
+        url.add_query_parameter("_HLS_msn", "12");
+        assert_eq!(
+            url.get_ref(),
+            "https://example.com/media.m3u8?_HLS_pathway=CDN%20A%26B%3D1&_HLS_msn=12#frag"
+        );
+        assert_eq!(
+            url.query_parameter("_HLS_pathway").as_deref(),
+            Some("CDN A&B=1")
+        );
+
+        let mut url = Url::new("media.m3u8?a=b".to_owned());
+        url.add_query_parameter("name", "é~");
+        assert_eq!(url.get_ref(), "media.m3u8?a=b&name=%C3%A9~");
+        assert_eq!(url.query_parameter("name").as_deref(), Some("é~"));
+    }
+}
@@ -7,6 +7,8 @@ export enum SourceBufferOperation {
   Push,
   /** Removing data from the `SourceBuffer`. */
   Remove,
+  /** Updating the `timestampOffset` of the `SourceBuffer`. */
+  SetTimestampOffset,
 }
 
 /*
@@ -35,8 +37,20 @@ interface RemoveAction {
   };
 }
 
+/**
+ * Action created by the QueuedSourceBuffer to update the `timestampOffset` of
+ * the SourceBuffer.
+ * Will be converted into an `SetTimestampOffsetQueueItem` once in the queue
+ */
+interface SetTimestampOffsetAction {
+  /** Identifies a `SetTimestampOffsetAction`. */
+  type: SourceBufferOperation.SetTimestampOffset;
+  /** The new `timestampOffset`, in seconds. */
+  value: number;
+}
+
 /** Actions understood by the QueuedSourceBuffer. */
-type QSBAction = PushAction | RemoveAction;
+type QSBAction = PushAction | RemoveAction | SetTimestampOffsetAction;
 
 /** Item waiting in the queue to push a new chunk to the SourceBuffer. */
 interface PushQueueItem extends PushAction {
@@ -54,8 +68,19 @@ interface RemoveQueueItem extends RemoveAction {
   reject: (err: Error) => void;
 }
 
+/** Item waiting in the queue to update the SourceBuffer's `timestampOffset`. */
+interface SetTimestampOffsetQueueItem extends SetTimestampOffsetAction {
+  /** Resolve the corresponding operation's promise. */
+  resolve: () => void;
+  /** Reject the corresponding operation's promise. */
+  reject: (err: Error) => void;
+}
+
 /** Action waiting in the queue. */
-type QSBQueueItem = PushQueueItem | RemoveQueueItem;
+type QSBQueueItem =
+  | PushQueueItem
+  | RemoveQueueItem
+  | SetTimestampOffsetQueueItem;
 
 /**
  * Allows to push and remove new Segments to a SourceBuffer in a FIFO queue (not
@@ -170,6 +195,20 @@ export default class QueuedSourceBuffer {
     });
   }
 
+  /**
+   * Update the `timestampOffset` of the SourceBuffer (added to the same FIFO
+   * queue than `push`), which will apply to data pushed after it.
+   * @param {number} offset - The new `timestampOffset`, in seconds
+   * @returns {Promise}
+   */
+  public setTimestampOffset(offset: number): Promise<void> {
+    logger.debug("QSB: receiving order to update the timestampOffset", offset);
+    return this._addToQueue({
+      type: SourceBufferOperation.SetTimestampOffset,
+      value: offset,
+    });
+  }
+
   /**
    * Returns the currently buffered data, in a TimeRanges object.
    * @returns {TimeRanges}
@@ -291,6 +330,13 @@ export default class QueuedSourceBuffer {
           this._sourceBuffer.remove(start, end);
           break;
 
+        case SourceBufferOperation.SetTimestampOffset:
+          logger.debug("QSB: updating timestampOffset", this._pendingTask.value);
+          this._sourceBuffer.timestampOffset = this._pendingTask.value;
+          // No `updateend` event is sent for this operation, continue directly
+          this._flush();
+          break;
+
         default:
           assertNever(this._pendingTask);
       }
@@ -35,10 +35,15 @@ export type MainMessage =
   | SourceBufferOperationSuccessMainMessage
   | CodecsSupportUpdateMainMessage
   | UpdateWantedSpeedMainMessage
+  | UpdateScrubbingMainMessage
+  | UpdateDisplayCapabilitiesMainMessage
   | UpdateLoggerLevelMainMessage
   | LockVariantMainMessage
   | UpdateConfigMainMessage
-  | SetAudioTrackMainMessage;
+  | SetAudioTrackMainMessage
+  | SetVideoTrackMainMessage
+  | SetTextTrackMainMessage
+  | LoadSessionDataMainMessage;
 
 /**
  * Discriminants (value of the `type` property) for messages sent by the main
@@ -61,10 +66,15 @@ export const enum MainMessageType {
   SourceBufferOperationError = "sb-err",
   EndOfStreamError = "eos-err",
   UpdateWantedSpeed = "upd-speed",
+  UpdateScrubbing = "upd-scrub",
+  UpdateDisplayCapabilities = "upd-display",
   UpdateLoggerLevel = "upd-log",
   UpdateConfig = "upd-conf",
   LockVariant = "lock-var",
   SetAudioTrack = "set-audio",
+  SetVideoTrack = "set-video",
+  SetTextTrack = "set-text",
+  LoadSessionData = "load-session-data",
   CodecsSupportUpdate = "codecs-support-upd",
 }
 
@@ -81,7 +91,13 @@ export type WorkerMessage =
   | MediaOffsetUpdateWorkerMessage
   | VariantUpdateWorkerMessage
   | TrackUpdateWorkerMessage
+  | TextTrackUpdateWorkerMessage
   | VariantLockStatusChangeWorkerMessage
+  | DateRangeEventWorkerMessage
+  | InterstitialEventWorkerMessage
+  | SessionDataWorkerMessage
+  | AddTextCueWorkerMessage
+  | ClearTextCuesWorkerMessage
 
   // HTMLMediaElement/MSE actions
   | SeekWorkerMessage
@@ -94,6 +110,7 @@ export type WorkerMessage =
   | CreateSourceBufferWorkerMessage
   | AppendBufferWorkerMessage
   | RemoveBufferWorkerMessage
+  | SetTimestampOffsetWorkerMessage
   | EndOfStreamWorkerMessage
   | AreTypesSupportedWorkerMessage
 
@@ -122,6 +139,7 @@ export const enum WorkerMessageType {
   ContentInfoUpdate = "content-upd",
   MultivariantPlaylistParsed = "m-playlist",
   TrackUpdate = "track-upd",
+  TextTrackUpdate = "text-track-upd",
   ContentStopped = "ctnt-stop",
   Seek = "seek",
   Flush = "flush",
@@ -133,6 +151,7 @@ export const enum WorkerMessageType {
   CreateSourceBuffer = "creat-sb",
   AppendBuffer = "push-sb",
   RemoveBuffer = "rem-sb",
+  SetTimestampOffset = "set-ts-off",
   StartPlaybackObservation = "start-obs",
   StopPlaybackObservation = "stop-obs",
   EndOfStream = "eos",
@@ -141,6 +160,11 @@ export const enum WorkerMessageType {
   MediaOffsetUpdate = "media-off-upd",
   VariantUpdate = "variant-upd",
   VariantLockStatusChange = "variant-lck-upd",
+  DateRangeEvent = "date-range-evt",
+  InterstitialEvent = "interstitial-evt",
+  SessionData = "session-data",
+  AddTextCue = "add-text-cue",
+  ClearTextCues = "clear-text-cues",
   AreTypesSupported = "are-types-supp",
 }
 
@@ -417,7 +441,14 @@ export interface MultivariantPlaylistParsedWorkerMessage {
      */
     contentId: string;
     variants: VariantInfo[];
+    /**
+     * Variants which are not part of `variants` because they cannot be
+     * presented with the current output protection capabilities.
+     */
+    filteredVariants: FilteredVariantInfo[];
     audioTracks: AudioTrackInfo[];
+    videoTracks: VideoTrackInfo[];
+    textTracks: TextTrackInfo[];
   };
 }
 
@@ -437,6 +468,29 @@ export interface TrackUpdateWorkerMessage {
           isSelected: boolean;
         }
       | undefined;
+    videoTrack?:
+      | {
+          current: number;
+          isSelected: boolean;
+        }
+      | undefined;
+  };
+}
+
+export interface TextTrackUpdateWorkerMessage {
+  type: WorkerMessageType.TextTrackUpdate;
+  value: {
+    /**
+     * The identifier for the content for which the message was sent.
+     * This is the same `contentId` value that on the related
+     * `LoadContentMainMessage`.
+     */
+    contentId: string;
+    /**
+     * The `id` of the text track now selected, `undefined` if text tracks are
+     * disabled.
+     */
+    textTrackId: number | undefined;
   };
 }
 
@@ -453,12 +507,240 @@ export interface VariantLockStatusChangeWorkerMessage {
   };
 }
 
+/**
+ * Message sent when the current position entered or left a date range
+ * announced through an `EXT-X-DATERANGE` tag.
+ */
+export interface DateRangeEventWorkerMessage {
+  type: WorkerMessageType.DateRangeEvent;
+  value: {
+    /**
+     * The identifier for the content for which the message was sent.
+     * This is the same `contentId` value that on the related
+     * `LoadContentMainMessage`.
+     */
+    contentId: string;
+    /**
+     * If `true`, the current position just entered that date range.
+     * If `false`, it just left it.
+     */
+    isEntering: boolean;
+    /** Information on the corresponding date range. */
+    dateRange: DateRangeInfo;
+  };
+}
+
+/**
+ * Message sent when an HLS Interstitial began or stopped being played in place
+ * of the primary content.
+ */
+export interface InterstitialEventWorkerMessage {
+  type: WorkerMessageType.InterstitialEvent;
+  value: {
+    /**
+     * The identifier for the content for which the message was sent.
+     * This is the same `contentId` value that on the related
+     * `LoadContentMainMessage`.
+     */
+    contentId: string;
+    /**
+     * If `true`, the interstitial just began to be played.
+     * If `false`, it just ended and the primary content is being resumed.
+     */
+    isStarting: boolean;
+    /** `ID` of the `EXT-X-DATERANGE` tag announcing that interstitial. */
+    id: string;
+  };
+}
+
+/**
+ * Message sent when the data announced through an `EXT-X-SESSION-DATA` tag is
+ * available, generally after a `LoadSessionDataMainMessage`.
+ */
+export interface SessionDataWorkerMessage {
+  type: WorkerMessageType.SessionData;
+  value: {
+    /**
+     * The identifier for the content for which the message was sent.
+     * This is the same `contentId` value that on the related
+     * `LoadContentMainMessage`.
+     */
+    contentId: string;
+    /** The data in question. */
+    sessionData: SessionDataInfo;
+  };
+}
+
+/** Message sent when a cue of the current text track should be displayed. */
+export interface AddTextCueWorkerMessage {
+  type: WorkerMessageType.AddTextCue;
+  value: {
+    /**
+     * The identifier for the content for which the message was sent.
+     * This is the same `contentId` value that on the related
+     * `LoadContentMainMessage`.
+     */
+    contentId: string;
+    /** The cue in question. */
+    cue: TextCueInfo;
+  };
+}
+
+/**
+ * Message sent when all cues previously sent through `AddTextCue` messages
+ * should be removed, generally because the text track changed.
+ */
+export interface ClearTextCuesWorkerMessage {
+  type: WorkerMessageType.ClearTextCues;
+  value: {
+    /**
+     * The identifier for the content for which the message was sent.
+     * This is the same `contentId` value that on the related
+     * `LoadContentMainMessage`.
+     */
+    contentId: string;
+  };
+}
+
+/**
+ * A subtitles cue parsed by the worker, with the same semantics than a WebVTT
+ * cue.
+ */
+export interface TextCueInfo {
+  /** Identifier of that cue in the segment it was found in, if one. */
+  id: string | undefined;
+  /** Playlist position at which the cue begins to be displayed, in seconds. */
+  start: number;
+  /** Playlist position at which the cue stops being displayed, in seconds. */
+  end: number;
+  /** Text of the cue, which may contain WebVTT markup. */
+  text: string;
+  vertical: "" | "rl" | "lr";
+  line: number | "auto";
+  snapToLines: boolean;
+  lineAlign: "start" | "center" | "end";
+  position: number | "auto";
+  positionAlign: "auto" | "line-left" | "center" | "line-right";
+  size: number;
+  align: "start" | "center" | "end" | "left" | "right";
+  /** The region the cue should be displayed in, if one. */
+  region: TextCueRegionInfo | undefined;
+}
+
+/** A region in which `TextCueInfo` can be displayed, as in WebVTT. */
+export interface TextCueRegionInfo {
+  id: string;
+  width: number;
+  lines: number;
+  regionAnchorX: number;
+  regionAnchorY: number;
+  viewportAnchorX: number;
+  viewportAnchorY: number;
+  scroll: "" | "up";
+}
+
+/** Data announced through an `EXT-X-SESSION-DATA` tag. */
+export type SessionDataInfo = {
+  /** The `DATA-ID` attribute, identifying that data. */
+  dataId: string;
+  /** The `LANGUAGE` attribute of that data, if one. */
+  language: string | undefined;
+} & (
+  | {
+      /** The data was set through the `VALUE` attribute. */
+      type: "string";
+      value: string;
+    }
+  | {
+      /** The data is the JSON loaded from the `URI` attribute, parsed. */
+      type: "json";
+      value: unknown;
+    }
+  | {
+      /** The data is the raw file loaded from the `URI` attribute. */
+      type: "raw";
+      value: ArrayBuffer;
+    }
+);
+
+/** Information on a date range announced through an `EXT-X-DATERANGE` tag. */
+export interface DateRangeInfo {
+  /** Unique identifier for that date range. */
+  id: string;
+  /**
+   * Client-defined string specifying a set of attributes and their associated
+   * semantics.
+   */
+  class: string | undefined;
+  /** Position at which the date range begins, in seconds. */
+  start: number;
+  /**
+   * Position at which the date range ends, in seconds.
+   * `undefined` if not yet known.
+   */
+  end: number | undefined;
+  /** Duration of the date range in seconds, if announced. */
+  duration: number | undefined;
+  /**
+   * Expected duration of the date range in seconds, if announced, for when its
+   * actual duration is not yet known.
+   */
+  plannedDuration: number | undefined;
+  /**
+   * If `true`, the date range ends at the start of the following date range
+   * with the same `class`.
+   */
+  endOnNext: boolean;
+  /** `SCTE35-CMD` attribute, as an hexadecimal string (e.g. `"0xFC30"`). */
+  scte35Cmd: string | undefined;
+  /** `SCTE35-OUT` attribute, as an hexadecimal string (e.g. `"0xFC30"`). */
+  scte35Out: string | undefined;
+  /** `SCTE35-IN` attribute, as an hexadecimal string (e.g. `"0xFC30"`). */
+  scte35In: string | undefined;
+  /**
+   * Client-defined attributes (whose name begin with `X-`), with their name
+   * as key.
+   *
+   * Their value is a number for decimal floating-point values and a string
+   * for quoted-string and hexadecimal-sequence values.
+   */
+  clientAttributes: Partial<Record<string, string | number>>;
+}
+
 export interface VariantInfo {
   id: number;
   width: number | undefined;
   height: number | undefined;
   frameRate: number | undefined;
   bandwidth: number | undefined;
+  usesSupplementalCodecs: boolean;
+  videoRange: VideoRange;
+}
+
+/**
+ * Dynamic range of a video, as announced by the `VIDEO-RANGE` attribute of
+ * HLS variants:
+ *   - `"SDR"`: Standard Dynamic Range.
+ *   - `"HLG"`: Hybrid Log-Gamma, an High Dynamic Range format.
+ *   - `"PQ"`: Perceptual Quantizer, an High Dynamic Range format (e.g. HDR10
+ *     or Dolby Vision).
+ */
+export type VideoRange = "SDR" | "HLG" | "PQ";
+
+/**
+ * Information on a variant which cannot be played due to the output
+ * protection it requires.
+ */
+export interface FilteredVariantInfo extends VariantInfo {
+  /**
+   * The reason why that variant cannot be played:
+   *   - `"hdcp-level"`: it requires a higher HDCP level than the one set
+   *     through the `maxHdcpLevel` configuration.
+   *   - `"allowed-cpc"`: none of the Content Protection Configurations it
+   *     allows are satisfied according to the
+   *     `contentProtectionConfigurations` configuration.
+   */
+  filterReason: "hdcp-level" | "allowed-cpc";
 }
 
 export interface AudioTrackInfo {
@@ -467,6 +749,30 @@ export interface AudioTrackInfo {
   assocLanguage?: string | undefined;
   name: string;
   channels?: number | undefined;
+  spatialAudioIdentifiers: string[];
+  binaural: boolean;
+  immersive: boolean;
+  downmix: boolean;
+  characteristics: string[];
+}
+
+export interface VideoTrackInfo {
+  id: number;
+  language?: string | undefined;
+  assocLanguage?: string | undefined;
+  name: string;
+}
+
+export interface TextTrackInfo {
+  id: number;
+  language?: string | undefined;
+  assocLanguage?: string | undefined;
+  name: string;
+  forced: boolean;
+  default: boolean;
+  autoSelect: boolean;
+  characteristics: string[];
+  instreamId: string | undefined;
 }
 
 /**
@@ -714,6 +1020,34 @@ export interface RemoveBufferWorkerMessage {
   };
 }
 
+/**
+ * Sent when the Worker wants to update the `timestampOffset` property of the
+ * SourceBuffer corresponding to the `sourceBufferId` given.
+ *
+ * Like for `AppendBufferWorkerMessage` and `RemoveBufferWorkerMessage`, this
+ * operation should be queued after the ones previously sent for that same
+ * SourceBuffer.
+ */
+export interface SetTimestampOffsetWorkerMessage {
+  type: WorkerMessageType.SetTimestampOffset;
+  value: {
+    /**
+     * Identify the MediaSource currently used by the worker.
+     * The main thread should only update the SourceBuffer if it is still the
+     * MediaSource being used.
+     */
+    mediaSourceId: string;
+    /**
+     * Id uniquely identifying this SourceBuffer.
+     * It should be the same `sourceBufferId` than the one on the
+     * `CreateSourceBufferWorkerMessage`.
+     */
+    sourceBufferId: SourceBufferId;
+    /** The new `timestampOffset`, in seconds. */
+    offset: number;
+  };
+}
+
 /**
  * Sent when the worker wants to start receiving regularly "playback
  * observations", which are key attributes associated to the HTMLVideoElement.
@@ -1176,6 +1510,32 @@ export interface UpdateWantedSpeedMainMessage {
   };
 }
 
+/**
+ * Sent by the main thread to a Worker when the user started or stopped
+ * scrubbing.
+ */
+export interface UpdateScrubbingMainMessage {
+  type: MainMessageType.UpdateScrubbing;
+  value: {
+    /** Identify the MediaSource in question. */
+    mediaSourceId: string;
+    /** If `true`, the user is now scrubbing. */
+    isScrubbing: boolean;
+  };
+}
+
+/**
+ * Sent by the main thread to a Worker to report which video dynamic ranges
+ * can be rendered by the current display, initially and each time it changes.
+ */
+export interface UpdateDisplayCapabilitiesMainMessage {
+  type: MainMessageType.UpdateDisplayCapabilities;
+  value: {
+    /** The video dynamic ranges supported by the display. */
+    videoRanges: VideoRange[];
+  };
+}
+
 export interface UpdateLoggerLevelMainMessage {
   type: MainMessageType.UpdateLoggerLevel;
   value: LoggerLevel;
@@ -1212,6 +1572,57 @@ export interface SetAudioTrackMainMessage {
   };
 }
 
+export interface SetVideoTrackMainMessage {
+  type: MainMessageType.SetVideoTrack;
+  value: {
+    /**
+     * The identifier for the content on which the video track should be set.
+     * This is the same `contentId` value that on the related
+     * `LoadContentMainMessage`.
+     */
+    contentId: string;
+    /**
+     * The `id` of the wanted video track, `null` to rely on the content's
+     * default video renditions.
+     */
+    trackId: number | null;
+  };
+}
+
+export interface SetTextTrackMainMessage {
+  type: MainMessageType.SetTextTrack;
+  value: {
+    /**
+     * The identifier for the content on which the text track should be set.
+     * This is the same `contentId` value that on the related
+     * `LoadContentMainMessage`.
+     */
+    contentId: string;
+    /** The `id` of the wanted text track, `null` to disable text tracks. */
+    trackId: number | null;
+  };
+}
+
+/**
+ * Sent by the main thread to a Worker when the user asked for the data
+ * announced through an `EXT-X-SESSION-DATA` tag.
+ */
+export interface LoadSessionDataMainMessage {
+  type: MainMessageType.LoadSessionData;
+  value: {
+    /**
+     * The identifier for the content concerned.
+     * This is the same `contentId` value that on the related
+     * `LoadContentMainMessage`.
+     */
+    contentId: string;
+    /** The `DATA-ID` attribute of the wanted `EXT-X-SESSION-DATA` tag. */
+    dataId: string;
+    /** If set, the `LANGUAGE` attribute to prefer. */
+    language: string | undefined;
+  };
+}
+
 /**
  * Configuration object relied on by the `WaspHlsPlayer`.
  */
@@ -1225,6 +1636,42 @@ export interface WaspHlsPlayerConfig {
    */
   bufferGoal: number;
 
+  /**
+   * If `true`, audio renditions with accessibility characteristics (e.g. audio
+   * descriptions) will be preferred when no audio track is explicitely
+   * selected.
+   */
+  preferAccessibleAudio: boolean;
+
+  /**
+   * Maximum number of audio channels that can be output. Audio renditions with
+   * more channels will be avoided when no audio track is explicitely selected.
+   *
+   * To set to `-1` for no limit.
+   */
+  maxAudioChannels: number;
+
+  /**
+   * Highest HDCP level that can be enforced on the output by the current
+   * device. Variants requiring a higher level won't be played.
+   *
+   * To set to `null` if unknown, in which case the HDCP level required by
+   * variants is not considered.
+   */
+  maxHdcpLevel: "NONE" | "TYPE-0" | "TYPE-1" | null;
+
+  /**
+   * Content Protection Configurations (e.g. `"SW"` or `"HW"`) satisfied by
+   * the current device, per `KEYFORMAT` (e.g.
+   * `"com.apple.streamingkeydelivery"`).
+   * Variants whose Content Protection Configurations cannot be satisfied won't
+   * be played.
+   *
+   * To set to `null` if unknown, in which case the Content Protection
+   * Configurations allowed by variants are not considered.
+   */
+  contentProtectionConfigurations: Partial<Record<string, string[]>> | null;
+
   // Request options
 
   /**
@@ -0,0 +1,119 @@
+import logger from "../ts-common/logger";
+import type { TextCueInfo, TextCueRegionInfo } from "../ts-common/types";
+
+/**
+ * Class displaying the text cues parsed by the worker on an
+ * `HTMLMediaElement`, by relying on the browser's native WebVTT rendering.
+ */
+export default class TextCueRenderer {
+  private _mediaElement: HTMLMediaElement;
+  /**
+   * `TextTrack` on which cues are added, only created once the first cue is
+   * received as they cannot be removed from the media element afterwards.
+   */
+  private _textTrack: TextTrack | null;
+  /** `VTTRegion` objects created until now, per their identifier. */
+  private _regions: Map<string, VTTRegion>;
+
+  /**
+   * Create a new `TextCueRenderer` associated to the given `HTMLMediaElement`.
+   * @param {HTMLMediaElement} mediaElement
+   */
+  constructor(mediaElement: HTMLMediaElement) {
+    this._mediaElement = mediaElement;
+    this._textTrack = null;
+    this._regions = new Map();
+  }
+
+  /**
+   * Display the given cue at the right time.
+   * @param {Object} cueInfo - The cue, as parsed by the worker.
+   * @param {number} mediaOffset - Offset to add to the playlist position
+   * expressed in `cueInfo` to convert it into a media position.
+   */
+  public addCue(cueInfo: TextCueInfo, mediaOffset: number): void {
+    if (this._textTrack === null) {
+      this._textTrack = this._mediaElement.addTextTrack("subtitles");
+    }
+    const textTrack = this._textTrack;
+    textTrack.mode = "showing";
+    const start = cueInfo.start + mediaOffset;
+    const end = cueInfo.end + mediaOffset;
+    const cues = textTrack.cues;
+    if (cues !== null) {
+      for (let i = 0; i < cues.length; i++) {
+        const cue = cues[i] as VTTCue;
+        if (
+          cue.startTime === start &&
+          cue.endTime === end &&
+          cue.text === cueInfo.text
+        ) {
+          // Already added, e.g. because its segment was loaded again
+          return;
+        }
+      }
+    }
+    const cue = new VTTCue(start, end, cueInfo.text);
+    if (cueInfo.id !== undefined) {
+      cue.id = cueInfo.id;
+    }
+    try {
+      cue.vertical = cueInfo.vertical;
+      cue.snapToLines = cueInfo.snapToLines;
+      cue.line = cueInfo.line;
+      cue.lineAlign = cueInfo.lineAlign;
+      cue.position = cueInfo.position;
+      cue.positionAlign = cueInfo.positionAlign;
+      cue.size = cueInfo.size;
+      cue.align = cueInfo.align;
+      if (cueInfo.region !== undefined) {
+        cue.region = this._getRegion(cueInfo.region);
+      }
+    } catch (err) {
+      logger.warn("API: Could not apply all settings of a text cue", err);
+    }
+    textTrack.addCue(cue);
+  }
+
+  /** Remove all cues previously added through `addCue`. */
+  public clear(): void {
+    this._regions.clear();
+    const textTrack = this._textTrack;
+    if (textTrack === null) {
+      return;
+    }
+    const cues = textTrack.cues;
+    if (cues !== null) {
+      for (let i = cues.length - 1; i >= 0; i--) {
+        textTrack.removeCue(cues[i]);
+      }
+    }
+    textTrack.mode = "disabled";
+  }
+
+  /**
+   * Returns the `VTTRegion` corresponding to the given region information,
+   * or `null` if regions are not supported by the current browser.
+   * @param {Object} regionInfo
+   * @returns {VTTRegion|null}
+   */
+  private _getRegion(regionInfo: TextCueRegionInfo): VTTRegion | null {
+    if (typeof VTTRegion !== "function") {
+      return null;
+    }
+    let region = this._regions.get(regionInfo.id);
+    if (region === undefined) {
+      region = new VTTRegion();
+      region.id = regionInfo.id;
+      region.width = regionInfo.width;
+      region.lines = regionInfo.lines;
+      region.regionAnchorX = regionInfo.regionAnchorX;
+      region.regionAnchorY = regionInfo.regionAnchorY;
+      region.viewportAnchorX = regionInfo.viewportAnchorX;
+      region.viewportAnchorY = regionInfo.viewportAnchorY;
+      region.scroll = regionInfo.scroll;
+      this._regions.set(regionInfo.id, region);
+    }
+    return region;
+  }
+}
@@ -6,7 +6,12 @@ import logger from "../ts-common/logger";
 import noop from "../ts-common/noop";
 import type {
   AudioTrackInfo,
+  DateRangeInfo,
+  FilteredVariantInfo,
+  SessionDataInfo,
+  TextTrackInfo,
   VariantInfo,
+  VideoTrackInfo,
   WaspHlsPlayerConfig,
   WorkerMessage,
 } from "../ts-common/types";
@@ -24,10 +29,12 @@ import DEFAULT_CONFIG from "./default_config";
 import type { WaspError } from "./errors";
 import { WaspInitializationError } from "./errors";
 import postMessageToWorker from "./postMessageToWorker";
+import TextCueRenderer from "./TextCueRenderer";
 import type { ContentMetadata } from "./types";
 import { PlayerState } from "./types";
 import {
   canDemuxMpeg2Ts,
+  observeSupportedVideoRanges,
   potentiallyRelativeUrlToAbsoluteUrl,
   requestStopForContent,
   waitForLoad,
@@ -46,6 +53,7 @@ import {
   onRebufferingEndedMessage,
   onRebufferingStartedMessage,
   onRemoveBufferMessage,
+  onSetTimestampOffsetMessage,
   onSeekMessage,
   onStartPlaybackObservationMessage,
   onStopPlaybackObservationMessage,
@@ -55,9 +63,15 @@ import {
   onMultivariantPlaylistParsedMessage,
   onVariantUpdateMessage,
   onTrackUpdateMessage,
+  onTextTrackUpdateMessage,
   onFlushMessage,
   onAreTypesSupportedMessage,
   onVariantLockStatusChangeMessage,
+  onDateRangeEventMessage,
+  onInterstitialEventMessage,
+  onSessionDataMessage,
+  onAddTextCueMessage,
+  onClearTextCuesMessage,
 } from "./worker-message-handlers";
 
 // Allows to ensure a never-seen-before identifier is used for each content.
@@ -142,6 +156,11 @@ interface WaspHlsPlayerEvents {
    * Sent when the list of available HLS variants changed.
    */
   variantListUpdate: VariantInfo[];
+  /**
+   * Sent when the list of HLS variants which cannot be played due to the
+   * output protection they require changed.
+   */
+  filteredVariantListUpdate: FilteredVariantInfo[];
   /**
    * Sent when the current audio track loaded by the `WaspHlsPlayer` changed.
    */
@@ -150,6 +169,55 @@ interface WaspHlsPlayerEvents {
    * Sent when the list of available audio tracks changed.
    */
   audioTrackListUpdate: AudioTrackInfo[];
+  /**
+   * Sent when the current video track loaded by the `WaspHlsPlayer` changed.
+   */
+  videoTrackUpdate: VideoTrackInfo | undefined;
+  /**
+   * Sent when the list of available video tracks changed.
+   */
+  videoTrackListUpdate: VideoTrackInfo[];
+  /**
+   * Sent when the text track selected on the `WaspHlsPlayer` changed, with
+   * `undefined` as a payload if text tracks are now disabled.
+   */
+  textTrackUpdate: TextTrackInfo | undefined;
+  /**
+   * Sent when the list of available text tracks changed.
+   */
+  textTrackListUpdate: TextTrackInfo[];
+  /**
+   * Sent when the current position enters a date range announced through an
+   * `EXT-X-DATERANGE` tag, with information on that date range as a payload.
+   */
+  dateRangeEnter: DateRangeInfo;
+  /**
+   * Sent when the current position leaves a date range previously announced
+   * through a `dateRangeEnter` event, with information on that date range as a
+   * payload.
+   */
+  dateRangeLeave: DateRangeInfo;
+  /**
+   * Sent when an HLS Interstitial (e.g. an advertisement) begins to be played
+   * in place of the primary content.
+   */
+  interstitialStart: InterstitialEventPayload;
+  /**
+   * Sent when an HLS Interstitial previously announced through an
+   * `interstitialStart` event ended, and the primary content is being resumed.
+   */
+  interstitialEnd: InterstitialEventPayload;
+  /**
+   * Sent when the data announced through an `EXT-X-SESSION-DATA` tag, asked
+   * through the `loadSessionData` method, is available.
+   */
+  sessionData: SessionDataInfo;
+}
+
+/** Payload sent with `interstitialStart` and `interstitialEnd` events. */
+export interface InterstitialEventPayload {
+  /** `ID` of the `EXT-X-DATERANGE` tag announcing that interstitial. */
+  id: string;
 }
 
 /** Payload sent with a `contentInfoUpdate` event. */
@@ -235,6 +303,9 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
    */
   private __contentMetadata__: ContentMetadata | null;
 
+  /** Displays the cues of the current text track on `videoElement`. */
+  private __textCueRenderer__: TextCueRenderer;
+
   /** AbortController allowing to free resources when `dispose` is called. */
   private __destroyAbortController__: AbortController;
 
@@ -260,6 +331,7 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
     this.initializationStatus = InitializationStatus.Uninitialized;
     this.__worker__ = null;
     this.__contentMetadata__ = null;
+    this.__textCueRenderer__ = new TextCueRenderer(videoElement);
     this.__logLevelChangeListener__ = null;
     this.__destroyAbortController__ = new AbortController();
     this.__config__ = { ...DEFAULT_CONFIG, ...(config ?? {}) };
@@ -386,6 +458,7 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
     if (this.__contentMetadata__ !== null) {
       requestStopForContent(this.__contentMetadata__, this.__worker__);
     }
+    this.__textCueRenderer__.clear();
     const contentId = generateContentId();
     const loadingAborter = new AbortController();
     this.__contentMetadata__ = {
@@ -395,8 +468,13 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
       disposeMediaSource: null,
       sourceBuffers: [],
       variants: [],
+      filteredVariants: [],
       audioTracks: [],
       currentAudioTrack: undefined,
+      videoTracks: [],
+      currentVideoTrack: undefined,
+      textTracks: [],
+      currentTextTrack: undefined,
       currVariant: undefined,
       lockedVariant: null,
       playbackObserver: null,
@@ -698,6 +776,35 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
     });
   }
 
+  /**
+   * Indicate whether the user is currently scrubbing, that is moving the
+   * position through multiple successive seeks, for example by dragging a
+   * progress bar.
+   *
+   * If the content has I-frame-only variants, only I-frames will be loaded
+   * while scrubbing, so the position can be updated quickly.
+   *
+   * @param {boolean} isScrubbing
+   */
+  public setScrubbing(isScrubbing: boolean): void {
+    if (this.__worker__ === null) {
+      throw new Error("The Player is not initialized or is disposed.");
+    }
+    if (
+      this.__contentMetadata__ === null ||
+      this.__contentMetadata__.mediaSourceId === null
+    ) {
+      throw new Error("No content is loaded");
+    }
+    postMessageToWorker(this.__worker__, {
+      type: MainMessageType.UpdateScrubbing,
+      value: {
+        mediaSourceId: this.__contentMetadata__.mediaSourceId,
+        isScrubbing,
+      },
+    });
+  }
+
   /**
    * Returns the wanted playback speed, `1` meaning "regular" playback, `2`
    * meaning two times faster `0.5` meaning playing at half the speed etc.
@@ -786,6 +893,17 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
     return this.__contentMetadata__?.variants ?? [];
   }
 
+  /**
+   * Returns a list of the HLS variants which cannot be played due to the
+   * output protection they require, with the reason why.
+   * Returns an empty array if unknown or if no content is loaded.
+   *
+   * @returns {Array.<Object>}
+   */
+  public getFilteredVariantList(): FilteredVariantInfo[] {
+    return this.__contentMetadata__?.filteredVariants ?? [];
+  }
+
   /**
    * Returns a list of all available on audio tracks.
    * Returns an empty array if unknown or if no content is loaded.
@@ -841,6 +959,113 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
     });
   }
 
+  /**
+   * Returns a list of all available video tracks, e.g. the different camera
+   * angles of a multi-angle content.
+   * Returns an empty array if unknown, if no content is loaded or if the
+   * content does not define video tracks.
+   *
+   * @returns {Array.<Object>}
+   */
+  public getVideoTrackList(): VideoTrackInfo[] {
+    return this.__contentMetadata__?.videoTracks ?? [];
+  }
+
+  /**
+   * Returns the information on the currently loaded video track.
+   * Returns `undefined` if unknown, if no content is loaded or if the content
+   * does not define video tracks.
+   *
+   * @returns {Object|undefined}
+   */
+  public getCurrentVideoTrack(): VideoTrackInfo | undefined {
+    const id = this.__contentMetadata__?.currentVideoTrack?.id;
+    if (id === undefined) {
+      return undefined;
+    }
+    return this.getVideoTrackList()?.find((v) => v.id === id);
+  }
+
+  /**
+   * Set the wanted video track through its `id` property or indicate that you
+   * don't want to force a video track (you want to rely on the content
+   * default's ones) by communicating `null` instead.
+   *
+   * Note that if relying on `lockVariant`, updating the video track might lead
+   * to the automatic unlocking of the variant if and only if the wanted track
+   * has no equivalent in the locked variant. In that case, you will receive a
+   * `variantLockUpdate` event indicating that the lock is no more in place.
+   *
+   * @param {number|null} trackId - The value of the `id` property of the
+   * track you want to set or `null` if you want to rely on the
+   * content's default instead.
+   */
+  public setVideoTrack(trackId: number | null): void {
+    if (this.__worker__ === null) {
+      throw new Error("The Player is not initialized or is disposed.");
+    }
+    if (this.__contentMetadata__ === null) {
+      throw new Error("No content loaded");
+    }
+    postMessageToWorker(this.__worker__, {
+      type: MainMessageType.SetVideoTrack,
+      value: {
+        contentId: this.__contentMetadata__.contentId,
+        trackId,
+      },
+    });
+  }
+
+  /**
+   * Returns a list of all available text tracks.
+   * Returns an empty array if unknown or if no content is loaded.
+   *
+   * @returns {Array.<Object>}
+   */
+  public getTextTrackList(): TextTrackInfo[] {
+    return this.__contentMetadata__?.textTracks ?? [];
+  }
+
+  /**
+   * Returns the information on the currently selected text track.
+   * Returns `undefined` if unknown, if no content is loaded or if text tracks
+   * are disabled.
+   *
+   * @returns {Object|undefined}
+   */
+  public getCurrentTextTrack(): TextTrackInfo | undefined {
+    const id = this.__contentMetadata__?.currentTextTrack;
+    if (id === undefined) {
+      return undefined;
+    }
+    return this.getTextTrackList().find((t) => t.id === id);
+  }
+
+  /**
+   * Set the wanted text track through its `id` property or disable text
+   * tracks by communicating `null` instead.
+   *
+   * Text tracks are disabled by default.
+   *
+   * @param {number|null} trackId - The value of the `id` property of the
+   * track you want to set or `null` if you want to disable text tracks.
+   */
+  public setTextTrack(trackId: number | null): void {
+    if (this.__worker__ === null) {
+      throw new Error("The Player is not initialized or is disposed.");
+    }
+    if (this.__contentMetadata__ === null) {
+      throw new Error("No content loaded");
+    }
+    postMessageToWorker(this.__worker__, {
+      type: MainMessageType.SetTextTrack,
+      value: {
+        contentId: this.__contentMetadata__.contentId,
+        trackId,
+      },
+    });
+  }
+
   /**
    * Lock in place the wanted HLS variant through its `id` property.
    *
@@ -910,6 +1135,37 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
     return this.__contentMetadata__?.lockedVariant ?? null;
   }
 
+  /**
+   * Ask for the data announced through the `EXT-X-SESSION-DATA` tag with the
+   * given `DATA-ID` attribute in the Multivariant Playlist of the current
+   * content (e.g. its title).
+   *
+   * That data will be sent through a `sessionData` event, once loaded if it
+   * has to be fetched from the tag's `URI` attribute.
+   * A `warning` event will be sent instead if no such tag exists or if its data
+   * could not be loaded.
+   *
+   * @param {string} dataId - The `DATA-ID` attribute of the wanted tag.
+   * @param {string|undefined} [language] - If set, the tag with that
+   * `LANGUAGE` attribute will be preferred.
+   */
+  public loadSessionData(dataId: string, language?: string | undefined): void {
+    if (this.__worker__ === null) {
+      throw new Error("The Player is not initialized or is disposed.");
+    }
+    if (this.__contentMetadata__ === null) {
+      throw new Error("No content loaded");
+    }
+    postMessageToWorker(this.__worker__, {
+      type: MainMessageType.LoadSessionData,
+      value: {
+        contentId: this.__contentMetadata__.contentId,
+        dataId,
+        language,
+      },
+    });
+  }
+
   /**
    * Free all resources taken by the `WaspHlsPlayer`.
    */
@@ -965,6 +1221,12 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
         initialConfig: this.__config__,
       },
     });
+    observeSupportedVideoRanges((videoRanges) => {
+      postMessageToWorker(worker, {
+        type: MainMessageType.UpdateDisplayCapabilities,
+        value: { videoRanges },
+      });
+    }, this.__destroyAbortController__.signal);
 
     if (this.__logLevelChangeListener__ !== null) {
       logger.removeEventListener(
@@ -1055,6 +1317,9 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
         case WorkerMessageType.RemoveBuffer:
           onRemoveBufferMessage(data, this.__contentMetadata__, worker);
           break;
+        case WorkerMessageType.SetTimestampOffset:
+          onSetTimestampOffsetMessage(data, this.__contentMetadata__);
+          break;
         case WorkerMessageType.StartPlaybackObservation:
           onStartPlaybackObservationMessage(
             data,
@@ -1077,16 +1342,43 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
             onMultivariantPlaylistParsedMessage(data, this.__contentMetadata__)
           ) {
             this.trigger("variantListUpdate", this.getVariantList());
+            this.trigger(
+              "filteredVariantListUpdate",
+              this.getFilteredVariantList(),
+            );
             this.trigger("audioTrackListUpdate", this.getAudioTrackList());
+            this.trigger("videoTrackListUpdate", this.getVideoTrackList());
+            this.trigger("textTrackListUpdate", this.getTextTrackList());
           }
           break;
         case WorkerMessageType.TrackUpdate:
           if (onTrackUpdateMessage(data, this.__contentMetadata__)) {
             if (data.value.mediaType === MediaType.Audio) {
               this.trigger("audioTrackUpdate", this.getCurrentAudioTrack());
+            } else if (data.value.mediaType === MediaType.Video) {
+              this.trigger("videoTrackUpdate", this.getCurrentVideoTrack());
             }
           }
           break;
+        case WorkerMessageType.TextTrackUpdate:
+          if (onTextTrackUpdateMessage(data, this.__contentMetadata__)) {
+            this.trigger("textTrackUpdate", this.getCurrentTextTrack());
+          }
+          break;
+        case WorkerMessageType.AddTextCue:
+          onAddTextCueMessage(
+            data,
+            this.__contentMetadata__,
+            this.__textCueRenderer__,
+          );
+          break;
+        case WorkerMessageType.ClearTextCues:
+          onClearTextCuesMessage(
+            data,
+            this.__contentMetadata__,
+            this.__textCueRenderer__,
+          );
+          break;
         case WorkerMessageType.VariantUpdate:
           if (onVariantUpdateMessage(data, this.__contentMetadata__)) {
             this.trigger("variantUpdate", this.getCurrentVariant());
@@ -1101,6 +1393,33 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
           }
           break;
 
+        case WorkerMessageType.DateRangeEvent:
+          if (onDateRangeEventMessage(data, this.__contentMetadata__)) {
+            if (data.value.isEntering) {
+              this.trigger("dateRangeEnter", data.value.dateRange);
+            } else {
+              this.trigger("dateRangeLeave", data.value.dateRange);
+            }
+          }
+          break;
+
+        case WorkerMessageType.InterstitialEvent:
+          if (onInterstitialEventMessage(data, this.__contentMetadata__)) {
+            const payload = { id: data.value.id };
+            if (data.value.isStarting) {
+              this.trigger("interstitialStart", payload);
+            } else {
+              this.trigger("interstitialEnd", payload);
+            }
+          }
+          break;
+
+        case WorkerMessageType.SessionData:
+          if (onSessionDataMessage(data, this.__contentMetadata__)) {
+            this.trigger("sessionData", data.value.sessionData);
+          }
+          break;
+
         case WorkerMessageType.Error: {
           const error = onErrorMessage(data, this.__contentMetadata__);
           if (error !== null) {
@@ -1131,6 +1450,7 @@ export default class WaspHlsPlayer extends EventEmitter<WaspHlsPlayerEvents> {
         case WorkerMessageType.ContentStopped:
           if (onContentStoppedMessage(data, this.__contentMetadata__)) {
             this.__contentMetadata__ = null;
+            this.__textCueRenderer__.clear();
             this.trigger("playerStateChange", PlayerState.Stopped);
           }
           break;
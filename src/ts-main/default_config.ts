@@ -8,6 +8,10 @@ import type { WaspHlsPlayerConfig } from "../ts-common/types";
  */
 const DEFAULT_CONFIG: WaspHlsPlayerConfig = {
   bufferGoal: 15,
+  preferAccessibleAudio: false,
+  maxAudioChannels: -1,
+  maxHdcpLevel: null,
+  contentProtectionConfigurations: null,
   segmentMaxRetry: 5,
   segmentRequestTimeout: 20000,
   segmentBackoffBase: 300,
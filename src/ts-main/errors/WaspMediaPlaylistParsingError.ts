@@ -20,6 +20,9 @@ export default class WaspMediaPlaylistParsingError extends Error {
     | "MediaPlaylistMissingTargetDuration"
     | "MediaPlaylistUriWithoutExtInf"
     | "MediaPlaylistUnparsableByteRange"
+    | "MediaPlaylistUnparsableKey"
+    | "MediaPlaylistUndefinedVariable"
+    | "MediaPlaylistInvalidDeltaUpdate"
     | "MediaPlaylistOtherParsingError";
 
   /**
@@ -61,6 +64,9 @@ export default class WaspMediaPlaylistParsingError extends Error {
       case MediaPlaylistParsingErrorCode.UnparsableByteRange:
         this.code = "MediaPlaylistUnparsableByteRange";
         break;
+      case MediaPlaylistParsingErrorCode.UnparsableKey:
+        this.code = "MediaPlaylistUnparsableKey";
+        break;
       case MediaPlaylistParsingErrorCode.UnparsableExtInf:
         this.code = "MediaPlaylistUnparsableExtInf";
         break;
@@ -70,6 +76,12 @@ export default class WaspMediaPlaylistParsingError extends Error {
       case MediaPlaylistParsingErrorCode.UriWithoutExtInf:
         this.code = "MediaPlaylistUriWithoutExtInf";
         break;
+      case MediaPlaylistParsingErrorCode.UndefinedVariable:
+        this.code = "MediaPlaylistUndefinedVariable";
+        break;
+      case MediaPlaylistParsingErrorCode.InvalidDeltaUpdate:
+        this.code = "MediaPlaylistInvalidDeltaUpdate";
+        break;
       case MediaPlaylistParsingErrorCode.Unknown:
         this.code = "MediaPlaylistOtherParsingError";
         break;
@@ -22,6 +22,7 @@ export default class WaspMultivariantPlaylistParsingError extends Error {
     | "MultivariantPlaylistMediaTagMissingName"
     | "MultivariantPlaylistMediaTagMissingGroupId"
     | "MultivariantPlaylistMissingExtM3uHeader"
+    | "MultivariantPlaylistUndefinedVariable"
     | "MultivariantPlaylistOtherParsingError";
 
   /**
@@ -71,6 +72,9 @@ export default class WaspMultivariantPlaylistParsingError extends Error {
       case MultivariantPlaylistParsingErrorCode.MediaTagMissingGroupId:
         this.code = "MultivariantPlaylistMediaTagMissingGroupId";
         break;
+      case MultivariantPlaylistParsingErrorCode.UndefinedVariable:
+        this.code = "MultivariantPlaylistUndefinedVariable";
+        break;
       case MultivariantPlaylistParsingErrorCode.Unknown:
         this.code = "MultivariantPlaylistOtherParsingError";
         break;
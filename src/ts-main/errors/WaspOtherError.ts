@@ -17,6 +17,9 @@ export default class WaspOtherError extends Error {
     | "MediaSourceAttachmentError"
     | "NoSupportedVariant"
     | "UnfoundLockedVariant"
+    | "UnfoundSessionData"
+    | "SessionDataLoadingError"
+    | "TextTrackLoadingError"
     | "Unknown";
 
   /**
@@ -49,6 +52,15 @@ export default class WaspOtherError extends Error {
       case OtherErrorCode.NoSupportedVariant:
         this.code = WaspErrorCode.NoSupportedVariant;
         break;
+      case OtherErrorCode.UnfoundSessionData:
+        this.code = WaspErrorCode.UnfoundSessionData;
+        break;
+      case OtherErrorCode.SessionDataLoadingError:
+        this.code = WaspErrorCode.SessionDataLoadingError;
+        break;
+      case OtherErrorCode.TextTrackLoadingError:
+        this.code = WaspErrorCode.TextTrackLoadingError;
+        break;
       default:
         this.code = WaspErrorCode.Unknown;
         break;
@@ -16,7 +16,10 @@ export default class WaspSegmentParsingError extends Error {
   public readonly message: string;
 
   /** Specifies the exact error encountered. */
-  public readonly code: "SegmentTransmuxingError" | "SegmentParsingOtherError";
+  public readonly code:
+    | "SegmentTransmuxingError"
+    | "SegmentDecryptionError"
+    | "SegmentParsingOtherError";
 
   /**
    * Specifies the exact error encountered.
@@ -54,6 +57,9 @@ export default class WaspSegmentParsingError extends Error {
       case SegmentParsingErrorCode.TransmuxerError:
         this.code = "SegmentTransmuxingError";
         break;
+      case SegmentParsingErrorCode.DecryptionError:
+        this.code = "SegmentDecryptionError";
+        break;
       case SegmentParsingErrorCode.NoResource:
       case SegmentParsingErrorCode.NoSourceBuffer:
       case SegmentParsingErrorCode.UnknownError:
@@ -91,6 +91,21 @@ export const WaspErrorCode = {
    * The variant locked through the `lockVariant` API was not found.
    */
   UnfoundLockedVariant: "UnfoundLockedVariant",
+  /**
+   * No `EXT-X-SESSION-DATA` tag with the `DATA-ID` asked through the
+   * `loadSessionData` method was found.
+   */
+  UnfoundSessionData: "UnfoundSessionData",
+  /**
+   * The data of an `EXT-X-SESSION-DATA` tag asked through the
+   * `loadSessionData` method could not be loaded.
+   */
+  SessionDataLoadingError: "SessionDataLoadingError",
+  /**
+   * A Media Playlist or a segment of the text track selected through the
+   * `setTextTrack` method could not be loaded or parsed.
+   */
+  TextTrackLoadingError: "TextTrackLoadingError",
   /** An unknown error arised. */
   Unknown: "Unknown",
 
@@ -149,6 +164,13 @@ export const WaspErrorCode = {
    */
   MultivariantPlaylistMediaTagMissingGroupId:
     "MultivariantPlaylistMediaTagMissingGroupId",
+  /**
+   * A variable was referenced in the Multivariant Playlist without being
+   * defined through an `EXT-X-DEFINE` tag, or an `EXT-X-DEFINE` tag referenced
+   * a query parameter absent from the Multivariant Playlist's URL.
+   */
+  MultivariantPlaylistUndefinedVariable:
+    "MultivariantPlaylistUndefinedVariable",
   /**
    * An uncategorized error arised while parsing the Multivariant Playlist.
    */
@@ -179,11 +201,33 @@ export const WaspErrorCode = {
    * was not in the right format.
    */
   MediaPlaylistUnparsableByteRange: "MediaPlaylistUnparsableByteRange",
+  /**
+   * An `#EXT-X-KEY` tag in the Media Playlist was either missing its mandatory
+   * `METHOD` attribute or its `URI` attribute.
+   */
+  MediaPlaylistUnparsableKey: "MediaPlaylistUnparsableKey",
+  /**
+   * A variable was referenced in the Media Playlist without being defined
+   * through an `EXT-X-DEFINE` tag, or an `EXT-X-DEFINE` tag imported a
+   * variable or referenced a query parameter which did not exist.
+   */
+  MediaPlaylistUndefinedVariable: "MediaPlaylistUndefinedVariable",
+  /**
+   * An `#EXT-X-SKIP` tag in a Playlist Delta Update was either unparsable or
+   * skipped media segments which were not in the previous version of the
+   * Media Playlist.
+   */
+  MediaPlaylistInvalidDeltaUpdate: "MediaPlaylistInvalidDeltaUpdate",
   /** Another uncategorized error happened while parsing the Media Playlist. */
   MediaPlaylistOtherParsingError: "MediaPlaylistOtherParsingError",
 
   /** An error arised when trying to transmux a segment, */
   SegmentTransmuxingError: "SegmentTransmuxingError",
+  /**
+   * An encrypted segment could not be decrypted, either because its key was
+   * invalid or because the segment's data was not in the expected format.
+   */
+  SegmentDecryptionError: "SegmentDecryptionError",
   /** An uncategorized error arised when parsing a segment, */
   SegmentParsingOtherError: "SegmentParsingOtherError",
 
@@ -1,5 +1,14 @@
 import WaspHlsPlayer from "./api";
-export type { AudioTrackInfo, VariantInfo } from "./types";
+export type {
+  AudioTrackInfo,
+  DateRangeInfo,
+  FilteredVariantInfo,
+  SessionDataInfo,
+  TextTrackInfo,
+  VariantInfo,
+  VideoRange,
+  VideoTrackInfo,
+} from "./types";
 export { PlayerState } from "./types";
 export * from "./errors/index";
 export default WaspHlsPlayer;
@@ -1,5 +1,14 @@
 import type QueuedSourceBuffer from "../ts-common/QueuedSourceBuffer";
-import type { AudioTrackInfo, VariantInfo } from "../ts-common/types";
+import type {
+  AudioTrackInfo,
+  DateRangeInfo,
+  FilteredVariantInfo,
+  SessionDataInfo,
+  TextTrackInfo,
+  VariantInfo,
+  VideoRange,
+  VideoTrackInfo,
+} from "../ts-common/types";
 import type { PlaylistNature } from "../wasm/wasp_hls";
 import type { WaspError } from "./errors";
 import type PlaybackObserver from "./observePlayback";
@@ -123,6 +132,13 @@ export interface ContentMetadata {
    */
   variants: VariantInfo[];
 
+  /**
+   * List of HLS variants which cannot be played due to the output protection
+   * they require.
+   * Empty array if unknown.
+   */
+  filteredVariants: FilteredVariantInfo[];
+
   /**
    * List of all available audio tracks.
    * Empty array if unknown.
@@ -140,6 +156,36 @@ export interface ContentMetadata {
       }
     | undefined;
 
+  /**
+   * List of all available video tracks, e.g. the different camera angles of
+   * a multi-angle content.
+   * Empty array if the content does not define any.
+   */
+  videoTracks: VideoTrackInfo[];
+
+  /**
+   * Information on the currently loaded video track.
+   * `undefined` if unknown or if the content does not define video tracks.
+   */
+  currentVideoTrack:
+    | {
+        id: number;
+        isSelected: boolean;
+      }
+    | undefined;
+
+  /**
+   * List of all available text tracks.
+   * Empty array if unknown.
+   */
+  textTracks: TextTrackInfo[];
+
+  /**
+   * `id` of the currently selected text track.
+   * `undefined` if unknown or if text tracks are disabled.
+   */
+  currentTextTrack: number | undefined;
+
   /**
    * Variant actively locked if one, or `null` if no variant is actively locked
    * on the worker-side.
@@ -178,4 +224,13 @@ export const enum PlayerState {
   Error = "Error",
 }
 
-export type { AudioTrackInfo, VariantInfo };
+export type {
+  AudioTrackInfo,
+  DateRangeInfo,
+  FilteredVariantInfo,
+  SessionDataInfo,
+  TextTrackInfo,
+  VariantInfo,
+  VideoRange,
+  VideoTrackInfo,
+};
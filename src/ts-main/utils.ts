@@ -1,5 +1,6 @@
 import isNullOrUndefined from "../ts-common/isNullOrUndefined";
 import logger from "../ts-common/logger";
+import type { VideoRange } from "../ts-common/types";
 import { MainMessageType } from "../ts-common/types";
 import postMessageToWorker from "./postMessageToWorker";
 import type { ContentMetadata } from "./types";
@@ -35,6 +36,59 @@ export function canDemuxMpeg2Ts(): boolean {
   );
 }
 
+/**
+ * Media queries matching when the current display can render high dynamic
+ * range video.
+ */
+const HDR_MEDIA_QUERIES = [
+  "(video-dynamic-range: high)",
+  "(dynamic-range: high)",
+] as const;
+
+/**
+ * Media query matching when the `dynamic-range` media feature is supported,
+ * whatever the display's capabilities are.
+ */
+const DYNAMIC_RANGE_FEATURE_QUERY =
+  "(dynamic-range: standard), (dynamic-range: high)";
+
+/**
+ * Observe through media queries which video dynamic ranges can be rendered by
+ * the current display, calling `onUpdate` with them initially and then each
+ * time they change (e.g. when the window is moved to another screen), until
+ * `abortSignal` emits.
+ *
+ * Nothing is reported if that cannot be known in the current environment.
+ * @param {Function} onUpdate
+ * @param {AbortSignal} abortSignal
+ */
+export function observeSupportedVideoRanges(
+  onUpdate: (videoRanges: VideoRange[]) => void,
+  abortSignal: AbortSignal,
+): void {
+  if (
+    typeof matchMedia !== "function" ||
+    !matchMedia(DYNAMIC_RANGE_FEATURE_QUERY).matches
+  ) {
+    return;
+  }
+  const mediaQueryLists = HDR_MEDIA_QUERIES.map((q) => matchMedia(q));
+  for (const mediaQueryList of mediaQueryLists) {
+    mediaQueryList.addEventListener("change", reportVideoRanges);
+  }
+  abortSignal.addEventListener("abort", () => {
+    for (const mediaQueryList of mediaQueryLists) {
+      mediaQueryList.removeEventListener("change", reportVideoRanges);
+    }
+  });
+  reportVideoRanges();
+
+  function reportVideoRanges() {
+    const isHdr = mediaQueryLists.some((m) => m.matches);
+    onUpdate(isHdr ? ["SDR", "HLG", "PQ"] : ["SDR"]);
+  }
+}
+
 /**
  * Transforms received Error, in an unknown format, into an object with an
  * optional `name` string (if found on the Error) and a `message` string who is
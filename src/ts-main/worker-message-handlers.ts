@@ -14,6 +14,7 @@ import type {
   RebufferingEndedWorkerMessage,
   RebufferingStartedWorkerMessage,
   RemoveBufferWorkerMessage,
+  SetTimestampOffsetWorkerMessage,
   SeekWorkerMessage,
   SetMediaSourceDurationWorkerMessage,
   StartPlaybackObservationWorkerMessage,
@@ -27,9 +28,15 @@ import type {
   MultivariantPlaylistParsedWorkerMessage,
   VariantUpdateWorkerMessage,
   TrackUpdateWorkerMessage,
+  TextTrackUpdateWorkerMessage,
   FlushWorkerMessage,
   AreTypesSupportedWorkerMessage,
   VariantLockStatusChangeWorkerMessage,
+  DateRangeEventWorkerMessage,
+  InterstitialEventWorkerMessage,
+  SessionDataWorkerMessage,
+  AddTextCueWorkerMessage,
+  ClearTextCuesWorkerMessage,
   SourceBufferId,
 } from "../ts-common/types";
 import { MainMessageType, MediaSourceReadyState } from "../ts-common/types";
@@ -51,6 +58,7 @@ import {
   WaspSourceBufferError,
 } from "./errors";
 import PlaybackObserver from "./observePlayback";
+import type TextCueRenderer from "./TextCueRenderer";
 import postMessageToWorker from "./postMessageToWorker";
 import type { ContentMetadata } from "./types";
 import { clearElementSrc, getErrorInformation } from "./utils";
@@ -501,6 +509,38 @@ export function onRemoveBufferMessage(
   }
 }
 
+/**
+ * Handles `SetTimestampOffsetWorkerMessage` messages.
+ * @param {Object} msg - The worker's message received.
+ * @param {Object|null} contentMetadata - Metadata of the content currently
+ * playing. `null` if no content is currently playing.
+ */
+export function onSetTimestampOffsetMessage(
+  msg: SetTimestampOffsetWorkerMessage,
+  contentMetadata: ContentMetadata | null,
+): void {
+  if (contentMetadata?.mediaSourceId !== msg.value.mediaSourceId) {
+    logger.info(
+      "API: Ignoring timestampOffset update due to wrong `mediaSourceId`",
+    );
+    return;
+  }
+  const sbObject = contentMetadata.sourceBuffers.find(
+    ({ sourceBufferId }) => sourceBufferId === msg.value.sourceBufferId,
+  );
+  if (sbObject !== undefined) {
+    sbObject.queuedSourceBuffer
+      .setTimestampOffset(msg.value.offset)
+      .catch((err) => {
+        const { message } = getErrorInformation(
+          err,
+          "Unknown error when updating the SourceBuffer's timestampOffset",
+        );
+        logger.error("API: " + message);
+      });
+  }
+}
+
 /**
  * Handles `StartPlaybackObservationWorkerMessage` messages.
  * @param {Object} msg - The worker's message received.
@@ -886,7 +926,10 @@ export function onMultivariantPlaylistParsedMessage(
     return false;
   }
   contentMetadata.variants = msg.value.variants;
+  contentMetadata.filteredVariants = msg.value.filteredVariants;
   contentMetadata.audioTracks = msg.value.audioTracks;
+  contentMetadata.videoTracks = msg.value.videoTracks;
+  contentMetadata.textTracks = msg.value.textTracks;
   return true;
 }
 
@@ -906,19 +949,91 @@ export function onTrackUpdateMessage(
     logger.info("API: Ignoring warning due to wrong `contentId`");
     return false;
   }
-  if (msg.value.mediaType !== MediaType.Audio) {
-    logger.warn("API: track update for a type not handled for now");
+  if (msg.value.mediaType === MediaType.Audio) {
+    contentMetadata.currentAudioTrack = msg.value.audioTrack
+      ? {
+          id: msg.value.audioTrack.current,
+          isSelected: msg.value.audioTrack.isSelected,
+        }
+      : undefined;
+    return true;
+  }
+  if (msg.value.mediaType === MediaType.Video) {
+    contentMetadata.currentVideoTrack = msg.value.videoTrack
+      ? {
+          id: msg.value.videoTrack.current,
+          isSelected: msg.value.videoTrack.isSelected,
+        }
+      : undefined;
+    return true;
+  }
+  logger.warn("API: track update for a type not handled for now");
+  return false;
+}
+
+/**
+ * Handles `TextTrackUpdateWorkerMessage` messages.
+ * @param {Object} msg - The worker's message received.
+ * @param {Object|null} contentMetadata - Metadata of the content currently
+ * playing. `null` if no content is currently playing.
+ * This object may be mutated.
+ * @returns {boolean} - `true` if the current text track has been updated.
+ */
+export function onTextTrackUpdateMessage(
+  msg: TextTrackUpdateWorkerMessage,
+  contentMetadata: ContentMetadata | null,
+): boolean {
+  if (contentMetadata?.contentId !== msg.value.contentId) {
+    logger.info("API: Ignoring warning due to wrong `contentId`");
     return false;
   }
-  contentMetadata.currentAudioTrack = msg.value.audioTrack
-    ? {
-        id: msg.value.audioTrack.current,
-        isSelected: msg.value.audioTrack.isSelected,
-      }
-    : undefined;
+  if (contentMetadata.currentTextTrack === msg.value.textTrackId) {
+    return false;
+  }
+  contentMetadata.currentTextTrack = msg.value.textTrackId;
   return true;
 }
 
+/**
+ * Handles `AddTextCueWorkerMessage` messages.
+ * @param {Object} msg - The worker's message received.
+ * @param {Object|null} contentMetadata - Metadata of the content currently
+ * playing. `null` if no content is currently playing.
+ * @param {Object} textCueRenderer - The `TextCueRenderer` displaying text cues
+ * on the media element.
+ */
+export function onAddTextCueMessage(
+  msg: AddTextCueWorkerMessage,
+  contentMetadata: ContentMetadata | null,
+  textCueRenderer: TextCueRenderer,
+): void {
+  if (contentMetadata?.contentId !== msg.value.contentId) {
+    logger.info("API: Ignoring text cue due to wrong `contentId`");
+    return;
+  }
+  textCueRenderer.addCue(msg.value.cue, contentMetadata.mediaOffset ?? 0);
+}
+
+/**
+ * Handles `ClearTextCuesWorkerMessage` messages.
+ * @param {Object} msg - The worker's message received.
+ * @param {Object|null} contentMetadata - Metadata of the content currently
+ * playing. `null` if no content is currently playing.
+ * @param {Object} textCueRenderer - The `TextCueRenderer` displaying text cues
+ * on the media element.
+ */
+export function onClearTextCuesMessage(
+  msg: ClearTextCuesWorkerMessage,
+  contentMetadata: ContentMetadata | null,
+  textCueRenderer: TextCueRenderer,
+): void {
+  if (contentMetadata?.contentId !== msg.value.contentId) {
+    logger.info("API: Ignoring text cue removal due to wrong `contentId`");
+    return;
+  }
+  textCueRenderer.clear();
+}
+
 /**
  * Handles `VariantUpdateWorkerMessage` messages.
  * @param {Object} msg - The worker's message received.
@@ -990,6 +1105,60 @@ export function onVariantLockStatusChangeMessage(
   return false;
 }
 
+/**
+ * Handles `DateRangeEventWorkerMessage` messages.
+ * @param {Object} msg - The worker's message received.
+ * @param {Object|null} contentMetadata - Metadata of the content currently
+ * playing. `null` if no content is currently playing.
+ * @returns {boolean} - `true` if the event concerns the current content.
+ */
+export function onDateRangeEventMessage(
+  msg: DateRangeEventWorkerMessage,
+  contentMetadata: ContentMetadata | null,
+): boolean {
+  if (contentMetadata?.contentId !== msg.value.contentId) {
+    logger.info("API: Ignoring date range event due to wrong `contentId`");
+    return false;
+  }
+  return true;
+}
+
+/**
+ * Handles `InterstitialEventWorkerMessage` messages.
+ * @param {Object} msg - The worker's message received.
+ * @param {Object|null} contentMetadata - Metadata of the content currently
+ * playing. `null` if no content is currently playing.
+ * @returns {boolean} - `true` if the event concerns the current content.
+ */
+export function onInterstitialEventMessage(
+  msg: InterstitialEventWorkerMessage,
+  contentMetadata: ContentMetadata | null,
+): boolean {
+  if (contentMetadata?.contentId !== msg.value.contentId) {
+    logger.info("API: Ignoring interstitial event due to wrong `contentId`");
+    return false;
+  }
+  return true;
+}
+
+/**
+ * Handles `SessionDataWorkerMessage` messages.
+ * @param {Object} msg - The worker's message received.
+ * @param {Object|null} contentMetadata - Metadata of the content currently
+ * playing. `null` if no content is currently playing.
+ * @returns {boolean} - `true` if the data concerns the current content.
+ */
+export function onSessionDataMessage(
+  msg: SessionDataWorkerMessage,
+  contentMetadata: ContentMetadata | null,
+): boolean {
+  if (contentMetadata?.contentId !== msg.value.contentId) {
+    logger.info("API: Ignoring session data due to wrong `contentId`");
+    return false;
+  }
+  return true;
+}
+
 /**
  * Handles `ContentStoppedWorkerMessage` messages.
  * @param {Object} msg - The worker's message received.
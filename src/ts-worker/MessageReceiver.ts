@@ -313,6 +313,34 @@ export default function MessageReceiver() {
         break;
       }
 
+      case MainMessageType.UpdateScrubbing: {
+        const dispatcher = playerInstance.getDispatcher();
+        const contentInfo = playerInstance.getContentInfo();
+        if (
+          dispatcher === null ||
+          contentInfo === null ||
+          contentInfo.mediaSourceObj?.mediaSourceId !== data.value.mediaSourceId
+        ) {
+          return;
+        }
+        dispatcher.set_scrubbing(data.value.isScrubbing);
+        break;
+      }
+
+      case MainMessageType.UpdateDisplayCapabilities: {
+        const dispatcher = playerInstance.getDispatcher();
+        if (dispatcher === null) {
+          return;
+        }
+        const { videoRanges } = data.value;
+        dispatcher.set_supported_video_ranges(
+          videoRanges.includes("SDR"),
+          videoRanges.includes("HLG"),
+          videoRanges.includes("PQ"),
+        );
+        break;
+      }
+
       case MainMessageType.UpdateLoggerLevel:
         logger.setLevel(data.value);
         break;
@@ -342,6 +370,54 @@ export default function MessageReceiver() {
         break;
       }
 
+      case MainMessageType.SetVideoTrack: {
+        const dispatcher = playerInstance.getDispatcher();
+        if (dispatcher === null) {
+          return postUnitializedWorkerError(data.value.contentId);
+        }
+        const contentInfo = playerInstance.getContentInfo();
+        if (
+          contentInfo === null ||
+          contentInfo.contentId !== data.value.contentId
+        ) {
+          return;
+        }
+        dispatcher.set_video_track(data.value.trackId ?? undefined);
+        break;
+      }
+
+      case MainMessageType.SetTextTrack: {
+        const dispatcher = playerInstance.getDispatcher();
+        if (dispatcher === null) {
+          return postUnitializedWorkerError(data.value.contentId);
+        }
+        const contentInfo = playerInstance.getContentInfo();
+        if (
+          contentInfo === null ||
+          contentInfo.contentId !== data.value.contentId
+        ) {
+          return;
+        }
+        dispatcher.set_text_track(data.value.trackId ?? undefined);
+        break;
+      }
+
+      case MainMessageType.LoadSessionData: {
+        const dispatcher = playerInstance.getDispatcher();
+        if (dispatcher === null) {
+          return postUnitializedWorkerError(data.value.contentId);
+        }
+        const contentInfo = playerInstance.getContentInfo();
+        if (
+          contentInfo === null ||
+          contentInfo.contentId !== data.value.contentId
+        ) {
+          return;
+        }
+        dispatcher.load_session_data(data.value.dataId, data.value.language);
+        break;
+      }
+
       case MainMessageType.LockVariant: {
         const dispatcher = playerInstance.getDispatcher();
         if (dispatcher === null) {
@@ -4,14 +4,19 @@ import QueuedSourceBuffer from "../ts-common/QueuedSourceBuffer";
 import timeRangesToFloat64Array from "../ts-common/timeRangesToFloat64Array";
 import type {
   AudioTrackInfo,
+  DateRangeInfo,
+  FilteredVariantInfo,
+  SessionDataInfo,
   SourceBufferId,
+  TextCueInfo,
+  TextTrackInfo,
   VariantInfo,
+  VideoTrackInfo,
 } from "../ts-common/types.js";
 import { WorkerMessageType } from "../ts-common/types.js";
 import type {
   MediaPlaylistParsingErrorCode,
   MultivariantPlaylistParsingErrorCode,
-  OtherErrorCode,
   PlaylistNature,
   RequestErrorReason,
   SourceBufferCreationErrorCode,
@@ -23,7 +28,10 @@ import {
   AppendBufferResult,
   AttachMediaSourceErrorCode,
   AttachMediaSourceResult,
+  DateRangeAttributeType,
+  DateRangeEvent,
   EndOfStreamErrorCode,
+  InterstitialEvent,
   EndOfStreamResult,
   JsTimeRanges,
   LogLevel,
@@ -31,14 +39,23 @@ import {
   MediaSourceDurationUpdateResult,
   MediaSourceReadyState,
   MediaType,
+  OtherErrorCode,
   PushedSegmentErrorCode,
   RemoveBufferErrorCode,
   RemoveBufferResult,
   RemoveMediaSourceErrorCode,
   RemoveMediaSourceResult,
   SegmentParsingErrorCode,
+  SessionDataType,
 } from "../wasm/wasp_hls.js";
-import type { RequestId, ResourceId, TimerId } from "./globals";
+import type {
+  MainMediaSourceInstanceInfo,
+  RequestId,
+  ResourceId,
+  SourceBufferInstanceInfo,
+  TimerId,
+  WorkerMediaSourceInstanceInfo,
+} from "./globals";
 import {
   cachedCodecsSupport,
   jsMemoryResources,
@@ -373,6 +390,15 @@ export function getResourceData(
   return jsMemoryResources.get(resourceId);
 }
 
+/**
+ * @param {Uint8Array} data
+ * @returns {number}
+ */
+export function storeResource(data: Uint8Array): ResourceId {
+  // `data` is a view on the WebAssembly memory, copy it before it is reused
+  return jsMemoryResources.create(data.slice());
+}
+
 /**
  * @param {number} logLevel
  * @param {string} logStr
@@ -418,6 +444,13 @@ export function clearTimer(id: TimerId): void {
   clearTimeout(id);
 }
 
+/**
+ * @returns {number}
+ */
+export function getMonotonicTime(): number {
+  return timerFn();
+}
+
 /**
  * @param {string} url
  * @param {number|undefined} rangeStart
@@ -869,12 +902,16 @@ export function addSourceBuffer(
  * @param {number} sourceBufferId
  * @param {number} resourceId
  * @param {boolean} parseTimeInformation
+ * @param {number|undefined} alignedStart - If set, the `timestampOffset` of
+ * the SourceBuffer will be updated before pushing the segment so it starts at
+ * that time, in seconds.
  * @returns {Object}
  */
 export function appendBuffer(
   sourceBufferId: SourceBufferId,
   resourceId: ResourceId,
   parseTimeInformation?: boolean,
+  alignedStart?: number,
 ): AppendBufferResult {
   let segment = jsMemoryResources.get(resourceId);
   const mediaSourceObj = getMediaSourceObj();
@@ -941,6 +978,17 @@ export function appendBuffer(
     // TODO Check if mp4 first
     timeInfo = getTimeInformationFromMp4(segment, timescale);
   }
+  if (
+    alignedStart !== undefined &&
+    timeInfo !== undefined &&
+    timeInfo !== null
+  ) {
+    updateTimestampOffset(
+      mediaSourceObj,
+      sourceBufferObj,
+      alignedStart - timeInfo.time,
+    );
+  }
   try {
     if (sourceBufferObj.sourceBuffer !== null) {
       sourceBufferObj.sourceBuffer
@@ -1075,6 +1123,61 @@ export function removeBuffer(
   return RemoveBufferResult.success();
 }
 
+/**
+ * @param {number} sourceBufferId
+ * @param {number} offset
+ */
+export function setTimestampOffset(
+  sourceBufferId: SourceBufferId,
+  offset: number,
+): void {
+  const mediaSourceObj = getMediaSourceObj();
+  if (mediaSourceObj === undefined) {
+    logger.warn("Cannot set timestampOffset: no MediaSource created.");
+    return;
+  }
+  const sourceBufferObj = mediaSourceObj.sourceBuffers.find(
+    ({ id }) => id === sourceBufferId,
+  );
+  if (sourceBufferObj === undefined) {
+    logger.warn("Cannot set timestampOffset: SourceBuffer not found.");
+    return;
+  }
+  updateTimestampOffset(mediaSourceObj, sourceBufferObj, offset);
+}
+
+/**
+ * Schedule an update of the `timestampOffset` of the given SourceBuffer,
+ * either directly if MSE is available in the worker or by sending the
+ * corresponding message to the main thread.
+ * @param {Object} mediaSourceObj
+ * @param {Object} sourceBufferObj
+ * @param {number} offset
+ */
+function updateTimestampOffset(
+  mediaSourceObj: MainMediaSourceInstanceInfo | WorkerMediaSourceInstanceInfo,
+  sourceBufferObj:
+    | SourceBufferInstanceInfo<true>
+    | SourceBufferInstanceInfo<false>,
+  offset: number,
+): void {
+  if (sourceBufferObj.sourceBuffer !== null) {
+    sourceBufferObj.sourceBuffer.setTimestampOffset(offset).catch((err) => {
+      const error = err instanceof Error ? err : "Unknown Error";
+      logger.error("Error when updating the timestampOffset", error);
+    });
+  } else {
+    postMessageToMain({
+      type: WorkerMessageType.SetTimestampOffset,
+      value: {
+        mediaSourceId: mediaSourceObj.mediaSourceId,
+        sourceBufferId: sourceBufferObj.id,
+        offset,
+      },
+    });
+  }
+}
+
 /**
  * @returns {Object}
  */
@@ -1216,6 +1319,9 @@ export function updateContentInfo(
 export function announceFetchedContent(
   variantInfo: Uint32Array,
   audioTracksInfo: Uint32Array,
+  videoTracksInfo: Uint32Array,
+  textTracksInfo: Uint32Array,
+  filteredVariantsInfo: Uint32Array,
 ): void {
   const contentInfo = playerInstance.getContentInfo();
   const memory = playerInstance.getCurrentWasmMemory();
@@ -1227,28 +1333,24 @@ export function announceFetchedContent(
     let i = 0;
     i++; // Skip number of variants
     while (i < variantInfo.length) {
-      const id = variantInfo[i];
-      i++;
-
-      const height = variantInfo[i];
-      i++;
-
-      const width = variantInfo[i];
-      i++;
-
-      const frameRate = variantInfo[i];
-      i++;
+      const [variant, nextOffset] = parseVariantInfo(variantInfo, i);
+      i = nextOffset;
+      variantInfoObj.push(variant);
+    }
+  }
+  const filteredVariantsObj: FilteredVariantInfo[] = [];
+  {
+    let i = 0;
+    i++; // Skip number of filtered variants
+    while (i < filteredVariantsInfo.length) {
+      const [variant, nextOffset] = parseVariantInfo(filteredVariantsInfo, i);
+      i = nextOffset;
 
-      const bandwidth = variantInfo[i];
+      const filterReason =
+        filteredVariantsInfo[i] === 1 ? "hdcp-level" : "allowed-cpc";
       i++;
 
-      variantInfoObj.push({
-        id,
-        height: height === 0 ? undefined : height,
-        width: width === 0 ? undefined : width,
-        frameRate: frameRate === 0 ? undefined : frameRate,
-        bandwidth: bandwidth === 0 ? undefined : bandwidth,
-      });
+      filteredVariantsObj.push({ ...variant, filterReason });
     }
   }
   const audioTracksObj: AudioTrackInfo[] = [];
@@ -1288,12 +1390,168 @@ export function announceFetchedContent(
       const channels = audioTracksInfo[i];
       i++;
 
+      const nbSpatialIdentifiers = audioTracksInfo[i];
+      i++;
+      const spatialAudioIdentifiers: string[] = [];
+      for (let j = 0; j < nbSpatialIdentifiers; j++) {
+        const identifierLen = audioTracksInfo[i];
+        i++;
+        const identifierU8 = new Uint8Array(
+          memory.buffer,
+          audioTracksInfo[i],
+          identifierLen,
+        );
+        i++;
+        spatialAudioIdentifiers.push(cachedTextDecoder.decode(identifierU8));
+      }
+
+      const channelsFlags = audioTracksInfo[i];
+      i++;
+
+      const nbCharacteristics = audioTracksInfo[i];
+      i++;
+      const characteristics: string[] = [];
+      for (let j = 0; j < nbCharacteristics; j++) {
+        const characteristicLen = audioTracksInfo[i];
+        i++;
+        const characteristicU8 = new Uint8Array(
+          memory.buffer,
+          audioTracksInfo[i],
+          characteristicLen,
+        );
+        i++;
+        characteristics.push(cachedTextDecoder.decode(characteristicU8));
+      }
+
       audioTracksObj.push({
         id,
         language: language === "" ? undefined : language,
         assocLanguage: assocLanguage === "" ? undefined : assocLanguage,
         name,
-        channels,
+        channels: channels === 0 ? undefined : channels,
+        spatialAudioIdentifiers,
+        binaural: (channelsFlags & 1) !== 0,
+        immersive: (channelsFlags & 2) !== 0,
+        downmix: (channelsFlags & 4) !== 0,
+        characteristics,
+      });
+    }
+  }
+  const videoTracksObj: VideoTrackInfo[] = [];
+  {
+    let i = 0;
+    i++; // Skip number of video tracks
+    while (i < videoTracksInfo.length) {
+      const id = videoTracksInfo[i];
+      i++;
+
+      const languageLen = videoTracksInfo[i];
+      i++;
+      const languageU8 = new Uint8Array(
+        memory.buffer,
+        videoTracksInfo[i],
+        languageLen,
+      );
+      i++;
+      const language = cachedTextDecoder.decode(languageU8);
+
+      const assocLanguageLen = videoTracksInfo[i];
+      i++;
+      const assocLanguageU8 = new Uint8Array(
+        memory.buffer,
+        videoTracksInfo[i],
+        assocLanguageLen,
+      );
+      i++;
+      const assocLanguage = cachedTextDecoder.decode(assocLanguageU8);
+
+      const nameLen = videoTracksInfo[i];
+      i++;
+      const nameU8 = new Uint8Array(memory.buffer, videoTracksInfo[i], nameLen);
+      i++;
+      const name = cachedTextDecoder.decode(nameU8);
+
+      videoTracksObj.push({
+        id,
+        language: language === "" ? undefined : language,
+        assocLanguage: assocLanguage === "" ? undefined : assocLanguage,
+        name,
+      });
+    }
+  }
+  const textTracksObj: TextTrackInfo[] = [];
+  {
+    let i = 0;
+    i++; // Skip number of text tracks
+    while (i < textTracksInfo.length) {
+      const id = textTracksInfo[i];
+      i++;
+
+      const languageLen = textTracksInfo[i];
+      i++;
+      const languageU8 = new Uint8Array(
+        memory.buffer,
+        textTracksInfo[i],
+        languageLen,
+      );
+      i++;
+      const language = cachedTextDecoder.decode(languageU8);
+
+      const assocLanguageLen = textTracksInfo[i];
+      i++;
+      const assocLanguageU8 = new Uint8Array(
+        memory.buffer,
+        textTracksInfo[i],
+        assocLanguageLen,
+      );
+      i++;
+      const assocLanguage = cachedTextDecoder.decode(assocLanguageU8);
+
+      const nameLen = textTracksInfo[i];
+      i++;
+      const nameU8 = new Uint8Array(memory.buffer, textTracksInfo[i], nameLen);
+      i++;
+      const name = cachedTextDecoder.decode(nameU8);
+
+      const flags = textTracksInfo[i];
+      i++;
+
+      const nbCharacteristics = textTracksInfo[i];
+      i++;
+      const characteristics: string[] = [];
+      for (let j = 0; j < nbCharacteristics; j++) {
+        const characteristicLen = textTracksInfo[i];
+        i++;
+        const characteristicU8 = new Uint8Array(
+          memory.buffer,
+          textTracksInfo[i],
+          characteristicLen,
+        );
+        i++;
+        characteristics.push(cachedTextDecoder.decode(characteristicU8));
+      }
+
+      const instreamKind = textTracksInfo[i];
+      i++;
+      const instreamNumber = textTracksInfo[i];
+      i++;
+      let instreamId: string | undefined;
+      if (instreamKind === 1) {
+        instreamId = `CC${instreamNumber}`;
+      } else if (instreamKind === 2) {
+        instreamId = `SERVICE${instreamNumber}`;
+      }
+
+      textTracksObj.push({
+        id,
+        language: language === "" ? undefined : language,
+        assocLanguage: assocLanguage === "" ? undefined : assocLanguage,
+        name,
+        forced: (flags & 1) !== 0,
+        default: (flags & 2) !== 0,
+        autoSelect: (flags & 4) !== 0,
+        characteristics,
+        instreamId,
       });
     }
   }
@@ -1302,32 +1560,106 @@ export function announceFetchedContent(
     value: {
       contentId: contentInfo.contentId,
       variants: variantInfoObj,
+      filteredVariants: filteredVariantsObj,
       audioTracks: audioTracksObj,
+      videoTracks: videoTracksObj,
+      textTracks: textTracksObj,
     },
   });
 }
 
+/**
+ * `VideoRange` values, indexed by the corresponding `VideoDynamicRange` value
+ * communicated by the Rust-side.
+ */
+const VIDEO_RANGE_VALUES = ["SDR", "HLG", "PQ"] as const;
+
+/**
+ * Parse the information on a variant communicated by the Rust-side, starting
+ * at `offset` in `variantInfo`.
+ * @param {Uint32Array} variantInfo
+ * @param {number} offset
+ * @returns {Array.<Object|number>} - The parsed variant information and the
+ * offset in `variantInfo` just after it.
+ */
+function parseVariantInfo(
+  variantInfo: Uint32Array,
+  offset: number,
+): [VariantInfo, number] {
+  let i = offset;
+  const id = variantInfo[i];
+  i++;
+
+  const height = variantInfo[i];
+  i++;
+
+  const width = variantInfo[i];
+  i++;
+
+  const frameRate = variantInfo[i];
+  i++;
+
+  const bandwidth = variantInfo[i];
+  i++;
+
+  const usesSupplementalCodecs = variantInfo[i] !== 0;
+  i++;
+
+  const videoRange = VIDEO_RANGE_VALUES[variantInfo[i]] ?? "SDR";
+  i++;
+
+  const variant = {
+    id,
+    height: height === 0 ? undefined : height,
+    width: width === 0 ? undefined : width,
+    frameRate: frameRate === 0 ? undefined : frameRate,
+    bandwidth: bandwidth === 0 ? undefined : bandwidth,
+    usesSupplementalCodecs,
+    videoRange,
+  };
+  return [variant, i];
+}
+
 export function announceTrackUpdate(
   mediaType: MediaType,
-  currentAudioTrack: number,
-  isAudioTrackSelected: boolean,
+  currentTrack: number | undefined,
+  isTrackSelected: boolean,
 ): void {
   const contentInfo = playerInstance.getContentInfo();
   const memory = playerInstance.getCurrentWasmMemory();
   if (contentInfo === null || memory === null) {
     return;
   }
+  const track =
+    currentTrack !== undefined
+      ? {
+          current: currentTrack,
+          isSelected: isTrackSelected,
+        }
+      : undefined;
   postMessageToMain({
     type: WorkerMessageType.TrackUpdate,
     value: {
       mediaType,
       contentId: contentInfo.contentId,
-      audioTrack: currentAudioTrack
-        ? {
-            current: currentAudioTrack,
-            isSelected: isAudioTrackSelected,
-          }
-        : undefined,
+      audioTrack: mediaType === MediaType.Audio ? track : undefined,
+      videoTrack: mediaType === MediaType.Video ? track : undefined,
+    },
+  });
+}
+
+export function announceTextTrackUpdate(
+  currentTextTrack: number | undefined,
+): void {
+  const contentInfo = playerInstance.getContentInfo();
+  if (contentInfo === null) {
+    return;
+  }
+  postMessageToMain({
+    type: WorkerMessageType.TextTrackUpdate,
+    value: {
+      contentId: contentInfo.contentId,
+      textTrackId: currentTextTrack,
     },
   });
 }
@@ -1459,3 +1791,242 @@ export function announceVariantLockStatusChange(
     },
   });
 }
+
+export function announceDateRangeEvent(
+  event: DateRangeEvent,
+  id: string,
+  start: number,
+  end: number | undefined,
+  duration: number | undefined,
+  plannedDuration: number | undefined,
+  attributes: Uint32Array,
+): void {
+  const contentInfo = playerInstance.getContentInfo();
+  const memory = playerInstance.getCurrentWasmMemory();
+  if (contentInfo === null || memory === null) {
+    return;
+  }
+  const dateRange: DateRangeInfo = {
+    id,
+    class: undefined,
+    start,
+    end,
+    duration,
+    plannedDuration,
+    endOnNext: false,
+    scte35Cmd: undefined,
+    scte35Out: undefined,
+    scte35In: undefined,
+    clientAttributes: {},
+  };
+  let i = 0;
+  i++; // Skip number of attributes
+  while (i < attributes.length) {
+    const type = attributes[i] as DateRangeAttributeType;
+    i++;
+
+    const nameLen = attributes[i];
+    i++;
+    const nameU8 = new Uint8Array(memory.buffer, attributes[i], nameLen);
+    i++;
+    const name = cachedTextDecoder.decode(nameU8);
+
+    const valueLen = attributes[i];
+    i++;
+    const valueU8 = new Uint8Array(memory.buffer, attributes[i], valueLen);
+    i++;
+    const value = cachedTextDecoder.decode(valueU8);
+
+    switch (name) {
+      case "CLASS":
+        dateRange.class = value;
+        break;
+      case "END-ON-NEXT":
+        dateRange.endOnNext = value === "YES";
+        break;
+      case "SCTE35-CMD":
+        dateRange.scte35Cmd = value;
+        break;
+      case "SCTE35-OUT":
+        dateRange.scte35Out = value;
+        break;
+      case "SCTE35-IN":
+        dateRange.scte35In = value;
+        break;
+      default:
+        dateRange.clientAttributes[name] =
+          type === DateRangeAttributeType.Number ? Number(value) : value;
+        break;
+    }
+  }
+  postMessageToMain({
+    type: WorkerMessageType.DateRangeEvent,
+    value: {
+      contentId: contentInfo.contentId,
+      isEntering: event === DateRangeEvent.Enter,
+      dateRange,
+    },
+  });
+}
+
+/**
+ * Announce that the HLS Interstitial with the given `id` began or stopped
+ * being played in place of the primary content.
+ * @param {number} event
+ * @param {string} id
+ */
+export function announceInterstitialEvent(
+  event: InterstitialEvent,
+  id: string,
+): void {
+  const contentInfo = playerInstance.getContentInfo();
+  if (contentInfo === null) {
+    return;
+  }
+  postMessageToMain({
+    type: WorkerMessageType.InterstitialEvent,
+    value: {
+      contentId: contentInfo.contentId,
+      isStarting: event === InterstitialEvent.Start,
+      id,
+    },
+  });
+}
+
+/**
+ * Announce the data of the `EXT-X-SESSION-DATA` tag with the given `DATA-ID`
+ * and `LANGUAGE` attributes.
+ * @param {string} dataId
+ * @param {string|undefined} language
+ * @param {number} dataType
+ * @param {Uint8Array} data
+ */
+export function announceSessionData(
+  dataId: string,
+  language: string | undefined,
+  dataType: SessionDataType,
+  data: Uint8Array,
+): void {
+  const contentInfo = playerInstance.getContentInfo();
+  if (contentInfo === null) {
+    return;
+  }
+  let sessionData: SessionDataInfo;
+  switch (dataType) {
+    case SessionDataType.String:
+      sessionData = {
+        dataId,
+        language,
+        type: "string",
+        value: cachedTextDecoder.decode(data),
+      };
+      break;
+    case SessionDataType.Json:
+      try {
+        sessionData = {
+          dataId,
+          language,
+          type: "json",
+          value: JSON.parse(cachedTextDecoder.decode(data)) as unknown,
+        };
+      } catch (err) {
+        sendOtherError(
+          false,
+          OtherErrorCode.SessionDataLoadingError,
+          `The session data "${dataId}" is not valid JSON`,
+        );
+        return;
+      }
+      break;
+    case SessionDataType.Raw:
+      // Copy the data as the given view may point to WebAssembly's memory
+      sessionData = {
+        dataId,
+        language,
+        type: "raw",
+        value: data.slice().buffer,
+      };
+      break;
+  }
+  postMessageToMain({
+    type: WorkerMessageType.SessionData,
+    value: {
+      contentId: contentInfo.contentId,
+      sessionData,
+    },
+  });
+}
+
+const CUE_VERTICAL_VALUES = ["", "rl", "lr"] as const;
+const CUE_LINE_ALIGN_VALUES = ["start", "center", "end"] as const;
+const CUE_POSITION_ALIGN_VALUES = [
+  "auto",
+  "line-left",
+  "center",
+  "line-right",
+] as const;
+const CUE_ALIGN_VALUES = ["start", "center", "end", "left", "right"] as const;
+
+/**
+ * @param {string|undefined} id
+ * @param {number} start
+ * @param {number} end
+ * @param {string} text
+ * @param {Float64Array} settings
+ * @param {string|undefined} regionId
+ */
+export function addTextCue(
+  id: string | undefined,
+  start: number,
+  end: number,
+  text: string,
+  settings: Float64Array,
+  regionId: string | undefined,
+): void {
+  const contentInfo = playerInstance.getContentInfo();
+  if (contentInfo === null) {
+    return;
+  }
+  const cue: TextCueInfo = {
+    id,
+    start,
+    end,
+    text,
+    vertical: CUE_VERTICAL_VALUES[settings[0]] ?? "",
+    line: isNaN(settings[1]) ? "auto" : settings[1],
+    snapToLines: settings[2] === 1,
+    lineAlign: CUE_LINE_ALIGN_VALUES[settings[3]] ?? "start",
+    position: isNaN(settings[4]) ? "auto" : settings[4],
+    positionAlign: CUE_POSITION_ALIGN_VALUES[settings[5]] ?? "auto",
+    size: settings[6],
+    align: CUE_ALIGN_VALUES[settings[7]] ?? "center",
+    region: undefined,
+  };
+  if (settings[8] === 1 && regionId !== undefined) {
+    cue.region = {
+      id: regionId,
+      width: settings[9],
+      lines: settings[10],
+      regionAnchorX: settings[11],
+      regionAnchorY: settings[12],
+      viewportAnchorX: settings[13],
+      viewportAnchorY: settings[14],
+      scroll: settings[15] === 1 ? "up" : "",
+    };
+  }
+  postMessageToMain({
+    type: WorkerMessageType.AddTextCue,
+    value: { contentId: contentInfo.contentId, cue },
+  });
+}
+
+export function clearTextCues(): void {
+  const contentInfo = playerInstance.getContentInfo();
+  if (contentInfo === null) {
+    return;
+  }
+  postMessageToMain({
+    type: WorkerMessageType.ClearTextCues,
+    value: { contentId: contentInfo.contentId },
+  });
+}
@@ -161,6 +161,30 @@ export function updateDispatcherConfig(
   if (config.bufferGoal !== undefined) {
     dispatcher.set_buffer_goal(config.bufferGoal);
   }
+  if (config.preferAccessibleAudio !== undefined) {
+    dispatcher.set_prefer_accessible_audio(config.preferAccessibleAudio);
+  }
+  if (config.maxAudioChannels !== undefined) {
+    dispatcher.set_max_audio_channels(
+      config.maxAudioChannels < 0 ? undefined : config.maxAudioChannels,
+    );
+  }
+  if (config.maxHdcpLevel !== undefined) {
+    dispatcher.set_max_hdcp_level(config.maxHdcpLevel ?? undefined);
+  }
+  if (config.contentProtectionConfigurations !== undefined) {
+    const configurations = config.contentProtectionConfigurations;
+    dispatcher.set_allowed_cpc(
+      configurations === null
+        ? undefined
+        : Object.keys(configurations)
+            .map((keyFormat) => {
+              const labels = configurations[keyFormat] ?? [];
+              return `${keyFormat}:${labels.join("/")}`;
+            })
+            .join(","),
+    );
+  }
   if (config.segmentMaxRetry !== undefined) {
     let maxRetry = Math.min(config.segmentMaxRetry, I32_MAX_VALUE);
     maxRetry = Math.max(config.segmentMaxRetry, -1);